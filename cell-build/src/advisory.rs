@@ -0,0 +1,385 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Dependency inventory, licenses and RustSec advisories.
+//!
+//! The registry scans every published version: the crates in its `Cargo.lock`
+//! ([`dependencies`]), the license each declares ([`with_licenses`], read from
+//! the local Cargo registry cache) and the advisories in a checkout of the
+//! RustSec advisory database that affect them ([`AdvisoryDb::scan`]). The
+//! inventory is stored with the package, so versions already running can be
+//! rescanned as advisories are published after them.
+//!
+//! Only what the advisory database needs of semver is implemented here:
+//! comma separated comparators (`>=`, `>`, `<=`, `<`, `=`, `^`, `~`) on
+//! `major.minor.patch` versions, pre-release tags ignored.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub const ADVISORY_DB_URL: &str = "https://github.com/rustsec/advisory-db";
+
+/// A crate from crates.io locked into a build
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Dependency {
+    pub name: String,
+    pub version: String,
+    /// SPDX expression from the crate's Cargo.toml, if known
+    pub license: Option<String>,
+}
+
+/// Registry crates in a `Cargo.lock`. Path and git dependencies are the
+/// publisher's own code and have no advisories.
+pub fn dependencies(lockfile: &str) -> Result<Vec<Dependency>> {
+    #[derive(Deserialize)]
+    struct Lockfile {
+        #[serde(default)]
+        package: Vec<LockedPackage>,
+    }
+    #[derive(Deserialize)]
+    struct LockedPackage {
+        name: String,
+        version: String,
+        source: Option<String>,
+    }
+
+    let lock: Lockfile = toml::from_str(lockfile).context("Invalid Cargo.lock")?;
+    Ok(lock
+        .package
+        .into_iter()
+        .filter(|p| {
+            p.source
+                .as_deref()
+                .is_some_and(|s| s.starts_with("registry+"))
+        })
+        .map(|p| Dependency {
+            name: p.name,
+            version: p.version,
+            license: None,
+        })
+        .collect())
+}
+
+/// Fill in licenses from the crates unpacked under `$CARGO_HOME/registry/src`
+pub fn with_licenses(deps: &mut [Dependency]) {
+    let Some(cargo_home) = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|h| h.join(".cargo")))
+    else {
+        return;
+    };
+    let indexes: Vec<PathBuf> = fs::read_dir(cargo_home.join("registry/src"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+    for dep in deps.iter_mut().filter(|d| d.license.is_none()) {
+        let crate_dir = format!("{}-{}", dep.name, dep.version);
+        dep.license = indexes
+            .iter()
+            .find_map(|index| license(&index.join(&crate_dir).join("Cargo.toml")));
+    }
+}
+
+fn license(manifest: &Path) -> Option<String> {
+    let manifest: toml::Value = toml::from_str(&fs::read_to_string(manifest).ok()?).ok()?;
+    let package = manifest.get("package")?;
+    match package.get("license").and_then(|l| l.as_str()) {
+        Some(license) => Some(license.to_string()),
+        // Non-SPDX licenses ship as a file
+        None => package
+            .get("license-file")
+            .and_then(|f| f.as_str())
+            .map(|f| format!("file:{}", f)),
+    }
+}
+
+/// Dependencies per license, `unknown` for those without one
+pub fn license_inventory(deps: &[Dependency]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for dep in deps {
+        *counts
+            .entry(dep.license.as_deref().unwrap_or("unknown"))
+            .or_default() += 1;
+    }
+    let mut inventory: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(l, n)| (l.to_string(), n))
+        .collect();
+    inventory.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    inventory
+}
+
+/// One RustSec advisory (`crates/<package>/RUSTSEC-*.md`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advisory {
+    pub id: String,
+    pub package: String,
+    pub title: String,
+    pub date: String,
+    /// `unmaintained`, `unsound`, ... for advisories that are not vulnerabilities
+    pub informational: Option<String>,
+    pub withdrawn: bool,
+    pub patched: Vec<String>,
+    pub unaffected: Vec<String>,
+}
+
+impl Advisory {
+    /// Parse the TOML front matter (in a ```toml fence) and `# Title` of an
+    /// advisory file
+    pub fn parse(markdown: &str) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct FrontMatter {
+            advisory: AdvisorySection,
+            #[serde(default)]
+            versions: VersionsSection,
+        }
+        #[derive(Deserialize)]
+        struct AdvisorySection {
+            id: String,
+            package: String,
+            #[serde(default)]
+            date: String,
+            title: Option<String>,
+            informational: Option<String>,
+            withdrawn: Option<String>,
+        }
+        #[derive(Deserialize, Default)]
+        struct VersionsSection {
+            #[serde(default)]
+            patched: Vec<String>,
+            #[serde(default)]
+            unaffected: Vec<String>,
+        }
+
+        let body = markdown
+            .trim_start()
+            .strip_prefix("```toml")
+            .context("Advisory has no TOML front matter")?;
+        let (front, rest) = body
+            .split_once("```")
+            .context("Unterminated front matter")?;
+        let front: FrontMatter = toml::from_str(front).context("Invalid advisory front matter")?;
+        let title = front.advisory.title.or_else(|| {
+            rest.lines()
+                .find_map(|l| l.trim().strip_prefix("# "))
+                .map(|t| t.trim().to_string())
+        });
+
+        Ok(Self {
+            id: front.advisory.id,
+            package: front.advisory.package,
+            title: title.unwrap_or_default(),
+            date: front.advisory.date,
+            informational: front.advisory.informational,
+            withdrawn: front.advisory.withdrawn.is_some(),
+            patched: front.versions.patched,
+            unaffected: front.versions.unaffected,
+        })
+    }
+
+    /// Whether `version` is neither patched nor unaffected
+    pub fn affects(&self, version: &str) -> bool {
+        !self
+            .patched
+            .iter()
+            .chain(&self.unaffected)
+            .any(|req| version_matches(version, req))
+    }
+}
+
+/// A dependency with an advisory against its version
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub package: String,
+    pub version: String,
+    pub advisory: String,
+    pub title: String,
+    pub patched: Vec<String>,
+}
+
+/// Advisories by package, from a checkout of the RustSec advisory database
+#[derive(Debug, Default)]
+pub struct AdvisoryDb {
+    advisories: HashMap<String, Vec<Advisory>>,
+}
+
+impl AdvisoryDb {
+    /// `CELL_ADVISORY_DB`, else `~/.cell/advisory-db`
+    pub fn default_path() -> Option<PathBuf> {
+        std::env::var_os("CELL_ADVISORY_DB")
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|h| h.join(".cell/advisory-db")))
+    }
+
+    /// Clone the database into `path`, or pull it if already there
+    pub fn update(path: &Path) -> Result<()> {
+        let status = if path.join(".git").exists() {
+            Command::new("git")
+                .args(["pull", "--quiet", "--ff-only"])
+                .current_dir(path)
+                .status()?
+        } else {
+            Command::new("git")
+                .args(["clone", "--quiet", "--depth", "1", ADVISORY_DB_URL])
+                .arg(path)
+                .status()?
+        };
+        if !status.success() {
+            anyhow::bail!(
+                "Updating the advisory database at {} failed",
+                path.display()
+            );
+        }
+        Ok(())
+    }
+
+    /// Every advisory under `path/crates`. Files that fail to parse are
+    /// skipped, so one malformed advisory does not disable the scan.
+    pub fn open(path: &Path) -> Result<Self> {
+        let crates = path.join("crates");
+        if !crates.is_dir() {
+            anyhow::bail!("No advisory database at {}", path.display());
+        }
+        let mut db = Self::default();
+        for entry in walkdir::WalkDir::new(&crates).into_iter().flatten() {
+            if entry.path().extension().and_then(|e| e.to_str()) != Some("md") {
+                continue;
+            }
+            let Ok(markdown) = fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            if let Ok(advisory) = Advisory::parse(&markdown) {
+                db.insert(advisory);
+            }
+        }
+        Ok(db)
+    }
+
+    pub fn insert(&mut self, advisory: Advisory) {
+        self.advisories
+            .entry(advisory.package.clone())
+            .or_default()
+            .push(advisory);
+    }
+
+    pub fn len(&self) -> usize {
+        self.advisories.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.advisories.is_empty()
+    }
+
+    /// Vulnerabilities among `deps`; informational and withdrawn advisories
+    /// are left out
+    pub fn scan(&self, deps: &[Dependency]) -> Vec<Finding> {
+        let mut findings: Vec<Finding> = deps
+            .iter()
+            .flat_map(|dep| {
+                self.advisories
+                    .get(&dep.name)
+                    .into_iter()
+                    .flatten()
+                    .filter(|a| a.informational.is_none() && !a.withdrawn)
+                    .filter(|a| a.affects(&dep.version))
+                    .map(|a| Finding {
+                        package: dep.name.clone(),
+                        version: dep.version.clone(),
+                        advisory: a.id.clone(),
+                        title: a.title.clone(),
+                        patched: a.patched.clone(),
+                    })
+            })
+            .collect();
+        findings.sort_by(|a, b| {
+            a.package
+                .cmp(&b.package)
+                .then_with(|| a.advisory.cmp(&b.advisory))
+        });
+        findings
+    }
+}
+
+/// Whether `version` satisfies every comparator of `requirement`
+pub fn version_matches(version: &str, requirement: &str) -> bool {
+    let Some(version) = parse_version(version) else {
+        return false;
+    };
+    requirement
+        .split(',')
+        .all(|comparator| comparator_matches(version, comparator.trim()))
+}
+
+type Version = (u64, u64, u64);
+
+fn parse_version(version: &str) -> Option<Version> {
+    let (version, _) = parse_partial(version)?;
+    Some(version)
+}
+
+/// A version with missing parts as 0, and how many parts were given
+fn parse_partial(version: &str) -> Option<(Version, usize)> {
+    let core = version.trim().split(['-', '+']).next()?;
+    let parts: Vec<u64> = core
+        .split('.')
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+    match parts[..] {
+        [major] => Some(((major, 0, 0), 1)),
+        [major, minor] => Some(((major, minor, 0), 2)),
+        [major, minor, patch] => Some(((major, minor, patch), 3)),
+        _ => None,
+    }
+}
+
+fn comparator_matches(version: Version, comparator: &str) -> bool {
+    let (op, rest) = [">=", "<=", ">", "<", "=", "^", "~"]
+        .iter()
+        .find_map(|op| comparator.strip_prefix(op).map(|rest| (*op, rest)))
+        .unwrap_or(("^", comparator));
+    let Some((bound, parts)) = parse_partial(rest) else {
+        return false;
+    };
+    match op {
+        ">=" => version >= bound,
+        "<=" => version < upper(bound, parts, false),
+        ">" => version >= upper(bound, parts, false),
+        "<" => version < bound,
+        "=" => version >= bound && version < upper(bound, parts, false),
+        "~" => version >= bound && version < tilde_upper(bound, parts),
+        _ => version >= bound && version < upper(bound, parts, true),
+    }
+}
+
+/// First version past `bound`: past the given parts, or for caret
+/// requirements past the leftmost non-zero part
+fn upper((major, minor, patch): Version, parts: usize, caret: bool) -> Version {
+    let part = if caret {
+        match (major, minor, parts) {
+            (0, 0, 3) => 3,
+            (0, _, p) if p >= 2 => 2,
+            _ => 1,
+        }
+    } else {
+        parts
+    };
+    match part {
+        1 => (major + 1, 0, 0),
+        2 => (major, minor + 1, 0),
+        _ => (major, minor, patch + 1),
+    }
+}
+
+fn tilde_upper((major, minor, _): Version, parts: usize) -> Version {
+    if parts == 1 {
+        (major + 1, 0, 0)
+    } else {
+        (major, minor + 1, 0)
+    }
+}
@@ -0,0 +1,340 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Content-addressed store for compiled cell binaries.
+//!
+//! A binary is identified by `(cell, target triple, source hash)`. Once one node
+//! has compiled a cell, every other node with the same triple can fetch the
+//! result from it instead of running cargo again.
+//!
+//! Artifacts travel with a keyed BLAKE3 tag computed with the mesh artifact key
+//! (`~/.cell/keys/artifact.key`, shared by every node of an organism). Imports
+//! whose tag does not verify are rejected before anything touches the store.
+//!
+//! Between nodes, Axon transfers the files of an artifact directory as they
+//! are (see [`ArtifactKey::transfer_files`] and [`ArtifactStore::import_files`]).
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use walkdir::WalkDir;
+
+const BINARY_FILE: &str = "bin";
+const MANIFEST_FILE: &str = "artifact.json";
+
+/// Identity of a compiled binary.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ArtifactKey {
+    pub cell: String,
+    pub triple: String,
+    pub source_hash: String,
+}
+
+impl ArtifactKey {
+    pub fn new(cell: &str, triple: &str, source_hash: &str) -> Self {
+        Self {
+            cell: cell.to_string(),
+            triple: triple.to_string(),
+            source_hash: source_hash.to_string(),
+        }
+    }
+
+    /// Key for building `source_dir` on this host.
+    pub fn for_host(cell: &str, source_dir: &Path) -> Result<Self> {
+        Ok(Self::new(cell, &host_triple(), &source_hash(source_dir)?))
+    }
+
+    fn relative_dir(&self) -> PathBuf {
+        PathBuf::from(&self.cell)
+            .join(&self.triple)
+            .join(&self.source_hash)
+    }
+
+    /// Paths of the binary and its manifest below a store root, for fetching
+    /// them from another node's store.
+    pub fn transfer_files(&self) -> (String, String) {
+        let dir = format!("{}/{}/{}", self.cell, self.triple, self.source_hash);
+        (
+            format!("{}/{}", dir, BINARY_FILE),
+            format!("{}/{}", dir, MANIFEST_FILE),
+        )
+    }
+
+    /// Stable byte representation mixed into the artifact tag.
+    fn canonical(&self) -> String {
+        format!("{}\0{}\0{}\0", self.cell, self.triple, self.source_hash)
+    }
+}
+
+/// The architecture/OS pair binaries are compatible across (e.g. `x86_64-linux`).
+pub fn host_triple() -> String {
+    format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS)
+}
+
+/// Hash every `.rs` and `.toml` file under `dir`, skipping build output and runtime state.
+///
+/// Files are visited in sorted order and their relative paths are hashed too, so
+/// the result is identical on every node holding the same source tree.
+pub fn source_hash(dir: &Path) -> Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    let walker = WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+            !(e.file_type().is_dir() && (name == "target" || name == ".cell" || name == ".git"))
+        });
+
+    for entry in walker.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path
+            .extension()
+            .is_some_and(|ext| ext == "rs" || ext == "toml")
+        {
+            let rel = path.strip_prefix(dir).unwrap_or(path);
+            hasher.update(rel.to_string_lossy().as_bytes());
+            hasher.update(&fs::read(path)?);
+        }
+    }
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Compile a cell in release mode and return the path of its binary.
+pub fn build_release(name: &str, path: &Path) -> Result<PathBuf> {
+    let status = Command::new("cargo")
+        .args(["build", "--release", "-p", name])
+        .current_dir(path)
+        .status()
+        .with_context(|| format!("Failed to run cargo for {}", name))?;
+
+    if !status.success() {
+        bail!("Build failed for {}", name);
+    }
+
+    let target_dir = std::env::var("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| path.join("target"));
+    Ok(target_dir.join("release").join(name))
+}
+
+/// Key used to tag and verify artifacts exchanged between nodes.
+pub struct SigningKey([u8; 32]);
+
+impl SigningKey {
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Default location of the mesh artifact key.
+    pub fn default_path() -> Result<PathBuf> {
+        let home = dirs::home_dir().context("No HOME")?;
+        Ok(home.join(".cell/keys/artifact.key"))
+    }
+
+    /// Load the key at `path`, generating a fresh one if none exists yet.
+    ///
+    /// Nodes that should trust each other's artifacts must share this file.
+    pub fn load_or_create(path: &Path) -> Result<Self> {
+        if let Ok(bytes) = fs::read(path) {
+            let key: [u8; 32] = bytes
+                .as_slice()
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("Artifact key {:?} must be 32 bytes", path))?;
+            return Ok(Self(key));
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut key = [0u8; 32];
+        getrandom::getrandom(&mut key)
+            .map_err(|e| anyhow::anyhow!("No OS randomness for the artifact key: {}", e))?;
+        // Created 0600, so the key is never readable by others
+        let mut file = fs::OpenOptions::new();
+        file.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            file.mode(0o600);
+        }
+        file.open(path)
+            .and_then(|mut f| std::io::Write::write_all(&mut f, &key))
+            .with_context(|| format!("Failed to write artifact key {:?}", path))?;
+        Ok(Self(key))
+    }
+
+    pub fn sign(&self, key: &ArtifactKey, bytes: &[u8]) -> String {
+        self.tag(&[key.canonical().as_bytes(), bytes])
+            .to_hex()
+            .to_string()
+    }
+
+    pub fn verify(&self, key: &ArtifactKey, bytes: &[u8], tag: &str) -> bool {
+        self.matches(&[key.canonical().as_bytes(), bytes], tag)
+    }
+
+    /// Tag an arbitrary byte string (used for whole bundles such as spores).
+    pub fn sign_bytes(&self, bytes: &[u8]) -> String {
+        self.tag(&[bytes]).to_hex().to_string()
+    }
+
+    pub fn verify_bytes(&self, bytes: &[u8], tag: &str) -> bool {
+        self.matches(&[bytes], tag)
+    }
+
+    fn tag(&self, parts: &[&[u8]]) -> blake3::Hash {
+        let mut hasher = blake3::Hasher::new_keyed(&self.0);
+        for part in parts {
+            hasher.update(part);
+        }
+        hasher.finalize()
+    }
+
+    fn matches(&self, parts: &[&[u8]], tag: &str) -> bool {
+        match blake3::Hash::from_hex(tag) {
+            // blake3::Hash equality is constant-time
+            Ok(expected) => self.tag(parts) == expected,
+            Err(_) => false,
+        }
+    }
+}
+
+/// Metadata stored next to each binary.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ArtifactManifest {
+    key: ArtifactKey,
+    size: u64,
+    digest: String,
+    tag: String,
+}
+
+/// A binary in transit between nodes.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArtifactBlob {
+    pub key: ArtifactKey,
+    pub bytes: Vec<u8>,
+    pub tag: String,
+}
+
+/// On-disk artifact cache (`~/.cell/artifacts/<cell>/<triple>/<hash>/`).
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+impl ArtifactStore {
+    pub fn open_default() -> Result<Self> {
+        let home = dirs::home_dir().context("No HOME")?;
+        Ok(Self::at(home.join(".cell/artifacts")))
+    }
+
+    pub fn at(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path of the cached binary for `key`, if present and intact.
+    pub fn lookup(&self, key: &ArtifactKey) -> Option<PathBuf> {
+        let dir = self.root.join(key.relative_dir());
+        let manifest = self.read_manifest(&dir).ok()?;
+        let bin = dir.join(BINARY_FILE);
+        let bytes = fs::read(&bin).ok()?;
+        if blake3::hash(&bytes).to_hex().as_str() != manifest.digest {
+            return None;
+        }
+        Some(bin)
+    }
+
+    /// Copy a freshly built binary into the store and tag it.
+    pub fn insert(&self, key: &ArtifactKey, binary: &Path, signer: &SigningKey) -> Result<PathBuf> {
+        let bytes = fs::read(binary)
+            .with_context(|| format!("Failed to read built binary {:?}", binary))?;
+        let tag = signer.sign(key, &bytes);
+        self.write(key, &bytes, tag)
+    }
+
+    /// Package a cached binary for transfer to another node.
+    pub fn export(&self, key: &ArtifactKey) -> Result<Option<ArtifactBlob>> {
+        let Some(bin) = self.lookup(key) else {
+            return Ok(None);
+        };
+        let manifest = self.read_manifest(&self.root.join(key.relative_dir()))?;
+        Ok(Some(ArtifactBlob {
+            key: key.clone(),
+            bytes: fs::read(bin)?,
+            tag: manifest.tag,
+        }))
+    }
+
+    /// Verify and install a binary received from a peer.
+    pub fn import(&self, blob: &ArtifactBlob, signer: &SigningKey) -> Result<PathBuf> {
+        if !signer.verify(&blob.key, &blob.bytes, &blob.tag) {
+            bail!(
+                "Artifact signature mismatch for '{}' ({}, {})",
+                blob.key.cell,
+                blob.key.triple,
+                blob.key.source_hash
+            );
+        }
+        self.write(&blob.key, &blob.bytes, blob.tag.clone())
+    }
+
+    fn write(&self, key: &ArtifactKey, bytes: &[u8], tag: String) -> Result<PathBuf> {
+        let dir = self.root.join(key.relative_dir());
+        fs::create_dir_all(&dir)?;
+
+        // Write to a temp name first so concurrent spawns never exec a partial file
+        let tmp = dir.join(format!("{}.tmp.{}", BINARY_FILE, std::process::id()));
+        fs::write(&tmp, bytes)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&tmp, fs::Permissions::from_mode(0o755))?;
+        }
+        let bin = dir.join(BINARY_FILE);
+        fs::rename(&tmp, &bin)?;
+
+        let manifest = ArtifactManifest {
+            key: key.clone(),
+            size: bytes.len() as u64,
+            digest: blake3::hash(bytes).to_hex().to_string(),
+            tag,
+        };
+        fs::write(
+            dir.join(MANIFEST_FILE),
+            serde_json::to_vec_pretty(&manifest)?,
+        )?;
+        Ok(bin)
+    }
+
+    /// Verify and install an artifact received as the files named by
+    /// [`ArtifactKey::transfer_files`].
+    pub fn import_files(
+        &self,
+        key: &ArtifactKey,
+        binary: &Path,
+        manifest: &Path,
+        signer: &SigningKey,
+    ) -> Result<PathBuf> {
+        let manifest: ArtifactManifest = serde_json::from_slice(&fs::read(manifest)?)
+            .context("Received artifact manifest is corrupt")?;
+        if manifest.key != *key {
+            bail!("Received manifest is for {:?}, not {:?}", manifest.key, key);
+        }
+        let blob = ArtifactBlob {
+            key: key.clone(),
+            bytes: fs::read(binary)?,
+            tag: manifest.tag,
+        };
+        self.import(&blob, signer)
+    }
+
+    fn read_manifest(&self, dir: &Path) -> Result<ArtifactManifest> {
+        let content = fs::read(dir.join(MANIFEST_FILE))?;
+        Ok(serde_json::from_slice(&content)?)
+    }
+}
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! AsyncAPI 2 event catalog for pub/sub topics.
+//!
+//! A [`Topic`] names its payload protein and the cells that produce and
+//! consume it. [`document`] turns a catalog into one AsyncAPI document: each
+//! topic is a channel whose message payload is the protein's JSON schema,
+//! taken from the producing cell's [`Schema`] (same mapping as the OpenAPI
+//! generator). Producers and consumers are listed as `x-producers` and
+//! `x-consumers`.
+
+use crate::openapi::{protein_schema, type_schema};
+use crate::schema::Schema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+const ASYNCAPI_VERSION: &str = "2.6.0";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Topic {
+    pub name: String,
+    /// Payload type, as written in the producer's source
+    pub payload: String,
+    pub producers: Vec<String>,
+    pub consumers: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Build the catalog. `schemas` maps cell names to their schemas; payload
+/// types are looked up in the producers' schemas.
+pub fn document(title: &str, topics: &[Topic], schemas: &BTreeMap<String, Schema>) -> Value {
+    let mut channels = Map::new();
+    let mut components = Map::new();
+
+    for topic in topics {
+        let (payload, _) = type_schema(&topic.payload);
+        let message = format!("{}Message", topic.name.replace(['.', '/', '-'], "_"));
+
+        let mut channel = json!({
+            "subscribe": {
+                "operationId": format!("on_{}", topic.name.replace(['.', '/', '-'], "_")),
+                "message": { "$ref": format!("#/components/messages/{}", message) }
+            },
+            "x-producers": topic.producers,
+            "x-consumers": topic.consumers,
+        });
+        if let Some(description) = &topic.description {
+            channel["description"] = json!(description);
+        }
+        channels.insert(topic.name.clone(), channel);
+        components.insert(
+            message,
+            json!({ "name": topic.name, "contentType": "application/json", "payload": payload }),
+        );
+    }
+
+    // Proteins of every producing cell, first definition wins
+    let mut types = Map::new();
+    for cell in topics.iter().flat_map(|t| &t.producers) {
+        let Some(schema) = schemas.get(cell) else {
+            continue;
+        };
+        for (name, def) in &schema.types {
+            types
+                .entry(name.clone())
+                .or_insert_with(|| protein_schema(def));
+        }
+    }
+
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(serde_json::to_string(topics).unwrap_or_default().as_bytes());
+    for schema in schemas.values() {
+        hasher.update(&schema.fingerprint().to_le_bytes());
+    }
+    let version = hasher.finalize().to_hex()[..16].to_string();
+
+    json!({
+        "asyncapi": ASYNCAPI_VERSION,
+        "info": { "title": title, "version": version },
+        "defaultContentType": "application/json",
+        "channels": channels,
+        "components": { "messages": components, "schemas": types },
+    })
+}
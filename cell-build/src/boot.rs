@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Boot planning for kernel bring-up.
+//!
+//! [`BootGraph`] records which cells must be ready before another may start.
+//! Supervisors ask it for every cell whose dependencies are ready, start those
+//! concurrently, and come back as readiness events arrive. [`BootTimeline`]
+//! records when each cell started and became ready, so slow boots can be
+//! attributed to a specific cell.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::time::Instant;
+
+/// Readiness dependencies between cells.
+#[derive(Debug, Clone, Default)]
+pub struct BootGraph {
+    deps: BTreeMap<String, Vec<String>>,
+}
+
+impl BootGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `name`, which may only start once every cell in `after` is ready.
+    pub fn add(&mut self, name: &str, after: &[&str]) -> &mut Self {
+        self.deps.insert(
+            name.to_string(),
+            after.iter().map(|d| d.to_string()).collect(),
+        );
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.deps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deps.is_empty()
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = &str> {
+        self.deps.keys().map(|k| k.as_str())
+    }
+
+    /// Reject unknown dependencies and cycles before anything is started.
+    pub fn validate(&self) -> Result<()> {
+        for (cell, deps) in &self.deps {
+            for dep in deps {
+                if !self.deps.contains_key(dep) {
+                    bail!("'{}' waits for unknown cell '{}'", cell, dep);
+                }
+            }
+        }
+        let planned: HashSet<String> = self.waves_unchecked().into_iter().flatten().collect();
+        if planned.len() != self.deps.len() {
+            let stuck: Vec<_> = self
+                .deps
+                .keys()
+                .filter(|c| !planned.contains(*c))
+                .cloned()
+                .collect();
+            bail!("Dependency cycle between {:?}", stuck);
+        }
+        Ok(())
+    }
+
+    /// Cells not yet started whose dependencies are all ready.
+    pub fn startable(&self, ready: &HashSet<String>, started: &HashSet<String>) -> Vec<String> {
+        self.deps
+            .iter()
+            .filter(|(cell, deps)| {
+                !started.contains(*cell) && deps.iter().all(|d| ready.contains(d))
+            })
+            .map(|(cell, _)| cell.clone())
+            .collect()
+    }
+
+    /// Group cells into waves that can start together (for display and planning).
+    pub fn waves(&self) -> Result<Vec<Vec<String>>> {
+        self.validate()?;
+        Ok(self.waves_unchecked())
+    }
+
+    fn waves_unchecked(&self) -> Vec<Vec<String>> {
+        let mut ready = HashSet::new();
+        let mut waves = Vec::new();
+        loop {
+            let wave = self.startable(&ready, &ready);
+            if wave.is_empty() {
+                break;
+            }
+            ready.extend(wave.iter().cloned());
+            waves.push(wave);
+        }
+        waves
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BootEvent {
+    pub cell: String,
+    pub started_ms: u64,
+    pub ready_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Start/ready times of every cell during one boot.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BootTimeline {
+    #[serde(skip, default = "Instant::now")]
+    origin: Instant,
+    pub events: Vec<BootEvent>,
+    pub total_ms: u64,
+}
+
+impl Default for BootTimeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BootTimeline {
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            events: Vec::new(),
+            total_ms: 0,
+        }
+    }
+
+    fn now_ms(&self) -> u64 {
+        self.origin.elapsed().as_millis() as u64
+    }
+
+    pub fn started(&mut self, cell: &str) {
+        let started_ms = self.now_ms();
+        self.events.push(BootEvent {
+            cell: cell.to_string(),
+            started_ms,
+            ready_ms: None,
+            error: None,
+        });
+    }
+
+    pub fn ready(&mut self, cell: &str) {
+        let now = self.now_ms();
+        match self.event_mut(cell) {
+            Some(e) => e.ready_ms = Some(now),
+            // Already running before boot began
+            None => self.events.push(BootEvent {
+                cell: cell.to_string(),
+                started_ms: now,
+                ready_ms: Some(now),
+                error: None,
+            }),
+        }
+        self.total_ms = now;
+    }
+
+    pub fn failed(&mut self, cell: &str, error: &str) {
+        let now = self.now_ms();
+        if let Some(e) = self.event_mut(cell) {
+            e.error = Some(error.to_string());
+        }
+        self.total_ms = now;
+    }
+
+    fn event_mut(&mut self, cell: &str) -> Option<&mut BootEvent> {
+        self.events.iter_mut().find(|e| e.cell == cell)
+    }
+
+    /// Human-readable Gantt-style summary.
+    pub fn render(&self) -> String {
+        let width = 40usize;
+        let total = self.total_ms.max(1);
+        let name_width = self.events.iter().map(|e| e.cell.len()).max().unwrap_or(0);
+
+        let mut out = format!("Boot timeline ({} ms)\n", self.total_ms);
+        for e in &self.events {
+            let end = e.ready_ms.unwrap_or(self.total_ms);
+            let from = (e.started_ms * width as u64 / total) as usize;
+            let to = ((end * width as u64 / total) as usize)
+                .max(from + 1)
+                .min(width);
+            let bar = format!("{}{}", " ".repeat(from), "█".repeat(to - from));
+            let status = match (&e.error, e.ready_ms) {
+                (Some(err), _) => format!("failed: {}", err),
+                (None, Some(ready)) => format!("{} ms", ready - e.started_ms),
+                (None, None) => "pending".to_string(),
+            };
+            out.push_str(&format!(
+                "  {:<name_width$}  |{:<width$}|  {}\n",
+                e.cell,
+                bar,
+                status,
+                name_width = name_width,
+                width = width
+            ));
+        }
+        out
+    }
+}
@@ -0,0 +1,428 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Root daemon integration with the host service manager.
+//!
+//! Generates systemd units (Linux) or launchd plists (macOS) for kernel cells
+//! such as mycelium, so the root daemon is started by the OS with a restart
+//! policy and routed logs instead of being bootstrapped through `cargo run`.
+//!
+//! Units are installed system-wide (`/etc/systemd/system`,
+//! `/Library/LaunchDaemons`), which needs root, and run as the user in
+//! [`ServiceSpec::user`] so the daemon keeps that user's `~/.cell`.
+//!
+//! On systemd the resolver socket is owned by a `.socket` unit: the first
+//! connection activates the daemon, which picks the listener up through
+//! [`activated_listener`] and keeps the activation variables away from the
+//! processes it starts with [`without_activation`].
+
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Description of a kernel daemon to install.
+#[derive(Debug, Clone)]
+pub struct ServiceSpec {
+    pub name: String,
+    pub binary: PathBuf,
+    /// Unix socket handed over by the service manager (systemd only)
+    pub socket: Option<PathBuf>,
+    pub env: Vec<(String, String)>,
+    pub restart_sec: u32,
+    /// Account the daemon runs as; root when unset
+    pub user: Option<String>,
+    /// Where launchd writes the daemon's output
+    pub log_dir: Option<PathBuf>,
+}
+
+impl ServiceSpec {
+    pub fn new(name: &str, binary: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.to_string(),
+            binary: binary.into(),
+            socket: None,
+            env: Vec::new(),
+            restart_sec: 2,
+            user: None,
+            log_dir: None,
+        }
+    }
+
+    pub fn with_user(mut self, user: &str) -> Self {
+        self.user = Some(user.to_string());
+        self
+    }
+
+    pub fn with_socket(mut self, socket: impl Into<PathBuf>) -> Self {
+        self.socket = Some(socket.into());
+        self
+    }
+
+    pub fn with_env(mut self, key: &str, value: &str) -> Self {
+        self.env.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Unit name used by both managers, e.g. `cell-mycelium`.
+    pub fn unit_name(&self) -> String {
+        format!("cell-{}", self.name)
+    }
+
+    /// The default spec for a kernel daemon installed under `~/.cell/bin`.
+    pub fn kernel(name: &str) -> Result<Self> {
+        let home = dirs::home_dir().context("No HOME")?;
+        Ok(Self::kernel_in(name, &home))
+    }
+
+    /// [`ServiceSpec::kernel`] for the user whose home is `home`, e.g. when
+    /// installing through sudo.
+    pub fn kernel_in(name: &str, home: &Path) -> Self {
+        let mut spec = Self::new(name, home.join(".cell/bin").join(name))
+            .with_env("HOME", &home.to_string_lossy());
+        spec.log_dir = Some(home.join(".cell/logs"));
+        if name == "mycelium" {
+            spec = spec.with_socket(home.join(".cell/runtime/system/mycelium.sock"));
+        }
+        spec
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceManager {
+    Systemd,
+    Launchd,
+}
+
+impl ServiceManager {
+    pub fn detect() -> Option<Self> {
+        match std::env::consts::OS {
+            "linux" => Some(Self::Systemd),
+            "macos" => Some(Self::Launchd),
+            _ => None,
+        }
+    }
+
+    /// Directory system daemons are installed into.
+    pub fn unit_dir(self) -> PathBuf {
+        match self {
+            Self::Systemd => PathBuf::from("/etc/systemd/system"),
+            Self::Launchd => PathBuf::from("/Library/LaunchDaemons"),
+        }
+    }
+
+    /// Render every file needed for `spec`, as (file name, contents).
+    pub fn render(self, spec: &ServiceSpec) -> Result<Vec<(String, String)>> {
+        Ok(match self {
+            Self::Systemd => {
+                let mut files = vec![(
+                    format!("{}.service", spec.unit_name()),
+                    render_systemd_service(spec)?,
+                )];
+                if let Some(socket) = render_systemd_socket(spec) {
+                    files.push((format!("{}.socket", spec.unit_name()), socket));
+                }
+                files
+            }
+            Self::Launchd => vec![(
+                format!("{}.plist", launchd_label(spec)),
+                render_launchd_plist(spec),
+            )],
+        })
+    }
+
+    /// Write the unit files and enable them. Returns the written paths.
+    /// Needs root.
+    pub fn install(self, spec: &ServiceSpec) -> Result<Vec<PathBuf>> {
+        let dir = self.unit_dir();
+        fs::create_dir_all(&dir)?;
+
+        let mut written = Vec::new();
+        for (file, contents) in self.render(spec)? {
+            let path = dir.join(file);
+            fs::write(&path, contents).with_context(|| format!("Failed to write {:?}", path))?;
+            written.push(path);
+        }
+
+        match self {
+            Self::Systemd => {
+                run(Command::new("systemctl").arg("daemon-reload"))?;
+                let target = if spec.socket.is_some() {
+                    format!("{}.socket", spec.unit_name())
+                } else {
+                    format!("{}.service", spec.unit_name())
+                };
+                run(Command::new("systemctl").args(["enable", "--now", &target]))?;
+            }
+            Self::Launchd => {
+                run(Command::new("launchctl")
+                    .arg("load")
+                    .arg("-w")
+                    .arg(&written[0]))?;
+            }
+        }
+        Ok(written)
+    }
+
+    /// Whether `spec` has been installed.
+    pub fn is_installed(self, spec: &ServiceSpec) -> bool {
+        let dir = self.unit_dir();
+        self.render(spec)
+            .is_ok_and(|files| files.iter().all(|(file, _)| dir.join(file).exists()))
+    }
+
+    /// Ask the service manager to start an installed daemon.
+    pub fn start(self, spec: &ServiceSpec) -> Result<()> {
+        match self {
+            Self::Systemd => {
+                run(Command::new("systemctl")
+                    .args(["start", &format!("{}.service", spec.unit_name())]))
+            }
+            Self::Launchd => run(Command::new("launchctl")
+                .args(["kickstart", &format!("system/{}", launchd_label(spec))])),
+        }
+    }
+}
+
+fn run(cmd: &mut Command) -> Result<()> {
+    let status = cmd
+        .status()
+        .with_context(|| format!("Failed to run {:?}", cmd))?;
+    if !status.success() {
+        bail!("{:?} exited with {}", cmd, status);
+    }
+    Ok(())
+}
+
+/// Escape `value` for use inside a double-quoted systemd assignment.
+///
+/// Quotes and backslashes are C-escaped and `%` is doubled so it is not read
+/// as a specifier. A unit line cannot continue past a newline, so values
+/// holding one are refused.
+fn systemd_escape(value: &str) -> Result<String> {
+    if value.contains(['\n', '\r']) {
+        bail!(
+            "{:?} contains a line break, which a unit file cannot hold",
+            value
+        );
+    }
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '%' => out.push_str("%%"),
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+pub fn render_systemd_service(spec: &ServiceSpec) -> Result<String> {
+    let mut out = String::new();
+    out.push_str("[Unit]\n");
+    out.push_str(&format!("Description=Cell kernel daemon ({})\n", spec.name));
+    if spec.socket.is_some() {
+        out.push_str(&format!("Requires={}.socket\n", spec.unit_name()));
+        out.push_str(&format!("After={}.socket\n", spec.unit_name()));
+    }
+    out.push_str("\n[Service]\n");
+    out.push_str("Type=simple\n");
+    if let Some(user) = &spec.user {
+        out.push_str(&format!("User={}\n", user));
+    }
+    out.push_str(&format!("ExecStart={}\n", spec.binary.display()));
+    for (k, v) in &spec.env {
+        if k.is_empty() || k.contains('=') {
+            bail!("{:?} is not an environment variable name", k);
+        }
+        out.push_str(&format!(
+            "Environment=\"{}={}\"\n",
+            systemd_escape(k)?,
+            systemd_escape(v)?
+        ));
+    }
+    out.push_str("Restart=on-failure\n");
+    out.push_str(&format!("RestartSec={}\n", spec.restart_sec));
+    out.push_str("StandardOutput=journal\n");
+    out.push_str("StandardError=journal\n");
+    out.push_str(&format!("SyslogIdentifier={}\n", spec.unit_name()));
+    out.push_str("\n[Install]\n");
+    out.push_str("WantedBy=multi-user.target\n");
+    Ok(out)
+}
+
+pub fn render_systemd_socket(spec: &ServiceSpec) -> Option<String> {
+    let socket = spec.socket.as_ref()?;
+    // Owned by the daemon's user, who is the only one connecting
+    let owner = match &spec.user {
+        Some(user) => format!("SocketUser={}\n", user),
+        None => String::new(),
+    };
+    Some(format!(
+        "[Unit]\n\
+         Description=Cell kernel socket ({name})\n\
+         \n\
+         [Socket]\n\
+         ListenStream={path}\n\
+         {owner}\
+         SocketMode=0600\n\
+         RemoveOnStop=true\n\
+         \n\
+         [Install]\n\
+         WantedBy=sockets.target\n",
+        name = spec.name,
+        path = socket.display(),
+        owner = owner
+    ))
+}
+
+fn launchd_label(spec: &ServiceSpec) -> String {
+    format!("com.cell.{}", spec.name)
+}
+
+/// Escape `value` for use as XML character data.
+fn xml_escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// launchd has no portable socket hand-over for plain binaries, so the plist
+/// keeps the daemon alive instead and logs to `~/.cell/logs`.
+pub fn render_launchd_plist(spec: &ServiceSpec) -> String {
+    let log_dir = spec
+        .log_dir
+        .clone()
+        .or_else(|| dirs::home_dir().map(|h| h.join(".cell/logs")))
+        .unwrap_or_else(|| PathBuf::from("/tmp"));
+    let log = log_dir.join(format!("{}.log", spec.name));
+
+    let mut env = String::new();
+    if !spec.env.is_empty() {
+        env.push_str("    <key>EnvironmentVariables</key>\n    <dict>\n");
+        for (k, v) in &spec.env {
+            env.push_str(&format!(
+                "        <key>{}</key>\n        <string>{}</string>\n",
+                xml_escape(k),
+                xml_escape(v)
+            ));
+        }
+        env.push_str("    </dict>\n");
+    }
+    if let Some(user) = &spec.user {
+        env.push_str(&format!(
+            "    <key>UserName</key>\n    <string>{}</string>\n",
+            xml_escape(user)
+        ));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{label}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{binary}</string>
+    </array>
+{env}    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <dict>
+        <key>SuccessfulExit</key>
+        <false/>
+    </dict>
+    <key>ThrottleInterval</key>
+    <integer>{restart}</integer>
+    <key>StandardOutPath</key>
+    <string>{log}</string>
+    <key>StandardErrorPath</key>
+    <string>{log}</string>
+</dict>
+</plist>
+"#,
+        label = xml_escape(&launchd_label(spec)),
+        binary = xml_escape(&spec.binary.to_string_lossy()),
+        env = env,
+        restart = spec.restart_sec,
+        log = xml_escape(&log.to_string_lossy())
+    )
+}
+
+/// Variables systemd sets on an activated process
+const ACTIVATION_ENV: [&str; 3] = ["LISTEN_PID", "LISTEN_FDS", "LISTEN_FDNAMES"];
+
+/// Listener passed in by systemd socket activation, if this process was activated.
+///
+/// Follows the `sd_listen_fds` protocol: `LISTEN_PID` must match our pid and the
+/// first passed descriptor is always fd 3. Only the first call gets it; later
+/// calls return `None`, so the descriptor has a single owner. It is made
+/// close-on-exec as soon as it is claimed, so processes started afterwards
+/// never inherit it. The environment is left alone, since other threads may be
+/// reading it; start children through [`without_activation`] instead.
+#[cfg(unix)]
+pub fn activated_listener() -> Option<std::os::unix::net::UnixListener> {
+    use std::os::unix::io::FromRawFd;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    const SD_LISTEN_FDS_START: i32 = 3;
+    static CLAIMED: AtomicBool = AtomicBool::new(false);
+
+    let pid: u32 = std::env::var("LISTEN_PID").ok()?.parse().ok()?;
+    let fds: i32 = std::env::var("LISTEN_FDS").ok()?.parse().ok()?;
+    if pid != std::process::id() || fds < 1 {
+        return None;
+    }
+    if CLAIMED.swap(true, Ordering::AcqRel) {
+        return None;
+    }
+
+    // Fails only if systemd did not actually pass the descriptor
+    if unsafe { libc::fcntl(SD_LISTEN_FDS_START, libc::F_SETFD, libc::FD_CLOEXEC) } < 0 {
+        return None;
+    }
+    Some(unsafe { std::os::unix::net::UnixListener::from_raw_fd(SD_LISTEN_FDS_START) })
+}
+
+/// Keep the socket activation variables out of `cmd`'s environment, so the
+/// child doesn't think the descriptors are meant for it.
+pub fn without_activation(cmd: &mut Command) -> &mut Command {
+    for key in ACTIVATION_ENV {
+        cmd.env_remove(key);
+    }
+    cmd
+}
+
+/// Start `name` through the service manager if it was installed with `cell install-service`.
+pub(crate) fn start_installed(name: &str) -> bool {
+    let (Some(manager), Ok(spec)) = (ServiceManager::detect(), ServiceSpec::kernel(name)) else {
+        return false;
+    };
+    manager.is_installed(&spec) && manager.start(&spec).is_ok()
+}
+
+/// Write the rendered files into `dir` without enabling anything.
+pub fn render_to(dir: &Path, manager: ServiceManager, spec: &ServiceSpec) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+    manager
+        .render(spec)?
+        .into_iter()
+        .map(|(file, contents)| {
+            let path = dir.join(file);
+            fs::write(&path, contents)?;
+            Ok(path)
+        })
+        .collect()
+}
@@ -0,0 +1,176 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Prebuilt kernel binaries.
+//!
+//! `cell install` places the kernel cells (mycelium, hypervisor, builder, ...)
+//! in `~/.cell/bin` together with an `installed.json` index recording each
+//! binary's version and digest. Supervisors resolve kernel cells from there so
+//! production hosts need neither the source tree nor a toolchain.
+//!
+//! `cargo run -p <cell>` remains available as a fallback, but only in dev mode
+//! (`CELL_DEV=1`).
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Cells that make up the kernel, in boot order.
+pub const KERNEL_CELLS: &[&str] = &[
+    "mycelium",
+    "hypervisor",
+    "builder",
+    "mesh",
+    "axon",
+    "observer",
+];
+
+/// Release the installed kernel must match (major.minor).
+pub const KERNEL_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const INDEX_FILE: &str = "installed.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InstalledBinary {
+    pub version: String,
+    pub digest: String,
+}
+
+/// How to launch a kernel cell.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KernelLaunch {
+    Binary(PathBuf),
+    /// Dev mode only: build and run from the workspace
+    Cargo,
+}
+
+impl KernelLaunch {
+    pub fn command(&self, name: &str) -> Command {
+        match self {
+            Self::Binary(path) => Command::new(path),
+            Self::Cargo => {
+                let mut cmd = Command::new("cargo");
+                cmd.args(["run", "--release", "-p", name]);
+                cmd
+            }
+        }
+    }
+}
+
+/// True when running against a source checkout (`CELL_DEV=1`).
+pub fn dev_mode() -> bool {
+    std::env::var("CELL_DEV").is_ok_and(|v| v == "1" || v == "true")
+}
+
+/// `~/.cell/bin`, overridable with `CELL_BIN_DIR`.
+pub fn bin_dir() -> Result<PathBuf> {
+    if let Ok(dir) = std::env::var("CELL_BIN_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+    let home = dirs::home_dir().context("No HOME")?;
+    Ok(home.join(".cell/bin"))
+}
+
+/// Index of binaries installed into a bin directory.
+pub struct KernelBin {
+    dir: PathBuf,
+}
+
+impl KernelBin {
+    pub fn open_default() -> Result<Self> {
+        Ok(Self::at(bin_dir()?))
+    }
+
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn index(&self) -> HashMap<String, InstalledBinary> {
+        fs::read(self.dir.join(INDEX_FILE))
+            .ok()
+            .and_then(|b| serde_json::from_slice(&b).ok())
+            .unwrap_or_default()
+    }
+
+    /// Copy a built binary into the bin directory and record it.
+    pub fn install(&self, name: &str, built: &Path, version: &str) -> Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        let bytes =
+            fs::read(built).with_context(|| format!("Failed to read built binary {:?}", built))?;
+
+        let dest = self.dir.join(name);
+        let tmp = self.dir.join(format!(".{}.tmp", name));
+        fs::write(&tmp, &bytes)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&tmp, fs::Permissions::from_mode(0o755))?;
+        }
+        // rename keeps running instances on the old inode
+        fs::rename(&tmp, &dest)?;
+
+        let mut index = self.index();
+        index.insert(
+            name.to_string(),
+            InstalledBinary {
+                version: version.to_string(),
+                digest: blake3::hash(&bytes).to_hex().to_string(),
+            },
+        );
+        fs::write(
+            self.dir.join(INDEX_FILE),
+            serde_json::to_vec_pretty(&index)?,
+        )?;
+        Ok(dest)
+    }
+
+    /// Path of an installed binary that matches `expected_version` and its recorded digest.
+    pub fn verified(&self, name: &str, expected_version: &str) -> Result<PathBuf> {
+        let index = self.index();
+        let entry = index
+            .get(name)
+            .with_context(|| format!("'{}' is not installed in {:?}", name, self.dir))?;
+
+        if !same_release(&entry.version, expected_version) {
+            bail!(
+                "Installed '{}' is v{}, runtime expects v{} (re-run `cell install`)",
+                name,
+                entry.version,
+                expected_version
+            );
+        }
+
+        let path = self.dir.join(name);
+        let bytes = fs::read(&path).with_context(|| format!("Missing binary {:?}", path))?;
+        if blake3::hash(&bytes).to_hex().as_str() != entry.digest {
+            bail!("Installed '{}' does not match its recorded digest", name);
+        }
+        Ok(path)
+    }
+
+    /// Resolve how to launch `name`: installed binary, or cargo in dev mode.
+    pub fn resolve(&self, name: &str, expected_version: &str) -> Result<KernelLaunch> {
+        match self.verified(name, expected_version) {
+            Ok(path) => Ok(KernelLaunch::Binary(path)),
+            Err(_) if dev_mode() => Ok(KernelLaunch::Cargo),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Resolve a kernel cell from `~/.cell/bin` against this build's kernel version.
+pub fn resolve_kernel(name: &str) -> Result<KernelLaunch> {
+    KernelBin::open_default()?.resolve(name, KERNEL_VERSION)
+}
+
+/// Versions are compatible when major and minor agree.
+fn same_release(installed: &str, expected: &str) -> bool {
+    installed.split('.').take(2).eq(expected.split('.').take(2))
+}
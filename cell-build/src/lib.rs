@@ -13,10 +13,28 @@ use syn::parse_file;
 use syn::visit_mut::VisitMut;
 use walkdir::WalkDir;
 
+pub mod advisory;
+pub mod artifact;
+pub mod asyncapi;
+pub mod boot;
+pub mod daemon;
+pub mod kernel;
+pub mod openapi;
+pub mod proto;
+pub mod python;
+pub mod schema;
+pub mod spore;
+pub mod typescript;
+
 // === PROTOCOL ===
 #[derive(Serialize, Deserialize, Debug)]
 pub enum ResolverRequest {
-    EnsureRunning { cell_name: String },
+    EnsureRunning {
+        cell_name: String,
+        /// Release channel to run, resolved to a version by mycelium
+        #[serde(default)]
+        channel: Option<String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -195,7 +213,7 @@ fn main() {{
             if entry
                 .path()
                 .extension()
-                .map_or(false, |ext| ext == "rs" || ext == "toml")
+                .is_some_and(|ext| ext == "rs" || ext == "toml")
             {
                 if let Ok(bytes) = fs::read(entry.path()) {
                     hasher.update(&bytes);
@@ -347,6 +365,7 @@ pub fn resolve(cell_name: &str) -> Result<String> {
 
     let req = ResolverRequest::EnsureRunning {
         cell_name: cell_name.to_string(),
+        channel: pinned_channel(cell_name),
     };
     let req_json = serde_json::to_vec(&req)?;
     let len = req_json.len() as u32;
@@ -366,6 +385,21 @@ pub fn resolve(cell_name: &str) -> Result<String> {
     }
 }
 
+/// Channel the crate being built pins `cell_name` to in its Cell.toml, as in
+/// `ledger = { path = "../ledger", channel = "beta" }`
+fn pinned_channel(cell_name: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct NeighborManifest {
+        #[serde(default)]
+        neighbors: std::collections::HashMap<String, toml::Value>,
+    }
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").ok()?;
+    let content = fs::read_to_string(Path::new(&manifest_dir).join("Cell.toml")).ok()?;
+    let manifest: NeighborManifest = toml::from_str(&content).ok()?;
+    let channel = manifest.neighbors.get(cell_name)?.get("channel")?;
+    channel.as_str().map(str::to_string)
+}
+
 fn is_kernel_cell(pkg_name: &str) -> bool {
     matches!(
         pkg_name,
@@ -374,18 +408,30 @@ fn is_kernel_cell(pkg_name: &str) -> bool {
 }
 
 fn bootstrap_mycelium(socket_path: &Path) -> Result<UnixStream> {
-    let infra_target_dir = std::env::temp_dir().join("cell-infra-build");
-    std::fs::create_dir_all(&infra_target_dir).ok();
-
-    let _ = Command::new("cargo")
-        .args(&["run", "--release", "-p", "mycelium"])
-        .env("CARGO_TARGET_DIR", infra_target_dir)
-        .env_remove("CELL_SOCKET_DIR")
-        .env_remove("CELL_NODE_ID")
-        .env_remove("CELL_ORGANISM")
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn();
+    // Prefer the service manager when `cell install-service` has been run
+    if !daemon::start_installed("mycelium") {
+        // Cargo is only offered in dev mode; otherwise a missing or tampered binary is fatal
+        let launch = kernel::resolve_kernel("mycelium")
+            .context("Mycelium is not installed or failed verification (set CELL_DEV=1 to build it from source)")?;
+        let mut cmd = match launch {
+            kernel::KernelLaunch::Binary(_) => launch.command("mycelium"),
+            kernel::KernelLaunch::Cargo => {
+                let infra_target_dir = std::env::temp_dir().join("cell-infra-build");
+                std::fs::create_dir_all(&infra_target_dir).ok();
+                let mut cmd = kernel::KernelLaunch::Cargo.command("mycelium");
+                cmd.env("CARGO_TARGET_DIR", infra_target_dir);
+                cmd
+            }
+        };
+
+        let _ = cmd
+            .env_remove("CELL_SOCKET_DIR")
+            .env_remove("CELL_NODE_ID")
+            .env_remove("CELL_ORGANISM")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+    }
 
     let deadline = std::time::Instant::now() + Duration::from_secs(60);
     while std::time::Instant::now() < deadline {
@@ -407,6 +453,29 @@ impl CellBuilder {
     pub fn extract_macros(self) -> Result<Self> {
         Ok(self)
     }
+
+    /// Emit the cell's schema artifact to `~/.cell/schema/<cell>.cell-schema`,
+    /// so clients can be generated without this crate's source tree (see
+    /// [`schema::SchemaArtifact`]). The cell is named by `Cell.toml`, else by
+    /// the package.
+    pub fn emit_schema(self) -> Result<Self> {
+        let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR")?);
+        println!("cargo:rerun-if-changed=src");
+        println!("cargo:rerun-if-changed=Cell.toml");
+
+        let cell = fs::read_to_string(manifest_dir.join("Cell.toml"))
+            .ok()
+            .and_then(|content| toml::from_str::<PartialManifest>(&content).ok())
+            .and_then(|m| m.cell)
+            .map(|c| c.name)
+            .map_or_else(|| std::env::var("CARGO_PKG_NAME"), Ok)?;
+        let file = load_and_flatten_source(&manifest_dir.join("src/main.rs"))?;
+        let artifact = schema::SchemaArtifact::new(&cell, &file)?;
+
+        let dir = schema::artifact_dir().context("No HOME dir")?;
+        artifact.save(&schema::artifact_path(&dir, &cell))?;
+        Ok(self)
+    }
 }
 
 pub fn load_and_flatten_source(entry_path: &Path) -> Result<syn::File> {
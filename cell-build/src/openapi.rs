@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! OpenAPI 3 description of a cell's HTTP gateway mapping.
+//!
+//! Every handler method becomes `POST /{cell}/{method}`. The request body is a
+//! JSON object with one property per argument, the response is the method's
+//! return value as JSON, and proteins become component schemas in their serde
+//! form (enums externally tagged). Failures carry the cell's `ErrorResponse`.
+
+use crate::schema::{Schema, TypeDef};
+use serde_json::{json, Map, Value};
+
+const OPENAPI_VERSION: &str = "3.0.3";
+
+/// Build the OpenAPI document for `cell`.
+pub fn document(cell: &str, schema: &Schema) -> Value {
+    let mut paths = Map::new();
+    let mut methods: Vec<_> = schema.methods.iter().collect();
+    methods.sort_by_key(|(_, m)| m.index);
+
+    for (name, method) in methods {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for (arg, ty) in &method.args {
+            let (schema, optional) = type_schema(ty);
+            if !optional {
+                required.push(json!(arg));
+            }
+            properties.insert(arg.clone(), schema);
+        }
+
+        let mut body = json!({ "type": "object", "properties": properties });
+        if !required.is_empty() {
+            body["required"] = Value::Array(required);
+        }
+        let (response, _) = type_schema(&method.ret);
+
+        paths.insert(
+            format!("/{}/{}", cell, name),
+            json!({
+                "post": {
+                    "operationId": name,
+                    "tags": [cell],
+                    "requestBody": {
+                        "required": true,
+                        "content": { "application/json": { "schema": body } }
+                    },
+                    "responses": {
+                        "200": {
+                            "description": "Handler result",
+                            "content": { "application/json": { "schema": response } }
+                        },
+                        "default": {
+                            "description": "Error reported by the cell",
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/ErrorResponse" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }),
+        );
+    }
+
+    let mut components: Map<String, Value> = schema
+        .types
+        .iter()
+        .map(|(name, def)| (name.clone(), protein_schema(def)))
+        .collect();
+    components.insert(
+        "ErrorResponse".to_string(),
+        json!({
+            "type": "object",
+            "required": ["code", "message", "cell"],
+            "properties": {
+                "code": { "type": "integer", "format": "int32", "description": "CellError code" },
+                "message": { "type": "string" },
+                "cell": { "type": "string" },
+                "retry_after_ms": {
+                    "type": "integer",
+                    "format": "int32",
+                    "description": "Retry hint; 0 means the code's default"
+                }
+            }
+        }),
+    );
+
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": {
+            "title": cell,
+            "version": format!("{:016x}", schema.fingerprint()),
+        },
+        "paths": paths,
+        "components": { "schemas": components },
+    })
+}
+
+/// Component schema of a protein, in its serde JSON shape.
+pub(crate) fn protein_schema(def: &TypeDef) -> Value {
+    match def {
+        TypeDef::Struct(fields) => object(fields),
+        TypeDef::Enum(variants) => {
+            let units: Vec<&String> = variants
+                .iter()
+                .filter(|(_, f)| f.is_empty())
+                .map(|(v, _)| v)
+                .collect();
+            let mut one_of = Vec::new();
+            if !units.is_empty() {
+                one_of.push(json!({ "type": "string", "enum": units }));
+            }
+            for (variant, fields) in variants.iter().filter(|(_, f)| !f.is_empty()) {
+                let inner = match fields.as_slice() {
+                    [(name, ty)] if name == "0" => type_schema(ty).0,
+                    _ if fields[0].0 == "0" => json!({
+                        "type": "array",
+                        "items": {},
+                        "minItems": fields.len(),
+                        "maxItems": fields.len(),
+                    }),
+                    _ => object(fields),
+                };
+                one_of.push(json!({
+                    "type": "object",
+                    "required": [variant],
+                    "properties": { variant.as_str(): inner }
+                }));
+            }
+            match one_of.len() {
+                1 => one_of.remove(0),
+                _ => json!({ "oneOf": one_of }),
+            }
+        }
+    }
+}
+
+fn object(fields: &[(String, String)]) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for (name, ty) in fields {
+        let (schema, optional) = type_schema(ty);
+        if !optional {
+            required.push(json!(name));
+        }
+        properties.insert(name.clone(), schema);
+    }
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = Value::Array(required);
+    }
+    schema
+}
+
+/// JSON schema of a Rust type string, and whether it may be absent.
+pub(crate) fn type_schema(ty: &str) -> (Value, bool) {
+    match syn::parse_str::<syn::Type>(ty) {
+        Ok(parsed) => rust_type(&parsed),
+        Err(_) => (json!({}), false),
+    }
+}
+
+fn rust_type(ty: &syn::Type) -> (Value, bool) {
+    match ty {
+        syn::Type::Tuple(t) if t.elems.is_empty() => (json!({ "nullable": true }), false),
+        syn::Type::Tuple(t) => {
+            let len = t.elems.len();
+            (
+                json!({ "type": "array", "items": {}, "minItems": len, "maxItems": len }),
+                false,
+            )
+        }
+        syn::Type::Reference(r) => rust_type(&r.elem),
+        syn::Type::Array(a) => (
+            json!({ "type": "array", "items": rust_type(&a.elem).0 }),
+            false,
+        ),
+        syn::Type::Path(p) => {
+            let Some(last) = p.path.segments.last() else {
+                return (json!({}), false);
+            };
+            let args: Vec<&syn::Type> = match &last.arguments {
+                syn::PathArguments::AngleBracketed(a) => a
+                    .args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        syn::GenericArgument::Type(t) => Some(t),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            let name = last.ident.to_string();
+            let schema = match (name.as_str(), args.as_slice()) {
+                ("Option", [inner]) => {
+                    let mut schema = rust_type(inner).0;
+                    if let Some(obj) = schema.as_object_mut() {
+                        obj.insert("nullable".into(), json!(true));
+                    }
+                    return (schema, true);
+                }
+                // Handlers return `Result<T>`; only `T` reaches the caller
+                ("Result", [inner, ..]) => return rust_type(inner),
+                ("Box" | "Arc" | "Rc", [inner]) => return rust_type(inner),
+                ("Vec" | "VecDeque" | "HashSet" | "BTreeSet", [inner]) => {
+                    json!({ "type": "array", "items": rust_type(inner).0 })
+                }
+                ("HashMap" | "BTreeMap", [_, value]) => {
+                    json!({ "type": "object", "additionalProperties": rust_type(value).0 })
+                }
+                ("String" | "str" | "char", _) => json!({ "type": "string" }),
+                ("bool", _) => json!({ "type": "boolean" }),
+                ("f32", _) => json!({ "type": "number", "format": "float" }),
+                ("f64", _) => json!({ "type": "number", "format": "double" }),
+                ("u8" | "u16" | "u32" | "i8" | "i16" | "i32", _) => {
+                    json!({ "type": "integer", "format": "int32" })
+                }
+                ("u64" | "i64" | "usize" | "isize" | "u128" | "i128", _) => {
+                    json!({ "type": "integer", "format": "int64" })
+                }
+                (protein, []) => json!({ "$ref": format!("#/components/schemas/{}", protein) }),
+                _ => json!({}),
+            };
+            (schema, false)
+        }
+        _ => (json!({}), false),
+    }
+}
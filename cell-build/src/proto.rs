@@ -0,0 +1,511 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! `.proto` import for migrating gRPC services into the mesh.
+//!
+//! Messages become `#[protein]` structs, enums become unit-variant proteins
+//! and each `service` becomes a `#[service]` struct with a `#[handler]` impl
+//! whose methods are `todo!()` skeletons. Nested messages are flattened
+//! (`Outer.Inner` -> `OuterInner`); `oneof` groups become an optional enum
+//! protein. Field numbers are dropped: proteins are positional, so the
+//! declaration order is what matters on the wire.
+//!
+//! Streaming RPCs have no direct equivalent and are rejected.
+
+use anyhow::{bail, Context, Result};
+use convert_case::{Case, Casing};
+use std::collections::HashSet;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtoFile {
+    pub package: Option<String>,
+    pub messages: Vec<Message>,
+    pub enums: Vec<Enum>,
+    pub services: Vec<Service>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub name: String,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub ty: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enum {
+    pub name: String,
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub name: String,
+    /// Payload type for `oneof` variants
+    pub ty: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Service {
+    pub name: String,
+    pub rpcs: Vec<Rpc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rpc {
+    pub name: String,
+    pub input: String,
+    pub output: String,
+}
+
+/// Parse a `.proto` file into Rust-ready definitions.
+pub fn parse(src: &str) -> Result<ProtoFile> {
+    let mut parser = Parser::new(tokenize(src)?);
+    parser.file()?;
+    Ok(parser.file)
+}
+
+/// Translate a `.proto` file into cell source.
+pub fn import(src: &str) -> Result<String> {
+    Ok(render(&parse(src)?))
+}
+
+pub fn render(file: &ProtoFile) -> String {
+    let mut out = String::new();
+    match &file.package {
+        Some(package) => writeln!(out, "// Imported from protobuf package `{}`", package).unwrap(),
+        None => writeln!(out, "// Imported from protobuf").unwrap(),
+    }
+    writeln!(out, "\nuse anyhow::Result;\nuse cell_sdk::*;").unwrap();
+
+    for message in &file.messages {
+        writeln!(out, "\n#[protein]\npub struct {} {{", message.name).unwrap();
+        for field in &message.fields {
+            writeln!(out, "    pub {}: {},", field.name, field.ty).unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+    }
+
+    for e in &file.enums {
+        writeln!(out, "\n#[protein]\npub enum {} {{", e.name).unwrap();
+        for variant in &e.variants {
+            match &variant.ty {
+                Some(ty) => writeln!(out, "    {}({}),", variant.name, ty).unwrap(),
+                None => writeln!(out, "    {},", variant.name).unwrap(),
+            }
+        }
+        writeln!(out, "}}").unwrap();
+    }
+
+    for service in &file.services {
+        writeln!(out, "\n#[service]\npub struct {};", service.name).unwrap();
+        writeln!(out, "\n#[handler]\nimpl {} {{", service.name).unwrap();
+        for (i, rpc) in service.rpcs.iter().enumerate() {
+            if i > 0 {
+                writeln!(out).unwrap();
+            }
+            writeln!(
+                out,
+                "    pub async fn {}(&self, req: {}) -> Result<{}> {{\n        todo!()\n    }}",
+                rpc.name.to_case(Case::Snake),
+                rpc.input,
+                rpc.output
+            )
+            .unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+    }
+    out
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(String),
+    Str(String),
+    Symbol(char),
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = src.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '/' {
+            chars.next();
+            match chars.next() {
+                Some('/') => {
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                Some('*') => {
+                    let mut prev = ' ';
+                    for c in chars.by_ref() {
+                        if prev == '*' && c == '/' {
+                            break;
+                        }
+                        prev = c;
+                    }
+                }
+                _ => bail!("Unexpected '/'"),
+            }
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut s = String::new();
+            for d in chars.by_ref() {
+                if d == c {
+                    break;
+                }
+                s.push(d);
+            }
+            tokens.push(Token::Str(s));
+        } else if c.is_ascii_digit() || c == '-' {
+            let mut s = String::new();
+            while let Some(&d) = chars.peek() {
+                if !(d.is_ascii_alphanumeric() || d == '-' || d == '.') {
+                    break;
+                }
+                s.push(d);
+                chars.next();
+            }
+            tokens.push(Token::Number(s));
+        } else if c.is_alphabetic() || c == '_' || c == '.' {
+            let mut s = String::new();
+            while let Some(&d) = chars.peek() {
+                if !(d.is_alphanumeric() || d == '_' || d == '.') {
+                    break;
+                }
+                s.push(d);
+                chars.next();
+            }
+            tokens.push(Token::Ident(s));
+        } else {
+            tokens.push(Token::Symbol(c));
+            chars.next();
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    file: ProtoFile,
+    /// Flattened names of every message and enum, found up front so fields
+    /// may refer to types declared later
+    messages: HashSet<String>,
+    enums: HashSet<String>,
+}
+
+impl Parser {
+    fn new(tokens: Vec<Token>) -> Self {
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            file: ProtoFile {
+                package: None,
+                messages: Vec::new(),
+                enums: Vec::new(),
+                services: Vec::new(),
+            },
+            messages: HashSet::new(),
+            enums: HashSet::new(),
+        };
+        parser.declarations();
+        parser
+    }
+
+    fn declarations(&mut self) {
+        let mut scope: Vec<(String, usize)> = Vec::new();
+        let mut depth = 0;
+        for (i, token) in self.tokens.iter().enumerate() {
+            match token {
+                Token::Symbol('{') => depth += 1,
+                Token::Symbol('}') => {
+                    depth -= 1;
+                    if scope.last().is_some_and(|(_, d)| *d == depth) {
+                        scope.pop();
+                    }
+                }
+                Token::Ident(k) if k == "message" || k == "enum" => {
+                    let Some(Token::Ident(name)) = self.tokens.get(i + 1) else {
+                        continue;
+                    };
+                    let prefix: String = scope.iter().map(|(n, _)| n.as_str()).collect();
+                    let full = format!("{}{}", prefix, name);
+                    if k == "message" {
+                        self.messages.insert(full);
+                        scope.push((name.clone(), depth));
+                    } else {
+                        self.enums.insert(full);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .context("Unexpected end of file")?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        match self.next()? {
+            Token::Ident(s) => Ok(s),
+            other => bail!("Expected identifier, found {:?}", other),
+        }
+    }
+
+    fn symbol(&mut self, c: char) -> Result<()> {
+        match self.next()? {
+            Token::Symbol(s) if s == c => Ok(()),
+            other => bail!("Expected '{}', found {:?}", c, other),
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(c)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_ident(&mut self, word: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(s)) if s == word) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Skip to the end of the current statement, or past a `{ ... }` block.
+    fn skip_statement(&mut self) -> Result<()> {
+        let mut depth = 0;
+        loop {
+            match self.next()? {
+                Token::Symbol(';') if depth == 0 => return Ok(()),
+                Token::Symbol('{') => depth += 1,
+                Token::Symbol('}') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Map a protobuf type to Rust. Names resolve innermost scope first, like protoc.
+    fn resolve(&self, ty: &str, scope: &[String]) -> String {
+        if let Some(scalar) = scalar(ty) {
+            return scalar.to_string();
+        }
+        if ty == "google.protobuf.Empty" {
+            return "()".to_string();
+        }
+        // Drop the package: its segments are lowercase by convention
+        let parts: Vec<&str> = ty
+            .trim_start_matches('.')
+            .split('.')
+            .skip_while(|p| p.chars().next().is_some_and(|c| c.is_lowercase()))
+            .collect();
+        let name = parts.concat();
+        for i in (0..=scope.len()).rev() {
+            let candidate = format!("{}{}", scope[..i].concat(), name);
+            if self.messages.contains(&candidate) || self.enums.contains(&candidate) {
+                return candidate;
+            }
+        }
+        name
+    }
+
+    fn file(&mut self) -> Result<()> {
+        while self.peek().is_some() {
+            let keyword = self.ident()?;
+            match keyword.as_str() {
+                "package" => {
+                    self.file.package = Some(self.ident()?);
+                    self.symbol(';')?;
+                }
+                "message" => self.message(&[])?,
+                "enum" => self.enumeration(&[])?,
+                "service" => self.service()?,
+                // syntax, import, option
+                _ => self.skip_statement()?,
+            }
+        }
+        Ok(())
+    }
+
+    fn message(&mut self, outer: &[String]) -> Result<()> {
+        let mut scope = outer.to_vec();
+        scope.push(self.ident()?);
+        let name = scope.concat();
+        self.symbol('{')?;
+        let mut fields = Vec::new();
+        while !self.eat('}') {
+            let word = self.ident()?;
+            match word.as_str() {
+                "message" => self.message(&scope)?,
+                "enum" => self.enumeration(&scope)?,
+                "oneof" => {
+                    let group = self.ident()?;
+                    let ty = format!("{}{}", name, group.to_case(Case::Pascal));
+                    self.symbol('{')?;
+                    let mut variants = Vec::new();
+                    while !self.eat('}') {
+                        let field_ty = self.ident()?;
+                        let field = self.ident()?;
+                        self.skip_statement()?;
+                        variants.push(Variant {
+                            name: field.to_case(Case::Pascal),
+                            ty: Some(self.resolve(&field_ty, &scope)),
+                        });
+                    }
+                    self.file.enums.push(Enum {
+                        name: ty.clone(),
+                        variants,
+                    });
+                    fields.push(Field {
+                        name: group.to_case(Case::Snake),
+                        ty: format!("Option<{}>", ty),
+                    });
+                }
+                "option" | "reserved" | "extensions" => self.skip_statement()?,
+                _ => fields.push(self.field(word, &scope)?),
+            }
+        }
+        self.file.messages.push(Message { name, fields });
+        Ok(())
+    }
+
+    fn field(&mut self, first: String, scope: &[String]) -> Result<Field> {
+        let (label, ty) = match first.as_str() {
+            "repeated" | "optional" | "required" => (first.clone(), self.ident()?),
+            _ => (String::new(), first),
+        };
+
+        let ty = if ty == "map" {
+            self.symbol('<')?;
+            let key = self.ident()?;
+            let key = self.resolve(&key, scope);
+            self.symbol(',')?;
+            let value = self.ident()?;
+            let value = self.resolve(&value, scope);
+            self.symbol('>')?;
+            format!("std::collections::HashMap<{}, {}>", key, value)
+        } else {
+            let rust = self.resolve(&ty, scope);
+            match label.as_str() {
+                "repeated" => format!("Vec<{}>", rust),
+                "optional" => format!("Option<{}>", rust),
+                // proto3 singular message fields have presence
+                _ if self.messages.contains(&rust) => format!("Option<{}>", rust),
+                _ => rust,
+            }
+        };
+
+        let name = self.ident()?.to_case(Case::Snake);
+        self.skip_statement()?;
+        Ok(Field { name, ty })
+    }
+
+    fn enumeration(&mut self, outer: &[String]) -> Result<()> {
+        let raw = self.ident()?;
+        let name = format!("{}{}", outer.concat(), raw);
+        // STATUS_ACTIVE in `enum Status` becomes Active
+        let strip = format!("{}_", raw.to_case(Case::UpperSnake));
+        self.symbol('{')?;
+        let mut variants = Vec::new();
+        while !self.eat('}') {
+            let word = self.ident()?;
+            self.skip_statement()?;
+            if word == "option" || word == "reserved" {
+                continue;
+            }
+            let short = word.strip_prefix(&strip).unwrap_or(&word);
+            variants.push(Variant {
+                name: short.to_case(Case::Pascal),
+                ty: None,
+            });
+        }
+        self.file.enums.push(Enum { name, variants });
+        Ok(())
+    }
+
+    fn service(&mut self) -> Result<()> {
+        let name = self.ident()?;
+        self.symbol('{')?;
+        let mut rpcs = Vec::new();
+        while !self.eat('}') {
+            if !self.eat_ident("rpc") {
+                self.skip_statement()?;
+                continue;
+            }
+            let rpc = self.ident()?;
+            let input = self.rpc_type(&name, &rpc)?;
+            if !self.eat_ident("returns") {
+                bail!("{}.{}: expected `returns`", name, rpc);
+            }
+            let output = self.rpc_type(&name, &rpc)?;
+            // `;` or an options block
+            self.skip_statement()?;
+            rpcs.push(Rpc {
+                name: rpc,
+                input,
+                output,
+            });
+        }
+        self.file.services.push(Service { name, rpcs });
+        Ok(())
+    }
+
+    fn rpc_type(&mut self, service: &str, rpc: &str) -> Result<String> {
+        self.symbol('(')?;
+        if self.eat_ident("stream") {
+            bail!("{}.{}: streaming RPCs are not supported", service, rpc);
+        }
+        let ty = self.ident()?;
+        let ty = self.resolve(&ty, &[]);
+        self.symbol(')')?;
+        Ok(ty)
+    }
+}
+
+fn scalar(ty: &str) -> Option<&'static str> {
+    Some(match ty {
+        "double" => "f64",
+        "float" => "f32",
+        "int32" | "sint32" | "sfixed32" => "i32",
+        "int64" | "sint64" | "sfixed64" => "i64",
+        "uint32" | "fixed32" => "u32",
+        "uint64" | "fixed64" => "u64",
+        "bool" => "bool",
+        "string" => "String",
+        "bytes" => "Vec<u8>",
+        _ => return None,
+    })
+}
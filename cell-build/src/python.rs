@@ -0,0 +1,188 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Typed Python client for a cell, on top of the `cell_py` native module.
+//!
+//! Proteins become `TypedDict`s and type aliases in their serde shape, and the
+//! cell becomes a class with one `async` method per handler method. Calls go
+//! through `cell_py.Synapse`, which speaks the cell's native protocol; the
+//! cell must be bridged into the `cell_py` build (`CELL_PY_CELLS`).
+
+use crate::schema::{Schema, TypeDef};
+use convert_case::{Case, Casing};
+use std::fmt::Write;
+
+/// Render the Python module for `cell`.
+pub fn client(cell: &str, schema: &Schema) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "# Generated from the schema of `{}` ({:016x}). Do not edit.",
+        cell,
+        schema.fingerprint()
+    );
+    out.push_str(
+        "from __future__ import annotations\n\n\
+         from typing import Any, Dict, List, Literal, Optional, Tuple, TypedDict, Union\n\n\
+         import cell_py\n",
+    );
+
+    for (name, def) in &schema.types {
+        out.push_str("\n\n");
+        match def {
+            TypeDef::Struct(fields) if is_tuple(fields) => {
+                let _ = writeln!(out, "{} = {}", name, tuple(fields));
+            }
+            TypeDef::Struct(fields) if fields.is_empty() => {
+                let _ = writeln!(out, "class {}(TypedDict):\n    pass", name);
+            }
+            TypeDef::Struct(fields) => {
+                let _ = writeln!(out, "class {}(TypedDict):", name);
+                for (field, ty) in fields {
+                    let _ = writeln!(out, "    {}: {}", field, py_type(ty, false));
+                }
+            }
+            TypeDef::Enum(variants) => {
+                let mut arms = Vec::new();
+                for (variant, fields) in variants {
+                    if fields.is_empty() {
+                        arms.push(format!("Literal[{:?}]", variant));
+                        continue;
+                    }
+                    // Externally tagged: `{"Variant": <fields>}`
+                    let inner = if is_tuple(fields) {
+                        tuple(fields)
+                    } else {
+                        let props: Vec<String> = fields
+                            .iter()
+                            .map(|(f, ty)| format!("{:?}: {}", f, py_type(ty, true)))
+                            .collect();
+                        format!(
+                            "TypedDict({:?}, {{{}}})",
+                            format!("{}{}Fields", name, variant),
+                            props.join(", ")
+                        )
+                    };
+                    let arm = format!("{}{}", name, variant);
+                    let _ = writeln!(
+                        out,
+                        "{} = TypedDict({:?}, {{{:?}: {}}})",
+                        arm, arm, variant, inner
+                    );
+                    arms.push(format!("{:?}", arm));
+                }
+                let _ = writeln!(out, "{} = Union[{}]", name, arms.join(", "));
+            }
+        }
+    }
+
+    let class = cell.to_case(Case::Pascal);
+    let _ = write!(
+        out,
+        "\n\nclass {class}:\n    \"\"\"Client for the `{cell}` cell.\"\"\"\n\n    \
+         def __init__(self, synapse: cell_py.Synapse) -> None:\n        \
+         self._synapse = synapse\n\n    \
+         @classmethod\n    \
+         async def connect(cls) -> {class}:\n        \
+         return cls(await cell_py.Synapse.connect({cell:?}))\n",
+    );
+    let mut methods: Vec<_> = schema.methods.iter().collect();
+    methods.sort_by_key(|(_, m)| m.index);
+    for (name, method) in methods {
+        let params: String = method
+            .args
+            .iter()
+            .map(|(arg, ty)| format!(", {}: {}", arg, py_type(ty, false)))
+            .collect();
+        let args: Vec<String> = method
+            .args
+            .iter()
+            .map(|(arg, _)| format!("{:?}: {}", arg, arg))
+            .collect();
+        let _ = write!(
+            out,
+            "\n    async def {name}(self{params}) -> {ret}:\n        \
+             return await self._synapse.call({name:?}, {{{args}}})\n",
+            ret = py_type(&method.ret, false),
+            args = args.join(", "),
+        );
+    }
+    out
+}
+
+/// Tuple structs and variants name their fields by index
+fn is_tuple(fields: &[(String, String)]) -> bool {
+    fields.first().is_some_and(|(name, _)| name == "0")
+}
+
+/// Serde writes one-field tuples as the field itself
+fn tuple(fields: &[(String, String)]) -> String {
+    let types: Vec<String> = fields.iter().map(|(_, ty)| py_type(ty, true)).collect();
+    match types.as_slice() {
+        [single] => single.clone(),
+        _ => format!("Tuple[{}]", types.join(", ")),
+    }
+}
+
+/// Python annotation of a Rust type string. Outside annotations (`quoted`)
+/// proteins are forward references, since they may be defined further down.
+fn py_type(ty: &str, quoted: bool) -> String {
+    match syn::parse_str::<syn::Type>(ty) {
+        Ok(parsed) => rust_type(&parsed, quoted),
+        Err(_) => "Any".to_string(),
+    }
+}
+
+fn rust_type(ty: &syn::Type, quoted: bool) -> String {
+    match ty {
+        syn::Type::Tuple(t) if t.elems.is_empty() => "None".to_string(),
+        syn::Type::Tuple(t) => {
+            let elems: Vec<String> = t.elems.iter().map(|e| rust_type(e, quoted)).collect();
+            format!("Tuple[{}]", elems.join(", "))
+        }
+        syn::Type::Reference(r) => rust_type(&r.elem, quoted),
+        syn::Type::Array(a) => format!("List[{}]", rust_type(&a.elem, quoted)),
+        syn::Type::Slice(s) => format!("List[{}]", rust_type(&s.elem, quoted)),
+        syn::Type::Path(p) => {
+            let Some(last) = p.path.segments.last() else {
+                return "Any".to_string();
+            };
+            let args: Vec<&syn::Type> = match &last.arguments {
+                syn::PathArguments::AngleBracketed(a) => a
+                    .args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        syn::GenericArgument::Type(t) => Some(t),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            let name = last.ident.to_string();
+            match (name.as_str(), args.as_slice()) {
+                ("Option", [inner]) => format!("Optional[{}]", rust_type(inner, quoted)),
+                // Handlers return `Result<T>`; only `T` reaches the caller
+                ("Result", [inner, ..]) => rust_type(inner, quoted),
+                ("Box" | "Arc" | "Rc", [inner]) => rust_type(inner, quoted),
+                ("Vec" | "VecDeque" | "HashSet" | "BTreeSet", [inner]) => {
+                    format!("List[{}]", rust_type(inner, quoted))
+                }
+                ("HashMap" | "BTreeMap", [_, value]) => {
+                    format!("Dict[str, {}]", rust_type(value, quoted))
+                }
+                ("String" | "str" | "char", _) => "str".to_string(),
+                ("bool", _) => "bool".to_string(),
+                ("f32" | "f64", _) => "float".to_string(),
+                (
+                    "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64"
+                    | "i128" | "isize",
+                    _,
+                ) => "int".to_string(),
+                (protein, []) if quoted => format!("{:?}", protein),
+                (protein, []) => protein.to_string(),
+                _ => "Any".to_string(),
+            }
+        }
+        _ => "Any".to_string(),
+    }
+}
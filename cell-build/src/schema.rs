@@ -0,0 +1,609 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Structural view of a cell's public surface, for compatibility checks.
+//!
+//! [`Schema`] records handler signatures and `#[protein]` types by name, with
+//! types kept as normalized token strings. [`Schema::diff`] lists which
+//! method, field or variant differs and whether the change breaks peers built
+//! against the other side. Consumers pin the schema they were built against in
+//! a lockfile at `.cell/schema/<cell>.lock.json`; the registry holds the
+//! published one. Methods may also advertise a [`RetryPolicy`] that generated
+//! clients enforce.
+//!
+//! A cell's build script can also emit a [`SchemaArtifact`] (`<cell>.cell-schema`,
+//! see [`crate::CellBuilder::emit_schema`]): its public surface compiled out of
+//! the flattened source, which `cell_remote!` generates clients from when the
+//! cell's source tree is not around.
+
+use anyhow::{bail, Context, Result};
+use quote::ToTokens;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    pub methods: BTreeMap<String, Method>,
+    pub types: BTreeMap<String, TypeDef>,
+    /// Type parameters of generic proteins, e.g. `Page` -> `["T"]`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub generics: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Method {
+    /// Declaration order, which is the method's discriminant on the wire
+    pub index: u32,
+    pub args: Vec<(String, String)>,
+    pub ret: String,
+    /// Retry policy advertised with `#[handler(retry(...))]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+}
+
+/// How generated clients retry a method. Calls the cell rejected before the
+/// handler ran are always safe to repeat; anything else only when the method
+/// is idempotent.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub idempotent: bool,
+    /// Total attempts, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on each further one
+    pub backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            idempotent: false,
+            max_attempts: 3,
+            backoff_ms: 100,
+        }
+    }
+}
+
+impl fmt::Display for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} attempts, {}ms backoff",
+            self.max_attempts, self.backoff_ms
+        )?;
+        if self.idempotent {
+            write!(f, ", idempotent")?;
+        }
+        Ok(())
+    }
+}
+
+impl RetryPolicy {
+    /// Parse the body of `retry(max_attempts = 3, backoff_ms = 50, idempotent)`.
+    pub fn parse(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Self> {
+        let mut policy = Self::default();
+        meta.parse_nested_meta(|inner| {
+            if inner.path.is_ident("idempotent") {
+                policy.idempotent = true;
+            } else if inner.path.is_ident("max_attempts") {
+                let n: syn::LitInt = inner.value()?.parse()?;
+                policy.max_attempts = n.base10_parse()?;
+                if policy.max_attempts == 0 {
+                    return Err(inner.error("max_attempts must be at least 1"));
+                }
+            } else if inner.path.is_ident("backoff_ms") {
+                let ms: syn::LitInt = inner.value()?.parse()?;
+                policy.backoff_ms = ms.base10_parse()?;
+            } else {
+                return Err(inner.error("unsupported retry attribute"));
+            }
+            Ok(())
+        })?;
+        Ok(policy)
+    }
+
+    /// The policy declared on a handler method, if any.
+    pub fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Option<Self>> {
+        let mut found = None;
+        for attr in attrs.iter().filter(|a| a.path().is_ident("handler")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("retry") {
+                    found = Some(Self::parse(&meta)?);
+                } else if meta.input.peek(syn::Token![=]) {
+                    // Other keys (fallback = "...", priority = 90)
+                    meta.value()?.parse::<syn::Lit>()?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(found)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum TypeDef {
+    /// Fields in declaration order; tuple fields are named by index
+    Struct(Vec<(String, String)>),
+    /// Variants in declaration order, each with its fields
+    Enum(Vec<(String, Vec<(String, String)>)>),
+}
+
+/// One difference between two schemas, read as "base -> self".
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    /// Whether peers built against the base can no longer talk to self
+    pub breaking: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Renamed { from: String },
+    Changed { base: String, now: String },
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ChangeKind::Added => write!(f, "+ {}", self.path)?,
+            ChangeKind::Removed => write!(f, "- {}", self.path)?,
+            ChangeKind::Renamed { from } => write!(f, "> {} (was `{}`)", self.path, from)?,
+            ChangeKind::Changed { base, now } => {
+                write!(f, "~ {}: `{}` -> `{}`", self.path, base, now)?
+            }
+        }
+        if self.breaking {
+            write!(f, " [breaking]")?;
+        }
+        Ok(())
+    }
+}
+
+impl Schema {
+    /// Collect proteins and `#[handler]` methods from a (flattened) cell source,
+    /// including its inline modules. Methods of every `#[handler]` block are
+    /// numbered in source order.
+    pub fn from_file(file: &syn::File) -> Self {
+        let mut schema = Schema::default();
+        schema.collect(&file.items);
+        schema
+    }
+
+    fn collect(&mut self, items: &[syn::Item]) {
+        let has_attr =
+            |attrs: &[syn::Attribute], name: &str| attrs.iter().any(|a| a.path().is_ident(name));
+
+        for item in items {
+            match item {
+                syn::Item::Struct(s) if has_attr(&s.attrs, "protein") => {
+                    self.types
+                        .insert(s.ident.to_string(), TypeDef::Struct(fields(&s.fields)));
+                    self.record_generics(&s.ident, &s.generics);
+                }
+                syn::Item::Enum(e) if has_attr(&e.attrs, "protein") => {
+                    let variants = e
+                        .variants
+                        .iter()
+                        .map(|v| (v.ident.to_string(), fields(&v.fields)))
+                        .collect();
+                    self.types
+                        .insert(e.ident.to_string(), TypeDef::Enum(variants));
+                    self.record_generics(&e.ident, &e.generics);
+                }
+                syn::Item::Impl(i) if has_attr(&i.attrs, "handler") => {
+                    for impl_item in &i.items {
+                        let syn::ImplItem::Fn(f) = impl_item else {
+                            continue;
+                        };
+                        // Fed by the cytokine broker, not part of the protocol
+                        if has_attr(&f.attrs, "subscriber") {
+                            continue;
+                        }
+                        let index = self.methods.len() as u32;
+                        let args = f
+                            .sig
+                            .inputs
+                            .iter()
+                            .filter_map(|arg| match arg {
+                                syn::FnArg::Typed(t) if !is_call_context(&t.ty) => {
+                                    Some((tokens(&t.pat), tokens(&t.ty)))
+                                }
+                                _ => None,
+                            })
+                            .collect();
+                        let ret = match &f.sig.output {
+                            syn::ReturnType::Default => "()".to_string(),
+                            syn::ReturnType::Type(_, ty) => tokens(ty),
+                        };
+                        let retry = RetryPolicy::from_attrs(&f.attrs).unwrap_or_default();
+                        let method = Method {
+                            index,
+                            args,
+                            ret,
+                            retry,
+                        };
+                        self.methods.insert(f.sig.ident.to_string(), method);
+                    }
+                }
+                syn::Item::Mod(m) => {
+                    if let Some((_, content)) = &m.content {
+                        self.collect(content);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn record_generics(&mut self, ident: &syn::Ident, generics: &syn::Generics) {
+        let params: Vec<String> = generics
+            .type_params()
+            .map(|p| p.ident.to_string())
+            .collect();
+        if !params.is_empty() {
+            self.generics.insert(ident.to_string(), params);
+        }
+    }
+
+    pub fn from_source(src: &str) -> Result<Self> {
+        Ok(Self::from_file(&syn::parse_file(src)?))
+    }
+
+    pub fn fingerprint(&self) -> u64 {
+        let canonical = serde_json::to_vec(self).expect("schema serializes");
+        let hash = blake3::hash(&canonical);
+        u64::from_le_bytes(hash.as_bytes()[0..8].try_into().unwrap())
+    }
+
+    /// Field-level differences from `base` to `self`. Encoding is positional,
+    /// so renames are harmless while reordering, retyping or removing is not.
+    pub fn diff(&self, base: &Schema) -> Vec<SchemaChange> {
+        let mut out = Vec::new();
+
+        let mut removed: Vec<(&String, &Method)> = Vec::new();
+        for (name, old) in &base.methods {
+            let path = format!("method `{}`", name);
+            let Some(new) = self.methods.get(name) else {
+                removed.push((name, old));
+                continue;
+            };
+            if new.index != old.index {
+                out.push(changed(
+                    format!("{} position", path),
+                    old.index,
+                    new.index,
+                    true,
+                ));
+            }
+            compare_fields(&format!("{} arg", path), &old.args, &new.args, &mut out);
+            if new.ret != old.ret {
+                out.push(changed(
+                    format!("{} return", path),
+                    &old.ret,
+                    &new.ret,
+                    true,
+                ));
+            }
+            // Clients enforce the policy they were built with
+            if new.retry != old.retry {
+                let show =
+                    |p: &Option<RetryPolicy>| p.map_or("none".to_string(), |p| p.to_string());
+                out.push(changed(
+                    format!("{} retry", path),
+                    show(&old.retry),
+                    show(&new.retry),
+                    false,
+                ));
+            }
+        }
+        for (name, new) in self
+            .methods
+            .iter()
+            .filter(|(n, _)| !base.methods.contains_key(*n))
+        {
+            let path = format!("method `{}`", name);
+            // Same slot and signature under a new name
+            if let Some(i) = removed.iter().position(|(_, old)| {
+                old.index == new.index && old.args == new.args && old.ret == new.ret
+            }) {
+                let (from, _) = removed.remove(i);
+                out.push(change(
+                    path,
+                    ChangeKind::Renamed { from: from.clone() },
+                    false,
+                ));
+            } else {
+                // Appending keeps existing discriminants
+                let breaking = (new.index as usize) < base.methods.len();
+                out.push(change(path, ChangeKind::Added, breaking));
+            }
+        }
+        for (name, _) in removed {
+            out.push(change(
+                format!("method `{}`", name),
+                ChangeKind::Removed,
+                true,
+            ));
+        }
+
+        for (name, old) in &base.types {
+            let path = format!("type `{}`", name);
+            match (old, self.types.get(name)) {
+                (_, None) => out.push(change(path, ChangeKind::Removed, true)),
+                (TypeDef::Struct(old), Some(TypeDef::Struct(new))) => {
+                    compare_fields(&format!("{} field", path), old, new, &mut out)
+                }
+                (TypeDef::Enum(old), Some(TypeDef::Enum(new))) => {
+                    compare_variants(&path, old, new, &mut out)
+                }
+                (old, Some(new)) => out.push(changed(path, kind(old), kind(new), true)),
+            }
+        }
+        for name in self.types.keys().filter(|n| !base.types.contains_key(*n)) {
+            out.push(change(format!("type `{}`", name), ChangeKind::Added, false));
+        }
+        // Field types already name the parameters; their number is what's new
+        for name in base.types.keys().filter(|n| self.types.contains_key(*n)) {
+            let (old, new) = (base.params(name), self.params(name));
+            if old.len() != new.len() {
+                out.push(changed(
+                    format!("type `{}` parameters", name),
+                    format!("<{}>", old.join(", ")),
+                    format!("<{}>", new.join(", ")),
+                    true,
+                ));
+            }
+        }
+
+        out
+    }
+
+    fn params(&self, name: &str) -> &[String] {
+        self.generics.get(name).map_or(&[], |p| p.as_slice())
+    }
+
+    pub fn is_breaking(changes: &[SchemaChange]) -> bool {
+        changes.iter().any(|c| c.breaking)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read(path).with_context(|| format!("Failed to read schema {:?}", path))?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("Failed to write schema {:?}", path))
+    }
+}
+
+/// Public surface of a cell as emitted at build time.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SchemaArtifact {
+    pub cell: String,
+    /// Fingerprint of [`Self::schema`], checked on load
+    pub fingerprint: u64,
+    /// Proteins and handler signatures as Rust source, see
+    /// [`crate::spore::extract_schema`]
+    pub surface: String,
+}
+
+impl SchemaArtifact {
+    /// Compile the artifact from a cell's flattened source.
+    pub fn new(cell: &str, file: &syn::File) -> Result<Self> {
+        let surface = crate::spore::extract_schema(file);
+        let fingerprint = Schema::from_source(&surface)?.fingerprint();
+        Ok(Self {
+            cell: cell.to_string(),
+            fingerprint,
+            surface,
+        })
+    }
+
+    pub fn schema(&self) -> Result<Schema> {
+        Schema::from_source(&self.surface)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read(path).with_context(|| format!("Failed to read artifact {:?}", path))?;
+        let artifact: Self = serde_json::from_slice(&json)?;
+        if artifact.schema()?.fingerprint() != artifact.fingerprint {
+            bail!("Schema artifact {:?} does not match its fingerprint", path);
+        }
+        Ok(artifact)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("Failed to write artifact {:?}", path))
+    }
+}
+
+/// `<dir>/<cell>.cell-schema`
+pub fn artifact_path(dir: &Path, cell: &str) -> PathBuf {
+    dir.join(format!("{}.cell-schema", cell))
+}
+
+/// Where cell builds emit their artifacts: `~/.cell/schema`.
+pub fn artifact_dir() -> Option<PathBuf> {
+    Some(dirs::home_dir()?.join(".cell/schema"))
+}
+
+/// The artifact of `cell` a consumer crate sees: one vendored next to its
+/// lockfiles, else the one the cell's last build emitted.
+pub fn find_artifact(crate_dir: &Path, cell: &str) -> Option<PathBuf> {
+    let vendored = artifact_path(&crate_dir.join(".cell").join("schema"), cell);
+    let emitted = artifact_dir().map(|dir| artifact_path(&dir, cell));
+    std::iter::once(vendored)
+        .chain(emitted)
+        .find(|p| p.exists())
+}
+
+/// Where a consumer crate pins the schema of `cell`.
+pub fn lock_path(crate_dir: &Path, cell: &str) -> PathBuf {
+    crate_dir
+        .join(".cell")
+        .join("schema")
+        .join(format!("{}.lock.json", cell))
+}
+
+/// The schema a consumer should build against: its lockfile if it has one,
+/// else the cell's schema artifact, else the registry copy of the cell.
+/// Returns where it was found.
+pub fn published(crate_dir: &Path, cell: &str) -> Result<Option<(Schema, PathBuf)>> {
+    let lock = lock_path(crate_dir, cell);
+    if lock.exists() {
+        return Ok(Some((Schema::load(&lock)?, lock)));
+    }
+
+    if let Some(path) = find_artifact(crate_dir, cell) {
+        return Ok(Some((SchemaArtifact::load(&path)?.schema()?, path)));
+    }
+
+    match registry_source(cell) {
+        Some(path) => {
+            let file = crate::load_and_flatten_source(&path)?;
+            let surface = crate::spore::extract_schema(&file);
+            Ok(Some((Schema::from_source(&surface)?, path)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// The registry copy of a cell's public source, if it is installed.
+pub fn registry_source(cell: &str) -> Option<PathBuf> {
+    let registry = dirs::home_dir()?.join(".cell/registry").join(cell);
+    // `schema.rs` comes from an unpacked spore, `src/main.rs` from a source install
+    [registry.join("schema.rs"), registry.join("src/main.rs")]
+        .into_iter()
+        .find(|p| p.exists())
+}
+
+/// Whether a handler argument is the `&CallContext` the Membrane passes in,
+/// which callers never send
+pub fn is_call_context(ty: &syn::Type) -> bool {
+    let syn::Type::Reference(r) = ty else {
+        return false;
+    };
+    matches!(&*r.elem, syn::Type::Path(p)
+        if p.path.segments.last().is_some_and(|s| s.ident == "CallContext"))
+}
+
+fn tokens(t: &impl ToTokens) -> String {
+    t.to_token_stream().to_string().replace(' ', "")
+}
+
+fn fields(fields: &syn::Fields) -> Vec<(String, String)> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            let name = f
+                .ident
+                .as_ref()
+                .map_or_else(|| i.to_string(), |id| id.to_string());
+            (name, tokens(&f.ty))
+        })
+        .collect()
+}
+
+fn kind(t: &TypeDef) -> &'static str {
+    match t {
+        TypeDef::Struct(_) => "struct",
+        TypeDef::Enum(_) => "enum",
+    }
+}
+
+fn change(path: String, kind: ChangeKind, breaking: bool) -> SchemaChange {
+    SchemaChange {
+        path,
+        kind,
+        breaking,
+    }
+}
+
+fn changed(path: String, base: impl ToString, now: impl ToString, breaking: bool) -> SchemaChange {
+    let kind = ChangeKind::Changed {
+        base: base.to_string(),
+        now: now.to_string(),
+    };
+    change(path, kind, breaking)
+}
+
+/// Fields are positional on the wire: a new name in the same slot is a rename,
+/// anything that changes the layout breaks.
+fn compare_fields(
+    path: &str,
+    base: &[(String, String)],
+    now: &[(String, String)],
+    out: &mut Vec<SchemaChange>,
+) {
+    for i in 0..base.len().max(now.len()) {
+        match (base.get(i), now.get(i)) {
+            (Some((bn, bt)), Some((nn, nt))) if bt != nt => {
+                out.push(changed(format!("{} `{}`", path, nn), bt, nt, true));
+                if bn != nn {
+                    let kind = ChangeKind::Renamed { from: bn.clone() };
+                    out.push(change(format!("{} `{}`", path, nn), kind, false));
+                }
+            }
+            (Some((bn, _)), Some((nn, _))) if bn != nn => {
+                let kind = ChangeKind::Renamed { from: bn.clone() };
+                out.push(change(format!("{} `{}`", path, nn), kind, false));
+            }
+            (Some((bn, bt)), None) => out.push(change(
+                format!("{} `{}: {}`", path, bn, bt),
+                ChangeKind::Removed,
+                true,
+            )),
+            (None, Some((nn, nt))) => out.push(change(
+                format!("{} `{}: {}`", path, nn, nt),
+                ChangeKind::Added,
+                true,
+            )),
+            _ => {}
+        }
+    }
+}
+
+/// Variants are positional too, but appending one leaves existing tags alone.
+fn compare_variants(
+    path: &str,
+    base: &[(String, Vec<(String, String)>)],
+    now: &[(String, Vec<(String, String)>)],
+    out: &mut Vec<SchemaChange>,
+) {
+    for i in 0..base.len().max(now.len()) {
+        match (base.get(i), now.get(i)) {
+            (Some((bn, bf)), Some((nn, nf))) => {
+                if bn != nn {
+                    let kind = ChangeKind::Renamed { from: bn.clone() };
+                    out.push(change(format!("{} variant `{}`", path, nn), kind, false));
+                }
+                compare_fields(&format!("{}::{} field", path, nn), bf, nf, out);
+            }
+            (Some((bn, _)), None) => out.push(change(
+                format!("{} variant `{}`", path, bn),
+                ChangeKind::Removed,
+                true,
+            )),
+            (None, Some((nn, _))) => out.push(change(
+                format!("{} variant `{}`", path, nn),
+                ChangeKind::Added,
+                false,
+            )),
+            (None, None) => {}
+        }
+    }
+}
@@ -0,0 +1,293 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! `.spore` - single-file deployable cell bundle.
+//!
+//! A spore carries everything a node needs to run a cell without its Cargo
+//! project: the release binary, the `Cell.toml` manifest, the public schema
+//! (proteins and handler signatures) and an optional default config.
+//!
+//! Layout:
+//! ```text
+//! b"SPORE\0" | u8 version | u32 header_len | header (JSON) | sections... | 64-byte hex tag
+//! ```
+//! Sections follow in header order: binary, Cell.toml, schema, config. The tag is
+//! the keyed BLAKE3 hash of every preceding byte, using the mesh artifact key.
+
+use crate::artifact::{build_release, host_triple, source_hash, ArtifactKey, SigningKey};
+use crate::load_and_flatten_source;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 6] = b"SPORE\0";
+const FORMAT_VERSION: u8 = 1;
+const TAG_LEN: usize = 64;
+
+/// Metadata header of a spore.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SporeManifest {
+    pub name: String,
+    pub version: String,
+    pub triple: String,
+    pub source_hash: String,
+    pub created_at: u64,
+    pub binary_digest: String,
+    pub binary_len: u64,
+    pub manifest_len: u64,
+    pub schema_len: u64,
+    pub config_len: Option<u64>,
+}
+
+/// A decoded spore bundle.
+#[derive(Debug, Clone)]
+pub struct Spore {
+    pub manifest: SporeManifest,
+    pub binary: Vec<u8>,
+    pub cell_toml: String,
+    pub schema: String,
+    pub config: Option<String>,
+}
+
+impl Spore {
+    /// Build the cell at `cell_dir` in release mode and bundle it.
+    pub fn pack(cell_dir: &Path) -> Result<Self> {
+        let cargo_toml = fs::read_to_string(cell_dir.join("Cargo.toml"))
+            .with_context(|| format!("No Cargo.toml in {:?}", cell_dir))?;
+        let cargo: toml::Value = toml::from_str(&cargo_toml)?;
+        let package = cargo
+            .get("package")
+            .context("Cargo.toml has no [package]")?;
+        let name = package
+            .get("name")
+            .and_then(|v| v.as_str())
+            .context("Package has no name")?;
+        let version = package
+            .get("version")
+            .and_then(|v| v.as_str())
+            .unwrap_or("0.0.0");
+
+        let cell_toml = fs::read_to_string(cell_dir.join("Cell.toml")).unwrap_or_default();
+        let schema = extract_schema(&load_and_flatten_source(&cell_dir.join("src/main.rs"))?);
+        let config = ["config/default.toml", "config.toml"]
+            .iter()
+            .find_map(|p| fs::read_to_string(cell_dir.join(p)).ok());
+
+        let binary_path = build_release(name, cell_dir)?;
+        let binary = fs::read(&binary_path)
+            .with_context(|| format!("Failed to read built binary {:?}", binary_path))?;
+
+        Ok(Self::assemble(
+            name,
+            version,
+            &source_hash(cell_dir)?,
+            binary,
+            cell_toml,
+            schema,
+            config,
+        ))
+    }
+
+    /// Bundle already-prepared parts for the host triple.
+    pub fn assemble(
+        name: &str,
+        version: &str,
+        source_hash: &str,
+        binary: Vec<u8>,
+        cell_toml: String,
+        schema: String,
+        config: Option<String>,
+    ) -> Self {
+        let manifest = SporeManifest {
+            name: name.to_string(),
+            version: version.to_string(),
+            triple: host_triple(),
+            source_hash: source_hash.to_string(),
+            created_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            binary_digest: blake3::hash(&binary).to_hex().to_string(),
+            binary_len: binary.len() as u64,
+            manifest_len: cell_toml.len() as u64,
+            schema_len: schema.len() as u64,
+            config_len: config.as_ref().map(|c| c.len() as u64),
+        };
+        Self {
+            manifest,
+            binary,
+            cell_toml,
+            schema,
+            config,
+        }
+    }
+
+    /// Key under which the contained binary is stored in an `ArtifactStore`.
+    pub fn artifact_key(&self) -> ArtifactKey {
+        ArtifactKey::new(
+            &self.manifest.name,
+            &self.manifest.triple,
+            &self.manifest.source_hash,
+        )
+    }
+
+    pub fn to_bytes(&self, signer: &SigningKey) -> Result<Vec<u8>> {
+        let header = serde_json::to_vec(&self.manifest)?;
+        let mut out = Vec::with_capacity(self.binary.len() + header.len() + 1024);
+        out.extend_from_slice(MAGIC);
+        out.push(FORMAT_VERSION);
+        out.extend_from_slice(&(header.len() as u32).to_le_bytes());
+        out.extend_from_slice(&header);
+        out.extend_from_slice(&self.binary);
+        out.extend_from_slice(self.cell_toml.as_bytes());
+        out.extend_from_slice(self.schema.as_bytes());
+        if let Some(config) = &self.config {
+            out.extend_from_slice(config.as_bytes());
+        }
+        let tag = signer.sign_bytes(&out);
+        out.extend_from_slice(tag.as_bytes());
+        Ok(out)
+    }
+
+    /// Decode a spore, rejecting it unless the tag verifies under `signer`.
+    pub fn from_bytes(bytes: &[u8], signer: &SigningKey) -> Result<Self> {
+        let prefix = MAGIC.len() + 1 + 4;
+        if bytes.len() < prefix + TAG_LEN || &bytes[..MAGIC.len()] != MAGIC {
+            bail!("Not a spore bundle");
+        }
+        if bytes[MAGIC.len()] != FORMAT_VERSION {
+            bail!("Unsupported spore format version {}", bytes[MAGIC.len()]);
+        }
+
+        let (body, tag) = bytes.split_at(bytes.len() - TAG_LEN);
+        let tag = std::str::from_utf8(tag).context("Malformed spore signature")?;
+        if !signer.verify_bytes(body, tag) {
+            bail!("Spore signature mismatch");
+        }
+
+        let header_len =
+            u32::from_le_bytes(body[MAGIC.len() + 1..prefix].try_into().unwrap()) as usize;
+        let mut cursor = Cursor {
+            buf: body,
+            pos: prefix,
+        };
+        let manifest: SporeManifest = serde_json::from_slice(cursor.take(header_len)?)?;
+
+        let binary = cursor.take(manifest.binary_len as usize)?.to_vec();
+        if blake3::hash(&binary).to_hex().as_str() != manifest.binary_digest {
+            bail!("Spore binary digest mismatch");
+        }
+        let cell_toml = cursor.take_string(manifest.manifest_len as usize)?;
+        let schema = cursor.take_string(manifest.schema_len as usize)?;
+        let config = match manifest.config_len {
+            Some(len) => Some(cursor.take_string(len as usize)?),
+            None => None,
+        };
+        if cursor.pos != body.len() {
+            bail!("Trailing bytes in spore");
+        }
+
+        Ok(Self {
+            manifest,
+            binary,
+            cell_toml,
+            schema,
+            config,
+        })
+    }
+
+    pub fn write(&self, path: &Path, signer: &SigningKey) -> Result<()> {
+        fs::write(path, self.to_bytes(signer)?)
+            .with_context(|| format!("Failed to write spore {:?}", path))
+    }
+
+    pub fn open(path: &Path, signer: &SigningKey) -> Result<Self> {
+        let bytes = fs::read(path).with_context(|| format!("Failed to read spore {:?}", path))?;
+        Self::from_bytes(&bytes, signer)
+    }
+
+    /// Lay the spore out as a runnable directory and return the binary path.
+    pub fn unpack(&self, dest: &Path) -> Result<PathBuf> {
+        fs::create_dir_all(dest)?;
+        let bin = dest.join(&self.manifest.name);
+        fs::write(&bin, &self.binary)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&bin, fs::Permissions::from_mode(0o755))?;
+        }
+        fs::write(dest.join("Cell.toml"), &self.cell_toml)?;
+        fs::write(dest.join("schema.rs"), &self.schema)?;
+        if let Some(config) = &self.config {
+            fs::write(dest.join("config.toml"), config)?;
+        }
+        fs::write(
+            dest.join("spore.json"),
+            serde_json::to_vec_pretty(&self.manifest)?,
+        )?;
+        Ok(bin)
+    }
+}
+
+struct Cursor<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.buf.len())
+            .context("Truncated spore")?;
+        let slice = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn take_string(&mut self, len: usize) -> Result<String> {
+        Ok(String::from_utf8(self.take(len)?.to_vec())?)
+    }
+}
+
+/// Reduce a cell's source to its public surface: `#[protein]` types and
+/// `#[handler]` impls with bodies stripped. Items in (flattened) submodules
+/// are hoisted to the top level.
+pub fn extract_schema(file: &syn::File) -> String {
+    let mut items = Vec::new();
+    collect_surface(&file.items, &mut items);
+    prettyplease::unparse(&syn::File {
+        shebang: None,
+        attrs: vec![],
+        items,
+    })
+}
+
+fn collect_surface(from: &[syn::Item], items: &mut Vec<syn::Item>) {
+    let has_attr =
+        |attrs: &[syn::Attribute], name: &str| attrs.iter().any(|a| a.path().is_ident(name));
+
+    for item in from {
+        match item {
+            syn::Item::Struct(s) if has_attr(&s.attrs, "protein") => items.push(item.clone()),
+            syn::Item::Enum(e) if has_attr(&e.attrs, "protein") => items.push(item.clone()),
+            syn::Item::Impl(i) if has_attr(&i.attrs, "handler") => {
+                let mut i = i.clone();
+                for impl_item in &mut i.items {
+                    if let syn::ImplItem::Fn(f) = impl_item {
+                        f.block = syn::parse_quote!({ unimplemented!() });
+                    }
+                }
+                items.push(syn::Item::Impl(i));
+            }
+            syn::Item::Mod(m) => {
+                if let Some((_, content)) = &m.content {
+                    collect_surface(content, items);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,315 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! TypeScript clients for a cell.
+//!
+//! [`react_hooks`] goes through the HTTP gateway, with the same mapping as
+//! [`crate::openapi`]: each method is `POST /{cell}/{method}` with one JSON
+//! property per argument. For every method the module exports a
+//! promise-returning function (`ledgerDeposit(args)`) and a hook
+//! (`useLedgerDeposit()`) tracking `data`, `error` and `loading`; failures are
+//! thrown as `CellError` carrying the cell's `ErrorResponse`.
+//!
+//! [`node_client`] wraps the `cell-node` addon, which calls the cell over its
+//! native protocol: a `Ledger` class with one method per handler method and a
+//! typed `subscribe`. Both take the same arguments object.
+//!
+//! In both, proteins become interfaces and union types in their serde shape.
+
+use crate::schema::{Schema, TypeDef};
+use convert_case::{Case, Casing};
+use std::fmt::Write;
+
+const RUNTIME: &str = r#"import { useCallback, useState } from "react";
+
+/** Failure reported by the cell (its `ErrorResponse`) */
+export class CellError extends Error {
+  constructor(
+    public code: number,
+    message: string,
+    public cell: string,
+    public retryAfterMs: number,
+  ) {
+    super(message);
+    this.name = "CellError";
+  }
+}
+
+let gatewayUrl = "";
+
+/** Base URL of the HTTP gateway; defaults to the page's origin */
+export function setGatewayUrl(url: string): void {
+  gatewayUrl = url.replace(/\/+$/, "");
+}
+
+async function call<T>(method: string, args: object): Promise<T> {
+  const res = await fetch(`${gatewayUrl}/${CELL}/${method}`, {
+    method: "POST",
+    headers: { "content-type": "application/json" },
+    body: JSON.stringify(args),
+  });
+  const body = await res.json();
+  if (!res.ok) {
+    throw new CellError(body.code, body.message, body.cell, body.retry_after_ms ?? 0);
+  }
+  return body as T;
+}
+
+export interface CallState<A, T> {
+  call: (args: A) => Promise<T>;
+  data?: T;
+  error?: Error;
+  loading: boolean;
+}
+
+function useCall<A, T>(fn: (args: A) => Promise<T>): CallState<A, T> {
+  const [state, setState] = useState<{ data?: T; error?: Error; loading: boolean }>({
+    loading: false,
+  });
+  const run = useCallback(
+    async (args: A) => {
+      setState((s) => ({ ...s, error: undefined, loading: true }));
+      try {
+        const data = await fn(args);
+        setState({ data, loading: false });
+        return data;
+      } catch (error) {
+        setState({ error: error as Error, loading: false });
+        throw error;
+      }
+    },
+    [fn],
+  );
+  return { ...state, call: run };
+}
+"#;
+
+/// Render the React hooks module for `cell`.
+pub fn react_hooks(cell: &str, schema: &Schema) -> String {
+    let prefix = cell.to_case(Case::Camel);
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "// Generated from the schema of `{}` ({:016x}). Do not edit.\n",
+        cell,
+        schema.fingerprint()
+    );
+    out.push_str(RUNTIME);
+    let _ = writeln!(out, "\nconst CELL = {:?};", cell);
+    proteins(&mut out, schema);
+
+    let mut methods: Vec<_> = schema.methods.iter().collect();
+    methods.sort_by_key(|(_, m)| m.index);
+    for (name, method) in methods {
+        let function = format!("{}{}", prefix, name.to_case(Case::Pascal));
+        let args = format!("{}Args", function.to_case(Case::Pascal));
+        let ret = ts_type(&method.ret).0;
+        let _ = writeln!(out);
+        let _ = writeln!(out, "export interface {} {}", args, object(&method.args));
+        let _ = writeln!(
+            out,
+            "\nexport function {}(args: {}): Promise<{}> {{\n  return call({:?}, args);\n}}",
+            function, args, ret, name
+        );
+        let _ = writeln!(
+            out,
+            "\nexport function use{}(): CallState<{}, {}> {{\n  return useCall({});\n}}",
+            function.to_case(Case::Pascal),
+            args,
+            ret,
+            function
+        );
+    }
+    out
+}
+
+/// Render the TypeScript module for `cell` over the `cell-node` addon.
+pub fn node_client(cell: &str, schema: &Schema) -> String {
+    let class = cell.to_case(Case::Pascal);
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "// Generated from the schema of `{}` ({:016x}). Do not edit.\n",
+        cell,
+        schema.fingerprint()
+    );
+    out.push_str("import { connect, Subscription, Synapse } from \"cell-node\";\n");
+    let _ = writeln!(out, "\nconst CELL = {:?};", cell);
+    proteins(&mut out, schema);
+
+    let mut methods: Vec<_> = schema.methods.iter().collect();
+    methods.sort_by_key(|(_, m)| m.index);
+    let mut signatures = Vec::new();
+    let mut calls = String::new();
+    for (name, method) in methods {
+        let args = format!("{}{}Args", class, name.to_case(Case::Pascal));
+        let ret = ts_type(&method.ret).0;
+        let _ = writeln!(out);
+        let _ = writeln!(out, "export interface {} {}", args, object(&method.args));
+        signatures.push(format!("  {}: [{}, {}];", name, args, ret));
+        let _ = write!(
+            calls,
+            "\n  {}(args: {}): Promise<{}> {{\n    return this.synapse.fire({:?}, args);\n  }}\n",
+            name.to_case(Case::Camel),
+            args,
+            ret,
+            name
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "\n/** Arguments and result of each method */\nexport interface {}Methods {{\n{}\n}}",
+        class,
+        signatures.join("\n")
+    );
+    let _ = write!(
+        out,
+        "\nexport class {class} {{\n  \
+         private constructor(private readonly synapse: Synapse) {{}}\n\n  \
+         static async connect(): Promise<{class}> {{\n    \
+         return new {class}(await connect(CELL));\n  }}\n{calls}\n  \
+         /** Call `method` every `intervalMs` (default 1000) and report each new result */\n  \
+         subscribe<M extends keyof {class}Methods>(\n    \
+         method: M,\n    \
+         args: {class}Methods[M][0],\n    \
+         onValue: (err: Error | null, value: {class}Methods[M][1]) => void,\n    \
+         intervalMs?: number,\n  \
+         ): Subscription {{\n    \
+         return this.synapse.subscribe(method, args, onValue, intervalMs);\n  }}\n}}\n",
+    );
+    out
+}
+
+/// Interfaces and union types for every protein
+fn proteins(out: &mut String, schema: &Schema) {
+    for (name, def) in &schema.types {
+        // Generic proteins keep their type parameters
+        let name = match schema.generics.get(name) {
+            Some(params) => format!("{}<{}>", name, params.join(", ")),
+            None => name.clone(),
+        };
+        let _ = writeln!(out);
+        match def {
+            TypeDef::Struct(fields) if is_tuple(fields) => {
+                let _ = writeln!(out, "export type {} = {};", name, tuple(fields));
+            }
+            TypeDef::Struct(fields) => {
+                let _ = writeln!(out, "export interface {} {}", name, object(fields));
+            }
+            TypeDef::Enum(variants) => {
+                let arms: Vec<String> = variants
+                    .iter()
+                    .map(|(variant, fields)| match fields.as_slice() {
+                        [] => format!("{:?}", variant),
+                        f if is_tuple(f) => format!("{{ {}: {} }}", variant, tuple(f)),
+                        f => format!("{{ {}: {} }}", variant, object(f)),
+                    })
+                    .collect();
+                let _ = writeln!(out, "export type {} = {};", name, arms.join(" | "));
+            }
+        }
+    }
+}
+
+/// Tuple structs and variants name their fields by index
+fn is_tuple(fields: &[(String, String)]) -> bool {
+    fields.first().is_some_and(|(name, _)| name == "0")
+}
+
+/// Serde writes one-field tuples as the field itself
+fn tuple(fields: &[(String, String)]) -> String {
+    let types: Vec<String> = fields.iter().map(|(_, ty)| ts_type(ty).0).collect();
+    match types.as_slice() {
+        [single] => single.clone(),
+        _ => format!("[{}]", types.join(", ")),
+    }
+}
+
+fn object(fields: &[(String, String)]) -> String {
+    if fields.is_empty() {
+        return "{}".to_string();
+    }
+    let props: Vec<String> = fields
+        .iter()
+        .map(|(name, ty)| match ts_type(ty) {
+            (ty, true) => format!("{}?: {}", name, ty),
+            (ty, false) => format!("{}: {}", name, ty),
+        })
+        .collect();
+    format!("{{ {} }}", props.join("; "))
+}
+
+/// TypeScript type of a Rust type string, and whether it may be absent.
+fn ts_type(ty: &str) -> (String, bool) {
+    match syn::parse_str::<syn::Type>(ty) {
+        Ok(parsed) => rust_type(&parsed),
+        Err(_) => ("unknown".to_string(), false),
+    }
+}
+
+fn rust_type(ty: &syn::Type) -> (String, bool) {
+    let ts = match ty {
+        syn::Type::Tuple(t) if t.elems.is_empty() => "null".to_string(),
+        syn::Type::Tuple(t) => {
+            let elems: Vec<String> = t.elems.iter().map(|e| rust_type(e).0).collect();
+            format!("[{}]", elems.join(", "))
+        }
+        syn::Type::Reference(r) => return rust_type(&r.elem),
+        syn::Type::Array(a) => format!("{}[]", element(&a.elem)),
+        syn::Type::Slice(s) => format!("{}[]", element(&s.elem)),
+        syn::Type::Path(p) => {
+            let Some(last) = p.path.segments.last() else {
+                return ("unknown".to_string(), false);
+            };
+            let args: Vec<&syn::Type> = match &last.arguments {
+                syn::PathArguments::AngleBracketed(a) => a
+                    .args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        syn::GenericArgument::Type(t) => Some(t),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            let name = last.ident.to_string();
+            match (name.as_str(), args.as_slice()) {
+                ("Option", [inner]) => return (format!("{} | null", rust_type(inner).0), true),
+                // Handlers return `Result<T>`; only `T` reaches the caller
+                ("Result", [inner, ..]) => return rust_type(inner),
+                ("Box" | "Arc" | "Rc", [inner]) => return rust_type(inner),
+                ("Vec" | "VecDeque" | "HashSet" | "BTreeSet", [inner]) => {
+                    format!("{}[]", element(inner))
+                }
+                ("HashMap" | "BTreeMap", [_, value]) => {
+                    format!("Record<string, {}>", rust_type(value).0)
+                }
+                ("String" | "str" | "char", _) => "string".to_string(),
+                ("bool", _) => "boolean".to_string(),
+                (
+                    "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64"
+                    | "i128" | "isize" | "f32" | "f64",
+                    _,
+                ) => "number".to_string(),
+                (protein, []) => protein.to_string(),
+                (protein, args) => {
+                    let args: Vec<String> = args.iter().map(|a| rust_type(a).0).collect();
+                    format!("{}<{}>", protein, args.join(", "))
+                }
+            }
+        }
+        _ => "unknown".to_string(),
+    };
+    (ts, false)
+}
+
+/// Element type of an array, parenthesized when it is a union
+fn element(ty: &syn::Type) -> String {
+    let (ts, optional) = rust_type(ty);
+    if optional {
+        format!("({})", ts)
+    } else {
+        ts
+    }
+}
@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: MIT
+// cell-build/tests/advisory_test.rs
+//! Tests for the dependency inventory and the RustSec advisory scan.
+
+use cell_build::advisory::{
+    dependencies, license_inventory, version_matches, Advisory, AdvisoryDb, Dependency,
+};
+use std::fs;
+
+const LOCKFILE: &str = r#"
+version = 3
+
+[[package]]
+name = "ledger"
+version = "0.1.0"
+dependencies = ["time"]
+
+[[package]]
+name = "time"
+version = "0.1.43"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "ca8a50ef2360fbd1eeb0ecd46795a87a19024eb4b53c5dc916ca1fd95fe62438"
+
+[[package]]
+name = "tokio"
+version = "1.38.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+
+const TIME_ADVISORY: &str = r#"```toml
+[advisory]
+id = "RUSTSEC-2020-0071"
+package = "time"
+date = "2020-11-18"
+
+[versions]
+patched = [">= 0.2.23"]
+unaffected = ["= 0.2.0", "= 0.2.1", "= 0.2.2", "= 0.2.3", "= 0.2.4", "= 0.2.5", "= 0.2.6"]
+```
+
+# Potential segfault in the time crate
+
+Unix-like operating systems may segfault due to dereferencing a dangling pointer.
+"#;
+
+fn dep(name: &str, version: &str, license: Option<&str>) -> Dependency {
+    Dependency {
+        name: name.into(),
+        version: version.into(),
+        license: license.map(Into::into),
+    }
+}
+
+#[test]
+fn test_lockfile_lists_registry_crates_only() {
+    let deps = dependencies(LOCKFILE).unwrap();
+    assert_eq!(
+        deps,
+        vec![dep("time", "0.1.43", None), dep("tokio", "1.38.0", None)]
+    );
+    assert!(dependencies("not a lockfile [").is_err());
+}
+
+#[test]
+fn test_version_requirements() {
+    assert!(version_matches("0.2.23", ">= 0.2.23"));
+    assert!(!version_matches("0.2.22", ">= 0.2.23"));
+    assert!(version_matches("1.4.2", ">= 1.4.0, < 1.5"));
+    assert!(!version_matches("1.5.0", ">= 1.4.0, < 1.5"));
+    assert!(version_matches("0.2.4", "= 0.2.4"));
+    assert!(version_matches("0.2.9", "= 0.2"));
+    assert!(!version_matches("0.3.0", "= 0.2"));
+    assert!(version_matches("1.9.0", "^1.2.3"));
+    assert!(!version_matches("2.0.0", "^1.2.3"));
+    assert!(version_matches("0.4.9", "^0.4.5"));
+    assert!(!version_matches("0.5.0", "^0.4.5"));
+    assert!(version_matches("1.2.9", "~1.2.3"));
+    assert!(!version_matches("1.3.0", "~1.2.3"));
+    assert!(version_matches("0.1.9", "<= 0.1"));
+    assert!(!version_matches("0.1.9", "> 0.1"));
+    assert!(version_matches("1.0.0-rc.1", ">= 1.0.0"));
+    assert!(!version_matches("garbage", ">= 1.0.0"));
+}
+
+#[test]
+fn test_advisory_front_matter_and_title() {
+    let advisory = Advisory::parse(TIME_ADVISORY).unwrap();
+    assert_eq!(advisory.id, "RUSTSEC-2020-0071");
+    assert_eq!(advisory.package, "time");
+    assert_eq!(advisory.title, "Potential segfault in the time crate");
+    assert!(advisory.affects("0.1.43"));
+    assert!(!advisory.affects("0.2.4"));
+    assert!(!advisory.affects("0.3.36"));
+    assert!(Advisory::parse("# no front matter").is_err());
+}
+
+#[test]
+fn test_scan_reports_affected_versions() {
+    let dir = std::env::temp_dir().join(format!("cell-advisory-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let _guard = scopeguard::guard(dir.clone(), |d| {
+        let _ = fs::remove_dir_all(d);
+    });
+    fs::create_dir_all(dir.join("crates/time")).unwrap();
+    fs::create_dir_all(dir.join("crates/tokio")).unwrap();
+    fs::write(dir.join("crates/time/RUSTSEC-2020-0071.md"), TIME_ADVISORY).unwrap();
+    fs::write(
+        dir.join("crates/tokio/RUSTSEC-2099-0001.md"),
+        "```toml\n[advisory]\nid = \"RUSTSEC-2099-0001\"\npackage = \"tokio\"\n\
+         informational = \"unmaintained\"\n```\n\n# Not a vulnerability\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("crates/tokio/broken.md"),
+        "```toml\n[advisory\n```",
+    )
+    .unwrap();
+
+    let db = AdvisoryDb::open(&dir).unwrap();
+    assert_eq!(db.len(), 2);
+
+    let findings = db.scan(&dependencies(LOCKFILE).unwrap());
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].package, "time");
+    assert_eq!(findings[0].advisory, "RUSTSEC-2020-0071");
+    assert_eq!(findings[0].patched, vec![">= 0.2.23".to_string()]);
+
+    assert!(db.scan(&[dep("time", "0.3.36", None)]).is_empty());
+    assert!(AdvisoryDb::open(&dir.join("missing")).is_err());
+}
+
+#[test]
+fn test_license_inventory_counts() {
+    let deps = [
+        dep("serde", "1.0.0", Some("MIT OR Apache-2.0")),
+        dep("tokio", "1.0.0", Some("MIT")),
+        dep("anyhow", "1.0.0", Some("MIT OR Apache-2.0")),
+        dep("mystery", "0.1.0", None),
+    ];
+    assert_eq!(
+        license_inventory(&deps),
+        vec![
+            ("MIT OR Apache-2.0".to_string(), 2),
+            ("MIT".to_string(), 1),
+            ("unknown".to_string(), 1),
+        ]
+    );
+}
@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: MIT
+// cell-build/tests/artifact_test.rs
+//! Tests for the artifact store used to share built binaries between nodes.
+
+use cell_build::artifact::{source_hash, ArtifactKey, ArtifactStore, SigningKey};
+use std::fs;
+use std::path::PathBuf;
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cell-artifact-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_source_hash_ignores_target_dir() {
+    let dir = scratch_dir("hash");
+    let _guard = scopeguard::guard(dir.clone(), |d| {
+        let _ = fs::remove_dir_all(d);
+    });
+
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("src/main.rs"), "fn main() {}").unwrap();
+    fs::write(dir.join("Cargo.toml"), "[package]\nname = \"worker\"").unwrap();
+    let before = source_hash(&dir).unwrap();
+
+    fs::create_dir_all(dir.join("target/release")).unwrap();
+    fs::write(dir.join("target/release/build.rs"), "// generated").unwrap();
+    assert_eq!(before, source_hash(&dir).unwrap());
+
+    fs::write(dir.join("src/main.rs"), "fn main() { println!(); }").unwrap();
+    assert_ne!(before, source_hash(&dir).unwrap());
+}
+
+#[test]
+fn test_export_import_roundtrip() {
+    let dir = scratch_dir("roundtrip");
+    let _guard = scopeguard::guard(dir.clone(), |d| {
+        let _ = fs::remove_dir_all(d);
+    });
+
+    let signer = SigningKey::from_bytes([7u8; 32]);
+    let node_a = ArtifactStore::at(dir.join("a"));
+    let node_b = ArtifactStore::at(dir.join("b"));
+    let key = ArtifactKey::new("worker", "x86_64-linux", "abc123");
+
+    let built = dir.join("worker");
+    fs::write(&built, b"\x7fELF fake binary").unwrap();
+
+    assert!(node_a.lookup(&key).is_none());
+    node_a.insert(&key, &built, &signer).unwrap();
+    assert!(node_a.lookup(&key).is_some());
+
+    let blob = node_a.export(&key).unwrap().expect("artifact exported");
+    let installed = node_b.import(&blob, &signer).unwrap();
+    assert_eq!(fs::read(installed).unwrap(), b"\x7fELF fake binary");
+    assert!(node_b.lookup(&key).is_some());
+}
+
+#[test]
+fn test_import_rejects_bad_signature() {
+    let dir = scratch_dir("reject");
+    let _guard = scopeguard::guard(dir.clone(), |d| {
+        let _ = fs::remove_dir_all(d);
+    });
+
+    let node_a = ArtifactStore::at(dir.join("a"));
+    let node_b = ArtifactStore::at(dir.join("b"));
+    let key = ArtifactKey::new("worker", "x86_64-linux", "abc123");
+
+    let built = dir.join("worker");
+    fs::write(&built, b"binary").unwrap();
+    node_a
+        .insert(&key, &built, &SigningKey::from_bytes([1u8; 32]))
+        .unwrap();
+
+    // A node with a different mesh key must not accept the artifact
+    let blob = node_a.export(&key).unwrap().unwrap();
+    assert!(node_b
+        .import(&blob, &SigningKey::from_bytes([2u8; 32]))
+        .is_err());
+
+    // Tampered bytes fail even with the right key
+    let mut tampered = node_a.export(&key).unwrap().unwrap();
+    tampered.bytes.push(0);
+    assert!(node_b
+        .import(&tampered, &SigningKey::from_bytes([1u8; 32]))
+        .is_err());
+    assert!(node_b.lookup(&key).is_none());
+}
+
+#[test]
+fn test_new_signing_key_is_random_and_private() {
+    let dir = scratch_dir("keys");
+    let _guard = scopeguard::guard(dir.clone(), |d| {
+        let _ = fs::remove_dir_all(d);
+    });
+
+    let key = ArtifactKey::new("worker", "x86_64-linux", "abc123");
+    let a = SigningKey::load_or_create(&dir.join("a.key")).unwrap();
+    let b = SigningKey::load_or_create(&dir.join("b.key")).unwrap();
+    assert_ne!(a.sign(&key, b"bin"), b.sign(&key, b"bin"));
+
+    // Loading again yields the same key
+    let again = SigningKey::load_or_create(&dir.join("a.key")).unwrap();
+    assert!(again.verify(&key, b"bin", &a.sign(&key, b"bin")));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(dir.join("a.key"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
+
+#[test]
+fn test_import_transferred_files() {
+    let dir = scratch_dir("files");
+    let _guard = scopeguard::guard(dir.clone(), |d| {
+        let _ = fs::remove_dir_all(d);
+    });
+
+    let signer = SigningKey::from_bytes([7u8; 32]);
+    let node_a = ArtifactStore::at(dir.join("a"));
+    let node_b = ArtifactStore::at(dir.join("b"));
+    let key = ArtifactKey::new("worker", "x86_64-linux", "abc123");
+    let built = dir.join("worker");
+    fs::write(&built, b"\x7fELF fake binary").unwrap();
+    node_a.insert(&key, &built, &signer).unwrap();
+
+    // What a peer receives: node A's files, as they are
+    let (binary, manifest) = key.transfer_files();
+    let (binary, manifest) = (node_a.root().join(binary), node_a.root().join(manifest));
+    let installed = node_b
+        .import_files(&key, &binary, &manifest, &signer)
+        .unwrap();
+    assert_eq!(fs::read(installed).unwrap(), b"\x7fELF fake binary");
+
+    // A manifest for another artifact is not accepted for this one
+    let other = ArtifactKey::new("worker", "x86_64-linux", "def456");
+    assert!(node_b
+        .import_files(&other, &binary, &manifest, &signer)
+        .is_err());
+
+    fs::write(&binary, b"\x7fELF tampered").unwrap();
+    let fresh = ArtifactStore::at(dir.join("c"));
+    assert!(fresh
+        .import_files(&key, &binary, &manifest, &signer)
+        .is_err());
+}
@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: MIT
+// cell-build/tests/asyncapi_test.rs
+//! Tests for the AsyncAPI topic catalog.
+
+use cell_build::asyncapi::{document, Topic};
+use cell_build::schema::Schema;
+use std::collections::BTreeMap;
+
+const LEDGER: &str = r#"
+    #[protein]
+    pub struct Settled { pub account: String, pub amount: u64 }
+"#;
+
+#[test]
+fn test_topics_become_channels_with_protein_payloads() {
+    let topics = [Topic {
+        name: "ledger.settled".into(),
+        payload: "Settled".into(),
+        producers: vec!["ledger".into()],
+        consumers: vec!["audit".into(), "metrics".into()],
+        description: Some("A transfer cleared".into()),
+    }];
+    let schemas = BTreeMap::from([("ledger".to_string(), Schema::from_source(LEDGER).unwrap())]);
+
+    let doc = document("mesh events", &topics, &schemas);
+    assert_eq!(doc["asyncapi"], "2.6.0");
+
+    let channel = &doc["channels"]["ledger.settled"];
+    assert_eq!(
+        channel["x-consumers"],
+        serde_json::json!(["audit", "metrics"])
+    );
+    assert_eq!(
+        channel["subscribe"]["message"]["$ref"],
+        "#/components/messages/ledger_settledMessage"
+    );
+
+    let message = &doc["components"]["messages"]["ledger_settledMessage"];
+    assert_eq!(message["payload"]["$ref"], "#/components/schemas/Settled");
+    let settled = &doc["components"]["schemas"]["Settled"];
+    assert_eq!(
+        settled["required"],
+        serde_json::json!(["account", "amount"])
+    );
+}
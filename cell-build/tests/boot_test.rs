@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: MIT
+// cell-build/tests/boot_test.rs
+//! Tests for boot planning and the boot timeline.
+
+use cell_build::boot::{BootGraph, BootTimeline};
+use std::collections::HashSet;
+
+fn kernel() -> BootGraph {
+    let mut g = BootGraph::new();
+    g.add("builder", &[])
+        .add("mesh", &[])
+        .add("axon", &[])
+        .add("hypervisor", &["builder"])
+        .add("observer", &["hypervisor", "mesh"]);
+    g
+}
+
+#[test]
+fn test_independent_cells_start_together() {
+    let waves = kernel().waves().unwrap();
+    assert_eq!(waves.len(), 3);
+    assert_eq!(waves[0], vec!["axon", "builder", "mesh"]);
+    assert_eq!(waves[1], vec!["hypervisor"]);
+    assert_eq!(waves[2], vec!["observer"]);
+}
+
+#[test]
+fn test_startable_gates_on_readiness() {
+    let g = kernel();
+    let mut ready = HashSet::new();
+    let mut started: HashSet<String> = ["axon", "builder", "mesh"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+    // Nothing new until builder reports ready
+    ready.insert("mesh".to_string());
+    assert!(g.startable(&ready, &started).is_empty());
+
+    ready.insert("builder".to_string());
+    assert_eq!(g.startable(&ready, &started), vec!["hypervisor"]);
+
+    started.insert("hypervisor".to_string());
+    ready.insert("hypervisor".to_string());
+    assert_eq!(g.startable(&ready, &started), vec!["observer"]);
+}
+
+#[test]
+fn test_validate_rejects_cycles_and_unknown() {
+    let mut g = BootGraph::new();
+    g.add("a", &["b"]).add("b", &["a"]).add("c", &[]);
+    assert!(g.validate().is_err());
+
+    let mut g = BootGraph::new();
+    g.add("a", &["ghost"]);
+    assert!(g.validate().is_err());
+}
+
+#[test]
+fn test_timeline_render() {
+    let mut t = BootTimeline::new();
+    t.started("builder");
+    t.started("mesh");
+    t.ready("mesh");
+    t.failed("builder", "timeout");
+
+    let out = t.render();
+    assert!(out.starts_with("Boot timeline"));
+    assert!(out.contains("mesh"));
+    assert!(out.contains("failed: timeout"));
+}
@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: MIT
+// cell-build/tests/daemon_test.rs
+//! Tests for service unit generation.
+
+use cell_build::daemon::{render_to, ServiceManager, ServiceSpec};
+use std::fs;
+
+fn spec() -> ServiceSpec {
+    ServiceSpec::new("mycelium", "/opt/cell/bin/mycelium")
+        .with_socket("/run/cell/mycelium.sock")
+        .with_env("RUST_LOG", "info")
+}
+
+#[test]
+fn test_systemd_units() {
+    let files = ServiceManager::Systemd.render(&spec()).unwrap();
+    assert_eq!(files.len(), 2);
+
+    let (name, service) = &files[0];
+    assert_eq!(name, "cell-mycelium.service");
+    assert!(service.contains("ExecStart=/opt/cell/bin/mycelium"));
+    assert!(service.contains("Restart=on-failure"));
+    assert!(service.contains("StandardOutput=journal"));
+    assert!(service.contains("Requires=cell-mycelium.socket"));
+    assert!(service.contains("Environment=\"RUST_LOG=info\""));
+
+    let (name, socket) = &files[1];
+    assert_eq!(name, "cell-mycelium.socket");
+    assert!(socket.contains("ListenStream=/run/cell/mycelium.sock"));
+}
+
+#[test]
+fn test_systemd_without_socket() {
+    let files = ServiceManager::Systemd
+        .render(&ServiceSpec::new("hypervisor", "/bin/hv"))
+        .unwrap();
+    assert_eq!(files.len(), 1);
+    assert!(!files[0].1.contains("Requires="));
+}
+
+#[test]
+fn test_launchd_plist() {
+    let files = ServiceManager::Launchd.render(&spec()).unwrap();
+    assert_eq!(files.len(), 1);
+
+    let (name, plist) = &files[0];
+    assert_eq!(name, "com.cell.mycelium.plist");
+    assert!(plist.contains("<string>com.cell.mycelium</string>"));
+    assert!(plist.contains("<string>/opt/cell/bin/mycelium</string>"));
+    assert!(plist.contains("<key>RUST_LOG</key>"));
+    assert!(plist.contains("<key>KeepAlive</key>"));
+}
+
+#[test]
+fn test_units_run_as_user() {
+    let spec = spec().with_user("alice");
+    let files = ServiceManager::Systemd.render(&spec).unwrap();
+    assert!(files[0].1.contains("User=alice"));
+    assert!(files[0].1.contains("WantedBy=multi-user.target"));
+    assert!(files[1].1.contains("SocketUser=alice"));
+
+    let plist = &ServiceManager::Launchd.render(&spec).unwrap()[0].1;
+    assert!(plist.contains("<key>UserName</key>\n    <string>alice</string>"));
+}
+
+#[test]
+fn test_launchd_escapes_values() {
+    let spec = ServiceSpec::new("mycelium", "/opt/a&b/mycelium").with_env("FILTER", "<info>");
+    let plist = &ServiceManager::Launchd.render(&spec).unwrap()[0].1;
+    assert!(plist.contains("<string>/opt/a&amp;b/mycelium</string>"));
+    assert!(plist.contains("<string>&lt;info&gt;</string>"));
+    assert!(!plist.contains("<info>"));
+}
+
+#[test]
+fn test_systemd_escapes_values() {
+    let spec = ServiceSpec::new("mycelium", "/opt/cell/bin/mycelium")
+        .with_env("RUST_LOG", r#"cell="debug" at 100% in C:\logs"#);
+    let service = &ServiceManager::Systemd.render(&spec).unwrap()[0].1;
+    assert!(service.contains(r#"Environment="RUST_LOG=cell=\"debug\" at 100%% in C:\\logs""#));
+}
+
+#[test]
+fn test_systemd_rejects_unwritable_env() {
+    for (key, value) in [
+        ("RUST_LOG", "info\nExecStartPre=/bin/sh -c id"),
+        ("RUST_LOG", "info\r"),
+        ("A=B", "info"),
+    ] {
+        let spec = ServiceSpec::new("mycelium", "/opt/cell/bin/mycelium").with_env(key, value);
+        assert!(ServiceManager::Systemd.render(&spec).is_err(), "{:?}", key);
+        assert!(!ServiceManager::Systemd.is_installed(&spec));
+    }
+}
+
+#[test]
+fn test_render_to_dir() {
+    let dir = std::env::temp_dir().join(format!("cell-daemon-{}", std::process::id()));
+    let _guard = scopeguard::guard(dir.clone(), |d| {
+        let _ = fs::remove_dir_all(d);
+    });
+
+    let written = render_to(&dir, ServiceManager::Systemd, &spec()).unwrap();
+    assert_eq!(written.len(), 2);
+    assert!(written.iter().all(|p| p.exists()));
+}
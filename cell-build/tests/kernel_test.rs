@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: MIT
+// cell-build/tests/kernel_test.rs
+//! Tests for resolving prebuilt kernel binaries.
+
+use cell_build::kernel::{KernelBin, KernelLaunch};
+use std::fs;
+
+#[test]
+fn test_install_and_resolve() {
+    let dir = std::env::temp_dir().join(format!("cell-kernel-bin-{}", std::process::id()));
+    let _guard = scopeguard::guard(dir.clone(), |d| {
+        let _ = fs::remove_dir_all(d);
+    });
+    fs::create_dir_all(&dir).unwrap();
+
+    let built = dir.join("hypervisor.build");
+    fs::write(&built, b"hypervisor binary").unwrap();
+
+    let bin = KernelBin::at(dir.join("bin"));
+    assert!(bin.verified("hypervisor", "0.4.1").is_err());
+
+    let installed = bin.install("hypervisor", &built, "0.4.1").unwrap();
+    assert_eq!(
+        bin.resolve("hypervisor", "0.4.7").unwrap(),
+        KernelLaunch::Binary(installed.clone())
+    );
+
+    // Different minor release is rejected
+    assert!(bin.verified("hypervisor", "0.5.0").is_err());
+
+    // A binary swapped out behind the index is rejected
+    fs::write(&installed, b"something else").unwrap();
+    assert!(bin.verified("hypervisor", "0.4.1").is_err());
+}
@@ -118,7 +118,7 @@ fn compute_hash_std(path: &PathBuf) -> String {
         if entry
             .path()
             .extension()
-            .map_or(false, |ext| ext == "rs" || ext == "toml")
+            .is_some_and(|ext| ext == "rs" || ext == "toml")
         {
             if let Ok(bytes) = fs::read(entry.path()) {
                 bytes.hash(&mut hasher);
@@ -165,7 +165,7 @@ fn test_cache_invalidation() {
     // We test the hash computation and cache file management instead.
 
     let cell_name = "test-cache-cell";
-    let _guard = scopeguard::guard(cell_name, |name| cleanup_mock_cell(name));
+    let _guard = scopeguard::guard(cell_name, cleanup_mock_cell);
 
     let cell_path = setup_mock_cell(cell_name);
 
@@ -250,7 +250,7 @@ fn test_missing_macro_feature() {
     // Test error when cell exists but feature doesn't
 
     let cell_name = "test-missing-feature-cell";
-    let _guard = scopeguard::guard(cell_name, |name| cleanup_mock_cell(name));
+    let _guard = scopeguard::guard(cell_name, cleanup_mock_cell);
 
     setup_mock_cell(cell_name);
 
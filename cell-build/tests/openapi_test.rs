@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: MIT
+// cell-build/tests/openapi_test.rs
+//! Tests for OpenAPI generation from cell schemas.
+
+use cell_build::openapi::document;
+use cell_build::schema::Schema;
+
+const LEDGER: &str = r#"
+    #[protein]
+    pub struct Deposit { pub account: String, pub amount: u64, pub memo: Option<String> }
+
+    #[protein]
+    pub enum Outcome { Applied, Deferred, Rejected(String) }
+
+    #[handler]
+    impl Ledger {
+        async fn deposit(&self, req: Deposit) -> Result<Outcome> { todo!() }
+        async fn history(&self, account: String, limit: u32) -> Result<Vec<Deposit>> { todo!() }
+    }
+"#;
+
+#[test]
+fn test_each_method_is_a_post_route() {
+    let doc = document("ledger", &Schema::from_source(LEDGER).unwrap());
+
+    assert_eq!(doc["openapi"], "3.0.3");
+    let history = &doc["paths"]["/ledger/history"]["post"];
+    assert_eq!(history["operationId"], "history");
+
+    let body = &history["requestBody"]["content"]["application/json"]["schema"];
+    assert_eq!(body["required"], serde_json::json!(["account", "limit"]));
+    assert_eq!(body["properties"]["limit"]["type"], "integer");
+
+    let ok = &history["responses"]["200"]["content"]["application/json"]["schema"];
+    assert_eq!(ok["type"], "array");
+    assert_eq!(ok["items"]["$ref"], "#/components/schemas/Deposit");
+}
+
+#[test]
+fn test_proteins_use_their_serde_shape() {
+    let doc = document("ledger", &Schema::from_source(LEDGER).unwrap());
+    let schemas = &doc["components"]["schemas"];
+
+    let deposit = &schemas["Deposit"];
+    assert_eq!(
+        deposit["required"],
+        serde_json::json!(["account", "amount"])
+    );
+    assert_eq!(deposit["properties"]["memo"]["nullable"], true);
+
+    let outcome = &schemas["Outcome"]["oneOf"];
+    assert_eq!(
+        outcome[0]["enum"],
+        serde_json::json!(["Applied", "Deferred"])
+    );
+    assert_eq!(outcome[1]["properties"]["Rejected"]["type"], "string");
+    assert!(schemas["ErrorResponse"].is_object());
+}
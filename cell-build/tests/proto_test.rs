@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: MIT
+// cell-build/tests/proto_test.rs
+//! Tests for `.proto` import.
+
+use cell_build::proto::{import, parse};
+use cell_build::schema::Schema;
+
+const ORDERS: &str = r#"
+    syntax = "proto3";
+    package shop.v1;
+
+    // An order as the storefront submits it
+    message Order {
+        string id = 1;
+        repeated Line lines = 2;
+        Status status = 3;
+        map<string, int64> tags = 4;
+        Address shipping = 5;
+        optional string note = 6 [deprecated = true];
+
+        message Address { string city = 1; }
+
+        oneof payment {
+            string card_token = 7;
+            uint64 voucher = 8;
+        }
+    }
+
+    message Line { string sku = 1; uint32 qty = 2; }
+
+    enum Status {
+        STATUS_UNSPECIFIED = 0;
+        STATUS_PAID = 1;
+    }
+
+    service OrderService {
+        rpc PlaceOrder(Order) returns (shop.v1.Line);
+        rpc Ping(google.protobuf.Empty) returns (google.protobuf.Empty) {}
+    }
+"#;
+
+#[test]
+fn test_messages_become_proteins() {
+    let file = parse(ORDERS).unwrap();
+    assert_eq!(file.package.as_deref(), Some("shop.v1"));
+
+    let order = file.messages.iter().find(|m| m.name == "Order").unwrap();
+    let fields: Vec<(&str, &str)> = order
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.ty.as_str()))
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("id", "String"),
+            ("lines", "Vec<Line>"),
+            ("status", "Status"),
+            ("tags", "std::collections::HashMap<String, i64>"),
+            ("shipping", "Option<OrderAddress>"),
+            ("note", "Option<String>"),
+            ("payment", "Option<OrderPayment>"),
+        ]
+    );
+
+    let status = file.enums.iter().find(|e| e.name == "Status").unwrap();
+    let variants: Vec<&str> = status.variants.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(variants, ["Unspecified", "Paid"]);
+}
+
+#[test]
+fn test_services_become_handler_skeletons() {
+    let source = import(ORDERS).unwrap();
+    assert!(source.contains("#[service]\npub struct OrderService;"));
+    assert!(source.contains("pub async fn place_order(&self, req: Order) -> Result<Line>"));
+
+    // The output is a cell source the rest of the toolchain understands
+    let schema = Schema::from_source(&source).unwrap();
+    assert_eq!(schema.methods["ping"].ret, "Result<()>");
+    assert!(schema.types.contains_key("OrderPayment"));
+}
+
+#[test]
+fn test_streaming_rpcs_are_rejected() {
+    let err = parse("service Feed { rpc Watch(Req) returns (stream Event); }").unwrap_err();
+    assert!(err.to_string().contains("streaming"));
+}
@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MIT
+// cell-build/tests/python_test.rs
+//! Tests for the typed Python client generator.
+
+use cell_build::python::client;
+use cell_build::schema::Schema;
+
+const LEDGER: &str = r#"
+    #[protein]
+    pub struct Deposit { pub account: String, pub amount: u64, pub memo: Option<String> }
+
+    #[protein]
+    pub enum Outcome { Applied, Rejected(String), Split { parts: Vec<Deposit> } }
+
+    #[handler]
+    impl Ledger {
+        async fn deposit(&self, req: Deposit) -> Result<Outcome> { todo!() }
+        async fn history(&self, account: String, limit: u32) -> Result<Vec<Deposit>> { todo!() }
+    }
+"#;
+
+#[test]
+fn test_methods_call_through_the_native_synapse() {
+    let py = client("ledger", &Schema::from_source(LEDGER).unwrap());
+
+    assert!(py.contains("class Ledger:"));
+    assert!(py.contains("return cls(await cell_py.Synapse.connect(\"ledger\"))"));
+    assert!(py.contains(
+        "    async def history(self, account: str, limit: int) -> List[Deposit]:\n        \
+         return await self._synapse.call(\"history\", {\"account\": account, \"limit\": limit})"
+    ));
+    assert!(py.find("def deposit").unwrap() < py.find("def history").unwrap());
+}
+
+#[test]
+fn test_proteins_use_their_serde_shape() {
+    let py = client("ledger", &Schema::from_source(LEDGER).unwrap());
+
+    assert!(py.contains(
+        "class Deposit(TypedDict):\n    account: str\n    amount: int\n    memo: Optional[str]\n"
+    ));
+    assert!(py.contains("OutcomeRejected = TypedDict(\"OutcomeRejected\", {\"Rejected\": str})"));
+    assert!(py.contains(
+        "OutcomeSplit = TypedDict(\"OutcomeSplit\", {\"Split\": TypedDict(\"OutcomeSplitFields\", {\"parts\": List[\"Deposit\"]})})"
+    ));
+    assert!(
+        py.contains("Outcome = Union[Literal[\"Applied\"], \"OutcomeRejected\", \"OutcomeSplit\"]")
+    );
+}
@@ -0,0 +1,250 @@
+// SPDX-License-Identifier: MIT
+// cell-build/tests/schema_test.rs
+//! Tests for schema extraction and compatibility diffs.
+
+use cell_build::schema::{
+    artifact_path, lock_path, published, ChangeKind, RetryPolicy, Schema, SchemaArtifact,
+    SchemaChange,
+};
+
+const LEDGER: &str = r#"
+    #[protein]
+    pub struct Deposit { pub account: String, pub amount: u64 }
+
+    #[protein]
+    pub enum Outcome { Applied, Rejected(String) }
+
+    #[handler]
+    impl Ledger {
+        async fn deposit(&self, req: Deposit) -> Result<Outcome> { todo!() }
+        async fn balance(&self, account: String) -> Result<u64> { todo!() }
+    }
+"#;
+
+#[test]
+fn test_identical_sources_are_compatible() {
+    let a = Schema::from_source(LEDGER).unwrap();
+    let b = Schema::from_source(&LEDGER.replace("todo!()", "unimplemented!()")).unwrap();
+
+    assert!(a.diff(&b).is_empty());
+    assert_eq!(a.fingerprint(), b.fingerprint());
+    assert_eq!(
+        a.methods["deposit"].args,
+        [("req".to_string(), "Deposit".to_string())]
+    );
+}
+
+#[test]
+fn test_diff_names_the_changed_field() {
+    let base = Schema::from_source(LEDGER).unwrap();
+    let now = Schema::from_source(&LEDGER.replace("amount: u64", "amount: u128")).unwrap();
+
+    assert_eq!(
+        now.diff(&base),
+        [SchemaChange {
+            path: "type `Deposit` field `amount`".into(),
+            kind: ChangeKind::Changed {
+                base: "u64".into(),
+                now: "u128".into(),
+            },
+            breaking: true,
+        }]
+    );
+    assert_ne!(now.fingerprint(), base.fingerprint());
+}
+
+#[test]
+fn test_renames_and_appends_are_not_breaking() {
+    let base = Schema::from_source(LEDGER).unwrap();
+    let now = Schema::from_source(
+        &LEDGER
+            .replace("pub account: String", "pub owner: String")
+            .replace("async fn balance", "async fn balance_of")
+            .replace("Rejected(String) }", "Rejected(String), Deferred }"),
+    )
+    .unwrap();
+
+    let changes = now.diff(&base);
+    assert!(!Schema::is_breaking(&changes));
+    let lines: Vec<String> = changes.iter().map(|c| c.to_string()).collect();
+    assert_eq!(
+        lines,
+        [
+            "> method `balance_of` (was `balance`)",
+            "> type `Deposit` field `owner` (was `account`)",
+            "+ type `Outcome` variant `Deferred`",
+        ]
+    );
+}
+
+#[test]
+fn test_removing_or_reordering_is_breaking() {
+    let base = Schema::from_source(LEDGER).unwrap();
+    let now = Schema::from_source(
+        &LEDGER
+            .replace("Applied, Rejected(String)", "Rejected(String), Applied")
+            .replace(
+                "async fn balance(&self, account: String) -> Result<u64> { todo!() }",
+                "",
+            ),
+    )
+    .unwrap();
+
+    let changes = now.diff(&base);
+    assert!(Schema::is_breaking(&changes));
+    let lines: Vec<String> = changes.iter().map(|c| c.to_string()).collect();
+    assert!(lines.contains(&"- method `balance` [breaking]".to_string()));
+    assert!(lines.contains(&"+ type `Outcome`::Rejected field `0: String` [breaking]".to_string()));
+}
+
+#[test]
+fn test_retry_policy_is_advertised() {
+    let base = Schema::from_source(LEDGER).unwrap();
+    let now = Schema::from_source(&LEDGER.replace(
+        "async fn balance",
+        "#[handler(read, retry(max_attempts = 5, backoff_ms = 20, idempotent))]\n        async fn balance",
+    ))
+    .unwrap();
+
+    assert_eq!(
+        now.methods["balance"].retry,
+        Some(RetryPolicy {
+            idempotent: true,
+            max_attempts: 5,
+            backoff_ms: 20,
+        })
+    );
+    assert_eq!(now.methods["deposit"].retry, None);
+    let lines: Vec<String> = now.diff(&base).iter().map(|c| c.to_string()).collect();
+    assert_eq!(
+        lines,
+        ["~ method `balance` retry: `none` -> `5 attempts, 20ms backoff, idempotent`"]
+    );
+}
+
+#[test]
+fn test_subscribers_are_not_part_of_the_protocol() {
+    let base = Schema::from_source(LEDGER).unwrap();
+    let now = Schema::from_source(&LEDGER.replace(
+        "async fn deposit",
+        "#[subscriber(\"audit.freezes\")]\n        async fn on_freeze(&self, event: Deposit) -> Result<()> { todo!() }\n        async fn deposit",
+    ))
+    .unwrap();
+
+    assert!(!now.methods.contains_key("on_freeze"));
+    assert_eq!(now.methods["deposit"].index, 0);
+    assert_eq!(now.fingerprint(), base.fingerprint());
+}
+
+#[test]
+fn test_call_context_is_not_part_of_the_protocol() {
+    let base = Schema::from_source(LEDGER).unwrap();
+    let now = Schema::from_source(&LEDGER.replace(
+        "deposit(&self, req: Deposit)",
+        "deposit(&self, ctx: &CallContext, req: Deposit)",
+    ))
+    .unwrap();
+
+    assert_eq!(now.methods["deposit"].args, base.methods["deposit"].args);
+    assert_eq!(now.fingerprint(), base.fingerprint());
+}
+
+#[test]
+fn test_lockfile_takes_precedence() {
+    let dir = std::env::temp_dir().join(format!("cell-schema-{}", std::process::id()));
+    let _guard = scopeguard::guard(dir.clone(), |d| {
+        let _ = std::fs::remove_dir_all(d);
+    });
+    let schema = Schema::from_source(LEDGER).unwrap();
+    schema.save(&lock_path(&dir, "ledger")).unwrap();
+
+    let (found, origin) = published(&dir, "ledger").unwrap().unwrap();
+    assert_eq!(found, schema);
+    assert_eq!(origin, lock_path(&dir, "ledger"));
+}
+
+#[test]
+fn test_artifact_matches_the_source_schema() {
+    let file = syn::parse_file(&format!("mod api {{ {} }}", LEDGER)).unwrap();
+    let artifact = SchemaArtifact::new("ledger", &file).unwrap();
+
+    let schema = Schema::from_source(LEDGER).unwrap();
+    assert_eq!(artifact.schema().unwrap(), schema);
+    assert_eq!(artifact.fingerprint, schema.fingerprint());
+}
+
+#[test]
+fn test_vendored_artifact_is_published() {
+    let dir = std::env::temp_dir().join(format!("cell-artifact-{}", std::process::id()));
+    let _guard = scopeguard::guard(dir.clone(), |d| {
+        let _ = std::fs::remove_dir_all(d);
+    });
+    let artifact = SchemaArtifact::new("ledger", &syn::parse_file(LEDGER).unwrap()).unwrap();
+    let path = artifact_path(&dir.join(".cell/schema"), "ledger");
+    artifact.save(&path).unwrap();
+
+    let (found, origin) = published(&dir, "ledger").unwrap().unwrap();
+    assert_eq!(found, artifact.schema().unwrap());
+    assert_eq!(origin, path);
+
+    // An edited surface no longer matches the recorded fingerprint
+    let tampered = SchemaArtifact {
+        surface: artifact.surface.replace("u64", "u128"),
+        ..artifact
+    };
+    tampered.save(&path).unwrap();
+    assert!(SchemaArtifact::load(&path).is_err());
+}
+
+#[test]
+fn test_handler_blocks_across_modules_share_one_protocol() {
+    let dir = std::env::temp_dir().join(format!("cell-blocks-{}", std::process::id()));
+    let _guard = scopeguard::guard(dir.clone(), |d| {
+        let _ = std::fs::remove_dir_all(d);
+    });
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("main.rs"),
+        "mod reads;\n#[handler]\nimpl Ledger { async fn deposit(&self, req: Deposit) -> Result<u64> { todo!() } }\n#[handler]\nimpl Ledger { async fn freeze(&self, account: String) -> Result<()> { todo!() } }",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("reads.rs"),
+        "#[handler]\nimpl Ledger { async fn balance(&self, account: String) -> Result<u64> { todo!() } }",
+    )
+    .unwrap();
+
+    let file = cell_build::load_and_flatten_source(&dir.join("main.rs")).unwrap();
+    let schema = Schema::from_file(&file);
+    let order: Vec<(&str, u32)> = schema
+        .methods
+        .iter()
+        .map(|(name, m)| (name.as_str(), m.index))
+        .collect();
+    assert_eq!(order, [("balance", 0), ("deposit", 1), ("freeze", 2)]);
+    let artifact = SchemaArtifact::new("ledger", &file).unwrap();
+    assert_eq!(artifact.schema().unwrap(), schema);
+}
+
+#[test]
+fn test_generic_proteins_record_their_parameters() {
+    let src = r#"
+        #[protein]
+        pub struct Page<T: Archive> { pub items: Vec<T>, pub next: Option<String> }
+
+        #[handler]
+        impl Ledger {
+            async fn deposits(&self, cursor: Option<String>) -> Result<Page<Deposit>> { todo!() }
+        }
+    "#;
+    let base = Schema::from_source(src).unwrap();
+    assert_eq!(base.generics["Page"], ["T"]);
+    assert_eq!(base.methods["deposits"].ret, "Result<Page<Deposit>>");
+
+    let widened = Schema::from_source(&src.replace("<T: Archive>", "<T: Archive, C>")).unwrap();
+    let lines: Vec<String> = widened.diff(&base).iter().map(|c| c.to_string()).collect();
+    assert_eq!(
+        lines,
+        ["~ type `Page` parameters: `<T>` -> `<T, C>` [breaking]"]
+    );
+}
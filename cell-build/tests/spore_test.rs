@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: MIT
+// cell-build/tests/spore_test.rs
+//! Tests for the `.spore` bundle format.
+
+use cell_build::artifact::SigningKey;
+use cell_build::spore::{extract_schema, Spore};
+use std::fs;
+
+fn sample() -> Spore {
+    Spore::assemble(
+        "worker",
+        "0.1.0",
+        "abc123",
+        b"\x7fELF worker".to_vec(),
+        "[cell]\nname = \"worker\"\n".to_string(),
+        "pub struct Job;".to_string(),
+        Some("threads = 4\n".to_string()),
+    )
+}
+
+#[test]
+fn test_spore_roundtrip() {
+    let signer = SigningKey::from_bytes([3u8; 32]);
+    let spore = sample();
+
+    let bytes = spore.to_bytes(&signer).unwrap();
+    let decoded = Spore::from_bytes(&bytes, &signer).unwrap();
+
+    assert_eq!(decoded.manifest, spore.manifest);
+    assert_eq!(decoded.binary, spore.binary);
+    assert_eq!(decoded.cell_toml, spore.cell_toml);
+    assert_eq!(decoded.schema, spore.schema);
+    assert_eq!(decoded.config.as_deref(), Some("threads = 4\n"));
+    assert_eq!(decoded.artifact_key().source_hash, "abc123");
+}
+
+#[test]
+fn test_spore_rejects_tampering() {
+    let signer = SigningKey::from_bytes([3u8; 32]);
+    let mut bytes = sample().to_bytes(&signer).unwrap();
+
+    assert!(Spore::from_bytes(&bytes, &SigningKey::from_bytes([4u8; 32])).is_err());
+
+    let mid = bytes.len() / 2;
+    bytes[mid] ^= 0xff;
+    assert!(Spore::from_bytes(&bytes, &signer).is_err());
+
+    assert!(Spore::from_bytes(b"not a spore", &signer).is_err());
+}
+
+#[test]
+fn test_spore_unpack() {
+    let dir = std::env::temp_dir().join(format!("cell-spore-unpack-{}", std::process::id()));
+    let _guard = scopeguard::guard(dir.clone(), |d| {
+        let _ = fs::remove_dir_all(d);
+    });
+
+    let bin = sample().unpack(&dir).unwrap();
+    assert_eq!(bin, dir.join("worker"));
+    assert_eq!(fs::read(&bin).unwrap(), b"\x7fELF worker");
+    assert!(dir.join("Cell.toml").exists());
+    assert!(dir.join("schema.rs").exists());
+    assert!(dir.join("config.toml").exists());
+    assert!(dir.join("spore.json").exists());
+}
+
+#[test]
+fn test_extract_schema_strips_bodies() {
+    let file: syn::File = syn::parse_str(
+        r#"
+        #[protein]
+        pub struct Job { id: u64 }
+
+        struct Internal;
+
+        #[handler]
+        impl Worker {
+            async fn run(&self, job: Job) -> Result<u64> { secret_logic(job) }
+        }
+        "#,
+    )
+    .unwrap();
+
+    let schema = extract_schema(&file);
+    assert!(schema.contains("pub struct Job"));
+    assert!(schema.contains("async fn run(&self, job: Job) -> Result<u64>"));
+    assert!(!schema.contains("Internal"));
+    assert!(!schema.contains("secret_logic"));
+}
+
+#[test]
+fn test_extract_schema_hoists_submodules() {
+    let file: syn::File = syn::parse_str(
+        r#"
+        mod api {
+            #[protein]
+            pub struct Job { id: u64 }
+
+            #[handler]
+            impl Worker {
+                async fn run(&self, job: Job) -> Result<u64> { secret_logic(job) }
+            }
+        }
+        "#,
+    )
+    .unwrap();
+
+    let schema: syn::File = syn::parse_str(&extract_schema(&file)).unwrap();
+    assert_eq!(schema.items.len(), 2);
+    assert!(matches!(&schema.items[0], syn::Item::Struct(s) if s.ident == "Job"));
+    assert!(matches!(schema.items[1], syn::Item::Impl(_)));
+}
@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: MIT
+// cell-build/tests/typescript_test.rs
+//! Tests for the TypeScript client generators.
+
+use cell_build::schema::Schema;
+use cell_build::typescript::{node_client, react_hooks};
+
+const LEDGER: &str = r#"
+    #[protein]
+    pub struct Deposit { pub account: String, pub amount: u64, pub memo: Option<String> }
+
+    #[protein]
+    pub enum Outcome { Applied, Deferred, Rejected(String), Split { parts: Vec<u64> } }
+
+    #[handler]
+    impl Ledger {
+        async fn deposit(&self, req: Deposit) -> Result<Outcome> { todo!() }
+        async fn history(&self, account: String, limit: u32) -> Result<Vec<Deposit>> { todo!() }
+    }
+"#;
+
+#[test]
+fn test_each_method_gets_a_function_and_a_hook() {
+    let ts = react_hooks("ledger", &Schema::from_source(LEDGER).unwrap());
+
+    assert!(ts.contains("const CELL = \"ledger\";"));
+    assert!(ts.contains("export interface LedgerHistoryArgs { account: string; limit: number }"));
+    assert!(ts.contains(
+        "export function ledgerHistory(args: LedgerHistoryArgs): Promise<Deposit[]> {\n  return call(\"history\", args);\n}"
+    ));
+    assert!(
+        ts.contains("export function useLedgerDeposit(): CallState<LedgerDepositArgs, Outcome> {")
+    );
+    // Declaration order, as on the wire
+    assert!(ts.find("ledgerDeposit(").unwrap() < ts.find("ledgerHistory(").unwrap());
+}
+
+#[test]
+fn test_proteins_use_their_serde_shape() {
+    let ts = react_hooks("ledger", &Schema::from_source(LEDGER).unwrap());
+
+    assert!(ts.contains(
+        "export interface Deposit { account: string; amount: number; memo?: string | null }"
+    ));
+    assert!(ts.contains(
+        "export type Outcome = \"Applied\" | \"Deferred\" | { Rejected: string } | { Split: { parts: number[] } };"
+    ));
+}
+
+#[test]
+fn test_node_client_calls_through_the_addon() {
+    let ts = node_client("ledger", &Schema::from_source(LEDGER).unwrap());
+
+    assert!(ts.contains("import { connect, Subscription, Synapse } from \"cell-node\";"));
+    assert!(ts.contains("export interface LedgerHistoryArgs { account: string; limit: number }"));
+    assert!(ts.contains(
+        "  history(args: LedgerHistoryArgs): Promise<Deposit[]> {\n    return this.synapse.fire(\"history\", args);\n  }"
+    ));
+    assert!(ts.contains(
+        "export interface LedgerMethods {\n  deposit: [LedgerDepositArgs, Outcome];\n  history: [LedgerHistoryArgs, Deposit[]];\n}"
+    ));
+    assert!(ts.contains("return new Ledger(await connect(CELL));"));
+    assert!(ts.contains("export type Outcome = \"Applied\" | \"Deferred\""));
+}
+
+#[test]
+fn test_generic_proteins_keep_their_parameters() {
+    let src = r#"
+        #[protein]
+        pub struct Page<T: Archive> { pub items: Vec<T>, pub next: Option<String> }
+
+        #[handler]
+        impl Ledger {
+            async fn deposits(&self, cursor: Option<String>) -> Result<Page<u64>> { todo!() }
+        }
+    "#;
+    let ts = react_hooks("ledger", &Schema::from_source(src).unwrap());
+
+    assert!(ts.contains("export interface Page<T> { items: T[]; next?: string | null }"));
+    assert!(ts.contains("Promise<Page<number>>"));
+}
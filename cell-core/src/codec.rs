@@ -0,0 +1,55 @@
+// cell-core/src/codec.rs
+// SPDX-License-Identifier: MIT
+
+//! Wire formats of application payloads.
+//!
+//! Connections start out in rkyv. A client that wants another format sends
+//! the codecs it accepts on [`crate::channel::CODEC`], most preferred first,
+//! one byte each; the cell answers with the single byte of the codec it picked
+//! and uses it for the rest of the connection.
+
+use alloc::vec::Vec;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Codec {
+    /// Zero-copy archives, what Rust cells speak natively
+    Rkyv = 0,
+    Json = 1,
+    MsgPack = 2,
+}
+
+impl Codec {
+    pub const ALL: [Codec; 3] = [Codec::Rkyv, Codec::Json, Codec::MsgPack];
+
+    pub fn from_u8(byte: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| *c as u8 == byte)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Codec::Rkyv => "rkyv",
+            Codec::Json => "json",
+            Codec::MsgPack => "msgpack",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.name() == name)
+    }
+
+    /// Handshake payload offering `codecs`, most preferred first
+    pub fn offer(codecs: &[Codec]) -> Vec<u8> {
+        codecs.iter().map(|c| *c as u8).collect()
+    }
+
+    /// The first offered codec `supported` accepts. Unknown bytes are
+    /// skipped, and rkyv, which every cell speaks, is the fallback.
+    pub fn negotiate(offer: &[u8], supported: impl Fn(Codec) -> bool) -> Codec {
+        offer
+            .iter()
+            .filter_map(|b| Self::from_u8(*b))
+            .find(|c| supported(*c))
+            .unwrap_or(Codec::Rkyv)
+    }
+}
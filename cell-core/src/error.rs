@@ -1,11 +1,16 @@
 // SPDX-License-Identifier: MIT
 // cell-core/src/error.rs
+//! The error catalog shared by every cell.
+//!
+//! Codes travel across RPC (see `cell_model::error::RemoteError`), so a
+//! variant's number must never change once released.
 
 use core::fmt;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u16)]
 pub enum CellError {
+    // Transport (100-199)
     ConnectionRefused = 100,
     ConnectionReset = 101,
     Timeout = 102,
@@ -13,11 +18,123 @@ pub enum CellError {
     CapabilityMissing = 104,
     IoError = 105,
     CircuitBreakerOpen = 106,
+    TransportUnavailable = 107,
 
+    // Protocol (200-299)
     InvalidHeader = 200,
+    DeserializationFailure = 201,
+    InvalidMessage = 202,
     SerializationFailure = 203,
     Corruption = 204,
     ProtocolMismatch = 205,
+
+    // Resource (300-399)
+    OutOfMemory = 300,
+    QuotaExceeded = 301,
+    RateLimited = 302,
+    ResourceExhausted = 303,
+    /// Shed by an overloaded cell; retry after the hinted delay
+    Throttled = 304,
+
+    // State (400-499)
+    NotFound = 400,
+    AlreadyExists = 401,
+    InvalidState = 402,
+    DependencyFailed = 403,
+
+    // Internal (500-599)
+    InternalError = 500,
+    Panic = 501,
+    NotImplemented = 502,
+    /// A handler returned an error without a more specific code
+    HandlerFailed = 503,
+}
+
+/// Error classification categories
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    Transport,
+    Protocol,
+    Resource,
+    State,
+    Internal,
+}
+
+impl CellError {
+    const ALL: [CellError; 27] = [
+        Self::ConnectionRefused,
+        Self::ConnectionReset,
+        Self::Timeout,
+        Self::AccessDenied,
+        Self::CapabilityMissing,
+        Self::IoError,
+        Self::CircuitBreakerOpen,
+        Self::TransportUnavailable,
+        Self::InvalidHeader,
+        Self::DeserializationFailure,
+        Self::InvalidMessage,
+        Self::SerializationFailure,
+        Self::Corruption,
+        Self::ProtocolMismatch,
+        Self::OutOfMemory,
+        Self::QuotaExceeded,
+        Self::RateLimited,
+        Self::ResourceExhausted,
+        Self::Throttled,
+        Self::NotFound,
+        Self::AlreadyExists,
+        Self::InvalidState,
+        Self::DependencyFailed,
+        Self::InternalError,
+        Self::Panic,
+        Self::NotImplemented,
+        Self::HandlerFailed,
+    ];
+
+    pub fn code(self) -> u16 {
+        self as u16
+    }
+
+    pub fn from_code(code: u16) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.code() == code)
+    }
+
+    pub fn category(self) -> ErrorCategory {
+        match self.code() {
+            100..=199 => ErrorCategory::Transport,
+            200..=299 => ErrorCategory::Protocol,
+            300..=399 => ErrorCategory::Resource,
+            400..=499 => ErrorCategory::State,
+            _ => ErrorCategory::Internal,
+        }
+    }
+
+    /// Whether the same request may succeed if sent again later
+    pub fn is_retryable(self) -> bool {
+        match self {
+            Self::AccessDenied | Self::CapabilityMissing => false,
+            Self::DependencyFailed => true,
+            _ => matches!(
+                self.category(),
+                ErrorCategory::Transport | ErrorCategory::Resource
+            ),
+        }
+    }
+
+    /// Recommended delay before retrying
+    pub fn retry_delay(self) -> Option<core::time::Duration> {
+        if !self.is_retryable() {
+            return None;
+        }
+        Some(core::time::Duration::from_millis(match self {
+            Self::Timeout | Self::ConnectionReset => 100,
+            Self::ConnectionRefused => 500,
+            Self::RateLimited => 1000,
+            Self::Throttled => 500,
+            Self::CircuitBreakerOpen => 5000,
+            _ => 250,
+        }))
+    }
 }
 
 impl fmt::Display for CellError {
@@ -30,10 +147,38 @@ impl fmt::Display for CellError {
             CellError::CapabilityMissing => write!(f, "Capability Missing"),
             CellError::IoError => write!(f, "I/O Error"),
             CellError::CircuitBreakerOpen => write!(f, "Circuit Breaker Open"),
+            CellError::TransportUnavailable => write!(f, "Transport Unavailable"),
             CellError::InvalidHeader => write!(f, "Invalid Vesicle Header"),
+            CellError::DeserializationFailure => write!(f, "Deserialization Failure"),
+            CellError::InvalidMessage => write!(f, "Invalid Message"),
             CellError::SerializationFailure => write!(f, "Serialization Failure"),
             CellError::Corruption => write!(f, "Data Corruption Detected"),
             CellError::ProtocolMismatch => write!(f, "Protocol Mismatch"),
+            CellError::OutOfMemory => write!(f, "Out of Memory"),
+            CellError::QuotaExceeded => write!(f, "Quota Exceeded"),
+            CellError::RateLimited => write!(f, "Rate Limited"),
+            CellError::ResourceExhausted => write!(f, "Resource Exhausted"),
+            CellError::Throttled => write!(f, "Throttled"),
+            CellError::NotFound => write!(f, "Not Found"),
+            CellError::AlreadyExists => write!(f, "Already Exists"),
+            CellError::InvalidState => write!(f, "Invalid State"),
+            CellError::DependencyFailed => write!(f, "Dependency Failed"),
+            CellError::InternalError => write!(f, "Internal Error"),
+            CellError::Panic => write!(f, "Panic in Cell"),
+            CellError::NotImplemented => write!(f, "Not Implemented"),
+            CellError::HandlerFailed => write!(f, "Handler Failed"),
+        }
+    }
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport => write!(f, "transport"),
+            Self::Protocol => write!(f, "protocol"),
+            Self::Resource => write!(f, "resource"),
+            Self::State => write!(f, "state"),
+            Self::Internal => write!(f, "internal"),
         }
     }
 }
@@ -5,17 +5,39 @@ extern crate alloc;
 #[cfg(feature = "std")]
 extern crate std;
 
+pub mod codec;
 pub mod error;
 pub mod vesicle;
 
-pub use error::CellError;
-pub use vesicle::{Vesicle, VesicleHeader};
+pub use codec::Codec;
+pub use error::{CellError, ErrorCategory};
+pub use vesicle::{TraceContext, Vesicle, VesicleHeader};
 
 pub mod channel {
     pub const APP: u8 = 0;
     pub const ROUTING: u8 = 1;
     pub const OPS: u8 = 2;
     pub const MACRO_COORDINATION: u8 = 3;
+    /// Per-connection principal override (admin impersonation)
+    pub const AUTH: u8 = 4;
+    /// Per-connection wire format of APP payloads, see [`crate::codec`]
+    pub const CODEC: u8 = 5;
+    /// Calls to methods a running cell registered after it started, see
+    /// `cell_sdk::extension`
+    pub const EXTENSION: u8 = 6;
+}
+
+/// Response framing: `[u32 len][payload]`, or `[u32 len][u64 id][payload]`
+/// when answering a correlated request
+pub mod frame {
+    /// Set in the length prefix when the response came from a fallback handler
+    pub const DEGRADED: u32 = 1 << 31;
+    /// Set in the length prefix when the payload is an `ErrorResponse`
+    pub const ERROR: u32 = 1 << 30;
+    /// Set in the length prefix when the request's correlation id precedes
+    /// the payload (not counted in the length)
+    pub const CORRELATED: u32 = 1 << 29;
+    pub const LEN_MASK: u32 = !(DEGRADED | ERROR | CORRELATED);
 }
 
 #[repr(C)]
@@ -1,6 +1,8 @@
 // cell-core/src/vesicle.rs
 // SPDX-License-Identifier: MIT
 
+use alloc::format;
+use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
 
@@ -12,11 +14,83 @@ pub struct VesicleHeader {
     pub source_id: u64, // Blake3 Hash of sender cell name (for replies)
     pub ttl: u8,        // Hops remaining
     pub flags: u8,      // Reserved (0x01 = Fragment, 0x02 = Ack...)
-    pub _pad: [u8; 6],  // Alignment to 24 bytes
+    pub priority: u8,   // Call chain priority, with PRIORITIZED
+    pub _pad: [u8; 5],  // Alignment to 24 bytes
 }
 
 impl VesicleHeader {
     pub const SIZE: usize = 24;
+    /// Flag: a u64 correlation id follows the channel byte, and the response
+    /// is framed with [`crate::frame::CORRELATED`] and the same id
+    pub const CORRELATED: u8 = 0x04;
+    /// Flag: a [`TraceContext`] follows the channel byte and correlation id
+    pub const TRACED: u8 = 0x08;
+    /// Flag: `priority` is that of the call chain the request belongs to,
+    /// for the receiver to inherit
+    pub const PRIORITIZED: u8 = 0x10;
+}
+
+/// W3C trace context of a request: its trace, the caller's span, and flags.
+/// Carried on the wire as `[16 trace id][8 span id][1 flags]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TraceContext {
+    pub trace_id: [u8; 16],
+    pub span_id: [u8; 8],
+    pub flags: u8,
+}
+
+impl TraceContext {
+    pub const SIZE: usize = 25;
+    /// Flag: the caller records this trace
+    pub const SAMPLED: u8 = 0x01;
+
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[..16].copy_from_slice(&self.trace_id);
+        bytes[16..24].copy_from_slice(&self.span_id);
+        bytes[24] = self.flags;
+        bytes
+    }
+
+    /// `None` if `bytes` is short or an id is all zeros, which W3C reserves
+    /// for "invalid"
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.get(..Self::SIZE)?;
+        let ctx = Self {
+            trace_id: bytes[..16].try_into().ok()?,
+            span_id: bytes[16..24].try_into().ok()?,
+            flags: bytes[24],
+        };
+        let valid = ctx.trace_id != [0; 16] && ctx.span_id != [0; 8];
+        valid.then_some(ctx)
+    }
+
+    /// The same trace, from another span
+    pub fn with_span(self, span_id: [u8; 8]) -> Self {
+        Self { span_id, ..self }
+    }
+
+    pub fn trace_id_hex(&self) -> String {
+        hex(&self.trace_id)
+    }
+
+    pub fn span_id_hex(&self) -> String {
+        hex(&self.span_id)
+    }
+
+    /// `traceparent` header value, e.g. for HTTP gateways
+    pub fn traceparent(&self) -> String {
+        format!(
+            "00-{}-{}-{:02x}",
+            self.trace_id_hex(),
+            self.span_id_hex(),
+            self.flags
+        )
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
 }
 
 /// A wrapper around a data buffer.
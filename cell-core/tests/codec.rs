@@ -0,0 +1,28 @@
+use cell_core::Codec;
+
+#[test]
+fn the_first_supported_offer_wins() {
+    let offer = Codec::offer(&[Codec::MsgPack, Codec::Json]);
+    assert_eq!(Codec::negotiate(&offer, |_| true), Codec::MsgPack);
+    assert_eq!(
+        Codec::negotiate(&offer, |c| c != Codec::MsgPack),
+        Codec::Json
+    );
+}
+
+#[test]
+fn rkyv_is_the_fallback() {
+    assert_eq!(Codec::negotiate(&[], |_| true), Codec::Rkyv);
+    assert_eq!(Codec::negotiate(&[1, 2], |c| c == Codec::Rkyv), Codec::Rkyv);
+    // Codecs from newer clients are skipped
+    assert_eq!(Codec::negotiate(&[42, 1], |_| true), Codec::Json);
+}
+
+#[test]
+fn names_round_trip() {
+    for codec in Codec::ALL {
+        assert_eq!(Codec::from_name(codec.name()), Some(codec));
+        assert_eq!(Codec::from_u8(codec as u8), Some(codec));
+    }
+    assert_eq!(Codec::from_name("cbor"), None);
+}
@@ -0,0 +1,27 @@
+use cell_core::TraceContext;
+
+#[test]
+fn trace_context_round_trips() {
+    let ctx = TraceContext {
+        trace_id: [0xab; 16],
+        span_id: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08],
+        flags: TraceContext::SAMPLED,
+    };
+    let mut frame = ctx.to_bytes().to_vec();
+    frame.extend_from_slice(b"payload");
+    assert_eq!(TraceContext::from_bytes(&frame), Some(ctx));
+    assert_eq!(
+        ctx.traceparent(),
+        "00-abababababababababababababababab-0102030405060708-01"
+    );
+}
+
+#[test]
+fn invalid_contexts_are_rejected() {
+    assert_eq!(TraceContext::from_bytes(&[1; 24]), None);
+    assert_eq!(TraceContext::from_bytes(&[0; 25]), None);
+
+    let mut no_span = [1u8; 25];
+    no_span[16..24].fill(0);
+    assert_eq!(TraceContext::from_bytes(&no_span), None);
+}
@@ -41,7 +41,6 @@ pub fn expand_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
     };
 
     let item_struct = parse_macro_input!(item as ItemStruct);
-    let struct_name = item_struct.ident.clone();
 
     // Convert struct back to string to pass to runner
     let struct_source = quote! { #item_struct }.to_string();
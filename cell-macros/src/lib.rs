@@ -6,17 +6,27 @@ use proc_macro::TokenStream;
 use quote::{format_ident, quote};
 use syn::{parse::Parse, parse_macro_input, ItemImpl, Type, FnArg, Pat, ReturnType, Token, Ident, LitStr, GenericArgument, PathArguments, Item};
 use convert_case::{Case, Casing};
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use std::collections::HashMap;
 
 mod expand;
+mod service;
+mod stats;
+#[allow(dead_code)]
 mod test;
+#[allow(dead_code)]
 mod coordination;
 
 // === CELL_REMOTE ===
 struct CellRemoteArgs {
     module_name: Ident,
     cell_name: String,
+    /// `methods = [a, b]`: generate only these client methods
+    methods: Option<Vec<Ident>>,
+    /// `serde = false`: rkyv derives only
+    serde: bool,
+    /// `proteins = Ledger`: import proteins from another `cell_remote!` module
+    /// instead of generating them again
+    proteins: Option<syn::Path>,
 }
 
 impl Parse for CellRemoteArgs {
@@ -24,78 +34,263 @@ impl Parse for CellRemoteArgs {
         let module_name: Ident = input.parse()?;
         input.parse::<Token![=]>()?;
         let cell_name_lit: LitStr = input.parse()?;
-        Ok(CellRemoteArgs { module_name, cell_name: cell_name_lit.value() })
+
+        let mut methods = None;
+        let mut serde = true;
+        let mut proteins = None;
+        while input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            if key == "methods" {
+                let list;
+                syn::bracketed!(list in input);
+                let names = list.parse_terminated(Ident::parse, Token![,])?;
+                methods = Some(names.into_iter().collect());
+            } else if key == "serde" {
+                serde = input.parse::<syn::LitBool>()?.value;
+            } else if key == "proteins" {
+                proteins = Some(input.parse()?);
+            } else {
+                return Err(syn::Error::new(key.span(), "expected `methods = [...]`, `serde = false` or `proteins = Module`"));
+            }
+        }
+        Ok(CellRemoteArgs { module_name, cell_name: cell_name_lit.value(), methods, serde, proteins })
+    }
+}
+
+/// Derives every protein and protocol enum gets. Without serde only rkyv is
+/// derived, which is all cells need on the wire; JSON bridges (cell-py,
+/// cell-node, codec transcoding, slowlog arguments) need serde too.
+fn wire_derives(serde: bool) -> proc_macro2::TokenStream {
+    let serde = serde.then(|| quote! {
+        #[derive(::cell_sdk::serde::Serialize, ::cell_sdk::serde::Deserialize)]
+        #[serde(crate = "::cell_sdk::serde")]
+    });
+    quote! {
+        #serde
+        #[derive(::cell_sdk::rkyv::Archive, ::cell_sdk::rkyv::Serialize, ::cell_sdk::rkyv::Deserialize)]
+        #[archive(check_bytes)]
+        #[archive(crate = "::cell_sdk::rkyv")]
+    }
+}
+
+/// `serde = false` in `#[protein(...)]`
+fn protein_serde(attrs: &[syn::Attribute]) -> syn::Result<bool> {
+    let mut serde = true;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("protein")) {
+        if matches!(attr.meta, syn::Meta::Path(_)) {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("serde") {
+                serde = meta.value()?.parse::<syn::LitBool>()?.value;
+                Ok(())
+            } else {
+                Err(meta.error("expected `serde = false`"))
+            }
+        })?;
     }
+    Ok(serde)
 }
 
+/// `cell_remote!(Ledger = "ledger")` generates `Ledger::Client` and the
+/// proteins its methods take and return. Options, after the cell name:
+///
+/// - `methods = [deposit, balance]`: only these client methods
+/// - `serde = false`: proteins and protocol derive rkyv only, which is all a
+///   client talking to cells needs
+/// - `proteins = Ledger`: import the proteins from another `cell_remote!`
+///   module, or `crate` for a cell calling itself, instead of generating them
+///   again; that module must reach every protein this one does
+///
+/// `CELL_CODEGEN_STATS` and `CELL_CODEGEN_BUDGET` report and cap the size of
+/// the expansion (see `stats.rs`).
 #[proc_macro]
 pub fn cell_remote(input: TokenStream) -> TokenStream {
     let args = parse_macro_input!(input as CellRemoteArgs);
     let module_name = args.module_name;
     let cell_name = &args.cell_name;
+    let stats = stats::Stats::start("cell_remote!", cell_name);
 
-    println!("cargo:warning=cell_remote! macro running for cell: {}", cell_name);
-    println!("cargo:warning=This should appear during build!");
-
-    // 1. Fetch Source (Filesystem Only - No RPC)
-    let source_code = fetch_remote_source_or_fallback(cell_name);
+    // 1. Fetch Schema (Filesystem Only - No RPC)
+    let schema_source = fetch_remote_schema(cell_name);
     
     // 2. Extract Methods
-    let methods = extract_handler_methods(&source_code);
+    let methods = extract_handler_methods(&schema_source);
     if methods.is_empty() {
         return syn::Error::new(
             module_name.span(), 
-            format!("No #[handler] methods found for '{}'. Ensure source is accessible in Workspace or Registry.", cell_name)
+            format!("No #[handler] methods found for '{}'. Ensure its .cell-schema artifact or source is accessible in Workspace or Registry.", cell_name)
         ).to_compile_error().into();
     }
 
-    // 3. Extract Proteins
-    let proteins = extract_proteins(&source_code);
+    // Only the selected methods get client code; the rest stay as placeholder
+    // variants so discriminants still match the cell's protocol enum
+    if let Some(selected) = &args.methods {
+        if let Some(unknown) = selected.iter().find(|m| !methods.iter().any(|(name, _, _)| name == *m)) {
+            return syn::Error::new(
+                unknown.span(),
+                format!("'{}' has no #[handler] method '{}'", cell_name, unknown)
+            ).to_compile_error().into();
+        }
+    }
+    let wanted = |name: &Ident| args.methods.as_ref().is_none_or(|selected| selected.contains(name));
+
+    // Checked against the published schema by assert_compatible!
+    let schema = cell_build::schema::Schema::from_source(&schema_source).unwrap_or_default();
+    let schema_fingerprint = schema.fingerprint();
+
+    // 3. Extract Proteins (those the selected methods reach)
+    let proteins = extract_proteins(&schema_source);
+    let mut used = std::collections::HashSet::new();
+    for (_, params, ret_type) in methods.iter().filter(|(name, _, _)| wanted(name)) {
+        for (_, arg_type) in params {
+            collect_idents(quote! { #arg_type }, &mut used);
+        }
+        collect_idents(quote! { #ret_type }, &mut used);
+    }
+    loop {
+        let before = used.len();
+        for (name, _, item) in &proteins {
+            if used.contains(&name.to_string()) {
+                collect_idents(item.clone(), &mut used);
+            }
+        }
+        if used.len() == before {
+            break;
+        }
+    }
+    let proteins: Vec<_> = proteins
+        .into_iter()
+        .filter(|(name, _, _)| used.contains(&name.to_string()))
+        .collect();
+    let protein_count = proteins.len();
+    let proteins: Vec<_> = match &args.proteins {
+        // Shared with another client of the cell, which must reach them too
+        Some(shared) => {
+            let shared = if shared.leading_colon.is_some() || shared.segments[0].ident == "crate" {
+                quote! { #shared }
+            } else {
+                quote! { super::#shared }
+            };
+            let names = proteins.iter().map(|(name, _, _)| name);
+            vec![quote! { pub use #shared::{#(#names),*}; }]
+        }
+        None => proteins
+            .into_iter()
+            .map(|(_, serde, item)| {
+                let derives = wire_derives(serde && args.serde);
+                quote! {
+                    #derives
+                    #[derive(Clone, Debug, PartialEq)]
+                    #item
+                }
+            })
+            .collect(),
+    };
+    let wire = wire_derives(args.serde);
+
+    // Methods marked #[handler(read)] may be served by read replicas
+    let read_methods = extract_read_methods(&schema_source);
+    let has_reads = !read_methods.is_empty();
 
     let protocol_name = format_ident!("{}Protocol", cell_name.to_case(Case::Pascal));
     let response_name = format_ident!("{}Response", cell_name.to_case(Case::Pascal));
 
     let req_variants: Vec<_> = methods.iter().map(|(name, args, _)| {
         let variant_name = format_ident!("{}", name.to_string().to_case(Case::Pascal));
+        if !wanted(name) {
+            return skipped_variant(&variant_name);
+        }
         let fields: Vec<_> = args.iter().map(|(arg_name, arg_type)| quote! { #arg_name: #arg_type }).collect();
         quote! { #variant_name { #(#fields),* } }
     }).collect();
 
     let resp_variants: Vec<_> = methods.iter().map(|(name, _, ret_type)| {
         let variant_name = format_ident!("{}", name.to_string().to_case(Case::Pascal));
+        if !wanted(name) {
+            return skipped_variant(&variant_name);
+        }
         quote! { #variant_name(#ret_type) }
     }).collect();
 
-    let client_methods: Vec<_> = methods.iter().map(|(name, args, ret_type)| {
+    let client_methods: Vec<_> = methods.iter().filter(|(name, _, _)| wanted(name)).map(|(name, args, ret_type)| {
         let variant_name = format_ident!("{}", name.to_string().to_case(Case::Pascal));
         let arg_sigs: Vec<_> = args.iter().map(|(arg_name, arg_type)| quote! { #arg_name: #arg_type }).collect();
         let arg_names: Vec<_> = args.iter().map(|(arg_name, _)| arg_name).collect();
 
+        let name_str = name.to_string();
+        let fire = if read_methods.contains(name) {
+            quote! {
+                // Reads go to a replica; if it cannot be reached, to the leader
+                let fired = match self.replica() {
+                    Some(replica) => match replica.fire(&req).await {
+                        Err(e) if !::cell_sdk::error::is_remote(&e) => self.conn.fire(&req).await,
+                        other => other,
+                    },
+                    None => self.conn.fire(&req).await,
+                };
+            }
+        } else {
+            quote! { let fired = self.conn.fire(&req).await; }
+        };
+
+        // Retry policy advertised by the cell, see cell_sdk::retry
+        let retry = match schema.methods.get(&name_str).and_then(|m| m.retry) {
+            Some(cell_build::schema::RetryPolicy { idempotent, max_attempts, backoff_ms }) => quote! {
+                Some(::cell_sdk::retry::RetryPolicy {
+                    idempotent: #idempotent,
+                    max_attempts: #max_attempts,
+                    backoff_ms: #backoff_ms,
+                })
+            },
+            None => quote! { None },
+        };
+
         quote! {
             pub async fn #name(&self, #(#arg_sigs),*) -> ::anyhow::Result<#ret_type> {
+                use ::cell_sdk::error::{CellError, ErrorContext};
+                let fail = |code: CellError, message: String| {
+                    // Accepted in compat mode: say why the response does not decode
+                    let message = match self.conn.schema_mismatch() {
+                        Some((built, served)) if matches!(code, CellError::DeserializationFailure | CellError::ProtocolMismatch) => format!(
+                            "{} (client built against schema {:016x}, cell serves {:016x})", message, built, served
+                        ),
+                        _ => message,
+                    };
+                    ::anyhow::Error::from(ErrorContext::new(code).with_message(message).with_operation(#name_str))
+                };
                 let req = #protocol_name::#variant_name { #(#arg_names),* };
                 
                 // CHANGED: Use ResilientSynapse for automatic reconnection
-                let resp_wrapper = self.conn.fire(&req).await
-                    .map_err(|e| ::anyhow::anyhow!("RPC Error: {}", e))?;
+                // Errors reported by the cell keep their code and origin
+                let fired = ::cell_sdk::retry::run(self.options.policy(#retry), || {
+                    ::cell_sdk::retry::bounded(self.options.timeout, async {
+                        #fire
+                        fired
+                    })
+                }).await;
+                let resp_wrapper = fired.map_err(|e| ::anyhow::Error::from(
+                    ErrorContext::classify(&e, CellError::TransportUnavailable).with_operation(#name_str)
+                ))?;
                 
                 let resp_bytes = resp_wrapper.into_owned();
                 
                 if resp_bytes.is_empty() {
-                    return Err(::anyhow::anyhow!("Empty response from cell"));
+                    return Err(fail(CellError::InvalidMessage, "Empty response from cell".into()));
                 }
 
                 let archived = ::cell_sdk::rkyv::check_archived_root::<#response_name>(&resp_bytes)
-                    .map_err(|e| ::anyhow::anyhow!("Validation Error: {}", e))?;
+                    .map_err(|e| fail(CellError::DeserializationFailure, format!("Validation Error: {}", e)))?;
                 
                 let resp: #response_name = ::cell_sdk::rkyv::Deserialize::deserialize(
                     archived, 
                     &mut ::cell_sdk::rkyv::de::deserializers::SharedDeserializeMap::new()
-                ).map_err(|e| ::anyhow::anyhow!("Deserialization Error: {}", e))?;
+                ).map_err(|e| fail(CellError::DeserializationFailure, format!("Deserialization Error: {}", e)))?;
                     
                 match resp {
                     #response_name::#variant_name(result) => Ok(result),
-                    _ => Err(::anyhow::anyhow!("Protocol Mismatch")),
+                    _ => Err(fail(CellError::ProtocolMismatch, "Protocol Mismatch".into())),
                 }
             }
         }
@@ -107,37 +302,81 @@ pub fn cell_remote(input: TokenStream) -> TokenStream {
         pub mod #module_name {
             use ::cell_sdk::*;
 
+            /// Fingerprint of the cell schema this client was generated from
+            pub const SCHEMA_FINGERPRINT: u64 = #schema_fingerprint;
+
             #(#proteins)*
 
-            #[derive(::cell_sdk::serde::Serialize, ::cell_sdk::serde::Deserialize)]
-            #[derive(::cell_sdk::rkyv::Archive, ::cell_sdk::rkyv::Serialize, ::cell_sdk::rkyv::Deserialize)]
-            #[archive(check_bytes)]
-            #[serde(crate = "::cell_sdk::serde")]
-            #[archive(crate = "::cell_sdk::rkyv")]
+            #wire
             pub enum #protocol_name { #(#req_variants),* }
 
-            #[derive(::cell_sdk::serde::Serialize, ::cell_sdk::serde::Deserialize)]
-            #[derive(::cell_sdk::rkyv::Archive, ::cell_sdk::rkyv::Serialize, ::cell_sdk::rkyv::Deserialize)]
-            #[archive(check_bytes)]
-            #[serde(crate = "::cell_sdk::serde")]
-            #[archive(crate = "::cell_sdk::rkyv")]
+            #wire
             pub enum #response_name { #(#resp_variants),* }
 
+            /// Call timeout and retry overrides for [`Client::connect_with`]
+            pub use ::cell_sdk::retry::ClientOptions as Options;
+
             pub struct Client {
                 // CHANGED: Use ResilientSynapse instead of Arc<Synapse>
-                conn: ::cell_sdk::ResilientSynapse,
+                // Calls spread over `options.connections` synapses
+                conn: ::cell_sdk::SynapsePool,
+                // Read replicas, used round-robin by read methods
+                replicas: ::std::sync::Arc<[::cell_sdk::ResilientSynapse]>,
+                next_replica: ::std::sync::Arc<::std::sync::atomic::AtomicUsize>,
+                options: Options,
             }
 
             impl Client {
                 pub async fn connect() -> ::anyhow::Result<Self> {
+                    Self::connect_with(Options::default()).await
+                }
+
+                /// Connect with a call timeout and retry overrides for every call
+                pub async fn connect_with(options: Options) -> ::anyhow::Result<Self> {
                     // CHANGED: Use ResilientSynapse::grow for automatic reconnection
-                    let conn = ::cell_sdk::ResilientSynapse::grow(#cell_name).await?;
-                    Ok(Self { conn })
+                    // Fails fast if the cell serves another schema (see cell_sdk::source)
+                    let config = ::cell_sdk::ResilienceConfig {
+                        schema_fingerprint: SCHEMA_FINGERPRINT,
+                        multiplex: options.multiplex,
+                        ..::std::default::Default::default()
+                    };
+                    let size = options.connections.unwrap_or(1);
+                    let conn = ::cell_sdk::SynapsePool::grow_with_config(#cell_name, size, config).await?;
+                    let mut client = Self::new(conn).with_options(options);
+                    if #has_reads {
+                        client.replicas = ::cell_sdk::replica::discover(#cell_name).await.into();
+                    }
+                    Ok(client)
                 }
                 
-                // CHANGED: Constructor takes ResilientSynapse
-                pub fn new(conn: ::cell_sdk::ResilientSynapse) -> Self {
-                    Self { conn }
+                // CHANGED: Constructor takes ResilientSynapse or a SynapsePool
+                pub fn new(conn: impl ::std::convert::Into<::cell_sdk::SynapsePool>) -> Self {
+                    Self {
+                        conn: conn.into(),
+                        replicas: ::std::sync::Arc::from([]),
+                        next_replica: ::std::default::Default::default(),
+                        options: Options::default(),
+                    }
+                }
+
+                pub fn with_options(mut self, options: Options) -> Self {
+                    self.options = options;
+                    self
+                }
+
+                /// A handle to the same connection whose calls give up after `timeout`
+                pub fn timeout(&self, timeout: ::std::time::Duration) -> Self {
+                    let mut client = self.clone();
+                    client.options.timeout = Some(timeout);
+                    client
+                }
+
+                fn replica(&self) -> Option<&::cell_sdk::ResilientSynapse> {
+                    if self.replicas.is_empty() {
+                        return None;
+                    }
+                    let i = self.next_replica.fetch_add(1, ::std::sync::atomic::Ordering::Relaxed);
+                    self.replicas.get(i % self.replicas.len())
                 }
 
                 // NEW: Get connection state for monitoring
@@ -147,7 +386,7 @@ pub fn cell_remote(input: TokenStream) -> TokenStream {
 
                 // NEW: Get connection metrics
                 pub async fn metrics(&self) -> ::cell_sdk::ConnMetrics {
-                    self.conn.metrics.read().await.clone()
+                    self.conn.metrics().await
                 }
 
                 // NEW: Force reconnection if needed
@@ -163,16 +402,105 @@ pub fn cell_remote(input: TokenStream) -> TokenStream {
                 fn clone(&self) -> Self {
                     Self {
                         conn: self.conn.clone(),
+                        replicas: self.replicas.clone(),
+                        next_replica: self.next_replica.clone(),
+                        options: self.options,
                     }
                 }
             }
         }
     };
-    TokenStream::from(expanded)
+    stats.finish(expanded, protein_count, methods.len()).into()
+}
+
+// === ASSERT_COMPATIBLE ===
+struct AssertCompatibleArgs {
+    module_name: Ident,
+    cell_name: LitStr,
+}
+
+impl Parse for AssertCompatibleArgs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let module_name: Ident = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let cell_name: LitStr = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(AssertCompatibleArgs { module_name, cell_name })
+    }
 }
 
-fn fetch_remote_source_or_fallback(cell_name: &str) -> String {
-    // 1. Check Workspace (Monorepo)
+/// `assert_compatible!(Ledger, "ledger")`: fail the build unless the client
+/// generated by `cell_remote!(Ledger = "ledger")` matches the schema pinned in
+/// `.cell/schema/ledger.lock.json`, or failing that the registry copy.
+#[proc_macro]
+pub fn assert_compatible(input: TokenStream) -> TokenStream {
+    use cell_build::schema::{self, Schema};
+
+    let args = parse_macro_input!(input as AssertCompatibleArgs);
+    let module_name = args.module_name;
+    let cell_name = args.cell_name.value();
+    let error = |message: String| syn::Error::new(args.cell_name.span(), message).to_compile_error().into();
+
+    let local = match Schema::from_source(&fetch_remote_schema(&cell_name)) {
+        Ok(schema) => schema,
+        Err(e) => return error(format!("Could not parse the schema of '{}': {}", cell_name, e)),
+    };
+
+    let crate_dir = std::path::PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default());
+    let (published, origin) = match schema::published(&crate_dir, &cell_name) {
+        Ok(Some(found)) => found,
+        Ok(None) => return error(format!(
+            "No published schema for '{}'. Pin one at {:?} or install the cell into the registry.",
+            cell_name, schema::lock_path(&crate_dir, &cell_name)
+        )),
+        Err(e) => return error(format!("Could not load the published schema of '{}': {}", cell_name, e)),
+    };
+
+    let changes = local.diff(&published);
+    if !changes.is_empty() {
+        let lines: Vec<String> = changes.iter().map(|c| c.to_string()).collect();
+        return error(format!(
+            "`{}` is incompatible with the schema of '{}' published at {:?}:\n{}",
+            module_name, cell_name, origin, lines.join("\n")
+        ));
+    }
+
+    let fingerprint = published.fingerprint();
+    let message = format!("`{}` was not generated from the published schema of '{}'", module_name, cell_name);
+    quote! {
+        const _: () = ::std::assert!(#module_name::SCHEMA_FINGERPRINT == #fingerprint, #message);
+    }.into()
+}
+
+/// Public surface (proteins and handler signatures, see
+/// `cell_build::spore::extract_schema`) of a remote cell, from the first of:
+/// a `.cell-schema` artifact vendored in `.cell/schema/`, the cell's source in
+/// the workspace, the artifact its last build emitted to `~/.cell/schema/`,
+/// the registry. Workspace source wins over emitted artifacts because the cell
+/// may not have been rebuilt since its last edit.
+fn fetch_remote_schema(cell_name: &str) -> String {
+    use cell_build::schema::{artifact_dir, artifact_path, SchemaArtifact};
+
+    let surface = |main: &std::path::Path| {
+        cell_build::load_and_flatten_source(main)
+            .map(|file| cell_build::spore::extract_schema(&file))
+            .unwrap_or_default()
+    };
+    let artifact = |path: std::path::PathBuf| {
+        SchemaArtifact::load(&path)
+            .unwrap_or_else(|e| panic!("Bad schema artifact for cell '{}': {}", cell_name, e))
+            .surface
+    };
+
+    // 1. Vendored artifact
+    if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
+        let vendored = artifact_path(&std::path::Path::new(&manifest_dir).join(".cell/schema"), cell_name);
+        if vendored.exists() {
+            return artifact(vendored);
+        }
+    }
+
+    // 2. Check Workspace (Monorepo)
     if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
         let current = std::path::Path::new(&manifest_dir);
         let mut candidate_root = current;
@@ -187,7 +515,7 @@ fn fetch_remote_source_or_fallback(cell_name: &str) -> String {
             
             for c in &candidates {
                 if c.exists() {
-                    return std::fs::read_to_string(c).unwrap_or_default();
+                    return surface(c);
                 }
             }
             if let Some(p) = candidate_root.parent() {
@@ -198,49 +526,121 @@ fn fetch_remote_source_or_fallback(cell_name: &str) -> String {
         }
     }
 
-    // 2. Check Registry
-    let home = dirs::home_dir().expect("No HOME");
-    let registry = home.join(".cell/registry").join(cell_name);
-    let c = registry.join("src/main.rs");
-    if c.exists() { return std::fs::read_to_string(c).unwrap_or_default(); }
+    // 3. Emitted artifact
+    if let Some(emitted) = artifact_dir().map(|dir| artifact_path(&dir, cell_name)).filter(|p| p.exists()) {
+        return artifact(emitted);
+    }
+
+    // 4. Check Registry
+    if let Some(c) = cell_build::schema::registry_source(cell_name) {
+        return surface(&c);
+    }
+
+    panic!("Could not find the schema of cell '{}'. Vendor its .cell-schema artifact, or it must be in the workspace or ~/.cell/registry", cell_name);
+}
+
+/// `#[handler]` impl blocks of `service`, in source order
+fn handler_blocks(items: &[Item], service: &Ident) -> Vec<ItemImpl> {
+    let mut blocks = Vec::new();
+    for item in items {
+        match item {
+            Item::Impl(i) if i.attrs.iter().any(|a| a.path().is_ident("handler")) => {
+                if let Type::Path(p) = &*i.self_ty {
+                    if p.path.segments.last().is_some_and(|s| s.ident == *service) {
+                        blocks.push(i.clone());
+                    }
+                }
+            }
+            Item::Mod(m) => {
+                if let Some((_, content)) = &m.content {
+                    blocks.extend(handler_blocks(content, service));
+                }
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
 
-    panic!("Could not find source for cell '{}'. It must be in the workspace or ~/.cell/registry", cell_name);
+fn fn_names(items: &[syn::ImplItem]) -> Vec<String> {
+    items.iter().filter_map(|item| match item {
+        syn::ImplItem::Fn(m) => Some(m.sig.ident.to_string()),
+        _ => None,
+    }).collect()
 }
 
-fn extract_proteins(src: &str) -> Vec<proc_macro2::TokenStream> {
+/// Stand-in for a method left out with `methods = [...]`
+fn skipped_variant(variant_name: &Ident) -> proc_macro2::TokenStream {
+    let skipped = format_ident!("__{}", variant_name);
+    quote! {
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        #skipped
+    }
+}
+
+fn collect_idents(tokens: proc_macro2::TokenStream, out: &mut std::collections::HashSet<String>) {
+    for tree in tokens {
+        match tree {
+            proc_macro2::TokenTree::Ident(i) => { out.insert(i.to_string()); }
+            proc_macro2::TokenTree::Group(g) => collect_idents(g.stream(), out),
+            _ => {}
+        }
+    }
+}
+
+/// Check a protein's parameters and qualify bare `Archive` bounds, which the
+/// rkyv derives need and `use cell_sdk::*` does not bring into scope.
+fn protein_generics(generics: &mut syn::Generics) -> syn::Result<()> {
+    if let Some(lifetime) = generics.lifetimes().next() {
+        return Err(syn::Error::new_spanned(
+            lifetime,
+            "proteins cross process boundaries and must own their data; use String or Vec instead of borrows",
+        ));
+    }
+    let qualify = |bounds: &mut syn::punctuated::Punctuated<syn::TypeParamBound, syn::Token![+]>| {
+        for bound in bounds {
+            if let syn::TypeParamBound::Trait(t) = bound {
+                if t.path.is_ident("Archive") {
+                    t.path = syn::parse_quote!(::cell_sdk::rkyv::Archive);
+                }
+            }
+        }
+    };
+    for param in generics.type_params_mut() {
+        qualify(&mut param.bounds);
+    }
+    if let Some(where_clause) = &mut generics.where_clause {
+        for predicate in &mut where_clause.predicates {
+            if let syn::WherePredicate::Type(t) = predicate {
+                qualify(&mut t.bounds);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// (protein name, whether it derives serde, item without `#[protein]`)
+fn extract_proteins(src: &str) -> Vec<(Ident, bool, proc_macro2::TokenStream)> {
     let syntax = syn::parse_file(src).unwrap_or_else(|_| syn::File { items: vec![], shebang: None, attrs: vec![] });
     let mut proteins = Vec::new();
     for item in syntax.items {
         match item {
-            Item::Struct(mut s) => {
-                if s.attrs.iter().any(|a| a.path().is_ident("protein")) {
-                    s.attrs.retain(|a| !a.path().is_ident("protein"));
-                    let tokens = quote! {
-                        #[derive(::cell_sdk::serde::Serialize, ::cell_sdk::serde::Deserialize)]
-                        #[derive(::cell_sdk::rkyv::Archive, ::cell_sdk::rkyv::Serialize, ::cell_sdk::rkyv::Deserialize)]
-                        #[archive(check_bytes)]
-                        #[serde(crate = "::cell_sdk::serde")]
-                        #[archive(crate = "::cell_sdk::rkyv")]
-                        #[derive(Clone, Debug, PartialEq)]
-                        #s
-                    };
-                    proteins.push(tokens);
+            Item::Struct(mut s) if s.attrs.iter().any(|a| a.path().is_ident("protein")) => {
+                let Ok(serde) = protein_serde(&s.attrs) else { continue };
+                s.attrs.retain(|a| !a.path().is_ident("protein"));
+                if protein_generics(&mut s.generics).is_err() {
+                    continue;
                 }
+                proteins.push((s.ident.clone(), serde, quote! { #s }));
             }
-            Item::Enum(mut e) => {
-                if e.attrs.iter().any(|a| a.path().is_ident("protein")) {
-                    e.attrs.retain(|a| !a.path().is_ident("protein"));
-                    let tokens = quote! {
-                        #[derive(::cell_sdk::serde::Serialize, ::cell_sdk::serde::Deserialize)]
-                        #[derive(::cell_sdk::rkyv::Archive, ::cell_sdk::rkyv::Serialize, ::cell_sdk::rkyv::Deserialize)]
-                        #[archive(check_bytes)]
-                        #[serde(crate = "::cell_sdk::serde")]
-                        #[archive(crate = "::cell_sdk::rkyv")]
-                        #[derive(Clone, Debug, PartialEq)]
-                        #e
-                    };
-                    proteins.push(tokens);
+            Item::Enum(mut e) if e.attrs.iter().any(|a| a.path().is_ident("protein")) => {
+                let Ok(serde) = protein_serde(&e.attrs) else { continue };
+                e.attrs.retain(|a| !a.path().is_ident("protein"));
+                if protein_generics(&mut e.generics).is_err() {
+                    continue;
                 }
+                proteins.push((e.ident.clone(), serde, quote! { #e }));
             }
             _ => {}
         }
@@ -248,7 +648,16 @@ fn extract_proteins(src: &str) -> Vec<proc_macro2::TokenStream> {
     proteins
 }
 
-fn extract_handler_methods(src: &str) -> Vec<(Ident, Vec<(Ident, Type)>, Type)> {
+/// Method to call when a handler method fails or times out
+struct Fallback {
+    method: Ident,
+    timeout_ms: Option<u64>,
+}
+
+/// (method name, arguments without the `&CallContext`, return type)
+type HandlerMethod = (Ident, Vec<(Ident, Type)>, Type);
+
+fn extract_handler_methods(src: &str) -> Vec<HandlerMethod> {
     let syntax = syn::parse_file(src).unwrap_or_else(|_| syn::File { items: vec![], shebang: None, attrs: vec![] });
     let mut methods = Vec::new();
     for item in syntax.items {
@@ -256,9 +665,15 @@ fn extract_handler_methods(src: &str) -> Vec<(Ident, Vec<(Ident, Type)>, Type)>
             if i.attrs.iter().any(|a| a.path().is_ident("handler")) {
                 for impl_item in i.items {
                     if let syn::ImplItem::Fn(m) = impl_item {
+                        if m.attrs.iter().any(|a| a.path().is_ident("subscriber")) {
+                            continue;
+                        }
                         let name = m.sig.ident;
                         let args: Vec<_> = m.sig.inputs.iter().filter_map(|arg| {
                             if let FnArg::Typed(pt) = arg {
+                                if cell_build::schema::is_call_context(&pt.ty) {
+                                    return None;
+                                }
                                 if let Pat::Ident(pi) = &*pt.pat {
                                     return Some((pi.ident.clone(), *pt.ty.clone()));
                                 }
@@ -275,6 +690,44 @@ fn extract_handler_methods(src: &str) -> Vec<(Ident, Vec<(Ident, Type)>, Type)>
     methods
 }
 
+/// Names of handler methods marked `#[handler(read)]`
+fn extract_read_methods(src: &str) -> Vec<Ident> {
+    let syntax = syn::parse_file(src).unwrap_or_else(|_| syn::File { items: vec![], shebang: None, attrs: vec![] });
+    let mut reads = Vec::new();
+    for item in syntax.items {
+        let syn::Item::Impl(i) = item else { continue };
+        if !i.attrs.iter().any(|a| a.path().is_ident("handler")) {
+            continue;
+        }
+        for impl_item in i.items {
+            let syn::ImplItem::Fn(m) = impl_item else { continue };
+            let is_read = m.attrs.iter().filter(|a| a.path().is_ident("handler")).any(|a| {
+                let mut read = false;
+                let _ = a.parse_nested_meta(|meta| {
+                    read |= meta.path.is_ident("read");
+                    // Skip values of other keys (fallback = "...", retry(...))
+                    if meta.input.peek(Token![=]) {
+                        meta.value()?.parse::<syn::Lit>()?;
+                    } else if meta.input.peek(syn::token::Paren) {
+                        meta.parse_nested_meta(|inner| {
+                            if inner.input.peek(Token![=]) {
+                                inner.value()?.parse::<syn::Lit>()?;
+                            }
+                            Ok(())
+                        })?;
+                    }
+                    Ok(())
+                });
+                read
+            });
+            if is_read {
+                reads.push(m.sig.ident);
+            }
+        }
+    }
+    reads
+}
+
 /// Extracts T from Result<T, E> or returns the type as-is
 fn extract_ok_type(ret: &ReturnType) -> Type {
     match ret {
@@ -298,18 +751,45 @@ fn extract_ok_type(ret: &ReturnType) -> Type {
 
 // === ATTRIBUTE MACROS ===
 
+/// `#[service]` on the struct a `#[handler]` impl serves generates
+/// `Service::builder()`: one setter per field (db handles, clients of other
+/// cells, config) and a `build()` that names any field left unset. Tests build
+/// the service the same way with fakes; fields marked `#[service(default)]`
+/// may be skipped.
 #[proc_macro_attribute]
-pub fn service(_: TokenStream, item: TokenStream) -> TokenStream { item }
+pub fn service(_: TokenStream, item: TokenStream) -> TokenStream {
+    service::service_impl(item)
+}
 
+/// Wire type: rkyv for cells, serde for everything else.
+///
+/// Proteins may be generic, e.g. `#[protein] struct Page<T: Archive> { items:
+/// Vec<T>, next: Option<String> }`; a bare `Archive` bound means rkyv's. They
+/// must own their data, so lifetime parameters are rejected.
+///
+/// `#[protein(serde = false)]` derives rkyv only, for proteins that never
+/// leave cells as JSON. Handler protocols derive serde, so such a protein can
+/// be stored or emitted but not passed to or returned from a handler.
 #[proc_macro_attribute]
-pub fn protein(_: TokenStream, item: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(item as syn::DeriveInput);
+pub fn protein(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut serde = true;
+    let attr_parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("serde") {
+            serde = meta.value()?.parse::<syn::LitBool>()?.value;
+            Ok(())
+        } else {
+            Err(meta.error("expected `serde = false`"))
+        }
+    });
+    parse_macro_input!(attr with attr_parser);
+
+    let mut input = parse_macro_input!(item as syn::DeriveInput);
+    if let Err(e) = protein_generics(&mut input.generics) {
+        return e.to_compile_error().into();
+    }
+    let derives = wire_derives(serde);
     let expanded = quote! {
-        #[derive(::cell_sdk::serde::Serialize, ::cell_sdk::serde::Deserialize)]
-        #[derive(::cell_sdk::rkyv::Archive, ::cell_sdk::rkyv::Serialize, ::cell_sdk::rkyv::Deserialize)]
-        #[archive(check_bytes)]
-        #[serde(crate = "::cell_sdk::serde")]
-        #[archive(crate = "::cell_sdk::rkyv")]
+        #derives
         #[derive(Clone, Debug, PartialEq)]
         #input
     };
@@ -321,26 +801,192 @@ pub fn expand(attr: TokenStream, item: TokenStream) -> TokenStream {
     expand::expand_impl(attr, item)
 }
 
+/// `#[handler]`, or `#[handler(actor_key = "order_id")]` to run calls with the
+/// same `order_id` argument one at a time through a per-key mailbox.
+///
+/// Methods may carry `#[handler(fallback = "cached_quote", timeout_ms = 500)]`:
+/// if the method fails (or exceeds the optional timeout), `cached_quote` is
+/// called with the same arguments and the response is tagged as degraded.
+///
+/// `#[handler(read)]` marks a method that does not write: clients generated by
+/// `cell_remote!` send it to a read replica of the cell when one is running.
+///
+/// `#[handler(priority = 90)]` sets the method's load-shedding priority (0-255,
+/// default 50, 100 and above never shed; see `cell_sdk::shed`).
+///
+/// `#[handler(retry(max_attempts = 3, backoff_ms = 50, idempotent))]` publishes
+/// a retry policy that clients generated by `cell_remote!` apply to the method
+/// (see `cell_sdk::retry`).
+///
+/// A method whose first argument is `ctx: &CallContext` gets the caller's
+/// identity, organism, trace id and deadline with every call; callers do not
+/// send it (see `cell_sdk::context`). With a fallback `timeout_ms`, the
+/// primary's deadline is its budget.
+///
+/// `#[subscriber("ledger.deposits")]` on `async fn on_deposit(&self, event:
+/// Deposited) -> Result<()>` leaves the method out of the protocol and calls
+/// it with every `Deposited` emitted to the topic while the cell serves (see
+/// `cell_sdk::cytokine`).
+///
+/// A service may put `#[handler]` on several impl blocks, also in submodules.
+/// The first block in the source generates the protocol, in source order; the
+/// argument and return types of every method must be in scope there, and its
+/// `actor_key` applies to all of them.
+///
+/// Mark the service struct `#[service]` to construct it with a builder.
 #[proc_macro_attribute]
-pub fn handler(_: TokenStream, item: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(item as ItemImpl);
+pub fn handler(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut actor_key: Option<Ident> = None;
+    let attr_parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("actor_key") {
+            let key: LitStr = meta.value()?.parse()?;
+            actor_key = Some(format_ident!("{}", key.value()));
+            Ok(())
+        } else {
+            Err(meta.error("unsupported handler attribute"))
+        }
+    });
+    parse_macro_input!(attr with attr_parser);
+
+    let mut input = parse_macro_input!(item as ItemImpl);
+
     let self_ty = &input.self_ty;
     
     let service_name = match &**self_ty {
         Type::Path(p) => p.path.segments.last().unwrap().ident.clone(),
         _ => panic!("Handler must implement struct"),
     };
+    let stats = stats::Stats::start("#[handler]", &service_name.to_string());
+
+    // Served over OPS GetSource; only available when the cell is a binary crate
+    let source = std::env::var("CARGO_MANIFEST_DIR")
+        .ok()
+        .map(|dir| std::path::Path::new(&dir).join("src/main.rs"))
+        .filter(|main| main.exists())
+        .and_then(|main| cell_build::load_and_flatten_source(&main).ok());
+
+    // Methods may be spread over several #[handler] blocks, in any module: the
+    // first block in the source generates the protocol for all of them, the
+    // others only contribute their methods
+    let blocks = source.as_ref().map(|file| handler_blocks(&file.items, &service_name)).unwrap_or_default();
+    let own = fn_names(&input.items);
+    let mut others: Vec<syn::ImplItem> = match blocks.iter().position(|b| fn_names(&b.items) == own) {
+        Some(0) => blocks.into_iter().skip(1).flat_map(|b| b.items).collect(),
+        Some(_) => {
+            for impl_item in &mut input.items {
+                if let syn::ImplItem::Fn(m) = impl_item {
+                    m.attrs.retain(|a| !a.path().is_ident("handler") && !a.path().is_ident("subscriber"));
+                }
+            }
+            return quote! { #input }.into();
+        }
+        None => Vec::new(),
+    };
+
+    // Method-level #[handler(...)]: parse and strip before re-emitting the impl
+    let mut fallbacks: HashMap<Ident, Fallback> = HashMap::new();
+    let mut priorities: HashMap<Ident, u8> = HashMap::new();
+    let mut subscribers: Vec<(Ident, LitStr, Type)> = Vec::new();
+    for impl_item in input.items.iter_mut().chain(others.iter_mut()) {
+        if let syn::ImplItem::Fn(m) = impl_item {
+            if let Some(attr) = m.attrs.iter().find(|a| a.path().is_ident("subscriber")) {
+                let topic: LitStr = match attr.parse_args() {
+                    Ok(topic) => topic,
+                    Err(e) => return e.to_compile_error().into(),
+                };
+                let protein = m.sig.inputs.iter().find_map(|arg| match arg {
+                    FnArg::Typed(pt) => Some(*pt.ty.clone()),
+                    FnArg::Receiver(_) => None,
+                });
+                let Some(protein) = protein else {
+                    return syn::Error::new_spanned(&m.sig, "a subscriber takes the protein it receives")
+                        .to_compile_error()
+                        .into();
+                };
+                subscribers.push((m.sig.ident.clone(), topic, protein));
+                m.attrs.retain(|a| !a.path().is_ident("subscriber"));
+                continue;
+            }
+            let mut fallback = None;
+            let mut timeout_ms = None;
+            for attr in m.attrs.iter().filter(|a| a.path().is_ident("handler")) {
+                let parsed = attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("fallback") {
+                        let name: LitStr = meta.value()?.parse()?;
+                        fallback = Some(format_ident!("{}", name.value()));
+                        Ok(())
+                    } else if meta.path.is_ident("timeout_ms") {
+                        let ms: syn::LitInt = meta.value()?.parse()?;
+                        timeout_ms = Some(ms.base10_parse::<u64>()?);
+                        Ok(())
+                    } else if meta.path.is_ident("read") {
+                        // Routing hint for generated clients (see cell_remote!)
+                        Ok(())
+                    } else if meta.path.is_ident("priority") {
+                        let priority: syn::LitInt = meta.value()?.parse()?;
+                        priorities.insert(m.sig.ident.clone(), priority.base10_parse::<u8>()?);
+                        Ok(())
+                    } else if meta.path.is_ident("retry") {
+                        // Advertised in the schema and enforced by generated clients
+                        cell_build::schema::RetryPolicy::parse(&meta)?;
+                        Ok(())
+                    } else {
+                        Err(meta.error("unsupported handler method attribute"))
+                    }
+                });
+                if let Err(e) = parsed {
+                    return e.to_compile_error().into();
+                }
+            }
+            m.attrs.retain(|a| !a.path().is_ident("handler"));
+            match (fallback, timeout_ms) {
+                (Some(method), timeout_ms) => {
+                    fallbacks.insert(m.sig.ident.clone(), Fallback { method, timeout_ms });
+                }
+                (None, Some(_)) => {
+                    return syn::Error::new_spanned(&m.sig.ident, "timeout_ms requires a fallback")
+                        .to_compile_error()
+                        .into();
+                }
+                (None, None) => {}
+            }
+        }
+    }
 
     let protocol_name = format_ident!("{}Protocol", service_name);
     let response_name = format_ident!("{}Response", service_name);
     let archived_protocol_name = format_ident!("Archived{}Protocol", service_name);
 
     let mut methods = Vec::new();
-    for impl_item in &input.items {
+    // Methods taking a `&CallContext` first, filled in per call rather than sent
+    let mut with_context: Vec<Ident> = Vec::new();
+    for impl_item in input.items.iter().chain(&others) {
         if let syn::ImplItem::Fn(m) = impl_item {
+            // Subscribers are fed by the cytokine broker, not called over the protocol
+            if subscribers.iter().any(|(name, _, _)| *name == m.sig.ident) {
+                continue;
+            }
             let name = m.sig.ident.clone();
+            let typed = m.sig.inputs.iter().filter_map(|arg| match arg {
+                FnArg::Typed(pt) => Some(pt),
+                FnArg::Receiver(_) => None,
+            });
+            for (i, pt) in typed.enumerate() {
+                if !cell_build::schema::is_call_context(&pt.ty) {
+                    continue;
+                }
+                if i > 0 {
+                    return syn::Error::new_spanned(pt, "the CallContext must be the first argument")
+                        .to_compile_error()
+                        .into();
+                }
+                with_context.push(name.clone());
+            }
             let args: Vec<_> = m.sig.inputs.iter().filter_map(|arg| {
                 if let FnArg::Typed(pt) = arg {
+                    if cell_build::schema::is_call_context(&pt.ty) {
+                        return None;
+                    }
                     if let Pat::Ident(pi) = &*pt.pat {
                         return Some((pi.ident.clone(), *pt.ty.clone()));
                     }
@@ -370,46 +1016,124 @@ pub fn handler(_: TokenStream, item: TokenStream) -> TokenStream {
         let field_bindings: Vec<_> = field_names.iter().map(|n| quote!{ #n }).collect();
         
         // CRITICAL FIX: Deserialize each field individually, handling Result properly
-        let deserializers: Vec<_> = args.iter().map(|(n, _)| {
+        let deserializers: Vec<_> = args.iter().map(|(n, t)| {
+            // The actor key is used before the call that would infer its type
+            let ty = if actor_key.as_ref() == Some(n) { quote! { : #t } } else { quote! {} };
             quote! {
-                let #n = ::cell_sdk::rkyv::Deserialize::deserialize(
+                let #n #ty = ::cell_sdk::rkyv::Deserialize::deserialize(
                     #n, 
                     &mut ::cell_sdk::rkyv::de::deserializers::SharedDeserializeMap::new()
-                ).map_err(|_| ::cell_sdk::CellError::SerializationFailure)?;
+                ).map_err(|_| ::cell_sdk::CellError::DeserializationFailure)?;
             }
         }).collect();
         
         let call_args = field_names;
+        // The context goes first; a fallback takes the same arguments as its primary
+        let ctx = with_context.contains(name).then(|| quote! { &__ctx, });
+        let call = match fallbacks.get(name) {
+            Some(Fallback { method, timeout_ms }) => {
+                let timeout = match timeout_ms {
+                    Some(ms) => quote! { Some(::std::time::Duration::from_millis(#ms)) },
+                    None => quote! { None },
+                };
+                // The primary's deadline is its budget, the fallback gets the caller's
+                let primary_ctx = match (&ctx, timeout_ms) {
+                    (Some(_), Some(ms)) => quote! {
+                        &::cell_sdk::context::current().within(::std::time::Duration::from_millis(#ms)),
+                    },
+                    _ => quote! { #ctx },
+                };
+                let primary_args = call_args.iter().map(|n| quote! { ::core::clone::Clone::clone(&#n) });
+                let name_str = name.to_string();
+                let method_str = method.to_string();
+                quote! {
+                    let result = match ::cell_sdk::degrade::within(#timeout, self.#name(#primary_ctx #(#primary_args),*)).await {
+                        Ok(r) => r,
+                        Err(e) => {
+                            ::cell_sdk::tracing::warn!("{} failed ({}), falling back to {}", #name_str, e, #method_str);
+                            ::cell_sdk::degrade::mark();
+                            // Errors keep their code for the Membrane to report
+                            self.#method(#ctx #(#call_args),*).await?
+                        }
+                    };
+                }
+            }
+            None => quote! {
+                let result = self.#name(#ctx #(#call_args),*).await?;
+            },
+        };
+        let context = ctx.as_ref().map(|_| quote! { let __ctx = ::cell_sdk::context::current(); });
+
+        // Calls keyed by the actor key wait for their turn in the key's mailbox
+        let mailbox = match &actor_key {
+            Some(key) if args.iter().any(|(n, _)| n == key) => quote! {
+                let _slot = MAILBOXES.get_or_init(::cell_sdk::actor::Mailboxes::new).enter(&#key).await;
+            },
+            _ => quote! {},
+        };
 
         quote! {
             #archived_protocol_name::#variant { #(#field_bindings),* } => {
                 #(#deserializers)*
+                #mailbox
+                #context
                 // Call the actual handler method - it returns Result<T>
-                let result = self.#name(#(#call_args),*).await
-                    .map_err(|e| ::cell_sdk::CellError::SerializationFailure)?;
+                #call
                 // Wrap in response enum - variant holds T directly, not Result<T>
                 Ok(#response_name::#variant(result))
             }
         }
     }).collect();
 
-    let mut hasher = DefaultHasher::new();
-    service_name.to_string().hash(&mut hasher);
-    let fingerprint = hasher.finish();
+    let method_names: Vec<_> = methods.iter().map(|(name, _, _)| {
+        let variant = format_ident!("{}", name.to_string().to_case(Case::Pascal));
+        let name_str = name.to_string();
+        let priority = match priorities.get(name) {
+            Some(p) => quote! { #p },
+            None => quote! { ::cell_sdk::shed::DEFAULT_PRIORITY },
+        };
+        quote! { #archived_protocol_name::#variant { .. } => (#name_str, #priority) }
+    }).collect();
+
+    // Shared by every method, so one key is serialized across all of them
+    let mailboxes = match &actor_key {
+        Some(_) => quote! {
+            static MAILBOXES: ::std::sync::OnceLock<::cell_sdk::actor::Mailboxes> =
+                ::std::sync::OnceLock::new();
+        },
+        None => quote! {},
+    };
 
+    let schema = source.as_ref().map(cell_build::spore::extract_schema);
+    // Same fingerprint cell_remote! computes from this schema, compared in the
+    // connect handshake; 0 (no source) leaves it unchecked
+    let fingerprint = schema.as_deref().map_or(0, |schema| {
+        cell_build::schema::Schema::from_source(schema).unwrap_or_default().fingerprint()
+    });
+    let register_schema = match schema {
+        Some(schema) => quote! { ::cell_sdk::source::register(#schema); },
+        None => quote! {},
+    };
+
+    let subscribe: Vec<_> = subscribers.iter().map(|(name, topic, protein)| {
+        quote! {
+            {
+                let svc = service.clone();
+                ::cell_sdk::cytokine::spawn_subscriber::<#protein, _, _>(#topic, move |protein| {
+                    let svc = svc.clone();
+                    async move { svc.#name(protein).await }
+                });
+            }
+        }
+    }).collect();
+
+    // Served protocols keep serde: codec transcoding and the slow log use it
+    let wire = wire_derives(true);
     let expanded = quote! {
-        #[derive(::cell_sdk::serde::Serialize, ::cell_sdk::serde::Deserialize)]
-        #[derive(::cell_sdk::rkyv::Archive, ::cell_sdk::rkyv::Serialize, ::cell_sdk::rkyv::Deserialize)]
-        #[archive(check_bytes)]
-        #[serde(crate = "::cell_sdk::serde")]
-        #[archive(crate = "::cell_sdk::rkyv")]
+        #wire
         pub enum #protocol_name { #(#req_variants),* }
 
-        #[derive(::cell_sdk::serde::Serialize, ::cell_sdk::serde::Deserialize)]
-        #[derive(::cell_sdk::rkyv::Archive, ::cell_sdk::rkyv::Serialize, ::cell_sdk::rkyv::Deserialize)]
-        #[archive(check_bytes)]
-        #[serde(crate = "::cell_sdk::serde")]
-        #[archive(crate = "::cell_sdk::rkyv")]
+        #wire
         pub enum #response_name { #(#resp_variants),* }
 
         #input
@@ -418,7 +1142,11 @@ pub fn handler(_: TokenStream, item: TokenStream) -> TokenStream {
             pub const SCHEMA_FINGERPRINT: u64 = #fingerprint;
 
             pub async fn serve(self, name: &str) -> ::anyhow::Result<()> {
+                #register_schema
+                ::cell_sdk::source::register_fingerprint(Self::SCHEMA_FINGERPRINT);
+                ::cell_sdk::codec::register::<#protocol_name, #response_name>();
                 let service = std::sync::Arc::new(self);
+                #(#subscribe)*
                 ::cell_sdk::Membrane::bind::<_, #protocol_name, #response_name>(
                     name,
                     move |archived_req| {
@@ -430,11 +1158,37 @@ pub fn handler(_: TokenStream, item: TokenStream) -> TokenStream {
             }
 
             async fn dispatch(&self, req: &#archived_protocol_name) -> ::anyhow::Result<#response_name> {
-                match req {
-                    #(#dispatch_arms),*
-                }
+                #mailboxes
+                let (method, priority) = match req {
+                    #(#method_names),*
+                };
+                ::cell_sdk::admission::check()?;
+                let in_flight = ::cell_sdk::shed::admit(method, priority)?;
+                let _turn = ::cell_sdk::priority::turn(in_flight.priority()).await;
+                let started = ::std::time::Instant::now();
+                // Calls the handler makes carry its priority on
+                let result: ::anyhow::Result<#response_name> = ::cell_sdk::priority::scope(
+                    in_flight.priority(),
+                    async {
+                        match req {
+                            #(#dispatch_arms),*
+                        }
+                    },
+                ).await;
+                let elapsed = started.elapsed();
+                ::cell_sdk::metrics::record(method, elapsed, result.is_ok());
+                ::cell_sdk::slo::record(method, elapsed, result.is_ok());
+                // The request is still archived, so arguments are only decoded for slow calls
+                ::cell_sdk::slowlog::observe(method, elapsed, result.is_ok(), || {
+                    let owned: #protocol_name = ::cell_sdk::rkyv::Deserialize::deserialize(
+                        req,
+                        &mut ::cell_sdk::rkyv::de::deserializers::SharedDeserializeMap::new(),
+                    ).ok()?;
+                    ::cell_sdk::slowlog::to_json(&owned)
+                });
+                result
             }
         }
     };
-    TokenStream::from(expanded)
+    stats.finish(expanded, 0, methods.len()).into()
 }
\ No newline at end of file
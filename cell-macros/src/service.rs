@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Fields, GenericArgument, ItemStruct, PathArguments, Type};
+
+/// `#[service]` on a struct with named fields generates `Service::builder()`.
+///
+/// Each field gets a setter taking anything `Into` its type, so an `Arc<Db>`
+/// field is set with `.db(db)` and a test double with `.db(fake)`. `build()`
+/// fails naming every field left unset, except `Option` fields (left `None`)
+/// and fields marked `#[service(default)]` (left `Default::default()`).
+/// Other items are passed through unchanged.
+pub fn service_impl(item: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(item as ItemStruct);
+    if !matches!(input.fields, Fields::Named(_)) {
+        return quote! { #input }.into();
+    }
+
+    let mut slots = Vec::new();
+    let mut setters = Vec::new();
+    let mut finish = Vec::new();
+    let mut required = Vec::new();
+    for field in input.fields.iter_mut() {
+        let mut default = false;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("service")) {
+            let parsed = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    default = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported service field attribute"))
+                }
+            });
+            if let Err(e) = parsed {
+                return e.to_compile_error().into();
+            }
+        }
+        field.attrs.retain(|a| !a.path().is_ident("service"));
+
+        let name = field.ident.as_ref().expect("named field");
+        let name_str = name.to_string();
+        let ty = &field.ty;
+        let docs = field.attrs.iter().filter(|a| a.path().is_ident("doc"));
+        let (slot, setter, value) = match option_inner(ty) {
+            Some(inner) => (
+                quote! { #ty },
+                quote! {
+                    #(#docs)*
+                    pub fn #name(mut self, value: impl ::std::convert::Into<#inner>) -> Self {
+                        self.#name = ::std::option::Option::Some(value.into());
+                        self
+                    }
+                },
+                quote! { self.#name },
+            ),
+            None => (
+                quote! { ::std::option::Option<#ty> },
+                quote! {
+                    #(#docs)*
+                    pub fn #name(mut self, value: impl ::std::convert::Into<#ty>) -> Self {
+                        self.#name = ::std::option::Option::Some(value.into());
+                        self
+                    }
+                },
+                if default {
+                    quote! { self.#name.unwrap_or_default() }
+                } else {
+                    required.push(quote! { (#name_str, self.#name.is_none()) });
+                    quote! { self.#name.expect("checked above") }
+                },
+            ),
+        };
+        slots.push(quote! { #name: #slot });
+        setters.push(setter);
+        finish.push(quote! { #name: #value });
+    }
+
+    let vis = &input.vis;
+    let ident = &input.ident;
+    let ident_str = ident.to_string();
+    let builder = format_ident!("{}Builder", ident);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let generics = &input.generics;
+    let names = input.fields.iter().map(|f| &f.ident);
+    let count = required.len();
+    let builder_doc = format!("Builder for [`{}`], see `{}::builder`", ident, ident);
+
+    let expanded = quote! {
+        #input
+
+        #[doc = #builder_doc]
+        #vis struct #builder #generics #where_clause {
+            #(#slots),*
+        }
+
+        impl #impl_generics ::std::default::Default for #builder #ty_generics #where_clause {
+            fn default() -> Self {
+                Self { #(#names: ::std::option::Option::None),* }
+            }
+        }
+
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Wire the service's shared resources one by one.
+            pub fn builder() -> #builder #ty_generics {
+                ::std::default::Default::default()
+            }
+        }
+
+        impl #impl_generics #builder #ty_generics #where_clause {
+            #(#setters)*
+
+            /// Fails with the names of the required fields that were not set.
+            pub fn build(self) -> ::anyhow::Result<#ident #ty_generics> {
+                let required: [(&str, bool); #count] = [#(#required),*];
+                let missing: ::std::vec::Vec<&str> = required
+                    .into_iter()
+                    .filter_map(|(name, unset)| unset.then_some(name))
+                    .collect();
+                if !missing.is_empty() {
+                    ::anyhow::bail!("{} is missing {}", #ident_str, missing.join(", "));
+                }
+                ::std::result::Result::Ok(#ident { #(#finish),* })
+            }
+        }
+    };
+    expanded.into()
+}
+
+/// `T` when `ty` is `Option<T>`
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
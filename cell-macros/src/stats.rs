@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+use quote::quote;
+use std::time::Instant;
+
+/// What one `cell_remote!` or `#[handler]` expansion generated.
+///
+/// With `CELL_CODEGEN_STATS=1` every expansion prints one line to stderr:
+/// proteins, protocol variants, output tokens and time spent in the macro
+/// (mostly finding and parsing the schema). With `CELL_CODEGEN_BUDGET=<n>`,
+/// an expansion of more than `n` tokens fails the build; for `cell_remote!`
+/// the error names the options (`methods = [...]`, `serde = false`,
+/// `proteins = ...`) that shrink it.
+pub struct Stats {
+    macro_name: &'static str,
+    target: String,
+    started: Instant,
+}
+
+impl Stats {
+    pub fn start(macro_name: &'static str, target: &str) -> Self {
+        Self {
+            macro_name,
+            target: target.to_string(),
+            started: Instant::now(),
+        }
+    }
+
+    pub fn finish(
+        self,
+        expanded: proc_macro2::TokenStream,
+        proteins: usize,
+        variants: usize,
+    ) -> proc_macro2::TokenStream {
+        let tokens = count(expanded.clone());
+        if std::env::var("CELL_CODEGEN_STATS").is_ok_and(|v| !v.is_empty() && v != "0") {
+            eprintln!(
+                "cell codegen: {} {}: {} proteins, {} variants, {} tokens, {:.1?}",
+                self.macro_name,
+                self.target,
+                proteins,
+                variants,
+                tokens,
+                self.started.elapsed()
+            );
+        }
+        let budget = std::env::var("CELL_CODEGEN_BUDGET")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok());
+        match budget {
+            Some(budget) if tokens > budget => {
+                let hint = if self.macro_name == "cell_remote!" {
+                    "; select methods with `methods = [...]`, drop serde with `serde = false` \
+                     or share proteins with `proteins = ...`"
+                } else {
+                    ""
+                };
+                let message = format!(
+                    "{} for '{}' generated {} tokens, over CELL_CODEGEN_BUDGET={}{}",
+                    self.macro_name, self.target, tokens, budget, hint
+                );
+                quote! {
+                    #expanded
+                    ::core::compile_error!(#message);
+                }
+            }
+            _ => expanded,
+        }
+    }
+}
+
+/// Tokens in `stream`, counting into groups
+fn count(stream: proc_macro2::TokenStream) -> usize {
+    stream
+        .into_iter()
+        .map(|tree| match tree {
+            proc_macro2::TokenTree::Group(g) => 1 + count(g.stream()),
+            _ => 1,
+        })
+        .sum()
+}
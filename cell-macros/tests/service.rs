@@ -0,0 +1,60 @@
+use cell_macros::service;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+trait Store: Send + Sync {
+    fn get(&self, key: &str) -> Option<u64>;
+}
+
+struct FakeStore(HashMap<String, u64>);
+
+impl Store for FakeStore {
+    fn get(&self, key: &str) -> Option<u64> {
+        self.0.get(key).copied()
+    }
+}
+
+#[service]
+#[derive(Clone)]
+struct Accounts {
+    /// Balances by account
+    store: Arc<dyn Store>,
+    region: String,
+    #[service(default)]
+    audit: Arc<Mutex<Vec<String>>>,
+    quota: Option<u32>,
+}
+
+fn fake() -> Arc<dyn Store> {
+    Arc::new(FakeStore(HashMap::from([("alice".to_string(), 7)])))
+}
+
+#[test]
+fn builder_wires_resources() {
+    let accounts = Accounts::builder()
+        .store(fake())
+        .region("eu-north")
+        .quota(5u32)
+        .build()
+        .unwrap();
+    assert_eq!(accounts.store.get("alice"), Some(7));
+    assert_eq!(accounts.region, "eu-north");
+    assert_eq!(accounts.quota, Some(5));
+    assert!(accounts.clone().audit.lock().unwrap().is_empty());
+}
+
+#[test]
+fn optional_and_default_fields_may_be_skipped() {
+    let accounts = Accounts::builder()
+        .store(fake())
+        .region("eu-north")
+        .build()
+        .unwrap();
+    assert_eq!(accounts.quota, None);
+}
+
+#[test]
+fn build_names_every_missing_field() {
+    let err = Accounts::builder().build().err().unwrap();
+    assert_eq!(err.to_string(), "Accounts is missing store, region");
+}
@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+use alloc::string::String;
+use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+
+/// Permission to act as another principal, issued by a holder of the mesh admin key.
+///
+/// Sent on `channel::AUTH`; once accepted, every following request on the same
+/// connection is handled as `principal`.
+#[derive(Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone)]
+#[archive(check_bytes)]
+pub struct ImpersonationGrant {
+    /// Who is impersonating (recorded in the audit log).
+    pub operator: String,
+    /// The principal requests are executed as.
+    pub principal: String,
+    /// The organism the principal belongs to; storage cells serve it that
+    /// organism's records.
+    pub organism: String,
+    /// The cell the grant is for; every other cell rejects it.
+    pub cell: String,
+    /// Free-form justification, e.g. a ticket reference.
+    pub reason: String,
+    /// Unix seconds after which the grant is rejected.
+    pub expires_at: u64,
+    /// Keyed BLAKE3 tag over the fields above (hex).
+    pub tag: String,
+}
+
+#[derive(Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone)]
+#[archive(check_bytes)]
+pub enum AuthResponse {
+    Accepted { principal: String },
+    Rejected { reason: String },
+}
@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Content-addressed blob bookkeeping for the `blobstore` cell.
+//!
+//! A blob is split into [`CHUNK_SIZE`] chunks, each stored once under its
+//! blake3 hash; the blob itself is named by the hash of its contents. Blobs
+//! are reference counted, and a chunk lives as long as any blob uses it.
+//! Hashing and disk I/O stay in the cell; [`BlobIndex`] only tracks who
+//! references what.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::vec::Vec;
+use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+
+pub const CHUNK_SIZE: usize = 1024 * 1024;
+
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct BlobManifest {
+    pub hash: String,
+    pub size: u64,
+    /// Chunk hashes, in order
+    pub chunks: Vec<String>,
+}
+
+/// What a garbage collection pass may delete.
+#[derive(
+    Archive,
+    Serialize,
+    Deserialize,
+    SerdeSerialize,
+    SerdeDeserialize,
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct GcPlan {
+    pub blobs: Vec<String>,
+    pub chunks: Vec<String>,
+}
+
+#[derive(SerdeSerialize, SerdeDeserialize, Debug, Clone, Default)]
+pub struct BlobIndex {
+    blobs: BTreeMap<String, (BlobManifest, u32)>,
+}
+
+impl BlobIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a stored blob and take one reference to it. Returns the new count.
+    pub fn insert(&mut self, manifest: BlobManifest) -> u32 {
+        let entry = self
+            .blobs
+            .entry(manifest.hash.clone())
+            .or_insert((manifest, 0));
+        entry.1 += 1;
+        entry.1
+    }
+
+    pub fn get(&self, hash: &str) -> Option<&BlobManifest> {
+        self.blobs.get(hash).map(|(m, _)| m)
+    }
+
+    pub fn refs(&self, hash: &str) -> u32 {
+        self.blobs.get(hash).map_or(0, |(_, n)| *n)
+    }
+
+    /// Take another reference. `None` if the blob is unknown.
+    pub fn retain(&mut self, hash: &str) -> Option<u32> {
+        let (_, refs) = self.blobs.get_mut(hash)?;
+        *refs += 1;
+        Some(*refs)
+    }
+
+    /// Drop a reference. The blob stays until the next [`gc`](Self::gc).
+    pub fn release(&mut self, hash: &str) -> Option<u32> {
+        let (_, refs) = self.blobs.get_mut(hash)?;
+        *refs = refs.saturating_sub(1);
+        Some(*refs)
+    }
+
+    /// Forget unreferenced blobs. `stored` lists the chunks on disk; those no
+    /// remaining blob uses are returned for deletion, including orphans from
+    /// uploads that were never committed.
+    pub fn gc<'a>(&mut self, stored: impl IntoIterator<Item = &'a str>) -> GcPlan {
+        let mut plan = GcPlan::default();
+        self.blobs.retain(|hash, (_, refs)| {
+            if *refs == 0 {
+                plan.blobs.push(hash.clone());
+            }
+            *refs > 0
+        });
+
+        let live: BTreeSet<&str> = self
+            .blobs
+            .values()
+            .flat_map(|(m, _)| m.chunks.iter().map(String::as_str))
+            .collect();
+        plan.chunks = stored
+            .into_iter()
+            .filter(|c| !live.contains(c))
+            .map(String::from)
+            .collect();
+        plan
+    }
+}
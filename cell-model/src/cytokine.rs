@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Cytokines: topic broadcast between cells.
+//!
+//! A broker cell (`cells/cytokine`) keeps the most recent payloads of every
+//! topic in a [`TopicLog`]. Emitters send it rkyv-encoded proteins; each
+//! subscriber long-polls with its own cursor, so a slow subscriber never holds
+//! up the others and only learns how many payloads it `missed` once the log
+//! has moved past it.
+
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Name the broker cell serves under
+pub const BROKER: &str = "cytokine";
+
+/// Payloads kept per topic unless the broker is configured otherwise
+pub const DEFAULT_RETAIN: usize = 1024;
+
+/// Most payloads returned by one poll
+pub const MAX_BATCH: usize = 256;
+
+/// Longest a poll waits for a payload, below the callers' request timeout
+pub const MAX_WAIT_MS: u64 = 20_000;
+
+#[derive(Archive, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[archive(check_bytes)]
+pub enum CytokineRequest {
+    /// Broadcast `payload` to everyone subscribed to `topic`
+    Emit { topic: String, payload: Vec<u8> },
+    /// Payloads of `topic` from sequence number `from` on (`None`: from the
+    /// next emission), waiting up to `wait_ms` for one if there are none yet
+    Poll {
+        topic: String,
+        from: Option<u64>,
+        wait_ms: u64,
+    },
+}
+
+#[derive(Archive, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[archive(check_bytes)]
+pub enum CytokineResponse {
+    Emitted { seq: u64 },
+    Batch(Batch),
+    Error { message: String },
+}
+
+#[derive(Archive, Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[archive(check_bytes)]
+pub struct Batch {
+    /// Cursor for the next poll
+    pub next: u64,
+    /// Payloads dropped from the log before this subscriber read them
+    pub missed: u64,
+    pub payloads: Vec<Vec<u8>>,
+}
+
+/// Recent payloads of one topic, numbered from 0 in emission order
+#[derive(Debug, Clone)]
+pub struct TopicLog {
+    retain: usize,
+    next: u64,
+    recent: VecDeque<Vec<u8>>,
+}
+
+impl TopicLog {
+    pub fn new(retain: usize) -> Self {
+        Self {
+            retain: retain.max(1),
+            next: 0,
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Append a payload, dropping the oldest beyond `retain`. Returns its
+    /// sequence number.
+    pub fn push(&mut self, payload: Vec<u8>) -> u64 {
+        if self.recent.len() == self.retain {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(payload);
+        self.next += 1;
+        self.next - 1
+    }
+
+    /// Sequence number the next payload will get
+    pub fn next_seq(&self) -> u64 {
+        self.next
+    }
+
+    /// Up to `max` payloads from `from` on. A cursor past the end (the broker
+    /// restarted and numbers from 0 again) starts over at the oldest kept.
+    pub fn read(&self, from: Option<u64>, max: usize) -> Batch {
+        let oldest = self.next - self.recent.len() as u64;
+        let from = match from {
+            None => self.next,
+            Some(seq) if seq > self.next => oldest,
+            Some(seq) => seq,
+        };
+        let start = from.max(oldest);
+        let payloads: Vec<Vec<u8>> = self
+            .recent
+            .iter()
+            .skip((start - oldest) as usize)
+            .take(max)
+            .cloned()
+            .collect();
+        Batch {
+            next: start + payloads.len() as u64,
+            missed: start - from,
+            payloads,
+        }
+    }
+}
@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Deployment history and picking what to roll back to.
+//!
+//! The state-manager keeps a record of every applied manifest and every swap
+//! (`kind` is `apply`, `swap` or `rollback`) with the source hash that went
+//! live. Swaps also record the blobstore hash of the binary and the schema it
+//! served, so `cell rollback` can bring the binary back without rebuilding
+//! and check the old schema against what clients use now.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deployment {
+    pub cell: String,
+    /// Source hash (or manifest version) that went live
+    pub version: String,
+    pub kind: String,
+    /// Blobstore hash of the binary, if it was archived
+    pub artifact: Option<String>,
+    /// Schema source the version served
+    pub schema: Option<String>,
+    pub deployed_at: u64,
+}
+
+/// The record to roll back to, given `history` newest first.
+///
+/// Without `to` that is the newest version other than the live one. `to` may
+/// be a prefix of a version hash, as long as it names only one version. Of
+/// several records of the chosen version, the newest one with an archived
+/// artifact wins.
+pub fn rollback_target<'a>(
+    history: &'a [Deployment],
+    to: Option<&str>,
+) -> Result<&'a Deployment, String> {
+    let current = history
+        .first()
+        .ok_or_else(|| String::from("no deployments recorded"))?;
+
+    let version = match to {
+        Some(to) => {
+            let mut matching: Vec<&str> = history
+                .iter()
+                .map(|d| d.version.as_str())
+                .filter(|v| v.starts_with(to))
+                .collect();
+            matching.sort_unstable();
+            matching.dedup();
+            match matching[..] {
+                [] => return Err(format!("{} was never deployed as {}", current.cell, to)),
+                [v] if v == current.version => {
+                    return Err(format!("{} is the live version", v));
+                }
+                [v] => v,
+                _ => return Err(format!("{} matches several versions", to)),
+            }
+        }
+        None => history
+            .iter()
+            .map(|d| d.version.as_str())
+            .find(|v| *v != current.version)
+            .ok_or_else(|| format!("{} has no earlier version", current.cell))?,
+    };
+
+    let records = || history.iter().filter(|d| d.version == version);
+    Ok(records()
+        .find(|d| d.artifact.is_some())
+        .or_else(|| records().next())
+        .expect("version came from history"))
+}
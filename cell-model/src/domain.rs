@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Data domains for multi-tenant storage cells.
+//!
+//! State-manager and blobstore tag every record with the [`Domain`] it
+//! belongs to: the organism that owns it and, optionally, the region it must
+//! stay in. A host only stores records whose residency matches its own, and
+//! only serves a record to callers of its organism or holding a
+//! [`DomainGrant`] from IAM.
+
+use alloc::string::{String, ToString};
+use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+
+#[derive(
+    Archive,
+    Serialize,
+    Deserialize,
+    SerdeSerialize,
+    SerdeDeserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+#[archive(check_bytes)]
+pub struct Domain {
+    pub organism: String,
+    /// Region the data must stay in, e.g. `eu`; `None` may live anywhere
+    pub residency: Option<String>,
+}
+
+impl Domain {
+    pub fn new(organism: impl Into<String>, residency: Option<String>) -> Self {
+        Self {
+            organism: organism.into(),
+            residency,
+        }
+    }
+
+    /// `organism`, or `organism@residency`
+    pub fn label(&self) -> String {
+        match &self.residency {
+            Some(r) => alloc::format!("{}@{}", self.organism, r),
+            None => self.organism.clone(),
+        }
+    }
+
+    /// Inverse of [`label`](Self::label)
+    pub fn parse(label: &str) -> Self {
+        match label.split_once('@') {
+            Some((organism, r)) if !r.is_empty() => Self::new(organism, Some(r.to_string())),
+            _ => Self::new(label.trim_end_matches('@'), None),
+        }
+    }
+
+    /// Whether a host in `host_residency` may store records of this domain
+    pub fn storable_in(&self, host_residency: Option<&str>) -> bool {
+        match &self.residency {
+            Some(r) => host_residency == Some(r.as_str()),
+            None => true,
+        }
+    }
+}
+
+/// Lets `principal` read and write records of another organism.
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct DomainGrant {
+    pub principal: String,
+    pub organism: String,
+    /// Only records of this residency; `None` covers all of the organism's
+    pub residency: Option<String>,
+    /// Unix seconds after which the grant no longer applies
+    pub expires_at: Option<u64>,
+}
+
+impl DomainGrant {
+    pub fn covers(&self, principal: &str, domain: &Domain, now_secs: u64) -> bool {
+        self.principal == principal
+            && self.organism == domain.organism
+            && self
+                .residency
+                .as_ref()
+                .is_none_or(|r| domain.residency.as_ref() == Some(r))
+            && self.expires_at.is_none_or(|t| now_secs < t)
+    }
+}
@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Methods a running cell adds after it started.
+//!
+//! The `#[handler]` protocol is fixed when the cell is compiled, so methods a
+//! plugin brings along are called on their own channel
+//! (`cell_core::channel::EXTENSION`) with an [`ExtensionCall`] naming the
+//! method. Clients find out what is there by asking over OPS
+//! (`OpsRequest::Extensions`); the generation changes with every registration,
+//! so a client can tell whether its list is stale.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use rkyv::{Archive, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize};
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct ExtensionMethod {
+    pub name: String,
+    /// Plugin that registered it
+    pub plugin: String,
+    /// Its public surface in the form of a cell schema: the `#[protein]` types
+    /// it takes and returns and its signature, for tools and client generators
+    pub schema: String,
+}
+
+#[derive(
+    Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct ExtensionCall {
+    pub method: String,
+    /// Arguments, in the encoding the method's plugin reads
+    pub args: Vec<u8>,
+}
+
+#[derive(
+    Archive,
+    RkyvSerialize,
+    RkyvDeserialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Default,
+)]
+#[archive(check_bytes)]
+pub struct ExtensionList {
+    /// Bumped whenever a method is registered or removed
+    pub generation: u64,
+    pub methods: Vec<ExtensionMethod>,
+}
+
+impl ExtensionList {
+    pub fn get(&self, name: &str) -> Option<&ExtensionMethod> {
+        self.methods.iter().find(|m| m.name == name)
+    }
+}
@@ -5,16 +5,39 @@
 
 extern crate alloc;
 
+pub mod auth;
+pub mod blob;
 pub mod bridge;
 pub mod config;
+pub mod cytokine;
+pub mod deploy;
+pub mod domain;
 pub mod error;
+pub mod extension;
 pub mod io;
 pub mod macro_coordination;
 pub mod manifest;
+pub mod metering;
+pub mod middleware;
+pub mod metrics;
 pub mod ops;
+pub mod placement;
+pub mod pressure;
 pub mod protocol;
+pub mod provenance;
+pub mod reaper;
+pub mod release;
+pub mod quota;
+pub mod replay;
+pub mod routing;
 pub mod schema;
+pub mod signed_entry;
+pub mod slo;
+pub mod upgrade;
 pub mod vesicle;
+pub mod view;
+pub mod wal_archive;
+pub mod watchdog;
 
 // Re-export common types for convenience
 pub use error::Error;
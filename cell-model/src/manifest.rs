@@ -1,8 +1,10 @@
 // cell-model/src/manifest.rs
 // SPDX-License-Identifier: MIT
 
+use crate::release::Channel;
 use alloc::string::String;
 use alloc::vec::Vec;
+use rkyv::{Archive, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -26,6 +28,29 @@ pub struct CellManifest {
     pub resources: ResourceLimits,
     #[serde(default)]
     pub placement: PlacementStrategy,
+    /// Hardware the cell needs, e.g. `["gpu"]` or `["gpu:nvidia:8192"]`
+    #[serde(default)]
+    pub requires: Vec<String>,
+    /// Objectives per handler method, `[slo.<method>]`
+    #[serde(default)]
+    pub slo: HashMap<String, crate::slo::Slo>,
+    /// `critical`, `standard` (the default) or `best-effort`: what the node
+    /// sheds first under memory or CPU pressure
+    #[serde(default)]
+    pub priority: crate::pressure::PriorityClass,
+    /// Where the Membrane listens: `unix` (the default, through the IO cell),
+    /// `tcp://<ip>:<port>` or `quic://<ip>:<port>`. `CELL_TRANSPORT`
+    /// overrides it.
+    pub transport: Option<String>,
+    /// Engine and location of the cell's persistent state, `[storage]`
+    #[serde(default)]
+    pub storage: StorageConfig,
+    /// Raft group the cell replicates its state in, `[consensus]`
+    pub consensus: Option<ConsensusConfig>,
+    /// Plugin cells whose modules see every request, `[[middleware]]`, in
+    /// the order they run
+    #[serde(default)]
+    pub middleware: Vec<crate::middleware::MiddlewareRef>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -48,9 +73,49 @@ pub enum NeighborConfig {
         path: String,
         #[serde(default)]
         autostart: bool,
+        /// Release channel to run, resolved to a version at spawn time
+        #[serde(default)]
+        channel: Option<Channel>,
     },
 }
 
+impl NeighborConfig {
+    pub fn path(&self) -> &str {
+        match self {
+            NeighborConfig::Path(path) | NeighborConfig::Detailed { path, .. } => path,
+        }
+    }
+
+    pub fn channel(&self) -> Option<Channel> {
+        match self {
+            NeighborConfig::Path(_) => None,
+            NeighborConfig::Detailed { channel, .. } => *channel,
+        }
+    }
+
+    /// `host:port` of a neighbor on another host, written `tcp://host:port`
+    pub fn tcp_addr(&self) -> Option<&str> {
+        tcp_addr(self.path())
+    }
+}
+
+/// `host:port` of a `tcp://host:port` endpoint; `None` for anything else.
+pub fn tcp_addr(endpoint: &str) -> Option<&str> {
+    scheme_addr(endpoint, "tcp://")
+}
+
+/// `host:port` of a `quic://host:port` endpoint; `None` for anything else.
+pub fn quic_addr(endpoint: &str) -> Option<&str> {
+    scheme_addr(endpoint, "quic://")
+}
+
+fn scheme_addr<'a>(endpoint: &'a str, scheme: &str) -> Option<&'a str> {
+    endpoint
+        .trim()
+        .strip_prefix(scheme)
+        .filter(|addr| addr.contains(':'))
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct WorkspaceMeta {
     pub namespace: String,
@@ -74,3 +139,303 @@ pub struct PlacementStrategy {
     pub required_instruction_set: Option<String>,
     pub require_tee: bool,
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StorageConfig {
+    #[serde(default)]
+    pub engine: StorageEngine,
+    /// Database file, relative to the cell's state directory unless
+    /// absolute; each engine has its own default name
+    pub path: Option<String>,
+}
+
+/// What a stateful cell keeps its data in. `CELL_STORAGE` overrides the
+/// engine from Cell.toml.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageEngine {
+    #[default]
+    Sqlite,
+    /// Pure Rust, for embedded builds without a C toolchain
+    Redb,
+    /// Nothing survives a restart: tests and throwaway meshes
+    Memory,
+}
+
+impl StorageEngine {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim() {
+            "sqlite" => Some(StorageEngine::Sqlite),
+            "redb" => Some(StorageEngine::Redb),
+            "memory" => Some(StorageEngine::Memory),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StorageEngine::Sqlite => "sqlite",
+            StorageEngine::Redb => "redb",
+            StorageEngine::Memory => "memory",
+        }
+    }
+
+    /// File name used when `[storage]` gives no path
+    pub fn default_file(self) -> Option<&'static str> {
+        match self {
+            StorageEngine::Sqlite => Some("state.db"),
+            StorageEngine::Redb => Some("state.redb"),
+            StorageEngine::Memory => None,
+        }
+    }
+}
+
+/// A member of a Raft group:
+///
+/// ```toml
+/// [consensus]
+/// id = 1
+/// peers = ["10.0.0.1:10001", "10.0.0.2:10002", "10.0.0.3:10003"]
+/// ```
+///
+/// Node `id` listens for its peers on port `10000 + id`, which is how the
+/// members tell each other apart.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConsensusConfig {
+    pub id: u64,
+    /// Raft addresses of the members; this node's own may be among them
+    #[serde(default)]
+    pub peers: Vec<String>,
+    /// Write-ahead log, relative to the cell's state directory unless
+    /// absolute; `raft-<id>.wal` by default
+    pub wal: Option<String>,
+}
+
+impl ConsensusConfig {
+    /// Port node `id` listens on
+    pub fn port(id: u64) -> u64 {
+        10000 + id
+    }
+
+    /// Members of the group, this node included
+    pub fn cluster_size(&self) -> usize {
+        let own = Self::port(self.id).to_string();
+        let others = self
+            .peers
+            .iter()
+            .filter(|addr| addr.rsplit_once(':').map(|(_, port)| port) != Some(own.as_str()))
+            .count();
+        others + 1
+    }
+
+    /// Members that must have an entry before it commits
+    pub fn quorum(&self) -> usize {
+        self.cluster_size() / 2 + 1
+    }
+
+    pub fn wal_file(&self) -> String {
+        self.wal
+            .clone()
+            .unwrap_or_else(|| format!("raft-{}.wal", self.id))
+    }
+}
+
+/// Desired state of a whole mesh, applied through the nucleus.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MeshManifest {
+    pub mesh: String,
+    #[serde(default)]
+    pub cells: Vec<CellDeployment>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CellDeployment {
+    pub name: String,
+    pub version: Option<String>,
+    #[serde(default = "default_replicas")]
+    pub replicas: u32,
+    /// Instances that must stay available during rolling operations
+    pub min_available: Option<u32>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub resources: ResourceLimits,
+    #[serde(default)]
+    pub placement: PlacementStrategy,
+    #[serde(default)]
+    pub requires: Vec<String>,
+    /// Replicas run a majority protocol (Raft); node maintenance never takes
+    /// down more than a minority of them at once
+    #[serde(default)]
+    pub quorum: bool,
+}
+
+fn default_replicas() -> u32 {
+    1
+}
+
+impl CellDeployment {
+    /// Cells edge placement keeps off nodes on battery or under thermal pressure
+    pub fn is_heavy(&self) -> bool {
+        self.resources.gpu || self.resources.cpu.is_some_and(|c| c >= 1.0)
+    }
+}
+
+/// What the mesh is actually running for one cell.
+#[derive(
+    Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct ObservedCell {
+    pub name: String,
+    pub version: Option<String>,
+    pub replicas: u32,
+    pub env: Vec<(String, String)>,
+}
+
+#[derive(
+    Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub enum DriftKind {
+    /// Declared in the manifest but not running
+    Missing,
+    /// Running but not declared in the manifest
+    Unexpected,
+    Version {
+        desired: String,
+        actual: Option<String>,
+    },
+    Replicas {
+        desired: u32,
+        actual: u32,
+    },
+    Env {
+        key: String,
+        desired: Option<String>,
+        actual: Option<String>,
+    },
+}
+
+/// A single discrepancy between the manifest and the running mesh.
+#[derive(
+    Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct Drift {
+    pub cell: String,
+    pub kind: DriftKind,
+}
+
+impl MeshManifest {
+    /// Compare the desired state against `observed`, sorted by cell name.
+    pub fn drift(&self, observed: &[ObservedCell]) -> Vec<Drift> {
+        let mut drift = Vec::new();
+
+        for desired in &self.cells {
+            let Some(actual) = observed.iter().find(|o| o.name == desired.name) else {
+                drift.push(Drift {
+                    cell: desired.name.clone(),
+                    kind: DriftKind::Missing,
+                });
+                continue;
+            };
+
+            if let Some(version) = &desired.version {
+                if actual.version.as_ref() != Some(version) {
+                    drift.push(Drift {
+                        cell: desired.name.clone(),
+                        kind: DriftKind::Version {
+                            desired: version.clone(),
+                            actual: actual.version.clone(),
+                        },
+                    });
+                }
+            }
+
+            if actual.replicas != desired.replicas {
+                drift.push(Drift {
+                    cell: desired.name.clone(),
+                    kind: DriftKind::Replicas {
+                        desired: desired.replicas,
+                        actual: actual.replicas,
+                    },
+                });
+            }
+
+            let actual_env: HashMap<&str, &str> = actual
+                .env
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str()))
+                .collect();
+            let mut keys: Vec<&str> = desired
+                .env
+                .keys()
+                .map(|k| k.as_str())
+                .chain(actual_env.keys().copied())
+                .collect();
+            keys.sort_unstable();
+            keys.dedup();
+            for key in keys {
+                let want = desired.env.get(key).map(|v| v.as_str());
+                let have = actual_env.get(key).copied();
+                if want != have {
+                    drift.push(Drift {
+                        cell: desired.name.clone(),
+                        kind: DriftKind::Env {
+                            key: key.into(),
+                            desired: want.map(Into::into),
+                            actual: have.map(Into::into),
+                        },
+                    });
+                }
+            }
+        }
+
+        for actual in observed {
+            if !self.cells.iter().any(|d| d.name == actual.name) {
+                drift.push(Drift {
+                    cell: actual.name.clone(),
+                    kind: DriftKind::Unexpected,
+                });
+            }
+        }
+
+        drift.sort_by(|a, b| a.cell.cmp(&b.cell));
+        drift
+    }
+}
+
+impl core::fmt::Display for Drift {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let show = |v: &Option<String>| v.clone().unwrap_or_else(|| "<unset>".into());
+        match &self.kind {
+            DriftKind::Missing => write!(f, "{}: declared but not running", self.cell),
+            DriftKind::Unexpected => write!(f, "{}: running but not declared", self.cell),
+            DriftKind::Version { desired, actual } => write!(
+                f,
+                "{}: version {} (desired {})",
+                self.cell,
+                show(actual),
+                desired
+            ),
+            DriftKind::Replicas { desired, actual } => write!(
+                f,
+                "{}: {} replicas (desired {})",
+                self.cell, actual, desired
+            ),
+            DriftKind::Env {
+                key,
+                desired,
+                actual,
+            } => write!(
+                f,
+                "{}: env {}={} (desired {})",
+                self.cell,
+                key,
+                show(actual),
+                show(desired)
+            ),
+        }
+    }
+}
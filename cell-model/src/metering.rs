@@ -0,0 +1,165 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Usage metering for chargeback.
+//!
+//! [`Meter`] aggregates the same events the quota ledger sees, per organism and
+//! principal, into fixed periods. Closing a period yields [`UsageRecord`]s for
+//! export; stored bytes carry over into the next period.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+
+/// Usage of one principal within one organism over one period.
+#[derive(Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq)]
+#[archive(check_bytes)]
+pub struct UsageRecord {
+    pub organism: String,
+    pub principal: String,
+    /// Unix seconds
+    pub period_start: u64,
+    pub period_end: u64,
+    pub requests: u64,
+    pub rejected: u64,
+    /// Integral of stored bytes over the period (divide by 3600 for byte-hours)
+    pub storage_byte_seconds: u64,
+    pub peak_storage_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+struct MeterAccount {
+    requests: u64,
+    rejected: u64,
+    storage_bytes: u64,
+    storage_since: u64,
+    byte_seconds: u64,
+    peak_storage: u64,
+}
+
+impl MeterAccount {
+    fn accrue(&mut self, now: u64) {
+        let elapsed = now.saturating_sub(self.storage_since);
+        self.byte_seconds = self
+            .byte_seconds
+            .saturating_add(self.storage_bytes.saturating_mul(elapsed));
+        self.storage_since = now;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Meter {
+    period_start: u64,
+    accounts: BTreeMap<(String, String), MeterAccount>,
+}
+
+impl Meter {
+    pub fn new(now_secs: u64) -> Self {
+        Self {
+            period_start: now_secs,
+            accounts: BTreeMap::new(),
+        }
+    }
+
+    pub fn period_start(&self) -> u64 {
+        self.period_start
+    }
+
+    fn account(&mut self, organism: &str, principal: &str) -> &mut MeterAccount {
+        let start = self.period_start;
+        self.accounts
+            .entry((organism.into(), principal.into()))
+            .or_insert_with(|| MeterAccount {
+                storage_since: start,
+                ..Default::default()
+            })
+    }
+
+    pub fn record_request(&mut self, organism: &str, principal: &str, admitted: bool) {
+        if admitted {
+            self.add_requests(organism, principal, 1, 0);
+        } else {
+            self.add_requests(organism, principal, 0, 1);
+        }
+    }
+
+    /// Add requests counted elsewhere, e.g. by the Membranes serving them.
+    pub fn add_requests(&mut self, organism: &str, principal: &str, admitted: u64, rejected: u64) {
+        let account = self.account(organism, principal);
+        account.requests = account.requests.saturating_add(admitted);
+        account.rejected = account.rejected.saturating_add(rejected);
+    }
+
+    /// Record that `principal` now holds `bytes` in storage.
+    pub fn record_storage(&mut self, organism: &str, principal: &str, bytes: u64, now_secs: u64) {
+        let account = self.account(organism, principal);
+        account.accrue(now_secs);
+        account.storage_bytes = bytes;
+        account.peak_storage = account.peak_storage.max(bytes);
+    }
+
+    /// Finish the current period and start the next one at `now_secs`.
+    pub fn close_period(&mut self, now_secs: u64) -> Vec<UsageRecord> {
+        let start = self.period_start;
+        let mut records = Vec::new();
+
+        for ((organism, principal), account) in self.accounts.iter_mut() {
+            account.accrue(now_secs);
+            let peak = account.peak_storage.max(account.storage_bytes);
+            if account.requests > 0 || account.rejected > 0 || account.byte_seconds > 0 {
+                records.push(UsageRecord {
+                    organism: organism.clone(),
+                    principal: principal.clone(),
+                    period_start: start,
+                    period_end: now_secs,
+                    requests: account.requests,
+                    rejected: account.rejected,
+                    storage_byte_seconds: account.byte_seconds,
+                    peak_storage_bytes: peak,
+                });
+            }
+            *account = MeterAccount {
+                storage_bytes: account.storage_bytes,
+                storage_since: now_secs,
+                peak_storage: account.storage_bytes,
+                ..Default::default()
+            };
+        }
+
+        self.accounts.retain(|_, a| a.storage_bytes > 0);
+        self.period_start = now_secs;
+        records
+    }
+}
+
+/// Render records as CSV with a header row.
+pub fn to_csv(records: &[UsageRecord]) -> String {
+    let mut out = String::from(
+        "organism,principal,period_start,period_end,requests,rejected,storage_byte_seconds,peak_storage_bytes\n",
+    );
+    for r in records {
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{}\n",
+            csv_field(&r.organism),
+            csv_field(&r.principal),
+            r.period_start,
+            r.period_end,
+            r.requests,
+            r.rejected,
+            r.storage_byte_seconds,
+            r.peak_storage_bytes
+        ));
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.into()
+    }
+}
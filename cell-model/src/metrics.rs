@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Per-method request metrics and their Prometheus text form.
+//!
+//! Generated dispatch counts every handler call in a [`MethodMetrics`]; OPS
+//! `Metrics` returns them inside a [`MetricsSnapshot`], and OPS `Prometheus`
+//! the same numbers rendered by [`prometheus`] for a scraper.
+
+use crate::ops::MetricsSnapshot;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+
+/// Upper bounds of the latency buckets in microseconds; a last bucket holds
+/// everything slower.
+pub const LATENCY_BUCKETS_US: [u64; 9] = [
+    1_000, 5_000, 10_000, 50_000, 100_000, 500_000, 1_000_000, 5_000_000, 10_000_000,
+];
+
+/// Index of the bucket counting a call of `micros`
+pub fn latency_bucket(micros: u64) -> usize {
+    LATENCY_BUCKETS_US.partition_point(|bound| *bound < micros)
+}
+
+/// Calls of one handler method since the cell started
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, Default,
+)]
+#[archive(check_bytes)]
+pub struct MethodMetrics {
+    pub method: String,
+    pub calls: u64,
+    pub errors: u64,
+    /// Calls per bucket of [`LATENCY_BUCKETS_US`], then the overflow bucket
+    pub latency_histogram: Vec<u64>,
+    pub latency_sum_us: u64,
+}
+
+impl MethodMetrics {
+    pub fn new(method: impl Into<String>) -> Self {
+        Self {
+            method: method.into(),
+            latency_histogram: alloc::vec![0; LATENCY_BUCKETS_US.len() + 1],
+            ..Self::default()
+        }
+    }
+
+    pub fn record(&mut self, micros: u64, ok: bool) {
+        self.calls += 1;
+        if !ok {
+            self.errors += 1;
+        }
+        self.latency_histogram[latency_bucket(micros)] += 1;
+        self.latency_sum_us += micros;
+    }
+}
+
+/// `snapshot` in the Prometheus text exposition format, labelled with `cell`
+pub fn prometheus(cell: &str, snapshot: &MetricsSnapshot) -> String {
+    let cell = escape(cell);
+    let mut out = String::new();
+    let _ = write_metrics(&mut out, &cell, snapshot);
+    out
+}
+
+fn write_metrics(out: &mut String, cell: &str, snapshot: &MetricsSnapshot) -> core::fmt::Result {
+    writeln!(out, "# HELP cell_requests_total Handler calls by method.")?;
+    writeln!(out, "# TYPE cell_requests_total counter")?;
+    for m in &snapshot.methods {
+        let method = escape(&m.method);
+        writeln!(
+            out,
+            "cell_requests_total{{cell=\"{}\",method=\"{}\"}} {}",
+            cell, method, m.calls
+        )?;
+    }
+    writeln!(
+        out,
+        "# HELP cell_request_errors_total Handler calls that returned an error."
+    )?;
+    writeln!(out, "# TYPE cell_request_errors_total counter")?;
+    for m in &snapshot.methods {
+        let method = escape(&m.method);
+        writeln!(
+            out,
+            "cell_request_errors_total{{cell=\"{}\",method=\"{}\"}} {}",
+            cell, method, m.errors
+        )?;
+    }
+    writeln!(out, "# HELP cell_request_duration_seconds Handler latency.")?;
+    writeln!(out, "# TYPE cell_request_duration_seconds histogram")?;
+    for m in &snapshot.methods {
+        let method = escape(&m.method);
+        let mut cumulative = 0;
+        for (i, count) in m.latency_histogram.iter().enumerate() {
+            cumulative += count;
+            let le = match LATENCY_BUCKETS_US.get(i) {
+                Some(us) => seconds(*us),
+                None => String::from("+Inf"),
+            };
+            writeln!(
+                out,
+                "cell_request_duration_seconds_bucket{{cell=\"{}\",method=\"{}\",le=\"{}\"}} {}",
+                cell, method, le, cumulative
+            )?;
+        }
+        writeln!(
+            out,
+            "cell_request_duration_seconds_sum{{cell=\"{}\",method=\"{}\"}} {}",
+            cell,
+            method,
+            seconds(m.latency_sum_us)
+        )?;
+        writeln!(
+            out,
+            "cell_request_duration_seconds_count{{cell=\"{}\",method=\"{}\"}} {}",
+            cell, method, m.calls
+        )?;
+    }
+    writeln!(out, "# TYPE cell_connections_active gauge")?;
+    writeln!(
+        out,
+        "cell_connections_active{{cell=\"{}\"}} {}",
+        cell, snapshot.connections_active
+    )?;
+    writeln!(out, "# TYPE cell_bytes_sent_total counter")?;
+    writeln!(
+        out,
+        "cell_bytes_sent_total{{cell=\"{}\"}} {}",
+        cell, snapshot.bytes_sent
+    )?;
+    writeln!(out, "# TYPE cell_bytes_received_total counter")?;
+    writeln!(
+        out,
+        "cell_bytes_received_total{{cell=\"{}\"}} {}",
+        cell, snapshot.bytes_received
+    )
+}
+
+/// Microseconds as decimal seconds, without float rounding noise
+fn seconds(us: u64) -> String {
+    let mut s = alloc::format!("{}.{:06}", us / 1_000_000, us % 1_000_000);
+    while s.ends_with('0') {
+        s.pop();
+    }
+    if s.ends_with('.') {
+        s.pop();
+    }
+    s
+}
+
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Middleware that runs inside a cell's Membrane, brought by another cell.
+//!
+//! A middleware plugin is a cell that serves a WASM module over OPS
+//! (`OpsRequest::MiddlewareModule`). Cells attach plugins in Cell.toml, in
+//! the order they run:
+//!
+//! ```toml
+//! [[middleware]]
+//! cell = "waf"
+//! bodies = true
+//! config = { deny = "union select,<script" }
+//!
+//! [[middleware]]
+//! cell = "request-metrics"
+//! ```
+//!
+//! On start the cell fetches each module and instantiates it in-process, in
+//! a sandbox of its own with no imports. Every request is shown to each
+//! module as a [`MiddlewareRequest`] before it reaches a handler, and stops
+//! at the first [`Verdict::Reject`]; once answered, each module hears the
+//! [`MiddlewareOutcome`].
+//!
+//! The module exports, with `i32` pointers into its memory and JSON in and
+//! out ([`MIDDLEWARE_ABI`] version 1):
+//!
+//! - `memory`
+//! - `cell_alloc(len) -> ptr`: room for the host to write `len` bytes
+//! - `cell_middleware_init(ptr, len)`, optional: the `config` table
+//! - `cell_middleware_request(ptr, len) -> i64`: a [`MiddlewareRequest`]
+//!   in, a [`Verdict`] out at `ptr << 32 | len`
+//! - `cell_middleware_response(ptr, len)`, optional: a [`MiddlewareOutcome`]
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use serde::{Deserialize, Serialize};
+
+/// Version of the guest interface above
+pub const MIDDLEWARE_ABI: u32 = 1;
+
+/// One `[[middleware]]` entry
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MiddlewareRef {
+    /// Plugin cell serving the module
+    pub cell: String,
+    /// Show the module request bodies that are UTF-8 (those of JSON
+    /// callers), for filters that inspect them
+    #[serde(default)]
+    pub bodies: bool,
+    /// Handed to `cell_middleware_init` as a JSON object
+    #[serde(default)]
+    pub config: BTreeMap<String, String>,
+}
+
+/// What a module sees of a request
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MiddlewareRequest {
+    /// The cell serving it
+    pub cell: String,
+    /// `cell_core::channel` it arrived on
+    pub channel: u8,
+    /// Authenticated caller, or the peer address for anonymous ones
+    pub principal: String,
+    pub peer: String,
+    /// Codec the caller speaks
+    pub codec: String,
+    /// Payload size in bytes
+    pub size: usize,
+    /// The payload, with `bodies` and when it is UTF-8
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+/// A module's answer to a request
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "verdict", rename_all = "snake_case")]
+pub enum Verdict {
+    /// On to the next module, then the handler
+    Continue,
+    /// Refused; the caller gets `message` as a permission error
+    Reject {
+        #[serde(default)]
+        message: String,
+    },
+}
+
+/// How a request a module let through went
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MiddlewareOutcome {
+    pub cell: String,
+    pub channel: u8,
+    pub principal: String,
+    /// Time in the handler, in microseconds
+    pub elapsed_us: u64,
+    pub ok: bool,
+}
@@ -3,6 +3,7 @@
 
 use alloc::string::String;
 use alloc::vec::Vec;
+use crate::watchdog::{HealthReport, HealthStatus};
 use rkyv::{Archive, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize};
 use serde::{Deserialize, Serialize};
 
@@ -15,10 +16,103 @@ pub enum OpsRequest {
     Status,
     /// Request Metrics Snapshot
     Metrics,
+    /// The same metrics in the Prometheus text format
+    Prometheus,
     /// Graceful Shutdown
     Shutdown,
     /// Fetch the source code of this cell for remote client generation
     GetSource,
+    /// Snapshot the cell's in-memory state (see `cell_sdk::state::CellState`)
+    Checkpoint,
+    /// Replace the cell's in-memory state with a snapshot
+    Restore { bytes: Vec<u8> },
+    /// Run the cell's self-health probes
+    Health,
+    /// Liveness and readiness, for supervisors deciding whether to route to
+    /// or restart the cell
+    HealthCheck,
+    /// Stop accepting application requests; in-flight ones finish
+    Drain,
+    /// Take over the listening socket of the running instance `from`, which
+    /// stops accepting and exits once its in-flight requests finish
+    Adopt { from: String },
+    /// Profile the process for `seconds` and return the encoded result
+    Profile {
+        kind: ProfileKind,
+        seconds: u32,
+        format: ProfileFormat,
+    },
+    /// Change the log filter for one target (module path), or for all when `None`
+    SetLogLevel {
+        target: Option<String>,
+        level: String,
+    },
+    /// Connections, tasks, SHM rings and memory, for debugging stuck cells
+    Inspect,
+    /// The most recent handler calls that exceeded the slow-request threshold
+    SlowRequests { limit: u32 },
+    /// Windowed call counts for methods with SLOs
+    Slo,
+    /// Sent by a synapse on connect with the schema fingerprint it was built
+    /// against; the cell answers with its own
+    Handshake { fingerprint: u64 },
+    /// Replay the cell's log through a fresh state machine and compare state
+    /// hashes (see `crate::replay`)
+    VerifyReplay,
+    /// Recently finished spans of one trace (hex trace id)
+    Spans { trace_id: String },
+    /// Methods registered at runtime (see `crate::extension`)
+    Extensions,
+    /// Load a plugin from a shared library on the cell's host and register
+    /// its methods
+    LoadPlugin { path: String },
+    /// The WASM module of a middleware plugin cell (see `crate::middleware`)
+    MiddlewareModule,
+}
+
+impl OpsRequest {
+    /// Requests that change the cell or read out its internals. A cell only
+    /// serves them on its admin socket; any other connection gets an error.
+    /// `GetSource` is not one: it returns the schema clients are built against.
+    pub fn is_privileged(&self) -> bool {
+        matches!(
+            self,
+            Self::Shutdown
+                | Self::Checkpoint
+                | Self::Restore { .. }
+                | Self::Drain
+                | Self::Adopt { .. }
+                | Self::Profile { .. }
+                | Self::SetLogLevel { .. }
+                | Self::VerifyReplay
+                | Self::LoadPlugin { .. }
+                | Self::Inspect
+                | Self::SlowRequests { .. }
+                | Self::Spans { .. }
+        )
+    }
+}
+
+#[derive(
+    Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone, Copy, PartialEq,
+)]
+#[archive(check_bytes)]
+pub enum ProfileKind {
+    /// Sampled CPU stacks at `frequency` Hz
+    Cpu { frequency: u32 },
+    /// Allocations made during the window that are still live at its end
+    Heap,
+}
+
+#[derive(
+    Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone, Copy, PartialEq,
+)]
+#[archive(check_bytes)]
+pub enum ProfileFormat {
+    /// Uncompressed pprof protobuf, as read by `go tool pprof`
+    Pprof,
+    /// Rendered flamegraph SVG
+    Flamegraph,
 }
 
 #[derive(Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone)]
@@ -30,12 +124,56 @@ pub enum OpsResponse {
         uptime_secs: u64,
         memory_usage: u64,
         consensus_role: String,
+        /// What opening the WAL cut off a damaged tail, if it had to
+        wal_repair: Option<String>,
     },
     Metrics(MetricsSnapshot),
+    Prometheus {
+        text: String,
+    },
     ShutdownAck,
     Source {
         bytes: Vec<u8>,
     },
+    Checkpoint {
+        bytes: Vec<u8>,
+    },
+    Restored,
+    Health(HealthReport),
+    HealthCheck(HealthStatus),
+    Draining,
+    /// Accepting on the adopted listener
+    Adopted,
+    Profile {
+        bytes: Vec<u8>,
+    },
+    /// The log filter in effect after `SetLogLevel`
+    LogLevel {
+        filter: String,
+    },
+    Inspect(InspectReport),
+    SlowRequests(Vec<SlowRequest>),
+    Slo(Vec<crate::slo::SloReport>),
+    /// Schema fingerprint the cell serves; 0 when it has none registered
+    Handshake {
+        fingerprint: u64,
+    },
+    Replay(crate::replay::ReplayReport),
+    Spans(Vec<SpanRecord>),
+    Extensions(crate::extension::ExtensionList),
+    /// Methods the plugin registered
+    PluginLoaded {
+        plugin: String,
+        methods: Vec<String>,
+    },
+    MiddlewareModule {
+        /// `crate::middleware::MIDDLEWARE_ABI` the module was built for
+        abi: u32,
+        wasm: Vec<u8>,
+    },
+    Error {
+        message: String,
+    },
 }
 
 #[derive(Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone)]
@@ -48,4 +186,82 @@ pub struct MetricsSnapshot {
     pub connections_active: u64,
     pub bytes_sent: u64,
     pub bytes_received: u64,
+    /// Per handler method, by name
+    pub methods: Vec<crate::metrics::MethodMetrics>,
+}
+
+#[derive(Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone, Default)]
+#[archive(check_bytes)]
+pub struct InspectReport {
+    pub connections: Vec<ConnectionInfo>,
+    pub tasks: TaskStats,
+    pub rings: Vec<RingInfo>,
+    pub memory: MemoryStats,
+}
+
+#[derive(Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone)]
+#[archive(check_bytes)]
+pub struct ConnectionInfo {
+    pub id: u64,
+    pub peer: String,
+    pub age_secs: u64,
+    pub bytes_received: u64,
+    pub bytes_sent: u64,
+    pub requests: u64,
+    /// Requests whose handler has not returned yet
+    pub pending: u32,
+}
+
+#[derive(Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone, Default)]
+#[archive(check_bytes)]
+pub struct TaskStats {
+    pub workers: u32,
+    pub alive_tasks: u64,
+    /// Tasks waiting in the runtime's global queue
+    pub queued_tasks: u64,
+}
+
+#[derive(Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone)]
+#[archive(check_bytes)]
+pub struct RingInfo {
+    pub capacity: u64,
+    pub used: u64,
+}
+
+#[derive(Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone, Default)]
+#[archive(check_bytes)]
+pub struct MemoryStats {
+    pub resident_bytes: u64,
+    pub virtual_bytes: u64,
+    /// Bytes handed out by the allocator, when it reports them
+    pub allocated_bytes: Option<u64>,
+}
+
+#[derive(Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone)]
+#[archive(check_bytes)]
+pub struct SlowRequest {
+    pub method: String,
+    pub duration_ms: u64,
+    /// Principal the call ran as, if the caller was identified
+    pub caller: Option<String>,
+    pub ok: bool,
+    /// JSON arguments, truncated, when argument capture is enabled
+    pub args: Option<String>,
+    pub at_unix_ms: u64,
+}
+
+/// One finished span: a handler call (`cell.request`) or an outgoing call
+/// (`cell.call`). Ids are lowercase hex, as in `traceparent`.
+#[derive(Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone)]
+#[archive(check_bytes)]
+pub struct SpanRecord {
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_id: Option<String>,
+    /// Cell that recorded the span
+    pub cell: String,
+    pub name: String,
+    pub start_unix_us: u64,
+    pub duration_us: u64,
+    pub ok: bool,
 }
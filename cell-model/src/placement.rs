@@ -0,0 +1,323 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Hardware requirements and node matching for placement.
+//!
+//! Cells declare requirements in `Cell.toml` (`requires = ["gpu"]`). Nodes
+//! report a [`NodeProfile`] built from hardware discovery, and the scheduler
+//! only places a cell on a node that satisfies every requirement.
+//!
+//! Edge nodes also report their [`PowerState`]. Heavy cells are kept off nodes
+//! running on battery or under thermal pressure, and [`migration_plan`] moves
+//! them away once an [`EdgePolicy`] threshold is crossed.
+//!
+//! Operators take a node out of service for maintenance by cordoning it (no
+//! new cells are placed there) and then draining it: [`drain_plan`] moves
+//! each of its cells to a node that can take it and stops the rest.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+
+#[derive(Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq)]
+#[archive(check_bytes)]
+pub struct GpuDevice {
+    /// `nvidia`, `amd`, `intel` or `apple`
+    pub vendor: String,
+    pub name: String,
+    pub memory_mb: u64,
+    /// Device node to expose to the cell, e.g. `/dev/nvidia0` or `/dev/dri/renderD128`
+    pub device: String,
+}
+
+/// Battery and thermal state of a node. Mains-powered servers report the default.
+#[derive(Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq, Default)]
+#[archive(check_bytes)]
+pub struct PowerState {
+    /// Running from battery (discharging)
+    pub on_battery: bool,
+    pub battery_pct: Option<u8>,
+    /// Hottest thermal zone in °C
+    pub temp_c: Option<f32>,
+    /// A thermal zone is past its passive trip point
+    pub throttled: bool,
+}
+
+/// What a node offers to the scheduler.
+#[derive(Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq, Default)]
+#[archive(check_bytes)]
+pub struct NodeProfile {
+    pub node_id: u64,
+    pub address: String,
+    pub gpus: Vec<GpuDevice>,
+    pub has_avx512: bool,
+    pub is_tee: bool,
+    pub power: PowerState,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Requirement {
+    /// `gpu`, `gpu:<vendor>`, `gpu:<vendor>:<min memory MB>`
+    Gpu {
+        vendor: Option<String>,
+        min_memory_mb: Option<u64>,
+    },
+    Avx512,
+    Tee,
+}
+
+impl Requirement {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut parts = s.trim().split(':');
+        match parts.next().unwrap_or_default() {
+            "gpu" => {
+                let vendor = parts.next().filter(|v| !v.is_empty()).map(|v| v.to_lowercase());
+                let min_memory_mb = match parts.next() {
+                    Some(m) => Some(
+                        m.parse()
+                            .map_err(|_| format!("Invalid GPU memory in requirement '{}'", s))?,
+                    ),
+                    None => None,
+                };
+                Ok(Self::Gpu {
+                    vendor,
+                    min_memory_mb,
+                })
+            }
+            "avx512" => Ok(Self::Avx512),
+            "tee" => Ok(Self::Tee),
+            other => Err(format!("Unknown requirement '{}'", other)),
+        }
+    }
+
+    /// Parse a list of requirements, e.g. from `Cell.toml` or a comma-separated string.
+    pub fn parse_all<S: AsRef<str>>(items: &[S]) -> Result<Vec<Self>, String> {
+        items
+            .iter()
+            .flat_map(|i| i.as_ref().split(','))
+            .filter(|i| !i.trim().is_empty())
+            .map(Self::parse)
+            .collect()
+    }
+
+    fn accepts(&self, gpu: &GpuDevice) -> bool {
+        match self {
+            Self::Gpu {
+                vendor,
+                min_memory_mb,
+            } => {
+                vendor.as_ref().is_none_or(|v| *v == gpu.vendor)
+                    && min_memory_mb.is_none_or(|m| gpu.memory_mb >= m)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl NodeProfile {
+    /// GPUs on this node usable under `requirements` (empty if no GPU is required).
+    pub fn matching_gpus(&self, requirements: &[Requirement]) -> Vec<&GpuDevice> {
+        let gpu_reqs: Vec<&Requirement> = requirements
+            .iter()
+            .filter(|r| matches!(r, Requirement::Gpu { .. }))
+            .collect();
+        if gpu_reqs.is_empty() {
+            return Vec::new();
+        }
+        self.gpus
+            .iter()
+            .filter(|g| gpu_reqs.iter().all(|r| r.accepts(g)))
+            .collect()
+    }
+
+    pub fn satisfies(&self, requirements: &[Requirement]) -> bool {
+        requirements.iter().all(|r| match r {
+            Requirement::Gpu { .. } => !self.matching_gpus(requirements).is_empty(),
+            Requirement::Avx512 => self.has_avx512,
+            Requirement::Tee => self.is_tee,
+        })
+    }
+}
+
+/// The first node that satisfies every requirement.
+pub fn place<'a>(nodes: &'a [NodeProfile], requirements: &[Requirement]) -> Option<&'a NodeProfile> {
+    nodes.iter().find(|n| n.satisfies(requirements))
+}
+
+/// Thresholds for edge nodes.
+///
+/// New heavy cells avoid any node on battery, throttled or hotter than
+/// `max_temp_c`; cells already running are only migrated once the battery
+/// drops below `min_battery_pct` or the node gets too hot, so a node briefly
+/// unplugged is not drained immediately.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgePolicy {
+    pub min_battery_pct: u8,
+    pub max_temp_c: f32,
+}
+
+impl Default for EdgePolicy {
+    fn default() -> Self {
+        Self {
+            min_battery_pct: 30,
+            max_temp_c: 90.0,
+        }
+    }
+}
+
+impl EdgePolicy {
+    /// Why heavy cells should not be started on `node`.
+    pub fn avoid(&self, node: &NodeProfile) -> Option<String> {
+        let p = &node.power;
+        if let Some(temp) = p.temp_c.filter(|t| *t > self.max_temp_c) {
+            return Some(format!("{:.0}°C exceeds {:.0}°C", temp, self.max_temp_c));
+        }
+        if p.throttled {
+            return Some("thermally throttled".into());
+        }
+        if p.on_battery {
+            return Some(match p.battery_pct {
+                Some(pct) => format!("on battery ({}%)", pct),
+                None => "on battery".into(),
+            });
+        }
+        None
+    }
+
+    /// Why heavy cells already on `node` should move elsewhere.
+    pub fn evict(&self, node: &NodeProfile) -> Option<String> {
+        let p = &node.power;
+        if let Some(temp) = p.temp_c.filter(|t| *t > self.max_temp_c) {
+            return Some(format!("{:.0}°C exceeds {:.0}°C", temp, self.max_temp_c));
+        }
+        if p.throttled {
+            return Some("thermally throttled".into());
+        }
+        match p.battery_pct {
+            Some(pct) if p.on_battery && pct < self.min_battery_pct => Some(format!(
+                "battery at {}% (below {}%)",
+                pct, self.min_battery_pct
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Like [`place`], but heavy cells skip nodes the policy wants to avoid.
+pub fn place_with_policy<'a>(
+    nodes: &'a [NodeProfile],
+    requirements: &[Requirement],
+    heavy: bool,
+    policy: &EdgePolicy,
+) -> Option<&'a NodeProfile> {
+    nodes
+        .iter()
+        .filter(|n| !heavy || policy.avoid(n).is_none())
+        .find(|n| n.satisfies(requirements))
+}
+
+/// A cell instance as seen by the scheduler.
+#[derive(Debug, Clone)]
+pub struct RunningCell {
+    pub name: String,
+    pub node_id: u64,
+    pub heavy: bool,
+    pub requirements: Vec<Requirement>,
+}
+
+#[derive(Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq)]
+#[archive(check_bytes)]
+pub struct Migration {
+    pub cell: String,
+    pub from: u64,
+    pub to: u64,
+    pub reason: String,
+}
+
+/// Heavy cells on nodes past the eviction thresholds, with a better node for each.
+/// Cells with nowhere better to go stay where they are.
+pub fn migration_plan(
+    nodes: &[NodeProfile],
+    running: &[RunningCell],
+    policy: &EdgePolicy,
+) -> Vec<Migration> {
+    running
+        .iter()
+        .filter(|c| c.heavy)
+        .filter_map(|c| {
+            let node = nodes.iter().find(|n| n.node_id == c.node_id)?;
+            let reason = policy.evict(node)?;
+            let target = nodes
+                .iter()
+                .filter(|n| n.node_id != c.node_id && policy.avoid(n).is_none())
+                .find(|n| n.satisfies(&c.requirements))?;
+            Some(Migration {
+                cell: c.name.clone(),
+                from: c.node_id,
+                to: target.node_id,
+                reason,
+            })
+        })
+        .collect()
+}
+
+/// What draining a node does with each of its cells.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DrainPlan {
+    pub migrations: Vec<Migration>,
+    /// Cells no other node can take; they are stopped
+    pub stopped: Vec<String>,
+}
+
+/// Move every cell on `node_id` to another node that satisfies its
+/// requirements and is not `cordoned`. Heavy cells also skip nodes the policy
+/// wants to avoid. Cells on other nodes are left alone.
+pub fn drain_plan(
+    nodes: &[NodeProfile],
+    running: &[RunningCell],
+    node_id: u64,
+    cordoned: &[u64],
+    policy: &EdgePolicy,
+) -> DrainPlan {
+    let targets: Vec<NodeProfile> = nodes
+        .iter()
+        .filter(|n| n.node_id != node_id && !cordoned.contains(&n.node_id))
+        .cloned()
+        .collect();
+
+    let mut plan = DrainPlan::default();
+    for c in running.iter().filter(|c| c.node_id == node_id) {
+        match place_with_policy(&targets, &c.requirements, c.heavy, policy) {
+            Some(target) => plan.migrations.push(Migration {
+                cell: c.name.clone(),
+                from: node_id,
+                to: target.node_id,
+                reason: "node drained".to_string(),
+            }),
+            None => plan.stopped.push(c.name.clone()),
+        }
+    }
+    plan
+}
+
+/// Environment telling a child process which GPUs it was given.
+pub fn gpu_env(gpus: &[&GpuDevice]) -> Vec<(String, String)> {
+    if gpus.is_empty() {
+        return Vec::new();
+    }
+    let mut env = Vec::new();
+    let devices: Vec<&str> = gpus.iter().map(|g| g.device.as_str()).collect();
+    env.push(("CELL_GPU_DEVICES".to_string(), devices.join(",")));
+
+    let nvidia: Vec<String> = gpus
+        .iter()
+        .filter(|g| g.vendor == "nvidia")
+        .filter_map(|g| g.device.strip_prefix("/dev/nvidia").map(|i| i.to_string()))
+        .collect();
+    if !nvidia.is_empty() {
+        env.push(("CUDA_VISIBLE_DEVICES".to_string(), nvidia.join(",")));
+    }
+    env
+}
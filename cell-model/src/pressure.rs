@@ -0,0 +1,197 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Priority classes and what a resource constrained node gives up first.
+//!
+//! Every cell declares a [`PriorityClass`] in its Cell.toml (`priority =
+//! "best-effort"`). The hypervisor samples [`Pressure`] from `/proc`,
+//! classifies it into a [`PressureLevel`] and, instead of letting the kernel
+//! OOM-kill at random, stops the cells [`shed`] picks: best-effort first, then
+//! standard. Critical cells are never shed. While the node is constrained it
+//! runs in degraded mode and only [`admits`] the classes it can afford.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+
+#[derive(
+    Archive,
+    Serialize,
+    Deserialize,
+    SerdeSerialize,
+    SerdeDeserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Default,
+)]
+#[archive(check_bytes)]
+#[serde(rename_all = "kebab-case")]
+pub enum PriorityClass {
+    /// Kept running whatever the pressure (axon, builder, storage)
+    Critical,
+    #[default]
+    Standard,
+    /// Shed first, and the first the kernel picks should it still OOM
+    BestEffort,
+}
+
+impl PriorityClass {
+    pub fn name(self) -> &'static str {
+        match self {
+            PriorityClass::Critical => "critical",
+            PriorityClass::Standard => "standard",
+            PriorityClass::BestEffort => "best-effort",
+        }
+    }
+
+    /// `/proc/<pid>/oom_score_adj` for processes of this class. Lowering it
+    /// needs CAP_SYS_RESOURCE, so without it critical cells stay at 0.
+    pub fn oom_score_adj(self) -> i32 {
+        match self {
+            PriorityClass::Critical => -500,
+            PriorityClass::Standard => 0,
+            PriorityClass::BestEffort => 500,
+        }
+    }
+}
+
+#[derive(
+    Archive,
+    Serialize,
+    Deserialize,
+    SerdeSerialize,
+    SerdeDeserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[archive(check_bytes)]
+pub enum PressureLevel {
+    Normal,
+    /// Best-effort cells are shed
+    Constrained,
+    /// Only critical cells keep running
+    Critical,
+}
+
+impl PressureLevel {
+    pub fn degraded(self) -> bool {
+        self != PressureLevel::Normal
+    }
+}
+
+/// A sample of how loaded the node is
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct Pressure {
+    /// `MemAvailable` as a percentage of `MemTotal`
+    pub mem_available_pct: f32,
+    /// One minute load average divided by the number of CPUs
+    pub load_per_cpu: f32,
+}
+
+impl Pressure {
+    /// From the contents of `/proc/meminfo` and `/proc/loadavg`
+    pub fn from_proc(meminfo: &str, loadavg: &str, cpus: usize) -> Option<Self> {
+        let field = |name: &str| -> Option<f32> {
+            meminfo
+                .lines()
+                .find_map(|l| l.strip_prefix(name)?.strip_prefix(':'))?
+                .split_whitespace()
+                .next()?
+                .parse()
+                .ok()
+        };
+        let total = field("MemTotal").filter(|t| *t > 0.0)?;
+        let available = field("MemAvailable")?;
+        let load: f32 = loadavg.split_whitespace().next()?.parse().ok()?;
+        Some(Self {
+            mem_available_pct: available * 100.0 / total,
+            load_per_cpu: load / cpus.max(1) as f32,
+        })
+    }
+}
+
+/// When a [`Pressure`] sample counts as constrained or critical. A node
+/// returns to normal only once it is `recover_margin_pct` above the
+/// constrained memory threshold, so shed cells do not flap back on.
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct PressureThresholds {
+    pub constrained_mem_pct: f32,
+    pub critical_mem_pct: f32,
+    pub constrained_load: f32,
+    pub critical_load: f32,
+    pub recover_margin_pct: f32,
+}
+
+impl Default for PressureThresholds {
+    fn default() -> Self {
+        Self {
+            constrained_mem_pct: 10.0,
+            critical_mem_pct: 5.0,
+            constrained_load: 2.0,
+            critical_load: 4.0,
+            recover_margin_pct: 5.0,
+        }
+    }
+}
+
+impl PressureThresholds {
+    /// Level for `sample`, given the level the node is in now
+    pub fn classify(&self, sample: &Pressure, current: PressureLevel) -> PressureLevel {
+        let level = if sample.mem_available_pct < self.critical_mem_pct
+            || sample.load_per_cpu > self.critical_load
+        {
+            PressureLevel::Critical
+        } else if sample.mem_available_pct < self.constrained_mem_pct
+            || sample.load_per_cpu > self.constrained_load
+        {
+            PressureLevel::Constrained
+        } else {
+            PressureLevel::Normal
+        };
+
+        if level < current
+            && sample.mem_available_pct < self.constrained_mem_pct + self.recover_margin_pct
+        {
+            // Not clear of the threshold yet: step down no further than constrained
+            return current.min(PressureLevel::Constrained).max(level);
+        }
+        level
+    }
+}
+
+/// Whether a node at `level` starts cells of `class`
+pub fn admits(class: PriorityClass, level: PressureLevel) -> bool {
+    match level {
+        PressureLevel::Normal => true,
+        PressureLevel::Constrained => class != PriorityClass::BestEffort,
+        PressureLevel::Critical => class == PriorityClass::Critical,
+    }
+}
+
+/// The running cells to stop at `level`, lowest priority first
+pub fn shed(running: &[(String, PriorityClass)], level: PressureLevel) -> Vec<String> {
+    let mut victims: Vec<&(String, PriorityClass)> = running
+        .iter()
+        .filter(|(_, class)| !admits(*class, level))
+        .collect();
+    victims.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    victims.into_iter().map(|(name, _)| name.clone()).collect()
+}
@@ -57,6 +57,21 @@ pub enum Primitive { String, U8, U16, U32, U64, I8, I16, I32, I64, F32, F64, Boo
 pub enum MitosisRequest {
     Spawn { cell_name: String, config: Option<CellInitConfig> },
     Test { target_cell: String, filter: Option<String> },
+    /// Running instances of a cell (see [`is_instance_of`])
+    ListInstances { cell_name: String },
+    /// Stop one instance and start it again with the same config
+    Restart { instance: String },
+    /// Bind the cell's socket now but start the cell only when the first
+    /// connection arrives, handing it the listener (socket activation)
+    SpawnLazy { cell_name: String, config: Option<CellInitConfig> },
+    /// Stop one instance for good: SIGTERM, then SIGKILL once the grace period is over
+    Stop { instance: String },
+    /// Refuse (or, with `false`, accept again) new cells on this node while
+    /// it is under maintenance. Cells already running are left alone.
+    Cordon { cordoned: bool },
+    /// Start the cell from an archived binary (its blobstore hash) instead of
+    /// building the registry source, e.g. to roll back to an earlier version
+    SpawnArtifact { cell_name: String, artifact: String, config: Option<CellInitConfig> },
 }
 
 #[derive(Serialize, Deserialize, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug)]
@@ -64,6 +79,18 @@ pub enum MitosisRequest {
 pub enum MitosisResponse {
     Ok { socket_path: String },
     Denied { reason: String },
+    Instances { names: Vec<String> },
+}
+
+/// Replicas of `cell` are named `cell` or `cell-<n>`.
+pub fn is_instance_of(instance: &str, cell: &str) -> bool {
+    match instance.strip_prefix(cell) {
+        Some("") => true,
+        Some(rest) => rest
+            .strip_prefix('-')
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit())),
+        None => false,
+    }
 }
 
 // --- MESH PROTOCOL ---
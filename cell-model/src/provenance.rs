@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Build provenance: which source, toolchain and flags produced a binary.
+//!
+//! The builder describes every binary it produces in an in-toto [`Statement`]
+//! whose subject is the binary's blake3 digest, and signs it with its node key
+//! inside a DSSE-style [`Envelope`]. The envelope is kept next to the binary,
+//! named by that digest, so a hypervisor can find the attestation of any
+//! binary it is about to run, built locally or fetched from the blobstore,
+//! and check both the signature and that the digest still matches.
+//!
+//! Signing and JSON live in `cell_sdk::provenance`; this module only fixes
+//! the format and what is signed.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+pub const STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+pub const PREDICATE_TYPE: &str = "https://github.com/Leif-Rydenfalk/cell/provenance/v1";
+pub const PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+/// Digest algorithm of the subjects, as in-toto names it
+pub const DIGEST: &str = "blake3";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Statement {
+    #[serde(rename = "_type")]
+    pub statement_type: String,
+    pub subject: Vec<Subject>,
+    #[serde(rename = "predicateType")]
+    pub predicate_type: String,
+    pub predicate: Provenance,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Subject {
+    pub name: String,
+    /// Algorithm to hex digest
+    pub digest: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Provenance {
+    /// Node whose key signed the statement
+    pub builder: String,
+    /// Git remote the source came from, if it was a checkout
+    pub source_repo: Option<String>,
+    pub commit: Option<String>,
+    /// Hash of the source tree the builder compiled
+    pub source_hash: String,
+    /// `rustc -V` of the toolchain
+    pub toolchain: String,
+    /// Cargo arguments, then `RUSTFLAGS` if set
+    pub build_flags: Vec<String>,
+    /// Unix seconds
+    pub built_at: u64,
+}
+
+impl Statement {
+    /// A statement about the binary of `cell` with blake3 `digest`
+    pub fn new(cell: &str, digest: &str, predicate: Provenance) -> Self {
+        let mut digests = BTreeMap::new();
+        digests.insert(DIGEST.to_string(), digest.to_string());
+        Self {
+            statement_type: STATEMENT_TYPE.to_string(),
+            subject: vec![Subject {
+                name: cell.to_string(),
+                digest: digests,
+            }],
+            predicate_type: PREDICATE_TYPE.to_string(),
+            predicate,
+        }
+    }
+
+    /// Check that this is a cell provenance statement about the binary with
+    /// blake3 `digest`
+    pub fn covers(&self, digest: &str) -> Result<(), String> {
+        if self.statement_type != STATEMENT_TYPE {
+            return Err(format!("not an in-toto statement: {}", self.statement_type));
+        }
+        if self.predicate_type != PREDICATE_TYPE {
+            return Err(format!("unknown predicate {}", self.predicate_type));
+        }
+        let subject = self.subject.iter().any(|s| {
+            s.digest
+                .get(DIGEST)
+                .is_some_and(|d| d.eq_ignore_ascii_case(digest))
+        });
+        if !subject {
+            return Err(format!("no subject with {} digest {}", DIGEST, digest));
+        }
+        Ok(())
+    }
+}
+
+/// A signed statement. The payload is the statement's JSON exactly as it was
+/// signed; it is parsed only after a signature over it verified.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Envelope {
+    #[serde(rename = "payloadType")]
+    pub payload_type: String,
+    pub payload: String,
+    pub signatures: Vec<EnvelopeSignature>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EnvelopeSignature {
+    /// Name of the signing node, whose public key verifiers trust
+    pub keyid: String,
+    /// Ed25519 signature over [`Envelope::signed_bytes`]
+    pub sig: Vec<u8>,
+}
+
+impl Envelope {
+    /// DSSE pre-authentication encoding of `payload`: what is signed, so a
+    /// signature cannot be replayed for another payload type
+    pub fn pae(payload_type: &str, payload: &[u8]) -> Vec<u8> {
+        let mut out = format!(
+            "DSSEv1 {} {} {} ",
+            payload_type.len(),
+            payload_type,
+            payload.len()
+        )
+        .into_bytes();
+        out.extend_from_slice(payload);
+        out
+    }
+
+    pub fn signed_bytes(&self) -> Vec<u8> {
+        Self::pae(&self.payload_type, self.payload.as_bytes())
+    }
+}
@@ -0,0 +1,312 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Per-principal quota accounting.
+//!
+//! [`QuotaLedger`] is shared by the `quota` cell (the authoritative view), the
+//! Membrane (request rate and in-flight requests) and storage cells (bytes
+//! stored). Time is passed in explicitly as Unix milliseconds.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+
+const WINDOW_MS: u64 = 60_000;
+
+/// Limits for one principal. `None` means unlimited.
+#[derive(
+    Archive,
+    Serialize,
+    Deserialize,
+    SerdeSerialize,
+    SerdeDeserialize,
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct QuotaLimits {
+    pub requests_per_min: Option<u32>,
+    pub storage_bytes: Option<u64>,
+    pub queue_depth: Option<u32>,
+}
+
+#[derive(
+    Archive,
+    Serialize,
+    Deserialize,
+    SerdeSerialize,
+    SerdeDeserialize,
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct QuotaUsage {
+    pub requests_last_min: u32,
+    pub storage_bytes: u64,
+    pub queue_depth: u32,
+}
+
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, Copy, PartialEq,
+)]
+#[archive(check_bytes)]
+pub enum QuotaKind {
+    Requests,
+    Storage,
+    Queue,
+}
+
+/// A principal at (or over) one of its limits.
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct QuotaBreach {
+    pub principal: String,
+    pub kind: QuotaKind,
+    pub current: u64,
+    pub limit: u64,
+}
+
+impl fmt::Display for QuotaBreach {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.kind {
+            QuotaKind::Requests => "requests/min",
+            QuotaKind::Storage => "storage bytes",
+            QuotaKind::Queue => "queued requests",
+        };
+        write!(
+            f,
+            "quota exceeded for '{}': {} {} (limit {})",
+            self.principal, self.current, what, self.limit
+        )
+    }
+}
+
+/// Usage of one principal. Request times are only kept while a rate limit
+/// applies, and an account holding nothing is dropped, so principals seen once
+/// do not stay in the ledger.
+#[derive(Debug, Clone, Default)]
+struct Account {
+    requests: VecDeque<u64>,
+    storage_bytes: u64,
+    queue_depth: u32,
+}
+
+impl Account {
+    fn is_idle(&self) -> bool {
+        self.requests.is_empty() && self.storage_bytes == 0 && self.queue_depth == 0
+    }
+
+    fn prune(&mut self, now_ms: u64) {
+        while self
+            .requests
+            .front()
+            .is_some_and(|t| now_ms.saturating_sub(*t) >= WINDOW_MS)
+        {
+            self.requests.pop_front();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct QuotaLedger {
+    default_limits: QuotaLimits,
+    limits: BTreeMap<String, QuotaLimits>,
+    accounts: BTreeMap<String, Account>,
+}
+
+impl QuotaLedger {
+    /// Ledger applying `default_limits` to principals without their own limits.
+    pub fn new(default_limits: QuotaLimits) -> Self {
+        Self {
+            default_limits,
+            ..Self::default()
+        }
+    }
+
+    pub fn set_limits(&mut self, principal: &str, limits: QuotaLimits) {
+        self.limits.insert(principal.into(), limits);
+    }
+
+    /// Apply `default_limits` to every principal, dropping their own limits.
+    /// Usage so far is kept.
+    pub fn reset_limits(&mut self, default_limits: QuotaLimits) {
+        self.default_limits = default_limits;
+        self.limits.clear();
+    }
+
+    pub fn limits(&self, principal: &str) -> &QuotaLimits {
+        self.limits.get(principal).unwrap_or(&self.default_limits)
+    }
+
+    fn account(&mut self, principal: &str) -> &mut Account {
+        self.accounts.entry(principal.into()).or_default()
+    }
+
+    fn forget_if_idle(&mut self, principal: &str) {
+        if self.accounts.get(principal).is_some_and(Account::is_idle) {
+            self.accounts.remove(principal);
+        }
+    }
+
+    fn breach(&self, principal: &str, kind: QuotaKind, current: u64, limit: u64) -> QuotaBreach {
+        QuotaBreach {
+            principal: principal.into(),
+            kind,
+            current,
+            limit,
+        }
+    }
+
+    fn rate_breach(&mut self, principal: &str, now_ms: u64) -> Option<QuotaBreach> {
+        let limit = self.limits(principal).requests_per_min?;
+        let current = match self.accounts.get_mut(principal) {
+            Some(account) => {
+                account.prune(now_ms);
+                account.requests.len() as u64
+            }
+            None => 0,
+        };
+        (current >= limit as u64)
+            .then(|| self.breach(principal, QuotaKind::Requests, current, limit as u64))
+    }
+
+    fn queue_breach(&self, principal: &str) -> Option<QuotaBreach> {
+        let limit = self.limits(principal).queue_depth?;
+        let current = self.accounts.get(principal).map_or(0, |a| a.queue_depth);
+        (current >= limit)
+            .then(|| self.breach(principal, QuotaKind::Queue, current as u64, limit as u64))
+    }
+
+    fn record_request(&mut self, principal: &str, now_ms: u64) {
+        if self.limits(principal).requests_per_min.is_some() {
+            self.account(principal).requests.push_back(now_ms);
+        }
+    }
+
+    /// Count a request against the per-minute rate, or reject it.
+    pub fn admit_request(&mut self, principal: &str, now_ms: u64) -> Result<(), QuotaBreach> {
+        let result = match self.rate_breach(principal, now_ms) {
+            Some(breach) => Err(breach),
+            None => {
+                self.record_request(principal, now_ms);
+                Ok(())
+            }
+        };
+        self.forget_if_idle(principal);
+        result
+    }
+
+    /// Admit a request within both the per-minute rate and the queue. Nothing
+    /// is counted unless both allow it; pair every `Ok` with
+    /// [`leave_queue`](Self::leave_queue).
+    pub fn admit(&mut self, principal: &str, now_ms: u64) -> Result<(), QuotaBreach> {
+        let breach = match self.rate_breach(principal, now_ms) {
+            Some(breach) => Some(breach),
+            None => self.queue_breach(principal),
+        };
+        if let Some(breach) = breach {
+            self.forget_if_idle(principal);
+            return Err(breach);
+        }
+        self.record_request(principal, now_ms);
+        self.account(principal).queue_depth += 1;
+        Ok(())
+    }
+
+    /// Apply a change in stored bytes. Growth past the limit is rejected; shrinking always succeeds.
+    pub fn reserve_storage(&mut self, principal: &str, delta: i64) -> Result<(), QuotaBreach> {
+        let limit = self.limits(principal).storage_bytes;
+        let account = self.account(principal);
+        let next = if delta >= 0 {
+            account.storage_bytes.saturating_add(delta as u64)
+        } else {
+            account.storage_bytes.saturating_sub(delta.unsigned_abs())
+        };
+        if let Some(limit) = limit {
+            if delta > 0 && next > limit {
+                return Err(self.breach(principal, QuotaKind::Storage, next, limit));
+            }
+        }
+        self.account(principal).storage_bytes = next;
+        self.forget_if_idle(principal);
+        Ok(())
+    }
+
+    /// Enter the principal's queue of in-flight requests.
+    pub fn enter_queue(&mut self, principal: &str) -> Result<(), QuotaBreach> {
+        if let Some(breach) = self.queue_breach(principal) {
+            return Err(breach);
+        }
+        self.account(principal).queue_depth += 1;
+        Ok(())
+    }
+
+    pub fn leave_queue(&mut self, principal: &str) {
+        if let Some(account) = self.accounts.get_mut(principal) {
+            account.queue_depth = account.queue_depth.saturating_sub(1);
+            self.forget_if_idle(principal);
+        }
+    }
+
+    pub fn usage(&mut self, principal: &str, now_ms: u64) -> QuotaUsage {
+        let Some(account) = self.accounts.get_mut(principal) else {
+            return QuotaUsage::default();
+        };
+        account.prune(now_ms);
+        let usage = QuotaUsage {
+            requests_last_min: account.requests.len() as u32,
+            storage_bytes: account.storage_bytes,
+            queue_depth: account.queue_depth,
+        };
+        self.forget_if_idle(principal);
+        usage
+    }
+
+    pub fn principals(&self) -> impl Iterator<Item = &str> {
+        self.accounts.keys().map(|p| p.as_str())
+    }
+
+    /// Every usage at or above `threshold_pct` percent of its limit.
+    pub fn alerts(&mut self, now_ms: u64, threshold_pct: u8) -> Vec<QuotaBreach> {
+        let principals: Vec<String> = self.accounts.keys().cloned().collect();
+        let mut alerts = Vec::new();
+        for principal in principals {
+            let usage = self.usage(&principal, now_ms);
+            let limits = self.limits(&principal).clone();
+            let checks = [
+                (
+                    QuotaKind::Requests,
+                    usage.requests_last_min as u64,
+                    limits.requests_per_min.map(u64::from),
+                ),
+                (
+                    QuotaKind::Storage,
+                    usage.storage_bytes,
+                    limits.storage_bytes,
+                ),
+                (
+                    QuotaKind::Queue,
+                    usage.queue_depth as u64,
+                    limits.queue_depth.map(u64::from),
+                ),
+            ];
+            for (kind, current, limit) in checks {
+                if let Some(limit) = limit {
+                    if current * 100 >= limit * threshold_pct as u64 {
+                        alerts.push(self.breach(&principal, kind, current, limit));
+                    }
+                }
+            }
+        }
+        alerts
+    }
+}
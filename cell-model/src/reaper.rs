@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Orphaned runtime artifacts and when the `reaper` cell may delete them.
+//!
+//! The reaper scans for shared memory segments, axon proxy sockets, WAL
+//! temp files and test sockets, and probes each: is a file still open, is a
+//! socket still accepting. [`plan`] turns those [`Finding`]s into the
+//! artifacts to remove; scanning, probing and deleting stay in the cell.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+
+#[derive(
+    Archive,
+    Serialize,
+    Deserialize,
+    SerdeSerialize,
+    SerdeDeserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+)]
+#[archive(check_bytes)]
+pub enum ArtifactKind {
+    /// `/dev/shm` segment left by a crashed cell
+    ShmSegment,
+    /// `axon_proxy_*.sock` whose axon is gone
+    ProxySocket,
+    /// `*.tmp`, `*.snapshot.tmp` or `*.snapshot.part` next to a WAL
+    WalTemp,
+    /// Socket under `target/test-sockets`
+    TestSocket,
+}
+
+impl ArtifactKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ArtifactKind::ShmSegment => "shm",
+            ArtifactKind::ProxySocket => "proxy-socket",
+            ArtifactKind::WalTemp => "wal-temp",
+            ArtifactKind::TestSocket => "test-socket",
+        }
+    }
+}
+
+/// Something the reaper found on disk
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct Finding {
+    pub path: String,
+    pub kind: ArtifactKind,
+    pub bytes: u64,
+    /// Seconds since last modification
+    pub age_secs: u64,
+    /// Open by a process, or a socket still accepting connections
+    pub in_use: bool,
+}
+
+/// How old an unused artifact must be before it is removed. The grace
+/// periods cover writers that have not yet opened or renamed their file.
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct ReapPolicy {
+    pub shm_grace_secs: u64,
+    pub socket_grace_secs: u64,
+    pub wal_temp_grace_secs: u64,
+    pub test_socket_ttl_secs: u64,
+}
+
+impl Default for ReapPolicy {
+    fn default() -> Self {
+        Self {
+            shm_grace_secs: 300,
+            socket_grace_secs: 60,
+            wal_temp_grace_secs: 3600,
+            test_socket_ttl_secs: 3600,
+        }
+    }
+}
+
+impl ReapPolicy {
+    pub fn grace_secs(&self, kind: ArtifactKind) -> u64 {
+        match kind {
+            ArtifactKind::ShmSegment => self.shm_grace_secs,
+            ArtifactKind::ProxySocket => self.socket_grace_secs,
+            ArtifactKind::WalTemp => self.wal_temp_grace_secs,
+            ArtifactKind::TestSocket => self.test_socket_ttl_secs,
+        }
+    }
+}
+
+/// The findings that are orphaned: unused and past their grace period
+pub fn plan(findings: &[Finding], policy: &ReapPolicy) -> Vec<Finding> {
+    findings
+        .iter()
+        .filter(|f| !f.in_use && f.age_secs >= policy.grace_secs(f.kind))
+        .cloned()
+        .collect()
+}
+
+/// Whether `file_name` is a temp file left by an interrupted WAL, snapshot or
+/// state write
+pub fn is_wal_temp(file_name: &str) -> bool {
+    file_name.ends_with(".tmp") || file_name.ends_with(".snapshot.part")
+}
+
+/// Whether `file_name` is a proxy socket bound by axon
+pub fn is_proxy_socket(file_name: &str) -> bool {
+    file_name.starts_with("axon_proxy_") && file_name.ends_with(".sock")
+}
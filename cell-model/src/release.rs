@@ -0,0 +1,184 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Release channels and resolving a pinned cell to a concrete version.
+//!
+//! A published version never changes; a [`Channel`] is a name that moves
+//! between versions. The registry keeps every move as a [`ChannelEntry`], so
+//! the head of a channel is its newest entry and the history doubles as the
+//! audit trail. Versions only move up: nightly to beta, beta to stable.
+//!
+//! Consumers pin a neighbor as `ledger@beta` (or `channel = "beta"` in
+//! Cell.toml), a version as `ledger@1.4.0`, or nothing at all; see
+//! [`CellRef`]. Mycelium and the builder resolve the pin when the cell is
+//! spawned, so a restart never picks up a version nobody asked for.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+
+#[derive(
+    Archive,
+    Serialize,
+    Deserialize,
+    SerdeSerialize,
+    SerdeDeserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+#[archive(check_bytes)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    Nightly,
+    Beta,
+    Stable,
+}
+
+impl Channel {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "nightly" => Some(Channel::Nightly),
+            "beta" => Some(Channel::Beta),
+            "stable" => Some(Channel::Stable),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Channel::Nightly => "nightly",
+            Channel::Beta => "beta",
+            Channel::Stable => "stable",
+        }
+    }
+
+    /// The channel a version on this one is promoted to next
+    pub fn next(self) -> Option<Self> {
+        match self {
+            Channel::Nightly => Some(Channel::Beta),
+            Channel::Beta => Some(Channel::Stable),
+            Channel::Stable => None,
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pin {
+    /// Whatever is installed or newest
+    Latest,
+    Channel(Channel),
+    Version(String),
+}
+
+/// A cell name with an optional pin, written `name`, `name@channel` or
+/// `name@version`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellRef {
+    pub name: String,
+    pub pin: Pin,
+}
+
+impl CellRef {
+    pub fn parse(reference: &str) -> Self {
+        let reference = reference.trim();
+        let Some((name, pin)) = reference.split_once('@') else {
+            return Self::latest(reference);
+        };
+        let pin = match Channel::parse(pin) {
+            Some(channel) => Pin::Channel(channel),
+            None if pin.is_empty() => Pin::Latest,
+            None => Pin::Version(pin.to_string()),
+        };
+        Self {
+            name: name.to_string(),
+            pin,
+        }
+    }
+
+    pub fn latest(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            pin: Pin::Latest,
+        }
+    }
+
+    pub fn on(name: &str, channel: Channel) -> Self {
+        Self {
+            name: name.to_string(),
+            pin: Pin::Channel(channel),
+        }
+    }
+
+    pub fn at(name: &str, version: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            pin: Pin::Version(version.to_string()),
+        }
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        self.pin != Pin::Latest
+    }
+}
+
+impl fmt::Display for CellRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.pin {
+            Pin::Latest => f.write_str(&self.name),
+            Pin::Channel(channel) => write!(f, "{}@{}", self.name, channel),
+            Pin::Version(version) => write!(f, "{}@{}", self.name, version),
+        }
+    }
+}
+
+/// One move of a channel, oldest first in the registry's history
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct ChannelEntry {
+    pub channel: Channel,
+    pub version: String,
+    /// `publish`, `push` or `promote`
+    pub action: String,
+    pub actor: String,
+    pub at: u64,
+}
+
+/// Current version on `channel`, given `history` oldest first
+pub fn head(history: &[ChannelEntry], channel: Channel) -> Option<&str> {
+    history
+        .iter()
+        .rev()
+        .find(|e| e.channel == channel)
+        .map(|e| e.version.as_str())
+}
+
+/// The version a promotion from `from` to `to` moves.
+///
+/// Only the next channel up may be promoted to, and only to a version it
+/// does not already carry.
+pub fn promotion(history: &[ChannelEntry], from: Channel, to: Channel) -> Result<&str, String> {
+    if from.next() != Some(to) {
+        return Err(format!("{} cannot be promoted to {}", from, to));
+    }
+    let version = head(history, from).ok_or_else(|| format!("nothing on {}", from))?;
+    if head(history, to) == Some(version) {
+        return Err(format!("{} is already on {}", version, to));
+    }
+    Ok(version)
+}
@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Deterministic replay audits of consensus state machines.
+//!
+//! Asked over OPS (`OpsRequest::VerifyReplay`), a consensus cell reads its
+//! write-ahead log back from disk, applies every entry its live state machine
+//! has applied to a fresh instance, and hashes both states. The observer runs
+//! this periodically: a short log points at WAL corruption or truncation,
+//! differing hashes at a state machine that is not deterministic.
+
+use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct ReplayReport {
+    /// Last log index the live state machine has applied
+    pub applied: u64,
+    pub live_hash: [u8; 32],
+    /// Entries read back from the log and applied to the fresh instance
+    pub replayed: u64,
+    pub replay_hash: [u8; 32],
+}
+
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, Copy, PartialEq,
+)]
+#[archive(check_bytes)]
+pub enum ReplayOutcome {
+    Consistent,
+    /// The log on disk ends before what was applied
+    LogShort,
+    /// Same entries, different state
+    Diverged,
+}
+
+impl ReplayReport {
+    pub fn outcome(&self) -> ReplayOutcome {
+        if self.replayed < self.applied {
+            ReplayOutcome::LogShort
+        } else if self.live_hash != self.replay_hash {
+            ReplayOutcome::Diverged
+        } else {
+            ReplayOutcome::Consistent
+        }
+    }
+}
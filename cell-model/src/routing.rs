@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Weighted traffic splitting for Axon proxies.
+//!
+//! A target mounted through Axon normally tunnels every connection to the
+//! cell of the same name. With weights set (Axon's `set_weights`), each new
+//! connection goes to one of several [`Backend`]s in proportion to its
+//! weight; the swap-coordinator uses this to shift a canary's share from
+//! `cell` to `cell-new` step by step. Connections already open stay where
+//! they are.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct Backend {
+    pub cell: String,
+    /// Relative share of new connections; 0 takes none
+    pub weight: u32,
+}
+
+impl Backend {
+    pub fn new(cell: impl Into<String>, weight: u32) -> Self {
+        Self {
+            cell: cell.into(),
+            weight,
+        }
+    }
+}
+
+/// Sum of the weights, or an error if nothing would receive traffic
+pub fn validate(backends: &[Backend]) -> Result<u64, String> {
+    let total: u64 = backends.iter().map(|b| b.weight as u64).sum();
+    if total == 0 {
+        return Err(String::from("at least one backend needs a non-zero weight"));
+    }
+    Ok(total)
+}
+
+/// The backend for a connection, given `roll` drawn uniformly from
+/// `0..total` (see [`validate`]). Rolls past the total wrap around.
+pub fn pick(backends: &[Backend], roll: u64) -> Option<&str> {
+    let total: u64 = backends.iter().map(|b| b.weight as u64).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut roll = roll % total;
+    for backend in backends {
+        if roll < backend.weight as u64 {
+            return Some(&backend.cell);
+        }
+        roll -= backend.weight as u64;
+    }
+    None
+}
+
+/// `percent` of new connections to `canary`, the rest to `stable`
+pub fn canary(stable: &str, canary: &str, percent: u8) -> Vec<Backend> {
+    let percent = percent.min(100) as u32;
+    vec![
+        Backend::new(stable, 100 - percent),
+        Backend::new(canary, percent),
+    ]
+}
@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Producer-signed log entries.
+//!
+//! Registry and audit logs must not trust their leader: it could append
+//! entries nobody wrote, or append a real one twice. A producer signs each
+//! command with its own Ed25519 key over [`SignedCommand::payload`], which binds
+//! the log, the producer and a per-producer sequence number. Every replica
+//! verifies the signature against the producer's public key before applying the
+//! entry and tracks sequence numbers in [`ProducerWatermarks`], so forged and
+//! replayed entries are skipped identically everywhere.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+
+const DOMAIN: &[u8] = b"cell-signed-entry\0";
+
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct SignedCommand {
+    /// Name the replicas know the producer's public key by
+    pub producer: String,
+    /// Strictly increasing per producer
+    pub seq: u64,
+    pub data: Vec<u8>,
+    /// Ed25519 signature over [`SignedCommand::payload`]
+    pub signature: Vec<u8>,
+}
+
+impl SignedCommand {
+    /// Bytes the producer signs for `log`, the replicated cell's name
+    pub fn payload(log: &str, producer: &str, seq: u64, data: &[u8]) -> Vec<u8> {
+        let mut out =
+            Vec::with_capacity(DOMAIN.len() + log.len() + producer.len() + data.len() + 10);
+        out.extend_from_slice(DOMAIN);
+        for field in [log.as_bytes(), producer.as_bytes()] {
+            out.extend_from_slice(field);
+            out.push(0);
+        }
+        out.extend_from_slice(&seq.to_le_bytes());
+        out.extend_from_slice(data);
+        out
+    }
+
+    pub fn signed_payload(&self, log: &str) -> Vec<u8> {
+        Self::payload(log, &self.producer, self.seq, &self.data)
+    }
+}
+
+/// Highest sequence number applied per producer. Part of the replicated
+/// state: a snapshot has to carry them.
+#[derive(
+    Archive,
+    Serialize,
+    Deserialize,
+    SerdeSerialize,
+    SerdeDeserialize,
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct ProducerWatermarks {
+    applied: BTreeMap<String, u64>,
+}
+
+impl ProducerWatermarks {
+    /// Record `seq` for `producer`; false if it is not newer than the last one
+    pub fn admit(&mut self, producer: &str, seq: u64) -> bool {
+        match self.applied.get_mut(producer) {
+            Some(last) if seq <= *last => false,
+            Some(last) => {
+                *last = seq;
+                true
+            }
+            None => {
+                self.applied.insert(producer.into(), seq);
+                true
+            }
+        }
+    }
+
+    pub fn last(&self, producer: &str) -> Option<u64> {
+        self.applied.get(producer).copied()
+    }
+}
@@ -0,0 +1,219 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Service level objectives and error budgets.
+//!
+//! Cells declare objectives per handler method in `Cell.toml`:
+//!
+//! ```toml
+//! [slo.charge]
+//! availability = 99.9    # percent of calls that succeed
+//! latency_ms = 250
+//! latency_target = 99.0  # percent of calls answered within latency_ms
+//! window_mins = 60
+//! ```
+//!
+//! The cell counts its own calls in a [`SloLedger`]; [`SloReport::status`]
+//! turns the counts into compliance and the share of the error budget left.
+//! Time is passed in explicitly as Unix milliseconds.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+
+const MINUTE_MS: u64 = 60_000;
+
+/// Objectives for one method. Unset objectives are not evaluated.
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct Slo {
+    /// Percentage of calls that must succeed
+    #[serde(default)]
+    pub availability: Option<f64>,
+    /// Latency bound for `latency_target` percent of calls
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+    #[serde(default = "default_latency_target")]
+    pub latency_target: f64,
+    /// Sliding window the objectives are measured over
+    #[serde(default = "default_window_mins")]
+    pub window_mins: u32,
+}
+
+fn default_latency_target() -> f64 {
+    99.0
+}
+
+fn default_window_mins() -> u32 {
+    60
+}
+
+impl Default for Slo {
+    fn default() -> Self {
+        Self {
+            availability: None,
+            latency_ms: None,
+            latency_target: default_latency_target(),
+            window_mins: default_window_mins(),
+        }
+    }
+}
+
+#[derive(
+    Archive,
+    Serialize,
+    Deserialize,
+    SerdeSerialize,
+    SerdeDeserialize,
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct SloCounts {
+    pub total: u64,
+    pub failed: u64,
+    /// Calls slower than `latency_ms`
+    pub slow: u64,
+}
+
+/// One method's objectives and its calls within the window.
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct SloReport {
+    pub method: String,
+    pub objective: Slo,
+    pub counts: SloCounts,
+}
+
+/// Compliance in percent, and the fraction of the error budget left (1.0
+/// untouched, 0.0 or below exhausted). With both objectives set, the budget is
+/// the smaller of the two.
+#[derive(Debug, Clone, PartialEq, SerdeSerialize, SerdeDeserialize)]
+pub struct SloStatus {
+    pub method: String,
+    pub availability: f64,
+    pub latency: f64,
+    pub budget_remaining: f64,
+}
+
+impl SloStatus {
+    pub fn exhausted(&self) -> bool {
+        self.budget_remaining <= 0.0
+    }
+}
+
+impl SloReport {
+    pub fn status(&self) -> SloStatus {
+        let SloCounts {
+            total,
+            failed,
+            slow,
+        } = self.counts;
+        let mut budget_remaining = 1.0f64;
+        if let Some(target) = self.objective.availability {
+            budget_remaining = budget_remaining.min(budget(target, failed, total));
+        }
+        if self.objective.latency_ms.is_some() {
+            budget_remaining =
+                budget_remaining.min(budget(self.objective.latency_target, slow, total));
+        }
+        SloStatus {
+            method: self.method.clone(),
+            availability: percent_good(failed, total),
+            latency: percent_good(slow, total),
+            budget_remaining,
+        }
+    }
+}
+
+fn percent_good(bad: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 100.0;
+    }
+    100.0 * (total - bad.min(total)) as f64 / total as f64
+}
+
+/// Share of the allowed bad calls not yet spent.
+fn budget(target_percent: f64, bad: u64, total: u64) -> f64 {
+    let allowed = (100.0 - target_percent) * total as f64 / 100.0;
+    if allowed <= 0.0 {
+        return if bad == 0 { 1.0 } else { 0.0 };
+    }
+    1.0 - bad as f64 / allowed
+}
+
+/// Per-method call counts in one-minute buckets, for methods with objectives.
+#[derive(Debug, Clone, Default)]
+pub struct SloLedger {
+    objectives: BTreeMap<String, Slo>,
+    buckets: BTreeMap<String, VecDeque<(u64, SloCounts)>>,
+}
+
+impl SloLedger {
+    pub fn new(objectives: BTreeMap<String, Slo>) -> Self {
+        Self {
+            objectives,
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    pub fn is_tracked(&self, method: &str) -> bool {
+        self.objectives.contains_key(method)
+    }
+
+    pub fn record(&mut self, method: &str, duration_ms: u64, ok: bool, now_ms: u64) {
+        let Some(objective) = self.objectives.get(method) else {
+            return;
+        };
+        let minute = now_ms / MINUTE_MS;
+        let buckets = self.buckets.entry(method.into()).or_default();
+        if buckets.back().is_none_or(|(m, _)| *m != minute) {
+            buckets.push_back((minute, SloCounts::default()));
+        }
+        let window = (objective.window_mins as u64).max(1);
+        while buckets.front().is_some_and(|(m, _)| m + window <= minute) {
+            buckets.pop_front();
+        }
+        let (_, counts) = buckets.back_mut().expect("bucket pushed above");
+        counts.total += 1;
+        counts.failed += u64::from(!ok);
+        counts.slow += u64::from(objective.latency_ms.is_some_and(|ms| duration_ms > ms));
+    }
+
+    /// Counts within each method's window, sorted by method.
+    pub fn report(&self, now_ms: u64) -> Vec<SloReport> {
+        let minute = now_ms / MINUTE_MS;
+        self.objectives
+            .iter()
+            .map(|(method, objective)| {
+                let window = (objective.window_mins as u64).max(1);
+                let mut counts = SloCounts::default();
+                for (_, c) in self
+                    .buckets
+                    .get(method)
+                    .into_iter()
+                    .flatten()
+                    .filter(|(m, _)| m + window > minute)
+                {
+                    counts.total += c.total;
+                    counts.failed += c.failed;
+                    counts.slow += c.slow;
+                }
+                SloReport {
+                    method: method.clone(),
+                    objective: objective.clone(),
+                    counts,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Sequencing rolling OS upgrades across nodes.
+//!
+//! The nucleus upgrades one node at a time: drain it, wait for its cells to
+//! come up elsewhere, let the operator (or a hook) upgrade and reboot it, and
+//! uncordon it once it rejoins. Cells declared `quorum = true` in the mesh
+//! manifest run a majority protocol (Raft), so a node is only taken down when
+//! every such group keeps a majority of its replicas without it.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Live replicas of a quorum-bearing cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuorumGroup {
+    pub cell: String,
+    /// Replicas the manifest asks for; the majority is counted against this
+    pub replicas: u32,
+    /// Node of each live replica (a node may host several)
+    pub members: Vec<u64>,
+}
+
+/// Smallest number of replicas out of `replicas` that still forms a majority
+pub fn majority(replicas: u32) -> u32 {
+    replicas / 2 + 1
+}
+
+impl QuorumGroup {
+    /// Replicas left running if `node` goes down
+    pub fn live_without(&self, node: u64) -> u32 {
+        self.members.iter().filter(|m| **m != node).count() as u32
+    }
+}
+
+/// Why `node` cannot go down yet: the first group that would be left without
+/// a majority. A group already below its majority (a replica still migrating)
+/// blocks every node, so the upgrade waits for it to recover.
+pub fn blocked_by(groups: &[QuorumGroup], node: u64) -> Option<String> {
+    groups
+        .iter()
+        .find(|g| g.live_without(node) < majority(g.replicas))
+        .map(|g| {
+            format!(
+                "{} would keep {} of {} replicas without node {} (needs {})",
+                g.cell,
+                g.live_without(node),
+                g.replicas,
+                node,
+                majority(g.replicas)
+            )
+        })
+}
+
+/// The order to upgrade `nodes` in: nodes hosting no quorum members first,
+/// then the rest by how many they host, so groups are disturbed as late and
+/// as little as possible. Ties keep their given order.
+pub fn upgrade_order(nodes: &[u64], groups: &[QuorumGroup]) -> Vec<u64> {
+    let hosted = |node: &u64| {
+        groups
+            .iter()
+            .map(|g| g.members.iter().filter(|m| *m == node).count())
+            .sum::<usize>()
+    };
+    let mut order: Vec<u64> = Vec::new();
+    for node in nodes {
+        if !order.contains(node) {
+            order.push(*node);
+        }
+    }
+    order.sort_by_key(hosted);
+    order
+}
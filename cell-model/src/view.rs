@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! What an untrusted dashboard may ask the mesh.
+//!
+//! Axon's viewer proxy takes [`ViewRequest`]s from outside and answers them
+//! with [`ViewResponse`]s. The request type is the whole interface: it can
+//! only name a cell and one of the read-only OPS queries, or ask for the
+//! topology. There is no variant that calls a handler or carries an OPS
+//! request through, so a viewer cannot reach a mutating API however the
+//! proxy is configured.
+
+use crate::ops::{OpsRequest, OpsResponse};
+use alloc::string::String;
+use alloc::vec::Vec;
+use rkyv::{Archive, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize};
+use serde::{Deserialize, Serialize};
+
+#[derive(Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone)]
+#[archive(check_bytes)]
+pub enum ViewRequest {
+    Status {
+        cell: String,
+    },
+    Metrics {
+        cell: String,
+    },
+    Prometheus {
+        cell: String,
+    },
+    /// Liveness and readiness, as supervisors see them
+    HealthCheck {
+        cell: String,
+    },
+    Slo {
+        cell: String,
+    },
+    /// Cells running on this host and announced on the LAN
+    Topology,
+}
+
+impl ViewRequest {
+    /// The cell asked about and the OPS query that answers, for all but
+    /// [`Topology`](Self::Topology)
+    pub fn ops(&self) -> Option<(&str, OpsRequest)> {
+        match self {
+            Self::Status { cell } => Some((cell, OpsRequest::Status)),
+            Self::Metrics { cell } => Some((cell, OpsRequest::Metrics)),
+            Self::Prometheus { cell } => Some((cell, OpsRequest::Prometheus)),
+            Self::HealthCheck { cell } => Some((cell, OpsRequest::HealthCheck)),
+            Self::Slo { cell } => Some((cell, OpsRequest::Slo)),
+            Self::Topology => None,
+        }
+    }
+}
+
+#[derive(Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone)]
+#[archive(check_bytes)]
+pub enum ViewResponse {
+    /// The cell's answer to the OPS query
+    Ops(OpsResponse),
+    Topology(Vec<TopologyEntry>),
+    Error {
+        message: String,
+    },
+}
+
+/// One cell instance, without anything a viewer could dial locally
+#[derive(Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Debug, Clone)]
+#[archive(check_bytes)]
+pub struct TopologyEntry {
+    pub cell: String,
+    pub instance_id: u64,
+    /// `ip:port` where it announced itself on the LAN
+    pub lan_address: Option<String>,
+    /// Has a socket on this host
+    pub local: bool,
+    pub alive: bool,
+}
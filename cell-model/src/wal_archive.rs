@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Archived write-ahead log segments, for point-in-time recovery.
+//!
+//! A consensus cell cuts its committed log into fixed-length segments
+//! (entries `1..=len`, `len+1..=2*len`, ...). Once the commit index passes
+//! the end of a segment it is sealed: its entries can no longer change, so it
+//! is stored as one blob and listed in the cell's [`ArchiveCatalog`]. Restoring
+//! after disk loss replays the catalog's segments up to the requested index.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+
+/// Entries per segment unless the cell is configured otherwise
+pub const DEFAULT_SEGMENT_ENTRIES: u64 = 1024;
+
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct WalSegment {
+    /// Log index of the first entry
+    pub first: u64,
+    /// Log index of the last entry, inclusive
+    pub last: u64,
+    /// Blobstore hash of the encoded entries
+    pub blob: String,
+}
+
+/// Segments archived so far, ordered by index
+#[derive(SerdeSerialize, SerdeDeserialize, Debug, Clone, Default, PartialEq)]
+pub struct ArchiveCatalog {
+    segments: Vec<WalSegment>,
+}
+
+impl ArchiveCatalog {
+    pub fn new(segments: Vec<WalSegment>) -> Self {
+        let mut catalog = Self::default();
+        for segment in segments {
+            catalog.record(segment);
+        }
+        catalog
+    }
+
+    pub fn segments(&self) -> &[WalSegment] {
+        &self.segments
+    }
+
+    /// Last index archived without a gap from the start of the log
+    pub fn archived_through(&self) -> u64 {
+        let mut through = 0;
+        for segment in &self.segments {
+            if segment.first > through + 1 {
+                break;
+            }
+            through = through.max(segment.last);
+        }
+        through
+    }
+
+    /// Sealed ranges not archived yet, oldest first: every whole segment of
+    /// `len` entries at or below `committed`.
+    pub fn pending(&self, committed: u64, len: u64) -> Vec<(u64, u64)> {
+        let len = len.max(1);
+        let mut ranges = Vec::new();
+        let mut first = self.archived_through() + 1;
+        while first + len - 1 <= committed {
+            ranges.push((first, first + len - 1));
+            first += len;
+        }
+        ranges
+    }
+
+    /// Add a segment. Every replica seals the same ranges, so a segment
+    /// starting where a recorded one does is a duplicate and is dropped.
+    pub fn record(&mut self, segment: WalSegment) -> bool {
+        let at = self.segments.partition_point(|s| s.first < segment.first);
+        if self.segments.get(at).map(|s| s.first) == Some(segment.first) {
+            return false;
+        }
+        self.segments.insert(at, segment);
+        true
+    }
+
+    /// Segments to replay for entries `1..=until`, stopping at the first gap.
+    pub fn covering(&self, until: u64) -> Vec<&WalSegment> {
+        let through = self.archived_through();
+        self.segments
+            .iter()
+            .filter(|s| s.first <= until && s.last <= through)
+            .collect()
+    }
+}
@@ -0,0 +1,184 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Cell self-health and the hypervisor's escalation ladder.
+//!
+//! A cell reports the result of its registered probes over OPS
+//! (`OpsRequest::Health`). The hypervisor feeds each report into the cell's
+//! [`WatchdogCounters`], which decide when to escalate: a degraded cell is
+//! first flagged, then drained, then restarted.
+//!
+//! Supervisors that only need to know whether to route to a cell ask with
+//! `OpsRequest::HealthCheck` instead, and get a [`HealthStatus`].
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, Copy, PartialEq,
+)]
+#[archive(check_bytes)]
+pub enum Health {
+    Healthy,
+    /// At least one probe failed, or the cell did not answer
+    Degraded,
+}
+
+/// Outcome of one self-check, e.g. "wal-writable".
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct ProbeResult {
+    pub name: String,
+    pub healthy: bool,
+    pub detail: String,
+}
+
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct HealthReport {
+    pub health: Health,
+    pub probes: Vec<ProbeResult>,
+}
+
+impl HealthReport {
+    pub fn from_probes(probes: Vec<ProbeResult>) -> Self {
+        let health = if probes.iter().all(|p| p.healthy) {
+            Health::Healthy
+        } else {
+            Health::Degraded
+        };
+        Self { health, probes }
+    }
+
+    pub fn failing(&self) -> impl Iterator<Item = &ProbeResult> {
+        self.probes.iter().filter(|p| !p.healthy)
+    }
+}
+
+/// Liveness and readiness of a cell, with the checks behind them.
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct HealthStatus {
+    /// The cell's runtime answered; restart it if not
+    pub live: bool,
+    /// Live, not draining and every check passes; route to it only if so
+    pub ready: bool,
+    pub draining: bool,
+    pub checks: Vec<ProbeResult>,
+}
+
+impl HealthStatus {
+    /// The answer of a running cell
+    pub fn new(draining: bool, checks: Vec<ProbeResult>) -> Self {
+        Self {
+            live: true,
+            ready: !draining && checks.iter().all(|c| c.healthy),
+            draining,
+            checks,
+        }
+    }
+
+    /// A cell that did not answer, and why
+    pub fn unreachable(detail: String) -> Self {
+        Self {
+            live: false,
+            ready: false,
+            draining: false,
+            checks: alloc::vec![ProbeResult {
+                name: String::from("reachable"),
+                healthy: false,
+                detail,
+            }],
+        }
+    }
+
+    /// The same checks as a report for the escalation ladder
+    pub fn report(&self) -> HealthReport {
+        HealthReport::from_probes(self.checks.clone())
+    }
+}
+
+#[derive(
+    Archive, Serialize, Deserialize, SerdeSerialize, SerdeDeserialize, Debug, Clone, Copy, PartialEq,
+)]
+#[archive(check_bytes)]
+pub enum Escalation {
+    /// Flag the cell as degraded
+    Degrade,
+    /// Stop sending it new requests
+    Drain,
+    /// Kill and respawn it
+    Restart,
+}
+
+/// Consecutive degraded reports before each step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EscalationPolicy {
+    pub drain_after: u32,
+    pub restart_after: u32,
+}
+
+impl Default for EscalationPolicy {
+    fn default() -> Self {
+        Self {
+            drain_after: 3,
+            restart_after: 6,
+        }
+    }
+}
+
+/// Per-cell watchdog state, kept by the hypervisor.
+#[derive(
+    Archive,
+    Serialize,
+    Deserialize,
+    SerdeSerialize,
+    SerdeDeserialize,
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+)]
+#[archive(check_bytes)]
+pub struct WatchdogCounters {
+    pub consecutive_degraded: u32,
+    /// Times the cell went from healthy to degraded
+    pub degraded_total: u64,
+    pub drains: u64,
+    pub restarts: u64,
+}
+
+impl WatchdogCounters {
+    /// Record one report and return the step to take now, if any. Each step
+    /// is taken once per degraded streak; a restart starts a new streak.
+    pub fn observe(&mut self, health: Health, policy: &EscalationPolicy) -> Option<Escalation> {
+        if health == Health::Healthy {
+            self.consecutive_degraded = 0;
+            return None;
+        }
+
+        self.consecutive_degraded += 1;
+        let streak = self.consecutive_degraded;
+        if streak >= policy.restart_after {
+            self.restarts += 1;
+            self.consecutive_degraded = 0;
+            Some(Escalation::Restart)
+        } else if streak == policy.drain_after {
+            self.drains += 1;
+            Some(Escalation::Drain)
+        } else if streak == 1 {
+            self.degraded_total += 1;
+            Some(Escalation::Degrade)
+        } else {
+            None
+        }
+    }
+}
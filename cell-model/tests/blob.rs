@@ -0,0 +1,32 @@
+use cell_model::blob::{BlobIndex, BlobManifest};
+
+fn manifest(hash: &str, chunks: &[&str]) -> BlobManifest {
+    BlobManifest {
+        hash: hash.into(),
+        size: chunks.len() as u64,
+        chunks: chunks.iter().map(|c| c.to_string()).collect(),
+    }
+}
+
+#[test]
+fn storing_twice_takes_two_references() {
+    let mut index = BlobIndex::new();
+    assert_eq!(index.insert(manifest("a", &["c1"])), 1);
+    assert_eq!(index.insert(manifest("a", &["c1"])), 2);
+    assert_eq!(index.release("a"), Some(1));
+    assert_eq!(index.retain("missing"), None);
+}
+
+#[test]
+fn gc_keeps_chunks_shared_with_live_blobs() {
+    let mut index = BlobIndex::new();
+    index.insert(manifest("old", &["c1", "c2"]));
+    index.insert(manifest("new", &["c2", "c3"]));
+    index.release("old");
+
+    let plan = index.gc(["c1", "c2", "c3", "orphan"]);
+    assert_eq!(plan.blobs, ["old"]);
+    assert_eq!(plan.chunks, ["c1", "orphan"]);
+    assert!(index.get("old").is_none());
+    assert_eq!(index.refs("new"), 1);
+}
@@ -0,0 +1,70 @@
+use cell_model::cytokine::{Batch, CytokineRequest, CytokineResponse, TopicLog};
+
+fn log_with(retain: usize, count: u8) -> TopicLog {
+    let mut log = TopicLog::new(retain);
+    for i in 0..count {
+        log.push(vec![i]);
+    }
+    log
+}
+
+#[test]
+fn new_subscribers_start_at_the_next_emission() {
+    let log = log_with(8, 3);
+    let batch = log.read(None, 16);
+    assert_eq!(batch.next, 3);
+    assert!(batch.payloads.is_empty());
+}
+
+#[test]
+fn reads_resume_from_the_cursor() {
+    let log = log_with(8, 5);
+    let first = log.read(Some(1), 2);
+    assert_eq!(first.payloads, vec![vec![1], vec![2]]);
+    assert_eq!(first.next, 3);
+
+    let rest = log.read(Some(first.next), 16);
+    assert_eq!(rest.payloads, vec![vec![3], vec![4]]);
+    assert_eq!(rest.next, 5);
+    assert_eq!(rest.missed, 0);
+}
+
+#[test]
+fn slow_readers_are_told_what_they_missed() {
+    // Only 2..5 are kept
+    let log = log_with(3, 5);
+    let batch = log.read(Some(0), 16);
+    assert_eq!(batch.missed, 2);
+    assert_eq!(batch.payloads, vec![vec![2], vec![3], vec![4]]);
+    assert_eq!(batch.next, 5);
+}
+
+#[test]
+fn a_cursor_past_the_end_starts_over() {
+    // The broker restarted after the subscriber had read up to 40
+    let log = log_with(8, 2);
+    let batch = log.read(Some(40), 16);
+    assert_eq!(batch.payloads, vec![vec![0], vec![1]]);
+    assert_eq!(batch.missed, 0);
+}
+
+#[test]
+fn protocol_round_trips() {
+    let req = CytokineRequest::Poll {
+        topic: "ledger.deposits".into(),
+        from: Some(7),
+        wait_ms: 1000,
+    };
+    let bytes = rkyv::to_bytes::<_, 256>(&req).unwrap();
+    let back: CytokineRequest = rkyv::from_bytes(&bytes).unwrap();
+    assert_eq!(back, req);
+
+    let resp = CytokineResponse::Batch(Batch {
+        next: 9,
+        missed: 0,
+        payloads: vec![vec![1, 2], vec![3]],
+    });
+    let bytes = rkyv::to_bytes::<_, 256>(&resp).unwrap();
+    let back: CytokineResponse = rkyv::from_bytes(&bytes).unwrap();
+    assert_eq!(back, resp);
+}
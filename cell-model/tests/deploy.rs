@@ -0,0 +1,56 @@
+use cell_model::deploy::{rollback_target, Deployment};
+
+fn deployed(version: &str, artifact: Option<&str>, at: u64) -> Deployment {
+    Deployment {
+        cell: "ledger".into(),
+        version: version.into(),
+        kind: "swap".into(),
+        artifact: artifact.map(Into::into),
+        schema: None,
+        deployed_at: at,
+    }
+}
+
+#[test]
+fn rolls_back_to_the_previous_version_by_default() {
+    let history = vec![
+        deployed("c3c3", Some("blob-c"), 30),
+        deployed("c3c3", None, 25),
+        deployed("b2b2", None, 20),
+        deployed("b2b2", Some("blob-b"), 15),
+        deployed("a1a1", Some("blob-a"), 10),
+    ];
+    let target = rollback_target(&history, None).unwrap();
+    assert_eq!(target.version, "b2b2");
+    // The newest record that can be fetched instead of rebuilt
+    assert_eq!(target.artifact.as_deref(), Some("blob-b"));
+
+    assert_eq!(
+        rollback_target(&history, Some("a1")).unwrap().deployed_at,
+        10
+    );
+}
+
+#[test]
+fn refuses_targets_that_are_live_unknown_or_ambiguous() {
+    let history = vec![
+        deployed("abc1", None, 30),
+        deployed("abd2", None, 20),
+        deployed("abd3", None, 10),
+        deployed("abd2", None, 5),
+    ];
+    assert!(rollback_target(&history, Some("abc"))
+        .unwrap_err()
+        .contains("live"));
+    assert!(rollback_target(&history, Some("ff")).is_err());
+    assert!(rollback_target(&history, Some("abd"))
+        .unwrap_err()
+        .contains("several"));
+    assert_eq!(
+        rollback_target(&history, Some("abd2")).unwrap().deployed_at,
+        20
+    );
+
+    assert!(rollback_target(&[], None).is_err());
+    assert!(rollback_target(&history[..1], None).is_err());
+}
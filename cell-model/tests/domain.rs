@@ -0,0 +1,43 @@
+use cell_model::domain::{Domain, DomainGrant};
+
+fn grant(residency: Option<&str>, expires_at: Option<u64>) -> DomainGrant {
+    DomainGrant {
+        principal: "analytics".into(),
+        organism: "acme".into(),
+        residency: residency.map(Into::into),
+        expires_at,
+    }
+}
+
+#[test]
+fn labels_round_trip() {
+    let eu = Domain::new("acme", Some("eu".into()));
+    assert_eq!(eu.label(), "acme@eu");
+    assert_eq!(Domain::parse("acme@eu"), eu);
+    assert_eq!(Domain::parse("acme"), Domain::new("acme", None));
+    assert_eq!(Domain::parse("acme@"), Domain::new("acme", None));
+}
+
+#[test]
+fn resident_data_stays_in_its_region() {
+    let eu = Domain::new("acme", Some("eu".into()));
+    assert!(eu.storable_in(Some("eu")));
+    assert!(!eu.storable_in(Some("us")));
+    assert!(!eu.storable_in(None));
+    assert!(Domain::new("acme", None).storable_in(Some("us")));
+}
+
+#[test]
+fn grants_cover_their_principal_organism_and_residency() {
+    let eu = Domain::new("acme", Some("eu".into()));
+    let us = Domain::new("acme", Some("us".into()));
+
+    assert!(grant(None, None).covers("analytics", &eu, 0));
+    assert!(grant(Some("eu"), None).covers("analytics", &eu, 0));
+    assert!(!grant(Some("eu"), None).covers("analytics", &us, 0));
+    assert!(!grant(None, None).covers("billing", &eu, 0));
+    assert!(!grant(None, None).covers("analytics", &Domain::new("globex", None), 0));
+
+    assert!(grant(None, Some(100)).covers("analytics", &eu, 99));
+    assert!(!grant(None, Some(100)).covers("analytics", &eu, 100));
+}
@@ -0,0 +1,136 @@
+use cell_model::manifest::{CellDeployment, Drift, DriftKind, MeshManifest, ObservedCell};
+
+fn deployment(name: &str, version: Option<&str>, env: &[(&str, &str)]) -> CellDeployment {
+    CellDeployment {
+        name: name.into(),
+        version: version.map(Into::into),
+        replicas: 1,
+        min_available: None,
+        env: env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        resources: Default::default(),
+        placement: Default::default(),
+        requires: Vec::new(),
+        quorum: false,
+    }
+}
+
+fn observed(name: &str, version: Option<&str>, env: &[(&str, &str)]) -> ObservedCell {
+    ObservedCell {
+        name: name.into(),
+        version: version.map(Into::into),
+        replicas: 1,
+        env: env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+    }
+}
+
+fn manifest(cells: Vec<CellDeployment>) -> MeshManifest {
+    MeshManifest {
+        mesh: "test".into(),
+        cells,
+    }
+}
+
+#[test]
+fn matching_mesh_has_no_drift() {
+    let desired = manifest(vec![
+        deployment("ledger", Some("1.2.0"), &[("RUST_LOG", "info")]),
+        deployment("auth", None, &[]),
+    ]);
+    let actual = [
+        observed("auth", Some("0.9.0"), &[]),
+        observed("ledger", Some("1.2.0"), &[("RUST_LOG", "info")]),
+    ];
+    assert!(desired.drift(&actual).is_empty());
+}
+
+#[test]
+fn version_and_env_drift() {
+    let desired = manifest(vec![deployment(
+        "ledger",
+        Some("1.3.0"),
+        &[("RUST_LOG", "debug")],
+    )]);
+    let actual = [observed(
+        "ledger",
+        Some("1.2.0"),
+        &[("RUST_LOG", "info"), ("STALE", "1")],
+    )];
+    assert_eq!(
+        desired.drift(&actual),
+        [
+            Drift {
+                cell: "ledger".into(),
+                kind: DriftKind::Version {
+                    desired: "1.3.0".into(),
+                    actual: Some("1.2.0".into()),
+                },
+            },
+            Drift {
+                cell: "ledger".into(),
+                kind: DriftKind::Env {
+                    key: "RUST_LOG".into(),
+                    desired: Some("debug".into()),
+                    actual: Some("info".into()),
+                },
+            },
+            Drift {
+                cell: "ledger".into(),
+                kind: DriftKind::Env {
+                    key: "STALE".into(),
+                    desired: None,
+                    actual: Some("1".into()),
+                },
+            },
+        ]
+    );
+
+    // A cell registered without a version drifts from a pinned one
+    let unversioned = [observed("ledger", None, &[("RUST_LOG", "debug")])];
+    assert_eq!(
+        desired.drift(&unversioned),
+        [Drift {
+            cell: "ledger".into(),
+            kind: DriftKind::Version {
+                desired: "1.3.0".into(),
+                actual: None,
+            },
+        }]
+    );
+}
+
+#[test]
+fn missing_and_unexpected_cells() {
+    let desired = manifest(vec![
+        deployment("auth", None, &[]),
+        deployment("ledger", None, &[]),
+    ]);
+    let mut scaled = observed("ledger", None, &[]);
+    scaled.replicas = 3;
+    let actual = [scaled, observed("legacy", None, &[])];
+    assert_eq!(
+        desired.drift(&actual),
+        [
+            Drift {
+                cell: "auth".into(),
+                kind: DriftKind::Missing,
+            },
+            Drift {
+                cell: "ledger".into(),
+                kind: DriftKind::Replicas {
+                    desired: 1,
+                    actual: 3,
+                },
+            },
+            Drift {
+                cell: "legacy".into(),
+                kind: DriftKind::Unexpected,
+            },
+        ]
+    );
+}
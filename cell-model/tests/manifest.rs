@@ -0,0 +1,78 @@
+use cell_model::manifest::{
+    quic_addr, tcp_addr, ConsensusConfig, NeighborConfig, StorageConfig, StorageEngine,
+};
+
+#[test]
+fn test_tcp_endpoints() {
+    assert_eq!(tcp_addr("tcp://0.0.0.0:9100"), Some("0.0.0.0:9100"));
+    assert_eq!(
+        tcp_addr(" tcp://ledger.internal:9100\n"),
+        Some("ledger.internal:9100")
+    );
+    assert_eq!(tcp_addr("tcp://ledger.internal"), None);
+    assert_eq!(tcp_addr("unix"), None);
+    assert_eq!(tcp_addr("../ledger"), None);
+    assert_eq!(tcp_addr("quic://10.0.0.2:9100"), None);
+    assert_eq!(quic_addr("quic://10.0.0.2:9100"), Some("10.0.0.2:9100"));
+}
+
+#[test]
+fn test_remote_neighbors() {
+    let remote = NeighborConfig::Detailed {
+        path: "tcp://10.0.0.2:9100".into(),
+        autostart: false,
+        channel: None,
+    };
+    assert_eq!(remote.tcp_addr(), Some("10.0.0.2:9100"));
+    assert_eq!(NeighborConfig::Path("../ledger".into()).tcp_addr(), None);
+}
+
+#[test]
+fn test_storage_engines() {
+    assert_eq!(StorageConfig::default().engine, StorageEngine::Sqlite);
+    for engine in [
+        StorageEngine::Sqlite,
+        StorageEngine::Redb,
+        StorageEngine::Memory,
+    ] {
+        assert_eq!(StorageEngine::parse(engine.name()), Some(engine));
+    }
+    assert_eq!(StorageEngine::parse(" redb\n"), Some(StorageEngine::Redb));
+    assert_eq!(StorageEngine::parse("sled"), None);
+    assert_eq!(StorageEngine::Redb.default_file(), Some("state.redb"));
+    assert_eq!(StorageEngine::Memory.default_file(), None);
+}
+
+#[test]
+fn test_consensus_membership() {
+    let peers = vec![
+        "10.0.0.1:10001".to_string(),
+        "10.0.0.2:10002".to_string(),
+        "10.0.0.3:10003".to_string(),
+    ];
+    // Listing its own address does not count a node twice
+    let listed = ConsensusConfig {
+        id: 1,
+        peers: peers.clone(),
+        wal: None,
+    };
+    assert_eq!(listed.cluster_size(), 3);
+    assert_eq!(listed.quorum(), 2);
+    assert_eq!(listed.wal_file(), "raft-1.wal");
+
+    let unlisted = ConsensusConfig {
+        id: 4,
+        peers,
+        wal: Some("/var/lib/raft.wal".into()),
+    };
+    assert_eq!(unlisted.cluster_size(), 4);
+    assert_eq!(unlisted.quorum(), 3);
+    assert_eq!(unlisted.wal_file(), "/var/lib/raft.wal");
+
+    let alone = ConsensusConfig {
+        id: 1,
+        peers: Vec::new(),
+        wal: None,
+    };
+    assert_eq!((alone.cluster_size(), alone.quorum()), (1, 1));
+}
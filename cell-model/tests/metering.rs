@@ -0,0 +1,63 @@
+use cell_model::metering::{to_csv, Meter};
+
+#[test]
+fn periods_aggregate_per_organism_and_principal() {
+    let mut meter = Meter::new(1_000);
+
+    meter.record_request("acme", "web", true);
+    meter.record_request("acme", "web", true);
+    meter.record_request("acme", "web", false);
+    meter.record_request("globex", "web", true);
+
+    // 100 bytes for 50s, then 300 bytes for 50s
+    meter.record_storage("acme", "db", 100, 1_000);
+    meter.record_storage("acme", "db", 300, 1_050);
+
+    let records = meter.close_period(1_100);
+    assert_eq!(records.len(), 3);
+
+    let db = records.iter().find(|r| r.principal == "db").unwrap();
+    assert_eq!(db.storage_byte_seconds, 100 * 50 + 300 * 50);
+    assert_eq!(db.peak_storage_bytes, 300);
+    assert_eq!((db.period_start, db.period_end), (1_000, 1_100));
+
+    let web = records
+        .iter()
+        .find(|r| r.organism == "acme" && r.principal == "web")
+        .unwrap();
+    assert_eq!((web.requests, web.rejected), (2, 1));
+
+    // Requests reset, held storage carries over
+    let next = meter.close_period(1_200);
+    assert_eq!(next.len(), 1);
+    assert_eq!(next[0].principal, "db");
+    assert_eq!(next[0].storage_byte_seconds, 300 * 100);
+    assert_eq!(next[0].requests, 0);
+}
+
+#[test]
+fn csv_has_header_and_escapes_fields() {
+    let mut meter = Meter::new(0);
+    meter.record_request("acme", "svc,\"x\"", true);
+    let csv = to_csv(&meter.close_period(60));
+
+    let mut lines = csv.lines();
+    assert!(lines.next().unwrap().starts_with("organism,principal,"));
+    assert_eq!(lines.next().unwrap(), "acme,\"svc,\"\"x\"\"\",0,60,1,0,0,0");
+    assert!(lines.next().is_none());
+}
+
+#[test]
+fn counted_requests_add_up_with_recorded_ones() {
+    let mut meter = Meter::new(0);
+    meter.record_request("acme", "web", true);
+    // As spooled by two Membranes
+    meter.add_requests("acme", "web", 5, 2);
+    meter.add_requests("globex", "web", 1, 0);
+
+    let records = meter.close_period(60);
+    let acme = records.iter().find(|r| r.organism == "acme").unwrap();
+    assert_eq!((acme.requests, acme.rejected), (6, 2));
+    let globex = records.iter().find(|r| r.organism == "globex").unwrap();
+    assert_eq!((globex.requests, globex.rejected), (1, 0));
+}
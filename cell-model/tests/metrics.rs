@@ -0,0 +1,53 @@
+use cell_model::metrics::{latency_bucket, prometheus, MethodMetrics, LATENCY_BUCKETS_US};
+use cell_model::ops::MetricsSnapshot;
+
+fn snapshot(methods: Vec<MethodMetrics>) -> MetricsSnapshot {
+    MetricsSnapshot {
+        requests_total: methods.iter().map(|m| m.calls).sum(),
+        requests_success: methods.iter().map(|m| m.calls - m.errors).sum(),
+        requests_failed: methods.iter().map(|m| m.errors).sum(),
+        latency_histogram: vec![],
+        connections_active: 2,
+        bytes_sent: 10,
+        bytes_received: 20,
+        methods,
+    }
+}
+
+#[test]
+fn bucket_bounds_are_inclusive() {
+    assert_eq!(latency_bucket(0), 0);
+    assert_eq!(latency_bucket(1_000), 0);
+    assert_eq!(latency_bucket(1_001), 1);
+    assert_eq!(latency_bucket(10_000_001), LATENCY_BUCKETS_US.len());
+}
+
+#[test]
+fn histogram_buckets_are_cumulative() {
+    let mut charge = MethodMetrics::new("charge");
+    charge.record(500, true);
+    charge.record(3_000, false);
+    charge.record(20_000_000, true);
+
+    let text = prometheus("ledger", &snapshot(vec![charge]));
+    let lines: Vec<&str> = text.lines().collect();
+    for expected in [
+        r#"cell_requests_total{cell="ledger",method="charge"} 3"#,
+        r#"cell_request_errors_total{cell="ledger",method="charge"} 1"#,
+        r#"cell_request_duration_seconds_bucket{cell="ledger",method="charge",le="0.001"} 1"#,
+        r#"cell_request_duration_seconds_bucket{cell="ledger",method="charge",le="0.005"} 2"#,
+        r#"cell_request_duration_seconds_bucket{cell="ledger",method="charge",le="10"} 2"#,
+        r#"cell_request_duration_seconds_bucket{cell="ledger",method="charge",le="+Inf"} 3"#,
+        r#"cell_request_duration_seconds_sum{cell="ledger",method="charge"} 20.0035"#,
+        r#"cell_request_duration_seconds_count{cell="ledger",method="charge"} 3"#,
+        r#"cell_connections_active{cell="ledger"} 2"#,
+    ] {
+        assert!(lines.contains(&expected), "missing {}", expected);
+    }
+}
+
+#[test]
+fn label_values_are_escaped() {
+    let text = prometheus("a\"b", &snapshot(vec![MethodMetrics::new("x\\y")]));
+    assert!(text.contains(r#"cell_requests_total{cell="a\"b",method="x\\y"} 0"#));
+}
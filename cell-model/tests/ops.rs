@@ -0,0 +1,56 @@
+use cell_model::ops::{OpsRequest, ProfileFormat, ProfileKind};
+
+#[test]
+fn client_requests_are_not_privileged() {
+    for req in [
+        OpsRequest::Ping,
+        OpsRequest::Status,
+        OpsRequest::Metrics,
+        OpsRequest::HealthCheck,
+        OpsRequest::GetSource,
+        OpsRequest::Handshake { fingerprint: 7 },
+        OpsRequest::Extensions,
+        OpsRequest::MiddlewareModule,
+    ] {
+        assert!(!req.is_privileged(), "{:?}", req);
+    }
+}
+
+#[test]
+fn control_requests_are_privileged() {
+    for req in [
+        OpsRequest::Shutdown,
+        OpsRequest::Drain,
+        OpsRequest::Restore { bytes: vec![1] },
+        OpsRequest::Adopt {
+            from: "worker".to_string(),
+        },
+        OpsRequest::SetLogLevel {
+            target: None,
+            level: "debug".to_string(),
+        },
+        OpsRequest::LoadPlugin {
+            path: "/opt/plugins/libresize.so".to_string(),
+        },
+    ] {
+        assert!(req.is_privileged(), "{:?}", req);
+    }
+}
+
+#[test]
+fn introspection_requests_are_privileged() {
+    for req in [
+        OpsRequest::Inspect,
+        OpsRequest::SlowRequests { limit: 10 },
+        OpsRequest::Spans {
+            trace_id: "4bf92f3577b34da6".to_string(),
+        },
+        OpsRequest::Profile {
+            kind: ProfileKind::Heap,
+            seconds: 5,
+            format: ProfileFormat::Pprof,
+        },
+    ] {
+        assert!(req.is_privileged(), "{:?}", req);
+    }
+}
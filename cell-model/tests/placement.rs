@@ -0,0 +1,180 @@
+use cell_model::placement::{
+    drain_plan, gpu_env, migration_plan, place, place_with_policy, EdgePolicy, GpuDevice,
+    NodeProfile, PowerState, Requirement, RunningCell,
+};
+
+fn gpu(vendor: &str, memory_mb: u64, device: &str) -> GpuDevice {
+    GpuDevice {
+        vendor: vendor.into(),
+        name: format!("{} test gpu", vendor),
+        memory_mb,
+        device: device.into(),
+    }
+}
+
+fn node(id: u64, gpus: Vec<GpuDevice>) -> NodeProfile {
+    NodeProfile {
+        node_id: id,
+        address: format!("10.0.0.{}:9000", id),
+        gpus,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn parses_requirements() {
+    let reqs = Requirement::parse_all(&["gpu:NVIDIA:8192", "tee,avx512"]).unwrap();
+    assert_eq!(
+        reqs,
+        vec![
+            Requirement::Gpu {
+                vendor: Some("nvidia".into()),
+                min_memory_mb: Some(8192)
+            },
+            Requirement::Tee,
+            Requirement::Avx512,
+        ]
+    );
+    assert!(Requirement::parse("fpga").is_err());
+    assert!(Requirement::parse("gpu:nvidia:lots").is_err());
+}
+
+#[test]
+fn gpu_cells_only_land_on_capable_nodes() {
+    let nodes = vec![
+        node(1, vec![]),
+        node(2, vec![gpu("amd", 16384, "/dev/dri/renderD128")]),
+        node(3, vec![gpu("nvidia", 4096, "/dev/nvidia0"), gpu("nvidia", 24576, "/dev/nvidia1")]),
+    ];
+
+    let any_gpu = Requirement::parse_all(&["gpu"]).unwrap();
+    assert_eq!(place(&nodes, &any_gpu).unwrap().node_id, 2);
+
+    let big_nvidia = Requirement::parse_all(&["gpu:nvidia:8192"]).unwrap();
+    let chosen = place(&nodes, &big_nvidia).unwrap();
+    assert_eq!(chosen.node_id, 3);
+    let gpus = chosen.matching_gpus(&big_nvidia);
+    assert_eq!(gpus.len(), 1);
+
+    let env = gpu_env(&gpus);
+    assert!(env.contains(&("CUDA_VISIBLE_DEVICES".into(), "1".into())));
+    assert!(env.contains(&("CELL_GPU_DEVICES".into(), "/dev/nvidia1".into())));
+
+    assert!(place(&nodes, &Requirement::parse_all(&["gpu:intel"]).unwrap()).is_none());
+    // No requirements: any node
+    assert_eq!(place(&nodes, &[]).unwrap().node_id, 1);
+}
+
+fn edge(id: u64, on_battery: bool, battery_pct: Option<u8>, temp_c: f32) -> NodeProfile {
+    NodeProfile {
+        power: PowerState {
+            on_battery,
+            battery_pct,
+            temp_c: Some(temp_c),
+            throttled: false,
+        },
+        ..node(id, vec![])
+    }
+}
+
+#[test]
+fn heavy_cells_avoid_battery_and_hot_nodes() {
+    let policy = EdgePolicy::default();
+    let nodes = vec![
+        edge(1, true, Some(80), 50.0),
+        NodeProfile {
+            power: PowerState {
+                throttled: true,
+                ..Default::default()
+            },
+            ..node(2, vec![])
+        },
+        edge(3, false, Some(100), 60.0),
+    ];
+
+    assert_eq!(place_with_policy(&nodes, &[], true, &policy).unwrap().node_id, 3);
+    // Light cells may go anywhere
+    assert_eq!(place_with_policy(&nodes, &[], false, &policy).unwrap().node_id, 1);
+
+    let only_battery = vec![edge(1, true, Some(80), 50.0)];
+    assert!(place_with_policy(&only_battery, &[], true, &policy).is_none());
+
+    // Over the limit before the firmware throttles
+    let hot = edge(4, false, Some(100), 95.0);
+    assert!(policy.avoid(&hot).unwrap().contains("95°C"));
+    assert!(place_with_policy(&[hot], &[], true, &policy).is_none());
+}
+
+#[test]
+fn migrates_heavy_cells_once_thresholds_are_crossed() {
+    let policy = EdgePolicy::default();
+    let running = |node_id, heavy| RunningCell {
+        name: format!("cell-on-{}", node_id),
+        node_id,
+        heavy,
+        requirements: vec![],
+    };
+
+    // Unplugged but well charged: stay put
+    let nodes = vec![edge(1, true, Some(80), 50.0), edge(2, false, None, 50.0)];
+    assert!(migration_plan(&nodes, &[running(1, true)], &policy).is_empty());
+
+    // Battery low: heavy cells move, light ones stay
+    let nodes = vec![edge(1, true, Some(20), 50.0), edge(2, false, None, 50.0)];
+    let plan = migration_plan(&nodes, &[running(1, true), running(1, false)], &policy);
+    assert_eq!(plan.len(), 1);
+    assert_eq!((plan[0].from, plan[0].to), (1, 2));
+    assert!(plan[0].reason.contains("20%"));
+
+    // Overheating, but no better node: nothing to do
+    let nodes = vec![edge(1, false, None, 95.0), edge(2, true, Some(90), 40.0)];
+    assert!(migration_plan(&nodes, &[running(1, true)], &policy).is_empty());
+}
+
+#[test]
+fn draining_moves_cells_off_the_node_or_stops_them() {
+    let policy = EdgePolicy::default();
+    let nodes = vec![
+        node(1, vec![gpu("nvidia", 8192, "/dev/nvidia0")]),
+        node(2, vec![]),
+        node(3, vec![gpu("nvidia", 8192, "/dev/nvidia0")]),
+        edge(4, true, Some(90), 40.0),
+    ];
+    let cell = |name: &str, node_id, heavy, requires: &[&str]| RunningCell {
+        name: name.into(),
+        node_id,
+        heavy,
+        requirements: Requirement::parse_all(requires).unwrap(),
+    };
+    let running = vec![
+        cell("web", 1, false, &[]),
+        cell("trainer", 1, true, &["gpu"]),
+        cell("indexer", 1, true, &[]),
+        cell("elsewhere", 2, false, &[]),
+    ];
+
+    let plan = drain_plan(&nodes, &running, 1, &[1], &policy);
+    let moves: Vec<(&str, u64)> = plan
+        .migrations
+        .iter()
+        .map(|m| (m.cell.as_str(), m.to))
+        .collect();
+    assert_eq!(moves, vec![("web", 2), ("trainer", 3), ("indexer", 2)]);
+    assert!(plan.migrations.iter().all(|m| m.from == 1));
+    assert!(plan.stopped.is_empty());
+
+    // With node 3 cordoned too, the GPU cell has nowhere to go
+    let plan = drain_plan(&nodes, &running, 1, &[1, 3], &policy);
+    assert_eq!(plan.stopped, vec!["trainer".to_string()]);
+    assert_eq!(plan.migrations.len(), 2);
+
+    // Only a node on battery left: light cells move, heavy ones stop
+    let plan = drain_plan(&nodes, &running, 1, &[2, 3], &policy);
+    let moves: Vec<(&str, u64)> = plan
+        .migrations
+        .iter()
+        .map(|m| (m.cell.as_str(), m.to))
+        .collect();
+    assert_eq!(moves, vec![("web", 4)]);
+    assert_eq!(plan.stopped, vec!["trainer".to_string(), "indexer".to_string()]);
+}
@@ -0,0 +1,84 @@
+use cell_model::pressure::{
+    admits, shed, Pressure, PressureLevel, PressureThresholds, PriorityClass,
+};
+
+fn sample(mem_available_pct: f32, load_per_cpu: f32) -> Pressure {
+    Pressure {
+        mem_available_pct,
+        load_per_cpu,
+    }
+}
+
+#[test]
+fn pressure_is_read_from_proc() {
+    let meminfo =
+        "MemTotal:       16000000 kB\nMemFree:         1000000 kB\nMemAvailable:    2000000 kB\n";
+    let p = Pressure::from_proc(meminfo, "6.00 4.00 2.00 3/900 1234\n", 4).unwrap();
+    assert_eq!(p.mem_available_pct, 12.5);
+    assert_eq!(p.load_per_cpu, 1.5);
+    assert!(Pressure::from_proc("MemFree: 10 kB\n", "1.0", 1).is_none());
+}
+
+#[test]
+fn levels_step_down_only_once_clear_of_the_threshold() {
+    let t = PressureThresholds::default();
+    let normal = PressureLevel::Normal;
+    assert_eq!(
+        t.classify(&sample(50.0, 0.5), normal),
+        PressureLevel::Normal
+    );
+    assert_eq!(
+        t.classify(&sample(8.0, 0.5), normal),
+        PressureLevel::Constrained
+    );
+    assert_eq!(
+        t.classify(&sample(50.0, 3.0), normal),
+        PressureLevel::Constrained
+    );
+    assert_eq!(
+        t.classify(&sample(3.0, 0.5), normal),
+        PressureLevel::Critical
+    );
+
+    // 12% is above the constrained threshold but inside the recovery margin
+    let critical = PressureLevel::Critical;
+    assert_eq!(
+        t.classify(&sample(12.0, 0.5), critical),
+        PressureLevel::Constrained
+    );
+    assert_eq!(
+        t.classify(&sample(12.0, 0.5), PressureLevel::Constrained),
+        PressureLevel::Constrained
+    );
+    assert_eq!(
+        t.classify(&sample(20.0, 0.5), critical),
+        PressureLevel::Normal
+    );
+}
+
+#[test]
+fn best_effort_is_shed_first_and_critical_never() {
+    let running = vec![
+        ("axon".to_string(), PriorityClass::Critical),
+        ("api".to_string(), PriorityClass::Standard),
+        ("indexer".to_string(), PriorityClass::BestEffort),
+        ("cache".to_string(), PriorityClass::BestEffort),
+    ];
+    assert!(shed(&running, PressureLevel::Normal).is_empty());
+    assert_eq!(
+        shed(&running, PressureLevel::Constrained),
+        vec!["cache", "indexer"]
+    );
+    assert_eq!(
+        shed(&running, PressureLevel::Critical),
+        vec!["cache", "indexer", "api"]
+    );
+
+    assert!(admits(PriorityClass::Standard, PressureLevel::Constrained));
+    assert!(!admits(
+        PriorityClass::BestEffort,
+        PressureLevel::Constrained
+    ));
+    assert!(admits(PriorityClass::Critical, PressureLevel::Critical));
+    assert_eq!(PriorityClass::default(), PriorityClass::Standard);
+}
@@ -0,0 +1,44 @@
+use cell_model::provenance::{Envelope, Provenance, Statement, PREDICATE_TYPE};
+
+fn provenance() -> Provenance {
+    Provenance {
+        builder: "node-a".into(),
+        source_repo: Some("https://github.com/example/ledger".into()),
+        commit: Some("4f2c9e1".into()),
+        source_hash: "9a0b".into(),
+        toolchain: "rustc 1.80.0 (051478957 2024-07-21)".into(),
+        build_flags: vec!["build".into(), "--release".into()],
+        built_at: 1_700_000_000,
+    }
+}
+
+#[test]
+fn test_statement_covers_its_subject() {
+    let statement = Statement::new("ledger", "ABCD01", provenance());
+    assert_eq!(statement.subject[0].name, "ledger");
+    assert!(statement.covers("abcd01").is_ok());
+    assert!(statement.covers("abcd02").is_err());
+
+    let foreign = Statement {
+        predicate_type: "https://slsa.dev/provenance/v1".into(),
+        ..statement.clone()
+    };
+    assert!(foreign.covers("abcd01").is_err());
+    assert_eq!(statement.predicate_type, PREDICATE_TYPE);
+}
+
+#[test]
+fn test_pre_authentication_encoding() {
+    assert_eq!(
+        Envelope::pae("application/vnd.in-toto+json", b"{}"),
+        b"DSSEv1 28 application/vnd.in-toto+json 2 {}".to_vec()
+    );
+    let envelope = Envelope {
+        payload_type: "text/plain".into(),
+        payload: "hello".into(),
+        signatures: vec![],
+    };
+    assert_eq!(envelope.signed_bytes(), b"DSSEv1 10 text/plain 5 hello");
+    // The type is bound too: the same payload signed as another type differs
+    assert_ne!(Envelope::pae("a", b"bc"), Envelope::pae("ab", b"c"));
+}
@@ -0,0 +1,121 @@
+use cell_model::quota::{QuotaKind, QuotaLedger, QuotaLimits};
+
+fn limits() -> QuotaLimits {
+    QuotaLimits {
+        requests_per_min: Some(2),
+        storage_bytes: Some(100),
+        queue_depth: Some(1),
+    }
+}
+
+#[test]
+fn request_rate_is_a_sliding_minute() {
+    let mut ledger = QuotaLedger::new(limits());
+
+    assert!(ledger.admit_request("svc", 0).is_ok());
+    assert!(ledger.admit_request("svc", 10_000).is_ok());
+    let breach = ledger.admit_request("svc", 20_000).unwrap_err();
+    assert_eq!(breach.kind, QuotaKind::Requests);
+    assert_eq!(breach.limit, 2);
+
+    // Other principals have their own budget
+    assert!(ledger.admit_request("other", 20_000).is_ok());
+
+    // The first request has left the window
+    assert!(ledger.admit_request("svc", 60_000).is_ok());
+}
+
+#[test]
+fn storage_growth_is_capped_but_shrinking_is_not() {
+    let mut ledger = QuotaLedger::new(limits());
+
+    assert!(ledger.reserve_storage("svc", 80).is_ok());
+    let breach = ledger.reserve_storage("svc", 40).unwrap_err();
+    assert_eq!(breach.kind, QuotaKind::Storage);
+    assert_eq!(breach.current, 120);
+    assert_eq!(ledger.usage("svc", 0).storage_bytes, 80);
+
+    assert!(ledger.reserve_storage("svc", -200).is_ok());
+    assert_eq!(ledger.usage("svc", 0).storage_bytes, 0);
+}
+
+#[test]
+fn per_principal_limits_and_alerts() {
+    let mut ledger = QuotaLedger::new(QuotaLimits::default());
+    ledger.set_limits("batch", limits());
+
+    // Unlimited by default
+    for t in 0..10 {
+        assert!(ledger.admit_request("web", t).is_ok());
+    }
+    assert!(ledger.enter_queue("web").is_ok());
+    assert!(ledger.enter_queue("web").is_ok());
+
+    assert!(ledger.enter_queue("batch").is_ok());
+    assert!(ledger.enter_queue("batch").is_err());
+    ledger.leave_queue("batch");
+    assert!(ledger.enter_queue("batch").is_ok());
+
+    assert!(ledger.reserve_storage("batch", 90).is_ok());
+    let alerts = ledger.alerts(0, 80);
+    assert_eq!(alerts.len(), 2);
+    assert!(alerts.iter().all(|a| a.principal == "batch"));
+    assert!(alerts.iter().any(|a| a.kind == QuotaKind::Storage));
+    assert!(alerts.iter().any(|a| a.kind == QuotaKind::Queue));
+}
+
+#[test]
+fn resetting_limits_keeps_usage() {
+    let mut ledger = QuotaLedger::new(QuotaLimits::default());
+    ledger.set_limits("batch", limits());
+    assert!(ledger.admit_request("batch", 0).is_ok());
+    assert!(ledger.admit_request("batch", 0).is_ok());
+    assert!(ledger.admit_request("batch", 0).is_err());
+
+    ledger.reset_limits(QuotaLimits::default());
+    assert_eq!(ledger.limits("batch"), &QuotaLimits::default());
+    assert!(ledger.admit_request("batch", 0).is_ok());
+    // Unlimited requests are not recorded
+    assert_eq!(ledger.usage("batch", 0).requests_last_min, 2);
+}
+
+#[test]
+fn rejected_admissions_are_not_counted() {
+    let mut ledger = QuotaLedger::new(limits());
+
+    assert!(ledger.admit("svc", 0).is_ok());
+    // The queue is full, so the rate is not charged either
+    let breach = ledger.admit("svc", 1_000).unwrap_err();
+    assert_eq!(breach.kind, QuotaKind::Queue);
+    assert_eq!(ledger.usage("svc", 1_000).requests_last_min, 1);
+
+    ledger.leave_queue("svc");
+    assert!(ledger.admit("svc", 2_000).is_ok());
+    ledger.leave_queue("svc");
+    let breach = ledger.admit("svc", 3_000).unwrap_err();
+    assert_eq!(breach.kind, QuotaKind::Requests);
+    assert_eq!(ledger.usage("svc", 3_000).queue_depth, 0);
+}
+
+#[test]
+fn idle_principals_are_forgotten() {
+    let mut ledger = QuotaLedger::new(QuotaLimits::default());
+    ledger.set_limits("batch", limits());
+
+    for peer in ["peer-1", "peer-2", "peer-3"] {
+        assert!(ledger.admit(peer, 0).is_ok());
+        ledger.leave_queue(peer);
+    }
+    assert!(ledger.admit("batch", 0).is_ok());
+    ledger.leave_queue("batch");
+    assert!(ledger.reserve_storage("web", 10).is_ok());
+    assert_eq!(
+        ledger.principals().collect::<Vec<_>>(),
+        vec!["batch", "web"]
+    );
+
+    // Once its requests leave the window and its bytes are freed, nothing is kept
+    assert_eq!(ledger.usage("batch", 60_000).requests_last_min, 0);
+    assert!(ledger.reserve_storage("web", -10).is_ok());
+    assert_eq!(ledger.principals().count(), 0);
+}
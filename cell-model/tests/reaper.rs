@@ -0,0 +1,65 @@
+use cell_model::reaper::{is_proxy_socket, is_wal_temp, plan, ArtifactKind, Finding, ReapPolicy};
+
+fn finding(path: &str, kind: ArtifactKind, age_secs: u64, in_use: bool) -> Finding {
+    Finding {
+        path: path.into(),
+        kind,
+        bytes: 10,
+        age_secs,
+        in_use,
+    }
+}
+
+#[test]
+fn only_unused_artifacts_past_their_grace_are_reaped() {
+    let policy = ReapPolicy::default();
+    let findings = vec![
+        finding("/dev/shm/cell_old", ArtifactKind::ShmSegment, 600, false),
+        finding("/dev/shm/cell_fresh", ArtifactKind::ShmSegment, 10, false),
+        finding("/dev/shm/cell_open", ArtifactKind::ShmSegment, 600, true),
+        finding(
+            "/tmp/cell/axon_proxy_a.sock",
+            ArtifactKind::ProxySocket,
+            60,
+            false,
+        ),
+        finding("s/ledger.wal.tmp", ArtifactKind::WalTemp, 3599, false),
+        finding(
+            "target/test-sockets/x.sock",
+            ArtifactKind::TestSocket,
+            7200,
+            false,
+        ),
+        finding(
+            "target/test-sockets/live.sock",
+            ArtifactKind::TestSocket,
+            7200,
+            true,
+        ),
+    ];
+
+    let reaped: Vec<_> = plan(&findings, &policy)
+        .into_iter()
+        .map(|f| f.path)
+        .collect();
+    assert_eq!(
+        reaped,
+        vec![
+            "/dev/shm/cell_old",
+            "/tmp/cell/axon_proxy_a.sock",
+            "target/test-sockets/x.sock"
+        ]
+    );
+}
+
+#[test]
+fn recognises_temp_files_and_proxy_sockets() {
+    assert!(is_wal_temp("ledger.wal.tmp"));
+    assert!(is_wal_temp("ledger.snapshot.tmp"));
+    assert!(is_wal_temp("ledger.snapshot.part"));
+    assert!(!is_wal_temp("ledger.wal"));
+    assert!(!is_wal_temp("ledger.snapshot"));
+
+    assert!(is_proxy_socket("axon_proxy_ledger.sock"));
+    assert!(!is_proxy_socket("ledger.sock"));
+}
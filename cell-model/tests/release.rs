@@ -0,0 +1,77 @@
+use cell_model::manifest::NeighborConfig;
+use cell_model::release::{head, promotion, CellRef, Channel, ChannelEntry, Pin};
+
+fn entry(channel: Channel, version: &str) -> ChannelEntry {
+    ChannelEntry {
+        channel,
+        version: version.into(),
+        action: "push".into(),
+        actor: "alice".into(),
+        at: 0,
+    }
+}
+
+#[test]
+fn test_cell_refs() {
+    assert_eq!(CellRef::parse("ledger"), CellRef::latest("ledger"));
+    assert_eq!(
+        CellRef::parse("ledger@beta"),
+        CellRef::on("ledger", Channel::Beta)
+    );
+    assert_eq!(
+        CellRef::parse("ledger@1.4.0").pin,
+        Pin::Version("1.4.0".into())
+    );
+    assert!(!CellRef::parse("ledger@").is_pinned());
+    assert_eq!(CellRef::parse("ledger@stable").to_string(), "ledger@stable");
+    assert_eq!(CellRef::at("ledger", "1.4.0").to_string(), "ledger@1.4.0");
+}
+
+#[test]
+fn test_channel_heads() {
+    let history = vec![
+        entry(Channel::Nightly, "1.0.0"),
+        entry(Channel::Beta, "1.0.0"),
+        entry(Channel::Nightly, "1.1.0"),
+    ];
+    assert_eq!(head(&history, Channel::Nightly), Some("1.1.0"));
+    assert_eq!(head(&history, Channel::Beta), Some("1.0.0"));
+    assert_eq!(head(&history, Channel::Stable), None);
+}
+
+#[test]
+fn test_promotions_move_one_channel_up() {
+    let history = vec![
+        entry(Channel::Nightly, "1.0.0"),
+        entry(Channel::Beta, "1.0.0"),
+        entry(Channel::Nightly, "1.1.0"),
+    ];
+    assert_eq!(
+        promotion(&history, Channel::Nightly, Channel::Beta),
+        Ok("1.1.0")
+    );
+    assert_eq!(
+        promotion(&history, Channel::Beta, Channel::Stable),
+        Ok("1.0.0")
+    );
+    assert!(promotion(&history, Channel::Nightly, Channel::Stable).is_err());
+    assert!(promotion(&history, Channel::Stable, Channel::Beta).is_err());
+
+    let promoted = vec![
+        entry(Channel::Beta, "1.0.0"),
+        entry(Channel::Stable, "1.0.0"),
+    ];
+    assert!(promotion(&promoted, Channel::Beta, Channel::Stable).is_err());
+    assert!(promotion(&[], Channel::Beta, Channel::Stable).is_err());
+}
+
+#[test]
+fn test_neighbor_channels() {
+    let pinned = NeighborConfig::Detailed {
+        path: "../ledger".into(),
+        autostart: true,
+        channel: Some(Channel::Beta),
+    };
+    assert_eq!(pinned.channel(), Some(Channel::Beta));
+    assert_eq!(NeighborConfig::Path("../cache".into()).channel(), None);
+}
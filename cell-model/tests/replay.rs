@@ -0,0 +1,26 @@
+use cell_model::replay::{ReplayOutcome, ReplayReport};
+
+fn report(applied: u64, replayed: u64, live: u8, replay: u8) -> ReplayReport {
+    ReplayReport {
+        applied,
+        live_hash: [live; 32],
+        replayed,
+        replay_hash: [replay; 32],
+    }
+}
+
+#[test]
+fn matching_hashes_over_the_whole_log_are_consistent() {
+    assert_eq!(report(10, 10, 7, 7).outcome(), ReplayOutcome::Consistent);
+}
+
+#[test]
+fn differing_hashes_mean_divergence() {
+    assert_eq!(report(10, 10, 7, 8).outcome(), ReplayOutcome::Diverged);
+}
+
+#[test]
+fn a_log_shorter_than_what_was_applied_is_reported_first() {
+    // The hashes differ too, but the missing entries explain it
+    assert_eq!(report(10, 6, 7, 8).outcome(), ReplayOutcome::LogShort);
+}
@@ -0,0 +1,25 @@
+use cell_model::routing::{canary, pick, validate, Backend};
+
+#[test]
+fn connections_split_by_weight() {
+    let backends = canary("ledger", "ledger-new", 10);
+    assert_eq!(validate(&backends), Ok(100));
+
+    let to_canary = (0..1000)
+        .filter(|roll| pick(&backends, *roll) == Some("ledger-new"))
+        .count();
+    assert_eq!(to_canary, 100);
+    assert_eq!(pick(&backends, 0), Some("ledger"));
+    assert_eq!(pick(&backends, 95), Some("ledger-new"));
+}
+
+#[test]
+fn zero_weights_take_no_traffic() {
+    let backends = canary("ledger", "ledger-new", 100);
+    assert!((0..100).all(|roll| pick(&backends, roll) == Some("ledger-new")));
+
+    let none = vec![Backend::new("ledger", 0)];
+    assert!(validate(&none).is_err());
+    assert_eq!(pick(&none, 3), None);
+    assert_eq!(canary("a", "b", 250)[1].weight, 100);
+}
@@ -0,0 +1,49 @@
+use cell_model::signed_entry::{ProducerWatermarks, SignedCommand};
+
+#[test]
+fn payload_binds_log_producer_and_sequence() {
+    let base = SignedCommand::payload("audit", "billing", 1, b"event");
+
+    assert_ne!(
+        base,
+        SignedCommand::payload("registry", "billing", 1, b"event")
+    );
+    assert_ne!(
+        base,
+        SignedCommand::payload("audit", "billing2", 1, b"event")
+    );
+    assert_ne!(
+        base,
+        SignedCommand::payload("audit", "billing", 2, b"event")
+    );
+    // Field boundaries are unambiguous
+    assert_ne!(
+        SignedCommand::payload("ab", "c", 1, b""),
+        SignedCommand::payload("a", "bc", 1, b"")
+    );
+}
+
+#[test]
+fn watermarks_reject_replays_per_producer() {
+    let mut marks = ProducerWatermarks::default();
+
+    assert!(marks.admit("billing", 1));
+    assert!(marks.admit("billing", 5));
+    assert!(!marks.admit("billing", 5));
+    assert!(!marks.admit("billing", 3));
+    assert!(marks.admit("search", 1));
+    assert_eq!(marks.last("billing"), Some(5));
+    assert_eq!(marks.last("nobody"), None);
+}
+
+#[test]
+fn watermarks_survive_archiving() {
+    let mut marks = ProducerWatermarks::default();
+    marks.admit("billing", 4);
+    marks.admit("audit", 9);
+
+    let bytes = rkyv::to_bytes::<_, 256>(&marks).unwrap();
+    let restored: ProducerWatermarks = rkyv::from_bytes(&bytes).unwrap();
+    assert_eq!(restored, marks);
+    assert_eq!(restored.last("billing"), Some(4));
+}
@@ -0,0 +1,60 @@
+use cell_model::slo::{Slo, SloCounts, SloLedger, SloReport};
+use std::collections::BTreeMap;
+
+const MINUTE: u64 = 60_000;
+
+fn objective() -> Slo {
+    Slo {
+        availability: Some(99.0),
+        latency_ms: Some(100),
+        window_mins: 10,
+        ..Slo::default()
+    }
+}
+
+fn report(total: u64, failed: u64, slow: u64) -> SloReport {
+    SloReport {
+        method: "charge".into(),
+        objective: objective(),
+        counts: SloCounts {
+            total,
+            failed,
+            slow,
+        },
+    }
+}
+
+#[test]
+fn budget_is_the_share_of_allowed_failures_left() {
+    // 1% of 1000 calls may fail; 5 did
+    let status = report(1000, 5, 0).status();
+    assert!((status.budget_remaining - 0.5).abs() < 1e-9);
+    assert!((status.availability - 99.5).abs() < 1e-9);
+    assert!(!status.exhausted());
+
+    assert!(report(1000, 10, 0).status().exhausted());
+    assert_eq!(report(0, 0, 0).status().budget_remaining, 1.0);
+}
+
+#[test]
+fn the_tighter_objective_sets_the_budget() {
+    let status = report(1000, 0, 20).status();
+    assert!((status.latency - 98.0).abs() < 1e-9);
+    assert!(status.exhausted());
+}
+
+#[test]
+fn ledger_counts_within_the_window_only() {
+    let mut ledger = SloLedger::new(BTreeMap::from([("charge".to_string(), objective())]));
+    ledger.record("charge", 500, false, 0);
+    ledger.record("charge", 10, true, 5 * MINUTE);
+    ledger.record("refund", 10, false, 5 * MINUTE);
+    assert!(!ledger.is_tracked("refund"));
+
+    let counts = ledger.report(5 * MINUTE)[0].counts;
+    assert_eq!((counts.total, counts.failed, counts.slow), (2, 1, 1));
+
+    // The first call has left the ten-minute window
+    let counts = ledger.report(12 * MINUTE)[0].counts;
+    assert_eq!((counts.total, counts.failed, counts.slow), (1, 0, 0));
+}
@@ -0,0 +1,51 @@
+use cell_model::upgrade::{blocked_by, majority, upgrade_order, QuorumGroup};
+
+fn group(cell: &str, replicas: u32, members: &[u64]) -> QuorumGroup {
+    QuorumGroup {
+        cell: cell.into(),
+        replicas,
+        members: members.to_vec(),
+    }
+}
+
+#[test]
+fn majorities() {
+    assert_eq!(majority(1), 1);
+    assert_eq!(majority(3), 2);
+    assert_eq!(majority(4), 3);
+    assert_eq!(majority(5), 3);
+}
+
+#[test]
+fn nodes_go_down_only_if_every_group_keeps_a_majority() {
+    let groups = vec![group("ledger", 3, &[1, 2, 3]), group("config", 1, &[4])];
+    assert_eq!(blocked_by(&groups, 1), None);
+    assert_eq!(blocked_by(&groups, 5), None);
+    // A single replica is its own majority
+    assert!(blocked_by(&groups, 4).unwrap().starts_with("config"));
+
+    // Two replicas on one node: losing it leaves one of three
+    let packed = vec![group("ledger", 3, &[1, 1, 2])];
+    let reason = blocked_by(&packed, 1).unwrap();
+    assert!(reason.contains("keep 1 of 3"), "{}", reason);
+
+    // Still missing the replica moved off the last node: wait
+    let recovering = vec![group("ledger", 3, &[2, 3])];
+    assert!(blocked_by(&recovering, 2).is_some());
+    assert!(blocked_by(&recovering, 7).is_none());
+    let lost = vec![group("ledger", 3, &[2])];
+    assert!(blocked_by(&lost, 7).is_some());
+}
+
+#[test]
+fn nodes_without_quorum_members_are_upgraded_first() {
+    let groups = vec![
+        group("ledger", 3, &[1, 2, 3]),
+        group("config", 3, &[1, 3, 5]),
+    ];
+    assert_eq!(
+        upgrade_order(&[1, 2, 3, 4, 5, 4], &groups),
+        vec![4, 2, 5, 1, 3]
+    );
+    assert!(upgrade_order(&[], &groups).is_empty());
+}
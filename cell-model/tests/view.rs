@@ -0,0 +1,18 @@
+use cell_model::view::ViewRequest;
+
+#[test]
+fn views_only_map_to_unprivileged_ops() {
+    let cell = || "ledger".to_string();
+    for req in [
+        ViewRequest::Status { cell: cell() },
+        ViewRequest::Metrics { cell: cell() },
+        ViewRequest::Prometheus { cell: cell() },
+        ViewRequest::HealthCheck { cell: cell() },
+        ViewRequest::Slo { cell: cell() },
+    ] {
+        let (target, ops) = req.ops().unwrap();
+        assert_eq!(target, "ledger");
+        assert!(!ops.is_privileged(), "{:?}", ops);
+    }
+    assert!(ViewRequest::Topology.ops().is_none());
+}
@@ -0,0 +1,45 @@
+use cell_model::wal_archive::{ArchiveCatalog, WalSegment};
+
+fn segment(first: u64, last: u64) -> WalSegment {
+    WalSegment {
+        first,
+        last,
+        blob: format!("{}-{}", first, last),
+    }
+}
+
+#[test]
+fn only_whole_committed_segments_are_pending() {
+    let catalog = ArchiveCatalog::default();
+    assert_eq!(catalog.pending(9, 4), [(1, 4), (5, 8)]);
+    assert!(catalog.pending(3, 4).is_empty());
+}
+
+#[test]
+fn pending_resumes_after_the_archived_prefix() {
+    let mut catalog = ArchiveCatalog::default();
+    assert!(catalog.record(segment(1, 4)));
+    assert_eq!(catalog.archived_through(), 4);
+    assert_eq!(catalog.pending(12, 4), [(5, 8), (9, 12)]);
+}
+
+#[test]
+fn duplicate_segments_from_other_replicas_are_dropped() {
+    let mut catalog = ArchiveCatalog::new(vec![segment(5, 8), segment(1, 4)]);
+    assert!(!catalog.record(segment(1, 4)));
+    assert_eq!(catalog.segments(), [segment(1, 4), segment(5, 8)]);
+}
+
+#[test]
+fn restore_stops_at_the_first_gap() {
+    let catalog = ArchiveCatalog::new(vec![segment(1, 4), segment(9, 12)]);
+    assert_eq!(catalog.archived_through(), 4);
+    assert_eq!(catalog.covering(10), [&segment(1, 4)]);
+}
+
+#[test]
+fn restore_takes_segments_that_start_at_or_before_the_index() {
+    let catalog = ArchiveCatalog::new(vec![segment(1, 4), segment(5, 8), segment(9, 12)]);
+    assert_eq!(catalog.covering(5), [&segment(1, 4), &segment(5, 8)]);
+    assert!(catalog.covering(0).is_empty());
+}
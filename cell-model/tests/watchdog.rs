@@ -0,0 +1,74 @@
+use cell_model::watchdog::{
+    Escalation, EscalationPolicy, Health, HealthReport, HealthStatus, ProbeResult, WatchdogCounters,
+};
+
+fn probe(name: &str, healthy: bool) -> ProbeResult {
+    ProbeResult {
+        name: name.into(),
+        healthy,
+        detail: String::new(),
+    }
+}
+
+#[test]
+fn one_failing_probe_degrades_the_cell() {
+    let report = HealthReport::from_probes(vec![probe("wal", true), probe("db", false)]);
+    assert_eq!(report.health, Health::Degraded);
+    assert_eq!(
+        report
+            .failing()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>(),
+        ["db"]
+    );
+
+    assert_eq!(HealthReport::from_probes(vec![]).health, Health::Healthy);
+}
+
+#[test]
+fn escalates_degrade_then_drain_then_restart() {
+    let policy = EscalationPolicy {
+        drain_after: 2,
+        restart_after: 4,
+    };
+    let mut counters = WatchdogCounters::default();
+
+    let steps: Vec<_> = (0..4)
+        .map(|_| counters.observe(Health::Degraded, &policy))
+        .collect();
+    assert_eq!(
+        steps,
+        [
+            Some(Escalation::Degrade),
+            Some(Escalation::Drain),
+            None,
+            Some(Escalation::Restart)
+        ]
+    );
+    assert_eq!(counters.restarts, 1);
+    assert_eq!(counters.consecutive_degraded, 0);
+
+    // Recovering resets the streak
+    counters.observe(Health::Degraded, &policy);
+    assert_eq!(counters.observe(Health::Healthy, &policy), None);
+    assert_eq!(
+        counters.observe(Health::Degraded, &policy),
+        Some(Escalation::Degrade)
+    );
+    assert_eq!(counters.degraded_total, 3);
+    assert_eq!(counters.drains, 1);
+}
+
+#[test]
+fn failing_checks_and_draining_make_a_live_cell_unready() {
+    let status = HealthStatus::new(false, vec![probe("wal", true), probe("db", false)]);
+    assert!(status.live && !status.ready);
+    assert_eq!(status.report().health, Health::Degraded);
+
+    let draining = HealthStatus::new(true, vec![probe("wal", true)]);
+    assert!(draining.live && !draining.ready);
+
+    let gone = HealthStatus::unreachable("connection refused".into());
+    assert!(!gone.live && !gone.ready);
+    assert_eq!(gone.checks[0].detail, "connection refused");
+}
@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: MIT
+// cell-node/src/lib.rs
+//! Node.js addon calling Cell synapses.
+//!
+//! `connect(cell)` resolves to a `Synapse` wrapping a [`ResilientSynapse`], so
+//! Node services reach cells over their Unix socket (or SHM, TCP, QUIC)
+//! instead of the HTTP gateway. Arguments and results cross as plain objects
+//! in the gateway's JSON shape: `fire(method, args)` takes one property per
+//! argument and resolves to the method's return value.
+//!
+//! ```text
+//! CELL_NODE_CELLS=ledger,audit npm run build
+//! cell schema codegen running:ledger --target typescript-node -o ledger.ts
+//! ```
+//!
+//! ```ts
+//! import { Ledger } from "./ledger";
+//!
+//! const ledger = await Ledger.connect();
+//! const outcome = await ledger.deposit({ req: { account: "acc-1", amount: 100 } });
+//! const sub = ledger.subscribe("balance", { account: "acc-1" }, (err, balance) => { ... });
+//! sub.close();
+//! ```
+//!
+//! Cells cannot push to callers yet, so `subscribe` polls the method and
+//! reports each result that differs from the last one. Failures are rejected
+//! with an `Error` whose message is `<cell>: <message> (code <n>)`, `n` being
+//! the cell's `ErrorResponse` code.
+
+#[macro_use]
+extern crate napi_derive;
+
+use cell_sdk::error::{CellError, ErrorContext};
+use cell_sdk::json_bridge::{call, Caller};
+use cell_sdk::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{JsFunction, Status};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+
+// `cell_remote!` and `targets()` for every cell in `CELL_NODE_CELLS`
+include!(concat!(env!("OUT_DIR"), "/cells.rs"));
+
+/// Default `subscribe` interval
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// `<cell>: <message> (code <n>)`
+fn to_js(err: anyhow::Error) -> napi::Error {
+    let ctx = ErrorContext::classify(&err, CellError::TransportUnavailable);
+    let resp = ctx.to_response(ctx.cell.as_deref().unwrap_or("cell-node"));
+    napi::Error::new(
+        Status::GenericFailure,
+        format!("{}: {} (code {})", resp.cell, resp.message, resp.code),
+    )
+}
+
+/// Connect to `cell`, which must be bridged into this build.
+#[napi]
+pub async fn connect(cell: String) -> napi::Result<Synapse> {
+    let call = *targets().get(cell.as_str()).ok_or_else(|| {
+        napi::Error::new(
+            Status::InvalidArg,
+            format!(
+                "'{}' is not bridged into cell-node; rebuild with it in CELL_NODE_CELLS",
+                cell
+            ),
+        )
+    })?;
+    let conn = ResilientSynapse::grow(&cell).await.map_err(to_js)?;
+    Ok(Synapse { cell, conn, call })
+}
+
+/// Cells this build can connect to
+#[napi]
+pub fn bridged() -> Vec<String> {
+    let mut cells: Vec<String> = targets().into_keys().map(str::to_string).collect();
+    cells.sort_unstable();
+    cells
+}
+
+/// Connection to one cell
+#[napi]
+pub struct Synapse {
+    cell: String,
+    conn: ResilientSynapse,
+    call: Caller,
+}
+
+#[napi]
+impl Synapse {
+    #[napi(getter)]
+    pub fn cell(&self) -> String {
+        self.cell.clone()
+    }
+
+    /// Call `method` with an object of its arguments; resolves to its result.
+    #[napi(
+        ts_args_type = "method: string, args: object",
+        ts_return_type = "Promise<any>"
+    )]
+    pub async fn fire(&self, method: String, args: Value) -> napi::Result<Value> {
+        (self.call)(self.conn.clone(), method, args)
+            .await
+            .map_err(to_js)
+    }
+
+    /// Call `method` every `intervalMs` (default 1000) and pass each result
+    /// that differs from the previous one to `onValue`. Failures are passed
+    /// as `err` and polling goes on until the subscription is closed.
+    #[napi(
+        ts_args_type = "method: string, args: object, onValue: (err: Error | null, value: any) => void, intervalMs?: number"
+    )]
+    pub fn subscribe(
+        &self,
+        method: String,
+        args: Value,
+        on_value: JsFunction,
+        interval_ms: Option<u32>,
+    ) -> napi::Result<Subscription> {
+        let notify: ThreadsafeFunction<Value, ErrorStrategy::CalleeHandled> =
+            on_value.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
+        let interval = interval_ms
+            .map(|ms| Duration::from_millis(ms.max(10) as u64))
+            .unwrap_or(POLL_INTERVAL);
+        let (conn, call) = (self.conn.clone(), self.call);
+
+        // On the addon's runtime: a plain `tokio::spawn` has none on the JS thread
+        let task = napi::bindgen_prelude::spawn(async move {
+            let mut ticks = napi::tokio::time::interval(interval);
+            let mut last: Option<Value> = None;
+            loop {
+                ticks.tick().await;
+                match call(conn.clone(), method.clone(), args.clone()).await {
+                    Ok(value) if last.as_ref() != Some(&value) => {
+                        last = Some(value.clone());
+                        notify.call(Ok(value), ThreadsafeFunctionCallMode::NonBlocking);
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        notify.call(Err(to_js(e)), ThreadsafeFunctionCallMode::NonBlocking);
+                    }
+                }
+            }
+        });
+        Ok(Subscription { task })
+    }
+}
+
+/// Running `subscribe`; polling stops on `close()`
+#[napi]
+pub struct Subscription {
+    task: napi::tokio::task::JoinHandle<()>,
+}
+
+#[napi]
+impl Subscription {
+    #[napi]
+    pub fn close(&self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
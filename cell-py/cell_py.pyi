@@ -0,0 +1,20 @@
+# Type stubs for the native `cell_py` module (cell-py/src/lib.rs).
+from typing import Any, Dict, List
+
+class CellError(Exception):
+    """Failure reported by a cell: `(code, message, cell, retry_after_ms)`."""
+
+    args: tuple[int, str, str, int]
+
+class Synapse:
+    """Connection to one cell over its native protocol."""
+
+    @staticmethod
+    async def connect(cell: str) -> Synapse: ...
+    async def call(self, method: str, args: Dict[str, Any]) -> Any: ...
+    @property
+    def cell(self) -> str: ...
+
+def bridged() -> List[str]:
+    """Cells this build can connect to (`CELL_PY_CELLS`)."""
+    ...
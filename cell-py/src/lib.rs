@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: MIT
+// cell-py/src/lib.rs
+//! Async Python bindings to Cell synapses.
+//!
+//! `cell_py.Synapse` wraps a [`ResilientSynapse`], so Python callers speak the
+//! native protocol (rkyv over the cell's socket, SHM or mesh transport) rather
+//! than going through the HTTP gateway. Arguments and results cross as Python
+//! values in the gateway's JSON shape: a call takes one dict entry per
+//! argument and returns the method's return value.
+//!
+//! ```text
+//! CELL_PY_CELLS=ledger,audit maturin develop --release
+//! cell schema codegen running:ledger --target python -o ledger.py
+//! ```
+//!
+//! ```python
+//! from ledger import Ledger
+//!
+//! ledger = await Ledger.connect()
+//! outcome = await ledger.deposit({"account": "acc-1", "amount": 100, "memo": None})
+//! ```
+//!
+//! Failures raise `cell_py.CellError` with the cell's `ErrorResponse` fields
+//! as `code`, `message`, `cell` and `retry_after_ms`.
+
+use cell_sdk::error::{CellError, ErrorContext};
+use cell_sdk::json_bridge::{call, Caller};
+use cell_sdk::*;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use serde_json::Value;
+use std::collections::HashMap;
+
+// `cell_remote!` and `targets()` for every cell in `CELL_PY_CELLS`
+include!(concat!(env!("OUT_DIR"), "/cells.rs"));
+
+pyo3::create_exception!(cell_py, PyCellError, PyException);
+
+/// `CellError(code, message, cell, retry_after_ms)`
+fn to_py(err: anyhow::Error) -> PyErr {
+    let ctx = ErrorContext::classify(&err, CellError::TransportUnavailable);
+    let resp = ctx.to_response(ctx.cell.as_deref().unwrap_or(""));
+    PyCellError::new_err((resp.code, resp.message, resp.cell, resp.retry_after_ms))
+}
+
+/// Connection to one cell
+#[pyclass(name = "Synapse", module = "cell_py", frozen)]
+struct Synapse {
+    cell: String,
+    conn: ResilientSynapse,
+    call: Caller,
+}
+
+#[pymethods]
+impl Synapse {
+    /// Connect to `cell`, which must be bridged into this build.
+    #[staticmethod]
+    fn connect(py: Python<'_>, cell: String) -> PyResult<Bound<'_, PyAny>> {
+        let call = *targets().get(cell.as_str()).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "'{}' is not bridged into cell_py; rebuild with it in CELL_PY_CELLS",
+                cell
+            ))
+        })?;
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let conn = ResilientSynapse::grow(&cell).await.map_err(to_py)?;
+            Ok(Synapse { cell, conn, call })
+        })
+    }
+
+    /// Call `method` with a dict of its arguments; resolves to its result.
+    fn call<'py>(
+        &self,
+        py: Python<'py>,
+        method: String,
+        args: Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let args: Value = pythonize::depythonize(&args)?;
+        let (conn, call) = (self.conn.clone(), self.call);
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let result = call(conn, method, args).await.map_err(to_py)?;
+            Python::with_gil(|py| Ok(pythonize::pythonize(py, &result)?.unbind()))
+        })
+    }
+
+    #[getter]
+    fn cell(&self) -> &str {
+        &self.cell
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<cell_py.Synapse {}>", self.cell)
+    }
+}
+
+/// Cells this build can connect to
+#[pyfunction]
+fn bridged() -> Vec<&'static str> {
+    let mut cells: Vec<_> = targets().into_keys().collect();
+    cells.sort_unstable();
+    cells
+}
+
+#[pymodule]
+fn cell_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Synapse>()?;
+    m.add("CellError", m.py().get_type_bound::<PyCellError>())?;
+    m.add_function(wrap_pyfunction!(bridged, m)?)?;
+    Ok(())
+}
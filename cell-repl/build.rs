@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: MIT
+// cell-repl/build.rs
+//! Links the cells named in `CELL_REPL_CELLS` (comma separated) into the crate.
+//!
+//! The wire format of a call is fixed at compile time, so each bridged cell
+//! gets a `cell_remote!` and an entry in `targets()`, as the webhook cell does.
+
+use std::env;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=CELL_REPL_CELLS");
+    let cells = env::var("CELL_REPL_CELLS").unwrap_or_default();
+
+    let mut code = String::new();
+    let mut targets = Vec::new();
+    for cell in cells.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+        let module = pascal(cell);
+        let _ = writeln!(code, "cell_remote!({} = {:?});", module, cell);
+        targets.push(format!(
+            "({:?}, call::<{m}::{m}Protocol, {m}::{m}Response> as Caller)",
+            cell,
+            m = module
+        ));
+    }
+    let _ = writeln!(
+        code,
+        "\n/// Cells this build can call\nfn targets() -> HashMap<&'static str, Caller> {{\n    HashMap::from([{}])\n}}",
+        targets.join(", ")
+    );
+
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap()).join("cells.rs");
+    std::fs::write(out, code).expect("write cells.rs");
+}
+
+fn pascal(name: &str) -> String {
+    name.split(['-', '_'])
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: MIT
+// cell-repl/src/lib.rs
+//! Blocking calls into a running mesh, for evcxr and Jupyter notebooks.
+//!
+//! Every function blocks on a runtime owned by the crate, so there is no
+//! `async` or `.await` at the prompt. Cells are connected on first use and the
+//! [`ResilientSynapse`] is kept for later calls. Arguments and results are
+//! JSON in the HTTP gateway's shape: one property per argument, and the
+//! method's return value back.
+//!
+//! ```text
+//! CELL_REPL_CELLS=ledger,audit evcxr_jupyter
+//! ```
+//!
+//! ```ignore
+//! :dep cell-repl = { path = "cell-repl" }
+//! :dep serde_json = "1"
+//! use cell_repl::*;
+//! use serde_json::json;
+//!
+//! call("ledger", "deposit", json!({ "req": { "account": "acc-1", "amount": 100 } }))?
+//! ```
+//!
+//! Results are [`Pretty`]: indented JSON at the prompt, and a JSON tree in
+//! Jupyter. `CELL_REPL_CELLS` is read when the crate is compiled, so set it
+//! before starting the kernel.
+
+use anyhow::Result;
+use cell_sdk::json_bridge::Caller;
+use cell_sdk::*;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::ops::Deref;
+use std::sync::{Mutex, OnceLock};
+use tokio::runtime::Runtime;
+
+mod bridge {
+    use super::*;
+    use cell_sdk::json_bridge::call;
+
+    // `cell_remote!` and `targets()` for every cell in `CELL_REPL_CELLS`
+    include!(concat!(env!("OUT_DIR"), "/cells.rs"));
+
+    pub(crate) fn caller(cell: &str) -> Option<Caller> {
+        targets().get(cell).copied()
+    }
+
+    pub(crate) fn cells() -> Vec<&'static str> {
+        let mut cells: Vec<_> = targets().into_keys().collect();
+        cells.sort_unstable();
+        cells
+    }
+}
+
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+static CONNECTIONS: OnceLock<Mutex<HashMap<String, ResilientSynapse>>> = OnceLock::new();
+
+fn runtime() -> &'static Runtime {
+    RUNTIME.get_or_init(|| Runtime::new().expect("Failed to start the cell-repl runtime"))
+}
+
+fn connections() -> &'static Mutex<HashMap<String, ResilientSynapse>> {
+    CONNECTIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Run a future to completion on the crate's runtime, for the parts of
+/// cell-sdk that have no blocking helper here.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    runtime().block_on(future)
+}
+
+/// Call `method` on `cell` with an object of its arguments, connecting first
+/// if needed.
+pub fn call(cell: &str, method: &str, args: Value) -> Result<Pretty> {
+    let caller = bridged_caller(cell)?;
+    let conn = connect(cell)?;
+    let result = block_on(caller(conn, method.to_string(), args))?;
+    Ok(Pretty(result))
+}
+
+/// Connection to `cell`, reusing the one from an earlier call.
+pub fn connect(cell: &str) -> Result<ResilientSynapse> {
+    if let Some(conn) = connections().lock().unwrap().get(cell) {
+        return Ok(conn.clone());
+    }
+    let conn = block_on(ResilientSynapse::grow(cell))?;
+    connections()
+        .lock()
+        .unwrap()
+        .insert(cell.to_string(), conn.clone());
+    Ok(conn)
+}
+
+/// Drop the cached connection to `cell`; the next call connects afresh.
+pub fn disconnect(cell: &str) -> bool {
+    connections().lock().unwrap().remove(cell).is_some()
+}
+
+/// Cells this build can call
+pub fn bridged() -> Vec<&'static str> {
+    bridge::cells()
+}
+
+fn bridged_caller(cell: &str) -> Result<Caller> {
+    bridge::caller(cell).ok_or_else(|| {
+        anyhow::anyhow!(
+            "'{}' is not bridged into cell-repl (bridged: {:?}); rebuild with it in CELL_REPL_CELLS",
+            cell,
+            bridged()
+        )
+    })
+}
+
+/// JSON result that prints indented
+#[derive(Clone, PartialEq)]
+pub struct Pretty(pub Value);
+
+impl Pretty {
+    pub fn into_inner(self) -> Value {
+        self.0
+    }
+
+    /// Picked up by evcxr in place of `Debug`: a JSON tree where the
+    /// frontend renders one, indented text elsewhere.
+    pub fn evcxr_display(&self) {
+        println!(
+            "EVCXR_BEGIN_CONTENT application/json\n{}\nEVCXR_END_CONTENT",
+            self.0
+        );
+        println!(
+            "EVCXR_BEGIN_CONTENT text/plain\n{}\nEVCXR_END_CONTENT",
+            self
+        );
+    }
+}
+
+impl Deref for Pretty {
+    type Target = Value;
+
+    fn deref(&self) -> &Value {
+        &self.0
+    }
+}
+
+impl From<Pretty> for Value {
+    fn from(pretty: Pretty) -> Value {
+        pretty.0
+    }
+}
+
+impl fmt::Display for Pretty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match serde_json::to_string_pretty(&self.0) {
+            Ok(text) => f.write_str(&text),
+            Err(_) => write!(f, "{}", self.0),
+        }
+    }
+}
+
+// The REPL prints results with `{:?}`
+impl fmt::Debug for Pretty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/actor.rs
+//! Per-key mailboxes for `#[handler(actor_key = "...")]`.
+//!
+//! Calls carrying the same key run one at a time, in arrival order; calls for
+//! different keys run in parallel. Stateful cells can then keep per-key state
+//! without a mutex per handler.
+
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Mutex as Turn, OwnedMutexGuard};
+
+#[derive(Default)]
+pub struct Mailboxes {
+    boxes: Mutex<HashMap<u64, Arc<Turn<()>>>>,
+}
+
+/// Held while a call runs; the next call for the key starts once it drops.
+pub struct Slot {
+    key: u64,
+    turn: Option<OwnedMutexGuard<()>>,
+    mailboxes: &'static Mailboxes,
+}
+
+impl Mailboxes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait for the turn of `key`. Turns are handed out first come, first served.
+    pub async fn enter<K: Hash + ?Sized>(&'static self, key: &K) -> Slot {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let key = hasher.finish();
+
+        let mailbox = self.boxes.lock().unwrap().entry(key).or_default().clone();
+        Slot {
+            key,
+            turn: Some(mailbox.lock_owned().await),
+            mailboxes: self,
+        }
+    }
+
+    /// Keys with a call running or waiting
+    pub fn active(&self) -> usize {
+        self.boxes.lock().unwrap().len()
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        // The guard holds a reference to the mailbox too
+        drop(self.turn.take());
+        let mut boxes = self.mailboxes.boxes.lock().unwrap();
+        // Nobody else holds or waits on this mailbox: drop it
+        if boxes.get(&self.key).is_some_and(|m| Arc::strong_count(m) == 1) {
+            boxes.remove(&self.key);
+        }
+    }
+}
@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/admin.rs
+//! The admin socket, the only place a cell takes privileged OPS requests.
+//!
+//! Any connection may ask a cell for its status, metrics, health or schema.
+//! Requests that change it or read out its internals
+//! (`OpsRequest::is_privileged`: Shutdown, Drain, Adopt, SetLogLevel, Profile,
+//! Checkpoint, Restore, VerifyReplay, LoadPlugin, Inspect, SlowRequests,
+//! Spans) are refused on the membrane and served on
+//! `~/.cell/io/<cell>.admin` instead. That socket is created 0600 and, like
+//! the handover junction, serves only processes of the cell's own user, so a
+//! client that merely consumes a cell cannot shut it down or reconfigure it.
+//!
+//! [`crate::state::ops`] sends privileged requests here, which means they
+//! only reach cells on the same host. Cells composed into one process share
+//! its trust, and take them on their in-memory connections.
+
+use anyhow::{Context, Result};
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use tokio::net::{UnixListener, UnixStream};
+
+pub fn socket_path(cell_name: &str) -> Result<PathBuf> {
+    let home = dirs::home_dir().context("No HOME dir")?;
+    Ok(home.join(".cell/io").join(format!("{}.admin", cell_name)))
+}
+
+/// Bind the admin socket of `cell_name`, replacing one left by a previous
+/// instance
+pub(crate) fn bind(cell_name: &str) -> Result<UnixListener> {
+    let path = socket_path(cell_name)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)
+        .with_context(|| format!("Failed to bind admin socket {:?}", path))?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+/// Uid of the connected process, if it is the cell's own user
+pub(crate) fn authorize(stream: &UnixStream) -> Option<u32> {
+    let uid = nix::unistd::getuid().as_raw();
+    stream
+        .peer_cred()
+        .ok()
+        .map(|c| c.uid())
+        .filter(|peer| *peer == uid)
+}
+
+/// Connect to the admin socket of `cell_name` on this host
+pub(crate) async fn connect(cell_name: &str) -> Result<UnixStream> {
+    let path = socket_path(cell_name)?;
+    UnixStream::connect(&path).await.with_context(|| {
+        format!(
+            "{} has no admin socket at {:?} (privileged OPS are only served on the cell's host)",
+            cell_name, path
+        )
+    })
+}
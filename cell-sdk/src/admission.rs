@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/admission.rs
+//! Dependency-aware admission control.
+//!
+//! Disabled until a cell calls [`enable`] (or [`enable_from_mesh`], which
+//! takes the required cells from the dependencies declared with
+//! `MeshBuilder::declare_dependencies`). A background task then probes each
+//! dependency's health over OPS. While one is unreachable, or degraded with
+//! `reject_degraded` set, handler calls fail at ingress with
+//! `DependencyFailed` naming the dependency, instead of waiting on a timeout
+//! further down the chain.
+//!
+//! Other components can feed what they observe with [`report`]; the next
+//! probe overwrites it.
+
+use crate::error::{CellError, ErrorContext};
+use crate::mesh::MeshBuilder;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyState {
+    Healthy,
+    /// Reachable, but a health probe failed
+    Degraded,
+    /// Did not answer its health probe
+    Unavailable,
+}
+
+#[derive(Debug, Clone)]
+pub struct AdmissionConfig {
+    pub required: Vec<String>,
+    /// Also reject while a dependency is merely degraded
+    pub reject_degraded: bool,
+    /// Zero disables probing; state then only changes through [`report`]
+    pub probe_interval: Duration,
+}
+
+impl AdmissionConfig {
+    pub fn new(required: Vec<String>) -> Self {
+        Self {
+            required,
+            reject_degraded: false,
+            probe_interval: Duration::from_secs(2),
+        }
+    }
+
+    pub fn reject_degraded(mut self) -> Self {
+        self.reject_degraded = true;
+        self
+    }
+
+    pub fn probe_interval(mut self, interval: Duration) -> Self {
+        self.probe_interval = interval;
+        self
+    }
+}
+
+struct Admission {
+    config: AdmissionConfig,
+    states: Mutex<BTreeMap<String, DependencyState>>,
+}
+
+static ADMISSION: OnceLock<Admission> = OnceLock::new();
+
+/// Start gating requests on `config.required`. Only the first call takes
+/// effect. Dependencies count as healthy until their first probe.
+pub fn enable(config: AdmissionConfig) {
+    let states = config
+        .required
+        .iter()
+        .map(|d| (d.clone(), DependencyState::Healthy))
+        .collect();
+    let interval = config.probe_interval;
+    let admission = Admission {
+        config,
+        states: Mutex::new(states),
+    };
+    if ADMISSION.set(admission).is_err() {
+        return;
+    }
+    info!("[Admission] Gating requests on {:?}", required());
+    if interval.is_zero() {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            for dep in required() {
+                let state = match crate::watchdog::probe(&dep).await {
+                    Ok(r) if r.health == crate::watchdog::Health::Healthy => {
+                        DependencyState::Healthy
+                    }
+                    Ok(_) => DependencyState::Degraded,
+                    Err(_) => DependencyState::Unavailable,
+                };
+                report(&dep, state);
+            }
+        }
+    });
+}
+
+/// [`enable`] with the dependencies `cell_name` declared to the mesh.
+pub async fn enable_from_mesh(cell_name: &str, reject_degraded: bool) {
+    let mut config = AdmissionConfig::new(MeshBuilder::dependencies(cell_name).await);
+    config.reject_degraded = reject_degraded;
+    enable(config);
+}
+
+fn required() -> Vec<String> {
+    ADMISSION
+        .get()
+        .map(|a| a.config.required.clone())
+        .unwrap_or_default()
+}
+
+/// Record the observed state of a required dependency.
+pub fn report(dependency: &str, state: DependencyState) {
+    let Some(admission) = ADMISSION.get() else {
+        return;
+    };
+    let mut states = admission.states.lock().unwrap();
+    if let Some(current) = states.get_mut(dependency) {
+        if *current != state {
+            warn!("[Admission] '{}' is now {:?}", dependency, state);
+            *current = state;
+        }
+    }
+}
+
+/// Last known state of each required dependency.
+pub fn states() -> Vec<(String, DependencyState)> {
+    ADMISSION
+        .get()
+        .map(|a| {
+            a.states
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(d, s)| (d.clone(), *s))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Called by generated dispatch before each handler call.
+pub fn check() -> Result<(), ErrorContext> {
+    let Some(admission) = ADMISSION.get() else {
+        return Ok(());
+    };
+    let states = admission.states.lock().unwrap();
+    let blocked = states.iter().find(|(_, state)| match state {
+        DependencyState::Healthy => false,
+        DependencyState::Degraded => admission.config.reject_degraded,
+        DependencyState::Unavailable => true,
+    });
+    match blocked {
+        Some((dep, state)) => Err(ErrorContext::new(CellError::DependencyFailed)
+            .with_message(format!(
+                "Required dependency '{}' is {}",
+                dep,
+                match state {
+                    DependencyState::Degraded => "degraded",
+                    _ => "unavailable",
+                }
+            ))
+            .with_retry_after(admission.config.probe_interval)),
+        None => Ok(()),
+    }
+}
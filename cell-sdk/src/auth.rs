@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/auth.rs
+//! Admin impersonation.
+//!
+//! Holding the mesh admin key (`~/.cell/keys/admin.key`) is the admin
+//! capability: it lets an operator issue an [`ImpersonationGrant`] and have a
+//! cell handle requests as another principal, e.g. to reproduce a permission
+//! issue seen by `service-x`. The Membrane verifies grants against the same key
+//! and exposes the effective caller to handlers through [`caller`].
+
+use anyhow::{anyhow, bail, Context, Result};
+pub use cell_model::auth::{AuthResponse, ImpersonationGrant};
+use std::path::PathBuf;
+
+tokio::task_local! {
+    static CALLER: Caller;
+}
+
+/// The principal a request is being handled as.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Caller {
+    pub principal: String,
+    /// Organism the principal belongs to, as the grant that authenticated it says
+    pub organism: String,
+    /// Operator that issued the impersonation, if any
+    pub impersonated_by: Option<String>,
+}
+
+/// Caller of the request currently being handled, if it was authenticated.
+pub fn caller() -> Option<Caller> {
+    CALLER.try_with(|c| c.clone()).ok()
+}
+
+/// Run `fut` with `caller` as the current caller.
+pub async fn scope<F: std::future::Future>(caller: Option<Caller>, fut: F) -> F::Output {
+    match caller {
+        Some(c) => CALLER.scope(c, fut).await,
+        None => fut.await,
+    }
+}
+
+pub struct AdminKey([u8; 32]);
+
+impl AdminKey {
+    pub fn default_path() -> Result<PathBuf> {
+        let home = dirs::home_dir().context("No HOME")?;
+        Ok(home.join(".cell/keys/admin.key"))
+    }
+
+    /// Load the admin key. Unlike the artifact key it is never created on demand:
+    /// whoever provisions the mesh decides who holds it.
+    pub fn load(path: &std::path::Path) -> Result<Self> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("No admin key at {:?} (admin capability required)", path))?;
+        let key: [u8; 32] = bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow!("Admin key {:?} must be 32 bytes", path))?;
+        Ok(Self(key))
+    }
+
+    pub fn load_default() -> Result<Self> {
+        Self::load(&Self::default_path()?)
+    }
+
+    /// A grant to call `cell` as `principal` of `organism`
+    pub fn issue(
+        &self,
+        operator: &str,
+        cell: &str,
+        principal: &str,
+        organism: &str,
+        reason: &str,
+        ttl_secs: u64,
+    ) -> ImpersonationGrant {
+        let mut grant = ImpersonationGrant {
+            operator: operator.to_string(),
+            principal: principal.to_string(),
+            organism: organism.to_string(),
+            cell: cell.to_string(),
+            reason: reason.to_string(),
+            expires_at: now() + ttl_secs,
+            tag: String::new(),
+        };
+        grant.tag = self.tag(&grant).to_hex().to_string();
+        grant
+    }
+
+    /// Check `grant` was issued with this key for `cell`, and is current
+    pub fn verify(&self, grant: &ImpersonationGrant, cell: &str) -> Result<()> {
+        // blake3::Hash compares in constant time
+        let tag = blake3::Hash::from_hex(&grant.tag).ok();
+        if tag != Some(self.tag(grant)) {
+            bail!("Impersonation grant signature mismatch");
+        }
+        if grant.cell != cell {
+            bail!("Impersonation grant is for '{}'", grant.cell);
+        }
+        if grant.expires_at < now() {
+            bail!("Impersonation grant expired");
+        }
+        Ok(())
+    }
+
+    fn tag(&self, grant: &ImpersonationGrant) -> blake3::Hash {
+        let mut hasher = blake3::Hasher::new_keyed(&self.0);
+        for field in [
+            &grant.operator,
+            &grant.principal,
+            &grant.organism,
+            &grant.cell,
+            &grant.reason,
+        ] {
+            hasher.update(field.as_bytes());
+            hasher.update(&[0]);
+        }
+        hasher.update(&grant.expires_at.to_le_bytes());
+        hasher.finalize()
+    }
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/codec.rs
+//! Negotiated wire formats for application calls.
+//!
+//! Cells speak rkyv. A client that cannot, e.g. one generated for Python or
+//! TypeScript, negotiates JSON or MessagePack first (see
+//! [`cell_core::codec`]); the Membrane then decodes its requests into the
+//! protocol, runs the handler as usual and encodes the response back. Payloads
+//! use the protocol's serde shape, externally tagged by method:
+//! `{"Deposit": {"req": {...}}}` in, `{"Deposit": {...}}` out. A failed call
+//! answers with [`ErrorResponse`] in the same codec. AUTH and OPS stay rkyv.
+//!
+//! Services generated by `#[handler]` [`register`] their protocol on serve;
+//! a cell without a registered protocol only ever picks rkyv.
+
+use crate::{ErrorResponse, Synapse};
+use anyhow::{anyhow, bail, Result};
+pub use cell_core::codec::Codec;
+use cell_model::rkyv::de::deserializers::SharedDeserializeMap;
+use cell_model::rkyv::ser::serializers::AllocSerializer;
+use cell_model::rkyv::validation::validators::DefaultValidator;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::Mutex;
+
+#[derive(Clone, Copy)]
+struct Transcoder {
+    request: fn(Codec, &[u8]) -> Result<Vec<u8>>,
+    response: fn(Codec, &[u8]) -> Result<Vec<u8>>,
+}
+
+static TRANSCODER: Mutex<Option<Transcoder>> = Mutex::new(None);
+
+/// Let non-rkyv clients call the cell's protocol `Req`, answered with `Resp`.
+pub fn register<Req, Resp>()
+where
+    Req: DeserializeOwned + rkyv::Serialize<AllocSerializer<1024>>,
+    Resp: rkyv::Archive + Serialize,
+    Resp::Archived: rkyv::Deserialize<Resp, SharedDeserializeMap>
+        + for<'a> rkyv::CheckBytes<DefaultValidator<'a>>,
+{
+    *TRANSCODER.lock().unwrap() = Some(Transcoder {
+        request: request::<Req>,
+        response: response::<Resp>,
+    });
+}
+
+/// Whether this cell can serve `codec`
+pub fn supports(codec: Codec) -> bool {
+    codec == Codec::Rkyv || TRANSCODER.lock().unwrap().is_some()
+}
+
+pub fn to_vec<T: Serialize>(codec: Codec, value: &T) -> Result<Vec<u8>> {
+    match codec {
+        Codec::Json => Ok(serde_json::to_vec(value)?),
+        Codec::MsgPack => Ok(rmp_serde::to_vec_named(value)?),
+        Codec::Rkyv => bail!("rkyv payloads are not serde-encoded"),
+    }
+}
+
+pub fn from_slice<T: DeserializeOwned>(codec: Codec, bytes: &[u8]) -> Result<T> {
+    match codec {
+        Codec::Json => Ok(serde_json::from_slice(bytes)?),
+        Codec::MsgPack => Ok(rmp_serde::from_slice(bytes)?),
+        Codec::Rkyv => bail!("rkyv payloads are not serde-encoded"),
+    }
+}
+
+/// Ask the cell behind `synapse` to use the first of `prefer` it supports
+/// for the rest of the connection.
+pub async fn negotiate(synapse: &Synapse, prefer: &[Codec]) -> Result<Codec> {
+    let resp = synapse
+        .fire_on_channel(cell_core::channel::CODEC, &Codec::offer(prefer))
+        .await?
+        .into_owned();
+    match resp.as_slice() {
+        [byte] => Codec::from_u8(*byte).ok_or_else(|| anyhow!("Unknown codec {}", byte)),
+        _ => bail!("Invalid codec handshake response"),
+    }
+}
+
+/// An incoming request in `codec`, as the rkyv bytes the handler expects
+pub(crate) fn decode_request(codec: Codec, bytes: &[u8]) -> Result<Vec<u8>> {
+    let transcoder = TRANSCODER
+        .lock()
+        .unwrap()
+        .ok_or_else(|| anyhow!("No protocol registered"))?;
+    (transcoder.request)(codec, bytes)
+}
+
+/// A handler's rkyv response, in `codec`
+pub(crate) fn encode_response(codec: Codec, bytes: &[u8]) -> Result<Vec<u8>> {
+    let transcoder = TRANSCODER
+        .lock()
+        .unwrap()
+        .ok_or_else(|| anyhow!("No protocol registered"))?;
+    (transcoder.response)(codec, bytes)
+}
+
+pub(crate) fn encode_error(codec: Codec, err: &ErrorResponse) -> Result<Vec<u8>> {
+    to_vec(codec, err)
+}
+
+fn request<Req>(codec: Codec, bytes: &[u8]) -> Result<Vec<u8>>
+where
+    Req: DeserializeOwned + rkyv::Serialize<AllocSerializer<1024>>,
+{
+    let req: Req = from_slice(codec, bytes)?;
+    Ok(rkyv::to_bytes::<_, 1024>(&req)?.into_vec())
+}
+
+fn response<Resp>(codec: Codec, bytes: &[u8]) -> Result<Vec<u8>>
+where
+    Resp: rkyv::Archive + Serialize,
+    Resp::Archived: rkyv::Deserialize<Resp, SharedDeserializeMap>
+        + for<'a> rkyv::CheckBytes<DefaultValidator<'a>>,
+{
+    let archived =
+        rkyv::check_archived_root::<Resp>(bytes).map_err(|e| anyhow!("Invalid response: {}", e))?;
+    let resp: Resp = rkyv::Deserialize::deserialize(archived, &mut SharedDeserializeMap::new())
+        .map_err(|e| anyhow!("Invalid response: {}", e))?;
+    to_vec(codec, &resp)
+}
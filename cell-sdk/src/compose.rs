@@ -0,0 +1,191 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/compose.rs
+//! Several cells in one OS process.
+//!
+//! [`compose!`](crate::compose!) runs each `#[handler]` service as a task.
+//! Their Membranes accept connections over in-memory duplex pipes instead of
+//! Unix sockets, and `Synapse::grow` for a hosted name connects through the
+//! same pipes, so composed cells call each other exactly as they would across
+//! processes. Each cell is still registered with the nucleus.
+//!
+//! Application requests skip the pipe altogether: the Membrane also registers
+//! a [`LocalDispatch`] that synapses call directly. The request is archived
+//! once by the caller and handed over without framing or validation, since
+//! the bytes never left the process.
+//!
+//! ```ignore
+//! cell_sdk::compose! {
+//!     "ledger" => LedgerService::new(),
+//!     "api" => ApiService::default(),
+//! }
+//! .await?;
+//! ```
+
+use crate::membrane::BoxFuture;
+use anyhow::{anyhow, Result};
+use rkyv::AlignedVec;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, OnceLock, RwLock};
+use tokio::io::DuplexStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tracing::info;
+
+/// Buffer size of each in-memory connection
+const PIPE_CAPACITY: usize = 256 * 1024;
+
+type Acceptor = mpsc::UnboundedSender<DuplexStream>;
+type Listener = mpsc::UnboundedReceiver<DuplexStream>;
+
+/// Calls a hosted cell's handler with an archived request, returning the
+/// archived response and whether it is degraded.
+pub type LocalDispatch =
+    Arc<dyn Fn(AlignedVec) -> BoxFuture<'static, Result<(Vec<u8>, bool)>> + Send + Sync>;
+
+#[derive(Default)]
+struct Hosted {
+    acceptors: HashMap<String, Acceptor>,
+    dispatchers: HashMap<String, LocalDispatch>,
+    /// Listeners not yet claimed by a Membrane
+    pending: HashMap<String, Listener>,
+}
+
+static HOSTED: OnceLock<RwLock<Hosted>> = OnceLock::new();
+
+fn hosted() -> &'static RwLock<Hosted> {
+    HOSTED.get_or_init(Default::default)
+}
+
+/// Host `name` in this process: its Membrane will serve in-memory connections.
+pub fn host(name: &str) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let mut hosted = hosted().write().unwrap();
+    hosted.acceptors.insert(name.to_string(), tx);
+    hosted.pending.insert(name.to_string(), rx);
+}
+
+pub fn is_hosted(name: &str) -> bool {
+    hosted().read().unwrap().acceptors.contains_key(name)
+}
+
+/// Open an in-memory connection to a cell hosted in this process.
+pub fn connect(name: &str) -> Option<DuplexStream> {
+    let hosted = hosted().read().unwrap();
+    let acceptor = hosted.acceptors.get(name)?;
+    let (client, server) = tokio::io::duplex(PIPE_CAPACITY);
+    acceptor.send(server).ok()?;
+    Some(client)
+}
+
+/// Direct handler entry point of a hosted cell, once its Membrane is up.
+pub fn dispatcher(name: &str) -> Option<LocalDispatch> {
+    hosted().read().unwrap().dispatchers.get(name).cloned()
+}
+
+pub(crate) fn register_dispatch(name: &str, dispatch: LocalDispatch) {
+    hosted()
+        .write()
+        .unwrap()
+        .dispatchers
+        .insert(name.to_string(), dispatch);
+}
+
+/// Claim the connection queue for `name`, if it is hosted.
+pub(crate) fn take_listener(name: &str) -> Option<Listener> {
+    hosted().write().unwrap().pending.remove(name)
+}
+
+type ServeFuture = BoxFuture<'static, Result<()>>;
+
+/// Services to run together; built by [`compose!`](crate::compose!).
+#[derive(Default)]
+pub struct Composition {
+    cells: Vec<(&'static str, ServeFuture)>,
+}
+
+impl Composition {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a cell. `serve` is the service's `serve(name)` future.
+    pub fn cell<F>(mut self, name: &'static str, serve: F) -> Self
+    where
+        F: Future<Output = Result<()>> + Send + 'static,
+    {
+        host(name);
+        self.cells.push((name, Box::pin(serve)));
+        self
+    }
+
+    /// Spawn every cell on the current runtime.
+    pub fn start(self) -> Running {
+        let mut names = Vec::new();
+        let mut tasks = JoinSet::new();
+        for (name, serve) in self.cells {
+            info!("[Compose] Starting '{}' in-process", name);
+            names.push(name);
+            tasks.spawn(async move { (name, serve.await) });
+        }
+        Running { names, tasks }
+    }
+}
+
+pub struct Running {
+    names: Vec<&'static str>,
+    tasks: JoinSet<(&'static str, Result<()>)>,
+}
+
+impl Running {
+    pub fn names(&self) -> &[&'static str] {
+        &self.names
+    }
+
+    /// Wait until any cell stops; the others are aborted.
+    pub async fn wait(mut self) -> Result<()> {
+        let Some(joined) = self.tasks.join_next().await else {
+            return Ok(());
+        };
+        self.tasks.abort_all();
+        let (name, result) = joined.map_err(|e| anyhow!("Composed cell panicked: {}", e))?;
+        match result {
+            Ok(()) => Err(anyhow!("Composed cell '{}' stopped", name)),
+            Err(e) => Err(anyhow!("Composed cell '{}' failed: {}", name, e)),
+        }
+    }
+}
+
+/// Run several `#[handler]` services in this process and register each with
+/// the nucleus. Evaluates to a future that resolves when any of them stops.
+#[macro_export]
+macro_rules! compose {
+    ($($name:literal => $service:expr),+ $(,)?) => {{
+        $crate::cell_remote!(ComposeNucleus = "nucleus");
+        let composition = $crate::compose::Composition::new()
+            $(.cell($name, $service.serve($name)))+;
+        async move {
+            let running = composition.start();
+            let node_id = $crate::identity::Identity::get().node_id;
+            for name in running.names() {
+                let registered = async {
+                    let nucleus = ComposeNucleus::Client::connect().await?;
+                    nucleus
+                        .register(ComposeNucleus::CellRegistration {
+                            name: name.to_string(),
+                            node_id,
+                            capabilities: vec!["composed".to_string()],
+                            endpoints: vec![format!("local://{}", name)],
+                            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+                            env: vec![],
+                        })
+                        .await
+                };
+                if let Err(e) = registered.await {
+                    $crate::tracing::warn!("[Compose] Could not register '{}' with nucleus: {}", name, e);
+                }
+            }
+            running.wait().await
+        }
+    }};
+}
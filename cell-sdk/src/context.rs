@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/context.rs
+//! Request metadata for handlers.
+//!
+//! A `#[handler]` method may take `ctx: &CallContext` as its first argument;
+//! it is not part of the protocol, the Membrane fills it in for every call.
+//! Code deeper down the call reaches the same context through [`current`].
+
+use crate::auth::Caller;
+use crate::trace::TraceContext;
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    static CONTEXT: CallContext;
+}
+
+/// Who is calling, from where, and how long they are willing to wait.
+#[derive(Debug, Clone)]
+pub struct CallContext {
+    /// Connection the request arrived on, e.g. `uid:1000`, `tcp:10.0.0.7` or
+    /// `local` for same-process callers
+    pub peer: String,
+    /// Authenticated caller, if the connection went through AUTH
+    pub caller: Option<Caller>,
+    pub organism: String,
+    /// Trace of the call, continued from the caller's; `span_id` is this call's
+    pub trace: TraceContext,
+    /// When the caller stops waiting, if known
+    pub deadline: Option<Instant>,
+    /// Priority of the caller's call chain, inherited by this call (see
+    /// [`crate::priority`])
+    pub priority: Option<u8>,
+}
+
+impl CallContext {
+    /// A call starting a new trace
+    pub fn new(peer: impl Into<String>, caller: Option<Caller>) -> Self {
+        Self::traced(peer, caller, None)
+    }
+
+    /// A call sent with the trace context `parent`
+    pub(crate) fn traced(
+        peer: impl Into<String>,
+        caller: Option<Caller>,
+        parent: Option<TraceContext>,
+    ) -> Self {
+        Self {
+            peer: peer.into(),
+            caller,
+            organism: crate::identity::Identity::get().organism.clone(),
+            trace: crate::trace::span_of(parent),
+            deadline: None,
+            priority: None,
+        }
+    }
+
+    /// Hex trace id, for correlating log lines across cells
+    pub fn trace_id(&self) -> String {
+        self.trace.trace_id_hex()
+    }
+
+    /// The principal quotas and logs attribute the call to
+    pub fn principal(&self) -> &str {
+        self.caller.as_ref().map_or(&self.peer, |c| &c.principal)
+    }
+
+    /// Tighten the deadline to at most `budget` from now
+    pub fn within(mut self, budget: Duration) -> Self {
+        let by = Instant::now() + budget;
+        self.deadline = Some(self.deadline.map_or(by, |d| d.min(by)));
+        self
+    }
+
+    /// Time left before the deadline; `None` without one
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|d| d.saturating_duration_since(Instant::now()))
+    }
+
+    /// Whether the caller has already given up
+    pub fn expired(&self) -> bool {
+        self.remaining() == Some(Duration::ZERO)
+    }
+}
+
+/// Context of the request currently being handled. Outside a Membrane call
+/// (tests, background tasks) it describes a local caller.
+pub fn current() -> CallContext {
+    CONTEXT
+        .try_with(|c| c.clone())
+        .unwrap_or_else(|_| CallContext::new("local", crate::auth::caller()))
+}
+
+/// Trace of the request being handled, if any
+pub(crate) fn current_trace() -> Option<TraceContext> {
+    CONTEXT.try_with(|c| c.trace).ok()
+}
+
+/// Priority the request being handled inherited, if any
+pub(crate) fn current_priority() -> Option<u8> {
+    CONTEXT.try_with(|c| c.priority).ok().flatten()
+}
+
+/// Run `fut` with `ctx` as the current context, and its caller as the
+/// current [`crate::auth::caller`].
+pub async fn scope<F: std::future::Future>(ctx: CallContext, fut: F) -> F::Output {
+    let caller = ctx.caller.clone();
+    CONTEXT.scope(ctx, crate::auth::scope(caller, fut)).await
+}
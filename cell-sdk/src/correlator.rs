@@ -0,0 +1,238 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/correlator.rs
+//! Many in-flight requests over one connection.
+//!
+//! A request sent with [`VesicleHeader::CORRELATED`] carries a u64 id after its
+//! channel byte. The Membrane handles such requests concurrently and answers
+//! each with [`frame::CORRELATED`] set and the same id ahead of the payload.
+//! A [`Correlator`] owns the connection: a writer task writes each request
+//! as one whole frame, and a reader task hands each response to the call
+//! waiting for its id. A call that times out or is cancelled leaves its
+//! request to be written in full, so the next frame never starts inside it,
+//! and a response that arrives after its call gave up is dropped instead of
+//! being read by the next call.
+//!
+//! `ResilientSynapse` uses one when `ResilienceConfig::multiplex` is set.
+
+use crate::response::Response;
+use anyhow::{anyhow, bail, Result};
+use cell_core::{frame, TraceContext, VesicleHeader};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+/// Largest response accepted
+const MAX_FRAME: usize = 100 * 1024 * 1024;
+
+type Reply = std::result::Result<(u32, Vec<u8>), String>;
+
+#[derive(Default)]
+struct Waiting {
+    calls: HashMap<u64, oneshot::Sender<Reply>>,
+    /// Why the reader stopped; no call can be answered after that
+    closed: Option<String>,
+}
+
+/// Forgets a call's id however the call ends
+struct Forget<'a> {
+    waiting: &'a StdMutex<Waiting>,
+    id: u64,
+}
+
+impl Drop for Forget<'_> {
+    fn drop(&mut self) {
+        self.waiting.lock().unwrap().calls.remove(&self.id);
+    }
+}
+
+pub struct Correlator {
+    /// Frames for the writer task
+    requests: mpsc::UnboundedSender<Vec<u8>>,
+    waiting: Arc<StdMutex<Waiting>>,
+    next_id: AtomicU64,
+    my_id: u64,
+    reader: JoinHandle<()>,
+}
+
+impl Correlator {
+    /// Take over `stream`; `my_id` is sent as the source of every request.
+    pub fn new<S>(stream: S, my_id: u64) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (reader, writer) = tokio::io::split(stream);
+        let waiting = Arc::new(StdMutex::new(Waiting::default()));
+        let (requests, queued) = mpsc::unbounded_channel();
+        // Ends once the Correlator is dropped and the queue is written
+        tokio::spawn(write_requests(writer, queued, waiting.clone()));
+        Self {
+            requests,
+            reader: tokio::spawn(read_responses(reader, waiting.clone())),
+            waiting,
+            next_id: AtomicU64::new(1),
+            my_id,
+        }
+    }
+
+    /// Calls awaiting their response
+    pub fn in_flight(&self) -> usize {
+        self.waiting.lock().unwrap().calls.len()
+    }
+
+    /// Whether the connection has failed; every further call will too
+    pub fn is_closed(&self) -> bool {
+        self.waiting.lock().unwrap().closed.is_some()
+    }
+
+    /// Send `payload` on `chan` and wait up to `timeout` for its response.
+    pub async fn send(
+        &self,
+        chan: u8,
+        payload: &[u8],
+        timeout: Duration,
+    ) -> Result<Response<'static, Vec<u8>>> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        {
+            let mut waiting = self.waiting.lock().unwrap();
+            if let Some(reason) = &waiting.closed {
+                bail!("Connection closed: {}", reason);
+            }
+            waiting.calls.insert(id, tx);
+        }
+        let _forget = Forget {
+            waiting: &self.waiting,
+            id,
+        };
+
+        let request = request_frame(self.my_id, id, chan, payload);
+        if self.requests.send(request).is_err() {
+            bail!("Connection closed");
+        }
+        let (prefix, body) = tokio::time::timeout(timeout, async {
+            match rx.await {
+                Ok(Ok(reply)) => Ok(reply),
+                Ok(Err(reason)) => Err(anyhow!("Connection closed: {}", reason)),
+                Err(_) => Err(anyhow!("Connection closed")),
+            }
+        })
+        .await
+        .map_err(|_| anyhow!("Socket read timeout"))??;
+
+        if prefix & frame::ERROR != 0 {
+            return Err(crate::error::from_frame(&body));
+        }
+        Ok(Response::from_frame(prefix, body))
+    }
+}
+
+impl Drop for Correlator {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+impl std::fmt::Debug for Correlator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Correlator")
+            .field("in_flight", &self.in_flight())
+            .finish()
+    }
+}
+
+/// `[u32 len][header][channel][u64 id][trace context, if traced][payload]`
+fn request_frame(my_id: u64, id: u64, chan: u8, payload: &[u8]) -> Vec<u8> {
+    let trace = crate::trace::outgoing();
+    let priority = crate::priority::current();
+    let mut flags = VesicleHeader::CORRELATED;
+    if trace.is_some() {
+        flags |= VesicleHeader::TRACED;
+    }
+    if priority.is_some() {
+        flags |= VesicleHeader::PRIORITIZED;
+    }
+    let header = VesicleHeader {
+        target_id: 0,
+        source_id: my_id,
+        ttl: 64,
+        flags,
+        priority: priority.unwrap_or(0),
+        _pad: [0; 5],
+    };
+    let trace_len = trace.map_or(0, |_| TraceContext::SIZE);
+    let len = VesicleHeader::SIZE + 1 + 8 + trace_len + payload.len();
+    let mut request = Vec::with_capacity(4 + len);
+    request.extend_from_slice(&(len as u32).to_le_bytes());
+    let h_bytes: [u8; 24] = unsafe { std::mem::transmute(header) };
+    request.extend_from_slice(&h_bytes);
+    request.push(chan);
+    request.extend_from_slice(&id.to_le_bytes());
+    if let Some(trace) = trace {
+        request.extend_from_slice(&trace.to_bytes());
+    }
+    request.extend_from_slice(payload);
+    request
+}
+
+/// Write queued frames in order, each in full. A failed write leaves the
+/// stream mid-frame, so the connection is closed.
+async fn write_requests<W: AsyncWrite + Unpin>(
+    mut writer: W,
+    mut queued: mpsc::UnboundedReceiver<Vec<u8>>,
+    waiting: Arc<StdMutex<Waiting>>,
+) {
+    while let Some(request) = queued.recv().await {
+        let written = async {
+            writer.write_all(&request).await?;
+            writer.flush().await
+        };
+        if let Err(e) = written.await {
+            close(&waiting, format!("write failed: {}", e));
+            return;
+        }
+    }
+}
+
+async fn read_responses<R: AsyncRead + Unpin>(mut reader: R, waiting: Arc<StdMutex<Waiting>>) {
+    let reason = loop {
+        match read_frame(&mut reader).await {
+            Ok((id, prefix, body)) => match waiting.lock().unwrap().calls.remove(&id) {
+                Some(call) => {
+                    let _ = call.send(Ok((prefix, body)));
+                }
+                None => debug!("[Correlator] Dropped response {} to an abandoned call", id),
+            },
+            Err(e) => break e.to_string(),
+        }
+    };
+    close(&waiting, reason);
+}
+
+/// Fail every waiting call and all later ones
+fn close(waiting: &StdMutex<Waiting>, reason: String) {
+    let mut waiting = waiting.lock().unwrap();
+    for (_, call) in waiting.calls.drain() {
+        let _ = call.send(Err(reason.clone()));
+    }
+    waiting.closed.get_or_insert(reason);
+}
+
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<(u64, u32, Vec<u8>)> {
+    let prefix = reader.read_u32_le().await?;
+    if prefix & frame::CORRELATED == 0 {
+        bail!("Peer answered without a correlation id (multiplexing unsupported)");
+    }
+    let id = reader.read_u64_le().await?;
+    let len = (prefix & frame::LEN_MASK) as usize;
+    if len > MAX_FRAME {
+        bail!("Response too large: {} bytes", len);
+    }
+    let mut body = vec![0u8; len];
+    reader.read_exact(&mut body).await?;
+    Ok((id, prefix, body))
+}
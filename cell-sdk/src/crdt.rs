@@ -1,9 +1,58 @@
 // SPDX-License-Identifier: MIT
-// Distributed State Primitives
+// cell-sdk/src/crdt.rs
+//! Replicated state that converges without consensus.
+//!
+//! Every type here merges commutatively, associatively and idempotently, so
+//! replicas that swap their state in any order, any number of times, end up
+//! equal:
+//!
+//! - [`GCounter`] and [`PnCounter`]: a count per node, summed
+//! - [`LwwRegister`]: the value with the latest `(timestamp, node)` stamp
+//! - [`OrSet`]: a set whose adds win over concurrent removes
+//!
+//! [`Gossip`] keeps them in sync between cells the way pheromones announce
+//! cells on the LAN: every member broadcasts the full state of each topic
+//! over UDP on a tick and whenever it changes, and merges what it hears.
+//! Nothing waits for a quorum, so edge cells keep writing through a
+//! partition and converge once they hear each other again.
+//!
+//! ```ignore
+//! let gossip = Gossip::join(GossipConfig::from_env()).await?;
+//! let visits = gossip.replicate("visits", PnCounter::new(gossip.node_id()))?;
+//! visits.update(|c| c.inc());
+//! println!("{} visits mesh-wide", visits.get().value());
+//! ```
+//!
+//! Members share `CELL_GOSSIP_PORT` (default 9098), and broadcast reaches
+//! the ones on other hosts of the subnet. Cells on one host need a port each;
+//! `CELL_GOSSIP_PEERS` (comma-separated `ip:port`) lists members broadcast
+//! does not reach, and `CELL_GOSSIP_BROADCAST=0` leaves only those. A
+//! topic's state travels in one datagram, so it must encode to at most
+//! [`MAX_STATE`] bytes.
 
-use std::collections::HashMap;
-// Fixed: Removed unused imports
+use anyhow::{bail, Context, Result};
 use cell_macros::protein;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::sync::{watch, Notify};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Largest encoded state a topic may gossip, below the UDP datagram limit
+pub const MAX_STATE: usize = 60_000;
+
+const DEFAULT_PORT: u16 = 9098;
+
+/// State that merges with any other replica of itself
+pub trait Crdt: Clone + PartialEq + Serialize + DeserializeOwned + Send + Sync + 'static {
+    /// Fold in what `other` knows
+    fn merge(&mut self, other: &Self);
+}
 
 /// A G-Counter (Grow-only Counter)
 #[protein]
@@ -21,7 +70,11 @@ impl GCounter {
     }
 
     pub fn inc(&mut self) {
-        *self.counts.entry(self.id).or_insert(0) += 1;
+        self.add(1);
+    }
+
+    pub fn add(&mut self, n: u64) {
+        *self.counts.entry(self.id).or_insert(0) += n;
     }
 
     pub fn value(&self) -> u64 {
@@ -36,33 +89,492 @@ impl GCounter {
     }
 }
 
+impl Crdt for GCounter {
+    fn merge(&mut self, other: &Self) {
+        GCounter::merge(self, other)
+    }
+}
+
+/// A counter that also goes down: increments and decrements are counted
+/// apart, each grow-only
+#[protein]
+pub struct PnCounter {
+    incs: GCounter,
+    decs: GCounter,
+}
+
+impl PnCounter {
+    pub fn new(node_id: u64) -> Self {
+        Self {
+            incs: GCounter::new(node_id),
+            decs: GCounter::new(node_id),
+        }
+    }
+
+    pub fn inc(&mut self) {
+        self.incs.add(1);
+    }
+
+    pub fn dec(&mut self) {
+        self.decs.add(1);
+    }
+
+    pub fn add(&mut self, delta: i64) {
+        if delta >= 0 {
+            self.incs.add(delta as u64);
+        } else {
+            self.decs.add(delta.unsigned_abs());
+        }
+    }
+
+    pub fn value(&self) -> i64 {
+        self.incs.value() as i64 - self.decs.value() as i64
+    }
+
+    pub fn merge(&mut self, other: &PnCounter) {
+        self.incs.merge(&other.incs);
+        self.decs.merge(&other.decs);
+    }
+}
+
+impl Crdt for PnCounter {
+    fn merge(&mut self, other: &Self) {
+        PnCounter::merge(self, other)
+    }
+}
+
 /// A Replicated Register (Last-Write-Wins)
+///
+/// Writes are stamped with a timestamp and the writing node, so two writes
+/// with the same timestamp still resolve the same way everywhere.
 #[protein]
 pub struct LwwRegister<T> {
     value: T,
     timestamp: u64,
+    /// Node of the write that holds
+    writer: u64,
+    /// This replica's node, stamped on its writes
+    node: u64,
 }
 
 impl<T: Clone> LwwRegister<T> {
     pub fn new(value: T, timestamp: u64) -> Self {
-        Self { value, timestamp }
+        Self {
+            value,
+            timestamp,
+            writer: 0,
+            node: 0,
+        }
+    }
+
+    /// The replica of node `node`
+    pub fn with_node(mut self, node: u64) -> Self {
+        self.node = node;
+        self
     }
 
     pub fn set(&mut self, value: T, timestamp: u64) {
-        if timestamp > self.timestamp {
+        if (timestamp, self.node) > (self.timestamp, self.writer) {
             self.value = value;
             self.timestamp = timestamp;
+            self.writer = self.node;
         }
     }
 
+    /// Write `value` now: stamped with the clock in ms, and after the value
+    /// it replaces even if the clock is behind
+    pub fn assign(&mut self, value: T) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        self.set(value, now.max(self.timestamp + 1));
+    }
+
     pub fn get(&self) -> &T {
         &self.value
     }
 
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
     pub fn merge(&mut self, other: &LwwRegister<T>) {
-        if other.timestamp > self.timestamp {
+        if (other.timestamp, other.writer) > (self.timestamp, self.writer) {
             self.value = other.value.clone();
             self.timestamp = other.timestamp;
+            self.writer = other.writer;
+        }
+    }
+}
+
+impl<T> Crdt for LwwRegister<T>
+where
+    T: Clone + PartialEq + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    fn merge(&mut self, other: &Self) {
+        LwwRegister::merge(self, other)
+    }
+}
+
+/// An observed-remove set: a remove takes out the adds it has seen, so an
+/// add concurrent with it survives.
+///
+/// Removed adds are remembered for good, so a set that churns grows.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(bound(deserialize = "T: Deserialize<'de> + Ord"))]
+pub struct OrSet<T> {
+    node: u64,
+    /// Adds made by this replica so far, to tag the next one
+    counter: u64,
+    /// Live `(node, n)` tags of each element's adds
+    adds: BTreeMap<T, BTreeSet<(u64, u64)>>,
+    /// Tags of removed adds
+    removed: BTreeSet<(u64, u64)>,
+}
+
+impl<T: Ord + Clone> OrSet<T> {
+    /// The replica of node `node_id`, which must be unique to this replica
+    pub fn new(node_id: u64) -> Self {
+        Self {
+            node: node_id,
+            counter: 0,
+            adds: BTreeMap::new(),
+            removed: BTreeSet::new(),
+        }
+    }
+
+    pub fn insert(&mut self, value: T) {
+        self.counter += 1;
+        let tag = (self.node, self.counter);
+        self.adds.entry(value).or_default().insert(tag);
+    }
+
+    /// Remove `value` as far as this replica has seen it added
+    pub fn remove(&mut self, value: &T) -> bool {
+        match self.adds.remove(value) {
+            Some(tags) => {
+                self.removed.extend(tags);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.adds.contains_key(value)
+    }
+
+    /// The elements, in order
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.adds.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.adds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adds.is_empty()
+    }
+
+    pub fn merge(&mut self, other: &OrSet<T>) {
+        self.removed.extend(other.removed.iter().copied());
+        for (value, tags) in &other.adds {
+            self.adds
+                .entry(value.clone())
+                .or_default()
+                .extend(tags.iter().copied());
         }
+        let removed = &self.removed;
+        self.adds.retain(|_, tags| {
+            tags.retain(|tag| !removed.contains(tag));
+            !tags.is_empty()
+        });
     }
-}
\ No newline at end of file
+}
+
+impl<T> Crdt for OrSet<T>
+where
+    T: Ord + Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    fn merge(&mut self, other: &Self) {
+        OrSet::merge(self, other)
+    }
+}
+
+/// Where and how often [`Gossip`] spreads state
+#[derive(Clone, Debug)]
+pub struct GossipConfig {
+    /// UDP port this member listens on, and broadcasts to
+    pub port: u16,
+    /// Broadcast to the LAN, besides `peers`
+    pub broadcast: bool,
+    /// Members broadcast does not reach
+    pub peers: Vec<SocketAddr>,
+    /// How often every topic goes out even if unchanged, so members that
+    /// missed a datagram or joined late catch up
+    pub interval: Duration,
+}
+
+impl Default for GossipConfig {
+    fn default() -> Self {
+        Self {
+            port: DEFAULT_PORT,
+            broadcast: true,
+            peers: Vec::new(),
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl GossipConfig {
+    /// `CELL_GOSSIP_PORT`, `CELL_GOSSIP_BROADCAST`, `CELL_GOSSIP_PEERS` and
+    /// `CELL_GOSSIP_INTERVAL_MS` over the defaults
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Some(port) = env_parse("CELL_GOSSIP_PORT") {
+            config.port = port;
+        }
+        if let Ok(broadcast) = std::env::var("CELL_GOSSIP_BROADCAST") {
+            config.broadcast = !matches!(broadcast.trim(), "0" | "false" | "off");
+        }
+        if let Ok(peers) = std::env::var("CELL_GOSSIP_PEERS") {
+            config.peers = peers
+                .split(',')
+                .filter_map(|p| p.trim().parse().ok())
+                .collect();
+        }
+        if let Some(ms) = env_parse::<u64>("CELL_GOSSIP_INTERVAL_MS") {
+            config.interval = Duration::from_millis(ms.max(1));
+        }
+        config
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok()?.trim().parse().ok()
+}
+
+/// One datagram: the full state of a topic
+#[derive(Serialize, Deserialize)]
+struct Rumor {
+    from: u64,
+    topic: String,
+    state: Vec<u8>,
+}
+
+/// A replicated value, without its type
+trait Topic: Send + Sync {
+    fn encode(&self) -> Result<Vec<u8>>;
+    /// Returns whether anything was new
+    fn merge(&self, state: &[u8]) -> Result<bool>;
+}
+
+impl<T: Crdt> Topic for watch::Sender<T> {
+    fn encode(&self) -> Result<Vec<u8>> {
+        Ok(rmp_serde::to_vec(&*self.borrow())?)
+    }
+
+    fn merge(&self, state: &[u8]) -> Result<bool> {
+        let other: T = rmp_serde::from_slice(state)?;
+        Ok(self.send_if_modified(|mine| {
+            let before = mine.clone();
+            mine.merge(&other);
+            *mine != before
+        }))
+    }
+}
+
+/// This cell's member of the gossip group
+pub struct Gossip {
+    node_id: u64,
+    socket: Arc<UdpSocket>,
+    targets: Vec<SocketAddr>,
+    topics: Mutex<HashMap<String, Arc<dyn Topic>>>,
+    /// Woken by local updates, to spread them right away
+    changed: Arc<Notify>,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl Gossip {
+    /// Bind `config.port` and start spreading and merging. Stops once the
+    /// last handle to it is dropped.
+    pub async fn join(config: GossipConfig) -> Result<Arc<Self>> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, config.port))
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to bind gossip port {} (cells on one host need a \
+                     CELL_GOSSIP_PORT each)",
+                    config.port
+                )
+            })?;
+        let mut targets = config.peers.clone();
+        if config.broadcast {
+            socket.set_broadcast(true)?;
+            targets.push(SocketAddr::from((Ipv4Addr::BROADCAST, config.port)));
+        }
+
+        let gossip = Arc::new(Self {
+            node_id: rand::random(),
+            socket: Arc::new(socket),
+            targets,
+            topics: Mutex::new(HashMap::new()),
+            changed: Arc::new(Notify::new()),
+            tasks: Mutex::new(Vec::new()),
+        });
+        let listener = tokio::spawn(listen(gossip.socket.clone(), Arc::downgrade(&gossip)));
+        let spreader = tokio::spawn(spread(
+            gossip.changed.clone(),
+            config.interval,
+            Arc::downgrade(&gossip),
+        ));
+        *gossip.tasks.lock().unwrap() = vec![listener, spreader];
+        Ok(gossip)
+    }
+
+    /// Random per member; use it as the node id of the CRDTs it replicates
+    pub fn node_id(&self) -> u64 {
+        self.node_id
+    }
+
+    /// Replicate `initial` as `topic`, merged with what other members hold
+    /// under the same name
+    pub fn replicate<T: Crdt>(self: &Arc<Self>, topic: &str, initial: T) -> Result<Replica<T>> {
+        let mut topics = self.topics.lock().unwrap();
+        if topics.contains_key(topic) {
+            bail!("Gossip topic '{}' is already replicated", topic);
+        }
+        let state = Arc::new(watch::channel(initial).0);
+        topics.insert(topic.to_string(), state.clone());
+        self.changed.notify_one();
+        Ok(Replica {
+            state,
+            gossip: self.clone(),
+        })
+    }
+
+    /// Send every topic to every target
+    async fn spread(&self) {
+        let topics: Vec<_> = self
+            .topics
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, topic)| (name.clone(), topic.clone()))
+            .collect();
+        for (name, topic) in topics {
+            let rumor = topic.encode().and_then(|state| {
+                let rumor = Rumor {
+                    from: self.node_id,
+                    topic: name.clone(),
+                    state,
+                };
+                Ok(rmp_serde::to_vec(&rumor)?)
+            });
+            let bytes = match rumor {
+                Ok(bytes) if bytes.len() <= MAX_STATE => bytes,
+                Ok(bytes) => {
+                    warn!(
+                        "Gossip topic '{}' is {} bytes, over {}; not sent",
+                        name,
+                        bytes.len(),
+                        MAX_STATE
+                    );
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Encoding gossip topic '{}' failed: {}", name, e);
+                    continue;
+                }
+            };
+            for target in &self.targets {
+                if let Err(e) = self.socket.send_to(&bytes, target).await {
+                    debug!("Gossip to {} failed: {}", target, e);
+                }
+            }
+        }
+    }
+
+    fn receive(&self, datagram: &[u8]) {
+        let rumor: Rumor = match rmp_serde::from_slice(datagram) {
+            Ok(rumor) => rumor,
+            Err(_) => return,
+        };
+        if rumor.from == self.node_id {
+            return;
+        }
+        let Some(topic) = self.topics.lock().unwrap().get(&rumor.topic).cloned() else {
+            return;
+        };
+        if let Err(e) = topic.merge(&rumor.state) {
+            warn!("Merging gossip topic '{}' failed: {}", rumor.topic, e);
+        }
+    }
+}
+
+impl Drop for Gossip {
+    fn drop(&mut self) {
+        // Frees the port, which the listener holds
+        for task in self.tasks.get_mut().unwrap().drain(..) {
+            task.abort();
+        }
+    }
+}
+
+async fn listen(socket: Arc<UdpSocket>, gossip: Weak<Gossip>) {
+    let mut buf = vec![0u8; 65536];
+    loop {
+        let len = match socket.recv_from(&mut buf).await {
+            Ok((len, _)) => len,
+            Err(e) => {
+                // ICMP errors from peers that went away surface here
+                debug!("Gossip receive failed: {}", e);
+                continue;
+            }
+        };
+        let Some(gossip) = gossip.upgrade() else {
+            break;
+        };
+        gossip.receive(&buf[..len]);
+    }
+}
+
+async fn spread(changed: Arc<Notify>, interval: Duration, gossip: Weak<Gossip>) {
+    let mut ticks = tokio::time::interval(interval);
+    loop {
+        tokio::select! {
+            _ = ticks.tick() => {}
+            _ = changed.notified() => {}
+        }
+        let Some(gossip) = gossip.upgrade() else {
+            break;
+        };
+        gossip.spread().await;
+    }
+}
+
+/// A CRDT kept in sync by [`Gossip`]
+pub struct Replica<T: Crdt> {
+    state: Arc<watch::Sender<T>>,
+    gossip: Arc<Gossip>,
+}
+
+impl<T: Crdt> Replica<T> {
+    /// The state as merged so far
+    pub fn get(&self) -> T {
+        self.state.borrow().clone()
+    }
+
+    /// Change the local replica and spread the result
+    pub fn update(&self, f: impl FnOnce(&mut T)) {
+        self.state.send_modify(f);
+        self.gossip.changed.notify_one();
+    }
+
+    /// Wakes on every change, local or merged
+    pub fn subscribe(&self) -> watch::Receiver<T> {
+        self.state.subscribe()
+    }
+}
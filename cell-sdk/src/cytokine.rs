@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/cytokine.rs
+//! Topic broadcast through the cytokine broker cell.
+//!
+//! ```ignore
+//! Cytokine::emit("ledger.deposits", &Deposited { account, amount }).await?;
+//!
+//! let mut deposits = Cytokine::subscribe::<Deposited>("ledger.deposits").await?;
+//! while let Ok(event) = deposits.recv().await { ... }
+//! ```
+//!
+//! Inside a `#[handler]` impl, `#[subscriber("ledger.deposits")]` on
+//! `async fn on_deposit(&self, event: Deposited) -> Result<()>` subscribes
+//! the method when the cell starts serving; it is not part of the cell's
+//! protocol. A subscriber only sees what is emitted after it subscribed,
+//! and the broker keeps a bounded log per topic (see
+//! `cell_model::cytokine`), so one that falls too far behind skips ahead.
+
+use crate::ResilientSynapse;
+use anyhow::{Context, Result};
+pub use cell_model::cytokine::{
+    ArchivedCytokineRequest, Batch, CytokineRequest, CytokineResponse, TopicLog, BROKER,
+    DEFAULT_RETAIN, MAX_BATCH, MAX_WAIT_MS,
+};
+use cell_model::rkyv::de::deserializers::SharedDeserializeMap;
+use cell_model::rkyv::ser::serializers::AllocSerializer;
+use cell_model::rkyv::validation::validators::DefaultValidator;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use tracing::{error, warn};
+
+/// Emitters share one connection; each receptor polls over its own.
+static EMITTER: OnceCell<ResilientSynapse> = OnceCell::const_new();
+
+/// Delay before a subscriber retries the broker
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+pub struct Cytokine;
+
+impl Cytokine {
+    /// Broadcast `protein` to `topic`. Returns its sequence number there.
+    pub async fn emit<T>(topic: &str, protein: &T) -> Result<u64>
+    where
+        T: rkyv::Serialize<AllocSerializer<1024>>,
+    {
+        let payload = rkyv::to_bytes::<_, 1024>(protein)
+            .map_err(|e| anyhow::anyhow!("Serialization failed: {}", e))?
+            .into_vec();
+        let conn = EMITTER
+            .get_or_try_init(|| ResilientSynapse::grow(BROKER))
+            .await?;
+        let req = CytokineRequest::Emit {
+            topic: topic.to_string(),
+            payload,
+        };
+        match exchange(conn, &req).await? {
+            CytokineResponse::Emitted { seq } => Ok(seq),
+            other => unexpected(other),
+        }
+    }
+
+    /// Receive what is emitted to `topic` from now on.
+    pub async fn subscribe<T>(topic: &str) -> Result<Receptor<T>>
+    where
+        T: rkyv::Archive,
+        T::Archived: rkyv::Deserialize<T, SharedDeserializeMap>
+            + for<'a> rkyv::CheckBytes<DefaultValidator<'a>>,
+    {
+        let conn = ResilientSynapse::grow(BROKER).await?;
+        let mut receptor = Receptor {
+            topic: topic.to_string(),
+            conn,
+            cursor: None,
+            pending: VecDeque::new(),
+            missed: 0,
+            _protein: PhantomData,
+        };
+        // Pin the cursor now, so emissions before the first recv are kept
+        receptor.poll(0).await?;
+        Ok(receptor)
+    }
+}
+
+/// Subscription to one topic
+pub struct Receptor<T> {
+    topic: String,
+    conn: ResilientSynapse,
+    cursor: Option<u64>,
+    pending: VecDeque<Vec<u8>>,
+    missed: u64,
+    _protein: PhantomData<fn() -> T>,
+}
+
+impl<T> Receptor<T>
+where
+    T: rkyv::Archive,
+    T::Archived:
+        rkyv::Deserialize<T, SharedDeserializeMap> + for<'a> rkyv::CheckBytes<DefaultValidator<'a>>,
+{
+    /// Next protein emitted to the topic, waiting as long as it takes.
+    /// Payloads that are not a `T` are logged and skipped.
+    pub async fn recv(&mut self) -> Result<T> {
+        loop {
+            while let Some(payload) = self.pending.pop_front() {
+                match decode::<T>(&payload) {
+                    Ok(protein) => return Ok(protein),
+                    Err(e) => warn!("[Cytokine] Skipping payload on '{}': {}", self.topic, e),
+                }
+            }
+            self.poll(MAX_WAIT_MS).await?;
+        }
+    }
+
+    /// Proteins dropped by the broker before this receptor read them
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    async fn poll(&mut self, wait_ms: u64) -> Result<()> {
+        let req = CytokineRequest::Poll {
+            topic: self.topic.clone(),
+            from: self.cursor,
+            wait_ms,
+        };
+        let Batch {
+            next,
+            missed,
+            payloads,
+        } = match exchange(&self.conn, &req).await? {
+            CytokineResponse::Batch(batch) => batch,
+            other => return unexpected(other),
+        };
+        if missed > 0 {
+            warn!("[Cytokine] '{}' fell {} behind", self.topic, missed);
+        }
+        self.cursor = Some(next);
+        self.missed += missed;
+        self.pending.extend(payloads);
+        Ok(())
+    }
+}
+
+/// Call `handler` with every protein emitted to `topic`, riding out broker
+/// outages. Used by `#[subscriber]` methods.
+pub fn spawn_subscriber<T, F, Fut>(topic: &'static str, handler: F) -> tokio::task::JoinHandle<()>
+where
+    T: rkyv::Archive + Send + 'static,
+    T::Archived:
+        rkyv::Deserialize<T, SharedDeserializeMap> + for<'a> rkyv::CheckBytes<DefaultValidator<'a>>,
+    F: Fn(T) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<()>> + Send,
+{
+    tokio::spawn(async move {
+        let mut receptor = loop {
+            match Cytokine::subscribe::<T>(topic).await {
+                Ok(r) => break r,
+                Err(e) => {
+                    warn!("[Cytokine] Cannot subscribe to '{}': {}", topic, e);
+                    tokio::time::sleep(RETRY_DELAY).await;
+                }
+            }
+        };
+        loop {
+            match receptor.recv().await {
+                Ok(protein) => {
+                    if let Err(e) = handler(protein).await {
+                        error!("[Cytokine] Subscriber to '{}' failed: {}", topic, e);
+                    }
+                }
+                // The synapse reconnects; the cursor carries on where it was
+                Err(e) => {
+                    warn!("[Cytokine] Polling '{}' failed: {}", topic, e);
+                    tokio::time::sleep(RETRY_DELAY).await;
+                }
+            }
+        }
+    })
+}
+
+fn decode<T>(payload: &[u8]) -> Result<T>
+where
+    T: rkyv::Archive,
+    T::Archived:
+        rkyv::Deserialize<T, SharedDeserializeMap> + for<'a> rkyv::CheckBytes<DefaultValidator<'a>>,
+{
+    let archived = rkyv::check_archived_root::<T>(payload).map_err(|e| anyhow::anyhow!("{}", e))?;
+    rkyv::Deserialize::deserialize(archived, &mut SharedDeserializeMap::new())
+        .map_err(|e| anyhow::anyhow!("{}", e))
+}
+
+async fn exchange(conn: &ResilientSynapse, req: &CytokineRequest) -> Result<CytokineResponse> {
+    let bytes = conn
+        .fire(req)
+        .await
+        .context("Cytokine broker unreachable")?
+        .into_owned();
+    let archived = rkyv::check_archived_root::<CytokineResponse>(&bytes)
+        .map_err(|e| anyhow::anyhow!("Invalid broker response: {}", e))?;
+    let resp: CytokineResponse = rkyv::Deserialize::deserialize(archived, &mut rkyv::Infallible)?;
+    Ok(resp)
+}
+
+fn unexpected<T>(resp: CytokineResponse) -> Result<T> {
+    match resp {
+        CytokineResponse::Error { message } => anyhow::bail!("Cytokine broker: {}", message),
+        other => anyhow::bail!("Unexpected broker response: {:?}", other),
+    }
+}
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/degrade.rs
+//! Graceful degradation for `#[handler(fallback = "...")]` methods.
+//!
+//! When a fallback answers a request, the generated dispatch calls [`mark`];
+//! the Membrane then sets [`cell_core::frame::DEGRADED`] on the response
+//! frame and synapses surface it as [`Response::Degraded`](crate::Response).
+
+use anyhow::{anyhow, Result};
+use std::cell::Cell;
+use std::future::Future;
+use std::time::Duration;
+
+tokio::task_local! {
+    static DEGRADED: Cell<bool>;
+}
+
+/// Mark the response of the current request as degraded.
+pub fn mark() {
+    let _ = DEGRADED.try_with(|d| d.set(true));
+}
+
+/// Run a request, reporting whether its response was marked degraded.
+pub async fn track<F: Future>(fut: F) -> (F::Output, bool) {
+    DEGRADED
+        .scope(Cell::new(false), async {
+            let out = fut.await;
+            (out, DEGRADED.with(|d| d.get()))
+        })
+        .await
+}
+
+/// Run a primary handler method, failing if it exceeds `timeout`.
+pub async fn within<T, F>(timeout: Option<Duration>, fut: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    match timeout {
+        Some(t) => tokio::time::timeout(t, fut)
+            .await
+            .map_err(|_| anyhow!("timed out after {:?}", t))?,
+        None => fut.await,
+    }
+}
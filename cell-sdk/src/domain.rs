@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/domain.rs
+//! Data domains in storage cells.
+//!
+//! Records untagged on write belong to this cell's organism (`CELL_ORGANISM`).
+//! A caller reaches the records of the organism it authenticated as, and of
+//! any other organism only with a [`DomainGrant`] for it; storage cells keep
+//! the grant table current from IAM with [`set_grants`]. Records resident
+//! elsewhere than this host (`CELL_RESIDENCY`) are never stored here. Checks
+//! fail closed: with no grant table only the caller's own organism is
+//! reachable, and without an authenticated caller nothing is.
+
+use crate::error::{CellError, ErrorContext};
+use crate::identity::Identity;
+pub use cell_model::domain::{Domain, DomainGrant};
+use std::sync::RwLock;
+
+static GRANTS: RwLock<Vec<DomainGrant>> = RwLock::new(Vec::new());
+
+/// The domain of records written here without a tag
+pub fn local() -> Domain {
+    Domain::new(Identity::get().organism.clone(), residency())
+}
+
+pub fn residency() -> Option<String> {
+    std::env::var("CELL_RESIDENCY")
+        .ok()
+        .filter(|r| !r.is_empty())
+}
+
+/// `label`, or the local domain when the record carries none
+pub fn resolve(label: Option<&str>) -> Domain {
+    label.map(Domain::parse).unwrap_or_else(local)
+}
+
+/// Replace the grant table
+pub fn set_grants(grants: Vec<DomainGrant>) {
+    *GRANTS.write().unwrap() = grants;
+}
+
+/// Whether the current caller may read or write records of `domain`
+pub fn allowed(domain: &Domain) -> bool {
+    let Some(caller) = crate::auth::caller() else {
+        return false;
+    };
+    if domain.organism == caller.organism {
+        return true;
+    }
+    let now = now_secs();
+    GRANTS
+        .read()
+        .unwrap()
+        .iter()
+        .any(|g| g.covers(&caller.principal, domain, now))
+}
+
+/// Refuse a write this host may not hold, or a caller outside `domain`
+pub fn authorize_write(domain: &Domain) -> anyhow::Result<()> {
+    if !domain.storable_in(residency().as_deref()) {
+        return Err(ErrorContext::new(CellError::AccessDenied)
+            .with_message(format!(
+                "Records of {} must stay in {}; this host is in {}",
+                domain.label(),
+                domain.residency.as_deref().unwrap_or("-"),
+                residency().as_deref().unwrap_or("no region")
+            ))
+            .into());
+    }
+    authorize(domain)
+}
+
+/// Refuse a caller outside `domain`
+pub fn authorize(domain: &Domain) -> anyhow::Result<()> {
+    if allowed(domain) {
+        return Ok(());
+    }
+    let principal = crate::auth::caller().map(|c| c.principal);
+    Err(ErrorContext::new(CellError::AccessDenied)
+        .with_message(format!(
+            "{} holds no grant for domain {}",
+            principal.as_deref().unwrap_or("Unauthenticated caller"),
+            domain.label()
+        ))
+        .into())
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
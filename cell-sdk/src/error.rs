@@ -1,199 +1,47 @@
 // SPDX-License-Identifier: MIT
-// cell-core/src/error.rs
-//! Comprehensive error types for the Cell ecosystem
+// cell-sdk/src/error.rs
+//! Structured errors for the Cell ecosystem
 //!
-//! This module provides structured error handling with:
-//! - Hierarchical error classification
-//! - Automatic error propagation via `?` operator
-//! - Rich error context for debugging
-//! - Error codes for programmatic handling
+//! The catalog itself ([`CellError`]) lives in `cell-core` so every cell agrees
+//! on the numeric codes. This module adds [`ErrorContext`], the error that
+//! crosses RPC: a Membrane answers a failed request with an
+//! [`ErrorResponse`](crate::ErrorResponse) frame, and synapses and generated
+//! clients surface it as an `ErrorContext` inside `anyhow::Error`:
+//!
+//! ```ignore
+//! match client.charge(req).await {
+//!     Err(e) => match e.downcast_ref::<ErrorContext>() {
+//!         Some(ctx) if ctx.is_retryable() => retry_later(ctx.retry_delay()),
+//!         Some(ctx) if ctx.code == CellError::NotFound => create_account(),
+//!         _ => return Err(e),
+//!     },
+//!     Ok(receipt) => receipt,
+//! }
+//! ```
+//!
+//! Handlers pick the code callers see by returning a `CellError` or an
+//! `ErrorContext`; any other error is reported as `HandlerFailed`.
 
+use crate::quota::{QuotaBreach, QuotaKind};
+use crate::ErrorResponse;
 use core::fmt;
-
-#[cfg(feature = "std")]
 use std::error::Error as StdError;
 
-/// Primary error classification for Cell operations
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(u16)]
-pub enum CellError {
-    // Transport layer errors (100-199)
-    ConnectionRefused = 100,
-    ConnectionReset = 101,
-    Timeout = 102,
-    AccessDenied = 103,
-    CapabilityMissing = 104,
-    IoError = 105,
-    CircuitBreakerOpen = 106,
-    TransportUnavailable = 107,
-
-    // Protocol errors (200-299)
-    InvalidHeader = 200,
-    SerializationFailure = 201,
-    DeserializationFailure = 202,
-    Corruption = 203,
-    ProtocolMismatch = 204,
-    InvalidMessage = 205,
-
-    // Resource errors (300-399)
-    OutOfMemory = 300,
-    QuotaExceeded = 301,
-    RateLimited = 302,
-    ResourceExhausted = 303,
-
-    // State errors (400-499)
-    NotFound = 400,
-    AlreadyExists = 401,
-    InvalidState = 402,
-    DependencyFailed = 403,
-
-    // Internal errors (500-599)
-    InternalError = 500,
-    Panic = 501,
-    NotImplemented = 502,
-}
-
-impl CellError {
-    /// Get error category for classification
-    pub fn category(&self) -> ErrorCategory {
-        match *self {
-            Self::ConnectionRefused
-            | Self::ConnectionReset
-            | Self::Timeout
-            | Self::AccessDenied
-            | Self::CapabilityMissing
-            | Self::IoError
-            | Self::CircuitBreakerOpen
-            | Self::TransportUnavailable => ErrorCategory::Transport,
-
-            Self::InvalidHeader
-            | Self::SerializationFailure
-            | Self::DeserializationFailure
-            | Self::Corruption
-            | Self::ProtocolMismatch
-            | Self::InvalidMessage => ErrorCategory::Protocol,
-
-            Self::OutOfMemory
-            | Self::QuotaExceeded
-            | Self::RateLimited
-            | Self::ResourceExhausted => ErrorCategory::Resource,
-
-            Self::NotFound | Self::AlreadyExists | Self::InvalidState | Self::DependencyFailed => {
-                ErrorCategory::State
-            }
-
-            Self::InternalError | Self::Panic | Self::NotImplemented => ErrorCategory::Internal,
-        }
-    }
-
-    /// Check if error is transient (can be retried)
-    pub fn is_transient(&self) -> bool {
-        matches!(
-            self.category(),
-            ErrorCategory::Transport | ErrorCategory::Resource
-        )
-    }
-
-    /// Check if error indicates permanent failure
-    pub fn is_permanent(&self) -> bool {
-        !self.is_transient()
-    }
-
-    /// Get recommended retry delay for transient errors
-    pub fn retry_delay(&self) -> Option<core::time::Duration> {
-        if !self.is_transient() {
-            return None;
-        }
-
-        Some(core::time::Duration::from_millis(match self {
-            Self::Timeout => 100,
-            Self::ConnectionRefused => 500,
-            Self::ConnectionReset => 100,
-            Self::CircuitBreakerOpen => 5000,
-            Self::RateLimited => 1000,
-            _ => 250,
-        }))
-    }
-}
-
-impl fmt::Display for CellError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            // Transport
-            Self::ConnectionRefused => write!(f, "Connection refused"),
-            Self::ConnectionReset => write!(f, "Connection reset by peer"),
-            Self::Timeout => write!(f, "Operation timed out"),
-            Self::AccessDenied => write!(f, "Access denied"),
-            Self::CapabilityMissing => write!(f, "Required capability not available"),
-            Self::IoError => write!(f, "I/O error"),
-            Self::CircuitBreakerOpen => write!(f, "Circuit breaker is open"),
-            Self::TransportUnavailable => write!(f, "Transport layer unavailable"),
-
-            // Protocol
-            Self::InvalidHeader => write!(f, "Invalid message header"),
-            Self::SerializationFailure => write!(f, "Serialization failed"),
-            Self::DeserializationFailure => write!(f, "Deserialization failed"),
-            Self::Corruption => write!(f, "Data corruption detected"),
-            Self::ProtocolMismatch => write!(f, "Protocol version mismatch"),
-            Self::InvalidMessage => write!(f, "Invalid message format"),
-
-            // Resource
-            Self::OutOfMemory => write!(f, "Out of memory"),
-            Self::QuotaExceeded => write!(f, "Resource quota exceeded"),
-            Self::RateLimited => write!(f, "Rate limit exceeded"),
-            Self::ResourceExhausted => write!(f, "Resource exhausted"),
-
-            // State
-            Self::NotFound => write!(f, "Resource not found"),
-            Self::AlreadyExists => write!(f, "Resource already exists"),
-            Self::InvalidState => write!(f, "Invalid state for operation"),
-            Self::DependencyFailed => write!(f, "Dependency check failed"),
-
-            // Internal
-            Self::InternalError => write!(f, "Internal error"),
-            Self::Panic => write!(f, "Panic in cell"),
-            Self::NotImplemented => write!(f, "Feature not implemented"),
-        }
-    }
-}
-
-#[cfg(feature = "std")]
-impl StdError for CellError {}
-
-/// Error classification categories
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum ErrorCategory {
-    Transport,
-    Protocol,
-    Resource,
-    State,
-    Internal,
-}
-
-impl fmt::Display for ErrorCategory {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Transport => write!(f, "transport"),
-            Self::Protocol => write!(f, "protocol"),
-            Self::Resource => write!(f, "resource"),
-            Self::State => write!(f, "state"),
-            Self::Internal => write!(f, "internal"),
-        }
-    }
-}
+pub use cell_core::error::{CellError, ErrorCategory};
 
 /// Rich error context for debugging and observability
-#[cfg(feature = "std")]
 #[derive(Debug, Clone)]
 pub struct ErrorContext {
     pub code: CellError,
     pub message: String,
     pub source: Option<String>,
+    /// Cell the error originated in
     pub cell: Option<String>,
     pub operation: Option<String>,
+    /// Overrides the code's default retry delay
+    pub retry_after: Option<std::time::Duration>,
 }
 
-#[cfg(feature = "std")]
 impl ErrorContext {
     pub fn new(code: CellError) -> Self {
         Self {
@@ -202,6 +50,7 @@ impl ErrorContext {
             source: None,
             cell: None,
             operation: None,
+            retry_after: None,
         }
     }
 
@@ -224,9 +73,96 @@ impl ErrorContext {
         self.operation = Some(op.into());
         self
     }
+
+    pub fn with_retry_after(mut self, delay: std::time::Duration) -> Self {
+        self.retry_after = Some(delay);
+        self
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        self.code.is_retryable()
+    }
+
+    pub fn retry_delay(&self) -> Option<std::time::Duration> {
+        let default = self.code.retry_delay()?;
+        Some(self.retry_after.unwrap_or(default))
+    }
+
+    /// Classify an arbitrary error. An `ErrorContext` or `CellError` anywhere
+    /// in the chain keeps its code; anything else gets `fallback`.
+    pub fn classify(err: &anyhow::Error, fallback: CellError) -> Self {
+        if let Some(ctx) = err.downcast_ref::<ErrorContext>() {
+            return ctx.clone();
+        }
+        if let Some(code) = err.downcast_ref::<CellError>() {
+            return Self::new(*code);
+        }
+        if let Some(io) = err.downcast_ref::<std::io::Error>() {
+            return Self::from(io);
+        }
+        Self::new(fallback).with_message(err.to_string())
+    }
+
+    /// Wire form, sent by the Membrane. `cell` is used unless the error
+    /// already names the cell it came from.
+    pub fn to_response(&self, cell: &str) -> ErrorResponse {
+        ErrorResponse {
+            code: self.code.code() as u32,
+            message: self.message.clone(),
+            cell: self.cell.clone().unwrap_or_else(|| cell.to_string()),
+            retry_after_ms: self
+                .retry_after
+                .map(|d| d.as_millis().min(u32::MAX as u128) as u32)
+                .unwrap_or(0),
+        }
+    }
+
+    pub fn from_response(resp: ErrorResponse) -> Self {
+        let code = u16::try_from(resp.code)
+            .ok()
+            .and_then(CellError::from_code)
+            .unwrap_or(CellError::InternalError);
+        let ctx = Self::new(code).with_message(resp.message).with_cell(resp.cell);
+        match resp.retry_after_ms {
+            0 => ctx,
+            ms => ctx.with_retry_after(std::time::Duration::from_millis(ms as u64)),
+        }
+    }
+}
+
+impl From<CellError> for ErrorContext {
+    fn from(code: CellError) -> Self {
+        Self::new(code)
+    }
+}
+
+impl From<&std::io::Error> for ErrorContext {
+    fn from(e: &std::io::Error) -> Self {
+        use std::io::ErrorKind;
+        let code = match e.kind() {
+            ErrorKind::ConnectionRefused | ErrorKind::NotFound => CellError::ConnectionRefused,
+            ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::BrokenPipe
+            | ErrorKind::UnexpectedEof => CellError::ConnectionReset,
+            ErrorKind::TimedOut => CellError::Timeout,
+            ErrorKind::PermissionDenied => CellError::AccessDenied,
+            _ => CellError::IoError,
+        };
+        Self::new(code).with_message(e.to_string())
+    }
+}
+
+impl From<&QuotaBreach> for ErrorContext {
+    fn from(breach: &QuotaBreach) -> Self {
+        let code = match breach.kind {
+            QuotaKind::Requests => CellError::RateLimited,
+            _ => CellError::QuotaExceeded,
+        };
+        Self::new(code).with_message(breach.to_string())
+    }
 }
 
-#[cfg(feature = "std")]
 impl fmt::Display for ErrorContext {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "[{}] {}", self.code as u16, self.message)?;
@@ -245,8 +181,32 @@ impl fmt::Display for ErrorContext {
     }
 }
 
-#[cfg(feature = "std")]
 impl StdError for ErrorContext {}
 
+/// Decode the payload of an error frame into the error the caller sees.
+pub(crate) fn from_frame(payload: &[u8]) -> anyhow::Error {
+    rkyv::check_archived_root::<ErrorResponse>(payload)
+        .ok()
+        .and_then(|archived| {
+            let resp: Result<ErrorResponse, _> =
+                rkyv::Deserialize::deserialize(archived, &mut rkyv::Infallible);
+            resp.ok()
+        })
+        // Connections that negotiated another codec get errors in it
+        .or_else(|| serde_json::from_slice(payload).ok())
+        .or_else(|| rmp_serde::from_slice(payload).ok())
+        .map(ErrorContext::from_response)
+        .unwrap_or_else(|| {
+            ErrorContext::new(CellError::InvalidMessage).with_message("Malformed error response")
+        })
+        .into()
+}
+
+/// Whether the error was reported by the remote cell, rather than the
+/// connection to it failing.
+pub fn is_remote(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<ErrorContext>().is_some_and(|ctx| ctx.cell.is_some())
+}
+
 /// Result type alias for Cell operations
 pub type CellResult<T> = Result<T, CellError>;
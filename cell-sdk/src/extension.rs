@@ -0,0 +1,399 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/extension.rs
+//! Methods registered while the cell runs.
+//!
+//! A `#[handler]` protocol is compiled into the cell and its clients, so a
+//! plugin loaded later cannot add variants to it. Its methods are registered
+//! here instead, by name, and called on `channel::EXTENSION` with their
+//! arguments as bytes in whatever encoding the plugin reads:
+//!
+//! ```ignore
+//! extension::register(
+//!     ExtensionMethod {
+//!         name: "thumbnail".into(),
+//!         plugin: "images".into(),
+//!         schema: THUMBNAIL_SCHEMA.into(),
+//!     },
+//!     extension::handler(|args| async move { render(&args) }),
+//! )?;
+//!
+//! // In another cell, without restarting either
+//! let methods = extension::list("media").await?;
+//! let thumb: Thumbnail = extension::call_json("media", "thumbnail", &req).await?;
+//! ```
+//!
+//! The cell's schema fingerprint stays that of its compiled protocol, so
+//! clients built against it keep connecting. What was added is listed over
+//! OPS `Extensions` with each method's schema and a generation that changes
+//! with every registration; clients compare generations to tell whether the
+//! list they hold is stale. Every registration is also published to the
+//! registry as `<cell>/extensions.json` beside the cell's schema (under
+//! `CELL_REGISTRY_DIR`, default `~/.cell/registry`), where [`published`]
+//! finds it while the cell is down.
+//!
+//! Calls pass the same admission checks as handler calls: dependency
+//! admission, priority load shedding at [`crate::shed::DEFAULT_PRIORITY`] (or the
+//! method's `ShedConfig` override) and the priority scheduler.
+//!
+//! With the `plugins` feature, [`load`] opens a shared library and registers
+//! the methods it exports (see [`load`] for the ABI); admins do it on a running
+//! cell with `OpsRequest::LoadPlugin`. A WASM host registers the exports of
+//! its guests with [`register`] the same way.
+
+use crate::error::{CellError, ErrorContext};
+use crate::membrane::BoxFuture;
+use crate::state::ops;
+use crate::synapse::Synapse;
+use anyhow::{anyhow, bail, Result};
+use cell_core::channel;
+pub use cell_model::extension::{ExtensionCall, ExtensionList, ExtensionMethod};
+use cell_model::ops::{OpsRequest, OpsResponse};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tracing::{info, warn};
+
+/// Runs one call: arguments in, result out
+pub type ExtensionHandler =
+    Arc<dyn Fn(Vec<u8>) -> BoxFuture<'static, Result<Vec<u8>>> + Send + Sync>;
+
+struct Registered {
+    method: ExtensionMethod,
+    handler: ExtensionHandler,
+}
+
+struct Registry {
+    generation: u64,
+    methods: BTreeMap<String, Registered>,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    generation: 0,
+    methods: BTreeMap::new(),
+});
+
+/// Wrap an async closure as an [`ExtensionHandler`]
+pub fn handler<F, Fut>(f: F) -> ExtensionHandler
+where
+    F: Fn(Vec<u8>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Vec<u8>>> + Send + 'static,
+{
+    Arc::new(move |args| Box::pin(f(args)))
+}
+
+/// Serve `method` from now on. A plugin may register a name again to replace
+/// its handler; a name another plugin registered is refused.
+pub fn register(method: ExtensionMethod, handler: ExtensionHandler) -> Result<()> {
+    let mut registry = REGISTRY.lock().unwrap();
+    check_owner(&registry, &method.plugin, &method.name)?;
+    info!(
+        "Registered extension method '{}' of plugin '{}'",
+        method.name, method.plugin
+    );
+    registry
+        .methods
+        .insert(method.name.clone(), Registered { method, handler });
+    registry.generation += 1;
+    publish(&registry);
+    Ok(())
+}
+
+fn check_owner(registry: &Registry, plugin: &str, name: &str) -> Result<()> {
+    match registry.methods.get(name) {
+        Some(existing) if existing.method.plugin != plugin => bail!(
+            "Extension method '{}' is already registered by plugin '{}'",
+            name,
+            existing.method.plugin
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Stop serving `name`. Calls already running finish.
+pub fn unregister(name: &str) -> Option<ExtensionMethod> {
+    let mut registry = REGISTRY.lock().unwrap();
+    let removed = registry.methods.remove(name)?;
+    registry.generation += 1;
+    publish(&registry);
+    Some(removed.method)
+}
+
+/// What this cell serves now
+pub fn methods() -> ExtensionList {
+    listed(&REGISTRY.lock().unwrap())
+}
+
+fn listed(registry: &Registry) -> ExtensionList {
+    ExtensionList {
+        generation: registry.generation,
+        methods: registry
+            .methods
+            .values()
+            .map(|r| r.method.clone())
+            .collect(),
+    }
+}
+
+fn registry_path(cell_name: &str) -> Option<PathBuf> {
+    let root = match std::env::var("CELL_REGISTRY_DIR") {
+        Ok(dir) => PathBuf::from(dir),
+        Err(_) => dirs::home_dir()?.join(".cell/registry"),
+    };
+    Some(root.join(cell_name).join("extensions.json"))
+}
+
+/// Write the list to the registry. Called with the lock held, so a later
+/// generation is never overwritten by an earlier one.
+fn publish(registry: &Registry) {
+    let cell = crate::status::cell_name();
+    let Some(path) = registry_path(&cell) else {
+        warn!(
+            "No registry directory; extensions of '{}' not published",
+            cell
+        );
+        return;
+    };
+    let write = || -> Result<()> {
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&listed(registry))?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    };
+    if let Err(e) = write() {
+        warn!(
+            "Failed to publish extensions of '{}' to {}: {}",
+            cell,
+            path.display(),
+            e
+        );
+    }
+}
+
+/// Methods `cell_name` last published to the registry, whether or not it
+/// is running.
+pub fn published(cell_name: &str) -> Result<Option<ExtensionList>> {
+    let Some(path) = registry_path(cell_name) else {
+        return Ok(None);
+    };
+    match std::fs::read(&path) {
+        Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Run a call that arrived on `channel::EXTENSION`
+pub(crate) async fn dispatch(call: ExtensionCall) -> Result<Vec<u8>> {
+    let handler = REGISTRY
+        .lock()
+        .unwrap()
+        .methods
+        .get(&call.method)
+        .map(|r| r.handler.clone())
+        .ok_or_else(|| {
+            ErrorContext::new(CellError::NotFound)
+                .with_message(format!("No extension method '{}'", call.method))
+                .with_operation(call.method.as_str())
+        })?;
+    crate::admission::check()?;
+    let in_flight = crate::shed::admit(&call.method, crate::shed::DEFAULT_PRIORITY)?;
+    let _turn = crate::priority::turn(in_flight.priority()).await;
+    let started = Instant::now();
+    let result = crate::priority::scope(in_flight.priority(), handler(call.args)).await;
+    crate::metrics::record(&call.method, started.elapsed(), result.is_ok());
+    result
+}
+
+/// Ask a running cell which methods it has registered.
+pub async fn list(cell_name: &str) -> Result<ExtensionList> {
+    match ops(cell_name, &OpsRequest::Extensions).await? {
+        OpsResponse::Extensions(list) => Ok(list),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
+
+/// Call an extension method of a running cell with encoded arguments.
+pub async fn call(cell_name: &str, method: &str, args: Vec<u8>) -> Result<Vec<u8>> {
+    let synapse = Synapse::grow(cell_name).await?;
+    let req = ExtensionCall {
+        method: method.to_string(),
+        args,
+    };
+    let bytes = rkyv::to_bytes::<_, 1024>(&req)?.into_vec();
+    Ok(synapse
+        .fire_on_channel(channel::EXTENSION, &bytes)
+        .await?
+        .into_owned())
+}
+
+/// [`call`] for methods that take and return JSON.
+pub async fn call_json<A, R>(cell_name: &str, method: &str, args: &A) -> Result<R>
+where
+    A: serde::Serialize,
+    R: serde::de::DeserializeOwned,
+{
+    let resp = call(cell_name, method, serde_json::to_vec(args)?).await?;
+    serde_json::from_slice(&resp).map_err(|e| {
+        anyhow::Error::from(
+            ErrorContext::new(CellError::DeserializationFailure)
+                .with_message(format!("Invalid result of '{}': {}", method, e)),
+        )
+    })
+}
+
+/// Have a running cell load the plugin at `path` on its host. Returns the
+/// plugin's name and the methods it registered.
+pub async fn load_remote(cell_name: &str, path: &str) -> Result<(String, Vec<String>)> {
+    let req = OpsRequest::LoadPlugin {
+        path: path.to_string(),
+    };
+    match ops(cell_name, &req).await? {
+        OpsResponse::PluginLoaded { plugin, methods } => Ok((plugin, methods)),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
+
+/// `cell_plugin_manifest()` of a plugin library
+#[cfg(feature = "plugins")]
+#[derive(serde::Deserialize)]
+struct PluginManifest {
+    plugin: String,
+    methods: Vec<PluginMethod>,
+}
+
+#[cfg(feature = "plugins")]
+#[derive(serde::Deserialize)]
+struct PluginMethod {
+    name: String,
+    #[serde(default)]
+    schema: String,
+}
+
+#[cfg(feature = "plugins")]
+type PluginCall = unsafe extern "C" fn(
+    method: *const u8,
+    method_len: usize,
+    args: *const u8,
+    args_len: usize,
+    out: *mut *mut u8,
+    out_len: *mut usize,
+) -> i32;
+
+#[cfg(feature = "plugins")]
+type PluginFree = unsafe extern "C" fn(ptr: *mut u8, len: usize);
+
+/// Open the shared library at `path` and register its methods. Returns the
+/// plugin's name and the methods it registered.
+///
+/// The library exports, all `extern "C"`:
+///
+/// - `cell_plugin_manifest() -> *const c_char`: NUL-terminated JSON,
+///   `{"plugin": "images", "methods": [{"name": "thumbnail", "schema": "..."}]}`
+/// - `cell_plugin_call(method, method_len, args, args_len, out, out_len) -> i32`:
+///   0 with the result in `*out`, anything else with an error message there
+/// - `cell_plugin_free(ptr, len)`: release a buffer `cell_plugin_call` returned
+///
+/// Calls run on the blocking pool. Libraries stay loaded until the process
+/// exits, since calls into them may still be running; loading a newer build
+/// of a plugin replaces its handlers.
+#[cfg(feature = "plugins")]
+pub fn load(path: &std::path::Path) -> Result<(String, Vec<String>)> {
+    use anyhow::Context;
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+
+    // SAFETY: runs the library's initializers; only admins pick what is loaded
+    let library = unsafe { libloading::Library::new(path) }
+        .with_context(|| format!("Failed to load plugin {}", path.display()))?;
+    let library: &'static libloading::Library = Box::leak(Box::new(library));
+
+    // SAFETY: the symbols have the signatures documented above
+    let (manifest, call, free) = unsafe {
+        let manifest =
+            library.get::<unsafe extern "C" fn() -> *const c_char>(b"cell_plugin_manifest\0")?;
+        let json = CStr::from_ptr(manifest()).to_str()?;
+        let manifest: PluginManifest = serde_json::from_str(json)
+            .with_context(|| format!("Invalid manifest in plugin {}", path.display()))?;
+        let call = *library.get::<PluginCall>(b"cell_plugin_call\0")?;
+        let free = *library.get::<PluginFree>(b"cell_plugin_free\0")?;
+        (manifest, call, free)
+    };
+
+    // All or nothing: check every name before registering any
+    {
+        let registry = REGISTRY.lock().unwrap();
+        for method in &manifest.methods {
+            check_owner(&registry, &manifest.plugin, &method.name)?;
+        }
+    }
+
+    let mut names = Vec::new();
+    for method in manifest.methods {
+        let name = method.name.clone();
+        let handler = handler(move |args| {
+            let name = name.clone();
+            async move {
+                // SAFETY: `call` and `free` come from a library that is never unloaded
+                tokio::task::spawn_blocking(move || unsafe { invoke(call, free, &name, &args) })
+                    .await?
+            }
+        });
+        names.push(method.name.clone());
+        register(
+            ExtensionMethod {
+                name: method.name,
+                plugin: manifest.plugin.clone(),
+                schema: method.schema,
+            },
+            handler,
+        )?;
+    }
+    info!(
+        "Loaded plugin '{}' from {} ({} methods)",
+        manifest.plugin,
+        path.display(),
+        names.len()
+    );
+    Ok((manifest.plugin, names))
+}
+
+#[cfg(not(feature = "plugins"))]
+pub fn load(path: &std::path::Path) -> Result<(String, Vec<String>)> {
+    bail!(
+        "Loading plugin {} needs cell-sdk's plugins feature",
+        path.display()
+    )
+}
+
+#[cfg(feature = "plugins")]
+unsafe fn invoke(call: PluginCall, free: PluginFree, method: &str, args: &[u8]) -> Result<Vec<u8>> {
+    let mut out = std::ptr::null_mut();
+    let mut out_len = 0;
+    let status = call(
+        method.as_ptr(),
+        method.len(),
+        args.as_ptr(),
+        args.len(),
+        &mut out,
+        &mut out_len,
+    );
+    let bytes = if out.is_null() {
+        Vec::new()
+    } else {
+        let bytes = std::slice::from_raw_parts(out, out_len).to_vec();
+        free(out, out_len);
+        bytes
+    };
+    if status != 0 {
+        return Err(ErrorContext::new(CellError::HandlerFailed)
+            .with_message(String::from_utf8_lossy(&bytes).into_owned())
+            .with_operation(method)
+            .into());
+    }
+    Ok(bytes)
+}
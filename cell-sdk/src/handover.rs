@@ -0,0 +1,196 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/handover.rs
+//! Listener handover between two instances of a cell.
+//!
+//! Every Membrane serving a Unix socket offers its listener on a gap junction
+//! socket, `~/.cell/io/<cell>.handover`. A successor told to [`adopt`] it
+//! (OPS `Adopt`, sent by [`hand_over`]) receives the listening socket over
+//! SCM_RIGHTS and starts accepting on it; only then does the old instance
+//! stop accepting. Connections queued in the backlog are picked up by
+//! whichever instance accepts next, so none are dropped, and the socket path
+//! never changes. The old instance then finishes its in-flight requests
+//! (waiting at most `CELL_HANDOVER_DRAIN_SECS`, default 30) and exits.
+//!
+//! The adopting instance offers the listener under the old name in turn, so
+//! the next swap finds it the same way.
+
+use crate::state::ops;
+use anyhow::{anyhow, bail, Context, Result};
+use cell_model::ops::{OpsRequest, OpsResponse};
+use nix::sys::socket::{recvmsg, sendmsg, ControlMessage, ControlMessageOwned, MsgFlags};
+use std::io::{IoSlice, IoSliceMut, Read, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::net::UnixStream as StdUnixStream;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, Notify};
+use tracing::{info, warn};
+
+/// An adopted listener, and the name it was served under
+pub(crate) type Adopted = (String, UnixListener);
+
+static ADOPTIONS: OnceLock<mpsc::UnboundedSender<Adopted>> = OnceLock::new();
+
+pub fn socket_path(cell_name: &str) -> Result<PathBuf> {
+    let home = dirs::home_dir().context("No HOME dir")?;
+    Ok(home
+        .join(".cell/io")
+        .join(format!("{}.handover", cell_name)))
+}
+
+/// Listeners this process adopts, for the Membrane to serve.
+pub(crate) fn adoptions() -> mpsc::UnboundedReceiver<Adopted> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let _ = ADOPTIONS.set(tx);
+    rx
+}
+
+/// Offer the listener `fd` to the next instance of `cell_name`. The returned
+/// notify fires once a successor accepts on it; stop accepting then.
+pub(crate) fn offer(cell_name: &str, fd: RawFd) -> Result<Arc<Notify>> {
+    let path = socket_path(cell_name)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let _ = std::fs::remove_file(&path);
+    let junction = UnixListener::bind(&path)
+        .with_context(|| format!("Failed to bind handover socket {:?}", path))?;
+
+    let released = Arc::new(Notify::new());
+    let notify = released.clone();
+    let cell_name = cell_name.to_string();
+    tokio::spawn(async move {
+        loop {
+            let Ok((stream, _)) = junction.accept().await else {
+                continue;
+            };
+            // The listener is only handed to processes of the same user
+            let uid = nix::unistd::getuid().as_raw();
+            if stream.peer_cred().map(|c| c.uid()).ok() != Some(uid) {
+                warn!(
+                    "[Handover] Refused {} to a process of another user",
+                    cell_name
+                );
+                continue;
+            }
+            // Keep accepting until the successor does
+            let exchange = async {
+                let stream = stream.into_std()?;
+                stream.set_nonblocking(false)?;
+                tokio::task::spawn_blocking(move || {
+                    send_listener(&stream, fd)?;
+                    let mut ack = [0u8; 1];
+                    (&stream).read_exact(&mut ack)?;
+                    Ok::<_, anyhow::Error>(())
+                })
+                .await?
+            };
+            match exchange.await {
+                Ok(()) => {
+                    info!("[Handover] {} listener adopted by successor", cell_name);
+                    notify.notify_one();
+                    return;
+                }
+                Err(e) => warn!("[Handover] Handing over {} failed: {}", cell_name, e),
+            }
+        }
+    });
+    Ok(released)
+}
+
+/// Take over the listener of the running instance `from`.
+pub async fn adopt(from: &str) -> Result<()> {
+    let sink = ADOPTIONS
+        .get()
+        .context("This cell serves no Unix listener to adopt into")?;
+    let path = socket_path(from)?;
+    let stream = UnixStream::connect(&path)
+        .await
+        .with_context(|| format!("{} offers no listener at {:?}", from, path))?
+        .into_std()?;
+    stream.set_nonblocking(false)?;
+    let (stream, fd) = tokio::task::spawn_blocking(move || {
+        let fd = recv_listener(&stream)?;
+        Ok::<_, anyhow::Error>((stream, fd))
+    })
+    .await??;
+
+    let listener = unsafe { std::os::unix::net::UnixListener::from_raw_fd(fd) };
+    listener.set_nonblocking(true)?;
+    sink.send((from.to_string(), UnixListener::from_std(listener)?))
+        .map_err(|_| anyhow!("Membrane stopped serving"))?;
+    // Accepting now: the old instance may stop
+    (&stream).write_all(&[1])?;
+    info!("[Handover] Adopted listener of {}", from);
+    Ok(())
+}
+
+fn send_listener(stream: &StdUnixStream, fd: RawFd) -> Result<()> {
+    let iov = [IoSlice::new(&[0u8])];
+    let cmsg = [ControlMessage::ScmRights(&[fd])];
+    sendmsg::<()>(stream.as_raw_fd(), &iov, &cmsg, MsgFlags::empty(), None)
+        .context("Failed to send listener")?;
+    Ok(())
+}
+
+fn recv_listener(stream: &StdUnixStream) -> Result<RawFd> {
+    let mut buf = [0u8; 1];
+    let mut iov = [IoSliceMut::new(&mut buf)];
+    let mut cmsg_buf = nix::cmsg_space!([RawFd; 1]);
+    let msg = recvmsg::<()>(
+        stream.as_raw_fd(),
+        &mut iov,
+        Some(&mut cmsg_buf),
+        MsgFlags::empty(),
+    )
+    .context("Failed to receive listener")?;
+    for cmsg in msg.cmsgs() {
+        if let ControlMessageOwned::ScmRights(fds) = cmsg {
+            if let Some(fd) = fds.first() {
+                return Ok(*fd);
+            }
+        }
+    }
+    bail!("Handover carried no listener")
+}
+
+/// Move the listener of running cell `from` to the running cell `to`.
+pub async fn hand_over(from: &str, to: &str) -> Result<()> {
+    match ops(
+        to,
+        &OpsRequest::Adopt {
+            from: from.to_string(),
+        },
+    )
+    .await?
+    {
+        OpsResponse::Adopted => Ok(()),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", to, message)),
+        _ => bail!("Unexpected OPS response from {}", to),
+    }
+}
+
+/// Wait for in-flight requests to finish after the listener was handed over.
+pub(crate) async fn retire() {
+    let limit = std::env::var("CELL_HANDOVER_DRAIN_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(30);
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(limit);
+    loop {
+        let pending = crate::inspect::pending_requests();
+        if pending == 0 {
+            return;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            warn!(
+                "[Handover] Exiting with {} requests still in flight",
+                pending
+            );
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
@@ -0,0 +1,230 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/inspect.rs
+//! Live view of a cell's connections, runtime and memory (OPS `Inspect`).
+//!
+//! The Membrane registers every connection it serves and meters its stream, so
+//! `cell inspect` can show which peers are attached and which requests are
+//! stuck in a handler.
+
+use crate::state::ops;
+use anyhow::{anyhow, bail, Result};
+use cell_model::ops::{
+    ConnectionInfo, InspectReport, MemoryStats, OpsRequest, OpsResponse, RingInfo, TaskStats,
+};
+use std::collections::BTreeMap;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static CONNECTIONS: Mutex<BTreeMap<u64, Arc<Connection>>> = Mutex::new(BTreeMap::new());
+
+/// One open connection, registered until the handle is dropped.
+pub(crate) struct Connection {
+    id: u64,
+    peer: String,
+    opened: Instant,
+    received: AtomicU64,
+    sent: AtomicU64,
+    requests: AtomicU64,
+    pending: AtomicU32,
+}
+
+pub(crate) struct ConnectionHandle(Arc<Connection>);
+
+impl Drop for ConnectionHandle {
+    fn drop(&mut self) {
+        CONNECTIONS.lock().unwrap().remove(&self.0.id);
+    }
+}
+
+impl ConnectionHandle {
+    pub(crate) fn open(peer: &str) -> Self {
+        let conn = Arc::new(Connection {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            peer: peer.to_string(),
+            opened: Instant::now(),
+            received: AtomicU64::new(0),
+            sent: AtomicU64::new(0),
+            requests: AtomicU64::new(0),
+            pending: AtomicU32::new(0),
+        });
+        CONNECTIONS.lock().unwrap().insert(conn.id, conn.clone());
+        Self(conn)
+    }
+
+    /// Wrap the connection's stream so its traffic is counted.
+    pub(crate) fn meter<S>(&self, inner: S) -> Metered<S> {
+        Metered {
+            inner,
+            conn: self.0.clone(),
+        }
+    }
+
+    /// Counts requests of this connection from a task of their own.
+    pub(crate) fn tracker(&self) -> RequestTracker {
+        RequestTracker(self.0.clone())
+    }
+}
+
+pub(crate) struct RequestTracker(Arc<Connection>);
+
+impl RequestTracker {
+    /// Mark a request as in flight until the guard is dropped.
+    pub(crate) fn request(&self) -> Pending {
+        self.0.requests.fetch_add(1, Ordering::Relaxed);
+        self.0.pending.fetch_add(1, Ordering::Relaxed);
+        Pending(self.0.clone())
+    }
+}
+
+pub(crate) struct Pending(Arc<Connection>);
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        self.0.pending.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// A stream that adds its traffic to a registered connection.
+pub(crate) struct Metered<S> {
+    inner: S,
+    conn: Arc<Connection>,
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for Metered<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let before = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        let read = buf.filled().len() - before;
+        self.conn.received.fetch_add(read as u64, Ordering::Relaxed);
+        poll
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for Metered<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = &poll {
+            self.conn.sent.fetch_add(*n as u64, Ordering::Relaxed);
+        }
+        poll
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+pub(crate) fn connection_count() -> usize {
+    CONNECTIONS.lock().unwrap().len()
+}
+
+/// Requests being handled on any connection right now.
+pub(crate) fn pending_requests() -> u64 {
+    CONNECTIONS
+        .lock()
+        .unwrap()
+        .values()
+        .map(|c| c.pending.load(Ordering::Relaxed) as u64)
+        .sum()
+}
+
+/// Snapshot of this process.
+pub fn report() -> InspectReport {
+    let connections = CONNECTIONS
+        .lock()
+        .unwrap()
+        .values()
+        .map(|c| ConnectionInfo {
+            id: c.id,
+            peer: c.peer.clone(),
+            age_secs: c.opened.elapsed().as_secs(),
+            bytes_received: c.received.load(Ordering::Relaxed),
+            bytes_sent: c.sent.load(Ordering::Relaxed),
+            requests: c.requests.load(Ordering::Relaxed),
+            pending: c.pending.load(Ordering::Relaxed),
+        })
+        .collect();
+
+    let tasks = match tokio::runtime::Handle::try_current() {
+        Ok(handle) => {
+            let metrics = handle.metrics();
+            TaskStats {
+                workers: metrics.num_workers() as u32,
+                alive_tasks: metrics.num_alive_tasks() as u64,
+                queued_tasks: metrics.global_queue_depth() as u64,
+            }
+        }
+        Err(_) => TaskStats::default(),
+    };
+
+    let rings = crate::shm::live_rings()
+        .iter()
+        .map(|r| RingInfo {
+            capacity: r.capacity() as u64,
+            used: r.used() as u64,
+        })
+        .collect();
+
+    InspectReport {
+        connections,
+        tasks,
+        rings,
+        memory: memory(),
+    }
+}
+
+/// Resident and virtual size from `/proc/self/statm`; zero where unavailable.
+fn memory() -> MemoryStats {
+    // statm counts 4 KiB pages on the platforms cells ship for
+    let page = 4096;
+    let statm = std::fs::read_to_string("/proc/self/statm").unwrap_or_default();
+    let mut fields = statm
+        .split_whitespace()
+        .map(|f| f.parse::<u64>().unwrap_or(0));
+    let virtual_pages = fields.next().unwrap_or(0);
+    let resident_pages = fields.next().unwrap_or(0);
+    MemoryStats {
+        resident_bytes: resident_pages * page,
+        virtual_bytes: virtual_pages * page,
+        allocated_bytes: allocated(),
+    }
+}
+
+#[cfg(feature = "heap-profile")]
+fn allocated() -> Option<u64> {
+    tikv_jemalloc_ctl::epoch::advance().ok()?;
+    tikv_jemalloc_ctl::stats::allocated::read()
+        .ok()
+        .map(|b| b as u64)
+}
+
+#[cfg(not(feature = "heap-profile"))]
+fn allocated() -> Option<u64> {
+    None
+}
+
+/// Ask a running cell for its [`InspectReport`].
+pub async fn fetch(cell_name: &str) -> Result<InspectReport> {
+    match ops(cell_name, &OpsRequest::Inspect).await? {
+        OpsResponse::Inspect(report) => Ok(report),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
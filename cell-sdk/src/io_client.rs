@@ -16,10 +16,19 @@ use tracing::warn;
 
 pub struct IoClient;
 
+/// Set when serving a listener passed by a socket-activating hypervisor
+static ACTIVATED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
 impl IoClient {
     /// Connects to the IO cell and requests a bound listener FD.
     /// FALLBACK: If IO cell is down, binds locally.
     pub async fn bind_membrane(cell_name: &str) -> Result<std::os::unix::net::UnixListener> {
+        // 0. Socket activated: the hypervisor bound our socket and passed it on
+        if let Some(listener) = Self::inherited()? {
+            info!("[IO] Serving listener inherited from the hypervisor");
+            return Ok(listener);
+        }
+
         // 1. Try Router
         if let Ok(mut stream) = Self::connect_to_io().await {
             let req = IoRequest::Bind {
@@ -91,6 +100,37 @@ impl IoClient {
         Ok(listener)
     }
 
+    /// The listener passed in `CELL_LISTEN_FD` by a socket-activating
+    /// hypervisor, if any.
+    fn inherited() -> Result<Option<std::os::unix::net::UnixListener>> {
+        let Ok(fd) = std::env::var("CELL_LISTEN_FD") else {
+            return Ok(None);
+        };
+        // Children of this cell must not take it over too
+        std::env::remove_var("CELL_LISTEN_FD");
+        let fd: RawFd = fd.parse().context("CELL_LISTEN_FD is not a descriptor")?;
+        nix::fcntl::fcntl(
+            fd,
+            nix::fcntl::FcntlArg::F_SETFD(nix::fcntl::FdFlag::FD_CLOEXEC),
+        )
+        .context("CELL_LISTEN_FD is not open")?;
+        ACTIVATED.store(true, std::sync::atomic::Ordering::Relaxed);
+        Ok(Some(unsafe {
+            std::os::unix::net::UnixListener::from_raw_fd(fd)
+        }))
+    }
+
+    /// How long a socket-activated cell may sit without connections before it
+    /// exits (`CELL_IDLE_EXIT_SECS`); the hypervisor starts it again on the
+    /// next one. `None` when not socket activated or unset.
+    pub fn idle_exit() -> Option<std::time::Duration> {
+        if !ACTIVATED.load(std::sync::atomic::Ordering::Relaxed) {
+            return None;
+        }
+        let secs = std::env::var("CELL_IDLE_EXIT_SECS").ok()?.parse().ok()?;
+        Some(std::time::Duration::from_secs(secs))
+    }
+
     /// Connects to the IO cell and requests a connection to a target.
     /// FALLBACK: If IO cell is down, connects directly to target socket.
     ///
@@ -0,0 +1,314 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/journal.rs
+//! Durable event journals.
+//!
+//! A cell that keeps its state in memory can make it survive restarts by
+//! journaling the events that change it: handlers [`Journal::append`] each
+//! event before acknowledging it, and on start the cell folds
+//! [`Journal::replay`] back into its state.
+//!
+//! ```ignore
+//! let journal = Journal::<LedgerEvent>::open("ledger")?;
+//! let mut accounts = Accounts::default();
+//! journal.replay(|_, event| accounts.apply(event))?;
+//!
+//! // In a handler
+//! journal.append(&LedgerEvent::Deposit { user, amount }).await?;
+//! ```
+//!
+//! Each cell's journal is one append-only file,
+//! `~/.cell/journals/<cell>.journal`. An append returns once its event is on disk, so an acknowledged event is
+//! never lost; a record torn by a crash is cut off the end when the journal
+//! is next opened. Events are MessagePack with field names, so adding an
+//! optional field to an event type keeps old journals readable.
+//!
+//! Journals only grow until the cell [`Journal::compact`]s them, rewriting
+//! the file as the few events that rebuild its current state (say, one
+//! balance per account). Compaction replaces the file atomically and never
+//! reuses sequence numbers.
+
+use anyhow::{bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+const MAGIC: &[u8; 8] = b"CELLJNL\x01";
+/// Magic, then the sequence number of the first record
+const HEADER_LEN: u64 = 16;
+/// Length and checksum before each record's payload
+const RECORD_HEADER_LEN: usize = 8;
+
+pub fn path(cell_name: &str) -> Result<PathBuf> {
+    let home = dirs::home_dir().context("No HOME dir")?;
+    Ok(home
+        .join(".cell/journals")
+        .join(format!("{}.journal", cell_name)))
+}
+
+/// An append-only log of `E`s. Clones share the file.
+pub struct Journal<E> {
+    shared: Arc<Shared>,
+    _event: PhantomData<fn(E) -> E>,
+}
+
+impl<E> Clone for Journal<E> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+            _event: PhantomData,
+        }
+    }
+}
+
+struct Shared {
+    path: PathBuf,
+    file: Mutex<Tail>,
+}
+
+/// The open file, positioned at its end
+struct Tail {
+    file: File,
+    /// Sequence number of the first record in the file
+    first: u64,
+    /// Sequence number the next append gets
+    next: u64,
+    len: u64,
+}
+
+impl<E: Serialize + DeserializeOwned> Journal<E> {
+    /// Open `cell_name`'s journal, creating it if there is none
+    pub fn open(cell_name: &str) -> Result<Self> {
+        Self::open_at(&path(cell_name)?)
+    }
+
+    pub fn open_at(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .with_context(|| format!("Failed to open journal {:?}", path))?;
+
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)?;
+        // Shorter than a header only if creating it was cut short
+        let tail = if content.len() < HEADER_LEN as usize {
+            file.set_len(0)?;
+            file.seek(SeekFrom::Start(0))?;
+            file.write_all(&header(0))?;
+            file.sync_all()?;
+            Tail {
+                file,
+                first: 0,
+                next: 0,
+                len: HEADER_LEN,
+            }
+        } else {
+            let first = read_header(&content).with_context(|| format!("{:?}", path))?;
+            let mut records = Records::new(&content);
+            let count = records.by_ref().count() as u64;
+            let len = records.offset as u64;
+            if len < content.len() as u64 {
+                warn!(
+                    "Journal {:?}: dropping {} bytes of a torn record at the end",
+                    path,
+                    content.len() as u64 - len
+                );
+                file.set_len(len)?;
+                file.sync_all()?;
+            }
+            file.seek(SeekFrom::Start(len))?;
+            Tail {
+                file,
+                first,
+                next: first + count,
+                len,
+            }
+        };
+
+        Ok(Self {
+            shared: Arc::new(Shared {
+                path: path.to_path_buf(),
+                file: Mutex::new(tail),
+            }),
+            _event: PhantomData,
+        })
+    }
+
+    /// Write `event` to disk. Returns its sequence number.
+    pub async fn append(&self, event: &E) -> Result<u64> {
+        let record = record(event)?;
+        let shared = self.shared.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut tail = shared.file.lock().unwrap();
+            let written = tail
+                .file
+                .write_all(&record)
+                .and_then(|_| tail.file.sync_data());
+            if let Err(e) = written {
+                // Leave no partial record for the next append to follow
+                let len = tail.len;
+                let _ = tail.file.set_len(len);
+                let _ = tail.file.seek(SeekFrom::Start(len));
+                return Err(anyhow::Error::from(e).context("Journal append failed"));
+            }
+            tail.len += record.len() as u64;
+            tail.next += 1;
+            Ok(tail.next - 1)
+        })
+        .await?
+    }
+
+    /// Hand every event to `apply` in order, with its sequence number.
+    /// Returns how many there were.
+    pub fn replay(&self, mut apply: impl FnMut(u64, E)) -> Result<u64> {
+        let (content, first) = {
+            let tail = self.shared.file.lock().unwrap();
+            let mut content = Vec::with_capacity(tail.len as usize);
+            File::open(&self.shared.path)?
+                .take(tail.len)
+                .read_to_end(&mut content)?;
+            (content, tail.first)
+        };
+        let mut seq = first;
+        for payload in Records::new(&content) {
+            let event = rmp_serde::from_slice(payload)
+                .with_context(|| format!("Undecodable journal event {}", seq))?;
+            apply(seq, event);
+            seq += 1;
+        }
+        Ok(seq - first)
+    }
+
+    /// Replace the journal with `events`, which must rebuild the same state
+    /// as everything appended so far. They are numbered on from the last
+    /// append.
+    ///
+    /// An event appended between reading the state and compacting is lost,
+    /// so compact under whatever lock handlers hold to append and apply.
+    pub async fn compact(&self, events: Vec<E>) -> Result<()> {
+        let records = events.iter().map(record).collect::<Result<Vec<_>>>()?;
+        let shared = self.shared.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut tail = shared.file.lock().unwrap();
+            let first = tail.next;
+            let staged = shared.path.with_extension("journal.compact");
+            let staging = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&staged)?;
+            let mut out = BufWriter::new(staging);
+            out.write_all(&header(first))?;
+            let mut len = HEADER_LEN;
+            for record in &records {
+                out.write_all(record)?;
+                len += record.len() as u64;
+            }
+            // Left at its end, ready for the next append
+            let file = out.into_inner().map_err(|e| e.into_error())?;
+            file.sync_all()?;
+            std::fs::rename(&staged, &shared.path)?;
+            if let Some(dir) = shared.path.parent() {
+                File::open(dir)?.sync_all()?;
+            }
+            *tail = Tail {
+                file,
+                first,
+                next: first + records.len() as u64,
+                len,
+            };
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Sequence number the next append gets
+    pub fn next_seq(&self) -> u64 {
+        self.shared.file.lock().unwrap().next
+    }
+
+    /// Events in the file, for deciding when to compact
+    pub fn len(&self) -> u64 {
+        let tail = self.shared.file.lock().unwrap();
+        tail.next - tail.first
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Size of the file in bytes
+    pub fn size(&self) -> u64 {
+        self.shared.file.lock().unwrap().len
+    }
+}
+
+fn header(first: u64) -> [u8; HEADER_LEN as usize] {
+    let mut header = [0; HEADER_LEN as usize];
+    header[..8].copy_from_slice(MAGIC);
+    header[8..].copy_from_slice(&first.to_le_bytes());
+    header
+}
+
+fn read_header(content: &[u8]) -> Result<u64> {
+    if content.len() < HEADER_LEN as usize || &content[..8] != MAGIC {
+        bail!("Not a cell journal");
+    }
+    Ok(u64::from_le_bytes(content[8..16].try_into().unwrap()))
+}
+
+/// Length, the first four bytes of the payload's BLAKE3 hash, payload
+fn record<E: Serialize>(event: &E) -> Result<Vec<u8>> {
+    let payload = rmp_serde::to_vec_named(event)?;
+    let len = u32::try_from(payload.len()).context("Journal event too large")?;
+    let mut record = Vec::with_capacity(RECORD_HEADER_LEN + payload.len());
+    record.extend_from_slice(&len.to_le_bytes());
+    record.extend_from_slice(&blake3::hash(&payload).as_bytes()[..4]);
+    record.extend_from_slice(&payload);
+    Ok(record)
+}
+
+/// Payloads of the intact records after the header. Stops at the first
+/// torn or corrupt one, leaving `offset` where it starts.
+struct Records<'a> {
+    content: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Records<'a> {
+    fn new(content: &'a [u8]) -> Self {
+        Self {
+            content,
+            offset: HEADER_LEN as usize,
+        }
+    }
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let rest = self.content.get(self.offset..)?;
+        if rest.len() < RECORD_HEADER_LEN {
+            return None;
+        }
+        let len = u32::from_le_bytes(rest[..4].try_into().unwrap()) as usize;
+        let payload = rest.get(RECORD_HEADER_LEN..RECORD_HEADER_LEN + len)?;
+        if blake3::hash(payload).as_bytes()[..4] != rest[4..8] {
+            return None;
+        }
+        self.offset += RECORD_HEADER_LEN + len;
+        Some(payload)
+    }
+}
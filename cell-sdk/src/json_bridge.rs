@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/json_bridge.rs
+//! Calls with JSON arguments over the native protocol.
+//!
+//! Uses the HTTP gateway's mapping: the arguments are an object with one
+//! property per argument, and the result is the method's return value. The
+//! wire format of a call is fixed at compile time, so each callable cell is
+//! linked in with `cell_remote!` and its [`call`] instantiated for its
+//! protocol:
+//!
+//! ```ignore
+//! cell_remote!(Ledger = "ledger");
+//! let caller: Caller = call::<Ledger::LedgerProtocol, Ledger::LedgerResponse>;
+//! let outcome = caller(conn, "deposit".into(), json!({ "req": { ... } })).await?;
+//! ```
+//!
+//! Used by the language bindings (cell-py, cell-node) and cell-repl.
+
+use crate::error::{CellError, ErrorContext};
+use crate::membrane::BoxFuture;
+use crate::ResilientSynapse;
+use anyhow::Result;
+use cell_model::rkyv::de::deserializers::SharedDeserializeMap;
+use cell_model::rkyv::ser::serializers::AllocSerializer;
+use cell_model::rkyv::validation::validators::DefaultValidator;
+use serde_json::Value;
+
+/// [`call`] for one protocol
+pub type Caller = fn(ResilientSynapse, String, Value) -> BoxFuture<'static, Result<Value>>;
+
+/// Call `method` on a cell whose protocol is `P`, with its arguments as JSON.
+pub fn call<P, R>(
+    conn: ResilientSynapse,
+    method: String,
+    args: Value,
+) -> BoxFuture<'static, Result<Value>>
+where
+    P: serde::de::DeserializeOwned + rkyv::Serialize<AllocSerializer<1024>> + Send,
+    R: rkyv::Archive + serde::Serialize,
+    R::Archived:
+        rkyv::Deserialize<R, SharedDeserializeMap> + for<'a> rkyv::CheckBytes<DefaultValidator<'a>>,
+{
+    Box::pin(async move {
+        let variant = to_pascal(&method);
+        let req: P = serde_json::from_value(serde_json::json!({ variant.as_str(): args }))
+            .map_err(|e| {
+                ErrorContext::new(CellError::InvalidMessage)
+                    .with_message(format!("Arguments do not match '{}': {}", method, e))
+            })?;
+
+        let resp = conn.fire(&req).await?.into_owned();
+        let archived = rkyv::check_archived_root::<R>(&resp)
+            .map_err(|e| anyhow::anyhow!("Invalid response: {}", e))?;
+        let resp: R = rkyv::Deserialize::deserialize(archived, &mut SharedDeserializeMap::new())
+            .map_err(|e| anyhow::anyhow!("Invalid response: {}", e))?;
+
+        // Responses are externally tagged by method: `{ "Deposit": ... }`
+        match serde_json::to_value(resp)? {
+            Value::Object(map) if map.len() == 1 => Ok(map.into_iter().next().unwrap().1),
+            other => Ok(other),
+        }
+    })
+}
+
+/// Protocol variant of a method: `get_balance` is `GetBalance`
+pub fn to_pascal(snake: &str) -> String {
+    snake
+        .split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -5,7 +5,7 @@ extern crate self as cell_sdk;
 
 pub use anyhow;
 pub use cell_core::{channel, CellError, Vesicle};
-pub use cell_macros::{cell_remote, expand, handler, protein, service};
+pub use cell_macros::{assert_compatible, cell_remote, expand, handler, protein, service};
 pub use cell_model::*;
 pub use clap;
 pub use dirs;
@@ -16,41 +16,91 @@ pub use rkyv::{
     CheckBytes,
 };
 
-#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+/// Failed request, sent with [`cell_core::frame::ERROR`] set. `code` is a
+/// [`CellError`] code, `cell` the cell the error originated in.
+#[derive(
+    Debug, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, serde::Serialize, serde::Deserialize,
+)]
 #[archive(check_bytes)]
 pub struct ErrorResponse {
     pub code: u32,
     pub message: String,
     pub cell: String,
+    /// Retry hint in milliseconds; 0 leaves it to the code's default
+    pub retry_after_ms: u32,
 }
 
 pub use serde;
 pub use tracing;
 
+pub mod actor;
+pub mod admin;
+pub mod admission;
+pub mod auth;
+pub mod codec;
+pub mod compose;
 pub mod config;
+pub mod context;
 pub mod connection_manager;
+pub mod correlator;
 pub mod crdt;
+pub mod cytokine;
+pub mod degrade;
+pub mod domain;
 pub mod error;
+pub mod extension;
+pub mod handover;
 pub mod identity;
+pub mod inspect;
+pub mod journal;
 pub mod io_client;
+pub mod json_bridge;
 pub mod logging;
 pub mod membrane;
 pub mod mesh;
 pub mod metrics;
+pub mod middleware;
+pub mod migrations;
 pub mod organogenisis;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod priority;
+pub mod profile;
+pub mod provenance;
+#[cfg(feature = "quic")]
+pub mod quic;
+pub mod quota;
+pub mod replay;
+pub mod replica;
 pub mod resilient_synapse; // NEW: Production-grade resilient connection
+pub mod retry;
 pub mod response;
 pub mod runtime;
+pub mod shed;
 pub mod shm;
+pub mod signing;
+pub mod slo;
+pub mod slowlog;
+pub mod source;
+pub mod state;
+pub mod status;
 pub mod synapse; // Legacy - kept for compatibility
+pub mod synapse_pool;
 pub mod system;
+pub mod tcp;
+pub mod telemetry;
 pub mod test_context;
 pub mod tissue;
+pub mod trace;
+pub mod watchdog;
 pub use crate::error::*;
 pub use connection_manager::{ConnectionManager, PoolConfig};
+pub use cytokine::{Cytokine, Receptor};
+pub use journal::Journal;
 
 // NEW: Re-export ResilientSynapse as the primary connection type
 pub use resilient_synapse::{ConnMetrics, ConnState, ResilienceConfig, ResilientSynapse};
+pub use synapse_pool::SynapsePool;
 
 pub use membrane::Membrane;
 pub use response::Response;
@@ -63,6 +113,7 @@ pub mod prelude {
         anyhow::{Error, Result},
         cell_remote,
         config::CellConfig,
+        context::CallContext,
         expand,
         handler,
         protein,
@@ -1,18 +1,26 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
 
-use tracing::{info};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use anyhow::{anyhow, bail, Context, Result};
+use cell_model::ops::{OpsRequest, OpsResponse};
+use std::sync::OnceLock;
+use tracing::info;
+use tracing_subscriber::{
+    layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry,
+};
+
+/// Handle to the installed filter, set once by [`filter_layer`].
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
 
 pub fn init_logging(cell_name: &str) {
     tracing_subscriber::registry()
-        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with(filter_layer())
         .with(
             tracing_subscriber::fmt::layer()
                 .with_target(true)
                 .with_level(true)
                 .with_thread_ids(true)
-                .json()
+                .json(),
         )
         .init();
 
@@ -23,6 +31,66 @@ pub fn init_logging(cell_name: &str) {
     );
 }
 
+/// `RUST_LOG` filter (default `info`) that OPS `SetLogLevel` can change at
+/// runtime. Must be the first layer on the registry.
+pub fn filter_layer() -> reload::Layer<EnvFilter, Registry> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let (layer, handle) = reload::Layer::new(filter);
+    let _ = FILTER.set(handle);
+    layer
+}
+
+/// Set `level` for one `target` (a module path), or for everything when
+/// `target` is `None`. Returns the resulting filter.
+pub fn set_level(target: Option<&str>, level: &str) -> Result<String> {
+    let handle = FILTER
+        .get()
+        .context("Cell has no reloadable log filter (see cell_sdk::logging::filter_layer)")?;
+    let level: tracing::level_filters::LevelFilter = level
+        .parse()
+        .map_err(|_| anyhow!("Invalid log level '{}'", level))?;
+    let directive = match target {
+        Some(target) => format!("{}={}", target, level),
+        None => level.to_string(),
+    };
+
+    let mut updated = String::new();
+    handle.modify(|filter| {
+        // A later directive for the same target replaces the earlier one
+        let current = filter.to_string();
+        let next = match current.is_empty() {
+            true => directive.clone(),
+            false => format!("{},{}", current, directive),
+        };
+        *filter = EnvFilter::new(&next);
+        updated = filter.to_string();
+    })?;
+    info!(filter = %updated, "Log filter changed");
+    Ok(updated)
+}
+
+/// Current filter, if this cell installed [`filter_layer`].
+pub fn current() -> Option<String> {
+    FILTER.get()?.with_current(|f| f.to_string()).ok()
+}
+
+/// Change the log filter of a running cell; see [`set_level`].
+pub async fn set_cell_level(
+    cell_name: &str,
+    target: Option<String>,
+    level: &str,
+) -> Result<String> {
+    let req = OpsRequest::SetLogLevel {
+        target,
+        level: level.to_string(),
+    };
+    match crate::state::ops(cell_name, &req).await? {
+        OpsResponse::LogLevel { filter } => Ok(filter),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
+
 // Add to every critical operation:
 #[macro_export]
 macro_rules! log_operation {
@@ -38,4 +106,4 @@ macro_rules! log_operation {
             }
         }
     };
-}
\ No newline at end of file
+}
@@ -1,22 +1,70 @@
 // SPDX-License-Identifier: MIT
 // cell-sdk/src/membrane.rs
 
+use crate::auth::{AdminKey, AuthResponse, Caller, ImpersonationGrant};
+use crate::context::CallContext;
+use crate::error::{CellError, ErrorContext};
 use crate::io_client::IoClient;
+use crate::ErrorResponse;
 use anyhow::{Context, Result};
-use cell_core::channel;
+use cell_core::{channel, Codec, TraceContext, VesicleHeader};
 use cell_model::rkyv::ser::serializers::AllocSerializer;
 use cell_model::rkyv::Archive;
 use std::future::Future;
+use std::os::unix::io::AsRawFd;
 use std::pin::Pin;
 use std::sync::Arc;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::{UnixListener, UnixStream};
-use tracing::{error, info};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, UnixListener};
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+/// Largest request accepted; the length prefix comes from the peer
+const MAX_FRAME: usize = 100 * 1024 * 1024;
 
 pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 
 pub struct Membrane;
 
+/// Runs one handler call. With `otel` the call gets a `cell.request` span,
+/// a child of the caller's, and is counted in the exported request metrics.
+#[cfg(feature = "otel")]
+async fn observe<T>(
+    name: &str,
+    principal: &str,
+    parent: Option<TraceContext>,
+    call: impl Future<Output = (Result<T>, bool)>,
+) -> (Result<T>, bool) {
+    use tracing::Instrument;
+    let span = tracing::info_span!(
+        crate::otel::REQUEST_SPAN,
+        cell = %name,
+        principal = %principal,
+        otel.kind = "server",
+        otel.status_code = tracing::field::Empty,
+    );
+    if let Some(parent) = parent {
+        crate::otel::set_remote_parent(&span, parent);
+    }
+    let started = std::time::Instant::now();
+    let outcome = call.instrument(span.clone()).await;
+    if outcome.0.is_err() {
+        crate::otel::mark_failed(&span);
+    }
+    crate::otel::record_request(name, started.elapsed(), outcome.0.is_ok());
+    outcome
+}
+
+#[cfg(not(feature = "otel"))]
+async fn observe<T>(
+    _name: &str,
+    _principal: &str,
+    _parent: Option<TraceContext>,
+    call: impl Future<Output = (Result<T>, bool)>,
+) -> (Result<T>, bool) {
+    call.await
+}
+
 impl Membrane {
     pub async fn bind<F, Req, Resp>(
         name: &str,
@@ -39,6 +87,114 @@ impl Membrane {
             + 'static,
         Resp: rkyv::Serialize<AllocSerializer<1024>> + Send + 'static,
     {
+        let handler = Arc::new(handler);
+        crate::status::serving(name);
+        crate::middleware::attach_declared().await?;
+        // Storage cells charge the quota cell; it does not limit them itself
+        if name != "quota" {
+            crate::quota::follow_published();
+        }
+
+        // Hosted by compose!: serve in-memory connections only
+        if let Some(mut listener) = crate::compose::take_listener(name) {
+            crate::compose::register_dispatch(
+                name,
+                Self::local_dispatch::<F, Req, Resp>(name.into(), handler.clone()),
+            );
+            info!("[Membrane] {} online (in-process)", name);
+            while let Some(stream) = listener.recv().await {
+                let (name, handler) = (name.to_string(), handler.clone());
+                tokio::spawn(async move {
+                    // Same process, same trust: served like the admin socket
+                    let _ = Self::handle_connection::<_, F, Req, Resp>(
+                        stream,
+                        name,
+                        "local".into(),
+                        handler,
+                        true,
+                    )
+                    .await;
+                });
+            }
+            return Ok(());
+        }
+
+        // Privileged OPS are only taken on the admin socket
+        tokio::spawn(Self::serve_admin::<F, Req, Resp>(
+            name.to_string(),
+            handler.clone(),
+        ));
+
+        // TCP selected: serve callers on other hosts directly, without the IO Cell
+        if let Some(addr) = crate::tcp::listen_addr()? {
+            let listener = TcpListener::bind(&addr)
+                .await
+                .with_context(|| format!("Failed to listen on tcp://{}", addr))?;
+            info!(
+                "[Membrane] {} online (tcp://{})",
+                name,
+                listener.local_addr()?
+            );
+
+            loop {
+                let (stream, addr) = match listener.accept().await {
+                    Ok(s) => s,
+                    Err(e) => {
+                        error!("Accept error: {}", e);
+                        continue;
+                    }
+                };
+                let _ = stream.set_nodelay(true);
+
+                // Quota principal for unauthenticated requests
+                let peer = format!("tcp:{}", addr.ip());
+                let (name, handler) = (name.to_string(), handler.clone());
+                tokio::spawn(async move {
+                    let _ = Self::handle_connection::<_, F, Req, Resp>(
+                        stream, name, peer, handler, false,
+                    )
+                    .await;
+                });
+            }
+        }
+
+        #[cfg(feature = "quic")]
+        if let Some(addr) = crate::quic::listen_addr() {
+            let endpoint = crate::quic::listen(&addr)?;
+            info!(
+                "[Membrane] {} online (quic://{})",
+                name,
+                endpoint.local_addr()?
+            );
+
+            while let Some(connecting) = endpoint.accept().await {
+                let (name, handler) = (name.to_string(), handler.clone());
+                tokio::spawn(async move {
+                    let conn = match connecting.await {
+                        Ok(conn) => conn,
+                        Err(e) => {
+                            warn!("QUIC handshake failed: {}", e);
+                            return;
+                        }
+                    };
+                    // Quota principal for unauthenticated requests
+                    let peer = format!("quic:{}", conn.remote_address().ip());
+                    // Each synapse dialing in opens one stream
+                    while let Ok((send, recv)) = conn.accept_bi().await {
+                        let stream = crate::quic::QuicStream::accepted(send, recv, conn.clone());
+                        let (name, peer, handler) = (name.clone(), peer.clone(), handler.clone());
+                        tokio::spawn(async move {
+                            let _ = Self::handle_connection::<_, F, Req, Resp>(
+                                stream, name, peer, handler, false,
+                            )
+                            .await;
+                        });
+                    }
+                });
+            }
+            return Ok(());
+        }
+
         let std_listener = IoClient::bind_membrane(name)
             .await
             .context("Failed to acquire listener from IO Cell")?;
@@ -48,10 +204,97 @@ impl Membrane {
 
         info!("[Membrane] {} online (FD inherited)", name);
 
-        let handler = Arc::new(handler);
+        // Socket activated: the hypervisor holds the socket while we are gone
+        if let Some(idle) = IoClient::idle_exit() {
+            tokio::spawn(Self::exit_when_idle(name.to_string(), idle));
+        }
+
+        // Our own listener, plus any adopted from a previous instance
+        let mut adoptions = crate::handover::adoptions();
+        let mut serving = tokio::task::JoinSet::new();
+        serving.spawn(Self::serve_unix::<F, Req, Resp>(
+            name.to_string(),
+            name.to_string(),
+            listener,
+            handler.clone(),
+        ));
+        loop {
+            tokio::select! {
+                Some((from, listener)) = adoptions.recv() => {
+                    serving.spawn(Self::serve_unix::<F, Req, Resp>(
+                        name.to_string(),
+                        from,
+                        listener,
+                        handler.clone(),
+                    ));
+                }
+                done = serving.join_next() => {
+                    if done.is_none() || serving.is_empty() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        // Every listener went to a successor: finish what we have, then exit
+        crate::handover::retire().await;
+        info!("[Membrane] {} retired after handover", name);
+        Ok(())
+    }
+
+    async fn exit_when_idle(name: String, idle: std::time::Duration) {
+        let mut idle_since = std::time::Instant::now();
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            if crate::inspect::connection_count() > 0 {
+                idle_since = std::time::Instant::now();
+            } else if idle_since.elapsed() >= idle {
+                info!("[Membrane] {} idle for {:?}, exiting until the next connection", name, idle);
+                std::process::exit(0);
+            }
+        }
+    }
+
+    /// Accept on `listener` until a successor adopts it (see `crate::handover`)
+    async fn serve_unix<F, Req, Resp>(
+        name: String,
+        offered_as: String,
+        listener: UnixListener,
+        handler: Arc<F>,
+    ) where
+        F: for<'a> Fn(&'a Req::Archived) -> BoxFuture<'a, Result<Resp>>
+            + Send
+            + Sync
+            + 'static
+            + Clone,
+        Req: Archive + Send + 'static,
+        for<'a> Req::Archived: rkyv::CheckBytes<rkyv::validation::validators::DefaultValidator<'a>>
+            + Send
+            + Sync
+            + 'static,
+        Resp: rkyv::Serialize<AllocSerializer<1024>> + Send + 'static,
+    {
+        let released = match crate::handover::offer(&offered_as, listener.as_raw_fd()) {
+            Ok(released) => Some(released),
+            Err(e) => {
+                warn!("[Membrane] {} cannot be handed over: {}", offered_as, e);
+                None
+            }
+        };
+        let handed_over = async {
+            match &released {
+                Some(released) => released.notified().await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::pin!(handed_over);
 
         loop {
-            let (stream, _) = match listener.accept().await {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                _ = &mut handed_over => return,
+            };
+            let (stream, _) = match accepted {
                 Ok(s) => s,
                 Err(e) => {
                     error!("Accept error: {}", e);
@@ -59,15 +302,78 @@ impl Membrane {
                 }
             };
 
-            let handler = handler.clone();
+            // Quota principal for unauthenticated requests
+            let peer = stream
+                .peer_cred()
+                .map(|c| format!("uid:{}", c.uid()))
+                .unwrap_or_else(|_| "anonymous".to_string());
+            let (name, handler) = (name.clone(), handler.clone());
+            tokio::spawn(async move {
+                let _ =
+                    Self::handle_connection::<_, F, Req, Resp>(stream, name, peer, handler, false)
+                        .await;
+            });
+        }
+    }
+
+    /// Serve the admin socket of `name` (see `crate::admin`) to processes of
+    /// the cell's own user
+    async fn serve_admin<F, Req, Resp>(name: String, handler: Arc<F>)
+    where
+        F: for<'a> Fn(&'a Req::Archived) -> BoxFuture<'a, Result<Resp>>
+            + Send
+            + Sync
+            + 'static
+            + Clone,
+        Req: Archive + Send + 'static,
+        for<'a> Req::Archived: rkyv::CheckBytes<rkyv::validation::validators::DefaultValidator<'a>>
+            + Send
+            + Sync
+            + 'static,
+        Resp: rkyv::Serialize<AllocSerializer<1024>> + Send + 'static,
+    {
+        let listener = match crate::admin::bind(&name) {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!("[Membrane] {} has no admin socket: {}", name, e);
+                return;
+            }
+        };
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    error!("Admin accept error: {}", e);
+                    continue;
+                }
+            };
+            let Some(uid) = crate::admin::authorize(&stream) else {
+                warn!(
+                    target: "audit",
+                    "Refused admin connection to {} from a process of another user", name
+                );
+                continue;
+            };
+            let peer = format!("admin:uid:{}", uid);
+            let (name, handler) = (name.clone(), handler.clone());
             tokio::spawn(async move {
-                let _ = Self::handle_connection::<F, Req, Resp>(stream, handler).await;
+                let _ =
+                    Self::handle_connection::<_, F, Req, Resp>(stream, name, peer, handler, true)
+                        .await;
             });
         }
     }
 
-    async fn handle_connection<F, Req, Resp>(mut stream: UnixStream, handler: Arc<F>) -> Result<()>
+    async fn handle_connection<S, F, Req, Resp>(
+        stream: S,
+        name: String,
+        peer: String,
+        handler: Arc<F>,
+        // Accepted on the admin socket: privileged OPS allowed
+        admin: bool,
+    ) -> Result<()>
     where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
         F: Fn(&Req::Archived) -> BoxFuture<Result<Resp>> + Send + Sync + 'static,
         Req: Archive + Send + 'static,
         for<'a> Req::Archived: rkyv::CheckBytes<rkyv::validation::validators::DefaultValidator<'a>>
@@ -76,16 +382,30 @@ impl Membrane {
             + 'static,
         Resp: rkyv::Serialize<AllocSerializer<1024>> + Send + 'static,
     {
+        // Listed by `cell inspect` until the connection closes
+        let conn = crate::inspect::ConnectionHandle::open(&peer);
+        let (mut reader, writer) = tokio::io::split(conn.meter(stream));
+        // Shared with correlated requests, which answer whenever they finish
+        let writer = Arc::new(Mutex::new(writer));
+        // Set by an accepted impersonation grant; applies to the rest of the connection
+        let mut caller: Option<Caller> = None;
+        // Wire format of APP payloads, negotiated on channel::CODEC
+        let mut codec = Codec::Rkyv;
+
         loop {
             let mut len_buf = [0u8; 4];
-            match stream.read_exact(&mut len_buf).await {
+            match reader.read_exact(&mut len_buf).await {
                 Ok(_) => (),
                 Err(_) => break,
             }
             let len = u32::from_le_bytes(len_buf) as usize;
+            if len > MAX_FRAME {
+                warn!("Dropping connection: {} byte frame exceeds the limit", len);
+                break;
+            }
 
             let mut buf = vec![0u8; len];
-            if let Err(e) = stream.read_exact(&mut buf).await {
+            if let Err(e) = reader.read_exact(&mut buf).await {
                 error!("Read error: {}", e);
                 break;
             }
@@ -96,83 +416,511 @@ impl Membrane {
             }
 
             let channel = buf[24];
-            let payload = &buf[25..];
+            // Flags byte of the VesicleHeader
+            let (id, payload) = if buf[17] & VesicleHeader::CORRELATED != 0 {
+                if buf.len() < 33 {
+                    error!("Correlated message too short: {} bytes", buf.len());
+                    continue;
+                }
+                let id = u64::from_le_bytes(buf[25..33].try_into().unwrap());
+                (Some(id), &buf[33..])
+            } else {
+                (None, &buf[25..])
+            };
+            // The caller's span, continued by the handler
+            let (trace, payload) = if buf[17] & VesicleHeader::TRACED != 0 {
+                let Some(trace) = TraceContext::from_bytes(payload) else {
+                    error!("Invalid trace context");
+                    continue;
+                };
+                (Some(trace), &payload[TraceContext::SIZE..])
+            } else {
+                (None, payload)
+            };
+            // The caller's call chain priority, inherited by the handler
+            let priority = (buf[17] & VesicleHeader::PRIORITIZED != 0).then_some(buf[18]);
 
-            if channel == channel::APP {
-                let aligned_payload = payload.to_vec();
-
-                // CRITICAL PATTERN: Convert CheckBytes error to String immediately
-                // The CheckBytes::Error type is NOT Send, so we must NOT hold it across await points.
-                // We use a synchronous block to perform validation, converting any error to String
-                // before entering the async error handling path.
-                let validation_result: Result<&Req::Archived, String> = {
-                    // This block is synchronous - no await points here
-                    match rkyv::check_archived_root::<Req>(&aligned_payload) {
-                        Ok(archived) => Ok(archived),
-                        Err(check_err) => {
-                            // IMMEDIATE CONVERSION: Drop check_err by formatting it
-                            Err(format!("Request validation failed: {:?}", check_err))
-                        }
+            if channel == channel::AUTH {
+                let resp = match Self::accept_grant(&name, payload) {
+                    Ok(c) => {
+                        let principal = c.principal.clone();
+                        caller = Some(c);
+                        AuthResponse::Accepted { principal }
                     }
+                    Err(e) => AuthResponse::Rejected {
+                        reason: e.to_string(),
+                    },
                 };
+                let resp_bytes = rkyv::to_bytes::<_, 256>(&resp)?.into_vec();
+                Self::reply(&writer, id, 0, &resp_bytes).await?;
+                continue;
+            }
 
-                let archived = match validation_result {
-                    Ok(a) => a,
-                   Err(err_msg) => {
-                        let error_response = ErrorResponse {
-                            code: 500,
-                            message: err_msg,
-                            cell: name.to_string(),
-                        };
-                        let err_bytes = rkyv::to_bytes::<_, 256>(&error_response)
-                            .unwrap_or_else(|_| vec![0u8; 4].into()); // Fallback
-                        
-                        // Write with a special header or channel indicating error
-                        stream.write_all(&(err_bytes.len() as u32).to_le_bytes()).await?;
-                        stream.write_all(&err_bytes).await?;
+            if channel == channel::CODEC {
+                codec = Codec::negotiate(payload, crate::codec::supports);
+                Self::reply(&writer, id, 0, &[codec as u8]).await?;
+                continue;
+            }
+
+            if channel == channel::OPS {
+                let resp = match rkyv::check_archived_root::<cell_model::ops::OpsRequest>(payload) {
+                    Ok(archived) => {
+                        let req: cell_model::ops::OpsRequest =
+                            rkyv::Deserialize::deserialize(archived, &mut rkyv::Infallible)?;
+                        if req.is_privileged() && !admin {
+                            warn!(
+                                target: "audit",
+                                "Refused privileged OPS request from {} outside the admin socket",
+                                peer
+                            );
+                            cell_model::ops::OpsResponse::Error {
+                                message: format!(
+                                    "Privileged OPS request refused: send it on {}'s admin socket",
+                                    name
+                                ),
+                            }
+                        } else {
+                            crate::state::handle_ops(req).await
+                        }
                     }
+                    Err(e) => cell_model::ops::OpsResponse::Error {
+                        message: format!("Malformed OPS request: {}", e),
+                    },
                 };
+                let resp_bytes = rkyv::to_bytes::<_, 1024>(&resp)?.into_vec();
+                Self::reply(&writer, id, 0, &resp_bytes).await?;
+                continue;
+            }
 
-                // Now call handler - archived is a simple reference
-                let response = match handler(archived).await {
-                    Ok(r) => r,
-                    Err(e) => {
-                        error!("Handler Error: {}", e);
-                        let err_bytes = format!("Handler error: {}", e).into_bytes();
-                        if let Err(e) = stream
-                            .write_all(&(err_bytes.len() as u32).to_le_bytes())
-                            .await
-                        {
+            if channel == channel::EXTENSION {
+                let principal = caller
+                    .as_ref()
+                    .map(|c| c.principal.clone())
+                    .unwrap_or_else(|| peer.clone());
+                let call = Self::serve_extension(
+                    name.clone(),
+                    peer.clone(),
+                    principal,
+                    caller.clone(),
+                    trace,
+                    priority,
+                    payload.to_vec(),
+                    conn.tracker(),
+                );
+                match id {
+                    Some(id) => {
+                        let writer = writer.clone();
+                        tokio::spawn(async move {
+                            let Some((flags, bytes)) = call.await else {
+                                return;
+                            };
+                            if let Err(e) = Self::reply(&writer, Some(id), flags, &bytes).await {
+                                error!("Write error: {}", e);
+                            }
+                        });
+                    }
+                    None => {
+                        let Some((flags, bytes)) = call.await else {
+                            continue;
+                        };
+                        if let Err(e) = Self::reply(&writer, None, flags, &bytes).await {
                             error!("Write error: {}", e);
                             break;
                         }
-                        if let Err(e) = stream.write_all(&err_bytes).await {
+                    }
+                }
+                continue;
+            }
+
+            if channel == channel::APP {
+                let principal = caller
+                    .as_ref()
+                    .map(|c| c.principal.clone())
+                    .unwrap_or_else(|| peer.clone());
+                let call = Self::serve_app::<F, Req, Resp>(
+                    name.clone(),
+                    peer.clone(),
+                    principal,
+                    caller.clone(),
+                    trace,
+                    priority,
+                    handler.clone(),
+                    codec,
+                    payload.to_vec(),
+                    conn.tracker(),
+                );
+                match id {
+                    // Answered when done; the next request is read meanwhile
+                    Some(id) => {
+                        let writer = writer.clone();
+                        tokio::spawn(async move {
+                            let Some((flags, bytes)) = call.await else {
+                                return;
+                            };
+                            if let Err(e) = Self::reply(&writer, Some(id), flags, &bytes).await {
+                                error!("Write error: {}", e);
+                            }
+                        });
+                    }
+                    None => {
+                        let Some((flags, bytes)) = call.await else {
+                            continue;
+                        };
+                        if let Err(e) = Self::reply(&writer, None, flags, &bytes).await {
                             error!("Write error: {}", e);
                             break;
                         }
-                        continue;
                     }
-                };
+                }
+            }
+        }
+        Ok(())
+    }
 
-                let resp_bytes = match rkyv::to_bytes::<_, 1024>(&response) {
-                    Ok(b) => b.into_vec(),
-                    Err(e) => {
-                        error!("Response serialization failed: {}", e);
-                        continue;
-                    }
-                };
+    /// Run one application request, encoded with `codec`. Returns the
+    /// response's frame flags and bytes, or `None` if the response could not
+    /// be serialized.
+    async fn serve_app<F, Req, Resp>(
+        name: String,
+        peer: String,
+        principal: String,
+        caller: Option<Caller>,
+        trace: Option<TraceContext>,
+        priority: Option<u8>,
+        handler: Arc<F>,
+        codec: Codec,
+        payload: Vec<u8>,
+        tracker: crate::inspect::RequestTracker,
+    ) -> Option<(u32, Vec<u8>)>
+    where
+        F: Fn(&Req::Archived) -> BoxFuture<Result<Resp>> + Send + Sync + 'static,
+        Req: Archive + Send + 'static,
+        for<'a> Req::Archived: rkyv::CheckBytes<rkyv::validation::validators::DefaultValidator<'a>>
+            + Send
+            + Sync
+            + 'static,
+        Resp: rkyv::Serialize<AllocSerializer<1024>> + Send + 'static,
+    {
+        // Drained by the hypervisor: callers should go elsewhere
+        if crate::watchdog::is_draining() {
+            let err =
+                ErrorContext::new(CellError::TransportUnavailable).with_message("Cell is draining");
+            return Self::error_frame(codec, err.to_response(&name));
+        }
+
+        // Middleware sees the request as the caller sent it
+        let shown = crate::middleware::active().then(|| {
+            crate::middleware::request(&name, channel::APP, &principal, &peer, codec, payload.len())
+        });
+        if let Some(shown) = &shown {
+            if let Err(err) = crate::middleware::screen(shown.clone(), payload.to_vec()).await {
+                return Self::error_frame(codec, err.to_response(&name));
+            }
+        }
 
-                let total_len = resp_bytes.len();
-                if let Err(e) = stream.write_all(&(total_len as u32).to_le_bytes()).await {
-                    error!("Write error: {}", e);
-                    break;
+        let aligned_payload = match codec {
+            Codec::Rkyv => payload,
+            _ => match crate::codec::decode_request(codec, &payload) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    let err = ErrorContext::new(CellError::DeserializationFailure)
+                        .with_message(format!("Malformed {} request: {}", codec.name(), e));
+                    return Self::error_frame(codec, err.to_response(&name));
+                }
+            },
+        };
+
+        // CRITICAL PATTERN: Convert CheckBytes error to String immediately
+        // The CheckBytes::Error type is NOT Send, so we must NOT hold it across await points.
+        // We use a synchronous block to perform validation, converting any error to String
+        // before entering the async error handling path.
+        let validation_result: Result<&Req::Archived, String> = {
+            // This block is synchronous - no await points here
+            match rkyv::check_archived_root::<Req>(&aligned_payload) {
+                Ok(archived) => Ok(archived),
+                Err(check_err) => {
+                    // IMMEDIATE CONVERSION: Drop check_err by formatting it
+                    Err(format!("Request validation failed: {:?}", check_err))
                 }
-                if let Err(e) = stream.write_all(&resp_bytes).await {
-                    error!("Write error: {}", e);
-                    break;
+            }
+        };
+
+        let archived = match validation_result {
+            Ok(a) => a,
+            Err(err_msg) => {
+                let err =
+                    ErrorContext::new(CellError::DeserializationFailure).with_message(err_msg);
+                return Self::error_frame(codec, err.to_response(&name));
+            }
+        };
+
+        if let Err(breach) =
+            crate::quota::admit(&crate::quota::organism_of(caller.as_ref()), &principal)
+        {
+            let err = ErrorContext::from(&breach);
+            return Self::error_frame(codec, err.to_response(&name));
+        }
+
+        // Now call handler - archived is a simple reference
+        let pending = tracker.request();
+        let started = std::time::Instant::now();
+        let cell = name.clone();
+        let (result, degraded) = observe(
+            &name,
+            &principal,
+            trace,
+            // Built once polled, inside the request span its trace id comes from
+            crate::degrade::track(async move {
+                let mut ctx = CallContext::traced(peer, caller, trace);
+                ctx.priority = priority;
+                crate::trace::serve(&cell, ctx, trace, handler(archived)).await
+            }),
+        )
+        .await;
+        drop(pending);
+        crate::quota::release(&principal);
+        if let Some(shown) = shown {
+            crate::middleware::finished(shown, started.elapsed(), result.is_ok());
+        }
+        let response = match result {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Handler Error: {}", e);
+                let err = ErrorContext::classify(&e, CellError::HandlerFailed);
+                return Self::error_frame(codec, err.to_response(&name));
+            }
+        };
+
+        let resp_bytes = match rkyv::to_bytes::<_, 1024>(&response) {
+            Ok(b) => b.into_vec(),
+            Err(e) => {
+                error!("Response serialization failed: {}", e);
+                return None;
+            }
+        };
+        let resp_bytes = match codec {
+            Codec::Rkyv => resp_bytes,
+            _ => match crate::codec::encode_response(codec, &resp_bytes) {
+                Ok(b) => b,
+                Err(e) => {
+                    error!("Response serialization failed: {}", e);
+                    return None;
                 }
+            },
+        };
+        let flags = if degraded {
+            cell_core::frame::DEGRADED
+        } else {
+            0
+        };
+        Some((flags, resp_bytes))
+    }
+
+    /// Run one call to a method registered at runtime (see
+    /// `crate::extension`). Arguments and result are the method's own bytes,
+    /// so the connection's codec does not apply.
+    async fn serve_extension(
+        name: String,
+        peer: String,
+        principal: String,
+        caller: Option<Caller>,
+        trace: Option<TraceContext>,
+        priority: Option<u8>,
+        payload: Vec<u8>,
+        tracker: crate::inspect::RequestTracker,
+    ) -> Option<(u32, Vec<u8>)> {
+        if crate::watchdog::is_draining() {
+            let err =
+                ErrorContext::new(CellError::TransportUnavailable).with_message("Cell is draining");
+            return Self::error_frame(Codec::Rkyv, err.to_response(&name));
+        }
+
+        let shown = crate::middleware::active().then(|| {
+            crate::middleware::request(
+                &name,
+                channel::EXTENSION,
+                &principal,
+                &peer,
+                Codec::Rkyv,
+                payload.len(),
+            )
+        });
+        if let Some(shown) = &shown {
+            if let Err(err) = crate::middleware::screen(shown.clone(), payload.to_vec()).await {
+                return Self::error_frame(Codec::Rkyv, err.to_response(&name));
+            }
+        }
+
+        let decoded: Result<crate::extension::ExtensionCall, String> =
+            match rkyv::check_archived_root::<crate::extension::ExtensionCall>(&payload) {
+                Ok(archived) => rkyv::Deserialize::deserialize(archived, &mut rkyv::Infallible)
+                    .map_err(|e| format!("{:?}", e)),
+                Err(e) => Err(format!("Request validation failed: {:?}", e)),
+            };
+        let call = match decoded {
+            Ok(call) => call,
+            Err(message) => {
+                let err =
+                    ErrorContext::new(CellError::DeserializationFailure).with_message(message);
+                return Self::error_frame(Codec::Rkyv, err.to_response(&name));
+            }
+        };
+
+        if let Err(breach) =
+            crate::quota::admit(&crate::quota::organism_of(caller.as_ref()), &principal)
+        {
+            let err = ErrorContext::from(&breach);
+            return Self::error_frame(Codec::Rkyv, err.to_response(&name));
+        }
+
+        let pending = tracker.request();
+        let started = std::time::Instant::now();
+        let cell = name.clone();
+        let (result, _) = observe(&name, &principal, trace, async move {
+            let mut ctx = CallContext::traced(peer, caller, trace);
+            ctx.priority = priority;
+            let result =
+                crate::trace::serve(&cell, ctx, trace, crate::extension::dispatch(call)).await;
+            (result, false)
+        })
+        .await;
+        drop(pending);
+        crate::quota::release(&principal);
+        if let Some(shown) = shown {
+            crate::middleware::finished(shown, started.elapsed(), result.is_ok());
+        }
+        match result {
+            Ok(bytes) => Some((0, bytes)),
+            Err(e) => {
+                error!("Extension Error: {}", e);
+                let err = ErrorContext::classify(&e, CellError::HandlerFailed);
+                Self::error_frame(Codec::Rkyv, err.to_response(&name))
+            }
+        }
+    }
+
+    /// Entry point for same-process callers. The request was archived by the
+    /// caller in this process, so it is used without validation; the caller's
+    /// identity carries over.
+    fn local_dispatch<F, Req, Resp>(name: String, handler: Arc<F>) -> crate::compose::LocalDispatch
+    where
+        F: for<'a> Fn(&'a Req::Archived) -> BoxFuture<'a, Result<Resp>>
+            + Send
+            + Sync
+            + 'static,
+        Req: Archive + Send + 'static,
+        for<'a> Req::Archived: rkyv::CheckBytes<rkyv::validation::validators::DefaultValidator<'a>>
+            + Send
+            + Sync
+            + 'static,
+        Resp: rkyv::Serialize<AllocSerializer<1024>> + Send + 'static,
+    {
+        Arc::new(move |bytes: rkyv::AlignedVec| {
+            let (name, handler) = (name.clone(), handler.clone());
+            Box::pin(async move {
+                let caller = crate::auth::caller();
+                let principal = caller
+                    .as_ref()
+                    .map(|c| c.principal.clone())
+                    .unwrap_or_else(|| "local".to_string());
+                crate::quota::admit(&crate::quota::organism_of(caller.as_ref()), &principal)
+                    .map_err(|b| ErrorContext::from(&b).with_cell(name.as_str()))?;
+
+                // SAFETY: produced by rkyv::to_bytes for Req in this process
+                let archived = unsafe { rkyv::archived_root::<Req>(&bytes) };
+                // Same task as the caller: its call span is the parent
+                let trace = crate::trace::outgoing();
+                let priority = crate::priority::current();
+                let cell = name.clone();
+                let (result, degraded) = observe(
+                    &name,
+                    &principal,
+                    trace,
+                    crate::degrade::track(async move {
+                        let mut ctx = CallContext::traced("local", caller, trace);
+                        ctx.priority = priority;
+                        crate::trace::serve(&cell, ctx, trace, handler(archived)).await
+                    }),
+                )
+                .await;
+                crate::quota::release(&principal);
+
+                let response = result.map_err(|e| {
+                    let err = ErrorContext::classify(&e, CellError::HandlerFailed);
+                    let cell = err.cell.clone().unwrap_or(name);
+                    err.with_cell(cell)
+                })?;
+                Ok((rkyv::to_bytes::<_, 1024>(&response)?.into_vec(), degraded))
+            })
+        })
+    }
+
+    /// Frame flags and bytes answering a failed request, in the request's codec.
+    fn error_frame(codec: Codec, err: ErrorResponse) -> Option<(u32, Vec<u8>)> {
+        let bytes = match codec {
+            Codec::Rkyv => rkyv::to_bytes::<_, 256>(&err)
+                .map(|b| b.into_vec())
+                .map_err(anyhow::Error::from),
+            _ => crate::codec::encode_error(codec, &err),
+        };
+        match bytes {
+            Ok(bytes) => Some((cell_core::frame::ERROR, bytes)),
+            Err(e) => {
+                error!("Error response serialization failed: {}", e);
+                None
             }
         }
+    }
+
+    /// Write one response frame, tagged with the request's correlation id if
+    /// it had one.
+    async fn reply<W: AsyncWrite + Unpin>(
+        writer: &Mutex<W>,
+        id: Option<u64>,
+        flags: u32,
+        bytes: &[u8],
+    ) -> Result<()> {
+        let mut frame = Vec::with_capacity(12 + bytes.len());
+        let mut len_prefix = bytes.len() as u32 | flags;
+        if id.is_some() {
+            len_prefix |= cell_core::frame::CORRELATED;
+        }
+        frame.extend_from_slice(&len_prefix.to_le_bytes());
+        if let Some(id) = id {
+            frame.extend_from_slice(&id.to_le_bytes());
+        }
+        frame.extend_from_slice(bytes);
+        writer.lock().await.write_all(&frame).await?;
         Ok(())
     }
+
+    /// Verify an impersonation grant against the local admin key.
+    fn accept_grant(name: &str, payload: &[u8]) -> Result<Caller> {
+        // The payload follows the frame header at an arbitrary offset
+        let mut aligned = rkyv::AlignedVec::with_capacity(payload.len());
+        aligned.extend_from_slice(payload);
+        let archived = rkyv::check_archived_root::<ImpersonationGrant>(&aligned)
+            .map_err(|e| anyhow::anyhow!("Malformed impersonation grant: {}", e))?;
+        let grant: ImpersonationGrant =
+            rkyv::Deserialize::deserialize(archived, &mut rkyv::Infallible)?;
+
+        let key = AdminKey::load_default()?;
+        if let Err(e) = key.verify(&grant, name) {
+            warn!(
+                target: "audit",
+                "Rejected impersonation of '{}' by '{}': {}", grant.principal, grant.operator, e
+            );
+            return Err(e);
+        }
+
+        info!(
+            target: "audit",
+            "'{}' impersonating '{}' ({})", grant.operator, grant.principal, grant.reason
+        );
+        Ok(Caller {
+            principal: grant.principal,
+            organism: grant.organism,
+            impersonated_by: Some(grant.operator),
+        })
+    }
 }
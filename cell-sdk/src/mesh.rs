@@ -22,6 +22,17 @@ impl MeshBuilder {
         guard.insert(cell.to_string(), deps.into_iter().collect());
     }
 
+    /// Dependencies `cell` declared with [`Self::declare_dependencies`].
+    pub async fn dependencies(cell: &str) -> Vec<String> {
+        let guard = get_dependency_map().read().await;
+        let mut deps: Vec<String> = guard
+            .get(cell)
+            .map(|d| d.iter().cloned().collect())
+            .unwrap_or_default();
+        deps.sort();
+        deps
+    }
+
     /// Announce presence by creating a lockfile or registry entry.
     pub async fn announce_self(name: &str) -> Result<()> {
         let cwd = std::env::current_dir()?;
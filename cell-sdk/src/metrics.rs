@@ -1,10 +1,87 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
 
-use std::sync::Arc;
+//! Request metrics.
+//!
+//! `#[handler]` dispatch reports every call to [`record`], which keeps call
+//! and error counts and a latency histogram per method. OPS `Metrics` returns
+//! them with the cell's connection totals; OPS `Prometheus` renders the same
+//! numbers as Prometheus text (see `cell_model::metrics`). [`Metrics`] is the
+//! client-side counterpart for a single connection.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration};
+use anyhow::{anyhow, bail, Result};
 use cell_macros::protein;
+use cell_model::metrics::{latency_bucket, MethodMetrics, LATENCY_BUCKETS_US};
+use cell_model::ops::{OpsRequest, OpsResponse};
+use crate::state::ops;
+
+static METHODS: Mutex<BTreeMap<String, MethodMetrics>> = Mutex::new(BTreeMap::new());
+
+/// Called by generated dispatch after each handler call.
+pub fn record(method: &str, elapsed: Duration, ok: bool) {
+    let mut methods = METHODS.lock().unwrap();
+    let micros = elapsed.as_micros() as u64;
+    match methods.get_mut(method) {
+        Some(m) => m.record(micros, ok),
+        None => {
+            let mut m = MethodMetrics::new(method);
+            m.record(micros, ok);
+            methods.insert(method.to_string(), m);
+        }
+    }
+}
+
+/// Everything recorded in this cell, for OPS `Metrics`.
+pub fn report() -> cell_model::ops::MetricsSnapshot {
+    let methods: Vec<MethodMetrics> = METHODS.lock().unwrap().values().cloned().collect();
+    let mut histogram = vec![0; LATENCY_BUCKETS_US.len() + 1];
+    for m in &methods {
+        for (total, count) in histogram.iter_mut().zip(&m.latency_histogram) {
+            *total += count;
+        }
+    }
+    let connections = crate::inspect::report().connections;
+    let requests_total = methods.iter().map(|m| m.calls).sum();
+    let requests_failed = methods.iter().map(|m| m.errors).sum();
+    cell_model::ops::MetricsSnapshot {
+        requests_total,
+        requests_success: requests_total - requests_failed,
+        requests_failed,
+        latency_histogram: histogram,
+        connections_active: connections.len() as u64,
+        bytes_sent: connections.iter().map(|c| c.bytes_sent).sum(),
+        bytes_received: connections.iter().map(|c| c.bytes_received).sum(),
+        methods,
+    }
+}
+
+/// [`report`] as Prometheus text, for OPS `Prometheus`.
+pub fn prometheus() -> String {
+    let cell = &crate::identity::Identity::get().cell_name;
+    cell_model::metrics::prometheus(cell, &report())
+}
+
+/// Ask a running cell for its request metrics.
+pub async fn fetch(cell_name: &str) -> Result<cell_model::ops::MetricsSnapshot> {
+    match ops(cell_name, &OpsRequest::Metrics).await? {
+        OpsResponse::Metrics(snapshot) => Ok(snapshot),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
+
+/// Scrape a running cell: its metrics in the Prometheus text format.
+pub async fn scrape(cell_name: &str) -> Result<String> {
+    match ops(cell_name, &OpsRequest::Prometheus).await? {
+        OpsResponse::Prometheus { text } => Ok(text),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
 
 pub struct Metrics {
     // Request metrics
@@ -49,19 +126,7 @@ impl Metrics {
             self.requests_failed.fetch_add(1, Ordering::Relaxed);
         }
 
-        let micros = duration.as_micros() as u64;
-        let bucket = match micros {
-            0..=1_000 => 0,
-            1_001..=5_000 => 1,
-            5_001..=10_000 => 2,
-            10_001..=50_000 => 3,
-            50_001..=100_000 => 4,
-            100_001..=500_000 => 5,
-            500_001..=1_000_000 => 6,
-            1_000_001..=5_000_000 => 7,
-            5_000_001..=10_000_000 => 8,
-            _ => 9,
-        };
+        let bucket = latency_bucket(duration.as_micros() as u64);
         self.latency_buckets[bucket].fetch_add(1, Ordering::Relaxed);
     }
 
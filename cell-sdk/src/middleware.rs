@@ -0,0 +1,412 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/middleware.rs
+//! The Membrane's middleware chain.
+//!
+//! Cells attach middleware plugin cells with `[[middleware]]` in Cell.toml
+//! (see `cell_model::middleware` for the format and the guest ABI). Before
+//! serving, the Membrane fetches each plugin's WASM module over OPS and
+//! instantiates it here; every application and extension request then goes
+//! through the chain before it reaches a handler. Middleware is where auth
+//! and filtering live, so nothing gets past it by accident: a cell whose
+//! plugins cannot be fetched does not start, and a module that fails
+//! refuses the request.
+//!
+//! A plugin cell hands out its module with [`provide`]:
+//!
+//! ```ignore
+//! cell_sdk::middleware::provide(include_bytes!("../waf.wasm").to_vec());
+//! ```
+//!
+//! Modules run with the `wasm` feature, each in a store of its own without
+//! imports, so they see nothing of the cell but what they are shown. A call
+//! gets `CELL_MIDDLEWARE_FUEL` instructions (default 10 million) and a module
+//! at most `CELL_MIDDLEWARE_MEMORY_MB` (default 64) of memory; one that traps
+//! is instantiated afresh for the next request. The Membrane runs the chain
+//! on the blocking pool, so a module using up its fuel holds up nothing but
+//! the request it is looking at. Cells can also put native links in the
+//! chain with [`install`].
+
+use crate::error::{CellError, ErrorContext};
+use crate::state::ops;
+use anyhow::{bail, Context, Result};
+use cell_core::Codec;
+use cell_model::manifest::CellManifest;
+pub use cell_model::middleware::{
+    MiddlewareOutcome, MiddlewareRef, MiddlewareRequest, Verdict, MIDDLEWARE_ABI,
+};
+use cell_model::ops::{OpsRequest, OpsResponse};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Attempts at fetching a plugin's module before the cell gives up starting
+const FETCH_ATTEMPTS: u32 = 10;
+
+/// One link of the chain
+pub trait Middleware: Send + Sync {
+    /// Let `request` on, or refuse it
+    fn request(&self, request: &MiddlewareRequest) -> Result<Verdict>;
+
+    /// Hear how a request that got through went
+    fn outcome(&self, _outcome: &MiddlewareOutcome) {}
+}
+
+struct Link {
+    name: String,
+    bodies: bool,
+    middleware: Arc<dyn Middleware>,
+}
+
+static CHAIN: RwLock<Vec<Link>> = RwLock::new(Vec::new());
+
+/// The module this cell serves as a plugin
+static MODULE: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+
+/// Serve `wasm` to cells that attach this one as middleware
+pub fn provide(wasm: Vec<u8>) {
+    *MODULE.lock().unwrap() = Some(wasm);
+}
+
+/// OPS `MiddlewareModule`
+pub(crate) fn module() -> OpsResponse {
+    match MODULE.lock().unwrap().clone() {
+        Some(wasm) => OpsResponse::MiddlewareModule {
+            abi: MIDDLEWARE_ABI,
+            wasm,
+        },
+        None => OpsResponse::Error {
+            message: "Not a middleware plugin".to_string(),
+        },
+    }
+}
+
+/// Append `middleware` to the chain as `name`. With `bodies` it is shown
+/// UTF-8 payloads.
+pub fn install(name: &str, bodies: bool, middleware: Arc<dyn Middleware>) {
+    info!("Middleware '{}' attached", name);
+    CHAIN.write().unwrap().push(Link {
+        name: name.to_string(),
+        bodies,
+        middleware,
+    });
+}
+
+/// Whether any middleware is attached; the Membrane skips the chain if not
+pub fn active() -> bool {
+    !CHAIN.read().unwrap().is_empty()
+}
+
+/// Attach the plugins in `./Cell.toml`. Fails if one cannot be loaded, or
+/// if Cell.toml does not parse, so a cell never serves without the
+/// middleware it declared.
+pub async fn attach_declared() -> Result<()> {
+    let Ok(content) = std::fs::read_to_string("Cell.toml") else {
+        return Ok(());
+    };
+    let declared = toml::from_str::<CellManifest>(&content)
+        .context("Failed to parse Cell.toml; not serving without its middleware")?
+        .middleware;
+    for plugin in declared {
+        let wasm = fetch(&plugin.cell).await?;
+        install(&plugin.cell, plugin.bodies, load(&plugin, &wasm)?);
+    }
+    Ok(())
+}
+
+/// The module `cell` serves, waiting for it to come up
+pub async fn fetch(cell: &str) -> Result<Vec<u8>> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match ops(cell, &OpsRequest::MiddlewareModule).await {
+            Ok(OpsResponse::MiddlewareModule { abi, wasm }) if abi == MIDDLEWARE_ABI => {
+                return Ok(wasm)
+            }
+            Ok(OpsResponse::MiddlewareModule { abi, .. }) => bail!(
+                "Middleware '{}' was built for ABI {}, this cell speaks {}",
+                cell,
+                abi,
+                MIDDLEWARE_ABI
+            ),
+            Ok(OpsResponse::Error { message }) => bail!("{}: {}", cell, message),
+            Ok(_) => bail!("Unexpected OPS response from {}", cell),
+            Err(e) if attempt < FETCH_ATTEMPTS => {
+                warn!("Middleware '{}' not reachable yet: {}", cell, e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            Err(e) => return Err(e.context(format!("Middleware '{}' unreachable", cell))),
+        }
+    }
+}
+
+/// Instantiate a plugin's module
+#[cfg(feature = "wasm")]
+pub fn load(plugin: &MiddlewareRef, wasm: &[u8]) -> Result<Arc<dyn Middleware>> {
+    Ok(Arc::new(wasm::Module::load(plugin, wasm)?))
+}
+
+#[cfg(not(feature = "wasm"))]
+pub fn load(plugin: &MiddlewareRef, _wasm: &[u8]) -> Result<Arc<dyn Middleware>> {
+    bail!(
+        "Running middleware '{}' needs cell-sdk's wasm feature",
+        plugin.cell
+    )
+}
+
+/// Run `request` through the chain. `payload` is shown to links that take
+/// bodies.
+pub fn check(request: &MiddlewareRequest, payload: &[u8]) -> Result<(), ErrorContext> {
+    let body = std::str::from_utf8(payload).ok();
+    for link in CHAIN.read().unwrap().iter() {
+        let verdict = if link.bodies && body.is_some() {
+            let shown = MiddlewareRequest {
+                body: body.map(str::to_string),
+                ..request.clone()
+            };
+            link.middleware.request(&shown)
+        } else {
+            link.middleware.request(request)
+        };
+        match verdict {
+            Ok(Verdict::Continue) => {}
+            Ok(Verdict::Reject { message }) => {
+                return Err(ErrorContext::new(CellError::AccessDenied)
+                    .with_message(format!(
+                        "Refused by middleware '{}': {}",
+                        link.name, message
+                    ))
+                    .with_operation(link.name.as_str()))
+            }
+            Err(e) => {
+                warn!("Middleware '{}' failed: {:#}", link.name, e);
+                return Err(ErrorContext::new(CellError::HandlerFailed)
+                    .with_message(format!("Middleware '{}' failed", link.name))
+                    .with_operation(link.name.as_str()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// [`check`] on the blocking pool, for the Membrane's request path
+pub(crate) async fn screen(
+    request: MiddlewareRequest,
+    payload: Vec<u8>,
+) -> Result<(), ErrorContext> {
+    tokio::task::spawn_blocking(move || check(&request, &payload))
+        .await
+        .unwrap_or_else(|e| {
+            Err(ErrorContext::new(CellError::HandlerFailed)
+                .with_message(format!("Middleware panicked: {}", e)))
+        })
+}
+
+/// Tell every link how a request went
+pub fn outcome(outcome: &MiddlewareOutcome) {
+    for link in CHAIN.read().unwrap().iter() {
+        link.middleware.outcome(outcome);
+    }
+}
+
+/// Tell every link how the request it was shown as `shown` went, on the
+/// blocking pool and without waiting for them
+pub(crate) fn finished(shown: MiddlewareRequest, elapsed: Duration, ok: bool) {
+    let finished = MiddlewareOutcome {
+        cell: shown.cell,
+        channel: shown.channel,
+        principal: shown.principal,
+        elapsed_us: elapsed.as_micros() as u64,
+        ok,
+    };
+    tokio::task::spawn_blocking(move || outcome(&finished));
+}
+
+/// The request the Membrane shows the chain, if there is one
+pub(crate) fn request(
+    cell: &str,
+    channel: u8,
+    principal: &str,
+    peer: &str,
+    codec: Codec,
+    size: usize,
+) -> MiddlewareRequest {
+    MiddlewareRequest {
+        cell: cell.to_string(),
+        channel,
+        principal: principal.to_string(),
+        peer: peer.to_string(),
+        codec: codec.name().to_string(),
+        size,
+        body: None,
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod wasm {
+    use super::{Middleware, MiddlewareOutcome, MiddlewareRef, MiddlewareRequest, Verdict};
+    use anyhow::{anyhow, Context, Result};
+    use std::sync::Mutex;
+    use tracing::warn;
+    use wasmtime::{
+        Config, Engine, InstancePre, Linker, Memory, Store, StoreLimits, StoreLimitsBuilder,
+        TypedFunc,
+    };
+
+    fn env_u64(name: &str, default: u64) -> u64 {
+        std::env::var(name)
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(default)
+    }
+
+    /// A plugin's module, instantiated
+    pub struct Module {
+        name: String,
+        pre: InstancePre<StoreLimits>,
+        config: Vec<u8>,
+        fuel: u64,
+        memory_limit: usize,
+        /// `None` after a trap, until the next call instantiates again
+        guest: Mutex<Option<Guest>>,
+    }
+
+    struct Guest {
+        store: Store<StoreLimits>,
+        memory: Memory,
+        alloc: TypedFunc<i32, i32>,
+        request: TypedFunc<(i32, i32), i64>,
+        response: Option<TypedFunc<(i32, i32), ()>>,
+    }
+
+    impl Module {
+        pub fn load(plugin: &MiddlewareRef, wasm: &[u8]) -> Result<Self> {
+            let mut config = Config::new();
+            config.consume_fuel(true);
+            let engine = Engine::new(&config)?;
+            let module = wasmtime::Module::new(&engine, wasm)
+                .with_context(|| format!("Invalid module from '{}'", plugin.cell))?;
+            // Nothing to import: the module only computes
+            let pre = Linker::new(&engine).instantiate_pre(&module)?;
+            let loaded = Self {
+                name: plugin.cell.clone(),
+                pre,
+                config: serde_json::to_vec(&plugin.config)?,
+                fuel: env_u64("CELL_MIDDLEWARE_FUEL", 10_000_000),
+                memory_limit: (env_u64("CELL_MIDDLEWARE_MEMORY_MB", 64) as usize) << 20,
+                guest: Mutex::new(None),
+            };
+            // Fail at start, not on the first request
+            *loaded.guest.lock().unwrap() = Some(loaded.instantiate()?);
+            Ok(loaded)
+        }
+
+        fn instantiate(&self) -> Result<Guest> {
+            let limits = StoreLimitsBuilder::new()
+                .memory_size(self.memory_limit)
+                .build();
+            let mut store = Store::new(self.pre.module().engine(), limits);
+            store.limiter(|limits| limits);
+            store.set_fuel(self.fuel)?;
+            let instance = self.pre.instantiate(&mut store)?;
+            let memory = instance
+                .get_memory(&mut store, "memory")
+                .ok_or_else(|| anyhow!("Middleware '{}' exports no memory", self.name))?;
+            let alloc = instance.get_typed_func(&mut store, "cell_alloc")?;
+            let request = instance.get_typed_func(&mut store, "cell_middleware_request")?;
+            let response = instance
+                .get_typed_func(&mut store, "cell_middleware_response")
+                .ok();
+            let mut guest = Guest {
+                store,
+                memory,
+                alloc,
+                request,
+                response,
+            };
+            if let Ok(init) =
+                instance.get_typed_func::<(i32, i32), ()>(&mut guest.store, "cell_middleware_init")
+            {
+                let (ptr, len) = guest.write(&self.config)?;
+                init.call(&mut guest.store, (ptr, len))?;
+            }
+            Ok(guest)
+        }
+
+        /// Run `call` on the guest, instantiating it first if the last call
+        /// trapped
+        fn with_guest<T>(&self, call: impl FnOnce(&mut Guest) -> Result<T>) -> Result<T> {
+            let mut slot = self.guest.lock().unwrap();
+            if slot.is_none() {
+                *slot = Some(self.instantiate()?);
+            }
+            let guest = slot.as_mut().expect("instantiated above");
+            guest.store.set_fuel(self.fuel)?;
+            let result = call(guest);
+            if result.is_err() {
+                // Its memory may be in any state now
+                *slot = None;
+            }
+            result
+        }
+    }
+
+    impl Guest {
+        /// Copy `bytes` into guest memory
+        fn write(&mut self, bytes: &[u8]) -> Result<(i32, i32)> {
+            let len = i32::try_from(bytes.len())?;
+            let ptr = self.alloc.call(&mut self.store, len)?;
+            self.memory
+                .write(&mut self.store, ptr as u32 as usize, bytes)?;
+            Ok((ptr, len))
+        }
+
+        /// Copy out the `(ptr << 32) | len` range a guest function returned
+        fn read(&self, packed: i64) -> Result<Vec<u8>> {
+            let ptr = (packed as u64 >> 32) as usize;
+            let len = (packed as u64 & 0xffff_ffff) as usize;
+            // The guest picks `len`: check it before allocating for it
+            let size = self.memory.data_size(&self.store);
+            if ptr.checked_add(len).is_none_or(|end| end > size) {
+                return Err(anyhow!(
+                    "Result at {}+{} is outside the module's {} bytes of memory",
+                    ptr,
+                    len,
+                    size
+                ));
+            }
+            let mut out = vec![0; len];
+            self.memory.read(&self.store, ptr, &mut out)?;
+            Ok(out)
+        }
+    }
+
+    impl Middleware for Module {
+        fn request(&self, request: &MiddlewareRequest) -> Result<Verdict> {
+            let input = serde_json::to_vec(request)?;
+            let output = self.with_guest(|guest| {
+                let (ptr, len) = guest.write(&input)?;
+                let packed = guest.request.call(&mut guest.store, (ptr, len))?;
+                guest.read(packed)
+            })?;
+            serde_json::from_slice(&output)
+                .with_context(|| format!("Invalid verdict from '{}'", self.name))
+        }
+
+        fn outcome(&self, outcome: &MiddlewareOutcome) {
+            let result = serde_json::to_vec(outcome)
+                .map_err(anyhow::Error::from)
+                .and_then(|input| {
+                    self.with_guest(|guest| {
+                        let Some(response) = guest.response.clone() else {
+                            return Ok(());
+                        };
+                        let (ptr, len) = guest.write(&input)?;
+                        response.call(&mut guest.store, (ptr, len))
+                    })
+                });
+            if let Err(e) = result {
+                warn!("Middleware '{}' failed on an outcome: {:#}", self.name, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/migrations.rs
+//! Startup migrations for stateful cells.
+//!
+//! A cell lists its migrations in version order and runs them against its
+//! store before calling `serve()`. The store records applied versions itself
+//! (a `_migrations` table, say), written in the same transaction as each
+//! migration, so the record can never disagree with the data:
+//!
+//! ```ignore
+//! const MIGRATIONS: &[Migration<Schema>] = &[
+//!     Migration { version: 1, name: "create-state", up: |db| { db.execute(CREATE, [])?; Ok(()) } },
+//! ];
+//! migrations::run_with(&mut schema, MIGRATIONS)?;
+//! ```
+
+use anyhow::{bail, Context, Result};
+use tracing::info;
+
+/// One schema or data upgrade. `up` receives the store being migrated.
+pub struct Migration<S: ?Sized> {
+    pub version: u32,
+    pub name: &'static str,
+    pub up: fn(&mut S) -> Result<()>,
+}
+
+/// A store that keeps its own record of applied migrations
+pub trait Migratable {
+    /// Versions recorded as applied to this store
+    fn applied(&mut self) -> Result<Vec<u32>>;
+
+    /// Run `migration.up` and record its version in one transaction: after
+    /// an error, neither its changes nor its record may remain.
+    fn apply(&mut self, migration: &Migration<Self>) -> Result<()>;
+}
+
+/// Run pending migrations against `store`, in order. Returns the versions
+/// applied now. Stops at the first failure; migrations before it stay applied.
+pub fn run_with<S: Migratable + ?Sized>(
+    store: &mut S,
+    migrations: &[Migration<S>],
+) -> Result<Vec<u32>> {
+    if let Some(w) = migrations.windows(2).find(|w| w[0].version >= w[1].version) {
+        bail!(
+            "Migrations out of order: {} ({}) before {} ({})",
+            w[0].version,
+            w[0].name,
+            w[1].version,
+            w[1].name
+        );
+    }
+
+    let applied = store
+        .applied()
+        .context("Failed to read the applied migrations")?;
+
+    // State written by a newer build: running older code on it is unsafe
+    let known = migrations.last().map(|m| m.version).unwrap_or(0);
+    if let Some(&latest) = applied.iter().max() {
+        if latest > known {
+            bail!(
+                "State is at migration {}, newer than this build knows ({})",
+                latest,
+                known
+            );
+        }
+    }
+
+    let mut ran = Vec::new();
+    for m in migrations.iter().filter(|m| !applied.contains(&m.version)) {
+        info!("[Migrations] Applying {} ({})", m.version, m.name);
+        store
+            .apply(m)
+            .with_context(|| format!("Migration {} ({}) failed", m.version, m.name))?;
+        ran.push(m.version);
+    }
+    Ok(ran)
+}
@@ -29,10 +29,20 @@ impl Organism {
         fs::create_dir_all(&io_dir)?;
 
         for (name, config) in &manifest.neighbors {
-            let rel_path_str = match config {
-                cell_model::manifest::NeighborConfig::Path(p) => p,
-                cell_model::manifest::NeighborConfig::Detailed { path, .. } => path,
-            };
+            // Remote neighbor: synapses dial the address in `.cell/neighbors/<name>/tcp`
+            if let Some(addr) = config.tcp_addr() {
+                let link_dir = neighbors_dir.join(name);
+                fs::create_dir_all(&link_dir)?;
+                fs::remove_file(link_dir.join("tx")).ok();
+                fs::write(link_dir.join("tcp"), addr)?;
+                tracing::info!(
+                    "[Organogenesis] Linked neighbor '{}' -> tcp://{}",
+                    name,
+                    addr
+                );
+                continue;
+            }
+            let rel_path_str = config.path();
 
             let target_root = cwd.join(rel_path_str);
 
@@ -51,6 +61,8 @@ impl Organism {
 
             fs::create_dir_all(&link_dir)?;
 
+            fs::remove_file(link_dir.join("tcp")).ok();
+
             let my_tx_link = link_dir.join("tx");
             let target_in_socket = target_io.join("in");
 
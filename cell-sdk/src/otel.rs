@@ -0,0 +1,524 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/otel.rs
+//! OpenTelemetry export (feature `otel`).
+//!
+//! [`init`] replaces the usual `tracing_subscriber::fmt().init()`: it keeps the
+//! console output and adds an OTLP exporter for spans and for the request
+//! metrics the membrane records. The exporter is configured per organism in
+//! `~/.cell/organisms/<organism>/otel.toml`:
+//!
+//! ```toml
+//! endpoint = "http://collector:4317"
+//! protocol = "grpc"            # or "http" (protobuf, usually port 4318)
+//! export_interval_secs = 30
+//!
+//! [headers]                    # http only
+//! authorization = "Bearer ..."
+//!
+//! [resource]                   # extra resource attributes
+//! "deployment.environment" = "prod"
+//!
+//! [sampling]
+//! ratio = 0.01                 # share of traces exported up front
+//! slow_ms = 250                # also keep traces at least this slow
+//! errors = true                # also keep traces with a failed span
+//! max_pending = 4096           # unsampled traces held until their root ends
+//! ```
+//!
+//! `OTEL_EXPORTER_OTLP_ENDPOINT` overrides the endpoint. Without either, no
+//! exporter is installed.
+//!
+//! Sampling is two-stage. The head decision keeps `ratio` of traces by trace
+//! id. Spans of the other traces are still recorded but held back; when the
+//! local root (`cell.request` in the Membrane, or an outermost `cell.call` in
+//! a Synapse) ends, the trace is exported if it was slow or failed and
+//! dropped otherwise. At most `max_pending` traces are held, oldest evicted
+//! first, so memory stays bounded under load.
+
+use crate::identity::Identity;
+use anyhow::{bail, Context, Result};
+use opentelemetry::metrics::{Counter, Histogram};
+use opentelemetry::trace::{
+    Link, SamplingDecision, SamplingResult, SpanContext, SpanId, SpanKind, Status, TraceContextExt,
+    TraceFlags, TraceId, TraceState, TracerProvider as _,
+};
+use opentelemetry::{global, KeyValue};
+use opentelemetry_otlp::{MetricExporter, SpanExporter, WithExportConfig, WithHttpConfig};
+use opentelemetry_sdk::export::trace::SpanData;
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+use opentelemetry_sdk::trace::{
+    BatchSpanProcessor, Sampler, ShouldSample, Span, SpanProcessor, TracerProvider,
+};
+use opentelemetry_sdk::{runtime, Resource};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    #[default]
+    Grpc,
+    Http,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OtelConfig {
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub protocol: Protocol,
+    #[serde(default = "default_interval")]
+    pub export_interval_secs: u64,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub resource: BTreeMap<String, String>,
+    #[serde(default)]
+    pub sampling: SamplingConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SamplingConfig {
+    /// Share of traces kept by the head decision, 0.0..=1.0
+    pub ratio: f64,
+    /// Traces whose local root took at least this long are kept regardless
+    pub slow_ms: Option<u64>,
+    /// Traces with a failed span are kept regardless
+    pub errors: bool,
+    pub max_pending: usize,
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            ratio: 1.0,
+            slow_ms: None,
+            errors: true,
+            max_pending: 4096,
+        }
+    }
+}
+
+fn default_interval() -> u64 {
+    30
+}
+
+impl Default for OtelConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            protocol: Protocol::default(),
+            export_interval_secs: default_interval(),
+            headers: BTreeMap::new(),
+            resource: BTreeMap::new(),
+            sampling: SamplingConfig::default(),
+        }
+    }
+}
+
+impl OtelConfig {
+    pub fn path(organism: &str) -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".cell/organisms")
+            .join(organism)
+            .join("otel.toml")
+    }
+
+    /// The organism's config, if any, with the environment override applied.
+    pub fn load(organism: &str) -> Result<Self> {
+        let mut config = Self::from_file(&Self::path(organism))?;
+        if let Ok(endpoint) = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT") {
+            config.endpoint = Some(endpoint);
+        }
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = std::fs::read_to_string(path)?;
+        toml::from_str(&raw).with_context(|| format!("Invalid OpenTelemetry config {:?}", path))
+    }
+}
+
+/// Resource attributes identifying this instance of `cell_name`.
+pub fn resource_attributes(cell_name: &str, config: &OtelConfig) -> Vec<KeyValue> {
+    let identity = Identity::get();
+    let version = version_hash();
+    let mut attributes = vec![
+        KeyValue::new("service.name", cell_name.to_string()),
+        KeyValue::new("service.namespace", identity.organism.clone()),
+        KeyValue::new("service.instance.id", identity.node_id.to_string()),
+        KeyValue::new("service.version", version.clone()),
+        KeyValue::new("cell.name", cell_name.to_string()),
+        KeyValue::new("cell.organism", identity.organism.clone()),
+        KeyValue::new("cell.node_id", identity.node_id.to_string()),
+        KeyValue::new("cell.version_hash", version),
+        KeyValue::new("cell.sdk.version", env!("CARGO_PKG_VERSION")),
+    ];
+    attributes.extend(
+        config
+            .resource
+            .iter()
+            .map(|(k, v)| KeyValue::new(k.clone(), v.clone())),
+    );
+    attributes
+}
+
+/// Hash of the running binary, so traces tell builds apart.
+pub fn version_hash() -> String {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| {
+        std::env::current_exe()
+            .and_then(std::fs::read)
+            .map(|bytes| blake3::hash(&bytes).to_hex()[..16].to_string())
+            .unwrap_or_else(|_| "unknown".to_string())
+    })
+    .clone()
+}
+
+/// Flushes and stops the exporters when dropped; keep it alive in `main`.
+#[must_use = "exporters stop when the guard is dropped"]
+pub struct OtelGuard {
+    tracer: Option<TracerProvider>,
+    meter: Option<SdkMeterProvider>,
+}
+
+impl Drop for OtelGuard {
+    fn drop(&mut self) {
+        if let Some(tracer) = self.tracer.take() {
+            if let Err(e) = tracer.shutdown() {
+                eprintln!("OpenTelemetry trace shutdown failed: {}", e);
+            }
+        }
+        if let Some(meter) = self.meter.take() {
+            if let Err(e) = meter.shutdown() {
+                eprintln!("OpenTelemetry metrics shutdown failed: {}", e);
+            }
+        }
+    }
+}
+
+/// Install logging plus, when configured, OTLP export for `cell_name`.
+pub fn init(cell_name: &str) -> Result<OtelGuard> {
+    let config = OtelConfig::load(&Identity::get().organism)?;
+    init_with(cell_name, &config)
+}
+
+pub fn init_with(cell_name: &str, config: &OtelConfig) -> Result<OtelGuard> {
+    let registry = tracing_subscriber::registry()
+        .with(crate::logging::filter_layer())
+        .with(tracing_subscriber::fmt::layer());
+
+    let Some(endpoint) = &config.endpoint else {
+        registry.try_init()?;
+        return Ok(OtelGuard {
+            tracer: None,
+            meter: None,
+        });
+    };
+
+    let resource = Resource::new(resource_attributes(cell_name, config));
+    let (spans, metrics) = match config.protocol {
+        Protocol::Grpc => {
+            if !config.headers.is_empty() {
+                bail!("OTLP headers are only supported with protocol = \"http\"");
+            }
+            (
+                SpanExporter::builder()
+                    .with_tonic()
+                    .with_endpoint(endpoint)
+                    .build()?,
+                MetricExporter::builder()
+                    .with_tonic()
+                    .with_endpoint(endpoint)
+                    .build()?,
+            )
+        }
+        Protocol::Http => {
+            let headers: std::collections::HashMap<String, String> =
+                config.headers.clone().into_iter().collect();
+            let base = endpoint.trim_end_matches('/');
+            (
+                SpanExporter::builder()
+                    .with_http()
+                    .with_endpoint(format!("{}/v1/traces", base))
+                    .with_headers(headers.clone())
+                    .build()?,
+                MetricExporter::builder()
+                    .with_http()
+                    .with_endpoint(format!("{}/v1/metrics", base))
+                    .with_headers(headers)
+                    .build()?,
+            )
+        }
+    };
+
+    let batch = BatchSpanProcessor::builder(spans, runtime::Tokio).build();
+    let tracer_provider = TracerProvider::builder()
+        .with_span_processor(TailSampler::new(batch, config.sampling.clone()))
+        .with_sampler(HeadSampler::new(config.sampling.ratio))
+        .with_resource(resource.clone())
+        .build();
+    let tracer = tracer_provider.tracer("cell-sdk");
+    global::set_tracer_provider(tracer_provider.clone());
+
+    let reader = PeriodicReader::builder(metrics, runtime::Tokio)
+        .with_interval(Duration::from_secs(config.export_interval_secs.max(1)))
+        .build();
+    let meter_provider = SdkMeterProvider::builder()
+        .with_reader(reader)
+        .with_resource(resource)
+        .build();
+    global::set_meter_provider(meter_provider.clone());
+
+    registry
+        .with(tracing_opentelemetry::layer().with_tracer(tracer))
+        .try_init()?;
+    tracing::info!(
+        cell = cell_name,
+        endpoint = endpoint.as_str(),
+        "OpenTelemetry export enabled"
+    );
+
+    Ok(OtelGuard {
+        tracer: Some(tracer_provider),
+        meter: Some(meter_provider),
+    })
+}
+
+pub use crate::trace::{CALL_SPAN, REQUEST_SPAN};
+
+/// Head stage: keeps `ratio` of new traces and follows the parent otherwise.
+/// Traces it passes on are recorded anyway so [`TailSampler`] can rescue them.
+#[derive(Debug, Clone)]
+pub struct HeadSampler {
+    ratio: Sampler,
+}
+
+impl HeadSampler {
+    pub fn new(ratio: f64) -> Self {
+        Self {
+            ratio: Sampler::TraceIdRatioBased(ratio.clamp(0.0, 1.0)),
+        }
+    }
+}
+
+impl ShouldSample for HeadSampler {
+    fn should_sample(
+        &self,
+        parent_context: Option<&opentelemetry::Context>,
+        trace_id: TraceId,
+        name: &str,
+        span_kind: &SpanKind,
+        attributes: &[KeyValue],
+        links: &[Link],
+    ) -> SamplingResult {
+        let parent = parent_context
+            .map(|cx| cx.span().span_context().clone())
+            .filter(SpanContext::is_valid);
+        let sampled = match &parent {
+            Some(parent) => parent.is_sampled(),
+            None => {
+                let head = self.ratio.should_sample(
+                    parent_context,
+                    trace_id,
+                    name,
+                    span_kind,
+                    attributes,
+                    links,
+                );
+                head.decision == SamplingDecision::RecordAndSample
+            }
+        };
+        SamplingResult {
+            decision: match sampled {
+                true => SamplingDecision::RecordAndSample,
+                false => SamplingDecision::RecordOnly,
+            },
+            attributes: Vec::new(),
+            trace_state: parent.map(|p| p.trace_state().clone()).unwrap_or_default(),
+        }
+    }
+}
+
+/// Tail stage: forwards head-sampled spans and holds the rest until their
+/// local root ends, then forwards the trace only if it was slow or failed.
+#[derive(Debug)]
+pub struct TailSampler<P> {
+    inner: P,
+    policy: SamplingConfig,
+    pending: Mutex<PendingTraces>,
+}
+
+#[derive(Debug, Default)]
+struct PendingTraces {
+    spans: HashMap<TraceId, Vec<SpanData>>,
+    order: VecDeque<TraceId>,
+}
+
+impl<P: SpanProcessor> TailSampler<P> {
+    pub fn new(inner: P, policy: SamplingConfig) -> Self {
+        Self {
+            inner,
+            policy,
+            pending: Mutex::new(PendingTraces::default()),
+        }
+    }
+
+    fn failed(span: &SpanData) -> bool {
+        matches!(span.status, Status::Error { .. })
+    }
+
+    fn slow(&self, span: &SpanData) -> bool {
+        let Some(slow_ms) = self.policy.slow_ms else {
+            return false;
+        };
+        span.end_time
+            .duration_since(span.start_time)
+            .map(|d| d.as_millis() >= slow_ms as u128)
+            .unwrap_or(false)
+    }
+
+    /// The exporter only takes sampled spans.
+    fn export(&self, mut span: SpanData) {
+        let cx = &span.span_context;
+        span.span_context = SpanContext::new(
+            cx.trace_id(),
+            cx.span_id(),
+            cx.trace_flags().with_sampled(true),
+            cx.is_remote(),
+            cx.trace_state().clone(),
+        );
+        self.inner.on_end(span);
+    }
+}
+
+impl<P: SpanProcessor> SpanProcessor for TailSampler<P> {
+    fn on_start(&self, span: &mut Span, cx: &opentelemetry::Context) {
+        self.inner.on_start(span, cx);
+    }
+
+    fn on_end(&self, span: SpanData) {
+        if span
+            .span_context
+            .trace_flags()
+            .contains(TraceFlags::SAMPLED)
+        {
+            return self.inner.on_end(span);
+        }
+        let trace_id = span.span_context.trace_id();
+        let root = span.parent_span_id == SpanId::INVALID || span.name == REQUEST_SPAN;
+
+        let mut pending = self.pending.lock().unwrap();
+        if !root {
+            if !pending.spans.contains_key(&trace_id) {
+                if pending.order.len() >= self.policy.max_pending {
+                    if let Some(oldest) = pending.order.pop_front() {
+                        pending.spans.remove(&oldest);
+                    }
+                }
+                pending.order.push_back(trace_id);
+            }
+            pending.spans.entry(trace_id).or_default().push(span);
+            return;
+        }
+
+        let held = pending.spans.remove(&trace_id).unwrap_or_default();
+        pending.order.retain(|t| *t != trace_id);
+        drop(pending);
+
+        let keep = self.slow(&span)
+            || (self.policy.errors && (Self::failed(&span) || held.iter().any(Self::failed)));
+        if keep {
+            for span in held.into_iter().chain(std::iter::once(span)) {
+                self.export(span);
+            }
+        }
+    }
+
+    fn force_flush(&self) -> opentelemetry::trace::TraceResult<()> {
+        self.inner.force_flush()
+    }
+
+    fn shutdown(&self) -> opentelemetry::trace::TraceResult<()> {
+        self.inner.shutdown()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource);
+    }
+}
+
+/// Make `span` a child of the caller's span, in another cell.
+pub(crate) fn set_remote_parent(span: &tracing::Span, parent: crate::trace::TraceContext) {
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+    let cx = SpanContext::new(
+        TraceId::from_bytes(parent.trace_id),
+        SpanId::from_bytes(parent.span_id),
+        TraceFlags::new(parent.flags),
+        true,
+        TraceState::default(),
+    );
+    span.set_parent(opentelemetry::Context::new().with_remote_span_context(cx));
+}
+
+/// The current span's ids, if it is recorded.
+pub(crate) fn current_span() -> Option<crate::trace::TraceContext> {
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+    let cx = tracing::Span::current().context();
+    let span = cx.span();
+    let sc = span.span_context();
+    sc.is_valid().then(|| crate::trace::TraceContext {
+        trace_id: sc.trace_id().to_bytes(),
+        span_id: sc.span_id().to_bytes(),
+        flags: sc.trace_flags().to_u8(),
+    })
+}
+
+/// Mark `span` failed so tail sampling keeps its trace.
+pub(crate) fn mark_failed(span: &tracing::Span) {
+    span.record("otel.status_code", "ERROR");
+}
+
+struct Instruments {
+    requests: Counter<u64>,
+    duration: Histogram<f64>,
+}
+
+fn instruments() -> &'static Instruments {
+    static INSTRUMENTS: OnceLock<Instruments> = OnceLock::new();
+    INSTRUMENTS.get_or_init(|| {
+        let meter = global::meter("cell-sdk");
+        Instruments {
+            requests: meter
+                .u64_counter("cell.requests")
+                .with_description("Requests handled by the membrane")
+                .build(),
+            duration: meter
+                .f64_histogram("cell.request.duration")
+                .with_description("Handler time per request")
+                .with_unit("s")
+                .build(),
+        }
+    })
+}
+
+/// Called by the membrane once per handled request.
+pub(crate) fn record_request(cell: &str, elapsed: Duration, ok: bool) {
+    let attributes = [
+        KeyValue::new("cell.name", cell.to_string()),
+        KeyValue::new("outcome", if ok { "ok" } else { "error" }),
+    ];
+    let instruments = instruments();
+    instruments.requests.add(1, &attributes);
+    instruments
+        .duration
+        .record(elapsed.as_secs_f64(), &attributes);
+}
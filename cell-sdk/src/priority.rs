@@ -0,0 +1,182 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/priority.rs
+//! Priority inheritance across cells.
+//!
+//! A handler call runs at its method's priority (`#[handler(priority = 90)]`,
+//! see [`crate::shed`]) or at the priority of the call chain it is part of,
+//! whichever is higher. Outgoing requests carry that priority in the vesicle
+//! header ([`cell_core::VesicleHeader::PRIORITIZED`]), so everything a
+//! high-priority call fans out to is high-priority too, in every cell down
+//! the chain: it is shed last, and runs ahead of lower-priority work queued
+//! in cells that schedule. Otherwise a critical request could wait behind
+//! background traffic in a cell it depends on.
+//!
+//! Scheduling is disabled until a cell calls [`enable`] with how many
+//! handler calls may run at once. Further calls wait for a [`turn`], highest
+//! priority first and in arrival order among equals. Background jobs a cell
+//! runs itself can take turns at [`BACKGROUND`], yielding to every request.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use tokio::sync::oneshot;
+
+/// Below every handler priority
+pub const BACKGROUND: u8 = 0;
+
+tokio::task_local! {
+    static PRIORITY: u8;
+}
+
+static SCHEDULER: OnceLock<Scheduler> = OnceLock::new();
+
+/// Priority of the call being handled: set by generated dispatch, or else
+/// inherited from the caller. `None` outside a prioritized call.
+pub fn current() -> Option<u8> {
+    PRIORITY
+        .try_with(|p| *p)
+        .ok()
+        .or_else(crate::context::current_priority)
+}
+
+/// `priority`, raised to that of the call chain it runs in
+pub fn inherit(priority: u8) -> u8 {
+    crate::context::current_priority().map_or(priority, |p| p.max(priority))
+}
+
+/// Run `fut` at `priority`; calls it makes carry it on
+pub async fn scope<F: Future>(priority: u8, fut: F) -> F::Output {
+    PRIORITY.scope(priority, fut).await
+}
+
+/// Schedule handler calls, at most `max_running` at once. Only the first
+/// call takes effect.
+pub fn enable(max_running: usize) {
+    let _ = SCHEDULER.set(Scheduler {
+        slots: max_running.max(1),
+        queue: Mutex::new(Queue::default()),
+    });
+}
+
+/// Calls waiting for a turn
+pub fn queued() -> usize {
+    SCHEDULER
+        .get()
+        .map_or(0, |s| s.queue.lock().unwrap().waiting.len())
+}
+
+/// Wait until work at `priority` may run. Keep the returned turn until it
+/// is done. Immediate while scheduling is disabled.
+pub async fn turn(priority: u8) -> Turn {
+    let Some(scheduler) = SCHEDULER.get() else {
+        return Turn { scheduler: None };
+    };
+    let handed = {
+        let mut queue = scheduler.queue.lock().unwrap();
+        if queue.running < scheduler.slots && queue.waiting.is_empty() {
+            queue.running += 1;
+            return Turn {
+                scheduler: Some(scheduler),
+            };
+        }
+        let (wake, handed) = oneshot::channel();
+        queue.arrived += 1;
+        let arrived = queue.arrived;
+        queue.waiting.push(Waiter {
+            priority,
+            arrived,
+            wake,
+        });
+        handed
+    };
+    let mut waiting = Waiting { handed, scheduler };
+    // Only fails if the scheduler dropped the sender, which it never does
+    // without handing over a slot first
+    let _ = (&mut waiting.handed).await;
+    Turn {
+        scheduler: Some(scheduler),
+    }
+}
+
+/// Leave to run work at a turn; the slot goes to the next waiter on drop
+pub struct Turn {
+    scheduler: Option<&'static Scheduler>,
+}
+
+impl Drop for Turn {
+    fn drop(&mut self) {
+        if let Some(scheduler) = self.scheduler {
+            scheduler.release();
+        }
+    }
+}
+
+struct Scheduler {
+    slots: usize,
+    queue: Mutex<Queue>,
+}
+
+impl Scheduler {
+    /// Hand a finished turn's slot to the first waiter still waiting
+    fn release(&self) {
+        let mut queue = self.queue.lock().unwrap();
+        while let Some(waiter) = queue.waiting.pop() {
+            if waiter.wake.send(()).is_ok() {
+                return;
+            }
+        }
+        queue.running -= 1;
+    }
+}
+
+#[derive(Default)]
+struct Queue {
+    running: usize,
+    arrived: u64,
+    waiting: BinaryHeap<Waiter>,
+}
+
+struct Waiter {
+    priority: u8,
+    arrived: u64,
+    wake: oneshot::Sender<()>,
+}
+
+impl Ord for Waiter {
+    /// Higher priority first, then earlier arrival
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.arrived.cmp(&self.arrived))
+    }
+}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+/// A waiter given up on before its turn started: pass on a slot it was
+/// handed meanwhile
+struct Waiting {
+    handed: oneshot::Receiver<()>,
+    scheduler: &'static Scheduler,
+}
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        if self.handed.try_recv().is_ok() {
+            self.scheduler.release();
+        }
+    }
+}
@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/profile.rs
+//! On-demand profiling over the OPS channel.
+//!
+//! CPU profiles need the `profile` feature and are sampled in-process with
+//! pprof-rs, so a running cell can be profiled without restarting it under a
+//! profiler. Heap profiles need the `heap-profile` feature and a cell that runs
+//! on jemalloc with profiling compiled in (`tikv-jemallocator` with
+//! `profiling`, started with `_RJEM_MALLOC_CONF=prof:true,prof_active:false`).
+//! A cell built without the feature answers with `CellError::NotImplemented`.
+
+use crate::state::ops;
+#[cfg(any(feature = "profile", feature = "heap-profile"))]
+use anyhow::Context;
+use anyhow::{anyhow, bail, Result};
+use cell_model::ops::{OpsRequest, OpsResponse, ProfileFormat, ProfileKind};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Longest window a single OPS request may hold the profiler for.
+pub const MAX_SECONDS: u32 = 300;
+
+/// Sampling rate used when the caller does not pick one.
+pub const DEFAULT_FREQUENCY: u32 = 99;
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Profile this process. Only one profile runs at a time.
+pub async fn capture(kind: ProfileKind, seconds: u32, format: ProfileFormat) -> Result<Vec<u8>> {
+    if seconds == 0 || seconds > MAX_SECONDS {
+        bail!("Profile window must be 1..={} seconds", MAX_SECONDS);
+    }
+    if RUNNING.swap(true, Ordering::AcqRel) {
+        bail!("A profile is already being captured");
+    }
+    let _running = Running;
+    let window = Duration::from_secs(seconds as u64);
+
+    match kind {
+        ProfileKind::Cpu { frequency } => cpu(frequency, window, format).await,
+        ProfileKind::Heap => heap(window, format).await,
+    }
+}
+
+#[cfg(feature = "profile")]
+async fn cpu(frequency: u32, window: Duration, format: ProfileFormat) -> Result<Vec<u8>> {
+    let frequency = if frequency == 0 {
+        DEFAULT_FREQUENCY
+    } else {
+        frequency
+    };
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(frequency as i32)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()
+        .context("Failed to start the CPU profiler")?;
+    tokio::time::sleep(window).await;
+    let report = guard.report().build()?;
+
+    let mut bytes = Vec::new();
+    match format {
+        ProfileFormat::Flamegraph => report.flamegraph(&mut bytes)?,
+        ProfileFormat::Pprof => {
+            use pprof::protos::Message;
+            report.pprof()?.encode(&mut bytes)?;
+        }
+    }
+    Ok(bytes)
+}
+
+#[cfg(not(feature = "profile"))]
+async fn cpu(_frequency: u32, _window: Duration, _format: ProfileFormat) -> Result<Vec<u8>> {
+    Err(not_built("CPU profiling", "profile"))
+}
+
+#[cfg(feature = "heap-profile")]
+async fn heap(window: Duration, format: ProfileFormat) -> Result<Vec<u8>> {
+    let ctl = jemalloc_pprof::PROF_CTL
+        .as_ref()
+        .context("jemalloc profiling is not available in this cell")?;
+    ctl.lock().await.activate()?;
+    tokio::time::sleep(window).await;
+
+    let mut ctl = ctl.lock().await;
+    let dump = match format {
+        ProfileFormat::Pprof => ctl.dump_pprof(),
+        ProfileFormat::Flamegraph => ctl.dump_flamegraph(),
+    };
+    ctl.deactivate()?;
+    dump
+}
+
+#[cfg(not(feature = "heap-profile"))]
+async fn heap(_window: Duration, _format: ProfileFormat) -> Result<Vec<u8>> {
+    Err(not_built("Heap profiling", "heap-profile"))
+}
+
+#[cfg(not(all(feature = "profile", feature = "heap-profile")))]
+fn not_built(what: &str, feature: &str) -> anyhow::Error {
+    use crate::error::{CellError, ErrorContext};
+    ErrorContext::new(CellError::NotImplemented)
+        .with_message(format!(
+            "{} requires cell-sdk's `{}` feature",
+            what, feature
+        ))
+        .with_operation("profile")
+        .into()
+}
+
+/// Releases the profiler when dropped, even if the capture was cancelled.
+struct Running;
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::Release);
+    }
+}
+
+/// Ask a running cell for a profile.
+pub async fn fetch(
+    cell_name: &str,
+    kind: ProfileKind,
+    seconds: u32,
+    format: ProfileFormat,
+) -> Result<Vec<u8>> {
+    let req = OpsRequest::Profile {
+        kind,
+        seconds,
+        format,
+    };
+    match ops(cell_name, &req).await? {
+        OpsResponse::Profile { bytes } => Ok(bytes),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
@@ -0,0 +1,138 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/provenance.rs
+//! Signing and checking build attestations (see `cell_model::provenance`).
+//!
+//! Each node signs with its own Ed25519 key at `~/.cell/keys/node.key`, under
+//! the name in `CELL_NODE_NAME` (else the host name). Hypervisors trust the
+//! nodes whose public keys are in `~/.cell/keys/nodes/<node>.pub`; a builder
+//! exports its own there when it first signs, so a single node trusts itself
+//! and a mesh distributes the files like the producer keys of
+//! `crate::signing`. Envelopes are stored as
+//! `~/.cell/bin/.attestations/<blake3 of the binary>.json`.
+
+use crate::signing::{ProducerKey, TrustedProducers};
+use anyhow::{anyhow, bail, Context, Result};
+pub use cell_model::provenance::{Envelope, Provenance, Statement};
+use cell_model::provenance::{EnvelopeSignature, PAYLOAD_TYPE};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Name this node signs as
+pub fn node_name() -> String {
+    std::env::var("CELL_NODE_NAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "local".to_string())
+}
+
+fn keys_dir() -> Result<PathBuf> {
+    Ok(dirs::home_dir().context("No HOME")?.join(".cell/keys"))
+}
+
+/// This node's signing key, created on first use
+pub fn node_key() -> Result<ProducerKey> {
+    let dir = keys_dir()?;
+    let key = ProducerKey::load_or_create(&dir.join("node.key"))?;
+    let name = node_name();
+    if !dir.join("nodes").join(format!("{}.pub", name)).exists() {
+        key.export_public(&dir.join("nodes"), &name)?;
+    }
+    Ok(key)
+}
+
+/// Public keys of the nodes whose builds this node runs
+pub fn trusted_nodes() -> Result<TrustedProducers> {
+    TrustedProducers::load_dir(&keys_dir()?.join("nodes"))
+}
+
+/// Hex blake3 of a binary, the subject digest of its attestation
+pub fn digest(binary: &Path) -> Result<String> {
+    let mut file =
+        std::fs::File::open(binary).with_context(|| format!("Cannot read {:?}", binary))?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+pub fn attestation_path(digest: &str) -> Result<PathBuf> {
+    Ok(dirs::home_dir()
+        .context("No HOME")?
+        .join(".cell/bin/.attestations")
+        .join(format!("{}.json", digest)))
+}
+
+/// Sign `statement` as `signer`
+pub fn sign(key: &ProducerKey, signer: &str, statement: &Statement) -> Result<Envelope> {
+    let payload = serde_json::to_string(statement)?;
+    let sig = key.sign_bytes(&Envelope::pae(PAYLOAD_TYPE, payload.as_bytes()));
+    Ok(Envelope {
+        payload_type: PAYLOAD_TYPE.to_string(),
+        payload,
+        signatures: vec![EnvelopeSignature {
+            keyid: signer.to_string(),
+            sig,
+        }],
+    })
+}
+
+/// Sign `statement` with the node key and store it for the binary with
+/// `digest`
+pub fn attest(digest: &str, statement: &Statement) -> Result<PathBuf> {
+    let envelope = sign(&node_key()?, &node_name(), statement)?;
+    let path = attestation_path(digest)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Readers never see a half written envelope
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(&envelope)?)?;
+    std::fs::rename(&tmp, &path)?;
+    Ok(path)
+}
+
+/// The statement in `envelope`, once a trusted node's signature over it
+/// verified and it covers the binary with `digest`
+pub fn verify(envelope: &Envelope, digest: &str, trusted: &TrustedProducers) -> Result<Statement> {
+    if envelope.payload_type != PAYLOAD_TYPE {
+        bail!("Unexpected payload type {}", envelope.payload_type);
+    }
+    let signed = envelope.signed_bytes();
+    let mut errors = Vec::new();
+    let signer = envelope.signatures.iter().find(|s| {
+        match trusted.verify_bytes(&s.keyid, &signed, &s.sig) {
+            Ok(()) => true,
+            Err(e) => {
+                errors.push(e.to_string());
+                false
+            }
+        }
+    });
+    if signer.is_none() {
+        bail!("No trusted signature ({})", errors.join("; "));
+    }
+    let statement: Statement =
+        serde_json::from_str(&envelope.payload).context("Invalid provenance statement")?;
+    statement.covers(digest).map_err(|e| anyhow!(e))?;
+    Ok(statement)
+}
+
+/// Check the stored attestation of `binary`. `Ok(None)` when it has none.
+pub fn check(binary: &Path) -> Result<Option<Statement>> {
+    let digest = digest(binary)?;
+    let path = attestation_path(&digest)?;
+    let Ok(bytes) = std::fs::read(&path) else {
+        return Ok(None);
+    };
+    let envelope: Envelope =
+        serde_json::from_slice(&bytes).with_context(|| format!("Invalid envelope {:?}", path))?;
+    verify(&envelope, &digest, &trusted_nodes()?).map(Some)
+}
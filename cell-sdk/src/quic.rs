@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/quic.rs
+//! Direct QUIC between cells, without the Axon proxy hop.
+//!
+//! A cell serves over QUIC when `CELL_TRANSPORT` (or `transport` in its
+//! `Cell.toml`) is `quic://<ip>:<port>`. Callers dial it by address:
+//! `Synapse::grow("ledger@10.0.0.2:9100")` or the same name given to
+//! `ResilientSynapse`. Each synapse opens one bidirectional stream and sends
+//! the same frames as on the Unix socket, so the Membrane serves the stream
+//! like any other connection.
+//!
+//! Like Axon, the server presents a self-signed certificate that clients do
+//! not verify: traffic is encrypted but the cell is not authenticated.
+
+use anyhow::{Context, Result};
+use cell_model::manifest::quic_addr;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// Name the server certificate is issued for and clients ask for
+const SERVER_NAME: &str = "localhost";
+
+/// `("ledger", "10.0.0.2:9100")` for `ledger@10.0.0.2:9100`
+pub fn split_target(target: &str) -> Option<(&str, &str)> {
+    target
+        .split_once('@')
+        .filter(|(cell, addr)| !cell.is_empty() && addr.contains(':'))
+}
+
+/// Address the Membrane should listen on, if the cell selected QUIC.
+pub fn listen_addr() -> Option<String> {
+    crate::tcp::selected().and_then(|spec| quic_addr(&spec).map(str::to_string))
+}
+
+/// One bidirectional stream, keeping its connection open
+pub struct QuicStream {
+    send: quinn::SendStream,
+    recv: quinn::RecvStream,
+    _conn: quinn::Connection,
+    /// Client side: the endpoint driving the connection
+    _endpoint: Option<quinn::Endpoint>,
+}
+
+impl QuicStream {
+    /// Stream accepted by a server endpoint.
+    pub fn accepted(
+        send: quinn::SendStream,
+        recv: quinn::RecvStream,
+        conn: quinn::Connection,
+    ) -> Self {
+        Self {
+            send,
+            recv,
+            _conn: conn,
+            _endpoint: None,
+        }
+    }
+}
+
+impl AsyncRead for QuicStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.recv).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for QuicStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.send).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.send).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.send).poll_shutdown(cx)
+    }
+}
+
+/// Server endpoint on `addr` with a fresh self-signed certificate.
+pub fn listen(addr: &str) -> Result<quinn::Endpoint> {
+    let addr: SocketAddr = addr
+        .parse()
+        .with_context(|| format!("Invalid QUIC listen address '{}'", addr))?;
+    let cert = rcgen::generate_simple_self_signed(vec![SERVER_NAME.into()])?;
+    let key = rustls::PrivateKey(cert.serialize_private_key_der());
+    let chain = vec![rustls::Certificate(cert.serialize_der()?)];
+    let mut config = quinn::ServerConfig::with_single_cert(chain, key)?;
+
+    let mut transport = quinn::TransportConfig::default();
+    transport.max_concurrent_uni_streams(0u8.into());
+    config.transport_config(Arc::new(transport));
+
+    quinn::Endpoint::server(config, addr)
+        .with_context(|| format!("Failed to listen on quic://{}", addr))
+}
+
+/// Dial `addr` (`host:port`) and open a stream.
+pub async fn connect(addr: &str) -> Result<QuicStream> {
+    let remote = tokio::net::lookup_host(addr)
+        .await
+        .with_context(|| format!("Failed to resolve '{}'", addr))?
+        .next()
+        .with_context(|| format!("'{}' has no address", addr))?;
+    let local: SocketAddr = if remote.is_ipv6() {
+        "[::]:0".parse()?
+    } else {
+        "0.0.0.0:0".parse()?
+    };
+    let mut endpoint = quinn::Endpoint::client(local)?;
+    endpoint.set_default_client_config(client_config());
+
+    let conn = endpoint
+        .connect(remote, SERVER_NAME)?
+        .await
+        .with_context(|| format!("Failed to connect to quic://{}", addr))?;
+    let (send, recv) = conn.open_bi().await?;
+    Ok(QuicStream {
+        send,
+        recv,
+        _conn: conn,
+        _endpoint: Some(endpoint),
+    })
+}
+
+fn client_config() -> quinn::ClientConfig {
+    struct AcceptAny;
+    impl rustls::client::ServerCertVerifier for AcceptAny {
+        fn verify_server_cert(
+            &self,
+            _end_entity: &rustls::Certificate,
+            _intermediates: &[rustls::Certificate],
+            _server_name: &rustls::ServerName,
+            _scts: &mut dyn Iterator<Item = &[u8]>,
+            _ocsp_response: &[u8],
+            _now: std::time::SystemTime,
+        ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+            Ok(rustls::client::ServerCertVerified::assertion())
+        }
+    }
+    let crypto = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(AcceptAny))
+        .with_no_client_auth();
+    quinn::ClientConfig::new(Arc::new(crypto))
+}
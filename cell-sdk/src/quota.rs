@@ -0,0 +1,244 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/quota.rs
+//! Quota enforcement in the Membrane.
+//!
+//! Disabled until a cell calls [`enforce`] or the `quota` cell has published
+//! limits. Once enabled, every request is charged to its principal
+//! ([`crate::context::CallContext::principal`]: the impersonated principal,
+//! otherwise the peer, e.g. `uid:1000`) against the request rate and queue
+//! depth limits.
+//!
+//! The `quota` cell [`publish`]es its limits to `~/.cell/quota/limits.json`.
+//! Every Membrane starts enforcing them when it starts serving and follows
+//! later changes; [`set_limits`] changes them for this process only.
+//!
+//! While enforcing, the Membrane also meters what it admits and rejects per
+//! organism and principal, and spools the counts to `~/.cell/quota/metering`
+//! every few seconds. The `quota` cell collects them with [`take_spooled`]
+//! into its usage records.
+
+use anyhow::{Context, Result};
+pub use cell_model::quota::{QuotaBreach, QuotaKind, QuotaLedger, QuotaLimits, QuotaUsage};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tracing::warn;
+
+static LEDGER: OnceLock<Mutex<QuotaLedger>> = OnceLock::new();
+/// (admitted, rejected) per (organism, principal) since the last spool
+static METERED: Mutex<BTreeMap<(String, String), (u64, u64)>> = Mutex::new(BTreeMap::new());
+
+/// How often a Membrane looks for newly published limits
+const RELOAD_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Limits the `quota` cell publishes to the cells on its node
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct QuotaTable {
+    /// For principals without their own
+    pub default: QuotaLimits,
+    pub principals: BTreeMap<String, QuotaLimits>,
+}
+
+pub fn table_path() -> Result<PathBuf> {
+    let home = dirs::home_dir().context("No HOME dir")?;
+    Ok(home.join(".cell/quota/limits.json"))
+}
+
+/// Requests one Membrane metered for a principal, not yet collected
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SpooledRequests {
+    pub organism: String,
+    pub principal: String,
+    pub admitted: u64,
+    pub rejected: u64,
+}
+
+pub fn spool_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().context("No HOME dir")?;
+    Ok(home.join(".cell/quota/metering"))
+}
+
+/// The published limits, if the `quota` cell has published any
+pub fn load_table() -> Result<Option<QuotaTable>> {
+    let path = table_path()?;
+    match std::fs::read(&path) {
+        Ok(bytes) => Ok(Some(
+            serde_json::from_slice(&bytes).with_context(|| format!("{:?}", path))?,
+        )),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Replace the published limits. Membranes pick them up within a few seconds.
+pub fn publish(table: &QuotaTable) -> Result<()> {
+    let path = table_path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    // Never leave a half-written table for a Membrane to read
+    let staged = path.with_extension("json.tmp");
+    std::fs::write(&staged, serde_json::to_vec_pretty(table)?)?;
+    std::fs::rename(&staged, &path)?;
+    Ok(())
+}
+
+/// Enforce the published limits in this process and follow their changes.
+/// The Membrane calls this when it starts serving.
+pub(crate) fn follow_published() {
+    tokio::spawn(async {
+        let mut seen = None;
+        loop {
+            let modified = match table_path() {
+                Ok(path) => tokio::fs::metadata(path)
+                    .await
+                    .and_then(|m| m.modified())
+                    .ok(),
+                Err(_) => None,
+            };
+            if modified.is_some() && modified != seen {
+                seen = modified;
+                match load_table() {
+                    Ok(Some(table)) => apply(&table),
+                    Ok(None) => {}
+                    Err(e) => warn!("[Quota] Ignoring published limits: {:#}", e),
+                }
+            }
+            if let Err(e) = spool() {
+                warn!("[Quota] Metered requests not spooled: {:#}", e);
+            }
+            tokio::time::sleep(RELOAD_INTERVAL).await;
+        }
+    });
+}
+
+/// Write the requests metered since the last call as one spool file
+fn spool() -> Result<()> {
+    let metered = std::mem::take(&mut *METERED.lock().unwrap());
+    if metered.is_empty() {
+        return Ok(());
+    }
+    let records: Vec<SpooledRequests> = metered
+        .iter()
+        .map(
+            |((organism, principal), &(admitted, rejected))| SpooledRequests {
+                organism: organism.clone(),
+                principal: principal.clone(),
+                admitted,
+                rejected,
+            },
+        )
+        .collect();
+    let written = spool_dir().and_then(|dir| {
+        std::fs::create_dir_all(&dir)?;
+        let name = format!("{}-{}", std::process::id(), now_ms());
+        // Collected only once renamed to `.json`
+        let staged = dir.join(format!("{}.tmp", name));
+        std::fs::write(&staged, serde_json::to_vec(&records)?)?;
+        std::fs::rename(&staged, dir.join(format!("{}.json", name)))?;
+        Ok(())
+    });
+    if written.is_err() {
+        // Keep the counts for the next attempt
+        let mut pending = METERED.lock().unwrap();
+        for (key, (admitted, rejected)) in metered {
+            let entry = pending.entry(key).or_default();
+            entry.0 += admitted;
+            entry.1 += rejected;
+        }
+    }
+    written
+}
+
+/// Read and remove every spool file Membranes on this node have written.
+pub fn take_spooled() -> Result<Vec<SpooledRequests>> {
+    let dir = spool_dir()?;
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut spooled = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let bytes = std::fs::read(&path)?;
+        std::fs::remove_file(&path)?;
+        match serde_json::from_slice::<Vec<SpooledRequests>>(&bytes) {
+            Ok(records) => spooled.extend(records),
+            Err(e) => warn!("[Quota] Dropping unreadable spool file {:?}: {}", path, e),
+        }
+    }
+    Ok(spooled)
+}
+
+fn apply(table: &QuotaTable) {
+    let ledger = LEDGER.get_or_init(|| Mutex::new(QuotaLedger::new(table.default.clone())));
+    let mut ledger = ledger.lock().unwrap();
+    ledger.reset_limits(table.default.clone());
+    for (principal, limits) in &table.principals {
+        ledger.set_limits(principal, limits.clone());
+    }
+}
+
+/// Enable enforcement for this process. Only the first call takes effect.
+pub fn enforce(ledger: QuotaLedger) {
+    let _ = LEDGER.set(Mutex::new(ledger));
+}
+
+pub fn set_limits(principal: &str, limits: QuotaLimits) {
+    if let Some(ledger) = LEDGER.get() {
+        ledger.lock().unwrap().set_limits(principal, limits);
+    }
+}
+
+pub fn usage(principal: &str) -> Option<QuotaUsage> {
+    LEDGER
+        .get()
+        .map(|l| l.lock().unwrap().usage(principal, now_ms()))
+}
+
+/// Organism a request is billed to: the authenticated caller's, otherwise
+/// this cell's own
+pub(crate) fn organism_of(caller: Option<&crate::auth::Caller>) -> String {
+    match caller {
+        Some(caller) => caller.organism.clone(),
+        None => crate::identity::Identity::get().organism.clone(),
+    }
+}
+
+/// Charge one request to `principal` of `organism` and meter it. Pair every
+/// `Ok` with [`release`].
+pub(crate) fn admit(organism: &str, principal: &str) -> Result<(), QuotaBreach> {
+    let Some(ledger) = LEDGER.get() else {
+        return Ok(());
+    };
+    let result = ledger.lock().unwrap().admit(principal, now_ms());
+    let mut metered = METERED.lock().unwrap();
+    let counts = metered
+        .entry((organism.to_string(), principal.to_string()))
+        .or_default();
+    if result.is_ok() {
+        counts.0 += 1;
+    } else {
+        counts.1 += 1;
+    }
+    result
+}
+
+pub(crate) fn release(principal: &str) {
+    if let Some(ledger) = LEDGER.get() {
+        ledger.lock().unwrap().leave_queue(principal);
+    }
+}
+
+pub fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/replay.rs
+//! Replay audits for consensus cells.
+//!
+//! A cell with a replicated log registers one verifier, which replays the log
+//! from disk through a fresh state machine and reports both state hashes (see
+//! `cell_model::replay`). The Membrane runs it on OPS `VerifyReplay`, and the
+//! observer cell calls [`fetch`] periodically to catch divergence or WAL
+//! corruption before a failover depends on the log.
+//!
+//! ```ignore
+//! replay::register(move || {
+//!     let raft = raft.clone();
+//!     async move { raft.verify_replay(Arc::new(Counter::default())).await }
+//! });
+//! ```
+
+use crate::membrane::BoxFuture;
+use crate::state::ops;
+use anyhow::{anyhow, bail, Result};
+use cell_model::ops::{OpsRequest, OpsResponse};
+pub use cell_model::replay::{ReplayOutcome, ReplayReport};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+type Verifier = Arc<dyn Fn() -> BoxFuture<'static, Result<ReplayReport>> + Send + Sync>;
+
+static VERIFIER: Mutex<Option<Verifier>> = Mutex::new(None);
+
+/// Set the cell's replay verifier, replacing any earlier one.
+pub fn register<F, Fut>(verify: F)
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<ReplayReport>> + Send + 'static,
+{
+    let verify: Verifier = Arc::new(move || Box::pin(verify()));
+    *VERIFIER.lock().unwrap() = Some(verify);
+}
+
+/// Run the registered verifier.
+pub async fn verify() -> Result<ReplayReport> {
+    let verify = VERIFIER
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| anyhow!("Cell has no replay verifier"))?;
+    verify().await
+}
+
+/// Hash of a state machine snapshot, as compared by replay audits. The
+/// snapshot must be deterministic: equal states, equal bytes.
+pub fn state_hash(snapshot: &[u8]) -> [u8; 32] {
+    *blake3::hash(snapshot).as_bytes()
+}
+
+/// Ask a running cell to audit its log.
+pub async fn fetch(cell_name: &str) -> Result<ReplayReport> {
+    match ops(cell_name, &OpsRequest::VerifyReplay).await? {
+        OpsResponse::Replay(report) => Ok(report),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/replica.rs
+//! Read replicas.
+//!
+//! A replica of cell `x` serves as `x-replica-0`, `x-replica-1`, ... and
+//! follows the leader named in `CELL_REPLICA_OF`. Clients generated by
+//! `cell_remote!` send `#[handler(read)]` methods to the replicas and
+//! everything else to the leader; replicas forward writes, and reads they are
+//! too stale for, to the leader themselves.
+
+use crate::ResilientSynapse;
+use tracing::info;
+
+/// Replicas are numbered from 0; discovery stops at the first gap
+const MAX_REPLICAS: usize = 16;
+
+pub fn name(cell_name: &str, index: usize) -> String {
+    format!("{}-replica-{}", cell_name, index)
+}
+
+/// Connect to the running replicas of a cell.
+pub async fn discover(cell_name: &str) -> Vec<ResilientSynapse> {
+    let mut replicas = Vec::new();
+    for i in 0..MAX_REPLICAS {
+        match ResilientSynapse::grow(&name(cell_name, i)).await {
+            Ok(conn) => replicas.push(conn),
+            Err(_) => break,
+        }
+    }
+    if !replicas.is_empty() {
+        info!("[Replica] Reading '{}' from {} replicas", cell_name, replicas.len());
+    }
+    replicas
+}
+
+/// This process's role, from `CELL_REPLICA_OF` (the leader's name) and
+/// `CELL_REPLICA_INDEX` (default 0). `None` for a leader.
+pub fn role() -> Option<(String, usize)> {
+    let leader = std::env::var("CELL_REPLICA_OF").ok()?;
+    let index = std::env::var("CELL_REPLICA_INDEX")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    Some((leader, index))
+}
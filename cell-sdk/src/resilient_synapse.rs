@@ -4,21 +4,27 @@
 //!
 //! Features:
 //! - Automatic reconnection with exponential backoff
-//! - Transport fallback (SHM → Socket → IO Cell)
+//! - Transport fallback (SHM → Socket → IO Cell), or TCP to remote neighbors
+//! - Direct QUIC to `cell@host:port` (feature `quic`)
 //! - Circuit breaker pattern
 //! - Health checking and failover
+//! - Schema fingerprint handshake on every (re)connect
 
+use crate::correlator::Correlator;
+use crate::error::{CellError, ErrorContext};
 use crate::io_client::IoClient;
 use crate::response::Response;
 use crate::shm::ShmClient;
+use crate::source::{self, SchemaCheck};
 use anyhow::{Context, Result};
-use cell_core::{channel, VesicleHeader};
+use cell_core::{channel, TraceContext, VesicleHeader};
 use cell_model::rkyv::ser::serializers::AllocSerializer;
 use rkyv::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::UnixStream;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream};
+use tokio::net::{TcpStream, UnixStream};
 use tokio::sync::{Mutex, RwLock};
 use tracing::{debug, error, info, warn};
 
@@ -45,6 +51,30 @@ pub enum Transport {
         health: Arc<RwLock<ConnState>>,
         last_activity: Arc<RwLock<Instant>>,
     },
+    // TCP to a neighbor on another host
+    Tcp {
+        stream: Arc<Mutex<TcpStream>>,
+        health: Arc<RwLock<ConnState>>,
+        last_activity: Arc<RwLock<Instant>>,
+    },
+    // QUIC stream to a cell dialed as `cell@host:port`
+    #[cfg(feature = "quic")]
+    Quic {
+        stream: Arc<Mutex<crate::quic::QuicStream>>,
+        health: Arc<RwLock<ConnState>>,
+        last_activity: Arc<RwLock<Instant>>,
+    },
+    // Socket shared by concurrent requests, matched by correlation id
+    Multiplexed {
+        conn: Arc<Correlator>,
+        health: Arc<RwLock<ConnState>>,
+        last_activity: Arc<RwLock<Instant>>,
+    },
+    // In-memory pipe to a cell hosted in this process (compose mode)
+    Local {
+        stream: Arc<Mutex<DuplexStream>>,
+        health: Arc<RwLock<ConnState>>,
+    },
 }
 
 // Manual Debug impl since ShmClient doesn't derive Debug
@@ -64,6 +94,39 @@ impl std::fmt::Debug for Transport {
                 .field("health", &health)
                 .field("last_activity", &last_activity)
                 .finish(),
+            Transport::Tcp {
+                health,
+                last_activity,
+                ..
+            } => f
+                .debug_struct("Transport::Tcp")
+                .field("health", &health)
+                .field("last_activity", &last_activity)
+                .finish(),
+            #[cfg(feature = "quic")]
+            Transport::Quic {
+                health,
+                last_activity,
+                ..
+            } => f
+                .debug_struct("Transport::Quic")
+                .field("health", &health)
+                .field("last_activity", &last_activity)
+                .finish(),
+            Transport::Multiplexed {
+                conn,
+                health,
+                last_activity,
+            } => f
+                .debug_struct("Transport::Multiplexed")
+                .field("conn", &conn)
+                .field("health", &health)
+                .field("last_activity", &last_activity)
+                .finish(),
+            Transport::Local { health, .. } => f
+                .debug_struct("Transport::Local")
+                .field("health", &health)
+                .finish(),
         }
     }
 }
@@ -77,6 +140,8 @@ pub struct ConnMetrics {
     pub requests_total: u64,
     pub requests_failed: u64,
     pub reconnections: u64,
+    /// Responses answered by a fallback handler
+    pub responses_degraded: u64,
     pub current_state: ConnState,
 }
 
@@ -101,6 +166,13 @@ pub struct ResilienceConfig {
     pub enable_transport_upgrade: bool,
     /// Enable transport downgrade (SHM → socket on failure)
     pub enable_transport_downgrade: bool,
+    /// Schema fingerprint the caller was built against; 0 skips the handshake
+    pub schema_fingerprint: u64,
+    /// What to do when the cell serves a different schema
+    pub schema_check: SchemaCheck,
+    /// Send concurrent requests over the socket at once instead of one after
+    /// another (see `crate::correlator`); needs a cell built with this SDK
+    pub multiplex: bool,
 }
 
 impl Default for ResilienceConfig {
@@ -115,6 +187,9 @@ impl Default for ResilienceConfig {
             request_timeout: Duration::from_secs(30),
             enable_transport_upgrade: true,
             enable_transport_downgrade: true,
+            schema_fingerprint: 0,
+            schema_check: SchemaCheck::from_env(),
+            multiplex: false,
         }
     }
 }
@@ -128,6 +203,8 @@ struct SynapseInner {
     config: ResilienceConfig,
     // Track consecutive failures for circuit breaker
     consecutive_failures: Arc<RwLock<u32>>,
+    // Fingerprint the cell answered the last handshake with
+    served_schema: Arc<AtomicU64>,
 }
 
 /// Production-grade resilient connection handle
@@ -142,6 +219,8 @@ pub struct ResilientSynapse {
     inner: Arc<RwLock<SynapseInner>>,
     // Public metrics access
     pub metrics: Arc<RwLock<ConnMetrics>>,
+    built_schema: u64,
+    served_schema: Arc<AtomicU64>,
 }
 
 impl ResilientSynapse {
@@ -162,6 +241,9 @@ impl ResilientSynapse {
         info!("[ResilientSynapse] Connecting to '{}'...", cell_name);
 
         let (transport, my_id) = Self::establish_connection(cell_name, &config).await?;
+        let served = Self::handshake(&transport, my_id, cell_name, &config).await?;
+        let built_schema = config.schema_fingerprint;
+        let served_schema = Arc::new(AtomicU64::new(served));
 
         let metrics = Arc::new(RwLock::new(ConnMetrics {
             created_at: Instant::now(),
@@ -170,6 +252,7 @@ impl ResilientSynapse {
             requests_total: 0,
             requests_failed: 0,
             reconnections: 0,
+            responses_degraded: 0,
             current_state: ConnState::Healthy,
         }));
 
@@ -180,11 +263,14 @@ impl ResilientSynapse {
             metrics: metrics.clone(),
             config,
             consecutive_failures: Arc::new(RwLock::new(0)),
+            served_schema: served_schema.clone(),
         };
 
         let synapse = Self {
             inner: Arc::new(RwLock::new(inner)),
             metrics: metrics.clone(),
+            built_schema,
+            served_schema,
         };
 
         // Start background health checker
@@ -208,7 +294,38 @@ impl ResilientSynapse {
         let hash = blake3::hash(my_name.as_bytes());
         let my_id = u64::from_le_bytes(hash.as_bytes()[..8].try_into().unwrap());
 
-        // Try 1: Direct neighbor link (fastest, no IO cell needed)
+        // `cell@host:port`: dial the cell directly over QUIC
+        if cell_name.contains('@') {
+            let transport = Self::dial_quic(cell_name).await?;
+            return Ok((Self::multiplex(transport, my_id, config), my_id));
+        }
+
+        // Try 0: Cell hosted in this process by compose!
+        if let Some(stream) = crate::compose::connect(cell_name) {
+            debug!("[ResilientSynapse] '{}' is hosted in-process", cell_name);
+            let transport = Transport::Local {
+                stream: Arc::new(Mutex::new(stream)),
+                health: Arc::new(RwLock::new(ConnState::Healthy)),
+            };
+            return Ok((transport, my_id));
+        }
+
+        // Try 1: Neighbor on another host, declared as tcp://host:port
+        if let Some(addr) = crate::tcp::neighbor_addr(cell_name) {
+            let stream = crate::tcp::connect(&addr).await?;
+            info!(
+                "[ResilientSynapse] Connected via tcp://{} to '{}'",
+                addr, cell_name
+            );
+            let transport = Transport::Tcp {
+                stream: Arc::new(Mutex::new(stream)),
+                health: Arc::new(RwLock::new(ConnState::Healthy)),
+                last_activity: Arc::new(RwLock::new(Instant::now())),
+            };
+            return Ok((Self::multiplex(transport, my_id, config), my_id));
+        }
+
+        // Try 2: Direct neighbor link (fastest, no IO cell needed)
         let neighbor_result = Self::try_neighbor_link(cell_name).await;
         if let Ok(stream) = neighbor_result {
             info!(
@@ -223,10 +340,10 @@ impl ResilientSynapse {
                     return Ok((shm, my_id));
                 }
             }
-            return Ok((transport, my_id));
+            return Ok((Self::multiplex(transport, my_id, config), my_id));
         }
 
-        // Try 2: IO Cell mediated connection
+        // Try 3: IO Cell mediated connection
         let io_result = IoClient::connect(cell_name).await;
         if let Ok(stream) = io_result {
             info!(
@@ -241,10 +358,10 @@ impl ResilientSynapse {
                     return Ok((shm, my_id));
                 }
             }
-            return Ok((transport, my_id));
+            return Ok((Self::multiplex(transport, my_id, config), my_id));
         }
 
-        // Try 3: Global registry (last resort)
+        // Try 4: Global registry (last resort)
         let global_result = Self::try_global_registry(cell_name).await;
         if let Ok(stream) = global_result {
             info!(
@@ -252,7 +369,7 @@ impl ResilientSynapse {
                 cell_name
             );
             let transport = Self::wrap_tokio_socket(stream, config).await?;
-            return Ok((transport, my_id));
+            return Ok((Self::multiplex(transport, my_id, config), my_id));
         }
 
         // All strategies failed
@@ -266,6 +383,78 @@ impl ResilientSynapse {
         );
     }
 
+    /// Exchange schema fingerprints with the cell and apply the configured
+    /// [`SchemaCheck`]. Returns the fingerprint the cell serves, 0 if unknown.
+    async fn handshake(
+        transport: &Transport,
+        my_id: u64,
+        cell_name: &str,
+        config: &ResilienceConfig,
+    ) -> Result<u64> {
+        if config.schema_fingerprint == 0 {
+            return Ok(0);
+        }
+        let req = source::handshake_request(config.schema_fingerprint)?;
+        let resp = match transport {
+            // Hosted by compose!, so built from the same tree
+            Transport::Local { .. } => return Ok(0),
+            Transport::Shm { client, .. } => client
+                .request_raw(&req, channel::OPS)
+                .await?
+                .get_bytes()
+                .to_vec(),
+            Transport::Socket { stream, .. } => {
+                let mut guard = stream.lock().await;
+                Self::send_socket(
+                    &mut *guard,
+                    my_id,
+                    channel::OPS,
+                    &req,
+                    config.request_timeout,
+                )
+                .await?
+                .into_owned()
+            }
+            Transport::Tcp { stream, .. } => {
+                let mut guard = stream.lock().await;
+                Self::send_socket(
+                    &mut *guard,
+                    my_id,
+                    channel::OPS,
+                    &req,
+                    config.request_timeout,
+                )
+                .await?
+                .into_owned()
+            }
+            #[cfg(feature = "quic")]
+            Transport::Quic { stream, .. } => {
+                let mut guard = stream.lock().await;
+                Self::send_socket(
+                    &mut *guard,
+                    my_id,
+                    channel::OPS,
+                    &req,
+                    config.request_timeout,
+                )
+                .await?
+                .into_owned()
+            }
+            Transport::Multiplexed { conn, .. } => conn
+                .send(channel::OPS, &req, config.request_timeout)
+                .await?
+                .into_owned(),
+        };
+        let served = source::handshake_response(&resp)?;
+        source::check(
+            cell_name,
+            config.schema_fingerprint,
+            served,
+            config.schema_check,
+        )?;
+        Ok(served)
+    }
+
     /// Try to connect via direct neighbor symlink
     async fn try_neighbor_link(cell_name: &str) -> Result<UnixStream> {
         let cwd = std::env::current_dir()?;
@@ -328,12 +517,93 @@ impl ResilientSynapse {
         })
     }
 
+    /// Open a QUIC stream to `target` (`cell@host:port`)
+    #[cfg(feature = "quic")]
+    async fn dial_quic(target: &str) -> Result<Transport> {
+        let (_, addr) = crate::quic::split_target(target)
+            .with_context(|| format!("Expected cell@host:port, got '{}'", target))?;
+        let stream = crate::quic::connect(addr).await?;
+        info!("[ResilientSynapse] Connected via quic://{}", addr);
+        Ok(Transport::Quic {
+            stream: Arc::new(Mutex::new(stream)),
+            health: Arc::new(RwLock::new(ConnState::Healthy)),
+            last_activity: Arc::new(RwLock::new(Instant::now())),
+        })
+    }
+
+    #[cfg(not(feature = "quic"))]
+    async fn dial_quic(target: &str) -> Result<Transport> {
+        anyhow::bail!("Dialing '{}' needs cell-sdk's quic feature", target)
+    }
+
+    /// Hand a socket, TCP or QUIC transport to a [`Correlator`] when
+    /// `config.multiplex` is set.
+    fn multiplex(transport: Transport, my_id: u64, config: &ResilienceConfig) -> Transport {
+        match transport {
+            #[cfg(feature = "quic")]
+            Transport::Quic {
+                stream,
+                health,
+                last_activity,
+            } if config.multiplex => match Arc::try_unwrap(stream) {
+                Ok(stream) => Transport::Multiplexed {
+                    conn: Arc::new(Correlator::new(stream.into_inner(), my_id)),
+                    health,
+                    last_activity,
+                },
+                Err(stream) => Transport::Quic {
+                    stream,
+                    health,
+                    last_activity,
+                },
+            },
+            Transport::Tcp {
+                stream,
+                health,
+                last_activity,
+            } if config.multiplex => match Arc::try_unwrap(stream) {
+                Ok(stream) => Transport::Multiplexed {
+                    conn: Arc::new(Correlator::new(stream.into_inner(), my_id)),
+                    health,
+                    last_activity,
+                },
+                Err(stream) => Transport::Tcp {
+                    stream,
+                    health,
+                    last_activity,
+                },
+            },
+            Transport::Socket {
+                stream,
+                health,
+                last_activity,
+            } if config.multiplex => match Arc::try_unwrap(stream) {
+                Ok(stream) => Transport::Multiplexed {
+                    conn: Arc::new(Correlator::new(stream.into_inner(), my_id)),
+                    health,
+                    last_activity,
+                },
+                Err(stream) => Transport::Socket {
+                    stream,
+                    health,
+                    last_activity,
+                },
+            },
+            other => other,
+        }
+    }
+
     /// Attempt to upgrade socket connection to SHM transport
     async fn try_upgrade_to_shm(transport: &Transport, cell_name: &str) -> Result<Transport> {
         // Only upgrade from socket
         let socket_arc = match transport {
             Transport::Socket { stream, .. } => stream.clone(),
             Transport::Shm { .. } => return Err(anyhow::anyhow!("Already using SHM")),
+            Transport::Multiplexed { .. } => return Err(anyhow::anyhow!("Multiplexed socket")),
+            Transport::Tcp { .. } => return Err(anyhow::anyhow!("Remote neighbor")),
+            #[cfg(feature = "quic")]
+            Transport::Quic { .. } => return Err(anyhow::anyhow!("Remote neighbor")),
+            Transport::Local { .. } => return Err(anyhow::anyhow!("In-process, no upgrade needed")),
         };
 
         let mut stream = socket_arc.lock().await;
@@ -434,20 +704,35 @@ impl ResilientSynapse {
         let mut delay = config.reconnect_base_delay;
 
         for attempt in 1..=config.max_reconnect_attempts {
-            match Self::establish_connection(&cell_name, &config).await {
-                Ok((new_transport, _)) => {
+            let connected = match Self::establish_connection(&cell_name, &config).await {
+                Ok((transport, my_id)) => Self::handshake(&transport, my_id, &cell_name, &config)
+                    .await
+                    .map(|served| (transport, served)),
+                Err(e) => Err(e),
+            };
+            match connected {
+                Ok((new_transport, served)) => {
                     info!(
                         "[ResilientSynapse] Reconnected to '{}' after {} attempts",
                         cell_name, attempt
                     );
 
                     inner.transport = new_transport;
+                    inner.served_schema.store(served, Ordering::Relaxed);
                     inner.metrics.write().await.reconnections += 1;
                     inner.metrics.write().await.current_state = ConnState::Healthy;
                     *inner.consecutive_failures.write().await = 0;
 
                     return Ok(());
                 }
+                // Replaced by a cell serving another schema: retrying cannot help
+                Err(e)
+                    if e.downcast_ref::<ErrorContext>()
+                        .is_some_and(|ctx| ctx.code == CellError::ProtocolMismatch) =>
+                {
+                    inner.metrics.write().await.current_state = ConnState::CircuitOpen;
+                    return Err(e);
+                }
                 Err(e) => {
                     warn!(
                         "[ResilientSynapse] Reconnect attempt {}/{} failed: {}",
@@ -480,6 +765,35 @@ impl ResilientSynapse {
     /// - Automatic reconnection on persistent failures
     /// - Transport downgrade (SHM → Socket) if SHM fails
     pub async fn fire<'a, Req>(&self, request: &Req) -> Result<Response<'a, Vec<u8>>>
+    where
+        Req: Serialize<AllocSerializer<1024>>,
+    {
+        #[cfg(feature = "otel")]
+        {
+            use tracing::Instrument;
+            let cell = self.inner.read().await.cell_name.clone();
+            let span = tracing::info_span!(
+                crate::otel::CALL_SPAN,
+                cell = %cell,
+                otel.kind = "client",
+                otel.status_code = tracing::field::Empty,
+            );
+            let result = crate::trace::call(&cell, self.fire_untraced(request))
+                .instrument(span.clone())
+                .await;
+            if result.is_err() {
+                crate::otel::mark_failed(&span);
+            }
+            return result;
+        }
+        #[cfg(not(feature = "otel"))]
+        {
+            let cell = self.inner.read().await.cell_name.clone();
+            crate::trace::call(&cell, self.fire_untraced(request)).await
+        }
+    }
+
+    async fn fire_untraced<'a, Req>(&self, request: &Req) -> Result<Response<'a, Vec<u8>>>
     where
         Req: Serialize<AllocSerializer<1024>>,
     {
@@ -491,8 +805,16 @@ impl ResilientSynapse {
         match self.try_send(&req_bytes).await {
             Ok(resp) => {
                 self.record_success().await;
+                if resp.is_degraded() {
+                    self.metrics.write().await.responses_degraded += 1;
+                }
                 return Ok(resp);
             }
+            // The cell answered with an error: nothing to recover from
+            Err(e) if crate::error::is_remote(&e) => {
+                self.record_success().await;
+                return Err(e);
+            }
             Err(e) => {
                 let error_str = e.to_string();
                 let is_transient = error_str.contains("Broken pipe")
@@ -634,61 +956,150 @@ impl ResilientSynapse {
                 stream,
                 health,
                 last_activity,
+            } => Self::send_exclusive(stream, health, last_activity, &inner, req_bytes).await,
+            Transport::Tcp {
+                stream,
+                health,
+                last_activity,
+            } => Self::send_exclusive(stream, health, last_activity, &inner, req_bytes).await,
+            #[cfg(feature = "quic")]
+            Transport::Quic {
+                stream,
+                health,
+                last_activity,
+            } => Self::send_exclusive(stream, health, last_activity, &inner, req_bytes).await,
+            Transport::Multiplexed {
+                conn,
+                health,
+                last_activity,
             } => {
-                // Check health
                 let state = *health.read().await;
                 if state == ConnState::CircuitOpen {
                     return Err(anyhow::anyhow!("Circuit breaker open"));
                 }
 
+                // No lock: concurrent calls share the socket
+                match conn
+                    .send(channel::APP, req_bytes, inner.config.request_timeout)
+                    .await
+                {
+                    Ok(resp) => {
+                        *last_activity.write().await = Instant::now();
+                        Ok(resp)
+                    }
+                    Err(e) => {
+                        if !crate::error::is_remote(&e) {
+                            *health.write().await = ConnState::Unhealthy;
+                        }
+                        Err(e)
+                    }
+                }
+            }
+            Transport::Local { stream, health } => {
+                // Application calls go straight to the handler once it is up
+                if let Some(dispatch) = crate::compose::dispatcher(&inner.cell_name) {
+                    let mut bytes = rkyv::AlignedVec::with_capacity(req_bytes.len());
+                    bytes.extend_from_slice(req_bytes);
+                    let (bytes, degraded) = dispatch(bytes).await?;
+                    let prefix = if degraded { cell_core::frame::DEGRADED } else { 0 };
+                    return Ok(Response::from_frame(prefix, bytes));
+                }
+
                 let mut guard = stream.lock().await;
                 let result = Self::send_socket(
-                    &mut guard,
+                    &mut *guard,
                     inner.my_id,
+                    channel::APP,
                     req_bytes,
                     inner.config.request_timeout,
                 )
                 .await;
+                if result.as_ref().is_err_and(|e| !crate::error::is_remote(e)) {
+                    *health.write().await = ConnState::Unhealthy;
+                }
+                result
+            }
+        }
+    }
 
-                drop(guard);
+    /// Send over a socket that carries one request at a time
+    async fn send_exclusive<'a, S: AsyncRead + AsyncWrite + Unpin>(
+        stream: &Mutex<S>,
+        health: &RwLock<ConnState>,
+        last_activity: &RwLock<Instant>,
+        inner: &SynapseInner,
+        req_bytes: &[u8],
+    ) -> Result<Response<'a, Vec<u8>>> {
+        // Check health
+        let state = *health.read().await;
+        if state == ConnState::CircuitOpen {
+            return Err(anyhow::anyhow!("Circuit breaker open"));
+        }
 
-                match result {
-                    Ok(resp) => {
-                        *last_activity.write().await = Instant::now();
-                        Ok(resp)
-                    }
-                    Err(e) => {
-                        *health.write().await = ConnState::Unhealthy;
-                        Err(e)
-                    }
+        let mut guard = stream.lock().await;
+        let result = Self::send_socket(
+            &mut *guard,
+            inner.my_id,
+            channel::APP,
+            req_bytes,
+            inner.config.request_timeout,
+        )
+        .await;
+
+        drop(guard);
+
+        match result {
+            Ok(resp) => {
+                *last_activity.write().await = Instant::now();
+                Ok(resp)
+            }
+            Err(e) => {
+                if !crate::error::is_remote(&e) {
+                    *health.write().await = ConnState::Unhealthy;
                 }
+                Err(e)
             }
         }
     }
 
-    /// Send over socket transport
-    async fn send_socket(
-        stream: &mut UnixStream,
+    /// Send over socket (or in-process pipe) transport
+    async fn send_socket<S: AsyncRead + AsyncWrite + Unpin>(
+        stream: &mut S,
         my_id: u64,
+        chan: u8,
         payload: &[u8],
         timeout: Duration,
     ) -> Result<Response<'static, Vec<u8>>> {
+        let trace = crate::trace::outgoing();
+        let priority = crate::priority::current();
+        let mut flags = 0;
+        if trace.is_some() {
+            flags |= VesicleHeader::TRACED;
+        }
+        if priority.is_some() {
+            flags |= VesicleHeader::PRIORITIZED;
+        }
         let header = VesicleHeader {
             target_id: 0,
             source_id: my_id,
             ttl: 64,
-            flags: 0,
-            _pad: [0; 6],
+            flags,
+            priority: priority.unwrap_or(0),
+            _pad: [0; 5],
         };
 
-        let total_len = 24 + 1 + payload.len();
+        let trace_len = trace.map_or(0, |_| TraceContext::SIZE);
+        let total_len = 24 + 1 + trace_len + payload.len();
 
         // Send with timeout
         tokio::time::timeout(timeout, async {
             stream.write_all(&(total_len as u32).to_le_bytes()).await?;
             let h_bytes: [u8; 24] = unsafe { std::mem::transmute(header) };
             stream.write_all(&h_bytes).await?;
-            stream.write_u8(channel::APP).await?;
+            stream.write_u8(chan).await?;
+            if let Some(trace) = trace {
+                stream.write_all(&trace.to_bytes()).await?;
+            }
             stream.write_all(payload).await?;
             stream.flush().await?;
             Ok::<(), anyhow::Error>(())
@@ -704,7 +1115,8 @@ impl ResilientSynapse {
             Err(_) => return Err(anyhow::anyhow!("Socket read timeout")),
         }
 
-        let len = u32::from_le_bytes(len_buf) as usize;
+        let len_prefix = u32::from_le_bytes(len_buf);
+        let len = (len_prefix & cell_core::frame::LEN_MASK) as usize;
         if len > 100 * 1024 * 1024 {
             // 100MB sanity limit
             return Err(anyhow::anyhow!("Response too large: {} bytes", len));
@@ -718,7 +1130,10 @@ impl ResilientSynapse {
             Err(_) => return Err(anyhow::anyhow!("Socket read timeout")),
         }
 
-        Ok(Response::Owned(buf))
+        if len_prefix & cell_core::frame::ERROR != 0 {
+            return Err(crate::error::from_frame(&buf));
+        }
+        Ok(Response::from_frame(len_prefix, buf))
     }
 
     /// Record successful request
@@ -762,7 +1177,14 @@ impl ResilientSynapse {
                 Transport::Shm { health, .. } => {
                     *health.write().await = ConnState::CircuitOpen;
                 }
-                Transport::Socket { health, .. } => {
+                Transport::Socket { health, .. }
+                | Transport::Tcp { health, .. }
+                | Transport::Multiplexed { health, .. }
+                | Transport::Local { health, .. } => {
+                    *health.write().await = ConnState::CircuitOpen;
+                }
+                #[cfg(feature = "quic")]
+                Transport::Quic { health, .. } => {
                     *health.write().await = ConnState::CircuitOpen;
                 }
             }
@@ -774,6 +1196,14 @@ impl ResilientSynapse {
         self.metrics.read().await.current_state
     }
 
+    /// `(built against, served)` schema fingerprints when the cell serves a
+    /// different schema and the connection was kept in compat mode.
+    pub fn schema_mismatch(&self) -> Option<(u64, u64)> {
+        let served = self.served_schema.load(Ordering::Relaxed);
+        (self.built_schema != 0 && served != 0 && served != self.built_schema)
+            .then_some((self.built_schema, served))
+    }
+
     /// Force reconnection (useful for explicit recovery)
     pub async fn force_reconnect(&self) -> Result<()> {
         let mut inner = self.inner.write().await;
@@ -4,6 +4,8 @@ pub enum Response<'a, T> {
     Owned(Vec<u8>),
     Borrowed(&'a [u8]),
     Typed(T),
+    /// Answered by a fallback handler
+    Degraded(Vec<u8>),
 }
 
 impl<'a, T> Response<'a, T> {
@@ -11,7 +13,21 @@ impl<'a, T> Response<'a, T> {
         match self {
             Response::Owned(v) => v,
             Response::Borrowed(v) => v.to_vec(),
+            Response::Degraded(v) => v,
             _ => Vec::new(),
         }
     }
+
+    pub fn is_degraded(&self) -> bool {
+        matches!(self, Response::Degraded(_))
+    }
+
+    /// Build from a response frame's length prefix and payload.
+    pub fn from_frame(len_prefix: u32, payload: Vec<u8>) -> Self {
+        if len_prefix & cell_core::frame::DEGRADED != 0 {
+            Response::Degraded(payload)
+        } else {
+            Response::Owned(payload)
+        }
+    }
 }
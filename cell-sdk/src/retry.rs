@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/retry.rs
+//! Client-side enforcement of the retry policy a cell advertises.
+//!
+//! A handler method declares `#[handler(retry(max_attempts = 3, backoff_ms = 50,
+//! idempotent))]`; the policy travels in the cell's schema and `cell_remote!`
+//! wraps each call of that method in [`run`]. Methods without a policy are
+//! called once.
+//!
+//! Calls the cell turned away before the handler ran (`Throttled` from load
+//! shedding, `RateLimited` from quotas) are retried for every method. Other
+//! retryable failures may have left the handler half done, so they are only
+//! retried when the method is idempotent.
+//!
+//! Consumers can bound calls and tune retries per client with [`ClientOptions`]
+//! (`Client::connect_with` and `.timeout(..)` on generated clients); the
+//! idempotency of a method stays the cell's to declare.
+
+use crate::error::{CellError, ErrorContext};
+use std::future::Future;
+use std::time::Duration;
+
+/// Longest pause between two attempts, whatever the backoff or hint says
+pub const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Mirror of the schema's `RetryPolicy`, built by generated clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub idempotent: bool,
+    /// Total attempts, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on each further one
+    pub backoff_ms: u64,
+}
+
+impl RetryPolicy {
+    /// Whether a failed attempt may be repeated under this policy.
+    pub fn should_retry(&self, err: &anyhow::Error) -> bool {
+        let ctx = ErrorContext::classify(err, CellError::TransportUnavailable);
+        if rejected_at_ingress(ctx.code) {
+            return true;
+        }
+        self.idempotent && ctx.is_retryable()
+    }
+
+    /// Pause before attempt `attempt + 1`: the exponential backoff, or the
+    /// cell's retry hint when that is longer.
+    pub fn delay(&self, attempt: u32, err: &anyhow::Error) -> Duration {
+        let backoff = Duration::from_millis(self.backoff_ms)
+            .saturating_mul(1u32 << attempt.saturating_sub(1).min(16));
+        let hint = err
+            .downcast_ref::<ErrorContext>()
+            .and_then(|ctx| ctx.retry_after)
+            .unwrap_or_default();
+        backoff.max(hint).min(MAX_BACKOFF)
+    }
+}
+
+/// Per-client call settings; `None` keeps the cell's advertised policy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClientOptions {
+    /// Bound on each attempt; `None` waits as long as the connection does
+    pub timeout: Option<Duration>,
+    /// Retries after the first attempt
+    pub retries: Option<u32>,
+    /// Delay before the first retry, doubled on each further one
+    pub backoff: Option<Duration>,
+    /// Connections to open to the cell, see [`crate::SynapsePool`]; `None`
+    /// opens one
+    pub connections: Option<usize>,
+    /// Share each connection between concurrent calls, see
+    /// [`crate::correlator`]
+    pub multiplex: bool,
+}
+
+impl ClientOptions {
+    /// The cell's policy for a method with these overrides applied. Methods
+    /// without one are treated as non-idempotent.
+    pub fn policy(&self, advertised: Option<RetryPolicy>) -> Option<RetryPolicy> {
+        if self.retries.is_none() && self.backoff.is_none() {
+            return advertised;
+        }
+        let base = advertised.unwrap_or(RetryPolicy {
+            idempotent: false,
+            max_attempts: 1,
+            backoff_ms: 100,
+        });
+        Some(RetryPolicy {
+            max_attempts: self
+                .retries
+                .map_or(base.max_attempts, |r| r.saturating_add(1)),
+            backoff_ms: self
+                .backoff
+                .map_or(base.backoff_ms, |b| b.as_millis() as u64),
+            ..base
+        })
+    }
+}
+
+/// Codes the Membrane or dispatch returns before the handler is entered
+fn rejected_at_ingress(code: CellError) -> bool {
+    matches!(code, CellError::Throttled | CellError::RateLimited)
+}
+
+/// Await one attempt, failing with `Timeout` once `timeout` has passed.
+pub async fn bounded<T>(
+    timeout: Option<Duration>,
+    call: impl Future<Output = anyhow::Result<T>>,
+) -> anyhow::Result<T> {
+    let Some(timeout) = timeout else {
+        return call.await;
+    };
+    match tokio::time::timeout(timeout, call).await {
+        Ok(result) => result,
+        Err(_) => Err(ErrorContext::new(CellError::Timeout)
+            .with_message(format!("No response within {:?}", timeout))
+            .into()),
+    }
+}
+
+/// Run `call` under `policy`, returning the first success or the last error.
+pub async fn run<T, F, Fut>(policy: Option<RetryPolicy>, mut call: F) -> anyhow::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let Some(policy) = policy else {
+        return call().await;
+    };
+    let mut attempt = 1;
+    loop {
+        match call().await {
+            Err(e) if attempt < policy.max_attempts && policy.should_retry(&e) => {
+                let delay = policy.delay(attempt, &e);
+                tracing::debug!(attempt, ?delay, "Retrying call: {}", e);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
@@ -14,6 +14,16 @@ use tracing::{info, warn};
 pub struct Runtime;
 
 impl Runtime {
+    /// Add a custom check to the cell's OPS `HealthCheck` (and its watchdog
+    /// probes). While it returns `Err` the cell reports itself not ready.
+    pub fn register_health_check<F, Fut>(name: &str, check: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        crate::watchdog::register(name, check)
+    }
+
     /// Entry point for cells with dependencies.
     pub async fn ignite_with_deps<S, Req, Resp>(service: S, name: &str, deps: &[&str]) -> Result<()>
     where
@@ -0,0 +1,152 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/shed.rs
+//! Priority-based load shedding for application requests.
+//!
+//! Disabled until a cell calls [`enable`]. Load is measured as *pressure*:
+//! in-flight handler calls against `max_in_flight`, or the moving average of
+//! handler latency against `latency_slo`, whichever is worse. Below 1.0
+//! everything is admitted. Above it, methods are shed from the lowest
+//! priority up; by 2.0 only methods at [`NEVER_SHED`] are still served.
+//!
+//! Priorities come from `#[handler(priority = 90)]` on the method (default
+//! [`DEFAULT_PRIORITY`]) and can be overridden per method in [`ShedConfig`].
+//! A call part of a higher-priority call chain is admitted at the chain's
+//! priority instead (see [`crate::priority`]).
+//! Shed calls fail with `Throttled` and a retry-after hint. OPS and health
+//! traffic never reaches a handler, so it is always served.
+
+use crate::error::{CellError, ErrorContext};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_PRIORITY: u8 = 50;
+/// Methods at this priority or above are never shed.
+pub const NEVER_SHED: u8 = 100;
+
+static CONFIG: OnceLock<ShedConfig> = OnceLock::new();
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+/// Moving average of handler latency in microseconds
+static LATENCY_US: AtomicU64 = AtomicU64::new(0);
+static SHED: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone)]
+pub struct ShedConfig {
+    /// In-flight calls at which shedding starts
+    pub max_in_flight: usize,
+    /// Average latency at which shedding starts
+    pub latency_slo: Option<Duration>,
+    /// Per-method priority overrides
+    pub priorities: HashMap<String, u8>,
+}
+
+impl ShedConfig {
+    pub fn new(max_in_flight: usize) -> Self {
+        Self {
+            max_in_flight: max_in_flight.max(1),
+            latency_slo: None,
+            priorities: HashMap::new(),
+        }
+    }
+
+    pub fn latency_slo(mut self, slo: Duration) -> Self {
+        self.latency_slo = Some(slo);
+        self
+    }
+
+    pub fn priority(mut self, method: &str, priority: u8) -> Self {
+        self.priorities.insert(method.to_string(), priority);
+        self
+    }
+}
+
+/// Enable shedding for this process. Only the first call takes effect.
+pub fn enable(config: ShedConfig) {
+    let _ = CONFIG.set(config);
+}
+
+/// Current pressure; 0.0 while shedding is disabled.
+pub fn pressure() -> f64 {
+    let Some(config) = CONFIG.get() else {
+        return 0.0;
+    };
+    let queue = IN_FLIGHT.load(Ordering::Relaxed) as f64 / config.max_in_flight as f64;
+    let latency = match config.latency_slo {
+        Some(slo) if !slo.is_zero() => {
+            LATENCY_US.load(Ordering::Relaxed) as f64 / slo.as_micros() as f64
+        }
+        _ => 0.0,
+    };
+    queue.max(latency)
+}
+
+/// Calls rejected since start.
+pub fn shed_total() -> u64 {
+    SHED.load(Ordering::Relaxed)
+}
+
+/// Called by generated dispatch before each handler call. Keep the returned
+/// guard until the call finishes.
+pub fn admit(method: &str, priority: u8) -> Result<InFlight, ErrorContext> {
+    let config = CONFIG.get();
+    let priority = config
+        .and_then(|c| c.priorities.get(method).copied())
+        .unwrap_or(priority);
+    let priority = crate::priority::inherit(priority);
+    if config.is_some() {
+        let pressure = pressure();
+        if priority < NEVER_SHED && pressure > 1.0 {
+            let overshoot = (pressure - 1.0).min(1.0);
+            let cutoff = (overshoot * NEVER_SHED as f64).ceil() as u8;
+            if priority < cutoff {
+                SHED.fetch_add(1, Ordering::Relaxed);
+                let average = Duration::from_micros(LATENCY_US.load(Ordering::Relaxed));
+                let retry_after = average
+                    .mul_f64(1.0 + overshoot)
+                    .clamp(Duration::from_millis(50), Duration::from_secs(10));
+                return Err(ErrorContext::new(CellError::Throttled)
+                    .with_message(format!(
+                        "'{}' (priority {}) shed at {:.0}% load",
+                        method,
+                        priority,
+                        pressure * 100.0
+                    ))
+                    .with_retry_after(retry_after));
+            }
+        }
+    }
+    IN_FLIGHT.fetch_add(1, Ordering::Relaxed);
+    Ok(InFlight {
+        started: Instant::now(),
+        priority,
+    })
+}
+
+/// An admitted call; dropping it records its latency.
+pub struct InFlight {
+    started: Instant,
+    priority: u8,
+}
+
+impl InFlight {
+    /// Priority the call was admitted at, inherited or its method's
+    pub fn priority(&self) -> u8 {
+        self.priority
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        IN_FLIGHT.fetch_sub(1, Ordering::Relaxed);
+        let sample = self.started.elapsed().as_micros() as u64;
+        // EWMA with weight 1/8; races only lose a sample
+        let average = LATENCY_US.load(Ordering::Relaxed);
+        let next = if average == 0 {
+            sample
+        } else {
+            average - average / 8 + sample / 8
+        };
+        LATENCY_US.store(next, Ordering::Relaxed);
+    }
+}
@@ -18,10 +18,23 @@ use std::marker::PhantomData;
 use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
 use std::ptr;
 use std::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, Weak};
 use thiserror::Error;
 use tracing::{debug, error, trace, warn};
 
+/// Rings mapped by this process, for `cell inspect`
+static LIVE_RINGS: Mutex<Vec<Weak<RingBuffer>>> = Mutex::new(Vec::new());
+
+/// Rings currently mapped by this process.
+pub fn live_rings() -> Vec<Arc<RingBuffer>> {
+    LIVE_RINGS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(Weak::upgrade)
+        .collect()
+}
+
 /// SHM-specific errors with structured error types
 #[derive(Error, Debug, Clone, PartialEq)]
 pub enum ShmError {
@@ -214,13 +227,29 @@ impl RingBuffer {
         let data = unsafe { ptr.add(DATA_OFFSET) };
         let capacity = size.saturating_sub(DATA_OFFSET);
 
-        Ok(Arc::new(Self {
+        let ring = Arc::new(Self {
             control,
             data,
             capacity,
             _mmap: mmap,
             _file: file,
-        }))
+        });
+        let mut live = LIVE_RINGS.lock().unwrap();
+        live.retain(|r| r.strong_count() > 0);
+        live.push(Arc::downgrade(&ring));
+        Ok(ring)
+    }
+
+    /// Total data capacity in bytes
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Bytes written but not yet consumed
+    pub fn used(&self) -> usize {
+        let write = unsafe { (*self.control).write_pos.load(Ordering::Acquire) };
+        let read = unsafe { (*self.control).read_pos.load(Ordering::Acquire) };
+        write.saturating_sub(read) as usize
     }
 
     /// Try to allocate a slot for writing
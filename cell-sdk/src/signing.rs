@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/signing.rs
+//! Producer signatures on replicated log entries.
+//!
+//! A producer (a cell writing to a registry or audit log) holds an Ed25519
+//! key at `~/.cell/keys/producer.key` and signs each command with
+//! [`ProducerKey::sign`]. Replicas only hold public keys, one file per producer
+//! in `~/.cell/keys/producers/<producer>.pub`, loaded as [`TrustedProducers`]:
+//! unlike the shared mesh keys, nothing a replica holds lets it forge an entry.
+//! See `cell_model::signed_entry` for what is signed.
+
+use anyhow::{anyhow, bail, Context, Result};
+pub use cell_model::signed_entry::{ProducerWatermarks, SignedCommand};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub struct ProducerKey(SigningKey);
+
+impl ProducerKey {
+    pub fn default_path() -> Result<PathBuf> {
+        let home = dirs::home_dir().context("No HOME")?;
+        Ok(home.join(".cell/keys/producer.key"))
+    }
+
+    /// Load the key at `path`, generating a fresh one if none exists yet.
+    /// Replicas learn its public half through [`ProducerKey::export_public`].
+    pub fn load_or_create(path: &Path) -> Result<Self> {
+        if let Ok(bytes) = std::fs::read(path) {
+            let seed: [u8; 32] = bytes
+                .as_slice()
+                .try_into()
+                .map_err(|_| anyhow!("Producer key {:?} must be 32 bytes", path))?;
+            return Ok(Self(SigningKey::from_bytes(&seed)));
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let seed: [u8; 32] = rand::random();
+        std::fs::write(path, seed)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(Self(SigningKey::from_bytes(&seed)))
+    }
+
+    pub fn load_default() -> Result<Self> {
+        Self::load_or_create(&Self::default_path()?)
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.0.verifying_key().to_bytes()
+    }
+
+    /// Write the public key as `<dir>/<producer>.pub`, for replicas to trust
+    pub fn export_public(&self, dir: &Path, producer: &str) -> Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}.pub", producer));
+        std::fs::write(&path, self.public_key())?;
+        Ok(path)
+    }
+
+    /// Raw Ed25519 signature over `payload`, for formats with their own
+    /// framing (see `crate::provenance`)
+    pub fn sign_bytes(&self, payload: &[u8]) -> Vec<u8> {
+        self.0.sign(payload).to_bytes().to_vec()
+    }
+
+    /// Sign `data` as entry `seq` of `producer` in `log`
+    pub fn sign(&self, log: &str, producer: &str, seq: u64, data: Vec<u8>) -> SignedCommand {
+        let payload = SignedCommand::payload(log, producer, seq, &data);
+        let signature = self.0.sign(&payload);
+        SignedCommand {
+            producer: producer.to_string(),
+            seq,
+            data,
+            signature: signature.to_bytes().to_vec(),
+        }
+    }
+}
+
+/// Public keys of the producers a log accepts entries from
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProducers {
+    keys: HashMap<String, VerifyingKey>,
+}
+
+impl TrustedProducers {
+    pub fn default_dir() -> Result<PathBuf> {
+        let home = dirs::home_dir().context("No HOME")?;
+        Ok(home.join(".cell/keys/producers"))
+    }
+
+    /// Every `<producer>.pub` in `dir`
+    pub fn load_dir(dir: &Path) -> Result<Self> {
+        let mut trusted = Self::default();
+        let entries = std::fs::read_dir(dir)
+            .with_context(|| format!("No trusted producer keys at {:?}", dir))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "pub") {
+                let Some(producer) = path.file_stem().and_then(|s| s.to_str()) else {
+                    continue;
+                };
+                let bytes = std::fs::read(&path)?;
+                let key: [u8; 32] = bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| anyhow!("Producer key {:?} must be 32 bytes", path))?;
+                trusted.insert(producer, key)?;
+            }
+        }
+        Ok(trusted)
+    }
+
+    pub fn insert(&mut self, producer: &str, public_key: [u8; 32]) -> Result<()> {
+        let key = VerifyingKey::from_bytes(&public_key)
+            .map_err(|e| anyhow!("Invalid key for producer '{}': {}", producer, e))?;
+        self.keys.insert(producer.to_string(), key);
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Check that a trusted producer signed `command` for `log`
+    pub fn verify(&self, log: &str, command: &SignedCommand) -> Result<()> {
+        let Some(key) = self.keys.get(&command.producer) else {
+            bail!("Unknown producer '{}'", command.producer);
+        };
+        let signature = Signature::from_slice(&command.signature)
+            .map_err(|_| anyhow!("Malformed signature from '{}'", command.producer))?;
+        if key
+            .verify_strict(&command.signed_payload(log), &signature)
+            .is_err()
+        {
+            bail!(
+                "Bad signature from '{}' on entry {}",
+                command.producer,
+                command.seq
+            );
+        }
+        Ok(())
+    }
+
+    /// Check a raw signature of `producer` over `payload`
+    pub fn verify_bytes(&self, producer: &str, payload: &[u8], signature: &[u8]) -> Result<()> {
+        let Some(key) = self.keys.get(producer) else {
+            bail!("Unknown producer '{}'", producer);
+        };
+        let signature = Signature::from_slice(signature)
+            .map_err(|_| anyhow!("Malformed signature from '{}'", producer))?;
+        if key.verify_strict(payload, &signature).is_err() {
+            bail!("Bad signature from '{}'", producer);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/slo.rs
+//! Per-method SLO tracking inside a cell.
+//!
+//! Objectives come from the `[slo.<method>]` tables of the cell's `Cell.toml`
+//! (see `cell_model::slo`), read on first use unless [`enable`] set them
+//! earlier. `#[handler]` dispatch reports every call to [`record`]; OPS `Slo`
+//! returns the windowed counts, from which the observer and `cell slo` compute
+//! compliance and error budgets. `cell rollout` refuses to touch a cell whose
+//! budget is spent.
+
+use crate::state::ops;
+use anyhow::{anyhow, bail, Result};
+use cell_model::manifest::CellManifest;
+use cell_model::ops::{OpsRequest, OpsResponse};
+use cell_model::slo::{Slo, SloLedger, SloReport, SloStatus};
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+static LEDGER: OnceLock<Mutex<SloLedger>> = OnceLock::new();
+
+/// Track `objectives` instead of the ones in `Cell.toml`. Only the first call
+/// (or first recorded request) takes effect.
+pub fn enable(objectives: BTreeMap<String, Slo>) {
+    let _ = LEDGER.set(Mutex::new(SloLedger::new(objectives)));
+}
+
+fn ledger() -> &'static Mutex<SloLedger> {
+    LEDGER.get_or_init(|| Mutex::new(SloLedger::new(from_manifest())))
+}
+
+/// Objectives declared in `./Cell.toml`, if any.
+fn from_manifest() -> BTreeMap<String, Slo> {
+    let Ok(content) = std::fs::read_to_string("Cell.toml") else {
+        return BTreeMap::new();
+    };
+    match toml::from_str::<CellManifest>(&content) {
+        Ok(manifest) => manifest.slo.into_iter().collect(),
+        Err(e) => {
+            warn!("Ignoring SLOs: failed to parse Cell.toml: {}", e);
+            BTreeMap::new()
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Called by generated dispatch after each handler call.
+pub fn record(method: &str, elapsed: Duration, ok: bool) {
+    let mut ledger = ledger().lock().unwrap();
+    if ledger.is_tracked(method) {
+        ledger.record(method, elapsed.as_millis() as u64, ok, now_ms());
+    }
+}
+
+/// Counts for every method with objectives, for OPS `Slo`.
+pub fn report() -> Vec<SloReport> {
+    ledger().lock().unwrap().report(now_ms())
+}
+
+/// Ask a running cell for its SLO counts.
+pub async fn fetch(cell_name: &str) -> Result<Vec<SloReport>> {
+    match ops(cell_name, &OpsRequest::Slo).await? {
+        OpsResponse::Slo(reports) => Ok(reports),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
+
+/// Compliance and budget of each method of a running cell.
+pub async fn status(cell_name: &str) -> Result<Vec<SloStatus>> {
+    Ok(fetch(cell_name)
+        .await?
+        .iter()
+        .map(SloReport::status)
+        .collect())
+}
@@ -0,0 +1,126 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/slowlog.rs
+//! Slow-request log.
+//!
+//! `#[handler]` dispatch times every call and reports it here. Calls at or
+//! above `CELL_SLOW_REQUEST_MS` (default 500) are logged under the
+//! `cell::slow` target and kept in a bounded in-memory log that OPS
+//! `SlowRequests` (and through it the observer cell) can read back.
+//!
+//! Arguments are only captured when `CELL_SLOW_REQUEST_ARGS` sets a size
+//! limit in bytes; they are serialized as JSON after the call, so fast calls
+//! pay nothing for it.
+
+use crate::state::ops;
+use anyhow::{anyhow, bail, Result};
+use cell_model::ops::{OpsRequest, OpsResponse, SlowRequest};
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// Records kept for OPS `SlowRequests`; older ones are dropped.
+pub const RETAINED: usize = 256;
+
+static LOG: Mutex<VecDeque<SlowRequest>> = Mutex::new(VecDeque::new());
+
+#[derive(Debug, Clone, Copy)]
+pub struct SlowLogConfig {
+    pub threshold: Duration,
+    /// Byte limit for captured arguments; `None` disables capture
+    pub max_args: Option<usize>,
+}
+
+impl SlowLogConfig {
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+        Self {
+            threshold: Duration::from_millis(var("CELL_SLOW_REQUEST_MS").unwrap_or(500)),
+            max_args: var("CELL_SLOW_REQUEST_ARGS")
+                .filter(|n| *n > 0)
+                .map(|n| n as usize),
+        }
+    }
+}
+
+fn config() -> &'static SlowLogConfig {
+    static CONFIG: OnceLock<SlowLogConfig> = OnceLock::new();
+    CONFIG.get_or_init(SlowLogConfig::from_env)
+}
+
+/// Called by generated dispatch after each handler call. `args` serializes
+/// the request and only runs for slow calls with capture enabled.
+pub fn observe(method: &str, elapsed: Duration, ok: bool, args: impl FnOnce() -> Option<String>) {
+    let config = config();
+    if elapsed < config.threshold {
+        return;
+    }
+    let args = config
+        .max_args
+        .and_then(|limit| args().map(|a| truncate(a, limit)));
+    record(SlowRequest {
+        method: method.to_string(),
+        duration_ms: elapsed.as_millis() as u64,
+        caller: crate::auth::caller().map(|c| c.principal),
+        ok,
+        args,
+        at_unix_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+    });
+}
+
+/// Log and retain one slow call.
+pub fn record(entry: SlowRequest) {
+    warn!(
+        target: "cell::slow",
+        method = %entry.method,
+        duration_ms = entry.duration_ms,
+        caller = entry.caller.as_deref().unwrap_or("-"),
+        ok = entry.ok,
+        args = entry.args.as_deref().unwrap_or(""),
+        "Slow request"
+    );
+    let mut log = LOG.lock().unwrap();
+    if log.len() == RETAINED {
+        log.pop_front();
+    }
+    log.push_back(entry);
+}
+
+/// The newest `limit` slow calls, oldest first.
+pub fn recent(limit: usize) -> Vec<SlowRequest> {
+    let log = LOG.lock().unwrap();
+    log.iter()
+        .skip(log.len().saturating_sub(limit))
+        .cloned()
+        .collect()
+}
+
+/// JSON form of a request for argument capture.
+pub fn to_json<T: serde::Serialize>(value: &T) -> Option<String> {
+    serde_json::to_string(value).ok()
+}
+
+fn truncate(mut s: String, limit: usize) -> String {
+    if s.len() <= limit {
+        return s;
+    }
+    let mut end = limit;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    s.truncate(end);
+    s.push('…');
+    s
+}
+
+/// Ask a running cell for its slow-request log.
+pub async fn fetch(cell_name: &str, limit: u32) -> Result<Vec<SlowRequest>> {
+    match ops(cell_name, &OpsRequest::SlowRequests { limit }).await? {
+        OpsResponse::SlowRequests(entries) => Ok(entries),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
@@ -0,0 +1,126 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/source.rs
+//! The public schema a cell answers `GetSource` with.
+//!
+//! `#[handler]` embeds the cell's proteins and handler signatures (bodies
+//! stripped, see `cell_build::spore::extract_schema`) and registers them when
+//! the service starts serving. Tools such as `cell schema diff` read it back
+//! from the running cell with [`fetch`].
+//!
+//! The schema fingerprint is registered alongside it. Synapses built against a
+//! schema send theirs in an OPS `Handshake` on connect and, by [`SchemaCheck`],
+//! refuse a cell that serves a different one instead of failing every call with
+//! a protocol mismatch.
+
+use crate::error::{CellError, ErrorContext};
+use crate::state::ops;
+use anyhow::{anyhow, bail, Result};
+use cell_model::ops::{OpsRequest, OpsResponse};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use tracing::warn;
+
+static SCHEMA: OnceLock<&'static str> = OnceLock::new();
+static FINGERPRINT: AtomicU64 = AtomicU64::new(0);
+
+/// What a synapse does when the cell serves another schema than the one it
+/// was built against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaCheck {
+    /// Fail the connect
+    #[default]
+    Strict,
+    /// Connect anyway; decode failures name both fingerprints
+    Compat,
+}
+
+impl SchemaCheck {
+    /// `CELL_SCHEMA_CHECK=compat` downgrades, e.g. for a rolling upgrade.
+    pub fn from_env() -> Self {
+        match std::env::var("CELL_SCHEMA_CHECK").as_deref() {
+            Ok("compat") => Self::Compat,
+            _ => Self::Strict,
+        }
+    }
+}
+
+pub fn register(schema: &'static str) {
+    let _ = SCHEMA.set(schema);
+}
+
+pub fn get() -> Option<&'static str> {
+    SCHEMA.get().copied()
+}
+
+/// Set the schema fingerprint this cell serves; 0 leaves handshakes unchecked.
+pub fn register_fingerprint(fingerprint: u64) {
+    FINGERPRINT.store(fingerprint, Ordering::Relaxed);
+}
+
+pub fn fingerprint() -> u64 {
+    FINGERPRINT.load(Ordering::Relaxed)
+}
+
+/// Answer a peer's handshake. The peer decides what a mismatch means; the
+/// cell only logs it so stale clients show up.
+pub(crate) fn handshake(peer: u64) -> u64 {
+    let ours = fingerprint();
+    if peer != 0 && ours != 0 && peer != ours {
+        warn!(
+            "Peer built against schema {:016x}, this cell serves {:016x}",
+            peer, ours
+        );
+    }
+    ours
+}
+
+/// OPS frame a synapse sends on connect.
+pub(crate) fn handshake_request(fingerprint: u64) -> Result<Vec<u8>> {
+    let req = OpsRequest::Handshake { fingerprint };
+    Ok(rkyv::to_bytes::<_, 256>(&req)?.into_vec())
+}
+
+/// The fingerprint in a handshake reply. Cells that predate the handshake
+/// answer with an error and count as unknown (0).
+pub(crate) fn handshake_response(bytes: &[u8]) -> Result<u64> {
+    let archived = rkyv::check_archived_root::<OpsResponse>(bytes)
+        .map_err(|e| anyhow!("Invalid OPS response: {:?}", e))?;
+    match rkyv::Deserialize::deserialize(archived, &mut rkyv::Infallible)? {
+        OpsResponse::Handshake { fingerprint } => Ok(fingerprint),
+        _ => Ok(0),
+    }
+}
+
+/// Compare the fingerprint a caller was built against with the one `cell_name`
+/// serves. Either being 0 means unknown and passes.
+pub fn check(cell_name: &str, built: u64, served: u64, mode: SchemaCheck) -> Result<()> {
+    if built == 0 || served == 0 || built == served {
+        return Ok(());
+    }
+    let message = format!(
+        "'{}' serves schema {:016x}, this client was built against {:016x}",
+        cell_name, served, built
+    );
+    match mode {
+        SchemaCheck::Strict => Err(ErrorContext::new(CellError::ProtocolMismatch)
+            .with_message(format!(
+                "{}; rebuild the client or set CELL_SCHEMA_CHECK=compat",
+                message
+            ))
+            .with_operation("handshake")
+            .into()),
+        SchemaCheck::Compat => {
+            warn!("{}; continuing in compat mode", message);
+            Ok(())
+        }
+    }
+}
+
+/// Ask a running cell for its schema.
+pub async fn fetch(cell_name: &str) -> Result<String> {
+    match ops(cell_name, &OpsRequest::GetSource).await? {
+        OpsResponse::Source { bytes } => Ok(String::from_utf8(bytes)?),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
@@ -0,0 +1,261 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/state.rs
+//! Moving in-memory state between instances.
+//!
+//! A cell implements [`CellState`] for its state and hands it to [`manage`]
+//! before calling `serve()`. The Membrane then answers the OPS `Checkpoint`
+//! and `Restore` commands, which the swap-coordinator (hot swap, rolling
+//! restart) and the nucleus (node migration) use through [`fetch`] and [`push`].
+//!
+//! For crash recovery, managed state is also written to
+//! `~/.cell/state/<cell>/checkpoint` every `CELL_CHECKPOINT_INTERVAL` seconds
+//! (default 30) and restored from there when the cell starts, whatever
+//! directory it was started from.
+
+use crate::membrane::BoxFuture;
+use crate::Synapse;
+use anyhow::{anyhow, bail, Context, Result};
+use cell_core::channel;
+use cell_model::ops::{OpsRequest, OpsResponse};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+pub trait CellState: Send + Sync + 'static {
+    fn checkpoint(&self) -> Vec<u8>;
+    fn restore(&mut self, bytes: Vec<u8>);
+}
+
+struct Managed {
+    checkpoint: Box<dyn Fn() -> BoxFuture<'static, Vec<u8>> + Send + Sync>,
+    restore: Box<dyn Fn(Vec<u8>) -> BoxFuture<'static, ()> + Send + Sync>,
+}
+
+static MANAGED: OnceLock<Managed> = OnceLock::new();
+
+/// Expose `state` to OPS checkpoint/restore and periodic crash checkpoints.
+/// Restores the last crash checkpoint, if any. Only the first call takes effect.
+pub async fn manage<S: CellState>(state: Arc<RwLock<S>>) {
+    if let Some(bytes) = read_local().await {
+        info!("[State] Restoring {} bytes from crash checkpoint", bytes.len());
+        state.write().await.restore(bytes);
+    }
+
+    let (read, write) = (state.clone(), state);
+    let managed = Managed {
+        checkpoint: Box::new(move || {
+            let state = read.clone();
+            Box::pin(async move { state.read().await.checkpoint() })
+        }),
+        restore: Box::new(move |bytes| {
+            let state = write.clone();
+            Box::pin(async move { state.write().await.restore(bytes) })
+        }),
+    };
+    if MANAGED.set(managed).is_err() {
+        return;
+    }
+
+    let interval = std::env::var("CELL_CHECKPOINT_INTERVAL")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(30);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(tokio::time::Duration::from_secs(interval));
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if let Some(bytes) = checkpoint().await {
+                if let Err(e) = write_local(&bytes).await {
+                    warn!("[State] Crash checkpoint failed: {}", e);
+                }
+            }
+        }
+    });
+}
+
+/// Snapshot of the managed state, if the cell has any.
+pub async fn checkpoint() -> Option<Vec<u8>> {
+    let managed = MANAGED.get()?;
+    Some((managed.checkpoint)().await)
+}
+
+pub async fn restore(bytes: Vec<u8>) -> Result<()> {
+    let managed = MANAGED.get().context("Cell has no managed state")?;
+    (managed.restore)(bytes).await;
+    Ok(())
+}
+
+/// Answer an OPS command on behalf of the Membrane.
+pub(crate) async fn handle_ops(req: OpsRequest) -> OpsResponse {
+    match req {
+        OpsRequest::Ping => OpsResponse::Pong,
+        OpsRequest::Status => crate::status::report(),
+        OpsRequest::Checkpoint => match checkpoint().await {
+            Some(bytes) => OpsResponse::Checkpoint { bytes },
+            None => OpsResponse::Error {
+                message: "Cell has no managed state".to_string(),
+            },
+        },
+        OpsRequest::Restore { bytes } => match restore(bytes).await {
+            Ok(()) => OpsResponse::Restored,
+            Err(e) => OpsResponse::Error {
+                message: e.to_string(),
+            },
+        },
+        OpsRequest::GetSource => match crate::source::get() {
+            Some(schema) => OpsResponse::Source {
+                bytes: schema.as_bytes().to_vec(),
+            },
+            None => OpsResponse::Error {
+                message: "Cell has no registered schema".to_string(),
+            },
+        },
+        OpsRequest::Handshake { fingerprint } => OpsResponse::Handshake {
+            fingerprint: crate::source::handshake(fingerprint),
+        },
+        OpsRequest::Health => OpsResponse::Health(crate::watchdog::report().await),
+        OpsRequest::HealthCheck => OpsResponse::HealthCheck(crate::watchdog::status().await),
+        OpsRequest::Drain => {
+            crate::watchdog::drain();
+            OpsResponse::Draining
+        }
+        OpsRequest::Shutdown => {
+            crate::watchdog::drain();
+            tokio::spawn(async {
+                // Let the acknowledgement reach the caller first
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                crate::handover::retire().await;
+                info!("[State] Shutting down on request");
+                std::process::exit(0);
+            });
+            OpsResponse::ShutdownAck
+        }
+        OpsRequest::Adopt { from } => match crate::handover::adopt(&from).await {
+            Ok(()) => OpsResponse::Adopted,
+            Err(e) => OpsResponse::Error {
+                message: e.to_string(),
+            },
+        },
+        OpsRequest::Profile {
+            kind,
+            seconds,
+            format,
+        } => match crate::profile::capture(kind, seconds, format).await {
+            Ok(bytes) => OpsResponse::Profile { bytes },
+            Err(e) => OpsResponse::Error {
+                message: e.to_string(),
+            },
+        },
+        OpsRequest::Metrics => OpsResponse::Metrics(crate::metrics::report()),
+        OpsRequest::Prometheus => OpsResponse::Prometheus {
+            text: crate::metrics::prometheus(),
+        },
+        OpsRequest::Inspect => OpsResponse::Inspect(crate::inspect::report()),
+        OpsRequest::Slo => OpsResponse::Slo(crate::slo::report()),
+        OpsRequest::VerifyReplay => match crate::replay::verify().await {
+            Ok(report) => OpsResponse::Replay(report),
+            Err(e) => OpsResponse::Error {
+                message: e.to_string(),
+            },
+        },
+        OpsRequest::Spans { trace_id } => OpsResponse::Spans(crate::trace::spans(&trace_id)),
+        OpsRequest::SlowRequests { limit } => {
+            OpsResponse::SlowRequests(crate::slowlog::recent(limit as usize))
+        }
+        OpsRequest::SetLogLevel { target, level } => {
+            match crate::logging::set_level(target.as_deref(), &level) {
+                Ok(filter) => OpsResponse::LogLevel { filter },
+                Err(e) => OpsResponse::Error {
+                    message: e.to_string(),
+                },
+            }
+        }
+        OpsRequest::Extensions => OpsResponse::Extensions(crate::extension::methods()),
+        OpsRequest::LoadPlugin { path } => {
+            match crate::extension::load(std::path::Path::new(&path)) {
+                Ok((plugin, methods)) => OpsResponse::PluginLoaded { plugin, methods },
+                Err(e) => OpsResponse::Error {
+                    message: e.to_string(),
+                },
+            }
+        }
+        OpsRequest::MiddlewareModule => crate::middleware::module(),
+        other => OpsResponse::Error {
+            message: format!("Unsupported OPS command {:?}", other),
+        },
+    }
+}
+
+/// Checkpoint the state of a running cell.
+pub async fn fetch(cell_name: &str) -> Result<Vec<u8>> {
+    match ops(cell_name, &OpsRequest::Checkpoint).await? {
+        OpsResponse::Checkpoint { bytes } => Ok(bytes),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
+
+/// Restore a running cell from a checkpoint taken with [`fetch`].
+pub async fn push(cell_name: &str, bytes: Vec<u8>) -> Result<()> {
+    match ops(cell_name, &OpsRequest::Restore { bytes }).await? {
+        OpsResponse::Restored => Ok(()),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
+
+/// Move state from one running instance to another.
+pub async fn transfer(from: &str, to: &str) -> Result<usize> {
+    let bytes = fetch(from).await?;
+    let len = bytes.len();
+    push(to, bytes).await?;
+    Ok(len)
+}
+
+/// Send one OPS command to a running cell. Privileged commands go to its
+/// admin socket (see `crate::admin`).
+pub(crate) async fn ops(cell_name: &str, req: &OpsRequest) -> Result<OpsResponse> {
+    let synapse = if req.is_privileged() {
+        Synapse::grow_admin(cell_name).await?
+    } else {
+        Synapse::grow(cell_name).await?
+    };
+    let req_bytes = rkyv::to_bytes::<_, 1024>(req)?.into_vec();
+    let resp = synapse
+        .fire_on_channel(channel::OPS, &req_bytes)
+        .await?
+        .into_owned();
+    let archived = rkyv::check_archived_root::<OpsResponse>(&resp)
+        .map_err(|e| anyhow!("Invalid OPS response: {:?}", e))?;
+    Ok(rkyv::Deserialize::deserialize(archived, &mut rkyv::Infallible)?)
+}
+
+/// The cell's local state store, `~/.cell/state/<cell>`.
+pub(crate) fn state_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().context("No HOME dir")?;
+    Ok(home
+        .join(".cell/state")
+        .join(&crate::identity::Identity::get().cell_name))
+}
+
+fn local_path() -> Result<PathBuf> {
+    Ok(state_dir()?.join("checkpoint"))
+}
+
+async fn read_local() -> Option<Vec<u8>> {
+    tokio::fs::read(local_path().ok()?).await.ok()
+}
+
+async fn write_local(bytes: &[u8]) -> Result<()> {
+    let path = local_path()?;
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    // Write then rename, so a crash mid-write keeps the previous checkpoint
+    let tmp = path.with_extension("tmp");
+    tokio::fs::write(&tmp, bytes).await?;
+    tokio::fs::rename(&tmp, &path).await?;
+    Ok(())
+}
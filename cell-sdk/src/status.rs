@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/status.rs
+//! OPS `Status`: name, uptime, memory and the state of the cell's log.
+//!
+//! Consensus cells set their role with [`set_consensus_role`] and, when
+//! opening the WAL had to cut off a torn or corrupt tail, pass the repair to
+//! [`report_wal_repair`]. Operators then see it with [`fetch`] instead of
+//! only in the log of the node that repaired it.
+
+use crate::state::ops;
+use anyhow::{anyhow, bail, Result};
+use cell_model::ops::{OpsRequest, OpsResponse};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+use tracing::warn;
+
+static SERVING: OnceLock<(String, Instant)> = OnceLock::new();
+static CONSENSUS_ROLE: Mutex<Option<String>> = Mutex::new(None);
+static WAL_REPAIR: Mutex<Option<String>> = Mutex::new(None);
+
+/// Called by the Membrane when it binds; the first name wins
+pub(crate) fn serving(name: &str) {
+    SERVING.get_or_init(|| (name.to_string(), Instant::now()));
+}
+
+/// `leader`, `follower`, `learner`, ... as the cell's consensus sees itself
+pub fn set_consensus_role(role: impl Into<String>) {
+    *CONSENSUS_ROLE.lock().unwrap() = Some(role.into());
+}
+
+/// Record that the WAL was repaired on open, e.g. `cell_consensus::wal::WalRepair`
+/// rendered with `to_string()`
+pub fn report_wal_repair(repair: impl Into<String>) {
+    let repair = repair.into();
+    warn!("[Status] WAL repaired: {}", repair);
+    *WAL_REPAIR.lock().unwrap() = Some(repair);
+}
+
+/// The name the Membrane serves under, or the identity's before it binds
+pub(crate) fn cell_name() -> String {
+    match SERVING.get() {
+        Some((name, _)) => name.clone(),
+        None => crate::identity::Identity::get().cell_name.clone(),
+    }
+}
+
+pub(crate) fn report() -> OpsResponse {
+    let name = cell_name();
+    let uptime_secs = SERVING
+        .get()
+        .map_or(0, |(_, since)| since.elapsed().as_secs());
+    OpsResponse::Status {
+        name,
+        uptime_secs,
+        memory_usage: crate::inspect::report().memory.resident_bytes,
+        consensus_role: CONSENSUS_ROLE
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| "Disabled".to_string()),
+        wal_repair: WAL_REPAIR.lock().unwrap().clone(),
+    }
+}
+
+/// Ask a running cell for its status
+pub async fn fetch(cell_name: &str) -> Result<OpsResponse> {
+    match ops(cell_name, &OpsRequest::Status).await? {
+        status @ OpsResponse::Status { .. } => Ok(status),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
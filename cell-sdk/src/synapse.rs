@@ -5,18 +5,29 @@ use crate::io_client::IoClient;
 use crate::response::Response;
 // Removed RingBuffer from import
 use crate::shm::ShmClient;
+use crate::source::{self, SchemaCheck};
 use anyhow::{Context, Result};
-use cell_core::{channel, VesicleHeader};
+use cell_core::{channel, TraceContext, VesicleHeader};
 use cell_model::rkyv::ser::serializers::AllocSerializer;
 use rkyv::Serialize;
 use std::sync::Arc;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::UnixStream;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream};
+use tokio::net::{TcpStream, UnixStream};
 use tokio::sync::Mutex;
 
 enum Transport {
     Socket(Arc<Mutex<UnixStream>>),
+    /// Neighbor on another host
+    Tcp(Arc<Mutex<TcpStream>>),
+    /// Cell dialed as `cell@host:port`
+    #[cfg(feature = "quic")]
+    Quic(Arc<Mutex<crate::quic::QuicStream>>),
     Shm(ShmClient),
+    /// Cell hosted in this process by compose!
+    Local {
+        cell_name: String,
+        pipe: Arc<Mutex<DuplexStream>>,
+    },
 }
 
 pub struct Synapse {
@@ -26,10 +37,37 @@ pub struct Synapse {
 
 impl Synapse {
     pub async fn grow(cell_name: &str) -> Result<Self> {
+        if let Some(stream) = crate::compose::connect(cell_name) {
+            return Ok(Self {
+                my_id: 0,
+                transport: Transport::Local {
+                    cell_name: cell_name.to_string(),
+                    pipe: Arc::new(Mutex::new(stream)),
+                },
+            });
+        }
+
+        let cwd = std::env::current_dir()?;
+        let my_name = cwd.file_name().unwrap_or_default().to_string_lossy();
+        let hash = blake3::hash(my_name.as_bytes());
+        let my_id = u64::from_le_bytes(hash.as_bytes()[..8].try_into().unwrap());
+
+        // `cell@host:port`: dial the cell directly over QUIC
+        if cell_name.contains('@') {
+            return Self::grow_remote(cell_name, my_id).await;
+        }
+
         crate::organogenisis::Organism::develop()?;
 
+        if let Some(addr) = crate::tcp::neighbor_addr(cell_name) {
+            let stream = crate::tcp::connect(&addr).await?;
+            return Ok(Self {
+                my_id,
+                transport: Transport::Tcp(Arc::new(Mutex::new(stream))),
+            });
+        }
+
         // 1. Try to connect via neighbor link first (most common case)
-        let cwd = std::env::current_dir()?;
         let neighbor_tx = cwd.join(".cell/neighbors").join(cell_name).join("tx");
 
         let _stream = if neighbor_tx.exists() {
@@ -50,10 +88,6 @@ impl Synapse {
             UnixStream::from_std(std_stream)?
         };
 
-        let my_name = cwd.file_name().unwrap_or_default().to_string_lossy();
-        let hash = blake3::hash(my_name.as_bytes());
-        let my_id = u64::from_le_bytes(hash.as_bytes()[..8].try_into().unwrap());
-
         // 1. Ask IO Cell to connect us
         let std_stream = IoClient::connect(cell_name)
             .await
@@ -72,6 +106,57 @@ impl Synapse {
         Ok(Self { my_id, transport })
     }
 
+    /// Connect to the admin socket of a cell on this host, for privileged OPS
+    /// requests (see `crate::admin`). Cells hosted in this process are reached
+    /// in memory as usual.
+    pub async fn grow_admin(cell_name: &str) -> Result<Self> {
+        if crate::compose::is_hosted(cell_name) {
+            return Self::grow(cell_name).await;
+        }
+        let stream = crate::admin::connect(cell_name).await?;
+        Ok(Self {
+            my_id: 0,
+            transport: Transport::Socket(Arc::new(Mutex::new(stream))),
+        })
+    }
+
+    #[cfg(feature = "quic")]
+    async fn grow_remote(target: &str, my_id: u64) -> Result<Self> {
+        let (_, addr) = crate::quic::split_target(target)
+            .with_context(|| format!("Expected cell@host:port, got '{}'", target))?;
+        let stream = crate::quic::connect(addr).await?;
+        Ok(Self {
+            my_id,
+            transport: Transport::Quic(Arc::new(Mutex::new(stream))),
+        })
+    }
+
+    #[cfg(not(feature = "quic"))]
+    async fn grow_remote(target: &str, _my_id: u64) -> Result<Self> {
+        anyhow::bail!("Dialing '{}' needs cell-sdk's quic feature", target)
+    }
+
+    /// Connect like [`grow`](Self::grow), then exchange schema fingerprints
+    /// with the cell and apply `mode` if they differ. In-process cells are
+    /// built from the same tree and skip the exchange.
+    pub async fn grow_checked(
+        cell_name: &str,
+        fingerprint: u64,
+        mode: SchemaCheck,
+    ) -> Result<Self> {
+        let synapse = Self::grow(cell_name).await?;
+        if matches!(synapse.transport, Transport::Local { .. }) {
+            return Ok(synapse);
+        }
+        let resp = synapse
+            .fire_on_channel(channel::OPS, &source::handshake_request(fingerprint)?)
+            .await?
+            .into_owned();
+        let served = source::handshake_response(&resp)?;
+        source::check(cell_name, fingerprint, served, mode)?;
+        Ok(synapse)
+    }
+
     async fn try_upgrade_to_shm(transport: &mut Transport) -> Result<ShmClient> {
         let stream_arc = match transport {
             Transport::Socket(s) => s.clone(),
@@ -102,11 +187,31 @@ impl Synapse {
                 let req_bytes = rkyv::to_bytes::<_, 1024>(request)?.into_vec();
                 self.send_socket(stream_arc, channel::APP, &req_bytes).await
             }
+            Transport::Tcp(stream_arc) => {
+                let req_bytes = rkyv::to_bytes::<_, 1024>(request)?.into_vec();
+                self.send_socket(stream_arc, channel::APP, &req_bytes).await
+            }
+            #[cfg(feature = "quic")]
+            Transport::Quic(stream_arc) => {
+                let req_bytes = rkyv::to_bytes::<_, 1024>(request)?.into_vec();
+                self.send_socket(stream_arc, channel::APP, &req_bytes).await
+            }
             Transport::Shm(client) => {
                 let req_bytes = rkyv::to_bytes::<_, 1024>(request)?.into_vec();
                 let msg = client.request_raw(&req_bytes, channel::APP).await?;
                 Ok(Response::Owned(msg.get_bytes().to_vec()))
             }
+            Transport::Local { cell_name, pipe } => {
+                let req_bytes = rkyv::to_bytes::<_, 1024>(request)?;
+                match crate::compose::dispatcher(cell_name) {
+                    Some(dispatch) => {
+                        let (bytes, degraded) = dispatch(req_bytes).await?;
+                        let prefix = if degraded { cell_core::frame::DEGRADED } else { 0 };
+                        Ok(Response::from_frame(prefix, bytes))
+                    }
+                    None => self.send_socket(pipe, channel::APP, &req_bytes).await,
+                }
+            }
         }
     }
 
@@ -117,45 +222,67 @@ impl Synapse {
     ) -> Result<Response<'a, Vec<u8>>> {
         match &self.transport {
             Transport::Socket(stream_arc) => self.send_socket(stream_arc, chan, payload).await,
+            Transport::Tcp(stream_arc) => self.send_socket(stream_arc, chan, payload).await,
+            #[cfg(feature = "quic")]
+            Transport::Quic(stream_arc) => self.send_socket(stream_arc, chan, payload).await,
             Transport::Shm(client) => {
                 let msg = client.request_raw(payload, chan).await?;
                 Ok(Response::Owned(msg.get_bytes().to_vec()))
             }
+            Transport::Local { pipe, .. } => self.send_socket(pipe, chan, payload).await,
         }
     }
 
-    async fn send_socket<'a>(
+    async fn send_socket<'a, S: AsyncRead + AsyncWrite + Unpin>(
         &self,
-        stream_arc: &Arc<Mutex<UnixStream>>,
+        stream_arc: &Arc<Mutex<S>>,
         chan: u8,
         payload: &[u8],
     ) -> Result<Response<'a, Vec<u8>>> {
         let mut stream = stream_arc.lock().await;
 
+        let trace = crate::trace::outgoing();
+        let priority = crate::priority::current();
+        let mut flags = 0;
+        if trace.is_some() {
+            flags |= VesicleHeader::TRACED;
+        }
+        if priority.is_some() {
+            flags |= VesicleHeader::PRIORITIZED;
+        }
         let header = VesicleHeader {
             target_id: 0,
             source_id: self.my_id,
             ttl: 64,
-            flags: 0,
-            _pad: [0; 6],
+            flags,
+            priority: priority.unwrap_or(0),
+            _pad: [0; 5],
         };
 
-        let total_len = 24 + 1 + payload.len();
+        let trace_len = trace.map_or(0, |_| TraceContext::SIZE);
+        let total_len = 24 + 1 + trace_len + payload.len();
 
         stream.write_all(&(total_len as u32).to_le_bytes()).await?;
 
         let h_bytes: [u8; 24] = unsafe { std::mem::transmute(header) };
         stream.write_all(&h_bytes).await?;
         stream.write_u8(chan).await?;
+        if let Some(trace) = trace {
+            stream.write_all(&trace.to_bytes()).await?;
+        }
         stream.write_all(payload).await?;
 
         let mut len_buf = [0u8; 4];
         stream.read_exact(&mut len_buf).await?;
-        let len = u32::from_le_bytes(len_buf) as usize;
+        let len_prefix = u32::from_le_bytes(len_buf);
+        let len = (len_prefix & cell_core::frame::LEN_MASK) as usize;
 
         let mut buf = vec![0u8; len];
         stream.read_exact(&mut buf).await?;
 
-        Ok(Response::Owned(buf))
+        if len_prefix & cell_core::frame::ERROR != 0 {
+            return Err(crate::error::from_frame(&buf));
+        }
+        Ok(Response::from_frame(len_prefix, buf))
     }
 }
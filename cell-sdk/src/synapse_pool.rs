@@ -0,0 +1,176 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/synapse_pool.rs
+//! Several connections to one cell behind a single handle.
+//!
+//! A [`ResilientSynapse`] carries one request at a time, so a client issuing
+//! many concurrent calls waits on its own pipe. A [`SynapsePool`] holds `size`
+//! connections and sends each call over the one with the fewest calls in
+//! flight. Generated clients use a pool of `Options::connections` synapses
+//! (one by default).
+
+use crate::resilient_synapse::{ConnMetrics, ConnState, ResilienceConfig, ResilientSynapse};
+use crate::response::Response;
+use anyhow::Result;
+use cell_model::rkyv::ser::serializers::AllocSerializer;
+use rkyv::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+struct Member {
+    synapse: ResilientSynapse,
+    in_flight: AtomicUsize,
+}
+
+/// Decrements a member's in-flight count when the call ends or is dropped
+struct InFlight<'a>(&'a AtomicUsize);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Clone)]
+pub struct SynapsePool {
+    members: Arc<[Member]>,
+}
+
+impl SynapsePool {
+    /// Open `size` connections to `cell_name` (at least one).
+    pub async fn grow(cell_name: &str, size: usize) -> Result<Self> {
+        Self::grow_with_config(cell_name, size, ResilienceConfig::default()).await
+    }
+
+    /// Open `size` connections with custom resilience configuration. Fails if
+    /// any of them cannot be established.
+    pub async fn grow_with_config(
+        cell_name: &str,
+        size: usize,
+        config: ResilienceConfig,
+    ) -> Result<Self> {
+        let connecting =
+            (0..size.max(1)).map(|_| ResilientSynapse::grow_with_config(cell_name, config.clone()));
+        let synapses = futures::future::try_join_all(connecting).await?;
+        Ok(Self::from_synapses(synapses))
+    }
+
+    /// Pool over already established connections.
+    ///
+    /// # Panics
+    ///
+    /// If `synapses` is empty.
+    pub fn from_synapses(synapses: Vec<ResilientSynapse>) -> Self {
+        assert!(!synapses.is_empty(), "SynapsePool needs a connection");
+        Self {
+            members: synapses
+                .into_iter()
+                .map(|synapse| Member {
+                    synapse,
+                    in_flight: AtomicUsize::new(0),
+                })
+                .collect(),
+        }
+    }
+
+    /// Number of connections
+    pub fn size(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Calls currently being sent or awaiting their response
+    pub fn in_flight(&self) -> usize {
+        self.members
+            .iter()
+            .map(|m| m.in_flight.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    fn least_busy(&self) -> &Member {
+        self.members
+            .iter()
+            .min_by_key(|m| m.in_flight.load(Ordering::Relaxed))
+            .expect("pool is never empty")
+    }
+
+    /// Send a request over the least busy connection.
+    pub async fn fire<'a, Req>(&self, request: &Req) -> Result<Response<'a, Vec<u8>>>
+    where
+        Req: Serialize<AllocSerializer<1024>>,
+    {
+        let member = self.least_busy();
+        member.in_flight.fetch_add(1, Ordering::Relaxed);
+        let _in_flight = InFlight(&member.in_flight);
+        member.synapse.fire(request).await
+    }
+
+    /// The worst state among the connections
+    pub async fn state(&self) -> ConnState {
+        let mut worst = ConnState::Healthy;
+        for member in self.members.iter() {
+            let state = member.synapse.state().await;
+            if severity(state) > severity(worst) {
+                worst = state;
+            }
+        }
+        worst
+    }
+
+    /// Metrics summed over the connections; timestamps are the most recent
+    /// (`created_at` the oldest) and the state is [`Self::state`].
+    pub async fn metrics(&self) -> ConnMetrics {
+        let mut total = self.members[0].synapse.metrics.read().await.clone();
+        for member in self.members[1..].iter() {
+            let m = member.synapse.metrics.read().await;
+            total.created_at = total.created_at.min(m.created_at);
+            total.last_success = total.last_success.max(m.last_success);
+            total.last_failure = total.last_failure.max(m.last_failure);
+            total.requests_total += m.requests_total;
+            total.requests_failed += m.requests_failed;
+            total.reconnections += m.reconnections;
+            total.responses_degraded += m.responses_degraded;
+            if severity(m.current_state) > severity(total.current_state) {
+                total.current_state = m.current_state;
+            }
+        }
+        total
+    }
+
+    /// See [`ResilientSynapse::schema_mismatch`]; every connection talks to
+    /// the same cell, so the first one answers for all.
+    pub fn schema_mismatch(&self) -> Option<(u64, u64)> {
+        self.members[0].synapse.schema_mismatch()
+    }
+
+    /// Reconnect every connection, failing on the first that cannot.
+    pub async fn force_reconnect(&self) -> Result<()> {
+        for member in self.members.iter() {
+            member.synapse.force_reconnect().await?;
+        }
+        Ok(())
+    }
+}
+
+fn severity(state: ConnState) -> u8 {
+    match state {
+        ConnState::Healthy => 0,
+        ConnState::Degraded => 1,
+        ConnState::Unhealthy => 2,
+        ConnState::Reconnecting => 3,
+        ConnState::CircuitOpen => 4,
+    }
+}
+
+impl From<ResilientSynapse> for SynapsePool {
+    fn from(synapse: ResilientSynapse) -> Self {
+        Self::from_synapses(vec![synapse])
+    }
+}
+
+impl std::fmt::Debug for SynapsePool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SynapsePool")
+            .field("size", &self.size())
+            .field("in_flight", &self.in_flight())
+            .finish()
+    }
+}
@@ -10,17 +10,109 @@ pub struct System;
 
 impl System {
     pub async fn spawn(cell_name: &str, config: Option<CellInitConfig>) -> Result<String> {
-        // Fixed: Removed unused 'mut'
-        let synapse = Synapse::grow("hypervisor")
-            .await
-            .map_err(|_| anyhow!("Hypervisor neighbor not found."))?;
-
         let req = MitosisRequest::Spawn {
             cell_name: cell_name.to_string(),
             config,
         };
 
-        let resp_wrapper = synapse.fire(&req).await?;
+        match Self::request(&req).await? {
+            MitosisResponse::Ok { socket_path } => Ok(socket_path),
+            MitosisResponse::Denied { reason } => Err(anyhow!("Spawn denied: {}", reason)),
+            _ => Err(anyhow!("Unexpected hypervisor response")),
+        }
+    }
+
+    /// Make `cell_name` reachable without starting it: the hypervisor holds
+    /// its socket and starts it on the first connection. Returns the socket path.
+    pub async fn spawn_lazy(cell_name: &str, config: Option<CellInitConfig>) -> Result<String> {
+        let req = MitosisRequest::SpawnLazy {
+            cell_name: cell_name.to_string(),
+            config,
+        };
+
+        match Self::request(&req).await? {
+            MitosisResponse::Ok { socket_path } => Ok(socket_path),
+            MitosisResponse::Denied { reason } => Err(anyhow!("Spawn denied: {}", reason)),
+            _ => Err(anyhow!("Unexpected hypervisor response")),
+        }
+    }
+
+    /// Start `cell_name` from an archived binary (a blobstore hash) rather
+    /// than building it. Returns the socket path.
+    pub async fn spawn_artifact(
+        cell_name: &str,
+        artifact: &str,
+        config: Option<CellInitConfig>,
+    ) -> Result<String> {
+        let req = MitosisRequest::SpawnArtifact {
+            cell_name: cell_name.to_string(),
+            artifact: artifact.to_string(),
+            config,
+        };
+
+        match Self::request(&req).await? {
+            MitosisResponse::Ok { socket_path } => Ok(socket_path),
+            MitosisResponse::Denied { reason } => Err(anyhow!("Spawn denied: {}", reason)),
+            _ => Err(anyhow!("Unexpected hypervisor response")),
+        }
+    }
+
+    /// Names of the running instances of `cell_name`
+    pub async fn list_instances(cell_name: &str) -> Result<Vec<String>> {
+        let req = MitosisRequest::ListInstances {
+            cell_name: cell_name.to_string(),
+        };
+
+        match Self::request(&req).await? {
+            MitosisResponse::Instances { names } => Ok(names),
+            MitosisResponse::Denied { reason } => Err(anyhow!("List denied: {}", reason)),
+            _ => Err(anyhow!("Unexpected hypervisor response")),
+        }
+    }
+
+    /// Restart a single instance, returning its socket path
+    pub async fn restart(instance: &str) -> Result<String> {
+        let req = MitosisRequest::Restart {
+            instance: instance.to_string(),
+        };
+
+        match Self::request(&req).await? {
+            MitosisResponse::Ok { socket_path } => Ok(socket_path),
+            MitosisResponse::Denied { reason } => Err(anyhow!("Restart denied: {}", reason)),
+            _ => Err(anyhow!("Unexpected hypervisor response")),
+        }
+    }
+
+    /// Stop a single instance gracefully; the hypervisor does not restart it
+    pub async fn stop(instance: &str) -> Result<()> {
+        let req = MitosisRequest::Stop {
+            instance: instance.to_string(),
+        };
+
+        match Self::request(&req).await? {
+            MitosisResponse::Ok { .. } => Ok(()),
+            MitosisResponse::Denied { reason } => Err(anyhow!("Stop denied: {}", reason)),
+            _ => Err(anyhow!("Unexpected hypervisor response")),
+        }
+    }
+
+    /// Stop (or resume) accepting new cells on this node
+    pub async fn cordon(cordoned: bool) -> Result<()> {
+        let req = MitosisRequest::Cordon { cordoned };
+
+        match Self::request(&req).await? {
+            MitosisResponse::Ok { .. } => Ok(()),
+            MitosisResponse::Denied { reason } => Err(anyhow!("Cordon denied: {}", reason)),
+            _ => Err(anyhow!("Unexpected hypervisor response")),
+        }
+    }
+
+    async fn request(req: &MitosisRequest) -> Result<MitosisResponse> {
+        let synapse = Synapse::grow("hypervisor")
+            .await
+            .map_err(|_| anyhow!("Hypervisor neighbor not found."))?;
+
+        let resp_wrapper = synapse.fire(req).await?;
         let resp_bytes = resp_wrapper.into_owned();
 
         if resp_bytes.is_empty() {
@@ -35,10 +127,7 @@ impl System {
             &mut cell_model::rkyv::de::deserializers::SharedDeserializeMap::new(),
         )?;
 
-        match resp {
-            MitosisResponse::Ok { socket_path } => Ok(socket_path),
-            MitosisResponse::Denied { reason } => Err(anyhow!("Spawn denied: {}", reason)),
-        }
+        Ok(resp)
     }
 
     pub async fn ignite_local_cluster() -> Result<()> {
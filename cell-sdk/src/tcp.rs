@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/tcp.rs
+//! Plain TCP between cells on different hosts.
+//!
+//! A cell serves over TCP instead of its Unix socket when `CELL_TRANSPORT`
+//! (or `transport` in its `Cell.toml`) is `tcp://<ip>:<port>`; the frames are
+//! the same as on the socket. Callers reach it through a neighbor declared
+//! as `ledger = "tcp://10.0.0.2:9100"`, which organogenesis records in
+//! `.cell/neighbors/ledger/tcp`. Nothing is encrypted or authenticated by
+//! the transport itself: use it on trusted networks, and QUIC (`quic://`,
+//! see `crate::quic`) everywhere else.
+
+use anyhow::{Context, Result};
+use cell_model::manifest::{quic_addr, tcp_addr, CellManifest};
+use tokio::net::TcpStream;
+use tracing::warn;
+
+/// Transport the cell selected: `CELL_TRANSPORT`, else `transport` in its
+/// `Cell.toml`.
+pub fn selected() -> Option<String> {
+    std::env::var("CELL_TRANSPORT")
+        .ok()
+        .or_else(manifest_transport)
+}
+
+/// Address the Membrane should listen on, if the cell selected TCP. Fails on
+/// a transport this build cannot serve.
+pub fn listen_addr() -> Result<Option<String>> {
+    let Some(spec) = selected() else {
+        return Ok(None);
+    };
+    if let Some(addr) = tcp_addr(&spec) {
+        return Ok(Some(addr.to_string()));
+    }
+    if quic_addr(&spec).is_some() {
+        if cfg!(feature = "quic") {
+            return Ok(None);
+        }
+        anyhow::bail!("Transport '{}' needs cell-sdk's quic feature", spec);
+    }
+    if spec.trim() == "unix" {
+        return Ok(None);
+    }
+    anyhow::bail!(
+        "Unsupported transport '{}' (expected unix, tcp://<ip>:<port> or quic://<ip>:<port>)",
+        spec
+    )
+}
+
+fn manifest_transport() -> Option<String> {
+    let content = std::fs::read_to_string("Cell.toml").ok()?;
+    match toml::from_str::<CellManifest>(&content) {
+        Ok(manifest) => manifest.transport,
+        Err(e) => {
+            warn!("Ignoring transport: failed to parse Cell.toml: {}", e);
+            None
+        }
+    }
+}
+
+/// Address of `cell_name` when it is a remote neighbor.
+pub fn neighbor_addr(cell_name: &str) -> Option<String> {
+    let cwd = std::env::current_dir().ok()?;
+    let path = cwd.join(".cell/neighbors").join(cell_name).join("tcp");
+    let addr = std::fs::read_to_string(path).ok()?;
+    Some(addr.trim().to_string()).filter(|a| !a.is_empty())
+}
+
+/// Connect with Nagle disabled, since every frame is a whole request.
+pub async fn connect(addr: &str) -> Result<TcpStream> {
+    let stream = TcpStream::connect(addr)
+        .await
+        .with_context(|| format!("Failed to connect to tcp://{}", addr))?;
+    stream.set_nodelay(true)?;
+    Ok(stream)
+}
@@ -0,0 +1,301 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/telemetry.rs
+//! Batching for telemetry producers.
+//!
+//! Logging, metrics and audit events are cheap to produce and expensive to send
+//! one RPC at a time. A [`Batcher`] buffers them and hands them to a flush
+//! function (usually a batch method on the target cell) once `max_batch`
+//! events are waiting or `max_delay` has passed:
+//!
+//! ```ignore
+//! let audit = Audit::Client::connect().await?;
+//! let events = Batcher::spawn(BatchConfig::default(), move |batch| {
+//!     let audit = audit.clone();
+//!     async move { audit.log_batch(batch).await.map(drop) }
+//! });
+//! events.push(event).await;
+//! ```
+//!
+//! When the target is slow or down the buffer fills up to `capacity`; the
+//! [`Overflow`] policy then decides what happens to new events. Failed
+//! flushes are retried. Events still buffered when the last handle drops are
+//! lost, so call [`Batcher::flush`] before shutting down.
+
+use crate::membrane::BoxFuture;
+use anyhow::Result;
+use rkyv::de::deserializers::SharedDeserializeMap;
+use rkyv::ser::serializers::AllocSerializer;
+use rkyv::validation::validators::DefaultValidator;
+use rkyv::{Archive, CheckBytes, Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::warn;
+
+#[derive(Debug, Clone)]
+pub enum Overflow {
+    /// Discard the oldest buffered event
+    DropOldest,
+    /// Wait in `push` until a flush makes room
+    Block,
+    /// Append new events to this file; they are sent once the buffer drains
+    SpillToDisk(PathBuf),
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchConfig {
+    pub max_batch: usize,
+    pub max_delay: Duration,
+    /// Events buffered in memory before `overflow` applies
+    pub capacity: usize,
+    pub overflow: Overflow,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            max_batch: 100,
+            max_delay: Duration::from_secs(1),
+            capacity: 10_000,
+            overflow: Overflow::DropOldest,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatcherStats {
+    pub flushed: u64,
+    pub dropped: u64,
+    pub spilled: u64,
+    pub flush_failures: u64,
+}
+
+type FlushFn<T> = Box<dyn Fn(Vec<T>) -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+struct Inner<T> {
+    config: BatchConfig,
+    queue: Mutex<VecDeque<T>>,
+    flush: FlushFn<T>,
+    /// One flush at a time, so batches arrive in order
+    flushing: tokio::sync::Mutex<()>,
+    ready: Arc<Notify>,
+    space: Notify,
+    flushed: AtomicU64,
+    dropped: AtomicU64,
+    spilled: AtomicU64,
+    flush_failures: AtomicU64,
+}
+
+pub struct Batcher<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Clone for Batcher<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Batcher<T>
+where
+    T: Archive + Serialize<AllocSerializer<256>> + Send + 'static,
+    T::Archived: Deserialize<T, SharedDeserializeMap> + for<'a> CheckBytes<DefaultValidator<'a>>,
+{
+    /// Start buffering. The background flusher stops once every handle is dropped.
+    pub fn spawn<F, Fut>(config: BatchConfig, flush: F) -> Self
+    where
+        F: Fn(Vec<T>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let inner = Arc::new(Inner {
+            config,
+            queue: Mutex::new(VecDeque::new()),
+            flush: Box::new(move |batch| Box::pin(flush(batch))),
+            flushing: tokio::sync::Mutex::new(()),
+            ready: Arc::new(Notify::new()),
+            space: Notify::new(),
+            flushed: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            spilled: AtomicU64::new(0),
+            flush_failures: AtomicU64::new(0),
+        });
+        tokio::spawn(Self::run(Arc::downgrade(&inner)));
+        Self { inner }
+    }
+
+    pub async fn push(&self, event: T) {
+        let inner = &self.inner;
+        let mut event = Some(event);
+        while let Some(e) = event.take() {
+            let space = inner.space.notified();
+            {
+                let mut queue = inner.queue.lock().unwrap();
+                if queue.len() < inner.config.capacity {
+                    queue.push_back(e);
+                    if queue.len() >= inner.config.max_batch {
+                        inner.ready.notify_one();
+                    }
+                    return;
+                }
+                match &inner.config.overflow {
+                    Overflow::DropOldest => {
+                        queue.pop_front();
+                        queue.push_back(e);
+                        inner.dropped.fetch_add(1, Ordering::Relaxed);
+                        return;
+                    }
+                    Overflow::SpillToDisk(path) => {
+                        match spill(path, &e) {
+                            Ok(()) => inner.spilled.fetch_add(1, Ordering::Relaxed),
+                            Err(err) => {
+                                warn!("[Batcher] Spill to {:?} failed, dropping event: {}", path, err);
+                                inner.dropped.fetch_add(1, Ordering::Relaxed)
+                            }
+                        };
+                        return;
+                    }
+                    Overflow::Block => event = Some(e),
+                }
+            }
+            inner.ready.notify_one();
+            space.await;
+        }
+    }
+
+    /// Send everything buffered (including spilled events) now.
+    pub async fn flush(&self) -> Result<()> {
+        while Self::flush_once(&self.inner).await? {}
+        Ok(())
+    }
+
+    pub fn stats(&self) -> BatcherStats {
+        let inner = &self.inner;
+        BatcherStats {
+            flushed: inner.flushed.load(Ordering::Relaxed),
+            dropped: inner.dropped.load(Ordering::Relaxed),
+            spilled: inner.spilled.load(Ordering::Relaxed),
+            flush_failures: inner.flush_failures.load(Ordering::Relaxed),
+        }
+    }
+
+    async fn run(inner: Weak<Inner<T>>) {
+        loop {
+            let Some(strong) = inner.upgrade() else { return };
+            let delay = strong.config.max_delay;
+            // Don't keep the batcher alive while waiting
+            let ready = strong.ready.clone();
+            drop(strong);
+            let _ = tokio::time::timeout(delay, ready.notified()).await;
+
+            let Some(inner) = inner.upgrade() else { return };
+            loop {
+                match Self::flush_once(&inner).await {
+                    Ok(true) => continue,
+                    Ok(false) => break,
+                    Err(e) => {
+                        warn!("[Batcher] Flush failed, retrying: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Send one batch. Returns whether there was anything to send.
+    async fn flush_once(inner: &Inner<T>) -> Result<bool> {
+        let _turn = inner.flushing.lock().await;
+        let batch: Vec<T> = {
+            let mut queue = inner.queue.lock().unwrap();
+            if queue.is_empty() {
+                if let Overflow::SpillToDisk(path) = &inner.config.overflow {
+                    queue.extend(unspill::<T>(path)?);
+                }
+            }
+            let n = queue.len().min(inner.config.max_batch);
+            queue.drain(..n).collect()
+        };
+        if batch.is_empty() {
+            return Ok(false);
+        }
+
+        let len = batch.len();
+        // Keep a copy to put back on failure
+        let retry: Vec<Vec<u8>> = batch.iter().map(encode).collect::<Result<_>>()?;
+        match (inner.flush)(batch).await {
+            Ok(()) => {
+                inner.flushed.fetch_add(len as u64, Ordering::Relaxed);
+                inner.space.notify_waiters();
+                Ok(true)
+            }
+            Err(e) => {
+                inner.flush_failures.fetch_add(1, Ordering::Relaxed);
+                let mut queue = inner.queue.lock().unwrap();
+                for bytes in retry.iter().rev() {
+                    queue.push_front(decode(bytes)?);
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+fn encode<T: Serialize<AllocSerializer<256>>>(event: &T) -> Result<Vec<u8>> {
+    Ok(rkyv::to_bytes::<_, 256>(event)?.into_vec())
+}
+
+fn decode<T>(bytes: &[u8]) -> Result<T>
+where
+    T: Archive,
+    T::Archived: Deserialize<T, SharedDeserializeMap> + for<'a> CheckBytes<DefaultValidator<'a>>,
+{
+    let mut aligned = rkyv::AlignedVec::with_capacity(bytes.len());
+    aligned.extend_from_slice(bytes);
+    let archived = rkyv::check_archived_root::<T>(&aligned)
+        .map_err(|e| anyhow::anyhow!("Corrupt spilled event: {}", e))?;
+    Ok(archived.deserialize(&mut SharedDeserializeMap::new())?)
+}
+
+/// Spill file: `[u32 len][rkyv event]` records
+fn spill<T: Serialize<AllocSerializer<256>>>(path: &PathBuf, event: &T) -> Result<()> {
+    let bytes = encode(event)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    file.write_all(&bytes)?;
+    Ok(())
+}
+
+/// Take every spilled event and empty the file.
+fn unspill<T>(path: &PathBuf) -> Result<Vec<T>>
+where
+    T: Archive,
+    T::Archived: Deserialize<T, SharedDeserializeMap> + for<'a> CheckBytes<DefaultValidator<'a>>,
+{
+    let data = match std::fs::read(path) {
+        Ok(d) => d,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut events = Vec::new();
+    let mut rest = &data[..];
+    while rest.len() >= 4 {
+        let len = u32::from_le_bytes(rest[..4].try_into().unwrap()) as usize;
+        let Some(record) = rest.get(4..4 + len) else { break };
+        events.push(decode(record)?);
+        rest = &rest[4 + len..];
+    }
+    std::fs::remove_file(path)?;
+    Ok(events)
+}
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/trace.rs
+//! Traces across Synapse hops.
+//!
+//! Requests carry the W3C trace context of the call that sent them (see
+//! [`cell_core::VesicleHeader::TRACED`]). An outgoing call continues the trace
+//! of the request being handled, or starts a new one; the Membrane continues
+//! the caller's trace in the handler's [`crate::context::CallContext`]. With
+//! `otel` the ids are those of the exported `cell.call` and `cell.request`
+//! spans, so a collector joins the hops into one trace.
+//!
+//! Each cell also keeps its last [`RETAINED`] finished spans. OPS `Spans`, and
+//! through it the observer cell, reads a trace back from every cell it crossed.
+
+use crate::state::ops;
+use anyhow::{anyhow, bail, Result};
+pub use cell_core::TraceContext;
+use cell_model::ops::{OpsRequest, OpsResponse, SpanRecord};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Name of the span the Membrane opens around each handler call.
+pub const REQUEST_SPAN: &str = "cell.request";
+/// Name of the span a Synapse opens around each outgoing call.
+pub const CALL_SPAN: &str = "cell.call";
+
+/// Spans kept for OPS `Spans`; older ones are dropped.
+pub const RETAINED: usize = 1024;
+
+static SPANS: Mutex<VecDeque<SpanRecord>> = Mutex::new(VecDeque::new());
+
+tokio::task_local! {
+    static CALL: TraceContext;
+}
+
+/// Context for the frame of an outgoing request, set by [`call`]
+pub fn outgoing() -> Option<TraceContext> {
+    CALL.try_with(|c| *c).ok()
+}
+
+/// Run an outgoing call to `cell` as a span of the request being handled,
+/// or of a new trace, and record it.
+pub async fn call<T>(cell: &str, fut: impl Future<Output = Result<T>>) -> Result<T> {
+    let parent = crate::context::current_trace();
+    let ctx = span_of(parent);
+    let started = (SystemTime::now(), Instant::now());
+    let result = CALL.scope(ctx, fut).await;
+    let local = &crate::identity::Identity::get().cell_name;
+    let name = format!("{} {}", CALL_SPAN, cell);
+    record(local, ctx, parent, name, started, result.is_ok());
+    result
+}
+
+/// The span of a handler call for a request sent with `parent`
+pub(crate) fn span_of(parent: Option<TraceContext>) -> TraceContext {
+    #[cfg(feature = "otel")]
+    if let Some(ctx) = crate::otel::current_span() {
+        return ctx;
+    }
+    match parent {
+        Some(parent) => parent.with_span(span_id()),
+        None => TraceContext {
+            trace_id: rand::random::<u128>().max(1).to_be_bytes(),
+            span_id: span_id(),
+            flags: TraceContext::SAMPLED,
+        },
+    }
+}
+
+/// Run a handler call in `ctx`, for a request sent with `parent`, and record
+/// its span under `cell`.
+pub(crate) async fn serve<T>(
+    cell: &str,
+    ctx: crate::context::CallContext,
+    parent: Option<TraceContext>,
+    fut: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let trace = ctx.trace;
+    let started = (SystemTime::now(), Instant::now());
+    let result = crate::context::scope(ctx, fut).await;
+    record(
+        cell,
+        trace,
+        parent,
+        REQUEST_SPAN.to_string(),
+        started,
+        result.is_ok(),
+    );
+    result
+}
+
+fn span_id() -> [u8; 8] {
+    rand::random::<u64>().max(1).to_be_bytes()
+}
+
+fn record(
+    cell: &str,
+    ctx: TraceContext,
+    parent: Option<TraceContext>,
+    name: String,
+    (at, started): (SystemTime, Instant),
+    ok: bool,
+) {
+    let span = SpanRecord {
+        trace_id: ctx.trace_id_hex(),
+        span_id: ctx.span_id_hex(),
+        parent_id: parent.map(|p| p.span_id_hex()),
+        cell: cell.to_string(),
+        name,
+        start_unix_us: at
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_micros() as u64)
+            .unwrap_or(0),
+        duration_us: started.elapsed().as_micros() as u64,
+        ok,
+    };
+    let mut spans = SPANS.lock().unwrap();
+    if spans.len() == RETAINED {
+        spans.pop_front();
+    }
+    spans.push_back(span);
+}
+
+/// Retained spans of the trace with hex id `trace_id`, oldest first.
+pub fn spans(trace_id: &str) -> Vec<SpanRecord> {
+    let spans = SPANS.lock().unwrap();
+    spans
+        .iter()
+        .filter(|s| s.trace_id == trace_id)
+        .cloned()
+        .collect()
+}
+
+/// Ask a running cell for its spans of one trace.
+pub async fn fetch(cell_name: &str, trace_id: &str) -> Result<Vec<SpanRecord>> {
+    let req = OpsRequest::Spans {
+        trace_id: trace_id.to_string(),
+    };
+    match ops(cell_name, &req).await? {
+        OpsResponse::Spans(spans) => Ok(spans),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/src/watchdog.rs
+//! Self-health probes.
+//!
+//! A cell registers checks with [`register`]; the Membrane runs them when the
+//! hypervisor asks over OPS and reports the cell degraded if any fails. The
+//! hypervisor escalates a degraded cell (see `cell_model::watchdog`): first it
+//! is flagged, then drained with [`drain_cell`], then restarted.
+//!
+//! Supervisors use [`check`] (OPS `HealthCheck`) rather than connecting to
+//! the socket: a cell is live when its runtime answers and ready when it is
+//! not draining and every check passes.
+//!
+//! ```ignore
+//! watchdog::register("wal-writable", move || {
+//!     let wal = wal.clone();
+//!     async move { wal.touch().await }
+//! });
+//! ```
+
+use crate::membrane::BoxFuture;
+use crate::state::ops;
+use anyhow::{anyhow, bail, Result};
+use cell_model::ops::{OpsRequest, OpsResponse};
+pub use cell_model::watchdog::{Health, HealthReport, HealthStatus, ProbeResult};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::warn;
+
+type Check = Arc<dyn Fn() -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+static CHECKS: Mutex<Vec<(String, Check)>> = Mutex::new(Vec::new());
+static DRAINING: AtomicBool = AtomicBool::new(false);
+
+/// A probe that takes longer than this counts as failed
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Add a self-check. Returning `Err` marks the cell degraded until it passes again.
+pub fn register<F, Fut>(name: &str, check: F)
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    let check: Check = Arc::new(move || Box::pin(check()));
+    CHECKS.lock().unwrap().push((name.to_string(), check));
+}
+
+/// Run every registered probe.
+pub async fn report() -> HealthReport {
+    let checks = CHECKS.lock().unwrap().clone();
+    let mut probes = Vec::with_capacity(checks.len());
+    for (name, check) in checks {
+        let (healthy, detail) = match tokio::time::timeout(PROBE_TIMEOUT, check()).await {
+            Ok(Ok(())) => (true, String::new()),
+            Ok(Err(e)) => (false, e.to_string()),
+            Err(_) => (false, format!("timed out after {:?}", PROBE_TIMEOUT)),
+        };
+        if !healthy {
+            warn!("[Watchdog] Probe '{}' failed: {}", name, detail);
+        }
+        probes.push(ProbeResult {
+            name,
+            healthy,
+            detail,
+        });
+    }
+    HealthReport::from_probes(probes)
+}
+
+/// Liveness and readiness of this cell, for OPS `HealthCheck`.
+pub async fn status() -> HealthStatus {
+    HealthStatus::new(is_draining(), report().await.probes)
+}
+
+/// Whether the hypervisor has drained this cell. A draining Membrane rejects
+/// new application requests.
+pub fn is_draining() -> bool {
+    DRAINING.load(Ordering::Relaxed)
+}
+
+pub(crate) fn drain() {
+    DRAINING.store(true, Ordering::Relaxed);
+}
+
+/// Ask a running cell for its health.
+pub async fn probe(cell_name: &str) -> Result<HealthReport> {
+    match ops(cell_name, &OpsRequest::Health).await? {
+        OpsResponse::Health(report) => Ok(report),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
+
+/// Ask a cell whether it is live and ready. A cell that does not answer in
+/// time is reported as neither, never as an error.
+pub async fn check(cell_name: &str) -> HealthStatus {
+    // Probes may take up to PROBE_TIMEOUT each; a hung runtime never answers
+    let answer =
+        tokio::time::timeout(PROBE_TIMEOUT * 2, ops(cell_name, &OpsRequest::HealthCheck)).await;
+    match answer {
+        Ok(Ok(OpsResponse::HealthCheck(status))) => status,
+        Ok(Ok(OpsResponse::Error { message })) => HealthStatus::unreachable(message),
+        Ok(Ok(_)) => HealthStatus::unreachable("unexpected OPS response".to_string()),
+        Ok(Err(e)) => HealthStatus::unreachable(e.to_string()),
+        Err(_) => HealthStatus::unreachable(format!("no answer within {:?}", PROBE_TIMEOUT * 2)),
+    }
+}
+
+/// Stop a running cell from taking new application requests.
+pub async fn drain_cell(cell_name: &str) -> Result<()> {
+    match ops(cell_name, &OpsRequest::Drain).await? {
+        OpsResponse::Draining => Ok(()),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
+
+/// Drain a running cell, let its in-flight requests finish and have it exit.
+/// Goes to its admin socket, so only works on this host.
+pub async fn shutdown_cell(cell_name: &str) -> Result<()> {
+    match ops(cell_name, &OpsRequest::Shutdown).await? {
+        OpsResponse::ShutdownAck => Ok(()),
+        OpsResponse::Error { message } => Err(anyhow!("{}: {}", cell_name, message)),
+        _ => bail!("Unexpected OPS response from {}", cell_name),
+    }
+}
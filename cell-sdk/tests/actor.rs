@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/actor.rs
+//! Per-key mailboxes serialize calls for one key and let other keys run.
+
+use cell_sdk::actor::Mailboxes;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+static MAILBOXES: OnceLock<Mailboxes> = OnceLock::new();
+
+fn mailboxes() -> &'static Mailboxes {
+    MAILBOXES.get_or_init(Mailboxes::new)
+}
+
+#[tokio::test]
+async fn same_key_runs_in_order_other_keys_in_parallel() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let first = mailboxes().enter("order-1").await;
+    let waiting = {
+        let log = log.clone();
+        tokio::spawn(async move {
+            let _slot = mailboxes().enter("order-1").await;
+            log.lock().unwrap().push("order-1 second");
+        })
+    };
+    let other = {
+        let log = log.clone();
+        tokio::spawn(async move {
+            let _slot = mailboxes().enter("order-2").await;
+            log.lock().unwrap().push("order-2");
+        })
+    };
+
+    other.await.unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(*log.lock().unwrap(), ["order-2"]);
+
+    log.lock().unwrap().push("order-1 first");
+    drop(first);
+    waiting.await.unwrap();
+    assert_eq!(
+        *log.lock().unwrap(),
+        ["order-2", "order-1 first", "order-1 second"]
+    );
+    assert_eq!(mailboxes().active(), 0);
+}
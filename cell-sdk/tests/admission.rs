@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/admission.rs
+//! Requests fail fast while a required dependency is down.
+
+use cell_sdk::admission::{check, enable, report, AdmissionConfig, DependencyState};
+use cell_sdk::error::CellError;
+use std::time::Duration;
+
+#[test]
+fn unhealthy_dependency_rejects_at_ingress() {
+    enable(
+        AdmissionConfig::new(vec!["ledger".into(), "vault".into()]).probe_interval(Duration::ZERO),
+    );
+    assert!(check().is_ok());
+
+    // Degraded only blocks with reject_degraded
+    report("vault", DependencyState::Degraded);
+    assert!(check().is_ok());
+
+    report("ledger", DependencyState::Unavailable);
+    let err = check().unwrap_err();
+    assert_eq!(err.code, CellError::DependencyFailed);
+    assert_eq!(err.message, "Required dependency 'ledger' is unavailable");
+
+    // Not a required dependency: ignored
+    report("audit", DependencyState::Unavailable);
+    report("ledger", DependencyState::Healthy);
+    assert!(check().is_ok());
+}
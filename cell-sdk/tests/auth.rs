@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/auth.rs
+//! A Membrane accepts an impersonation grant sent on `channel::AUTH` and
+//! handles the connection's requests as its principal, but only for a grant
+//! issued for that cell with the admin key.
+
+use cell_sdk::auth::{AdminKey, AuthResponse};
+use cell_sdk::correlator::Correlator;
+use cell_sdk::membrane::BoxFuture;
+use cell_sdk::Membrane;
+use std::time::Duration;
+
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug, Clone)]
+#[archive(check_bytes)]
+struct Whoami;
+
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug, Clone, PartialEq)]
+#[archive(check_bytes)]
+struct Principal {
+    name: String,
+}
+
+fn whoami(_: &ArchivedWhoami) -> BoxFuture<'_, anyhow::Result<Principal>> {
+    Box::pin(async move {
+        let name = cell_sdk::auth::caller().map_or_else(String::new, |c| c.principal);
+        Ok(Principal { name })
+    })
+}
+
+async fn connect(addr: &str) -> Correlator {
+    loop {
+        match cell_sdk::tcp::connect(addr).await {
+            Ok(stream) => return Correlator::new(stream, 0),
+            Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+        }
+    }
+}
+
+async fn present(conn: &Correlator, grant: &cell_sdk::auth::ImpersonationGrant) -> AuthResponse {
+    let bytes = rkyv::to_bytes::<_, 256>(grant).unwrap();
+    let resp = conn
+        .send(cell_sdk::channel::AUTH, &bytes, Duration::from_secs(1))
+        .await
+        .unwrap()
+        .into_owned();
+    rkyv::from_bytes(&resp).unwrap()
+}
+
+#[tokio::test]
+async fn grant_is_accepted_over_a_socket() {
+    let home = tempfile::tempdir().unwrap();
+    std::env::set_var("HOME", home.path());
+    let key_path = AdminKey::default_path().unwrap();
+    std::fs::create_dir_all(key_path.parent().unwrap()).unwrap();
+    std::fs::write(&key_path, rand::random::<[u8; 32]>()).unwrap();
+    let key = AdminKey::load_default().unwrap();
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let addr = format!("127.0.0.1:{}", port);
+    std::env::set_var("CELL_TRANSPORT", format!("tcp://{}", addr));
+    let server = tokio::spawn(Membrane::bind::<_, Whoami, Principal>(
+        "grant-target",
+        whoami,
+        None,
+        None,
+        None,
+    ));
+
+    let conn = connect(&addr).await;
+    let grant = key.issue("ops", "grant-target", "alice", "acme", "TICKET-1", 60);
+    match present(&conn, &grant).await {
+        AuthResponse::Accepted { principal } => assert_eq!(principal, "alice"),
+        AuthResponse::Rejected { reason } => panic!("grant rejected: {}", reason),
+    }
+    let req = rkyv::to_bytes::<_, 256>(&Whoami).unwrap();
+    let resp = conn
+        .send(cell_sdk::channel::APP, &req, Duration::from_secs(1))
+        .await
+        .unwrap()
+        .into_owned();
+    let resp: Principal = rkyv::from_bytes(&resp).unwrap();
+    assert_eq!(resp.name, "alice");
+
+    // Issued for another cell
+    let conn = connect(&addr).await;
+    let elsewhere = key.issue("ops", "billing", "alice", "acme", "TICKET-1", 60);
+    assert!(matches!(
+        present(&conn, &elsewhere).await,
+        AuthResponse::Rejected { .. }
+    ));
+
+    // Retargeted after signing
+    let mut forged = grant.clone();
+    forged.principal = "root".to_string();
+    assert!(matches!(
+        present(&conn, &forged).await,
+        AuthResponse::Rejected { .. }
+    ));
+
+    // Moved to another organism after signing
+    let mut forged = grant.clone();
+    forged.organism = "globex".to_string();
+    assert!(matches!(
+        present(&conn, &forged).await,
+        AuthResponse::Rejected { .. }
+    ));
+
+    server.abort();
+}
@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/codec.rs
+//! Connections that negotiate JSON or MessagePack call the same handlers.
+
+use cell_sdk::codec::{self, Codec};
+use cell_sdk::compose::{self, Composition};
+use cell_sdk::correlator::Correlator;
+use cell_sdk::membrane::BoxFuture;
+use cell_sdk::{channel, Membrane};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(
+    rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Serialize, Deserialize, Debug, Clone,
+)]
+#[archive(check_bytes)]
+enum EchoProtocol {
+    Double { value: u64 },
+}
+
+#[derive(
+    rkyv::Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+)]
+#[archive(check_bytes)]
+enum EchoResponse {
+    Double(u64),
+}
+
+fn double(req: &ArchivedEchoProtocol) -> BoxFuture<'_, anyhow::Result<EchoResponse>> {
+    let ArchivedEchoProtocol::Double { value } = req;
+    let value = *value;
+    Box::pin(async move {
+        if value == 0 {
+            anyhow::bail!("Nothing to double");
+        }
+        Ok(EchoResponse::Double(value * 2))
+    })
+}
+
+const SECOND: Duration = Duration::from_secs(1);
+
+async fn connect(name: &'static str) -> (compose::Running, Correlator) {
+    codec::register::<EchoProtocol, EchoResponse>();
+    let serve = Membrane::bind::<_, EchoProtocol, EchoResponse>(name, double, None, None, None);
+    let running = Composition::new().cell(name, serve).start();
+    (running, Correlator::new(compose::connect(name).unwrap(), 0))
+}
+
+async fn handshake(conn: &Correlator, offer: &[Codec]) -> Codec {
+    let resp = conn
+        .send(channel::CODEC, &Codec::offer(offer), SECOND)
+        .await
+        .unwrap()
+        .into_owned();
+    Codec::from_u8(resp[0]).unwrap()
+}
+
+#[tokio::test]
+async fn json_clients_call_handlers() {
+    let (_running, conn) = connect("codec-json").await;
+    assert_eq!(handshake(&conn, &[Codec::Json]).await, Codec::Json);
+
+    let resp = conn
+        .send(channel::APP, br#"{"Double":{"value":21}}"#, SECOND)
+        .await
+        .unwrap()
+        .into_owned();
+    assert_eq!(resp, br#"{"Double":42}"#);
+}
+
+#[tokio::test]
+async fn msgpack_clients_call_handlers() {
+    let (_running, conn) = connect("codec-msgpack").await;
+    assert_eq!(
+        handshake(&conn, &[Codec::MsgPack, Codec::Json]).await,
+        Codec::MsgPack
+    );
+
+    let req = codec::to_vec(Codec::MsgPack, &EchoProtocol::Double { value: 4 }).unwrap();
+    let resp = conn.send(channel::APP, &req, SECOND).await.unwrap();
+    let resp: EchoResponse = codec::from_slice(Codec::MsgPack, &resp.into_owned()).unwrap();
+    assert_eq!(resp, EchoResponse::Double(8));
+}
+
+#[tokio::test]
+async fn failures_answer_in_the_negotiated_codec() {
+    let (_running, conn) = connect("codec-errors").await;
+    handshake(&conn, &[Codec::Json]).await;
+
+    let err = conn
+        .send(channel::APP, br#"{"Double":{"value":0}}"#, SECOND)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Nothing to double"));
+
+    let err = conn
+        .send(channel::APP, br#"{"Triple":{"value":1}}"#, SECOND)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Malformed json request"));
+}
+
+#[tokio::test]
+async fn rkyv_stays_the_default() {
+    let (_running, conn) = connect("codec-default").await;
+
+    let req = rkyv::to_bytes::<_, 256>(&EchoProtocol::Double { value: 1 }).unwrap();
+    let resp = conn
+        .send(channel::APP, &req, SECOND)
+        .await
+        .unwrap()
+        .into_owned();
+    let archived = rkyv::check_archived_root::<EchoResponse>(&resp).unwrap();
+    assert!(matches!(archived, ArchivedEchoResponse::Double(v) if *v == 2));
+}
@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/compose.rs
+//! Cells hosted by a Composition are reachable in-process through Synapse::grow,
+//! and application calls to them bypass the pipe.
+
+use cell_sdk::compose::{self, Composition};
+use cell_sdk::membrane::BoxFuture;
+use cell_sdk::{Membrane, Synapse};
+
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug, Clone)]
+#[archive(check_bytes)]
+struct Ping {
+    value: u64,
+}
+
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug, Clone, PartialEq)]
+#[archive(check_bytes)]
+struct Pong {
+    value: u64,
+}
+
+fn echo(req: &ArchivedPing) -> BoxFuture<'_, anyhow::Result<Pong>> {
+    let value = req.value;
+    Box::pin(async move { Ok(Pong { value: value + 1 }) })
+}
+
+#[tokio::test]
+async fn composed_cells_talk_over_memory() {
+    let serve = Membrane::bind::<_, Ping, Pong>("compose-echo", echo, None, None, None);
+    let running = Composition::new().cell("compose-echo", serve).start();
+    assert_eq!(running.names(), ["compose-echo"]);
+    assert!(compose::is_hosted("compose-echo"));
+    assert!(!compose::is_hosted("compose-missing"));
+
+    let synapse = Synapse::grow("compose-echo").await.unwrap();
+    let resp = synapse.fire(&Ping { value: 41 }).await.unwrap().into_owned();
+    let pong = rkyv::check_archived_root::<Pong>(&resp).unwrap();
+    assert_eq!(pong.value, 42);
+}
+
+#[tokio::test]
+async fn hosted_cells_dispatch_directly() {
+    let serve = Membrane::bind::<_, Ping, Pong>("compose-direct", echo, None, None, None);
+    let _running = Composition::new().cell("compose-direct", serve).start();
+
+    let dispatch = loop {
+        if let Some(d) = compose::dispatcher("compose-direct") {
+            break d;
+        }
+        tokio::task::yield_now().await;
+    };
+    let req = rkyv::to_bytes::<_, 1024>(&Ping { value: 1 }).unwrap();
+    let (resp, degraded) = dispatch(req).await.unwrap();
+    assert!(!degraded);
+    let pong = rkyv::check_archived_root::<Pong>(&resp).unwrap();
+    assert_eq!(pong.value, 2);
+
+    let synapse = Synapse::grow("compose-direct").await.unwrap();
+    let resp = synapse.fire(&Ping { value: 7 }).await.unwrap().into_owned();
+    assert_eq!(rkyv::check_archived_root::<Pong>(&resp).unwrap().value, 8);
+}
@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/context.rs
+//! Handlers see who called them through the Membrane-provided context.
+
+use cell_sdk::compose::{self, Composition};
+use cell_sdk::context::{self, CallContext};
+use cell_sdk::correlator::Correlator;
+use cell_sdk::membrane::BoxFuture;
+use cell_sdk::{channel, Membrane};
+use std::time::Duration;
+
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug)]
+#[archive(check_bytes)]
+enum WhoProtocol {
+    Peer,
+}
+
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug)]
+#[archive(check_bytes)]
+enum WhoResponse {
+    Peer { peer: String, trace_id: String },
+}
+
+fn who(_req: &ArchivedWhoProtocol) -> BoxFuture<'_, anyhow::Result<WhoResponse>> {
+    Box::pin(async move {
+        let ctx = context::current();
+        Ok(WhoResponse::Peer {
+            peer: ctx.peer,
+            trace_id: ctx.trace_id(),
+        })
+    })
+}
+
+#[tokio::test]
+async fn membrane_fills_in_the_context() {
+    let serve = Membrane::bind::<_, WhoProtocol, WhoResponse>("context-who", who, None, None, None);
+    let _running = Composition::new().cell("context-who", serve).start();
+    let conn = Correlator::new(compose::connect("context-who").unwrap(), 0);
+
+    let req = rkyv::to_bytes::<_, 256>(&WhoProtocol::Peer).unwrap();
+    let mut traces = Vec::new();
+    for _ in 0..2 {
+        let resp = conn
+            .send(channel::APP, &req, Duration::from_secs(1))
+            .await
+            .unwrap()
+            .into_owned();
+        let ArchivedWhoResponse::Peer { peer, trace_id } =
+            rkyv::check_archived_root::<WhoResponse>(&resp).unwrap();
+        assert_eq!(peer.as_str(), "local");
+        traces.push(trace_id.to_string());
+    }
+    assert_ne!(traces[0], traces[1], "every call gets its own trace id");
+}
+
+#[tokio::test]
+async fn deadlines_only_tighten() {
+    let ctx = CallContext::new("local", None).within(Duration::from_secs(60));
+    let tighter = ctx.clone().within(Duration::from_millis(10));
+    assert!(tighter.remaining() < ctx.remaining());
+    assert!(tighter.clone().within(Duration::from_secs(60)).deadline == tighter.deadline);
+
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(tighter.expired());
+    assert!(!ctx.expired());
+}
+
+#[tokio::test]
+async fn scope_sets_the_caller_too() {
+    let caller = cell_sdk::auth::Caller {
+        principal: "billing".into(),
+        organism: "acme".into(),
+        impersonated_by: None,
+    };
+    let ctx = CallContext::new("uid:1000", Some(caller.clone()));
+    context::scope(ctx, async {
+        assert_eq!(context::current().principal(), "billing");
+        assert_eq!(cell_sdk::auth::caller(), Some(caller));
+    })
+    .await;
+    assert_eq!(context::current().peer, "local");
+}
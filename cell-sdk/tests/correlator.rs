@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/correlator.rs
+//! Concurrent requests on one connection each get their own response.
+
+use cell_sdk::compose::{self, Composition};
+use cell_sdk::correlator::Correlator;
+use cell_sdk::membrane::BoxFuture;
+use cell_sdk::Membrane;
+use std::time::Duration;
+
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug, Clone)]
+#[archive(check_bytes)]
+struct Ping {
+    value: u64,
+}
+
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug, Clone, PartialEq)]
+#[archive(check_bytes)]
+struct Pong {
+    value: u64,
+}
+
+/// Larger values take longer, so responses come back out of order
+fn sleepy_echo(req: &ArchivedPing) -> BoxFuture<'_, anyhow::Result<Pong>> {
+    let value = req.value;
+    Box::pin(async move {
+        tokio::time::sleep(Duration::from_millis(value * 20)).await;
+        Ok(Pong { value })
+    })
+}
+
+async fn correlator(name: &'static str) -> (compose::Running, Correlator) {
+    let serve = Membrane::bind::<_, Ping, Pong>(name, sleepy_echo, None, None, None);
+    let running = Composition::new().cell(name, serve).start();
+    let stream = compose::connect(name).unwrap();
+    (running, Correlator::new(stream, 0))
+}
+
+async fn ping(conn: &Correlator, value: u64, timeout: Duration) -> anyhow::Result<u64> {
+    let req = rkyv::to_bytes::<_, 256>(&Ping { value }).unwrap();
+    let resp = conn.send(0, &req, timeout).await?.into_owned();
+    Ok(rkyv::check_archived_root::<Pong>(&resp).unwrap().value)
+}
+
+#[tokio::test]
+async fn responses_find_their_callers() {
+    let (_running, conn) = correlator("correlated-echo").await;
+    let second = Duration::from_secs(1);
+    let (a, b, c) = tokio::join!(
+        ping(&conn, 3, second),
+        ping(&conn, 1, second),
+        ping(&conn, 2, second)
+    );
+    assert_eq!((a.unwrap(), b.unwrap(), c.unwrap()), (3, 1, 2));
+    assert_eq!(conn.in_flight(), 0);
+}
+
+#[tokio::test]
+async fn late_responses_are_not_read_by_the_next_call() {
+    let (_running, conn) = correlator("correlated-late").await;
+    assert!(ping(&conn, 5, Duration::from_millis(10)).await.is_err());
+    assert_eq!(ping(&conn, 0, Duration::from_secs(1)).await.unwrap(), 0);
+    // The abandoned call's response is dropped when it arrives
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(ping(&conn, 1, Duration::from_secs(1)).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn cancelled_calls_do_not_cut_their_request_short() {
+    let (_running, conn) = correlator("correlated-cancel").await;
+    // Far more than the pipe holds, so the write is still going when the call gives up
+    let large = vec![0u8; 1024 * 1024];
+    assert!(conn.send(0, &large, Duration::ZERO).await.is_err());
+    assert_eq!(ping(&conn, 1, Duration::from_secs(1)).await.unwrap(), 1);
+    assert!(!conn.is_closed());
+}
@@ -1,4 +1,5 @@
-use cell_sdk::crdt::{GCounter, LwwRegister};
+use cell_sdk::crdt::{GCounter, Gossip, GossipConfig, LwwRegister, OrSet, PnCounter};
+use std::time::Duration;
 
 #[test]
 fn test_gcounter_convergence() {
@@ -47,4 +48,93 @@ fn test_lww_register() {
     // Merge A (now B) into B - should be no change
     reg_b.merge(&reg_a);
     assert_eq!(*reg_b.get(), "Update B");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_pn_counter() {
+    let mut a = PnCounter::new(1);
+    let mut b = PnCounter::new(2);
+    a.add(5);
+    b.dec();
+    b.add(-3);
+    a.merge(&b);
+    b.merge(&a);
+    assert_eq!(a.value(), 1);
+    assert_eq!(b.value(), 1);
+
+    // Merging again changes nothing
+    a.merge(&b);
+    assert_eq!(a.value(), 1);
+}
+
+#[test]
+fn test_lww_same_timestamp() {
+    let mut a = LwwRegister::new("Initial", 100).with_node(1);
+    let mut b = LwwRegister::new("Initial", 100).with_node(2);
+    a.set("From A", 110);
+    b.set("From B", 110);
+
+    // The higher node wins the tie, whichever way the merge goes
+    a.merge(&b);
+    b.merge(&a);
+    assert_eq!(*a.get(), "From B");
+    assert_eq!(*b.get(), "From B");
+
+    a.assign("Later");
+    assert!(a.timestamp() > 110);
+    b.merge(&a);
+    assert_eq!(*b.get(), "Later");
+}
+
+#[test]
+fn test_or_set_add_wins() {
+    let mut a = OrSet::new(1);
+    let mut b = OrSet::new(2);
+    a.insert("x");
+    b.merge(&a);
+
+    // A removes x while B adds it again
+    assert!(a.remove(&"x"));
+    b.insert("x");
+    b.insert("y");
+
+    a.merge(&b);
+    b.merge(&a);
+    assert_eq!(a, b);
+    assert!(a.contains(&"x"));
+    assert_eq!(a.iter().copied().collect::<Vec<_>>(), ["x", "y"]);
+
+    // A remove that saw every add sticks
+    a.remove(&"x");
+    b.merge(&a);
+    assert!(!b.contains(&"x"));
+    assert_eq!(b.len(), 1);
+}
+
+#[tokio::test]
+async fn test_gossip_converges() {
+    let config = |port: u16, peer: u16| GossipConfig {
+        port,
+        broadcast: false,
+        peers: vec![format!("127.0.0.1:{}", peer).parse().unwrap()],
+        interval: Duration::from_millis(50),
+    };
+    let a = Gossip::join(config(39181, 39182)).await.unwrap();
+    let b = Gossip::join(config(39182, 39181)).await.unwrap();
+
+    let visits_a = a.replicate("visits", PnCounter::new(a.node_id())).unwrap();
+    let visits_b = b.replicate("visits", PnCounter::new(b.node_id())).unwrap();
+    assert!(a.replicate("visits", PnCounter::new(1)).is_err());
+
+    visits_a.update(|c| c.add(3));
+    visits_b.update(|c| c.dec());
+
+    let mut seen = visits_b.subscribe();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while visits_a.get().value() != 2 || visits_b.get().value() != 2 {
+            let _ = tokio::time::timeout(Duration::from_millis(50), seen.changed()).await;
+        }
+    })
+    .await
+    .expect("replicas did not converge");
+}
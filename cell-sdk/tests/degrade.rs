@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/degrade.rs
+//! Fallback responses are tagged as degraded on the wire.
+
+use cell_sdk::degrade;
+use cell_sdk::Response;
+use std::time::Duration;
+
+#[tokio::test]
+async fn marked_requests_are_reported_degraded() {
+    let (value, degraded) = degrade::track(async { 1 }).await;
+    assert_eq!((value, degraded), (1, false));
+
+    let (_, degraded) = degrade::track(async { degrade::mark() }).await;
+    assert!(degraded);
+
+    // Outside a tracked request marking is a no-op
+    degrade::mark();
+}
+
+#[tokio::test]
+async fn slow_primaries_time_out() {
+    let slow = async {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        Ok::<_, anyhow::Error>(1)
+    };
+    assert!(degrade::within(Some(Duration::from_millis(10)), slow).await.is_err());
+    assert_eq!(degrade::within(None, async { Ok(2) }).await.unwrap(), 2);
+}
+
+#[test]
+fn frame_flag_selects_degraded_response() {
+    let resp = Response::<()>::from_frame(cell_core::frame::DEGRADED | 3, vec![1, 2, 3]);
+    assert!(resp.is_degraded());
+    assert_eq!(resp.into_owned(), [1, 2, 3]);
+    assert!(!Response::<()>::from_frame(3, vec![1, 2, 3]).is_degraded());
+}
@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/domain.rs
+//! Cross-domain access in storage cells needs an IAM grant.
+
+use cell_sdk::auth::{self, Caller};
+use cell_sdk::domain::{self, Domain, DomainGrant};
+use cell_sdk::error::{CellError, ErrorContext};
+
+fn as_principal(principal: &str, organism: &str) -> Option<Caller> {
+    Some(Caller {
+        principal: principal.into(),
+        organism: organism.into(),
+        impersonated_by: None,
+    })
+}
+
+#[tokio::test]
+async fn foreign_domains_need_a_grant() {
+    let acme = Domain::new("acme", None);
+    domain::set_grants(vec![DomainGrant {
+        principal: "analytics".into(),
+        organism: "acme".into(),
+        residency: None,
+        expires_at: None,
+    }]);
+
+    // A caller reaches its own organism's records without a grant
+    auth::scope(as_principal("billing", "acme"), async {
+        assert!(domain::authorize(&acme).is_ok());
+    })
+    .await;
+    auth::scope(as_principal("analytics", "globex"), async {
+        assert!(domain::authorize(&acme).is_ok());
+    })
+    .await;
+
+    let err = auth::scope(as_principal("billing", "globex"), async {
+        domain::authorize(&acme).unwrap_err()
+    })
+    .await;
+    let ctx = err.downcast_ref::<ErrorContext>().unwrap();
+    assert_eq!(ctx.code, CellError::AccessDenied);
+
+    // This cell's organism is not the caller's
+    let local = domain::local();
+    auth::scope(as_principal("billing", "globex"), async {
+        assert!(domain::authorize(&local).is_err());
+    })
+    .await;
+
+    // Unauthenticated callers reach nothing
+    assert!(domain::authorize(&acme).is_err());
+    assert!(domain::authorize(&local).is_err());
+
+    domain::set_grants(Vec::new());
+    auth::scope(as_principal("analytics", "globex"), async {
+        assert!(domain::authorize(&acme).is_err());
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn resident_records_stay_in_their_region() {
+    let local = domain::local();
+    assert_eq!(domain::resolve(None), local);
+    let caller = as_principal("billing", &local.organism);
+    auth::scope(caller, async {
+        assert!(domain::authorize_write(&local).is_ok());
+
+        let eu = Domain::new(local.organism.clone(), Some("eu".into()));
+        let err = domain::authorize_write(&eu).unwrap_err();
+        assert!(err.to_string().contains("must stay in eu"), "{}", err);
+    })
+    .await;
+}
@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/error.rs
+//! Error codes survive the trip across RPC.
+
+use cell_sdk::error::{CellError, ErrorCategory, ErrorContext};
+
+#[test]
+fn codes_round_trip_and_classify() {
+    for code in [100, 107, 201, 205, 302, 400, 503] {
+        assert_eq!(CellError::from_code(code).unwrap().code(), code);
+    }
+    assert!(CellError::from_code(999).is_none());
+
+    assert_eq!(CellError::RateLimited.category(), ErrorCategory::Resource);
+    assert!(CellError::RateLimited.is_retryable());
+    assert!(!CellError::AccessDenied.is_retryable());
+    assert!(!CellError::NotFound.is_retryable());
+    assert!(CellError::NotFound.retry_delay().is_none());
+}
+
+#[test]
+fn remote_error_keeps_code_and_origin() {
+    let handler_err = anyhow::Error::from(
+        ErrorContext::new(CellError::NotFound)
+            .with_message("no such account")
+            .with_cell("ledger"),
+    );
+
+    // The Membrane of "billing" relays an error that started in "ledger"
+    let wire = ErrorContext::classify(&handler_err, CellError::HandlerFailed).to_response("billing");
+    let ctx = ErrorContext::from_response(wire);
+    assert_eq!(ctx.code, CellError::NotFound);
+    assert_eq!(ctx.cell.as_deref(), Some("ledger"));
+    assert_eq!(ctx.message, "no such account");
+
+    let plain = anyhow::anyhow!("disk full");
+    let ctx = ErrorContext::from_response(
+        ErrorContext::classify(&plain, CellError::HandlerFailed).to_response("billing"),
+    );
+    assert_eq!(ctx.code, CellError::HandlerFailed);
+    assert_eq!(ctx.cell.as_deref(), Some("billing"));
+}
+
+#[test]
+fn retry_hint_crosses_the_wire() {
+    let shed = ErrorContext::new(CellError::Throttled)
+        .with_retry_after(std::time::Duration::from_millis(1200));
+    let ctx = ErrorContext::from_response(shed.to_response("ledger"));
+    assert_eq!(
+        ctx.retry_delay(),
+        Some(std::time::Duration::from_millis(1200))
+    );
+
+    let default =
+        ErrorContext::from_response(ErrorContext::new(CellError::Throttled).to_response("ledger"));
+    assert_eq!(default.retry_delay(), CellError::Throttled.retry_delay());
+}
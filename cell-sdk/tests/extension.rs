@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/extension.rs
+//! Methods registered at runtime are listed under a new generation and
+//! published to the registry, and a name belongs to the plugin that
+//! registered it first.
+
+use cell_sdk::extension::{self, ExtensionMethod};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Keep published lists out of the real registry
+fn registry() -> &'static Path {
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let dir = std::env::temp_dir().join(format!("cell-extension-{}", std::process::id()));
+        std::env::set_var("CELL_REGISTRY_DIR", &dir);
+        dir
+    })
+}
+
+fn method(name: &str, plugin: &str) -> ExtensionMethod {
+    ExtensionMethod {
+        name: name.to_string(),
+        plugin: plugin.to_string(),
+        schema: format!("async fn {}(&self, req: Vec<u8>) -> Result<Vec<u8>>", name),
+    }
+}
+
+fn echo() -> extension::ExtensionHandler {
+    extension::handler(|args| async move { Ok(args) })
+}
+
+#[test]
+fn registering_bumps_the_generation() {
+    registry();
+    let before = extension::methods().generation;
+    extension::register(method("thumbnail", "images"), echo()).unwrap();
+
+    let list = extension::methods();
+    assert!(list.generation > before);
+    assert_eq!(list.get("thumbnail").unwrap().plugin, "images");
+
+    let registered = list.generation;
+    assert_eq!(
+        extension::unregister("thumbnail").unwrap().name,
+        "thumbnail"
+    );
+    assert!(extension::methods().generation > registered);
+    assert!(extension::methods().get("thumbnail").is_none());
+    assert!(extension::unregister("thumbnail").is_none());
+}
+
+#[test]
+fn names_belong_to_their_plugin() {
+    registry();
+    extension::register(method("transcode", "video"), echo()).unwrap();
+    // The same plugin may replace its handler, e.g. after a reload
+    extension::register(method("transcode", "video"), echo()).unwrap();
+    assert!(extension::register(method("transcode", "audio"), echo()).is_err());
+    assert_eq!(
+        extension::methods().get("transcode").unwrap().plugin,
+        "video"
+    );
+}
+
+#[test]
+fn registrations_are_published() {
+    let dir = registry();
+    extension::register(method("resize", "images"), echo()).unwrap();
+
+    // This process is the only cell publishing here
+    let cell = std::fs::read_dir(dir)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .file_name();
+    let cell = cell.to_str().unwrap();
+    let published = extension::published(cell).unwrap().unwrap();
+    assert_eq!(published.get("resize").unwrap().plugin, "images");
+
+    extension::unregister("resize");
+    let published = extension::published(cell).unwrap().unwrap();
+    assert!(published.get("resize").is_none());
+    assert!(extension::published("no-such-cell").unwrap().is_none());
+}
@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/health_check.rs
+//! OPS `HealthCheck`: custom checks decide readiness, silence means not live.
+
+use cell_sdk::runtime::Runtime;
+use cell_sdk::watchdog;
+
+#[tokio::test]
+async fn custom_checks_decide_readiness() {
+    Runtime::register_health_check("cache-warm", || async { anyhow::bail!("still loading") });
+
+    let status = watchdog::status().await;
+    assert!(status.live);
+    assert!(!status.ready);
+    let check = status
+        .checks
+        .iter()
+        .find(|c| c.name == "cache-warm")
+        .unwrap();
+    assert_eq!(check.detail, "still loading");
+}
+
+#[tokio::test]
+async fn unreachable_cells_are_neither_live_nor_ready() {
+    let status = watchdog::check("health-check-no-such-cell").await;
+    assert!(!status.live && !status.ready);
+}
@@ -0,0 +1,22 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/inspect.rs
+//! The OPS `Inspect` report reflects this process.
+
+use cell_sdk::inspect::report;
+use cell_sdk::shm::RingBuffer;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn report_lists_live_rings_and_runtime() {
+    let dir = tempfile::tempdir().unwrap();
+    let ring = RingBuffer::create_persistent(&dir.path().join("ring"), 1 << 20).unwrap();
+
+    let before = report();
+    assert_eq!(before.tasks.workers, 2);
+    assert!(before
+        .rings
+        .iter()
+        .any(|r| r.capacity == ring.capacity() as u64 && r.used == 0));
+
+    drop(ring);
+    assert!(report().rings.len() < before.rings.len());
+}
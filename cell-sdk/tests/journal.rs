@@ -0,0 +1,132 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/journal.rs
+//! Journals keep appended events across reopening, drop torn records and
+//! compact without reusing sequence numbers.
+
+use cell_sdk::journal::Journal;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+enum LedgerEvent {
+    Deposit { user: u64, amount: u64 },
+    Withdraw { user: u64, amount: u64 },
+}
+
+fn replayed(journal: &Journal<LedgerEvent>) -> Vec<(u64, LedgerEvent)> {
+    let mut events = Vec::new();
+    journal
+        .replay(|seq, event| events.push((seq, event)))
+        .unwrap();
+    events
+}
+
+#[tokio::test]
+async fn events_survive_reopening() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ledger.journal");
+
+    let journal = Journal::open_at(&path).unwrap();
+    assert!(journal.is_empty());
+    for user in 0..3 {
+        let seq = journal
+            .append(&LedgerEvent::Deposit { user, amount: 10 })
+            .await
+            .unwrap();
+        assert_eq!(seq, user);
+    }
+    drop(journal);
+
+    let journal = Journal::open_at(&path).unwrap();
+    let events = replayed(&journal);
+    assert_eq!(events.len(), 3);
+    assert_eq!(
+        events[2],
+        (
+            2,
+            LedgerEvent::Deposit {
+                user: 2,
+                amount: 10
+            }
+        )
+    );
+    assert_eq!(journal.next_seq(), 3);
+}
+
+#[tokio::test]
+async fn torn_record_is_dropped() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ledger.journal");
+
+    let journal = Journal::open_at(&path).unwrap();
+    journal
+        .append(&LedgerEvent::Deposit { user: 1, amount: 5 })
+        .await
+        .unwrap();
+    let intact = journal.size();
+    drop(journal);
+
+    // A crash halfway through writing the next record
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    file.write_all(&[40, 0, 0, 0, 1, 2, 3]).unwrap();
+    drop(file);
+
+    let journal = Journal::<LedgerEvent>::open_at(&path).unwrap();
+    assert_eq!(journal.size(), intact);
+    assert_eq!(replayed(&journal).len(), 1);
+    let seq = journal
+        .append(&LedgerEvent::Withdraw { user: 1, amount: 2 })
+        .await
+        .unwrap();
+    assert_eq!(seq, 1);
+    assert_eq!(replayed(&journal).len(), 2);
+}
+
+#[tokio::test]
+async fn compaction_keeps_numbering() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ledger.journal");
+
+    let journal = Journal::open_at(&path).unwrap();
+    for _ in 0..10 {
+        journal
+            .append(&LedgerEvent::Deposit { user: 1, amount: 1 })
+            .await
+            .unwrap();
+    }
+    let before = journal.size();
+    journal
+        .compact(vec![LedgerEvent::Deposit {
+            user: 1,
+            amount: 10,
+        }])
+        .await
+        .unwrap();
+    assert!(journal.size() < before);
+    assert_eq!(journal.len(), 1);
+
+    let seq = journal
+        .append(&LedgerEvent::Withdraw { user: 1, amount: 4 })
+        .await
+        .unwrap();
+    assert_eq!(seq, 11);
+    drop(journal);
+
+    let journal = Journal::open_at(&path).unwrap();
+    assert_eq!(
+        replayed(&journal),
+        vec![
+            (
+                10,
+                LedgerEvent::Deposit {
+                    user: 1,
+                    amount: 10
+                }
+            ),
+            (11, LedgerEvent::Withdraw { user: 1, amount: 4 }),
+        ]
+    );
+}
@@ -0,0 +1,22 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/logging.rs
+//! OPS `SetLogLevel` rewrites the installed filter in place.
+
+use cell_sdk::logging::{current, filter_layer, set_level};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+#[test]
+fn set_level_overrides_one_target_at_a_time() {
+    tracing_subscriber::registry().with(filter_layer()).init();
+    assert!(set_level(None, "loud").is_err());
+
+    set_level(Some("ledger::wal"), "debug").unwrap();
+    set_level(Some("ledger::wal"), "trace").unwrap();
+    let filter = set_level(None, "warn").unwrap();
+
+    assert_eq!(current().as_deref(), Some(filter.as_str()));
+    assert!(filter.contains("ledger::wal=trace"));
+    assert!(!filter.contains("ledger::wal=debug"));
+    assert!(tracing::enabled!(target: "ledger::wal", tracing::Level::TRACE));
+    assert!(!tracing::enabled!(target: "ledger::raft", tracing::Level::INFO));
+}
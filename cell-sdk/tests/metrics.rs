@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/metrics.rs
+//! Handler calls are counted per method and scraped as Prometheus text.
+
+use cell_sdk::metrics::{prometheus, record, report};
+use std::time::Duration;
+
+#[test]
+fn calls_are_counted_per_method() {
+    record("metrics_charge", Duration::from_millis(2), true);
+    record("metrics_charge", Duration::from_millis(700), false);
+    record("metrics_refund", Duration::from_micros(300), true);
+
+    let snapshot = report();
+    let charge = snapshot
+        .methods
+        .iter()
+        .find(|m| m.method == "metrics_charge")
+        .unwrap();
+    assert_eq!((charge.calls, charge.errors), (2, 1));
+    assert_eq!(charge.latency_histogram.iter().sum::<u64>(), 2);
+    assert!(snapshot.requests_total >= 3);
+    assert!(snapshot.requests_failed >= 1);
+
+    let text = prometheus();
+    assert!(text.contains("method=\"metrics_refund\",le=\"0.001\"} 1"));
+    assert!(text.contains("# TYPE cell_request_duration_seconds histogram"));
+}
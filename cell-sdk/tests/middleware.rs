@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/middleware.rs
+//! The middleware chain runs links in order, stops at the first rejection
+//! and shows bodies only to links that asked for them.
+
+use anyhow::Result;
+use cell_core::CellError;
+use cell_sdk::middleware::{self, Middleware, MiddlewareOutcome, MiddlewareRequest, Verdict};
+use std::sync::{Arc, Mutex};
+
+/// Rejects requests whose body contains `deny`, recording what it saw
+struct Filter {
+    deny: &'static str,
+    seen: Mutex<Vec<Option<String>>>,
+    outcomes: Mutex<Vec<bool>>,
+}
+
+impl Filter {
+    fn new(deny: &'static str) -> Arc<Self> {
+        Arc::new(Self {
+            deny,
+            seen: Mutex::new(Vec::new()),
+            outcomes: Mutex::new(Vec::new()),
+        })
+    }
+}
+
+impl Middleware for Filter {
+    fn request(&self, request: &MiddlewareRequest) -> Result<Verdict> {
+        self.seen.lock().unwrap().push(request.body.clone());
+        match &request.body {
+            Some(body) if body.contains(self.deny) => Ok(Verdict::Reject {
+                message: format!("'{}' is not allowed", self.deny),
+            }),
+            _ => Ok(Verdict::Continue),
+        }
+    }
+
+    fn outcome(&self, outcome: &MiddlewareOutcome) {
+        self.outcomes.lock().unwrap().push(outcome.ok);
+    }
+}
+
+fn request(size: usize) -> MiddlewareRequest {
+    MiddlewareRequest {
+        cell: "orders".to_string(),
+        channel: cell_core::channel::APP,
+        principal: "alice".to_string(),
+        peer: "tcp:127.0.0.1".to_string(),
+        codec: "json".to_string(),
+        size,
+        body: None,
+    }
+}
+
+// One test, since the chain is global to the process
+#[test]
+fn chain_filters_in_order() {
+    assert!(!middleware::active());
+    let waf = Filter::new("union select");
+    let blind = Filter::new("anything");
+    middleware::install("waf", true, waf.clone());
+    middleware::install("blind", false, blind.clone());
+    assert!(middleware::active());
+
+    let fine = br#"{"id":1}"#;
+    middleware::check(&request(fine.len()), fine).unwrap();
+    assert_eq!(
+        waf.seen.lock().unwrap().as_slice(),
+        &[Some(r#"{"id":1}"#.to_string())]
+    );
+    assert_eq!(blind.seen.lock().unwrap().as_slice(), &[None]);
+
+    let hostile = b"1 union select password";
+    let err = middleware::check(&request(hostile.len()), hostile).unwrap_err();
+    assert_eq!(err.code, CellError::AccessDenied);
+    assert!(err.message.contains("waf"), "{}", err.message);
+    // Stopped before the second link
+    assert_eq!(blind.seen.lock().unwrap().len(), 1);
+
+    // Not UTF-8: no body even for links that take them
+    let binary = [0xff, 0xfe, 0x00];
+    middleware::check(&request(binary.len()), &binary).unwrap();
+    assert_eq!(waf.seen.lock().unwrap().last(), Some(&None));
+
+    middleware::outcome(&MiddlewareOutcome {
+        cell: "orders".to_string(),
+        channel: cell_core::channel::APP,
+        principal: "alice".to_string(),
+        elapsed_us: 120,
+        ok: true,
+    });
+    assert_eq!(waf.outcomes.lock().unwrap().as_slice(), &[true]);
+    assert_eq!(blind.outcomes.lock().unwrap().as_slice(), &[true]);
+}
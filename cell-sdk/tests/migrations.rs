@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/migrations.rs
+//! Migrations run once, in order, and refuse state from a newer build.
+
+use cell_sdk::migrations::{self, Migratable, Migration};
+
+/// A store that commits a migration and its record together
+#[derive(Default)]
+struct Db {
+    tables: Vec<&'static str>,
+    applied: Vec<u32>,
+}
+
+impl Migratable for Db {
+    fn applied(&mut self) -> anyhow::Result<Vec<u32>> {
+        Ok(self.applied.clone())
+    }
+
+    fn apply(&mut self, migration: &Migration<Self>) -> anyhow::Result<()> {
+        let before = self.tables.clone();
+        if let Err(e) = (migration.up)(self) {
+            self.tables = before;
+            return Err(e);
+        }
+        self.applied.push(migration.version);
+        Ok(())
+    }
+}
+
+fn add_table(db: &mut Db) -> anyhow::Result<()> {
+    db.tables.push("accounts");
+    Ok(())
+}
+
+fn add_index(db: &mut Db) -> anyhow::Result<()> {
+    db.tables.push("accounts_by_owner");
+    Ok(())
+}
+
+fn half_done(db: &mut Db) -> anyhow::Result<()> {
+    db.tables.push("ledger");
+    anyhow::bail!("disk full")
+}
+
+const ACCOUNTS: Migration<Db> = Migration {
+    version: 1,
+    name: "accounts",
+    up: add_table,
+};
+
+const BY_OWNER: Migration<Db> = Migration {
+    version: 2,
+    name: "accounts-by-owner",
+    up: add_index,
+};
+
+const LEDGER: Migration<Db> = Migration {
+    version: 3,
+    name: "ledger",
+    up: half_done,
+};
+
+#[test]
+fn pending_migrations_run_once() {
+    let mut db = Db::default();
+    assert_eq!(migrations::run_with(&mut db, &[ACCOUNTS]).unwrap(), [1]);
+    assert!(migrations::run_with(&mut db, &[ACCOUNTS]).unwrap().is_empty());
+    assert_eq!(migrations::run_with(&mut db, &[ACCOUNTS, BY_OWNER]).unwrap(), [2]);
+    assert_eq!(db.tables, ["accounts", "accounts_by_owner"]);
+    assert_eq!(db.applied, [1, 2]);
+
+    // An older build must not touch state it does not understand
+    assert!(migrations::run_with(&mut db, &[ACCOUNTS]).is_err());
+    assert!(migrations::run_with(&mut db, &[BY_OWNER, ACCOUNTS]).is_err());
+}
+
+#[test]
+fn failed_migration_is_not_recorded() {
+    let mut db = Db::default();
+    assert!(migrations::run_with(&mut db, &[ACCOUNTS, BY_OWNER, LEDGER]).is_err());
+    assert_eq!(db.applied, [1, 2]);
+    assert_eq!(db.tables, ["accounts", "accounts_by_owner"]);
+}
@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/otel.rs
+//! Per-organism OTLP configuration and the resource attributes it exports.
+#![cfg(feature = "otel")]
+
+use cell_sdk::otel::{resource_attributes, OtelConfig, Protocol};
+
+#[test]
+fn missing_config_disables_export() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = OtelConfig::from_file(&dir.path().join("otel.toml")).unwrap();
+    assert_eq!(config, OtelConfig::default());
+    assert!(config.endpoint.is_none());
+}
+
+#[test]
+fn config_file_and_extra_resource_attributes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("otel.toml");
+    std::fs::write(
+        &path,
+        r#"
+            endpoint = "http://collector:4318"
+            protocol = "http"
+
+            [headers]
+            authorization = "Bearer token"
+
+            [resource]
+            "deployment.environment" = "staging"
+        "#,
+    )
+    .unwrap();
+
+    let config = OtelConfig::from_file(&path).unwrap();
+    assert_eq!(config.endpoint.as_deref(), Some("http://collector:4318"));
+    assert_eq!(config.protocol, Protocol::Http);
+    assert_eq!(config.export_interval_secs, 30);
+
+    let attributes = resource_attributes("ledger", &config);
+    let get = |key: &str| {
+        attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == key)
+            .map(|kv| kv.value.to_string())
+    };
+    assert_eq!(get("service.name").as_deref(), Some("ledger"));
+    assert_eq!(get("deployment.environment").as_deref(), Some("staging"));
+    assert!(get("cell.node_id").is_some());
+    assert!(get("cell.version_hash").is_some());
+}
+
+mod sampling {
+    use cell_sdk::otel::{HeadSampler, SamplingConfig, TailSampler, REQUEST_SPAN};
+    use opentelemetry::trace::{Span as _, Status, Tracer, TracerProvider as _};
+    use opentelemetry_sdk::testing::trace::InMemorySpanExporter;
+    use opentelemetry_sdk::trace::{SimpleSpanProcessor, TracerProvider};
+
+    fn provider(sampling: SamplingConfig) -> (TracerProvider, InMemorySpanExporter) {
+        let exporter = InMemorySpanExporter::default();
+        let processor = SimpleSpanProcessor::new(Box::new(exporter.clone()));
+        let provider = TracerProvider::builder()
+            .with_sampler(HeadSampler::new(sampling.ratio))
+            .with_span_processor(TailSampler::new(processor, sampling))
+            .build();
+        (provider, exporter)
+    }
+
+    fn request(provider: &TracerProvider, fail: bool) {
+        let tracer = provider.tracer("test");
+        tracer.in_span(REQUEST_SPAN, |cx| {
+            let mut query = tracer.start_with_context("query", &cx);
+            if fail {
+                query.set_status(Status::error("timeout"));
+            }
+            query.end();
+        });
+    }
+
+    #[test]
+    fn unsampled_traces_are_kept_only_when_they_fail() {
+        let (provider, exporter) = provider(SamplingConfig {
+            ratio: 0.0,
+            ..SamplingConfig::default()
+        });
+        request(&provider, false);
+        request(&provider, true);
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let names: Vec<&str> = spans.iter().map(|s| s.name.as_ref()).collect();
+        assert_eq!(names, ["query", REQUEST_SPAN]);
+        assert_eq!(
+            spans[0].span_context.trace_id(),
+            spans[1].span_context.trace_id()
+        );
+        assert!(spans.iter().all(|s| s.span_context.is_sampled()));
+    }
+
+    #[test]
+    fn head_sampled_traces_pass_through() {
+        let (provider, exporter) = provider(SamplingConfig::default());
+        request(&provider, false);
+        request(&provider, false);
+        assert_eq!(exporter.get_finished_spans().unwrap().len(), 4);
+    }
+}
@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/priority.rs
+//! Calls inherit their caller's chain priority, and scheduled calls run
+//! highest priority first.
+
+use cell_sdk::context::{self, CallContext};
+use cell_sdk::priority;
+use std::sync::{Arc, Mutex};
+
+#[tokio::test]
+async fn calls_inherit_chain_priority() {
+    assert_eq!(priority::current(), None);
+    assert_eq!(priority::inherit(50), 50);
+
+    let mut ctx = CallContext::new("uid:1000", None);
+    ctx.priority = Some(90);
+    context::scope(ctx, async {
+        assert_eq!(priority::current(), Some(90));
+        // Raised, never lowered
+        assert_eq!(priority::inherit(50), 90);
+        assert_eq!(priority::inherit(120), 120);
+        let admitted = cell_sdk::shed::admit("report", 50).unwrap();
+        assert_eq!(admitted.priority(), 90);
+    })
+    .await;
+
+    let set = priority::scope(70, async { priority::current() }).await;
+    assert_eq!(set, Some(70));
+}
+
+#[tokio::test]
+async fn scheduler_runs_highest_priority_first() {
+    priority::enable(1);
+    let running = priority::turn(50).await;
+
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let mut waiting = Vec::new();
+    for p in [priority::BACKGROUND, 90, 50, 90] {
+        let order = order.clone();
+        waiting.push(tokio::spawn(async move {
+            let _turn = priority::turn(p).await;
+            order.lock().unwrap().push(p);
+        }));
+        while priority::queued() < waiting.len() {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    // Giving up while queued frees nothing it was not given
+    let quitter = tokio::spawn(async { priority::turn(255).await });
+    while priority::queued() < 5 {
+        tokio::task::yield_now().await;
+    }
+    quitter.abort();
+    let _ = quitter.await;
+
+    drop(running);
+    for task in waiting {
+        task.await.unwrap();
+    }
+    assert_eq!(
+        *order.lock().unwrap(),
+        vec![90, 90, 50, priority::BACKGROUND]
+    );
+    assert_eq!(priority::queued(), 0);
+    drop(priority::turn(10).await);
+}
@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/profile.rs
+//! In-process profiles answer OPS `Profile` requests.
+
+use cell_sdk::ops::{ProfileFormat, ProfileKind};
+use cell_sdk::profile::{capture, MAX_SECONDS};
+
+const CPU: ProfileKind = ProfileKind::Cpu { frequency: 199 };
+
+#[tokio::test]
+async fn cpu_profile_renders_a_flamegraph() {
+    let spin = std::thread::spawn(|| {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_millis(1200);
+        let mut x = 0u64;
+        while std::time::Instant::now() < deadline {
+            x = x.wrapping_mul(31).wrapping_add(7);
+        }
+        x
+    });
+    let svg = capture(CPU, 1, ProfileFormat::Flamegraph).await.unwrap();
+    spin.join().unwrap();
+    assert!(String::from_utf8_lossy(&svg).contains("<svg"));
+}
+
+#[tokio::test]
+async fn window_is_bounded() {
+    assert!(capture(CPU, 0, ProfileFormat::Pprof).await.is_err());
+    assert!(capture(CPU, MAX_SECONDS + 1, ProfileFormat::Pprof)
+        .await
+        .is_err());
+}
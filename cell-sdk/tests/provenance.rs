@@ -0,0 +1,70 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/provenance.rs
+//! Hypervisors run a binary only on a trusted node's attestation of it.
+
+use cell_sdk::provenance::{self, Provenance, Statement};
+use cell_sdk::signing::{ProducerKey, TrustedProducers};
+
+fn setup() -> (tempfile::TempDir, ProducerKey, TrustedProducers) {
+    let dir = tempfile::tempdir().unwrap();
+    let key = ProducerKey::load_or_create(&dir.path().join("node.key")).unwrap();
+    key.export_public(&dir.path().join("nodes"), "node-a")
+        .unwrap();
+    let trusted = TrustedProducers::load_dir(&dir.path().join("nodes")).unwrap();
+    (dir, key, trusted)
+}
+
+fn statement(digest: &str) -> Statement {
+    Statement::new(
+        "ledger",
+        digest,
+        Provenance {
+            builder: "node-a".into(),
+            source_repo: Some("https://github.com/example/ledger".into()),
+            commit: Some("4f2c9e1".into()),
+            source_hash: "9a0b".into(),
+            toolchain: "rustc 1.80.0".into(),
+            build_flags: vec!["build".into(), "--release".into()],
+            built_at: 1_700_000_000,
+        },
+    )
+}
+
+#[test]
+fn attestations_verify_for_their_binary_only() {
+    let (dir, key, trusted) = setup();
+    let binary = dir.path().join("ledger");
+    std::fs::write(&binary, b"\x7fELF ledger").unwrap();
+    let digest = provenance::digest(&binary).unwrap();
+
+    let envelope = provenance::sign(&key, "node-a", &statement(&digest)).unwrap();
+    let verified = provenance::verify(&envelope, &digest, &trusted).unwrap();
+    assert_eq!(verified.predicate.commit.as_deref(), Some("4f2c9e1"));
+
+    // Swapped binary, same attestation
+    std::fs::write(&binary, b"\x7fELF backdoor").unwrap();
+    let swapped = provenance::digest(&binary).unwrap();
+    assert!(provenance::verify(&envelope, &swapped, &trusted).is_err());
+}
+
+#[test]
+fn tampered_and_untrusted_attestations_are_rejected() {
+    let (_dir, key, trusted) = setup();
+    let envelope = provenance::sign(&key, "node-a", &statement("abcd")).unwrap();
+
+    let mut tampered = envelope.clone();
+    tampered.payload = tampered.payload.replace("4f2c9e1", "0000000");
+    assert!(provenance::verify(&tampered, "abcd", &trusted).is_err());
+
+    let mut retyped = envelope.clone();
+    retyped.payload_type = "text/plain".into();
+    assert!(provenance::verify(&retyped, "abcd", &trusted).is_err());
+
+    let rogue_dir = tempfile::tempdir().unwrap();
+    let rogue = ProducerKey::load_or_create(&rogue_dir.path().join("rogue.key")).unwrap();
+    let forged = provenance::sign(&rogue, "node-a", &statement("abcd")).unwrap();
+    assert!(provenance::verify(&forged, "abcd", &trusted).is_err());
+
+    let unknown = provenance::sign(&key, "node-b", &statement("abcd")).unwrap();
+    assert!(provenance::verify(&unknown, "abcd", &trusted).is_err());
+}
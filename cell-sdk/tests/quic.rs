@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/quic.rs
+//! A synapse dials `cell@host:port` straight to a Membrane serving QUIC.
+#![cfg(feature = "quic")]
+
+use cell_sdk::membrane::BoxFuture;
+use cell_sdk::{Membrane, ResilientSynapse, Synapse};
+use std::time::Duration;
+
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug, Clone)]
+#[archive(check_bytes)]
+struct Ping {
+    value: u64,
+}
+
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug, Clone, PartialEq)]
+#[archive(check_bytes)]
+struct Pong {
+    value: u64,
+}
+
+fn echo(req: &ArchivedPing) -> BoxFuture<'_, anyhow::Result<Pong>> {
+    let value = req.value;
+    Box::pin(async move { Ok(Pong { value }) })
+}
+
+#[tokio::test]
+async fn synapses_dial_quic_directly() {
+    assert_eq!(
+        cell_sdk::quic::split_target("ledger@10.0.0.2:9100"),
+        Some(("ledger", "10.0.0.2:9100"))
+    );
+    assert_eq!(cell_sdk::quic::split_target("ledger"), None);
+
+    let port = std::net::UdpSocket::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    std::env::set_var("CELL_TRANSPORT", format!("quic://127.0.0.1:{}", port));
+    let server = tokio::spawn(Membrane::bind::<_, Ping, Pong>(
+        "quic-echo",
+        echo,
+        None,
+        None,
+        None,
+    ));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let target = format!("quic-echo@127.0.0.1:{}", port);
+
+    let synapse = Synapse::grow(&target).await.unwrap();
+    let resp = synapse.fire(&Ping { value: 3 }).await.unwrap().into_owned();
+    assert_eq!(rkyv::check_archived_root::<Pong>(&resp).unwrap().value, 3);
+
+    // Each synapse gets its own stream on the server
+    let resilient = ResilientSynapse::grow(&target).await.unwrap();
+    let resp = resilient
+        .fire(&Ping { value: 4 })
+        .await
+        .unwrap()
+        .into_owned();
+    assert_eq!(rkyv::check_archived_root::<Pong>(&resp).unwrap().value, 4);
+    server.abort();
+}
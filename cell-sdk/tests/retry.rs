@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/retry.rs
+//! Generated clients retry only what the cell's policy allows.
+
+use cell_sdk::error::{CellError, ErrorContext};
+use cell_sdk::retry::{bounded, run, ClientOptions, RetryPolicy};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+fn policy(idempotent: bool) -> RetryPolicy {
+    RetryPolicy {
+        idempotent,
+        max_attempts: 3,
+        backoff_ms: 1,
+    }
+}
+
+fn remote(code: CellError) -> anyhow::Error {
+    ErrorContext::new(code).with_cell("ledger").into()
+}
+
+async fn calls(policy: Option<RetryPolicy>, code: CellError) -> u32 {
+    let attempts = AtomicU32::new(0);
+    let result: anyhow::Result<()> = run(policy, || async {
+        attempts.fetch_add(1, Ordering::Relaxed);
+        Err(remote(code))
+    })
+    .await;
+    assert!(result.is_err());
+    attempts.into_inner()
+}
+
+#[tokio::test]
+async fn ingress_rejections_are_retried_for_any_method() {
+    assert_eq!(calls(Some(policy(false)), CellError::Throttled).await, 3);
+    assert_eq!(calls(Some(policy(false)), CellError::Timeout).await, 1);
+    assert_eq!(calls(Some(policy(true)), CellError::Timeout).await, 3);
+}
+
+#[tokio::test]
+async fn permanent_failures_and_missing_policies_are_not_retried() {
+    assert_eq!(calls(Some(policy(true)), CellError::NotFound).await, 1);
+    assert_eq!(calls(None, CellError::Throttled).await, 1);
+}
+
+#[tokio::test]
+async fn success_ends_the_loop() {
+    let attempts = AtomicU32::new(0);
+    let value = run(Some(policy(false)), || async {
+        match attempts.fetch_add(1, Ordering::Relaxed) {
+            0 => Err(remote(CellError::RateLimited)),
+            n => Ok(n),
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(value, 1);
+}
+
+#[test]
+fn delay_doubles_and_honours_the_cell_hint() {
+    let policy = RetryPolicy {
+        idempotent: true,
+        max_attempts: 5,
+        backoff_ms: 100,
+    };
+    let plain = remote(CellError::Timeout);
+    assert_eq!(policy.delay(1, &plain), Duration::from_millis(100));
+    assert_eq!(policy.delay(3, &plain), Duration::from_millis(400));
+
+    let hinted = ErrorContext::new(CellError::Throttled)
+        .with_retry_after(Duration::from_secs(2))
+        .into();
+    assert_eq!(policy.delay(1, &hinted), Duration::from_secs(2));
+}
+
+#[tokio::test]
+async fn bounded_calls_time_out() {
+    let hung = bounded(
+        Some(Duration::from_millis(10)),
+        std::future::pending::<anyhow::Result<()>>(),
+    );
+    let err = hung.await.unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ErrorContext>().unwrap().code,
+        CellError::Timeout
+    );
+    assert_eq!(bounded(None, async { Ok(1) }).await.unwrap(), 1);
+}
+
+#[test]
+fn client_options_override_attempts_but_not_idempotency() {
+    let advertised = Some(policy(true));
+    assert_eq!(ClientOptions::default().policy(advertised), advertised);
+
+    let options = ClientOptions {
+        retries: Some(5),
+        backoff: Some(Duration::from_millis(20)),
+        ..ClientOptions::default()
+    };
+    let tuned = options.policy(advertised).unwrap();
+    assert_eq!(
+        (tuned.max_attempts, tuned.backoff_ms, tuned.idempotent),
+        (6, 20, true)
+    );
+    assert!(!options.policy(None).unwrap().idempotent);
+}
@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/shed.rs
+//! Overload sheds low-priority methods first.
+
+use cell_sdk::error::CellError;
+use cell_sdk::shed::{admit, enable, pressure, ShedConfig, DEFAULT_PRIORITY, NEVER_SHED};
+
+#[test]
+fn sheds_from_the_lowest_priority_up() {
+    enable(ShedConfig::new(4).priority("health_report", NEVER_SHED));
+
+    let mut held: Vec<_> = (0..5)
+        .map(|_| admit("settle", NEVER_SHED).unwrap())
+        .collect();
+    assert!((pressure() - 1.25).abs() < 1e-9);
+
+    // 25% over: the bottom quarter of priorities goes first
+    let err = admit("export", 10).err().unwrap();
+    assert_eq!(err.code, CellError::Throttled);
+    assert!(err.retry_delay().unwrap() >= std::time::Duration::from_millis(50));
+    held.push(admit("transfer", DEFAULT_PRIORITY).unwrap());
+
+    held.extend((0..4).map(|_| admit("settle", NEVER_SHED).unwrap()));
+    assert!(admit("transfer", 99).is_err());
+    assert!(admit("health_report", 0).is_ok());
+
+    held.clear();
+    assert_eq!(pressure(), 0.0);
+}
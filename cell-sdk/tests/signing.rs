@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/signing.rs
+//! Replicas accept only entries a trusted producer signed for their log.
+
+use cell_sdk::signing::{ProducerKey, TrustedProducers};
+
+fn setup() -> (tempfile::TempDir, ProducerKey, TrustedProducers) {
+    let dir = tempfile::tempdir().unwrap();
+    let key = ProducerKey::load_or_create(&dir.path().join("producer.key")).unwrap();
+    key.export_public(&dir.path().join("producers"), "billing")
+        .unwrap();
+    let trusted = TrustedProducers::load_dir(&dir.path().join("producers")).unwrap();
+    (dir, key, trusted)
+}
+
+#[test]
+fn signed_entries_verify_for_their_log_only() {
+    let (_dir, key, trusted) = setup();
+    let command = key.sign("audit", "billing", 1, b"invoice #7".to_vec());
+
+    trusted.verify("audit", &command).unwrap();
+    assert!(trusted.verify("registry", &command).is_err());
+}
+
+#[test]
+fn forged_entries_are_rejected() {
+    let (_dir, key, trusted) = setup();
+
+    let mut tampered = key.sign("audit", "billing", 1, b"invoice #7".to_vec());
+    tampered.data = b"invoice #8".to_vec();
+    assert!(trusted.verify("audit", &tampered).is_err());
+
+    let mut renumbered = key.sign("audit", "billing", 1, b"invoice #7".to_vec());
+    renumbered.seq = 2;
+    assert!(trusted.verify("audit", &renumbered).is_err());
+
+    // A key nobody exported, e.g. the leader's own
+    let rogue_dir = tempfile::tempdir().unwrap();
+    let rogue = ProducerKey::load_or_create(&rogue_dir.path().join("rogue.key")).unwrap();
+    let forged = rogue.sign("audit", "billing", 1, b"invoice #7".to_vec());
+    assert!(trusted.verify("audit", &forged).is_err());
+
+    let unknown = key.sign("audit", "search", 1, b"query".to_vec());
+    assert!(trusted.verify("audit", &unknown).is_err());
+}
+
+#[test]
+fn keys_survive_a_reload() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("producer.key");
+    let first = ProducerKey::load_or_create(&path).unwrap();
+    let again = ProducerKey::load_or_create(&path).unwrap();
+    assert_eq!(first.public_key(), again.public_key());
+}
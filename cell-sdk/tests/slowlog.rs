@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/slowlog.rs
+//! Slow calls are retained with size-limited arguments.
+
+use cell_sdk::slowlog::{observe, recent, to_json};
+use std::time::Duration;
+
+#[test]
+fn slow_calls_keep_truncated_arguments() {
+    std::env::set_var("CELL_SLOW_REQUEST_MS", "100");
+    std::env::set_var("CELL_SLOW_REQUEST_ARGS", "12");
+
+    observe("balance", Duration::from_millis(5), true, || unreachable!());
+    observe("transfer", Duration::from_millis(250), false, || {
+        to_json(&("alice", "bob", 1_000_000u64))
+    });
+
+    let log = recent(10);
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].method, "transfer");
+    assert_eq!(log[0].duration_ms, 250);
+    assert!(!log[0].ok);
+    assert_eq!(log[0].args.as_deref(), Some("[\"alice\",\"bo…"));
+}
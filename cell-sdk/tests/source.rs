@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/source.rs
+//! Schema fingerprints are compared on connect, strictly unless downgraded.
+
+use cell_sdk::error::{CellError, ErrorContext};
+use cell_sdk::source::{self, SchemaCheck};
+
+#[test]
+fn matching_or_unknown_fingerprints_pass() {
+    assert!(source::check("ledger", 7, 7, SchemaCheck::Strict).is_ok());
+    assert!(source::check("ledger", 0, 7, SchemaCheck::Strict).is_ok());
+    assert!(source::check("ledger", 7, 0, SchemaCheck::Strict).is_ok());
+}
+
+#[test]
+fn strict_mismatch_fails_with_both_fingerprints() {
+    let err = source::check("ledger", 0xa, 0xb, SchemaCheck::Strict).unwrap_err();
+    let ctx = err.downcast_ref::<ErrorContext>().unwrap();
+    assert_eq!(ctx.code, CellError::ProtocolMismatch);
+    assert!(ctx.message.contains("000000000000000b"));
+    assert!(ctx.message.contains("000000000000000a"));
+    // Raised locally, so not mistaken for an answer from the cell
+    assert!(!cell_sdk::error::is_remote(&err));
+}
+
+#[test]
+fn compat_mode_connects_anyway() {
+    assert!(source::check("ledger", 0xa, 0xb, SchemaCheck::Compat).is_ok());
+}
+
+#[test]
+fn registered_fingerprint_is_served() {
+    source::register_fingerprint(42);
+    assert_eq!(source::fingerprint(), 42);
+}
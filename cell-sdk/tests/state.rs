@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/state.rs
+//! Managed state round-trips through checkpoint and restore, and starts
+//! from the cell's crash checkpoint.
+
+use cell_sdk::state::{self, CellState};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+#[derive(Default)]
+struct Counter {
+    value: u64,
+}
+
+impl CellState for Counter {
+    fn checkpoint(&self) -> Vec<u8> {
+        self.value.to_le_bytes().to_vec()
+    }
+
+    fn restore(&mut self, bytes: Vec<u8>) {
+        self.value = u64::from_le_bytes(bytes.try_into().unwrap_or_default());
+    }
+}
+
+#[tokio::test]
+async fn checkpoint_and_restore_managed_state() {
+    assert!(state::checkpoint().await.is_none());
+    assert!(state::restore(vec![]).await.is_err());
+
+    // Found by cell name under HOME, not relative to the working directory
+    let home = tempfile::tempdir().unwrap();
+    std::env::set_var("HOME", home.path());
+    let cell = &cell_sdk::identity::Identity::get().cell_name;
+    let dir = home.path().join(".cell/state").join(cell);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("checkpoint"), 7u64.to_le_bytes()).unwrap();
+
+    let counter = Arc::new(RwLock::new(Counter::default()));
+    state::manage(counter.clone()).await;
+    assert_eq!(counter.read().await.value, 7);
+
+    let snapshot = state::checkpoint().await.unwrap();
+    counter.write().await.value = 100;
+    state::restore(snapshot).await.unwrap();
+    assert_eq!(counter.read().await.value, 7);
+}
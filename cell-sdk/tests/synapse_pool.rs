@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/synapse_pool.rs
+//! A SynapsePool spreads concurrent calls over its connections.
+
+use cell_sdk::compose::Composition;
+use cell_sdk::membrane::BoxFuture;
+use cell_sdk::{Membrane, SynapsePool};
+use std::time::Duration;
+
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug, Clone)]
+#[archive(check_bytes)]
+struct Ping {
+    value: u64,
+}
+
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug, Clone, PartialEq)]
+#[archive(check_bytes)]
+struct Pong {
+    value: u64,
+}
+
+fn slow_echo(req: &ArchivedPing) -> BoxFuture<'_, anyhow::Result<Pong>> {
+    let value = req.value;
+    Box::pin(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        Ok(Pong { value: value + 1 })
+    })
+}
+
+#[tokio::test]
+async fn concurrent_calls_share_the_pool() {
+    let serve = Membrane::bind::<_, Ping, Pong>("pool-echo", slow_echo, None, None, None);
+    let _running = Composition::new().cell("pool-echo", serve).start();
+
+    let pool = SynapsePool::grow("pool-echo", 3).await.unwrap();
+    assert_eq!(pool.size(), 3);
+
+    let calls = (0..6).map(|value| {
+        let pool = pool.clone();
+        async move {
+            let resp = pool.fire(&Ping { value }).await.unwrap().into_owned();
+            rkyv::check_archived_root::<Pong>(&resp).unwrap().value
+        }
+    });
+    let values = futures::future::join_all(calls).await;
+    assert_eq!(values, [1, 2, 3, 4, 5, 6]);
+
+    assert_eq!(pool.in_flight(), 0);
+    assert_eq!(pool.metrics().await.requests_total, 6);
+}
+
+#[tokio::test]
+async fn a_pool_never_has_fewer_than_one_connection() {
+    let serve = Membrane::bind::<_, Ping, Pong>("pool-single", slow_echo, None, None, None);
+    let _running = Composition::new().cell("pool-single", serve).start();
+
+    let pool = SynapsePool::grow("pool-single", 0).await.unwrap();
+    assert_eq!(pool.size(), 1);
+}
@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/tcp.rs
+//! A Membrane told to listen on TCP serves the usual frames there.
+
+use cell_sdk::correlator::Correlator;
+use cell_sdk::membrane::BoxFuture;
+use cell_sdk::Membrane;
+use std::time::Duration;
+
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug, Clone)]
+#[archive(check_bytes)]
+struct Ping {
+    value: u64,
+}
+
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug, Clone, PartialEq)]
+#[archive(check_bytes)]
+struct Pong {
+    value: u64,
+}
+
+fn echo(req: &ArchivedPing) -> BoxFuture<'_, anyhow::Result<Pong>> {
+    let value = req.value;
+    Box::pin(async move { Ok(Pong { value }) })
+}
+
+#[tokio::test]
+async fn membrane_serves_over_tcp() {
+    std::env::set_var("CELL_TRANSPORT", "quic://127.0.0.1:9100");
+    assert!(cell_sdk::tcp::listen_addr().is_err());
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let addr = format!("127.0.0.1:{}", port);
+    std::env::set_var("CELL_TRANSPORT", format!("tcp://{}", addr));
+    assert_eq!(cell_sdk::tcp::listen_addr().unwrap(), Some(addr.clone()));
+
+    let server = tokio::spawn(Membrane::bind::<_, Ping, Pong>(
+        "tcp-echo", echo, None, None, None,
+    ));
+    let stream = loop {
+        match cell_sdk::tcp::connect(&addr).await {
+            Ok(stream) => break stream,
+            Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+        }
+    };
+
+    let conn = Correlator::new(stream, 0);
+    let req = rkyv::to_bytes::<_, 256>(&Ping { value: 7 }).unwrap();
+    let resp = conn
+        .send(cell_sdk::channel::APP, &req, Duration::from_secs(1))
+        .await
+        .unwrap()
+        .into_owned();
+    assert_eq!(rkyv::check_archived_root::<Pong>(&resp).unwrap().value, 7);
+    server.abort();
+}
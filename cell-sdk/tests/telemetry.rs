@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/telemetry.rs
+//! Batched events reach the target in order, whatever the overflow policy.
+
+use cell_sdk::telemetry::{BatchConfig, Batcher, Overflow};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn collector(
+    config: BatchConfig,
+    online: Arc<AtomicBool>,
+) -> (Batcher<u64>, Arc<Mutex<Vec<Vec<u64>>>>) {
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let sink = batches.clone();
+    let batcher = Batcher::spawn(config, move |batch| {
+        let sink = sink.clone();
+        let online = online.load(Ordering::Relaxed);
+        async move {
+            anyhow::ensure!(online, "target unreachable");
+            sink.lock().unwrap().push(batch);
+            Ok(())
+        }
+    });
+    (batcher, batches)
+}
+
+#[tokio::test]
+async fn full_batch_flushes_without_waiting_for_the_delay() {
+    let config = BatchConfig {
+        max_batch: 3,
+        max_delay: Duration::from_secs(3600),
+        ..BatchConfig::default()
+    };
+    let (events, batches) = collector(config, Arc::new(AtomicBool::new(true)));
+    for i in 0..3 {
+        events.push(i).await;
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(*batches.lock().unwrap(), [vec![0, 1, 2]]);
+}
+
+#[tokio::test]
+async fn drop_oldest_keeps_the_newest_events() {
+    let online = Arc::new(AtomicBool::new(false));
+    let config = BatchConfig {
+        max_batch: 10,
+        max_delay: Duration::from_secs(3600),
+        capacity: 2,
+        overflow: Overflow::DropOldest,
+    };
+    let (events, batches) = collector(config, online.clone());
+    for i in 0..4 {
+        events.push(i).await;
+    }
+    assert!(events.flush().await.is_err());
+
+    online.store(true, Ordering::Relaxed);
+    events.flush().await.unwrap();
+    assert_eq!(*batches.lock().unwrap(), [vec![2, 3]]);
+    let stats = events.stats();
+    assert_eq!((stats.flushed, stats.dropped), (2, 2));
+}
+
+#[tokio::test]
+async fn spilled_events_follow_the_buffer() {
+    let dir = tempfile::tempdir().unwrap();
+    let online = Arc::new(AtomicBool::new(false));
+    let config = BatchConfig {
+        max_batch: 10,
+        max_delay: Duration::from_secs(3600),
+        capacity: 2,
+        overflow: Overflow::SpillToDisk(dir.path().join("spill")),
+    };
+    let (events, batches) = collector(config, online.clone());
+    for i in 0..5 {
+        events.push(i).await;
+    }
+    assert_eq!(events.stats().spilled, 3);
+
+    online.store(true, Ordering::Relaxed);
+    events.flush().await.unwrap();
+    assert_eq!(*batches.lock().unwrap(), [vec![0, 1], vec![2, 3, 4]]);
+    assert!(!dir.path().join("spill").exists());
+}
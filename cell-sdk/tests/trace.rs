@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/trace.rs
+//! A call's trace continues in the handler it reaches, in another cell.
+
+use cell_sdk::compose::{self, Composition};
+use cell_sdk::correlator::Correlator;
+use cell_sdk::membrane::BoxFuture;
+use cell_sdk::{channel, context, trace, Membrane};
+use std::time::Duration;
+
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug)]
+#[archive(check_bytes)]
+enum PingProtocol {
+    Ping,
+}
+
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug)]
+#[archive(check_bytes)]
+enum PingResponse {
+    Ping { trace_id: String },
+}
+
+fn ping(_req: &ArchivedPingProtocol) -> BoxFuture<'_, anyhow::Result<PingResponse>> {
+    Box::pin(async move {
+        Ok(PingResponse::Ping {
+            trace_id: context::current().trace_id(),
+        })
+    })
+}
+
+async fn connect(name: &'static str) -> (compose::Running, Correlator) {
+    let serve = Membrane::bind::<_, PingProtocol, PingResponse>(name, ping, None, None, None);
+    let running = Composition::new().cell(name, serve).start();
+    (running, Correlator::new(compose::connect(name).unwrap(), 0))
+}
+
+async fn send(conn: &Correlator) -> anyhow::Result<String> {
+    let req = rkyv::to_bytes::<_, 256>(&PingProtocol::Ping).unwrap();
+    let resp = conn
+        .send(channel::APP, &req, Duration::from_secs(1))
+        .await?
+        .into_owned();
+    let ArchivedPingResponse::Ping { trace_id } =
+        rkyv::check_archived_root::<PingResponse>(&resp).unwrap();
+    Ok(trace_id.to_string())
+}
+
+#[tokio::test]
+async fn handlers_continue_the_callers_trace() {
+    let (_running, conn) = connect("trace-ping").await;
+
+    let trace_id = trace::call("trace-ping", send(&conn)).await.unwrap();
+    let spans = trace::spans(&trace_id);
+    let call = spans
+        .iter()
+        .find(|s| s.name == "cell.call trace-ping")
+        .expect("call span");
+    let request = spans
+        .iter()
+        .find(|s| s.name == trace::REQUEST_SPAN)
+        .expect("request span");
+
+    assert_eq!(call.parent_id, None);
+    assert_eq!(request.parent_id.as_ref(), Some(&call.span_id));
+    assert_eq!(request.cell, "trace-ping");
+    assert!(request.ok && call.ok);
+}
+
+#[tokio::test]
+async fn untraced_requests_start_a_trace() {
+    let (_running, conn) = connect("trace-root").await;
+
+    let first = send(&conn).await.unwrap();
+    let second = send(&conn).await.unwrap();
+    assert_ne!(first, second);
+    assert_eq!(trace::spans(&first)[0].parent_id, None);
+}
@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: MIT
+// cell-sdk/tests/watchdog.rs
+//! Registered probes decide the health the cell reports.
+
+use cell_sdk::watchdog::{self, Health};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn failing_probe_degrades_until_it_passes() {
+    let writable = Arc::new(AtomicBool::new(true));
+    watchdog::register("wal-writable", {
+        let writable = writable.clone();
+        move || {
+            let ok = writable.load(Ordering::Relaxed);
+            async move {
+                anyhow::ensure!(ok, "read-only filesystem");
+                Ok(())
+            }
+        }
+    });
+
+    assert_eq!(watchdog::report().await.health, Health::Healthy);
+
+    writable.store(false, Ordering::Relaxed);
+    let report = watchdog::report().await;
+    assert_eq!(report.health, Health::Degraded);
+    let failing: Vec<_> = report.failing().collect();
+    assert_eq!(failing[0].name, "wal-writable");
+    assert_eq!(failing[0].detail, "read-only filesystem");
+
+    writable.store(true, Ordering::Relaxed);
+    assert_eq!(watchdog::report().await.health, Health::Healthy);
+    assert!(!watchdog::is_draining());
+}
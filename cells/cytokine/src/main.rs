@@ -0,0 +1,124 @@
+// SPDX-License-Identifier: MIT
+// cells/cytokine/src/main.rs
+//! The Cytokine Broker - topic broadcast between cells
+//!
+//! Keeps the last `CELL_CYTOKINE_RETAIN` payloads (default 1024) of every
+//! topic in memory and answers long polls from subscribers. Payloads are
+//! opaque rkyv bytes; see `cell_sdk::cytokine` for the emitting and
+//! subscribing side. Nothing is persisted: after a restart, subscribers
+//! carry on with whatever is emitted next.
+
+use anyhow::Result;
+use cell_sdk::cytokine::{
+    ArchivedCytokineRequest, Batch, CytokineRequest, CytokineResponse, TopicLog, BROKER,
+    DEFAULT_RETAIN, MAX_BATCH, MAX_WAIT_MS,
+};
+use cell_sdk::membrane::BoxFuture;
+use cell_sdk::rkyv::Deserialize;
+use cell_sdk::Membrane;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+use tokio::time::{timeout_at, Duration, Instant};
+use tracing::info;
+
+struct Topic {
+    log: TopicLog,
+    /// Bumped on every emission to wake waiting polls
+    emitted: watch::Sender<u64>,
+}
+
+struct Broker {
+    retain: usize,
+    topics: Mutex<HashMap<String, Topic>>,
+}
+
+impl Broker {
+    fn new(retain: usize) -> Self {
+        Self {
+            retain,
+            topics: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn with_topic<T>(&self, name: String, f: impl FnOnce(&mut Topic) -> T) -> T {
+        let mut topics = self.topics.lock().unwrap();
+        let topic = topics.entry(name).or_insert_with(|| Topic {
+            log: TopicLog::new(self.retain),
+            emitted: watch::channel(0).0,
+        });
+        f(topic)
+    }
+
+    fn emit(&self, topic: String, payload: Vec<u8>) -> u64 {
+        self.with_topic(topic, |t| {
+            let seq = t.log.push(payload);
+            t.emitted.send_replace(t.log.next_seq());
+            seq
+        })
+    }
+
+    async fn poll(&self, topic: String, mut from: Option<u64>, wait_ms: u64) -> Batch {
+        let deadline = Instant::now() + Duration::from_millis(wait_ms.min(MAX_WAIT_MS));
+        loop {
+            // Subscribed under the lock, so an emission right after it still wakes us
+            let (batch, mut emitted) = self.with_topic(topic.clone(), |t| {
+                (t.log.read(from, MAX_BATCH), t.emitted.subscribe())
+            });
+            if !batch.payloads.is_empty() || batch.missed > 0 || Instant::now() >= deadline {
+                return batch;
+            }
+            from = Some(batch.next);
+            if timeout_at(deadline, emitted.changed()).await.is_err() {
+                return Batch {
+                    next: batch.next,
+                    ..Batch::default()
+                };
+            }
+        }
+    }
+
+    async fn handle(&self, req: CytokineRequest) -> CytokineResponse {
+        match req {
+            CytokineRequest::Emit { topic, payload } => CytokineResponse::Emitted {
+                seq: self.emit(topic, payload),
+            },
+            CytokineRequest::Poll {
+                topic,
+                from,
+                wait_ms,
+            } => CytokineResponse::Batch(self.poll(topic, from, wait_ms).await),
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_target(false)
+        .with_level(true)
+        .init();
+
+    let retain = std::env::var("CELL_CYTOKINE_RETAIN")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_RETAIN);
+    info!("[Cytokine] Keeping {} payloads per topic", retain);
+    let broker = Arc::new(Broker::new(retain));
+
+    Membrane::bind::<_, CytokineRequest, CytokineResponse>(
+        BROKER,
+        move |req: &ArchivedCytokineRequest| -> BoxFuture<'_, Result<CytokineResponse>> {
+            let broker = broker.clone();
+            let req: Result<CytokineRequest, _> = req.deserialize(&mut cell_sdk::rkyv::Infallible);
+            Box::pin(async move {
+                let req = req?;
+                Ok(broker.handle(req).await)
+            })
+        },
+        None,
+        None,
+        None,
+    )
+    .await
+}
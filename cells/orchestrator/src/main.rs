@@ -10,8 +10,12 @@
 //! - Automatically starts cells in dependency order
 //! - Monitors cell health and restarts failed cells
 //! - Provides cluster-wide status and control
+//! - Caches built binaries in the artifact store (signed, per triple + source hash), where peer hypervisors fetch them
+//! - Installs prebuilt `.spore` bundles produced by `cell pack`
 
 use cell_sdk::*;
+use cell_build::artifact::{build_release, host_triple, ArtifactKey, ArtifactStore, SigningKey};
+use cell_build::spore::Spore;
 use anyhow::{Context, Result};
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
@@ -32,6 +36,8 @@ struct ManagedCell {
     status: CellStatus,
     last_started: Option<std::time::Instant>,
     restart_count: u32,
+    /// Prebuilt binary (installed from a spore); built from source when absent
+    binary: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -65,6 +71,8 @@ struct OrchestratorService {
     cells: Arc<RwLock<HashMap<String, ManagedCell>>>,
     workspace_root: PathBuf,
     registry_dir: PathBuf,
+    artifacts: Arc<ArtifactStore>,
+    signing_key: Arc<SigningKey>,
 }
 
 #[handler]
@@ -244,6 +252,48 @@ impl OrchestratorService {
         
         Ok(stopped)
     }
+
+    /// Install a `.spore` bundle and manage it like a discovered cell
+    async fn install_spore(&self, path: String) -> Result<String> {
+        let spore = Spore::open(Path::new(&path), &self.signing_key)?;
+        let name = spore.manifest.name.clone();
+        if spore.manifest.triple != host_triple() {
+            anyhow::bail!(
+                "Spore '{}' is built for {}, this node is {}",
+                name, spore.manifest.triple, host_triple()
+            );
+        }
+        
+        let home = dirs::home_dir().context("No HOME directory")?;
+        let dest = home.join(".cell/spores").join(&name);
+        spore.unpack(&dest)?;
+        
+        // Seed the artifact store so peers can fetch this binary too
+        let key = spore.artifact_key();
+        let binary = match self.artifacts.lookup(&key) {
+            Some(bin) => bin,
+            None => self.artifacts.insert(&key, &dest.join(&name), &self.signing_key)?,
+        };
+        
+        let manifest = self.parse_manifest(&dest.join("Cell.toml")).await.unwrap_or_default();
+        let mut cells = self.cells.write().await;
+        cells.insert(name.clone(), ManagedCell {
+            name: name.clone(),
+            path: dest.clone(),
+            process: None,
+            manifest: CellManifest { name: name.clone(), version: spore.manifest.version.clone(), ..manifest },
+            status: CellStatus::Stopped,
+            last_started: None,
+            restart_count: 0,
+            binary: Some(binary),
+        });
+        drop(cells);
+        
+        self.register_in_registry(&name, &dest).await?;
+        
+        info!("🍄 Installed spore {} v{}", name, spore.manifest.version);
+        Ok(format!("Installed '{}' v{}", name, spore.manifest.version))
+    }
 }
 
 impl OrchestratorService {
@@ -278,6 +328,7 @@ impl OrchestratorService {
                                 status: CellStatus::Stopped,
                                 last_started: None,
                                 restart_count: 0,
+                                binary: None,
                             });
                             
                             // Register in global registry for discovery
@@ -358,17 +409,20 @@ impl OrchestratorService {
     
     /// Spawn a cell process
     async fn spawn_cell_process(&self, cell: &mut ManagedCell) -> Result<Child> {
-        let manifest_path = cell.path.join("Cargo.toml");
-        
-        // Check if it's a Rust project
-        if !manifest_path.exists() {
-            return Err(anyhow::anyhow!("No Cargo.toml found in {:?}", cell.path));
-        }
-        
         info!("🚀 Starting cell: {} from {:?}", cell.name, cell.path);
         
-        let child = Command::new("cargo")
-            .args(["run", "--release", "-p", &cell.name])
+        let binary = match &cell.binary {
+            Some(bin) => bin.clone(),
+            None => {
+                // Check if it's a Rust project
+                if !cell.path.join("Cargo.toml").exists() {
+                    return Err(anyhow::anyhow!("No Cargo.toml found in {:?}", cell.path));
+                }
+                self.resolve_binary(&cell.name, &cell.path).await?
+            }
+        };
+        
+        let child = Command::new(&binary)
             .current_dir(&cell.path)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
@@ -402,6 +456,24 @@ impl OrchestratorService {
         Err(anyhow::anyhow!("Cell {} failed to start (no socket found)", cell.name))
     }
     
+    /// Locate a binary for the cell: local artifact store, then cargo
+    async fn resolve_binary(&self, name: &str, path: &Path) -> Result<PathBuf> {
+        let key = ArtifactKey::for_host(name, path)?;
+        
+        if let Some(bin) = self.artifacts.lookup(&key) {
+            info!("📦 Using cached artifact for {} ({})", name, &key.source_hash[..12]);
+            return Ok(bin);
+        }
+        
+        info!("🔨 Building {} (no artifact for {})", name, key.triple);
+        let built = {
+            let name = name.to_string();
+            let path = path.to_path_buf();
+            tokio::task::spawn_blocking(move || build_release(&name, &path)).await??
+        };
+        self.artifacts.insert(&key, &built, &self.signing_key)
+    }
+    
     /// Health check loop - monitors all cells
     async fn health_check_loop(&self) {
         let mut interval = interval(Duration::from_secs(5));
@@ -490,6 +562,8 @@ async fn main() -> Result<()> {
         cells: Arc::new(RwLock::new(HashMap::new())),
         workspace_root: workspace_root.clone(),
         registry_dir,
+        artifacts: Arc::new(ArtifactStore::open_default()?),
+        signing_key: Arc::new(SigningKey::load_or_create(&SigningKey::default_path()?)?),
     };
     
     // Scan for cells
@@ -0,0 +1,1216 @@
+// SPDX-License-Identifier: MIT
+// cell-cli/src/commands.rs
+//! Mesh tooling commands of the `cell` binary: packing, schemas, deployment,
+//! operations and node maintenance.
+
+use anyhow::{Context, Result};
+use cell_build::artifact::{build_release, SigningKey};
+use cell_build::daemon::{ServiceManager, ServiceSpec};
+use cell_build::kernel::{KernelBin, KERNEL_CELLS, KERNEL_VERSION};
+use cell_build::load_and_flatten_source;
+use cell_build::schema::{self, Schema};
+use cell_build::spore::Spore;
+use cell_sdk::auth::AdminKey;
+use cell_sdk::cell_remote;
+use clap::Subcommand;
+use std::path::PathBuf;
+
+cell_remote!(Audit = "audit", methods = [log]);
+cell_remote!(Nucleus = "nucleus");
+cell_remote!(Registry = "registry", methods = [push, promote, channel_history, audit]);
+cell_remote!(StateManager = "state-manager", methods = [deployments]);
+cell_remote!(SwapCoordinator = "swap-coordinator");
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Build a cell in release mode and bundle it into a signed `.spore`
+    Pack {
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Output file (defaults to `<name>-<version>-<triple>.spore`)
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+        /// Signing key (defaults to the mesh artifact key)
+        #[arg(long, env = "CELL_ARTIFACT_KEY")]
+        key: Option<PathBuf>,
+    },
+    /// Send an encoded request to a cell, optionally as another principal
+    Call {
+        cell: String,
+        /// File holding the archived request (defaults to stdin)
+        request: Option<PathBuf>,
+        /// Principal to impersonate (requires the mesh admin key)
+        #[arg(long = "as")]
+        as_principal: Option<String>,
+        /// Organism of the impersonated principal (defaults to this one's)
+        #[arg(long)]
+        organism: Option<String>,
+        /// Justification recorded in the audit log
+        #[arg(long, default_value = "")]
+        reason: String,
+        /// Lifetime of the impersonation grant in seconds
+        #[arg(long, default_value_t = 300)]
+        ttl: u64,
+    },
+    /// Capture a CPU or heap profile from a running cell built with
+    /// cell-sdk's `profile` or `heap-profile` feature
+    Profile {
+        cell: String,
+        /// Length of the capture window
+        #[arg(long, default_value_t = 30)]
+        seconds: u32,
+        /// Sample live allocations instead of CPU stacks
+        #[arg(long)]
+        heap: bool,
+        /// CPU sampling rate in Hz
+        #[arg(long, default_value_t = cell_sdk::profile::DEFAULT_FREQUENCY)]
+        frequency: u32,
+        /// Write a flamegraph SVG here
+        #[arg(long, conflicts_with = "out")]
+        flamegraph: Option<PathBuf>,
+        /// Write the pprof protobuf here (defaults to `<cell>-<kind>.pb`)
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+    /// Show a running cell's connections, tasks, SHM rings and memory
+    Inspect { cell: String },
+    /// Show SLO compliance and error budgets of a running cell
+    Slo { cell: String },
+    /// Show per-method call counts and latencies of a running cell
+    Metrics {
+        cell: String,
+        /// Print the Prometheus text format instead
+        #[arg(long)]
+        prometheus: bool,
+    },
+    /// Change the log level of a running cell without redeploying it
+    LogLevel {
+        cell: String,
+        /// `trace`, `debug`, `info`, `warn`, `error` or `off`
+        level: String,
+        /// Module path to apply it to (defaults to the whole cell)
+        #[arg(long)]
+        target: Option<String>,
+    },
+    /// Add methods to a running cell from plugins, and list what was added
+    Plugin {
+        #[command(subcommand)]
+        action: PluginAction,
+    },
+    /// Show drift between the applied mesh manifest and what is running
+    Diff {
+        /// Correct drift automatically where possible
+        #[arg(long)]
+        reconcile: bool,
+    },
+    /// Inspect cell schemas
+    Schema {
+        #[command(subcommand)]
+        action: SchemaAction,
+    },
+    /// Generate cell code from foreign interface definitions
+    Import {
+        #[command(subcommand)]
+        kind: ImportKind,
+    },
+    /// Rolling operations on running cells
+    Rollout {
+        #[command(subcommand)]
+        action: RolloutAction,
+    },
+    /// Swap a cell back to an earlier deployed version
+    ///
+    /// Fetches the version's archived binary (or rebuilds it from the
+    /// registry) and hands over to it like a blue/green swap, after checking
+    /// its schema against the running one.
+    Rollback {
+        cell: String,
+        /// Version hash (or prefix) to go back to; defaults to the previous one
+        #[arg(long)]
+        to: Option<String>,
+        /// Roll back even if the old schema breaks today's clients
+        #[arg(long)]
+        force: bool,
+    },
+    /// Report known-vulnerable dependencies and licenses of a cell
+    ///
+    /// Rescans the dependency inventory the registry stored when the running
+    /// version was published against today's RustSec advisories. Fails if any
+    /// dependency is affected.
+    Audit {
+        cell: String,
+        /// Published version to audit instead of the one running
+        #[arg(long)]
+        version: Option<String>,
+    },
+    /// Move registry release channels (stable, beta, nightly) between versions
+    Channel {
+        #[command(subcommand)]
+        action: ChannelAction,
+    },
+    /// Take nodes out of service for maintenance and bring them back
+    Node {
+        #[command(subcommand)]
+        action: NodeAction,
+    },
+    /// Bring up the kernel through the control plane and report the boot timeline
+    Up,
+    /// Install kernel cell binaries into `~/.cell/bin`
+    Install {
+        /// Kernel cells to install (defaults to the whole kernel)
+        cells: Vec<String>,
+        /// Workspace to build from
+        #[arg(long, default_value = ".")]
+        workspace: PathBuf,
+        /// Install already-built binaries from this directory instead of building
+        #[arg(long)]
+        from_dir: Option<PathBuf>,
+    },
+    /// Install a kernel daemon (mycelium, hypervisor) as a system systemd/launchd service (needs root)
+    InstallService {
+        #[arg(default_value = "mycelium")]
+        daemon: String,
+        /// Binary to run (defaults to `~/.cell/bin/<daemon>`)
+        #[arg(long)]
+        binary: Option<PathBuf>,
+        /// Print the generated unit files instead of installing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SchemaAction {
+    /// Compare two schemas and classify each change as breaking or not
+    ///
+    /// Each side is a cell directory, a `.rs` source, a `.lock.json` lockfile,
+    /// a `.spore`, `registry:<cell>` or `running:<cell>`.
+    Diff {
+        base: String,
+        new: String,
+        /// Signing key for `.spore` artifacts (defaults to the mesh artifact key)
+        #[arg(long, env = "CELL_ARTIFACT_KEY")]
+        key: Option<PathBuf>,
+    },
+    /// Generate a client for a cell from its schema
+    ///
+    /// `schema` is read like a side of `cell schema diff`. Targets are
+    /// `typescript-react` (typed React hooks over the HTTP gateway),
+    /// `typescript-node` (a class over the native protocol, via `cell-node`)
+    /// and `python` (an async client over the native protocol, via `cell_py`).
+    Codegen {
+        schema: String,
+        #[arg(long, default_value = "typescript-react")]
+        target: String,
+        /// Cell name the client calls (defaults to the name in `schema`)
+        #[arg(long)]
+        cell: Option<String>,
+        /// Write the generated source here instead of stdout
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+        /// Signing key for `.spore` artifacts (defaults to the mesh artifact key)
+        #[arg(long, env = "CELL_ARTIFACT_KEY")]
+        key: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ImportKind {
+    /// Turn protobuf messages into proteins and services into handler skeletons
+    Proto {
+        file: PathBuf,
+        /// Write the generated source here instead of stdout
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum PluginAction {
+    /// Load a plugin library on the cell's host and register its methods
+    Load {
+        cell: String,
+        /// Shared library, as seen from the cell's host
+        path: String,
+    },
+    /// List the methods a running cell registered after it started
+    List { cell: String },
+}
+
+#[derive(Subcommand)]
+enum RolloutAction {
+    /// Restart instances one at a time, waiting for each to become ready
+    Restart {
+        cell: String,
+        /// Instances that must stay available (defaults to the manifest's `min_available`)
+        #[arg(long)]
+        min_available: Option<u32>,
+        /// Proceed even if the cell has spent an SLO error budget
+        #[arg(long)]
+        ignore_budget: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ChannelAction {
+    /// Point nightly or beta at a published version
+    Push {
+        cell: String,
+        version: String,
+        #[arg(long, default_value = "nightly")]
+        channel: String,
+    },
+    /// Move the version on one channel up to the next (nightly → beta → stable)
+    Promote {
+        cell: String,
+        #[arg(long)]
+        from: String,
+        #[arg(long)]
+        to: String,
+    },
+    /// Every move of the cell's channels, oldest first
+    History { cell: String },
+}
+
+#[derive(Subcommand)]
+enum NodeAction {
+    /// Stop scheduling new cells onto the node; running cells stay
+    Cordon { node_id: u64 },
+    /// Allow scheduling onto the node again
+    Uncordon { node_id: u64 },
+    /// Cordon the node, then move its cells elsewhere (or stop those no other node can run)
+    Drain { node_id: u64 },
+    /// Upgrade nodes one at a time, keeping quorum-bearing cells at a majority
+    ///
+    /// Each node is drained, upgraded by `--hook` (or by you, confirming with
+    /// `cell node upgraded <id>`), and uncordoned once it rejoins with the
+    /// same identity.
+    Upgrade {
+        #[arg(required = true)]
+        nodes: Vec<u64>,
+        /// Command that upgrades and reboots a node (sees `CELL_NODE_ID`, `CELL_NODE_ADDRESS`)
+        #[arg(long)]
+        hook: Option<String>,
+        /// Seconds to wait for quorum, migrations and the node rejoining
+        #[arg(long, default_value_t = 600)]
+        timeout: u64,
+    },
+    /// Confirm that a drained node has been upgraded
+    Upgraded { node_id: u64 },
+}
+
+pub async fn run(command: Commands) -> Result<()> {
+    match command {
+        Commands::Call {
+            cell,
+            request,
+            as_principal,
+            organism,
+            reason,
+            ttl,
+        } => cmd_call(cell, request, as_principal, organism, reason, ttl).await,
+        Commands::Diff { reconcile } => cmd_diff(reconcile).await,
+        Commands::Inspect { cell } => cmd_inspect(cell).await,
+        Commands::Slo { cell } => cmd_slo(cell).await,
+        Commands::Metrics { cell, prometheus } => cmd_metrics(cell, prometheus).await,
+        Commands::LogLevel {
+            cell,
+            level,
+            target,
+        } => cmd_log_level(cell, level, target).await,
+        Commands::Profile {
+            cell,
+            seconds,
+            heap,
+            frequency,
+            flamegraph,
+            out,
+        } => cmd_profile(cell, seconds, heap, frequency, flamegraph, out).await,
+        Commands::Plugin { action } => match action {
+            PluginAction::Load { cell, path } => cmd_plugin_load(cell, path).await,
+            PluginAction::List { cell } => cmd_plugin_list(cell).await,
+        },
+        Commands::Pack { path, out, key } => cmd_pack(path, out, key),
+        Commands::Schema { action } => match action {
+            SchemaAction::Diff { base, new, key } => cmd_schema_diff(base, new, key).await,
+            SchemaAction::Codegen {
+                schema,
+                target,
+                cell,
+                out,
+                key,
+            } => cmd_schema_codegen(schema, target, cell, out, key).await,
+        },
+        Commands::Import { kind } => match kind {
+            ImportKind::Proto { file, out } => cmd_import_proto(file, out),
+        },
+        Commands::Rollout { action } => match action {
+            RolloutAction::Restart {
+                cell,
+                min_available,
+                ignore_budget,
+            } => cmd_rollout_restart(cell, min_available, ignore_budget).await,
+        },
+        Commands::Rollback { cell, to, force } => cmd_rollback(cell, to, force).await,
+        Commands::Audit { cell, version } => cmd_audit(cell, version).await,
+        Commands::Channel { action } => match action {
+            ChannelAction::Push {
+                cell,
+                version,
+                channel,
+            } => cmd_channel_push(cell, version, channel).await,
+            ChannelAction::Promote { cell, from, to } => {
+                cmd_channel_promote(cell, from, to).await
+            }
+            ChannelAction::History { cell } => cmd_channel_history(cell).await,
+        },
+        Commands::Node { action } => match action {
+            NodeAction::Cordon { node_id } => cmd_node_cordon(node_id, true).await,
+            NodeAction::Uncordon { node_id } => cmd_node_cordon(node_id, false).await,
+            NodeAction::Drain { node_id } => cmd_node_drain(node_id).await,
+            NodeAction::Upgrade {
+                nodes,
+                hook,
+                timeout,
+            } => cmd_node_upgrade(nodes, hook, timeout).await,
+            NodeAction::Upgraded { node_id } => cmd_node_upgraded(node_id).await,
+        },
+        Commands::Up => cmd_up(),
+        Commands::Install {
+            cells,
+            workspace,
+            from_dir,
+        } => cmd_install(cells, workspace, from_dir),
+        Commands::InstallService {
+            daemon,
+            binary,
+            dry_run,
+        } => cmd_install_service(daemon, binary, dry_run),
+    }
+}
+
+fn load_signing_key(key: Option<PathBuf>) -> Result<SigningKey> {
+    let path = match key {
+        Some(p) => p,
+        None => SigningKey::default_path()?,
+    };
+    SigningKey::load_or_create(&path)
+}
+
+fn cmd_pack(path: PathBuf, out: Option<PathBuf>, key: Option<PathBuf>) -> Result<()> {
+    let abs_path = std::fs::canonicalize(&path).context("Invalid cell path")?;
+    let signer = load_signing_key(key)?;
+
+    println!("🧬 Packing {:?}", abs_path);
+    let spore = Spore::pack(&abs_path)?;
+    let m = &spore.manifest;
+
+    let out = out.unwrap_or_else(|| {
+        PathBuf::from(format!("{}-{}-{}.spore", m.name, m.version, m.triple))
+    });
+    spore.write(&out, &signer)?;
+
+    println!("🍄 Spore ready: {:?}", out);
+    println!("   ├─ cell:   {} v{}", m.name, m.version);
+    println!("   ├─ triple: {}", m.triple);
+    println!("   ├─ binary: {} bytes", m.binary_len);
+    println!("   └─ source: {}", &m.source_hash[..12]);
+    Ok(())
+}
+
+async fn cmd_schema_diff(base: String, new: String, key: Option<PathBuf>) -> Result<()> {
+    let base_schema = load_schema(&base, key.clone()).await?;
+    let new_schema = load_schema(&new, key).await?;
+
+    let changes = new_schema.diff(&base_schema);
+    if changes.is_empty() {
+        println!("✅ {} and {} are identical", base, new);
+        return Ok(());
+    }
+
+    println!("🧬 {} -> {}", base, new);
+    for change in &changes {
+        println!("   {}", change);
+    }
+    let breaking = changes.iter().filter(|c| c.breaking).count();
+    println!("\n{} change(s), {} breaking", changes.len(), breaking);
+    if breaking > 0 {
+        anyhow::bail!("{} is not wire compatible with {}", new, base);
+    }
+    Ok(())
+}
+
+async fn cmd_schema_codegen(
+    spec: String,
+    target: String,
+    cell: Option<String>,
+    out: Option<PathBuf>,
+    key: Option<PathBuf>,
+) -> Result<()> {
+    let schema = load_schema(&spec, key).await?;
+    let cell = cell.unwrap_or_else(|| cell_name_of(&spec));
+    let code = match target.as_str() {
+        "typescript-react" => cell_build::typescript::react_hooks(&cell, &schema),
+        "typescript-node" => cell_build::typescript::node_client(&cell, &schema),
+        "python" => cell_build::python::client(&cell, &schema),
+        other => anyhow::bail!(
+            "Unknown codegen target '{}' (expected typescript-react, typescript-node or python)",
+            other
+        ),
+    };
+    match out {
+        Some(out) => {
+            std::fs::write(&out, code)?;
+            println!("🧬 Wrote {:?}", out);
+        }
+        None => print!("{}", code),
+    }
+    Ok(())
+}
+
+/// The cell a schema spec names: `running:x` and `registry:x` name `x`, a
+/// cell directory its own name, a file its stem.
+fn cell_name_of(spec: &str) -> String {
+    if let Some(cell) = spec
+        .strip_prefix("running:")
+        .or_else(|| spec.strip_prefix("registry:"))
+    {
+        return cell.to_string();
+    }
+    let path = std::path::Path::new(spec);
+    let name = if path.is_dir() {
+        path.canonicalize()
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_owned()))
+    } else {
+        path.file_stem().map(|n| n.to_owned())
+    };
+    name.map(|n| n.to_string_lossy().trim_end_matches(".lock").to_string())
+        .unwrap_or_else(|| spec.to_string())
+}
+
+fn cmd_import_proto(file: PathBuf, out: Option<PathBuf>) -> Result<()> {
+    let src =
+        std::fs::read_to_string(&file).with_context(|| format!("Failed to read {:?}", file))?;
+    let code = cell_build::proto::import(&src)
+        .with_context(|| format!("Failed to import {:?}", file))?;
+    match out {
+        Some(out) => {
+            std::fs::write(&out, code)?;
+            println!("🧬 Wrote {:?}", out);
+        }
+        None => print!("{}", code),
+    }
+    Ok(())
+}
+
+async fn load_schema(spec: &str, key: Option<PathBuf>) -> Result<Schema> {
+    if let Some(cell) = spec.strip_prefix("running:") {
+        let source = cell_sdk::source::fetch(cell)
+            .await
+            .with_context(|| format!("Could not read the schema of running cell '{}'", cell))?;
+        return Schema::from_source(&source);
+    }
+    let path = match spec.strip_prefix("registry:") {
+        Some(cell) => schema::registry_source(cell)
+            .with_context(|| format!("'{}' is not installed in the registry", cell))?,
+        None => PathBuf::from(spec),
+    };
+
+    let name = path.to_string_lossy();
+    if path.is_dir() {
+        Ok(Schema::from_file(&load_and_flatten_source(&path.join("src/main.rs"))?))
+    } else if name.ends_with(".json") {
+        Schema::load(&path)
+    } else if name.ends_with(".spore") {
+        let spore = Spore::open(&path, &load_signing_key(key)?)?;
+        Schema::from_source(&spore.schema)
+    } else {
+        Ok(Schema::from_file(&load_and_flatten_source(&path)?))
+    }
+}
+
+async fn cmd_call(
+    cell: String,
+    request: Option<PathBuf>,
+    as_principal: Option<String>,
+    organism: Option<String>,
+    reason: String,
+    ttl: u64,
+) -> Result<()> {
+    use cell_sdk::auth::AuthResponse;
+    use std::io::{Read, Write};
+
+    let payload = match request {
+        Some(path) => std::fs::read(&path).with_context(|| format!("Failed to read {:?}", path))?,
+        None => {
+            let mut buf = Vec::new();
+            std::io::stdin().read_to_end(&mut buf)?;
+            buf
+        }
+    };
+
+    let synapse = cell_sdk::Synapse::grow(&cell)
+        .await
+        .with_context(|| format!("'{}' not reachable", cell))?;
+
+    if let Some(principal) = as_principal {
+        let key = AdminKey::load_default()?;
+        let operator = std::env::var("USER").unwrap_or_else(|_| "unknown".to_string());
+        let organism =
+            organism.unwrap_or_else(|| cell_sdk::identity::Identity::get().organism.clone());
+        let grant = key.issue(&operator, &cell, &principal, &organism, &reason, ttl);
+
+        let grant_bytes = cell_sdk::rkyv::to_bytes::<_, 256>(&grant)?.into_vec();
+        let resp = synapse
+            .fire_on_channel(cell_sdk::channel::AUTH, &grant_bytes)
+            .await?
+            .into_owned();
+        let resp: AuthResponse = cell_sdk::rkyv::from_bytes(&resp)
+            .map_err(|e| anyhow::anyhow!("Invalid auth response: {}", e))?;
+
+        let outcome = match &resp {
+            AuthResponse::Accepted { .. } => "accepted".to_string(),
+            AuthResponse::Rejected { reason } => format!("rejected: {}", reason),
+        };
+        record_impersonation(&operator, &principal, &cell, &reason, &outcome).await;
+
+        if let AuthResponse::Rejected { reason } = resp {
+            anyhow::bail!("'{}' refused impersonation: {}", cell, reason);
+        }
+        eprintln!("🎭 Calling '{}' as '{}'", cell, principal);
+    }
+
+    let resp = synapse
+        .fire_on_channel(cell_sdk::channel::APP, &payload)
+        .await?
+        .into_owned();
+    std::io::stdout().write_all(&resp)?;
+    Ok(())
+}
+
+/// Best effort: the target cell also logs the grant under the `audit` target.
+async fn record_impersonation(operator: &str, principal: &str, cell: &str, reason: &str, outcome: &str) {
+    let Ok(mut audit) = Audit::Client::connect().await else {
+        eprintln!("⚠ audit cell not reachable; impersonation not recorded centrally");
+        return;
+    };
+    let event = Audit::AuditEvent {
+        actor: operator.to_string(),
+        action: format!("impersonate:{}", principal),
+        resource: cell.to_string(),
+        outcome: outcome.to_string(),
+        metadata: reason.to_string(),
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    };
+    if let Err(e) = audit.log(event).await {
+        eprintln!("⚠ failed to record impersonation: {}", e);
+    }
+}
+
+async fn cmd_inspect(cell: String) -> Result<()> {
+    let report = cell_sdk::inspect::fetch(&cell).await?;
+    let mib = |b: u64| b as f64 / (1024.0 * 1024.0);
+
+    println!("🔬 {}", cell);
+    println!(
+        "   ├─ tasks: {} alive, {} queued, {} workers",
+        report.tasks.alive_tasks, report.tasks.queued_tasks, report.tasks.workers
+    );
+    let allocated = report
+        .memory
+        .allocated_bytes
+        .map(|b| format!(", {:.1} MiB allocated", mib(b)))
+        .unwrap_or_default();
+    println!(
+        "   ├─ memory: {:.1} MiB resident, {:.1} MiB virtual{}",
+        mib(report.memory.resident_bytes),
+        mib(report.memory.virtual_bytes),
+        allocated
+    );
+    for ring in &report.rings {
+        println!(
+            "   ├─ shm ring: {}/{} bytes ({:.0}%)",
+            ring.used,
+            ring.capacity,
+            100.0 * ring.used as f64 / ring.capacity.max(1) as f64
+        );
+    }
+    println!("   └─ {} connection(s)", report.connections.len());
+    for conn in &report.connections {
+        let stuck = if conn.pending > 0 {
+            format!(", {} pending", conn.pending)
+        } else {
+            String::new()
+        };
+        println!(
+            "      #{} {} age {}s, {} requests, {} B in / {} B out{}",
+            conn.id,
+            conn.peer,
+            conn.age_secs,
+            conn.requests,
+            conn.bytes_received,
+            conn.bytes_sent,
+            stuck
+        );
+    }
+    Ok(())
+}
+
+async fn cmd_log_level(cell: String, level: String, target: Option<String>) -> Result<()> {
+    let filter = cell_sdk::logging::set_cell_level(&cell, target, &level).await?;
+    println!("📝 '{}' now logs with `{}`", cell, filter);
+    Ok(())
+}
+
+async fn cmd_plugin_load(cell: String, path: String) -> Result<()> {
+    let (plugin, methods) = cell_sdk::extension::load_remote(&cell, &path).await?;
+    println!("🧩 '{}' loaded plugin '{}'", cell, plugin);
+    for (i, method) in methods.iter().enumerate() {
+        let branch = if i + 1 == methods.len() { "└─" } else { "├─" };
+        println!("   {} {}", branch, method);
+    }
+    Ok(())
+}
+
+async fn cmd_plugin_list(cell: String) -> Result<()> {
+    let list = cell_sdk::extension::list(&cell).await?;
+    println!("🧩 {} (generation {})", cell, list.generation);
+    if list.methods.is_empty() {
+        println!("   └─ no methods registered at runtime");
+        return Ok(());
+    }
+    for (i, method) in list.methods.iter().enumerate() {
+        let branch = if i + 1 == list.methods.len() { "└─" } else { "├─" };
+        println!("   {} {} (plugin '{}')", branch, method.name, method.plugin);
+    }
+    Ok(())
+}
+
+async fn cmd_profile(
+    cell: String,
+    seconds: u32,
+    heap: bool,
+    frequency: u32,
+    flamegraph: Option<PathBuf>,
+    out: Option<PathBuf>,
+) -> Result<()> {
+    use cell_sdk::ops::{ProfileFormat, ProfileKind};
+
+    let (kind, label) = match heap {
+        true => (ProfileKind::Heap, "heap"),
+        false => (ProfileKind::Cpu { frequency }, "cpu"),
+    };
+    let (format, path) = match flamegraph {
+        Some(path) => (ProfileFormat::Flamegraph, path),
+        None => (
+            ProfileFormat::Pprof,
+            out.unwrap_or_else(|| PathBuf::from(format!("{}-{}.pb", cell, label))),
+        ),
+    };
+
+    println!("🔥 Profiling '{}' ({}, {}s)", cell, label, seconds);
+    let bytes = cell_sdk::profile::fetch(&cell, kind, seconds, format).await?;
+    std::fs::write(&path, &bytes).with_context(|| format!("Failed to write {:?}", path))?;
+    println!("   └─ {} bytes written to {:?}", bytes.len(), path);
+    Ok(())
+}
+
+async fn cmd_diff(reconcile: bool) -> Result<()> {
+    let nucleus = Nucleus::Client::connect()
+        .await
+        .context("Nucleus not reachable")?;
+
+    let report = nucleus.drift().await?;
+    if report.entries.is_empty() {
+        println!("✅ Mesh '{}' matches its manifest", report.mesh);
+        return Ok(());
+    }
+
+    println!("⚠ Mesh '{}' has drifted:", report.mesh);
+    for entry in &report.entries {
+        println!("   ├─ [{}] {}", entry.kind, entry.detail);
+    }
+
+    if !reconcile {
+        println!("   └─ run `cell diff --reconcile` to correct");
+        return Ok(());
+    }
+
+    let result = nucleus.reconcile().await?;
+    for action in &result.actions {
+        println!("🔧 {}", action);
+    }
+    for entry in &result.unresolved {
+        println!("✗ needs manual action: {}", entry.detail);
+    }
+    Ok(())
+}
+
+async fn cmd_slo(cell: String) -> Result<()> {
+    let statuses = cell_sdk::slo::status(&cell).await?;
+    println!("🎯 {}", cell);
+    if statuses.is_empty() {
+        println!("   └─ no SLOs declared in Cell.toml");
+        return Ok(());
+    }
+    for (i, s) in statuses.iter().enumerate() {
+        let last = i + 1 == statuses.len();
+        let branch = if last { "└─" } else { "├─" };
+        let mark = if s.exhausted() { "🔥" } else { "✅" };
+        println!(
+            "   {} {} {}: availability {:.3}%, latency {:.3}%, budget {:.0}% left",
+            branch,
+            mark,
+            s.method,
+            s.availability,
+            s.latency,
+            100.0 * s.budget_remaining.max(0.0)
+        );
+    }
+    Ok(())
+}
+
+async fn cmd_metrics(cell: String, prometheus: bool) -> Result<()> {
+    if prometheus {
+        print!("{}", cell_sdk::metrics::scrape(&cell).await?);
+        return Ok(());
+    }
+    let snapshot = cell_sdk::metrics::fetch(&cell).await?;
+    println!(
+        "📈 {}: {} calls, {} failed",
+        cell, snapshot.requests_total, snapshot.requests_failed
+    );
+    if snapshot.methods.is_empty() {
+        println!("   └─ no handler calls yet");
+        return Ok(());
+    }
+    for (i, m) in snapshot.methods.iter().enumerate() {
+        let last = i + 1 == snapshot.methods.len();
+        let branch = if last { "└─" } else { "├─" };
+        let mean_ms = m.latency_sum_us as f64 / m.calls.max(1) as f64 / 1000.0;
+        println!(
+            "   {} {}: {} calls, {} errors, mean {:.2}ms",
+            branch, m.method, m.calls, m.errors, mean_ms
+        );
+    }
+    Ok(())
+}
+
+async fn cmd_rollout_restart(
+    cell: String,
+    min_available: Option<u32>,
+    ignore_budget: bool,
+) -> Result<()> {
+    // Risky operations wait until the cell is back within its error budgets
+    if !ignore_budget {
+        match cell_sdk::slo::status(&cell).await {
+            Ok(statuses) => {
+                let spent: Vec<&str> = statuses
+                    .iter()
+                    .filter(|s| s.exhausted())
+                    .map(|s| s.method.as_str())
+                    .collect();
+                if !spent.is_empty() {
+                    anyhow::bail!(
+                        "'{}' has exhausted the error budget of {} (use --ignore-budget to proceed)",
+                        cell,
+                        spent.join(", ")
+                    );
+                }
+            }
+            Err(e) => println!("⚠️  Could not check SLO budgets of '{}': {}", cell, e),
+        }
+    }
+
+    let coordinator = SwapCoordinator::Client::connect()
+        .await
+        .context("swap-coordinator not reachable")?;
+
+    let rollout_id = coordinator
+        .rollout_restart(cell.clone(), min_available)
+        .await?;
+    println!("🔄 Rolling restart of '{}' ({})", cell, rollout_id);
+
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        let Some(status) = coordinator.get_status(rollout_id.clone()).await? else {
+            anyhow::bail!("Rollout {} is no longer tracked", rollout_id);
+        };
+        match status.phase {
+            SwapCoordinator::SwapPhase::Completed => {
+                println!("   └─ ✅ all instances restarted");
+                return Ok(());
+            }
+            SwapCoordinator::SwapPhase::Failed { reason } => {
+                anyhow::bail!("Rollout failed: {}", reason);
+            }
+            phase => println!("   ├─ {:?} ({}%)", phase, status.progress),
+        }
+    }
+}
+
+async fn cmd_rollback(cell: String, to: Option<String>, force: bool) -> Result<()> {
+    use cell_sdk::deploy::{rollback_target, Deployment};
+
+    let state = StateManager::Client::connect()
+        .await
+        .context("state-manager not reachable")?;
+    let history: Vec<Deployment> = state
+        .deployments(cell.clone(), 50)
+        .await?
+        .into_iter()
+        .map(|d| Deployment {
+            cell: d.cell,
+            version: d.version,
+            kind: d.kind,
+            artifact: d.artifact,
+            schema: d.schema,
+            deployed_at: d.deployed_at,
+        })
+        .collect();
+    let target = rollback_target(&history, to.as_deref())
+        .map_err(|e| anyhow::anyhow!("Cannot roll back '{}': {}", cell, e))?;
+
+    // The old version must still understand what today's clients send
+    match &target.schema {
+        Some(source) => {
+            let old = Schema::from_source(source)?;
+            let running = load_schema(&format!("running:{}", cell), None).await?;
+            let changes = old.diff(&running);
+            if Schema::is_breaking(&changes) {
+                for change in changes.iter().filter(|c| c.breaking) {
+                    println!("   {}", change);
+                }
+                if !force {
+                    anyhow::bail!(
+                        "{} is not wire compatible with the running '{}' (use --force to proceed)",
+                        target.version,
+                        cell
+                    );
+                }
+                println!("⚠️  Rolling back to an incompatible schema");
+            }
+        }
+        None => println!(
+            "⚠️  No schema recorded for {}; skipping the compatibility check",
+            target.version
+        ),
+    }
+
+    let coordinator = SwapCoordinator::Client::connect()
+        .await
+        .context("swap-coordinator not reachable")?;
+    let swap_id = coordinator
+        .rollback(SwapCoordinator::RollbackRequest {
+            cell_name: cell.clone(),
+            version: target.version.clone(),
+            artifact: target.artifact.clone(),
+        })
+        .await?;
+    let source = if target.artifact.is_some() { "archived binary" } else { "rebuild" };
+    println!("⏪ Rolling '{}' back to {} from {} ({})", cell, target.version, source, swap_id);
+
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        let Some(status) = coordinator.get_status(swap_id.clone()).await? else {
+            anyhow::bail!("Rollback {} is no longer tracked", swap_id);
+        };
+        match status.phase {
+            SwapCoordinator::SwapPhase::Completed => {
+                println!("   └─ ✅ '{}' is back at {}", cell, target.version);
+                return Ok(());
+            }
+            SwapCoordinator::SwapPhase::Failed { reason } => {
+                anyhow::bail!("Rollback failed: {}", reason);
+            }
+            phase => println!("   ├─ {:?} ({}%)", phase, status.progress),
+        }
+    }
+}
+
+async fn cmd_audit(cell: String, version: Option<String>) -> Result<()> {
+    use cell_build::advisory::{license_inventory, Dependency};
+
+    let registry = Registry::Client::connect()
+        .await
+        .context("registry not reachable")?;
+    let explicit = version.is_some();
+    // What the mesh runs: the newest deployment, if it is a published version
+    let version = match version {
+        Some(version) => Some(version),
+        None => match StateManager::Client::connect().await {
+            Ok(state) => state
+                .deployments(cell.clone(), 1)
+                .await
+                .ok()
+                .and_then(|d| d.into_iter().next())
+                .map(|d| d.version),
+            Err(_) => None,
+        },
+    };
+
+    let mut reports = match &version {
+        Some(v) => registry.audit(cell.clone(), vec![v.clone()]).await?,
+        None => Vec::new(),
+    };
+    if reports.is_empty() && !explicit {
+        if let Some(v) = &version {
+            println!("⚠️  {} {} was not published with a scan; auditing every version", cell, v);
+        }
+        reports = registry.audit(cell.clone(), Vec::new()).await?;
+    }
+    if reports.is_empty() {
+        anyhow::bail!("No dependency scan for '{}'; publish it with its Cargo.lock", cell);
+    }
+
+    let mut vulnerable = 0;
+    for report in &reports {
+        println!(
+            "🔍 {} {}: {} dependencies, {} advisories checked",
+            report.name,
+            report.version,
+            report.dependencies.len(),
+            report.advisories
+        );
+        if report.advisories == 0 {
+            println!("   ├─ ⚠️  the registry has no advisory database");
+        }
+        for v in &report.vulnerabilities {
+            let fix = match v.patched.is_empty() {
+                true => "no patched version".to_string(),
+                false => format!("patched: {}", v.patched.join(" or ")),
+            };
+            println!("   ├─ ✗ {} {} {}: {} ({})", v.package, v.version, v.advisory, v.title, fix);
+        }
+        let deps: Vec<Dependency> = report
+            .dependencies
+            .iter()
+            .map(|d| Dependency {
+                name: d.name.clone(),
+                version: d.version.clone(),
+                license: d.license.clone(),
+            })
+            .collect();
+        let licenses: Vec<String> = license_inventory(&deps)
+            .into_iter()
+            .map(|(license, count)| format!("{} ×{}", license, count))
+            .collect();
+        println!("   └─ licenses: {}", licenses.join(", "));
+        vulnerable += report.vulnerabilities.len();
+    }
+    if vulnerable > 0 {
+        anyhow::bail!("{} known-vulnerable dependency version(s) in '{}'", vulnerable, cell);
+    }
+    Ok(())
+}
+
+async fn cmd_channel_push(cell: String, version: String, channel: String) -> Result<()> {
+    let registry = Registry::Client::connect()
+        .await
+        .context("registry not reachable")?;
+    registry
+        .push(Registry::ChannelPush {
+            name: cell.clone(),
+            version: version.clone(),
+            channel: channel.clone(),
+            actor: std::env::var("USER").unwrap_or_else(|_| "unknown".to_string()),
+        })
+        .await?;
+    println!("📦 {}@{} is now {}", cell, channel, version);
+    Ok(())
+}
+
+async fn cmd_channel_promote(cell: String, from: String, to: String) -> Result<()> {
+    let registry = Registry::Client::connect()
+        .await
+        .context("registry not reachable")?;
+    let version = registry
+        .promote(Registry::Promotion {
+            name: cell.clone(),
+            from: from.clone(),
+            to: to.clone(),
+            actor: std::env::var("USER").unwrap_or_else(|_| "unknown".to_string()),
+        })
+        .await?;
+    println!("⬆️  Promoted {} {} from {} to {}", cell, version, from, to);
+    println!("   └─ cells pinned to {}@{} pick it up on their next spawn", cell, to);
+    Ok(())
+}
+
+async fn cmd_channel_history(cell: String) -> Result<()> {
+    let registry = Registry::Client::connect()
+        .await
+        .context("registry not reachable")?;
+    let history = registry.channel_history(cell.clone()).await?;
+    if history.is_empty() {
+        println!("No channel moves recorded for '{}'", cell);
+    }
+    for m in history {
+        println!(
+            "{:>12}  {:<8} {:<10} {:<8} by {}",
+            m.at, m.channel, m.version, m.action, m.actor
+        );
+    }
+    Ok(())
+}
+
+async fn cmd_node_cordon(node_id: u64, cordoned: bool) -> Result<()> {
+    let nucleus = Nucleus::Client::connect()
+        .await
+        .context("Nucleus not reachable")?;
+    nucleus.cordon_node(node_id, cordoned).await?;
+    if cordoned {
+        println!("🚧 Node {} cordoned: no new cells will be placed on it", node_id);
+    } else {
+        println!("✅ Node {} is schedulable again", node_id);
+    }
+    Ok(())
+}
+
+async fn cmd_node_drain(node_id: u64) -> Result<()> {
+    let nucleus = Nucleus::Client::connect()
+        .await
+        .context("Nucleus not reachable")?;
+    println!("🚧 Draining node {}", node_id);
+    let report = nucleus.drain_node(node_id).await?;
+    for m in &report.migrated {
+        println!("   ├─ {} → node {}", m.cell, m.to);
+    }
+    for cell in &report.stopped {
+        println!("   ├─ {} stopped (no other node can run it)", cell);
+    }
+    println!(
+        "   └─ ✅ node {} is empty; run `cell node uncordon {}` after maintenance",
+        node_id, node_id
+    );
+    Ok(())
+}
+
+async fn cmd_node_upgrade(nodes: Vec<u64>, hook: Option<String>, timeout: u64) -> Result<()> {
+    let nucleus = Nucleus::Client::connect()
+        .await
+        .context("Nucleus not reachable")?;
+    let upgrade_id = nucleus
+        .upgrade_nodes(Nucleus::UpgradeRequest {
+            nodes,
+            hook,
+            timeout_secs: timeout,
+        })
+        .await?;
+    println!("🔧 Rolling upgrade {}", upgrade_id);
+
+    let mut last = String::new();
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        let Some(status) = nucleus.upgrade_status(upgrade_id.clone()).await? else {
+            anyhow::bail!("Upgrade {} is no longer tracked", upgrade_id);
+        };
+        let node = status.current.map(|n| format!("node {}", n)).unwrap_or_default();
+        let line = match status.phase {
+            Nucleus::UpgradePhase::Completed => {
+                println!("   └─ ✅ upgraded nodes {:?}", status.upgraded);
+                return Ok(());
+            }
+            Nucleus::UpgradePhase::Failed { reason } => {
+                anyhow::bail!("Upgrade failed at {}: {} (it stays cordoned)", node, reason);
+            }
+            Nucleus::UpgradePhase::AwaitingOperator => format!(
+                "{}: drained; upgrade it, then run `cell node upgraded {}`",
+                node,
+                status.current.unwrap_or_default()
+            ),
+            phase => format!("{}: {:?}", node, phase),
+        };
+        if line != last {
+            println!("   ├─ {}", line);
+            last = line;
+        }
+    }
+}
+
+async fn cmd_node_upgraded(node_id: u64) -> Result<()> {
+    let nucleus = Nucleus::Client::connect()
+        .await
+        .context("Nucleus not reachable")?;
+    nucleus.confirm_upgrade(node_id).await?;
+    println!("✅ Waiting for node {} to rejoin", node_id);
+    Ok(())
+}
+
+fn cmd_up() -> Result<()> {
+    // The control plane is installed alongside the kernel (`cell install control-plane`)
+    let launch = KernelBin::open_default()?
+        .resolve("control-plane", KERNEL_VERSION)
+        .context("control-plane is not installed (run `cell install control-plane` or set CELL_DEV=1)")?;
+
+    let status = launch.command("control-plane").status()?;
+    if !status.success() {
+        anyhow::bail!("control-plane exited with {}", status);
+    }
+    Ok(())
+}
+
+fn cmd_install(cells: Vec<String>, workspace: PathBuf, from_dir: Option<PathBuf>) -> Result<()> {
+    let cells: Vec<String> = if cells.is_empty() {
+        KERNEL_CELLS.iter().map(|c| c.to_string()).collect()
+    } else {
+        cells
+    };
+    let bin = KernelBin::open_default()?;
+    let workspace = std::fs::canonicalize(&workspace).context("Invalid workspace path")?;
+
+    println!("📦 Installing kernel v{} into {:?}", KERNEL_VERSION, bin.dir());
+    for name in &cells {
+        let built = match &from_dir {
+            Some(dir) => dir.join(name),
+            None => build_release(name, &workspace)?,
+        };
+        bin.install(name, &built, KERNEL_VERSION)?;
+        println!("   ├─ {}", name);
+    }
+    println!("   └─ done ({} binaries)", cells.len());
+    Ok(())
+}
+
+fn cmd_install_service(daemon: String, binary: Option<PathBuf>, dry_run: bool) -> Result<()> {
+    let manager = ServiceManager::detect().context("No supported service manager on this OS")?;
+    if !dry_run && !nix::unistd::getuid().is_root() {
+        anyhow::bail!("Installing a system daemon needs root (run it with sudo)");
+    }
+
+    // Under sudo the daemon runs as, and keeps the `~/.cell` of, whoever ran sudo
+    let invoker = std::env::var("SUDO_USER")
+        .ok()
+        .and_then(|name| nix::unistd::User::from_name(&name).ok().flatten());
+    let mut spec = match invoker {
+        Some(user) => ServiceSpec::kernel_in(&daemon, &user.dir).with_user(&user.name),
+        None => ServiceSpec::kernel(&daemon)?,
+    };
+    if let Some(binary) = binary {
+        spec.binary = std::fs::canonicalize(&binary).context("Invalid binary path")?;
+    }
+
+    if dry_run {
+        for (file, contents) in manager.render(&spec)? {
+            println!("# {}\n{}", file, contents);
+        }
+        return Ok(());
+    }
+
+    if !spec.binary.exists() {
+        anyhow::bail!(
+            "Binary {:?} not found (build it first or pass --binary)",
+            spec.binary
+        );
+    }
+
+    for path in manager.install(&spec)? {
+        println!("📝 Wrote {:?}", path);
+    }
+    println!("✅ {} installed ({:?})", spec.unit_name(), manager);
+    Ok(())
+}
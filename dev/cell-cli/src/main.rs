@@ -1,3 +1,5 @@
+mod commands;
+
 use anyhow::{anyhow, Context, Result};
 use cell_cli::genesis::{run_genesis, scan_cell_dependencies};
 use clap::{Parser, Subcommand};
@@ -32,6 +34,8 @@ enum Action {
     },
     /// Manage financial resources (ATP).
     Wallet { dir: PathBuf },
+    #[command(flatten)]
+    Mesh(commands::Commands),
 }
 
 type CellRegistry = HashMap<String, PathBuf>;
@@ -43,6 +47,7 @@ async fn main() -> Result<()> {
     match cli.action {
         Action::Mitosis { dir, donor } => mitosis(&dir, donor).await,
         Action::Wallet { dir } => wallet(&dir).await,
+        Action::Mesh(command) => commands::run(command).await,
     }
 }
 
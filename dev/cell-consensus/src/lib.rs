@@ -1,11 +1,25 @@
 pub mod network;
+pub mod raft;
 pub mod wal;
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use network::{RaftNetwork, Wire};
+use raft::{RaftCore, Role, Timing};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::{broadcast, Mutex};
-use wal::WriteAheadLog;
+use std::time::Duration;
+use tokio::sync::{broadcast, oneshot, watch, Mutex};
+use tokio::task::JoinHandle;
+use wal::{WalRepair, WriteAheadLog};
+
+/// Length of a Raft tick. With the default [`Timing`] followers campaign
+/// after 200-400ms without a leader, and leaders heartbeat every 40ms.
+const TICK: Duration = Duration::from_millis(20);
+/// How long `propose` waits for a majority to acknowledge an entry, and a
+/// follower for the leader to answer a forwarded batch
+const PROPOSE_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Represents an operation to be applied to the State Machine.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -14,6 +28,8 @@ pub enum LogEntry {
     Command(Vec<u8>),
     /// Configuration change (e.g., adding a peer) - Placeholder for future membership changes
     ConfigChange,
+    /// Appended by every new leader, so entries of earlier terms can commit
+    Noop,
 }
 
 /// Configuration for the Consensus Node
@@ -21,7 +37,8 @@ pub enum LogEntry {
 pub struct ConsensusConfig {
     /// Unique ID for this node in the cluster
     pub id: u64,
-    /// List of peer TCP addresses (e.g., "127.0.0.1:10002")
+    /// TCP addresses of the other members (e.g., "127.0.0.1:10002"). Node
+    /// `id` listens on port `10000 + id`, which is how peers are told apart.
     pub peers: Vec<String>,
     /// Path to the local Write-Ahead Log file
     pub storage_path: std::path::PathBuf,
@@ -34,118 +51,327 @@ pub trait StateMachine: Send + Sync + 'static {
 
 /// The Consensus Engine.
 ///
-/// This is a simplified "All-Write" / Leaderless replication node for the Cell MVP.
-/// It provides durability (WAL) and atomicity (broadcast), but full linearizability
-/// (Leader Election + Quorum) requires the full Raft implementation.
+/// A Raft member ([`raft::RaftCore`]) driven over TCP, with its log in the
+/// WAL. Only the elected leader appends proposals; followers pass batches on
+/// to it. An entry is applied to the State Machine once a majority of the
+/// cluster has it on disk.
 pub struct RaftNode {
     config: ConsensusConfig,
+    driver: Driver,
+    commits: watch::Receiver<u64>,
+    tasks: Vec<JoinHandle<()>>,
+    wal_repair: Option<WalRepair>,
+}
+
+/// What the background tasks share with the node, cloned individually to
+/// avoid a reference cycle with Arc<RaftNode>
+#[derive(Clone)]
+struct Driver {
+    core: Arc<Mutex<RaftCore>>,
     wal: Arc<Mutex<WriteAheadLog>>,
-    commit_index: Arc<Mutex<u64>>,
-    network: network::RaftNetwork,
+    network: Arc<RaftNetwork>,
     state_machine: Arc<dyn StateMachine>,
+    commits: Arc<watch::Sender<u64>>,
+    /// Batches this node forwarded to the leader, by tag
+    forwarded: Arc<std::sync::Mutex<HashMap<u64, oneshot::Sender<Result<u64, String>>>>>,
+    next_tag: Arc<AtomicU64>,
+}
+
+/// Outcome of proposing on this node
+enum Proposed {
+    /// Index of the last command
+    Committed(u64),
+    /// Not the leader; the commands come back untouched
+    NotLeader(Option<u64>, Vec<Vec<u8>>),
+}
+
+impl Driver {
+    /// Persist, apply and send what the core produced. Called with the core
+    /// locked, so records reach the WAL in the order the core made them.
+    async fn flush(&self, core: &mut RaftCore) -> Result<()> {
+        let ready = core.ready();
+        self.wal
+            .lock()
+            .await
+            .append_all(&ready.records)
+            .context("Failed to persist to WAL")?;
+
+        for entry in &ready.committed {
+            if let LogEntry::Command(data) = entry {
+                self.state_machine.apply(data);
+            }
+        }
+        self.commits.send_replace(core.commit_index());
+
+        // Only now that the records are durable may peers hear of them
+        for (to, msg) in ready.messages {
+            let network = self.network.clone();
+            tokio::spawn(async move {
+                let _ = network.send(to, Wire::Raft(msg)).await;
+            });
+        }
+        Ok(())
+    }
+
+    /// Append `commands` if this node leads, and wait until a majority has
+    /// them and they are applied here.
+    async fn propose_here(&self, commands: Vec<Vec<u8>>) -> Result<Proposed> {
+        let mut commits = self.commits.subscribe();
+        let (index, term) = {
+            let mut core = self.core.lock().await;
+            if core.role() != Role::Leader {
+                return Ok(Proposed::NotLeader(core.leader(), commands));
+            }
+            if commands.is_empty() {
+                return Ok(Proposed::Committed(core.commit_index()));
+            }
+            let mut index = 0;
+            for data in commands {
+                // Cannot fail: the role does not change while the core is locked
+                index = core
+                    .propose(LogEntry::Command(data))
+                    .map_err(|_| anyhow!("Lost leadership while proposing"))?;
+            }
+            let term = core.term();
+            self.flush(&mut core).await?;
+            (index, term)
+        };
+
+        tokio::time::timeout(PROPOSE_TIMEOUT, commits.wait_for(|c| *c >= index))
+            .await
+            .map_err(|_| anyhow!("Entry {} was not acknowledged by a majority in time", index))??;
+
+        // Committed at that index, but possibly a new leader's entry
+        if self.core.lock().await.term_at(index) != Some(term) {
+            bail!(
+                "Entry {} was replaced by a new leader before it committed",
+                index
+            );
+        }
+        Ok(Proposed::Committed(index))
+    }
+
+    /// Have `leader` propose `commands`, and wait until they are applied here
+    async fn forward(&self, leader: u64, commands: Vec<Vec<u8>>) -> Result<u64> {
+        let mut commits = self.commits.subscribe();
+        let tag = self.next_tag.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.forwarded.lock().unwrap().insert(tag, tx);
+        let _ = self
+            .network
+            .send(leader, Wire::Forward { tag, commands })
+            .await;
+        let answer = tokio::time::timeout(PROPOSE_TIMEOUT, rx).await;
+        self.forwarded.lock().unwrap().remove(&tag);
+
+        let index = match answer {
+            Ok(Ok(Ok(index))) => index,
+            Ok(Ok(Err(message))) => bail!("Leader {}: {}", leader, message),
+            _ => bail!(
+                "Leader {} did not answer a forwarded proposal in time",
+                leader
+            ),
+        };
+        // The leader has applied them; this node has once it hears of the commit
+        tokio::time::timeout(PROPOSE_TIMEOUT, commits.wait_for(|c| *c >= index))
+            .await
+            .map_err(|_| anyhow!("Entry {} was not replicated here in time", index))??;
+        Ok(index)
+    }
+
+    /// Take a message off the network
+    async fn receive(&self, from: u64, wire: Wire) {
+        match wire {
+            Wire::Raft(msg) => {
+                let mut core = self.core.lock().await;
+                core.step(from, msg);
+                if let Err(e) = self.flush(&mut core).await {
+                    eprintln!("[Raft] Critical: {:#}", e);
+                }
+            }
+            Wire::Forward { tag, commands } => {
+                // Waits for a majority, so off the message loop
+                let driver = self.clone();
+                tokio::spawn(async move {
+                    let result = match driver.propose_here(commands).await {
+                        Ok(Proposed::Committed(index)) => Ok(index),
+                        Ok(Proposed::NotLeader(..)) => Err("Not the leader anymore".to_string()),
+                        Err(e) => Err(format!("{:#}", e)),
+                    };
+                    let _ = driver
+                        .network
+                        .send(from, Wire::Forwarded { tag, result })
+                        .await;
+                });
+            }
+            Wire::Forwarded { tag, result } => {
+                if let Some(tx) = self.forwarded.lock().unwrap().remove(&tag) {
+                    let _ = tx.send(result);
+                }
+            }
+        }
+    }
 }
 
 impl RaftNode {
     /// Initialized the Consensus Node.
     ///
-    /// 1. Opens the Write-Ahead Log.
-    /// 2. Replays any existing logs to the State Machine (crash recovery).
-    /// 3. Binds the Network listener.
-    /// 4. Spawns a background task to handle incoming replication requests.
+    /// 1. Opens the Write-Ahead Log, cutting off a damaged tail, and recovers
+    ///    term, vote and log.
+    /// 2. Binds the Network listener.
+    /// 3. Replays the committed entries to the State Machine (crash recovery).
+    /// 4. Spawns the tick loop (elections, heartbeats) and the message loop.
     pub async fn new(
         config: ConsensusConfig,
         state_machine: Arc<dyn StateMachine>,
     ) -> Result<Arc<Self>> {
         // 1. Open WAL
-        let wal = WriteAheadLog::open(&config.storage_path)?;
-        let wal = Arc::new(Mutex::new(wal));
-
-        // 2. Crash Recovery: Replay Log BEFORE accepting new connections
-        let commit_idx = {
-            let mut w = wal.lock().await;
-            let entries = w.read_all()?;
-            if !entries.is_empty() {
-                println!(
-                    "[Raft] Recovering state: Replaying {} entries from WAL...",
-                    entries.len()
-                );
-                for entry in entries.iter() {
-                    if let LogEntry::Command(data) = entry {
-                        state_machine.apply(data);
-                    }
-                }
-            }
-            entries.len() as u64
-        };
+        let mut wal = WriteAheadLog::open(&config.storage_path)?;
+        let wal_repair = wal.repair().cloned();
+        let recovered = wal.recover()?;
+        if !recovered.log.is_empty() {
+            println!(
+                "[Raft] Recovering state: {} entries in WAL, {} committed (term {})",
+                recovered.log.len(),
+                recovered.commit,
+                recovered.term
+            );
+        }
 
-        // 3. Start Network Layer
-        let network = network::RaftNetwork::new(config.id, config.peers.clone()).await?;
+        // 2. Start Network Layer
+        let network = RaftNetwork::new(config.id, config.peers.clone()).await?;
+        let mut core =
+            RaftCore::restore(config.id, network.peer_ids(), Timing::default(), recovered);
+        if network.peer_ids().is_empty() {
+            // A cluster of one has nobody to wait for
+            core.campaign();
+        }
 
-        // 4. Background Replication Loop
-        // We clone components individually to avoid creating a reference cycle with Arc<RaftNode>
+        let (commits_tx, commits) = watch::channel(0);
         let net_rx = network.listen();
-        let wal_bg = wal.clone();
-        let sm_bg = state_machine.clone();
-
-        tokio::spawn(async move {
-            let mut rx = net_rx;
-            while let Ok(msg) = rx.recv().await {
-                // Handle incoming replication from peers
-                if let LogEntry::Command(data) = msg {
-                    // A. Persist to Disk
-                    let mut w = wal_bg.lock().await;
-                    // If WAL fails, we drop the message (Basic consistency check)
-                    if let Ok(_) = w.append(&LogEntry::Command(data.clone())) {
-                        // B. Apply to State
-                        sm_bg.apply(&data);
-                    } else {
-                        eprintln!("[Raft] Critical: Failed to write incoming log to disk.");
+        let driver = Driver {
+            core: Arc::new(Mutex::new(core)),
+            wal: Arc::new(Mutex::new(wal)),
+            network: Arc::new(network),
+            state_machine,
+            commits: Arc::new(commits_tx),
+            forwarded: Arc::default(),
+            next_tag: Arc::new(AtomicU64::new(0)),
+        };
+
+        // 3. Crash Recovery: replay committed entries BEFORE serving proposals
+        {
+            let mut core = driver.core.lock().await;
+            driver.flush(&mut core).await?;
+        }
+
+        // 4. Background Loops
+        let ticker = {
+            let driver = driver.clone();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(TICK);
+                loop {
+                    interval.tick().await;
+                    let mut core = driver.core.lock().await;
+                    core.tick();
+                    if let Err(e) = driver.flush(&mut core).await {
+                        eprintln!("[Raft] Critical: {:#}", e);
                     }
                 }
-            }
-            // Loop terminates when RaftNetwork is dropped (Sender closed)
-        });
+            })
+        };
+        let inbox = {
+            let driver = driver.clone();
+            tokio::spawn(async move {
+                let mut rx = net_rx;
+                loop {
+                    let (from, wire) = match rx.recv().await {
+                        Ok(envelope) => envelope,
+                        // Dropped messages are retried by Raft itself
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    };
+                    driver.receive(from, wire).await;
+                }
+            })
+        };
 
-        let node = Arc::new(Self {
+        Ok(Arc::new(Self {
             config,
-            wal,
-            commit_index: Arc::new(Mutex::new(commit_idx)),
-            network,
-            state_machine,
-        });
-
-        Ok(node)
+            driver,
+            commits,
+            tasks: vec![ticker, inbox],
+            wal_repair,
+        }))
     }
 
     /// Propose a new entry to the cluster.
     ///
-    /// 1. Writes to local disk (Durability).
-    /// 2. Broadcasts to peers (Replication).
-    /// 3. Applies to local state.
+    /// 1. Appends it to the leader's log and WAL (Durability).
+    /// 2. Replicates it to the followers (Replication).
+    /// 3. Returns once a majority has it and it is applied to local state.
+    ///
+    /// Fails on a follower; [`leader`](Self::leader) says where to go instead.
     pub async fn propose(&self, data: Vec<u8>) -> Result<()> {
-        let entry = LogEntry::Command(data.clone());
+        match self.driver.propose_here(vec![data]).await? {
+            Proposed::Committed(_) => Ok(()),
+            Proposed::NotLeader(leader, _) => Err(self.not_leader(leader)),
+        }
+    }
 
-        // 1. Write to Local WAL
-        {
-            let mut wal = self.wal.lock().await;
-            wal.append(&entry).context("Failed to persist to WAL")?;
+    /// Propose several entries at once, on any member.
+    ///
+    /// The leader appends them together and replicates them in one round.
+    /// A follower forwards the batch to the leader it knows of.
+    /// Returns the index of the last entry once all of them are applied to
+    /// local state; fails while no leader is elected.
+    pub async fn propose_batch(&self, commands: Vec<Vec<u8>>) -> Result<u64> {
+        match self.driver.propose_here(commands).await? {
+            Proposed::Committed(index) => Ok(index),
+            Proposed::NotLeader(Some(leader), commands) => {
+                self.driver.forward(leader, commands).await
+            }
+            Proposed::NotLeader(None, _) => Err(self.not_leader(None)),
         }
+    }
 
-        // 2. Replicate to Peers (Best Effort for MVP)
-        self.network.broadcast(entry).await?;
+    fn not_leader(&self, leader: Option<u64>) -> anyhow::Error {
+        match leader {
+            Some(leader) => anyhow!(
+                "Node {} is not the leader; propose to node {}",
+                self.config.id,
+                leader
+            ),
+            None => anyhow!("No leader elected yet"),
+        }
+    }
 
-        // 3. Apply to State Machine
-        self.state_machine.apply(&data);
+    /// Index of the last committed log entry.
+    pub async fn get_commit_index(&self) -> u64 {
+        *self.commits.borrow()
+    }
 
-        // 4. Update Commit Index
-        let mut idx = self.commit_index.lock().await;
-        *idx += 1;
+    /// The current leader, as far as this node knows
+    pub async fn leader(&self) -> Option<u64> {
+        self.driver.core.lock().await.leader()
+    }
 
-        Ok(())
+    pub async fn is_leader(&self) -> bool {
+        self.leader().await == Some(self.config.id)
     }
 
-    /// Returns the current number of entries committed to the log.
-    pub async fn get_commit_index(&self) -> u64 {
-        *self.commit_index.lock().await
+    /// What opening the WAL cut off its damaged tail, if anything. Cells
+    /// pass it on to their OPS status.
+    pub fn wal_repair(&self) -> Option<&WalRepair> {
+        self.wal_repair.as_ref()
+    }
+}
+
+impl Drop for RaftNode {
+    fn drop(&mut self) {
+        // Release the network (and its port) with the tasks holding it
+        for task in &self.tasks {
+            task.abort();
+        }
     }
 }
@@ -1,25 +1,61 @@
-use crate::LogEntry;
+use crate::raft::Message;
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::broadcast;
 use tokio::task::JoinHandle;
 
-/// Handles peer-to-peer replication traffic.
+/// How long a send waits for a peer to accept. Raft retries on its own, so a
+/// peer that is down only costs this much per message.
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Node `id` listens on this port
+pub fn port_of(id: u64) -> u64 {
+    10000 + id
+}
+
+/// The node id behind a peer address: nodes listen on `10000 + id`
+pub fn peer_id(addr: &str) -> Option<u64> {
+    let port: u64 = addr.rsplit_once(':')?.1.parse().ok()?;
+    port.checked_sub(10000)
+}
+
+/// What nodes send each other
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Wire {
+    Raft(Message),
+    /// Commands a follower was asked to propose, passed on to the leader
+    Forward {
+        tag: u64,
+        commands: Vec<Vec<u8>>,
+    },
+    /// The leader's answer to `Forward`: the index of the last command once
+    /// it committed
+    Forwarded {
+        tag: u64,
+        result: Result<u64, String>,
+    },
+}
+
+/// Handles peer-to-peer Raft traffic.
 /// Listens on `port + 1` of the cell's main port.
 pub struct RaftNetwork {
-    peers: Vec<String>,
-    inbox: broadcast::Sender<LogEntry>,
+    id: u64,
+    /// Peer id -> address
+    peers: HashMap<u64, String>,
+    inbox: broadcast::Sender<(u64, Wire)>,
     // Store handle to abort task on Drop
     _listener_task: JoinHandle<()>,
 }
 
 impl RaftNetwork {
     pub async fn new(my_id: u64, peers: Vec<String>) -> Result<Self> {
-        let (tx, _) = broadcast::channel(100);
+        let (tx, _) = broadcast::channel(1024);
 
-        let port = 10000 + my_id;
-        let addr = format!("0.0.0.0:{}", port);
+        let addr = format!("0.0.0.0:{}", port_of(my_id));
 
         // Explicitly set reuseaddr/port usually not needed for basic tests if we drop correctly,
         // but helps avoiding TIME_WAIT issues in tight loops.
@@ -39,8 +75,8 @@ impl RaftNetwork {
                             let len = u32::from_le_bytes(len_buf) as usize;
                             let mut buf = vec![0u8; len];
                             if socket.read_exact(&mut buf).await.is_ok() {
-                                if let Ok(entry) = bincode::deserialize(&buf) {
-                                    let _ = tx.send(entry);
+                                if let Ok(envelope) = bincode::deserialize(&buf) {
+                                    let _ = tx.send(envelope);
                                 }
                             }
                         }
@@ -49,26 +85,43 @@ impl RaftNetwork {
             }
         });
 
+        let peers = peers
+            .into_iter()
+            .filter_map(|addr| Some((peer_id(&addr)?, addr)))
+            .filter(|(id, _)| *id != my_id)
+            .collect();
+
         Ok(Self {
+            id: my_id,
             peers,
             inbox: tx,
             _listener_task: handle,
         })
     }
 
-    pub fn listen(&self) -> broadcast::Receiver<LogEntry> {
+    /// Ids of the other members
+    pub fn peer_ids(&self) -> Vec<u64> {
+        self.peers.keys().copied().collect()
+    }
+
+    /// `(from, message)` as peers send them
+    pub fn listen(&self) -> broadcast::Receiver<(u64, Wire)> {
         self.inbox.subscribe()
     }
 
-    pub async fn broadcast(&self, entry: LogEntry) -> Result<()> {
-        let bytes = bincode::serialize(&entry)?;
+    /// Best effort: a message to an unreachable peer is dropped
+    pub async fn send(&self, to: u64, msg: Wire) -> Result<()> {
+        let Some(addr) = self.peers.get(&to) else {
+            return Ok(());
+        };
+        let bytes = bincode::serialize(&(self.id, msg))?;
         let len = (bytes.len() as u32).to_le_bytes();
 
-        for peer in &self.peers {
-            if let Ok(mut stream) = TcpStream::connect(peer).await {
-                let _ = stream.write_all(&len).await;
-                let _ = stream.write_all(&bytes).await;
-            }
+        if let Ok(Ok(mut stream)) =
+            tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(addr)).await
+        {
+            let _ = stream.write_all(&len).await;
+            let _ = stream.write_all(&bytes).await;
         }
         Ok(())
     }
@@ -0,0 +1,542 @@
+//! The Raft protocol, without any IO.
+//!
+//! [`RaftCore`] is deterministic: time only advances through
+//! [`tick`](RaftCore::tick) and peers only speak through
+//! [`step`](RaftCore::step). Whatever it needs done — messages to send,
+//! records to persist, entries to apply — collects in a [`Ready`] that the
+//! caller drains with [`ready`](RaftCore::ready). Records must reach disk
+//! before the messages of the same `Ready` are sent.
+//!
+//! `RaftNode` drives the core over TCP and the WAL; the tests drive it over a
+//! simulated network.
+
+use crate::LogEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Entries per AppendEntries message, so a follower far behind catches up in
+/// bounded steps
+const MAX_BATCH: usize = 64;
+
+/// A log entry with the term it was created in
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub term: u64,
+    pub entry: LogEntry,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Message {
+    RequestVote {
+        term: u64,
+        last_log_index: u64,
+        last_log_term: u64,
+    },
+    Vote {
+        term: u64,
+        granted: bool,
+    },
+    AppendEntries {
+        term: u64,
+        prev_log_index: u64,
+        prev_log_term: u64,
+        entries: Vec<Entry>,
+        leader_commit: u64,
+    },
+    AppendResponse {
+        term: u64,
+        success: bool,
+        /// On success the last index now matching the leader; on failure
+        /// the follower's last index, where the leader backs up to
+        match_index: u64,
+    },
+}
+
+impl Message {
+    pub fn term(&self) -> u64 {
+        match self {
+            Message::RequestVote { term, .. }
+            | Message::Vote { term, .. }
+            | Message::AppendEntries { term, .. }
+            | Message::AppendResponse { term, .. } => *term,
+        }
+    }
+}
+
+/// A change to the durable state, written to the WAL
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Record {
+    HardState {
+        term: u64,
+        voted_for: Option<u64>,
+    },
+    /// The entry at `index`; it replaces whatever was at `index` and after
+    Entry {
+        index: u64,
+        entry: Entry,
+    },
+    /// Entries up to here are committed
+    Commit(u64),
+}
+
+/// Durable state rebuilt from the WAL's records
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Recovered {
+    pub term: u64,
+    pub voted_for: Option<u64>,
+    pub log: Vec<Entry>,
+    pub commit: u64,
+}
+
+impl Recovered {
+    pub fn replay(records: impl IntoIterator<Item = Record>) -> Self {
+        let mut state = Self::default();
+        for record in records {
+            match record {
+                Record::HardState { term, voted_for } => {
+                    state.term = term;
+                    state.voted_for = voted_for;
+                }
+                Record::Entry { index, entry } => {
+                    state.log.truncate(index.saturating_sub(1) as usize);
+                    state.log.push(entry);
+                }
+                Record::Commit(index) => state.commit = state.commit.max(index),
+            }
+        }
+        state.commit = state.commit.min(state.log.len() as u64);
+        state
+    }
+}
+
+/// Everything the core wants done since the last [`RaftCore::ready`]
+#[derive(Debug, Default)]
+pub struct Ready {
+    /// `(to, message)`
+    pub messages: Vec<(u64, Message)>,
+    pub records: Vec<Record>,
+    /// Newly committed entries, in log order, to apply to the state machine
+    pub committed: Vec<LogEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+/// Timeouts in ticks. A follower campaigns after between `election_ticks`
+/// and twice that without hearing from a leader.
+#[derive(Debug, Clone, Copy)]
+pub struct Timing {
+    pub election_ticks: u64,
+    pub heartbeat_ticks: u64,
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Self {
+            election_ticks: 10,
+            heartbeat_ticks: 2,
+        }
+    }
+}
+
+pub struct RaftCore {
+    id: u64,
+    peers: Vec<u64>,
+    timing: Timing,
+    role: Role,
+    term: u64,
+    voted_for: Option<u64>,
+    leader: Option<u64>,
+    /// Entry `i` is at index `i + 1`
+    log: Vec<Entry>,
+    commit_index: u64,
+    last_applied: u64,
+    votes: HashSet<u64>,
+    next_index: HashMap<u64, u64>,
+    match_index: HashMap<u64, u64>,
+    elapsed: u64,
+    timeout: u64,
+    rng: u64,
+    ready: Ready,
+}
+
+impl RaftCore {
+    pub fn new(id: u64, peers: Vec<u64>, timing: Timing) -> Self {
+        Self::restore(id, peers, timing, Recovered::default())
+    }
+
+    /// Resume from the WAL. Committed entries are applied again from the
+    /// start: the first [`ready`](Self::ready) carries them.
+    pub fn restore(id: u64, peers: Vec<u64>, timing: Timing, state: Recovered) -> Self {
+        let mut core = Self {
+            id,
+            peers: peers.into_iter().filter(|p| *p != id).collect(),
+            timing,
+            role: Role::Follower,
+            term: state.term,
+            voted_for: state.voted_for,
+            leader: None,
+            log: state.log,
+            commit_index: state.commit,
+            last_applied: 0,
+            votes: HashSet::new(),
+            next_index: HashMap::new(),
+            match_index: HashMap::new(),
+            elapsed: 0,
+            timeout: 0,
+            // Seeded by id: runs are reproducible, nodes still time out apart
+            rng: id.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1,
+            ready: Ready::default(),
+        };
+        core.reset_timeout();
+        core.apply();
+        core
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    pub fn term(&self) -> u64 {
+        self.term
+    }
+
+    pub fn leader(&self) -> Option<u64> {
+        self.leader
+    }
+
+    pub fn commit_index(&self) -> u64 {
+        self.commit_index
+    }
+
+    pub fn last_index(&self) -> u64 {
+        self.log.len() as u64
+    }
+
+    pub fn log(&self) -> &[Entry] {
+        &self.log
+    }
+
+    /// Term of the entry at `index`; 0 for index 0
+    pub fn term_at(&self, index: u64) -> Option<u64> {
+        match index {
+            0 => Some(0),
+            i => self.log.get(i as usize - 1).map(|e| e.term),
+        }
+    }
+
+    pub fn ready(&mut self) -> Ready {
+        std::mem::take(&mut self.ready)
+    }
+
+    pub fn tick(&mut self) {
+        self.elapsed += 1;
+        if self.role == Role::Leader {
+            if self.elapsed >= self.timing.heartbeat_ticks {
+                self.elapsed = 0;
+                self.broadcast_append();
+            }
+        } else if self.elapsed >= self.timeout {
+            self.campaign();
+        }
+    }
+
+    /// Stand for election in the next term
+    pub fn campaign(&mut self) {
+        self.term += 1;
+        self.role = Role::Candidate;
+        self.voted_for = Some(self.id);
+        self.leader = None;
+        self.votes = HashSet::from([self.id]);
+        self.persist_hard_state();
+        self.reset_timeout();
+
+        if self.votes.len() >= self.quorum() {
+            return self.become_leader();
+        }
+        let (last_log_index, last_log_term) = self.last_log();
+        for &peer in &self.peers {
+            self.ready.messages.push((
+                peer,
+                Message::RequestVote {
+                    term: self.term,
+                    last_log_index,
+                    last_log_term,
+                },
+            ));
+        }
+    }
+
+    /// Append `entry` to the log if this node leads. Returns its index, or
+    /// the leader to ask instead, if one is known.
+    pub fn propose(&mut self, entry: LogEntry) -> Result<u64, Option<u64>> {
+        if self.role != Role::Leader {
+            return Err(self.leader);
+        }
+        let index = self.append(entry);
+        self.maybe_commit();
+        self.broadcast_append();
+        Ok(index)
+    }
+
+    pub fn step(&mut self, from: u64, msg: Message) {
+        if msg.term() > self.term {
+            self.term = msg.term();
+            self.voted_for = None;
+            self.role = Role::Follower;
+            self.leader = None;
+            self.persist_hard_state();
+        }
+
+        match msg {
+            Message::RequestVote {
+                term,
+                last_log_index,
+                last_log_term,
+            } => {
+                let (my_index, my_term) = self.last_log();
+                let up_to_date = (last_log_term, last_log_index) >= (my_term, my_index);
+                let granted =
+                    term == self.term && self.voted_for.is_none_or(|v| v == from) && up_to_date;
+                if granted {
+                    self.voted_for = Some(from);
+                    self.persist_hard_state();
+                    self.elapsed = 0;
+                }
+                self.send(
+                    from,
+                    Message::Vote {
+                        term: self.term,
+                        granted,
+                    },
+                );
+            }
+
+            Message::Vote { term, granted } => {
+                if self.role == Role::Candidate && term == self.term && granted {
+                    self.votes.insert(from);
+                    if self.votes.len() >= self.quorum() {
+                        self.become_leader();
+                    }
+                }
+            }
+
+            Message::AppendEntries {
+                term,
+                prev_log_index,
+                prev_log_term,
+                entries,
+                leader_commit,
+            } => {
+                if term < self.term {
+                    return self.send(
+                        from,
+                        Message::AppendResponse {
+                            term: self.term,
+                            success: false,
+                            match_index: self.last_index(),
+                        },
+                    );
+                }
+                // A candidate that hears from this term's leader lost
+                self.role = Role::Follower;
+                self.leader = Some(from);
+                self.elapsed = 0;
+
+                if self.term_at(prev_log_index) != Some(prev_log_term) {
+                    let hint = self.last_index().min(prev_log_index.saturating_sub(1));
+                    return self.send(
+                        from,
+                        Message::AppendResponse {
+                            term: self.term,
+                            success: false,
+                            match_index: hint,
+                        },
+                    );
+                }
+
+                let matched = prev_log_index + entries.len() as u64;
+                for (i, entry) in entries.into_iter().enumerate() {
+                    let index = prev_log_index + 1 + i as u64;
+                    match self.term_at(index) {
+                        Some(t) if t == entry.term => continue,
+                        Some(_) => {
+                            // Conflicts are never committed: the leader has every committed entry
+                            assert!(
+                                index > self.commit_index,
+                                "committed entry {} conflicts",
+                                index
+                            );
+                            self.log.truncate(index as usize - 1);
+                        }
+                        None => {}
+                    }
+                    self.ready.records.push(Record::Entry {
+                        index,
+                        entry: entry.clone(),
+                    });
+                    self.log.push(entry);
+                }
+
+                if leader_commit > self.commit_index {
+                    self.set_commit(leader_commit.min(matched));
+                }
+                self.send(
+                    from,
+                    Message::AppendResponse {
+                        term: self.term,
+                        success: true,
+                        match_index: matched,
+                    },
+                );
+            }
+
+            Message::AppendResponse {
+                term,
+                success,
+                match_index,
+            } => {
+                if self.role != Role::Leader || term != self.term {
+                    return;
+                }
+                if success {
+                    let matched = self.match_index.entry(from).or_insert(0);
+                    *matched = (*matched).max(match_index);
+                    let next = *matched + 1;
+                    self.next_index.insert(from, next);
+                    self.maybe_commit();
+                    if next <= self.last_index() {
+                        self.send_append(from);
+                    }
+                } else {
+                    let next = self.next_index.entry(from).or_insert(1);
+                    *next = (match_index + 1).min(next.saturating_sub(1)).max(1);
+                    self.send_append(from);
+                }
+            }
+        }
+    }
+
+    fn become_leader(&mut self) {
+        self.role = Role::Leader;
+        self.leader = Some(self.id);
+        self.elapsed = 0;
+        let next = self.last_index() + 1;
+        self.next_index = self.peers.iter().map(|p| (*p, next)).collect();
+        self.match_index = self.peers.iter().map(|p| (*p, 0)).collect();
+        // Entries of earlier terms only commit under one of this term
+        self.append(LogEntry::Noop);
+        self.maybe_commit();
+        self.broadcast_append();
+    }
+
+    fn append(&mut self, entry: LogEntry) -> u64 {
+        let entry = Entry {
+            term: self.term,
+            entry,
+        };
+        let index = self.last_index() + 1;
+        self.ready.records.push(Record::Entry {
+            index,
+            entry: entry.clone(),
+        });
+        self.log.push(entry);
+        index
+    }
+
+    /// Commit the highest entry of this term a majority holds
+    fn maybe_commit(&mut self) {
+        for index in (self.commit_index + 1..=self.last_index()).rev() {
+            if self.term_at(index) != Some(self.term) {
+                break;
+            }
+            let holders = 1 + self.match_index.values().filter(|m| **m >= index).count();
+            if holders >= self.quorum() {
+                self.set_commit(index);
+                break;
+            }
+        }
+    }
+
+    fn set_commit(&mut self, index: u64) {
+        if index > self.commit_index {
+            self.commit_index = index;
+            self.ready.records.push(Record::Commit(index));
+            self.apply();
+        }
+    }
+
+    fn apply(&mut self) {
+        while self.last_applied < self.commit_index {
+            self.last_applied += 1;
+            let entry = self.log[self.last_applied as usize - 1].entry.clone();
+            self.ready.committed.push(entry);
+        }
+    }
+
+    fn broadcast_append(&mut self) {
+        for peer in self.peers.clone() {
+            self.send_append(peer);
+        }
+    }
+
+    fn send_append(&mut self, peer: u64) {
+        let next = self.next_index.get(&peer).copied().unwrap_or(1).max(1);
+        let prev_log_index = next - 1;
+        let entries = self
+            .log
+            .iter()
+            .skip(prev_log_index as usize)
+            .take(MAX_BATCH)
+            .cloned()
+            .collect();
+        let msg = Message::AppendEntries {
+            term: self.term,
+            prev_log_index,
+            prev_log_term: self.term_at(prev_log_index).unwrap_or(0),
+            entries,
+            leader_commit: self.commit_index,
+        };
+        self.send(peer, msg);
+    }
+
+    fn send(&mut self, to: u64, msg: Message) {
+        self.ready.messages.push((to, msg));
+    }
+
+    fn persist_hard_state(&mut self) {
+        self.ready.records.push(Record::HardState {
+            term: self.term,
+            voted_for: self.voted_for,
+        });
+    }
+
+    fn last_log(&self) -> (u64, u64) {
+        (
+            self.last_index(),
+            self.log.last().map(|e| e.term).unwrap_or(0),
+        )
+    }
+
+    fn quorum(&self) -> usize {
+        self.peers.len().div_ceil(2) + 1
+    }
+
+    fn reset_timeout(&mut self) {
+        // xorshift64
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        let spread = self.timing.election_ticks.max(1);
+        self.timeout = self.timing.election_ticks + self.rng % spread;
+        self.elapsed = 0;
+    }
+}
@@ -1,16 +1,70 @@
+use crate::raft::{Record, Recovered};
 use crate::LogEntry;
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use std::fs::{File, OpenOptions};
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 
-/// A simple append-only Write Ahead Log.
-/// Format: [Length: u64][CRC: u32][Payload: Bytes]
+/// Length and CRC32 of a record
+const HEADER_LEN: u64 = 12;
+
+/// A simple append-only Write Ahead Log of Raft [`Record`]s.
+/// Format: [Length: u64][CRC32 of payload: u32][Payload: Bytes]
+///
+/// A crash mid-append leaves a torn record at the end, and a bad disk can
+/// flip bits anywhere. [`open`](Self::open) checks every record and cuts the
+/// file back to the last one that is whole and matches its checksum, so
+/// nothing after a damaged record is ever replayed. Raft gets the lost suffix
+/// back from the leader; what was cut is reported in [`WalRepair`].
 pub struct WriteAheadLog {
     file: File,
+    repair: Option<WalRepair>,
+}
+
+/// What [`WriteAheadLog::open`] cut off the end of the file
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalRepair {
+    /// Records kept
+    pub records: usize,
+    /// Length of the file after the repair
+    pub valid_bytes: u64,
+    pub discarded_bytes: u64,
+    pub reason: Damage,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Damage {
+    /// The file ends inside a record: a write the crash interrupted
+    TornWrite,
+    /// A record's payload does not match its CRC
+    Checksum,
+    /// The checksum matches but the payload is not a record
+    Undecodable,
+}
+
+impl std::fmt::Display for WalRepair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self.reason {
+            Damage::TornWrite => "torn write",
+            Damage::Checksum => "checksum mismatch",
+            Damage::Undecodable => "undecodable record",
+        };
+        write!(
+            f,
+            "{} after record {}: truncated to {} bytes, {} discarded",
+            reason, self.records, self.valid_bytes, self.discarded_bytes
+        )
+    }
+}
+
+/// How a scan of the file ended
+enum Scan {
+    Clean,
+    Damaged { at: u64, reason: Damage },
 }
 
 impl WriteAheadLog {
+    /// Open the log, repairing a damaged tail first
     pub fn open(path: &Path) -> Result<Self> {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
@@ -22,73 +76,132 @@ impl WriteAheadLog {
             .open(path)
             .context("Failed to open WAL file")?;
 
-        Ok(Self { file })
+        let mut wal = Self { file, repair: None };
+        let (records, scan) = wal.scan()?;
+        if let Scan::Damaged { at, reason } = scan {
+            let len = wal.file.metadata()?.len();
+            wal.file.set_len(at)?;
+            wal.file.sync_all()?;
+            let repair = WalRepair {
+                records: records.len(),
+                valid_bytes: at,
+                discarded_bytes: len - at,
+                reason,
+            };
+            tracing::warn!("[WAL] Repaired {}: {}", path.display(), repair);
+            wal.repair = Some(repair);
+        }
+        Ok(wal)
     }
 
-    pub fn append(&mut self, entry: &LogEntry) -> Result<()> {
-        let bytes = bincode::serialize(entry)?;
-        let len = bytes.len() as u64;
-        let crc = crc32fast::hash(&bytes);
+    /// What opening the log had to cut off, if anything
+    pub fn repair(&self) -> Option<&WalRepair> {
+        self.repair.as_ref()
+    }
 
+    pub fn append(&mut self, record: &Record) -> Result<()> {
+        self.append_all(std::slice::from_ref(record))
+    }
+
+    /// Append `records` with a single fsync
+    pub fn append_all(&mut self, records: &[Record]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
         self.file.seek(SeekFrom::End(0))?;
-        self.file.write_all(&len.to_le_bytes())?;
-        self.file.write_all(&crc.to_le_bytes())?;
-        self.file.write_all(&bytes)?;
+        for record in records {
+            let bytes = bincode::serialize(record)?;
+            let len = bytes.len() as u64;
+            let crc = crc32fast::hash(&bytes);
+
+            self.file.write_all(&len.to_le_bytes())?;
+            self.file.write_all(&crc.to_le_bytes())?;
+            self.file.write_all(&bytes)?;
+        }
         self.file.sync_data()?; // Fsync for durability
         Ok(())
     }
 
+    /// Term, vote, log and commit index as of the last record
+    pub fn recover(&mut self) -> Result<Recovered> {
+        Ok(Recovered::replay(self.read_records()?))
+    }
+
+    /// The log as it stands after truncations, committed or not
     pub fn read_all(&mut self) -> Result<Vec<LogEntry>> {
-        let mut entries = Vec::new();
+        Ok(self.recover()?.log.into_iter().map(|e| e.entry).collect())
+    }
+
+    /// Every record. The tail was repaired on open, so damage found now
+    /// happened since and is an error rather than something to replay.
+    pub fn read_records(&mut self) -> Result<Vec<Record>> {
+        match self.scan()? {
+            (records, Scan::Clean) => Ok(records),
+            (records, Scan::Damaged { at, reason }) => bail!(
+                "WAL damaged at byte {} after record {} ({:?})",
+                at,
+                records.len(),
+                reason
+            ),
+        }
+    }
+
+    /// Read records from the start until the end of the file or the first
+    /// damaged one
+    fn scan(&mut self) -> Result<(Vec<Record>, Scan)> {
+        let len = self.file.metadata()?.len();
         self.file.seek(SeekFrom::Start(0))?;
+        let mut reader = BufReader::new(&mut self.file);
+        let mut records = Vec::new();
+        let mut offset = 0u64;
 
         let mut len_buf = [0u8; 8];
         let mut crc_buf = [0u8; 4];
-
-        loop {
-            // 1. Read Length
-            // If we fail to read the length (EOF), we assume end of log.
-            if self.file.read_exact(&mut len_buf).is_err() {
-                break;
+        while offset < len {
+            let damaged = |reason| Scan::Damaged { at: offset, reason };
+            if len - offset < HEADER_LEN {
+                return Ok((records, damaged(Damage::TornWrite)));
             }
-            let len = u64::from_le_bytes(len_buf);
-
-            // 2. Read CRC
-            // If we have a length but no CRC, it's a corrupted/partial tail.
-            if self.file.read_exact(&mut crc_buf).is_err() {
-                break;
+            reader.read_exact(&mut len_buf)?;
+            reader.read_exact(&mut crc_buf)?;
+            let payload_len = u64::from_le_bytes(len_buf);
+            // Checked before allocating: a corrupt length can be anything
+            if payload_len > len - offset - HEADER_LEN {
+                return Ok((records, damaged(Damage::TornWrite)));
             }
-            let _expected_crc = u32::from_le_bytes(crc_buf);
 
-            // 3. Read Payload
-            let mut payload = vec![0u8; len as usize];
-            // If we can't read the full payload, it's a partial write.
-            if self.file.read_exact(&mut payload).is_err() {
-                break;
+            let mut payload = vec![0u8; payload_len as usize];
+            reader.read_exact(&mut payload)?;
+            if crc32fast::hash(&payload) != u32::from_le_bytes(crc_buf) {
+                return Ok((records, damaged(Damage::Checksum)));
             }
-
-            // Optional: Verify CRC here in production
-            // if crc32fast::hash(&payload) != _expected_crc { break; }
-
-            // 4. Deserialize
-            // If deserialization fails, we stop (assume garbage data).
-            if let Ok(entry) = bincode::deserialize(&payload) {
-                entries.push(entry);
-            } else {
-                break;
+            match bincode::deserialize(&payload) {
+                Ok(record) => records.push(record),
+                Err(_) => return Ok((records, damaged(Damage::Undecodable))),
             }
+            offset += HEADER_LEN + payload_len;
         }
-
-        Ok(entries)
+        Ok((records, Scan::Clean))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::raft::Entry;
     use crate::LogEntry;
     use tempfile::NamedTempFile;
 
+    fn at(index: u64, term: u64, entry: &LogEntry) -> Record {
+        Record::Entry {
+            index,
+            entry: Entry {
+                term,
+                entry: entry.clone(),
+            },
+        }
+    }
+
     #[test]
     fn test_wal_write_and_read() -> Result<()> {
         let tmp = NamedTempFile::new()?;
@@ -100,9 +213,9 @@ mod tests {
         let entry2 = LogEntry::Command(b"world".to_vec());
         let entry3 = LogEntry::ConfigChange;
 
-        wal.append(&entry1)?;
-        wal.append(&entry2)?;
-        wal.append(&entry3)?;
+        wal.append(&at(1, 1, &entry1))?;
+        wal.append(&at(2, 1, &entry2))?;
+        wal.append(&at(3, 1, &entry3))?;
 
         let entries = wal.read_all()?;
         assert_eq!(entries.len(), 3);
@@ -122,7 +235,14 @@ mod tests {
 
         {
             let mut wal = WriteAheadLog::open(&path)?;
-            wal.append(&entry)?;
+            wal.append_all(&[
+                Record::HardState {
+                    term: 2,
+                    voted_for: Some(1),
+                },
+                at(1, 2, &entry),
+                Record::Commit(1),
+            ])?;
         }
 
         {
@@ -131,6 +251,9 @@ mod tests {
 
             assert_eq!(entries.len(), 1);
             assert_eq!(entries[0], entry);
+
+            let state = wal_reopened.recover()?;
+            assert_eq!((state.term, state.voted_for, state.commit), (2, Some(1), 1));
         }
 
         Ok(())
@@ -144,7 +267,7 @@ mod tests {
         // 1. Create valid WAL
         {
             let mut wal = WriteAheadLog::open(path)?;
-            wal.append(&LogEntry::Command(b"valid".to_vec()))?;
+            wal.append(&at(1, 1, &LogEntry::Command(b"valid".to_vec())))?;
         }
 
         // 2. Corrupt the file (truncate slightly to break CRC/Len)
@@ -158,7 +281,90 @@ mod tests {
 
         // Should handle the partial read gracefully and return empty (since the only entry is broken)
         assert!(entries.is_empty(), "Should not return corrupted entry");
+        let repair = wal.repair().expect("repair reported");
+        assert_eq!((repair.records, repair.valid_bytes), (0, 0));
+        assert_eq!(repair.reason, Damage::TornWrite);
+        assert_eq!(std::fs::metadata(path)?.len(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_wal_checksum_mismatch_truncates_to_last_valid_record() -> Result<()> {
+        let tmp = NamedTempFile::new()?;
+        let path = tmp.path();
+
+        let kept = LogEntry::Command(b"kept".to_vec());
+        let first_len = {
+            let mut wal = WriteAheadLog::open(path)?;
+            wal.append(&at(1, 1, &kept))?;
+            let first_len = std::fs::metadata(path)?.len();
+            wal.append(&at(2, 1, &LogEntry::Command(b"flipped".to_vec())))?;
+            wal.append(&at(3, 1, &LogEntry::Command(b"after".to_vec())))?;
+            assert!(wal.repair().is_none());
+            first_len
+        };
+
+        // Flip a payload byte of the second record
+        let mut bytes = std::fs::read(path)?;
+        let at_byte = first_len as usize + HEADER_LEN as usize + 1;
+        bytes[at_byte] ^= 0xff;
+        let total = bytes.len() as u64;
+        std::fs::write(path, bytes)?;
+
+        let mut wal = WriteAheadLog::open(path)?;
+        let repair = wal.repair().cloned().expect("repair reported");
+        assert_eq!(repair.reason, Damage::Checksum);
+        assert_eq!(repair.records, 1);
+        assert_eq!(repair.valid_bytes, first_len);
+        assert_eq!(repair.discarded_bytes, total - first_len);
+        assert_eq!(wal.read_all()?, vec![kept.clone()]);
+
+        // The repaired log takes appends and reopens clean
+        let next = LogEntry::Command(b"next".to_vec());
+        wal.append(&at(2, 2, &next))?;
+        drop(wal);
+        let mut wal = WriteAheadLog::open(path)?;
+        assert!(wal.repair().is_none());
+        assert_eq!(wal.read_all()?, vec![kept, next]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wal_garbage_length_is_a_torn_write() -> Result<()> {
+        let tmp = NamedTempFile::new()?;
+        let path = tmp.path();
+        {
+            let mut wal = WriteAheadLog::open(path)?;
+            wal.append(&at(1, 1, &LogEntry::Noop))?;
+        }
+        let valid = std::fs::metadata(path)?.len();
+        let mut file = std::fs::OpenOptions::new().append(true).open(path)?;
+        file.write_all(&u64::MAX.to_le_bytes())?;
+        file.write_all(&[0u8; 20])?;
+        drop(file);
+
+        let mut wal = WriteAheadLog::open(path)?;
+        assert_eq!(wal.repair().map(|r| r.reason), Some(Damage::TornWrite));
+        assert_eq!(wal.read_all()?, vec![LogEntry::Noop]);
+        assert_eq!(std::fs::metadata(path)?.len(), valid);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wal_truncation_replays_to_the_latest_entries() -> Result<()> {
+        let tmp = NamedTempFile::new()?;
+        let mut wal = WriteAheadLog::open(tmp.path())?;
+
+        let old = LogEntry::Command(b"old".to_vec());
+        let new = LogEntry::Command(b"new".to_vec());
+        wal.append(&at(1, 1, &old))?;
+        wal.append(&at(2, 1, &old))?;
+        wal.append(&at(3, 1, &old))?;
+        // A new leader overwrote index 2 onwards
+        wal.append(&at(2, 2, &new))?;
 
+        assert_eq!(wal.read_all()?, vec![old, new]);
         Ok(())
     }
 }
@@ -18,6 +18,23 @@ impl MockSM {
         self.applied.lock().unwrap().clone()
     }
 }
+
+/// Index of whichever node won the election
+async fn leader_of(nodes: &[&Arc<RaftNode>]) -> usize {
+    let start = std::time::Instant::now();
+    loop {
+        for (i, node) in nodes.iter().enumerate() {
+            if node.is_leader().await {
+                return i;
+            }
+        }
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(5),
+            "No leader elected"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+}
 impl StateMachine for MockSM {
     fn apply(&self, command: &[u8]) {
         if let Ok(s) = String::from_utf8(command.to_vec()) {
@@ -26,9 +43,26 @@ impl StateMachine for MockSM {
     }
 }
 
+async fn spawn_node(
+    id: u64,
+    peers: Vec<String>,
+    dir: &std::path::Path,
+) -> (Arc<RaftNode>, Arc<MockSM>) {
+    let sm = Arc::new(MockSM::new());
+    let config = ConsensusConfig {
+        id,
+        peers,
+        storage_path: dir.join(format!("node{}.wal", id)),
+    };
+    let node = RaftNode::new(config, sm.clone())
+        .await
+        .expect("Failed to spawn node");
+    (node, sm)
+}
+
 #[tokio::test]
 #[serial]
-async fn test_3_node_cluster_broadcast() -> Result<()> {
+async fn test_3_node_cluster_replication() -> Result<()> {
     let dir = tempdir()?;
 
     // --- Configs (Ports 10010, 10011, 10012) ---
@@ -38,53 +72,32 @@ async fn test_3_node_cluster_broadcast() -> Result<()> {
         "127.0.0.1:10012".to_string(),
     ];
 
-    // Helper to spawn node
-    async fn spawn_node(
-        id: u64,
-        peers: Vec<String>,
-        dir: &std::path::Path,
-    ) -> (Arc<RaftNode>, Arc<MockSM>) {
-        let sm = Arc::new(MockSM::new());
-        let config = ConsensusConfig {
-            id,
-            peers,
-            storage_path: dir.join(format!("node{}.wal", id)),
-        };
-        let node = RaftNode::new(config, sm.clone())
-            .await
-            .expect("Failed to spawn node");
-        (node, sm)
-    }
-
     // --- Spawn Nodes ---
-    // In this "All-Write" mesh, everyone peers with everyone for simplicity,
-    // or at least Leader (10) needs to know about 11 and 12.
-    // For this test, let's make Node 10 broadcast to 11 and 12.
-    let (node10, sm10) = spawn_node(
-        10,
-        vec!["127.0.0.1:10011".into(), "127.0.0.1:10012".into()],
-        dir.path(),
-    )
-    .await;
-    let (_node11, sm11) = spawn_node(11, vec![], dir.path()).await; // Passive
-    let (_node12, sm12) = spawn_node(12, vec![], dir.path()).await; // Passive
-
-    tokio::time::sleep(Duration::from_millis(200)).await;
+    // Everyone peers with everyone (a node skips its own address)
+    let (node10, sm10) = spawn_node(10, peers_all.clone(), dir.path()).await;
+    let (node11, sm11) = spawn_node(11, peers_all.clone(), dir.path()).await;
+    let (node12, sm12) = spawn_node(12, peers_all.clone(), dir.path()).await;
 
     // --- Action ---
-    println!("Node 10 Broadcasting 'ClusterMessage'...");
-    node10.propose(b"ClusterMessage".to_vec()).await?;
+    let nodes = [&node10, &node11, &node12];
+    let leader = nodes[leader_of(&nodes).await];
+    println!("Leader Proposing 'ClusterMessage'...");
+    leader.propose(b"ClusterMessage".to_vec()).await?;
 
     // --- Verify ---
     // Wait for replication
     let start = std::time::Instant::now();
     loop {
-        if sm11.get_all().len() == 1 && sm12.get_all().len() == 1 {
+        if [&sm10, &sm11, &sm12]
+            .iter()
+            .all(|sm| sm.get_all().len() == 1)
+        {
             break;
         }
         if start.elapsed() > Duration::from_secs(3) {
             panic!(
-                "Replication timeout. Node 11: {:?}, Node 12: {:?}",
+                "Replication timeout. Node 10: {:?}, Node 11: {:?}, Node 12: {:?}",
+                sm10.get_all(),
                 sm11.get_all(),
                 sm12.get_all()
             );
@@ -98,3 +111,29 @@ async fn test_3_node_cluster_broadcast() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+#[serial]
+async fn test_follower_batch_is_forwarded_to_leader() -> Result<()> {
+    let dir = tempdir()?;
+    let peers_all = vec![
+        "127.0.0.1:10020".to_string(),
+        "127.0.0.1:10021".to_string(),
+        "127.0.0.1:10022".to_string(),
+    ];
+    let (node20, sm20) = spawn_node(20, peers_all.clone(), dir.path()).await;
+    let (node21, sm21) = spawn_node(21, peers_all.clone(), dir.path()).await;
+    let (node22, sm22) = spawn_node(22, peers_all.clone(), dir.path()).await;
+
+    let nodes = [&node20, &node21, &node22];
+    let sms = [&sm20, &sm21, &sm22];
+    let follower = (leader_of(&nodes).await + 1) % nodes.len();
+
+    let batch = vec![b"one".to_vec(), b"two".to_vec(), b"three".to_vec()];
+    let index = nodes[follower].propose_batch(batch).await?;
+
+    // Applied on the proposing follower by the time the batch returns
+    assert_eq!(sms[follower].get_all(), ["one", "two", "three"]);
+    assert!(nodes[follower].get_commit_index().await >= index);
+    Ok(())
+}
@@ -22,6 +22,23 @@ impl MockSM {
     }
 }
 
+/// Index of whichever node won the election
+async fn leader_of(nodes: &[&Arc<RaftNode>]) -> usize {
+    let start = std::time::Instant::now();
+    loop {
+        for (i, node) in nodes.iter().enumerate() {
+            if node.is_leader().await {
+                return i;
+            }
+        }
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "No leader elected"
+        );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
 impl StateMachine for MockSM {
     fn apply(&self, command: &[u8]) {
         if let Ok(s) = String::from_utf8(command.to_vec()) {
@@ -35,20 +52,17 @@ impl StateMachine for MockSM {
 async fn test_replication_node_to_node() -> Result<()> {
     let dir = tempdir()?;
 
-    // --- Setup Node 2 (Follower/Peer) ---
+    // --- Setup Node 2 ---
     // ID 2 -> Ports 10002 (Consensus)
     let sm2 = Arc::new(MockSM::new());
     let config2 = ConsensusConfig {
         id: 2,
-        peers: vec![], // Doesn't need to broadcast back for this test
+        peers: vec!["127.0.0.1:10001".to_string()], // Points to Node 1
         storage_path: dir.path().join("node2.wal"),
     };
-    let _node2 = RaftNode::new(config2, sm2.clone()).await?;
-
-    // Give Node 2 a moment to bind listener
-    tokio::time::sleep(Duration::from_millis(100)).await;
+    let node2 = RaftNode::new(config2, sm2.clone()).await?;
 
-    // --- Setup Node 1 (Leader/Proposer) ---
+    // --- Setup Node 1 ---
     // ID 1 -> Port 10001 (Consensus)
     let sm1 = Arc::new(MockSM::new());
     let config1 = ConsensusConfig {
@@ -58,20 +72,27 @@ async fn test_replication_node_to_node() -> Result<()> {
     };
     let node1 = RaftNode::new(config1, sm1.clone()).await?;
 
-    // --- Action: Propose Data ---
-    println!("Node 1 Proposing 'Alpha'...");
-    node1.propose(b"Alpha".to_vec()).await?;
+    // --- Action: Propose Data on whichever node was elected ---
+    let (leader, (sm_leader, sm_follower)) = match leader_of(&[&node1, &node2]).await {
+        0 => (node1, (sm1, sm2)),
+        _ => (node2, (sm2, sm1)),
+    };
+    println!("Leader Proposing 'Alpha'...");
+    leader.propose(b"Alpha".to_vec()).await?;
 
-    println!("Node 1 Proposing 'Beta'...");
-    node1.propose(b"Beta".to_vec()).await?;
+    println!("Leader Proposing 'Beta'...");
+    leader.propose(b"Beta".to_vec()).await?;
+
+    // Committed means the follower has it too; applying follows its next heartbeat
+    let entries_leader = sm_leader.get_all();
+    assert_eq!(entries_leader, vec!["Alpha", "Beta"]);
 
     // --- Wait for Replication ---
-    // Since broadcast is async over TCP, we poll Node 2's state machine
     let start = std::time::Instant::now();
     let mut success = false;
 
     while start.elapsed() < Duration::from_secs(2) {
-        let entries = sm2.get_all();
+        let entries = sm_follower.get_all();
         if entries.len() == 2 {
             assert_eq!(entries[0], "Alpha");
             assert_eq!(entries[1], "Beta");
@@ -81,12 +102,7 @@ async fn test_replication_node_to_node() -> Result<()> {
         tokio::time::sleep(Duration::from_millis(50)).await;
     }
 
-    assert!(success, "Node 2 failed to receive entries within timeout");
-
-    // Verify Node 1 also applied it locally
-    let entries_1 = sm1.get_all();
-    assert_eq!(entries_1.len(), 2);
-    assert_eq!(entries_1[0], "Alpha");
+    assert!(success, "Follower failed to apply entries within timeout");
 
     Ok(())
 }
@@ -0,0 +1,328 @@
+use cell_consensus::raft::{Entry, Message, RaftCore, Record, Recovered, Role, Timing};
+use cell_consensus::LogEntry;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+// --- Simulated Network ---
+// Every node's Ready is drained after each tick and message; messages to or
+// from an isolated node are dropped. No clocks, no sockets: fully deterministic.
+struct Sim {
+    nodes: BTreeMap<u64, RaftCore>,
+    inflight: VecDeque<(u64, u64, Message)>,
+    isolated: HashSet<u64>,
+    applied: HashMap<u64, Vec<LogEntry>>,
+    records: HashMap<u64, Vec<Record>>,
+}
+
+impl Sim {
+    fn new(ids: &[u64]) -> Self {
+        let nodes = ids
+            .iter()
+            .map(|id| (*id, RaftCore::new(*id, ids.to_vec(), Timing::default())))
+            .collect();
+        Self {
+            nodes,
+            inflight: VecDeque::new(),
+            isolated: HashSet::new(),
+            applied: HashMap::new(),
+            records: HashMap::new(),
+        }
+    }
+
+    fn drain(&mut self, id: u64) {
+        let ready = self.nodes.get_mut(&id).unwrap().ready();
+        self.records.entry(id).or_default().extend(ready.records);
+        self.applied.entry(id).or_default().extend(ready.committed);
+        for (to, msg) in ready.messages {
+            self.inflight.push_back((id, to, msg));
+        }
+    }
+
+    fn deliver(&mut self) {
+        while let Some((from, to, msg)) = self.inflight.pop_front() {
+            if self.isolated.contains(&from) || self.isolated.contains(&to) {
+                continue;
+            }
+            self.nodes.get_mut(&to).unwrap().step(from, msg);
+            self.drain(to);
+        }
+    }
+
+    fn run(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            let ids: Vec<u64> = self.nodes.keys().copied().collect();
+            for id in ids {
+                self.nodes.get_mut(&id).unwrap().tick();
+                self.drain(id);
+            }
+            self.deliver();
+        }
+    }
+
+    fn leaders(&self) -> Vec<u64> {
+        self.nodes
+            .values()
+            .filter(|n| n.role() == Role::Leader && !self.isolated.contains(&n.id()))
+            .map(|n| n.id())
+            .collect()
+    }
+
+    fn elect(&mut self) -> u64 {
+        for _ in 0..20 {
+            self.run(10);
+            if let [leader] = self.leaders()[..] {
+                return leader;
+            }
+        }
+        panic!("No single leader elected: {:?}", self.leaders());
+    }
+
+    fn propose(&mut self, id: u64, data: &[u8]) -> u64 {
+        let index = self
+            .nodes
+            .get_mut(&id)
+            .unwrap()
+            .propose(LogEntry::Command(data.to_vec()))
+            .expect("proposed on a follower");
+        self.drain(id);
+        self.deliver();
+        index
+    }
+
+    fn commands(&self, id: u64) -> Vec<Vec<u8>> {
+        self.applied
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| match e {
+                LogEntry::Command(data) => Some(data.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+fn vote(term: u64, last_log_index: u64, last_log_term: u64) -> Message {
+    Message::RequestVote {
+        term,
+        last_log_index,
+        last_log_term,
+    }
+}
+
+fn granted(core: &mut RaftCore) -> bool {
+    match core.ready().messages.pop() {
+        Some((_, Message::Vote { granted, .. })) => granted,
+        other => panic!("Expected a vote, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_elects_exactly_one_leader() {
+    let mut sim = Sim::new(&[1, 2, 3]);
+    let leader = sim.elect();
+
+    let term = sim.nodes[&leader].term();
+    for node in sim.nodes.values() {
+        assert_eq!(node.term(), term);
+        assert_eq!(node.leader(), Some(leader));
+    }
+
+    // Heartbeats keep it in office
+    sim.run(100);
+    assert_eq!(sim.leaders(), vec![leader]);
+    assert_eq!(sim.nodes[&leader].term(), term);
+}
+
+#[test]
+fn test_followers_cannot_propose() {
+    let mut sim = Sim::new(&[1, 2, 3]);
+    let leader = sim.elect();
+    let follower = *sim.nodes.keys().find(|id| **id != leader).unwrap();
+
+    let refused = sim
+        .nodes
+        .get_mut(&follower)
+        .unwrap()
+        .propose(LogEntry::Command(b"x".to_vec()));
+    assert_eq!(refused, Err(Some(leader)));
+}
+
+#[test]
+fn test_commits_only_on_majority_ack() {
+    let mut sim = Sim::new(&[1, 2, 3, 4, 5]);
+    let leader = sim.elect();
+    let followers: Vec<u64> = sim
+        .nodes
+        .keys()
+        .copied()
+        .filter(|id| *id != leader)
+        .collect();
+
+    // Leader plus one follower is 2 of 5
+    sim.isolated.extend(&followers[1..]);
+    let index = sim.propose(leader, b"Alpha");
+    sim.run(50);
+    assert!(sim.nodes[&leader].commit_index() < index);
+    assert!(sim.commands(leader).is_empty());
+
+    // Plus a second follower is 3 of 5
+    sim.isolated.remove(&followers[1]);
+    sim.run(100);
+    let connected = [leader, followers[0], followers[1]];
+    for id in connected {
+        assert_eq!(sim.commands(id), vec![b"Alpha".to_vec()], "node {}", id);
+    }
+    for id in &followers[2..] {
+        assert!(sim.commands(*id).is_empty());
+    }
+
+    // The rest catch up once they are back
+    sim.isolated.clear();
+    sim.run(100);
+    for id in sim.nodes.keys() {
+        assert_eq!(sim.commands(*id), vec![b"Alpha".to_vec()], "node {}", id);
+    }
+}
+
+#[test]
+fn test_uncommitted_entries_of_a_deposed_leader_are_overwritten() {
+    let mut sim = Sim::new(&[1, 2, 3]);
+    let old = sim.elect();
+
+    // Cut off, the old leader still accepts a write it can never commit
+    sim.isolated.insert(old);
+    sim.propose(old, b"Lost");
+    let new = sim.elect();
+    assert_ne!(new, old);
+    assert!(sim.nodes[&new].term() > sim.nodes[&old].term());
+    sim.propose(new, b"Kept");
+    sim.run(20);
+
+    sim.isolated.clear();
+    sim.run(100);
+    assert_eq!(sim.leaders(), vec![new]);
+    assert_eq!(sim.nodes[&old].role(), Role::Follower);
+    for id in sim.nodes.keys() {
+        assert_eq!(sim.commands(*id), vec![b"Kept".to_vec()], "node {}", id);
+        assert_eq!(sim.nodes[id].log(), sim.nodes[&new].log());
+    }
+}
+
+#[test]
+fn test_votes_once_per_term_and_only_for_up_to_date_logs() {
+    let mut core = RaftCore::new(1, vec![2, 3], Timing::default());
+    core.step(2, vote(1, 0, 0));
+    assert!(granted(&mut core));
+    core.step(3, vote(1, 0, 0));
+    assert!(!granted(&mut core));
+    // Asking again is fine: the vote is already theirs
+    core.step(2, vote(1, 0, 0));
+    assert!(granted(&mut core));
+
+    let log = vec![
+        Entry {
+            term: 1,
+            entry: LogEntry::Noop,
+        },
+        Entry {
+            term: 2,
+            entry: LogEntry::Noop,
+        },
+    ];
+    let mut core = RaftCore::restore(
+        1,
+        vec![2, 3],
+        Timing::default(),
+        Recovered {
+            term: 2,
+            voted_for: None,
+            log,
+            commit: 0,
+        },
+    );
+    // Longer, but its last term is older
+    core.step(2, vote(3, 5, 1));
+    assert!(!granted(&mut core));
+    assert_eq!(core.term(), 3);
+    // Same last term, shorter log
+    core.step(3, vote(4, 1, 2));
+    assert!(!granted(&mut core));
+    core.step(3, vote(5, 2, 2));
+    assert!(granted(&mut core));
+}
+
+#[test]
+fn test_rejects_appends_that_do_not_match_its_log() {
+    let mut core = RaftCore::new(2, vec![1, 3], Timing::default());
+    let append = |prev_log_index, prev_log_term, term| Message::AppendEntries {
+        term,
+        prev_log_index,
+        prev_log_term,
+        entries: vec![Entry {
+            term,
+            entry: LogEntry::Command(vec![1]),
+        }],
+        leader_commit: 0,
+    };
+    let response = |core: &mut RaftCore| match core.ready().messages.pop() {
+        Some((
+            1,
+            Message::AppendResponse {
+                success,
+                match_index,
+                ..
+            },
+        )) => (success, match_index),
+        other => panic!("Expected an append response, got {:?}", other),
+    };
+
+    // Nothing at index 3 to match against
+    core.step(1, append(3, 1, 1));
+    assert_eq!(response(&mut core), (false, 0));
+    core.step(1, append(0, 0, 1));
+    assert_eq!(response(&mut core), (true, 1));
+    // Index 1 holds term 1, not 2
+    core.step(1, append(1, 2, 2));
+    assert_eq!(response(&mut core), (false, 0));
+    // A deposed leader's appends are refused outright
+    core.step(1, append(1, 1, 1));
+    assert_eq!(response(&mut core), (false, 1));
+    assert_eq!(core.term(), 2);
+}
+
+#[test]
+fn test_restart_replays_committed_entries_only() {
+    let mut sim = Sim::new(&[1, 2, 3]);
+    let leader = sim.elect();
+    sim.propose(leader, b"Alpha");
+    sim.propose(leader, b"Beta");
+    sim.run(10);
+    sim.isolated.extend(
+        sim.nodes
+            .keys()
+            .copied()
+            .filter(|id| *id != leader)
+            .collect::<Vec<_>>(),
+    );
+    sim.propose(leader, b"Pending");
+
+    let recovered = Recovered::replay(sim.records[&leader].clone());
+    assert_eq!(recovered.term, sim.nodes[&leader].term());
+    assert_eq!(recovered.log, sim.nodes[&leader].log());
+
+    let mut restarted = RaftCore::restore(leader, vec![1, 2, 3], Timing::default(), recovered);
+    assert_eq!(restarted.role(), Role::Follower);
+    let commands: Vec<LogEntry> = restarted
+        .ready()
+        .committed
+        .into_iter()
+        .filter(|e| matches!(e, LogEntry::Command(_)))
+        .collect();
+    assert_eq!(
+        commands,
+        vec![
+            LogEntry::Command(b"Alpha".to_vec()),
+            LogEntry::Command(b"Beta".to_vec())
+        ]
+    );
+}
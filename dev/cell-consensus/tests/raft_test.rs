@@ -23,52 +23,77 @@ impl StateMachine for MockSM {
     }
 }
 
+/// Index of whichever node won the election
+async fn leader_of(nodes: &[&Arc<RaftNode>]) -> usize {
+    let start = std::time::Instant::now();
+    loop {
+        for (i, node) in nodes.iter().enumerate() {
+            if node.is_leader().await {
+                return i;
+            }
+        }
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(5),
+            "No leader elected"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+}
+
 #[tokio::test]
 #[serial]
 async fn test_large_log_replication() -> Result<()> {
     let dir = tempdir()?;
 
-    // Node 20 (Leader) -> Node 21 (Follower)
+    // Node 20 <-> Node 21
     let sm20 = Arc::new(MockSM::new());
     let config20 = ConsensusConfig {
         id: 20,
         peers: vec!["127.0.0.1:10021".to_string()],
         storage_path: dir.path().join("node20.wal"),
     };
-    let node20 = RaftNode::new(config20, sm20.clone()).await?;
+    let node20 = RaftNode::new(config20.clone(), sm20.clone()).await?;
 
     let sm21 = Arc::new(MockSM::new());
     let config21 = ConsensusConfig {
         id: 21,
-        peers: vec![],
+        peers: vec!["127.0.0.1:10020".to_string()],
         storage_path: dir.path().join("node21.wal"),
     };
     // FIX: Clone config21 here so we can use it later to check disk
-    let _node21 = RaftNode::new(config21.clone(), sm21.clone()).await?;
+    let node21 = RaftNode::new(config21.clone(), sm21.clone()).await?;
 
-    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    // The follower is checked on disk below
+    let (leader, follower_sm, follower_config) = match leader_of(&[&node20, &node21]).await {
+        0 => (node20.clone(), sm21.clone(), config21.clone()),
+        _ => (node21.clone(), sm20.clone(), config20.clone()),
+    };
 
     // Send 100 messages
     println!("sending 100 logs...");
     for i in 0..100 {
-        node20.propose(vec![i as u8]).await?;
+        leader.propose(vec![i as u8]).await?;
     }
 
     // Wait for consistency
     let start = std::time::Instant::now();
     loop {
-        if sm21.len() == 100 {
+        if follower_sm.len() == 100 {
             break;
         }
         if start.elapsed() > std::time::Duration::from_secs(5) {
-            panic!("Failed to replicate 100 logs. Got: {}", sm21.len());
+            panic!("Failed to replicate 100 logs. Got: {}", follower_sm.len());
         }
         tokio::time::sleep(std::time::Duration::from_millis(50)).await;
     }
 
-    // Check WAL persistence on disk for Node 21
-    let mut wal = cell_consensus::wal::WriteAheadLog::open(&config21.storage_path)?;
-    let entries = wal.read_all()?;
+    // Check WAL persistence on disk for the follower; leaders add a Noop each
+    let mut wal = cell_consensus::wal::WriteAheadLog::open(&follower_config.storage_path)?;
+    let entries: Vec<LogEntry> = wal
+        .read_all()?
+        .into_iter()
+        .filter(|e| *e != LogEntry::Noop)
+        .collect();
     assert_eq!(entries.len(), 100);
     assert_eq!(entries[99], LogEntry::Command(vec![99]));
 
@@ -3,6 +3,8 @@
 
 use serde::{Deserialize, Serialize};
 use rkyv::{Archive, Serialize as RkyvSerialize, Deserialize as RkyvDeserialize};
+use cell_model::placement::{GpuDevice, NodeProfile, PowerState};
+use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Archive, RkyvSerialize, RkyvDeserialize, Default)]
 #[archive(check_bytes)]
@@ -11,23 +13,170 @@ pub struct HardwareCaps {
     pub total_memory_mb: u64,
     pub has_avx512: bool,
     pub has_gpu: bool,
+    pub gpus: Vec<GpuDevice>,
     pub is_tee: bool, // Trusted Execution Environment
     pub load_avg: f32,
     pub thermal_zone_temp: Option<f32>,
+    pub power: PowerState,
 }
 
 impl HardwareCaps {
     pub fn scan() -> Self {
         // In a real impl, utilize 'sysinfo' and 'raw-cpuid' crates
         // Mocking detection for this implementation to avoid huge dep trees in example code
+        let gpus = scan_gpus();
+        let power = scan_power();
         Self {
             cpu_cores: std::thread::available_parallelism().map(|n| n.get() as u32).unwrap_or(1),
             total_memory_mb: 16384,
             has_avx512: std::is_x86_feature_detected!("avx"), // approximating
-            has_gpu: std::path::Path::new("/dev/nvidia0").exists(),
+            has_gpu: !gpus.is_empty(),
+            gpus,
             is_tee: std::path::Path::new("/dev/sev").exists(),
             load_avg: 0.1, // Mock
-            thermal_zone_temp: Some(45.0),
+            thermal_zone_temp: power.temp_c,
+            power,
         }
     }
-}
\ No newline at end of file
+
+    /// Profile reported to the nucleus scheduler.
+    pub fn profile(&self, node_id: u64, address: &str) -> NodeProfile {
+        NodeProfile {
+            node_id,
+            address: address.to_string(),
+            gpus: self.gpus.clone(),
+            has_avx512: self.has_avx512,
+            is_tee: self.is_tee,
+            power: self.power.clone(),
+        }
+    }
+}
+
+/// Battery and thermal state from sysfs. Hosts without a battery or thermal
+/// zones (most servers, non-Linux) report mains power and no temperature.
+pub fn scan_power() -> PowerState {
+    let mut power = PowerState::default();
+
+    if let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") {
+        for supply in entries.flatten().map(|e| e.path()) {
+            let read = |f: &str| std::fs::read_to_string(supply.join(f)).map(|s| s.trim().to_string());
+            if read("type").as_deref() != Ok("Battery") {
+                continue;
+            }
+            if read("status").as_deref() == Ok("Discharging") {
+                power.on_battery = true;
+            }
+            if let Some(pct) = read("capacity").ok().and_then(|c| c.parse::<u8>().ok()) {
+                power.battery_pct = Some(power.battery_pct.map_or(pct, |p| p.min(pct)));
+            }
+        }
+    }
+
+    if let Ok(entries) = std::fs::read_dir("/sys/class/thermal") {
+        for zone in entries.flatten().map(|e| e.path()) {
+            let read_milli = |f: &str| {
+                std::fs::read_to_string(zone.join(f))
+                    .ok()
+                    .and_then(|s| s.trim().parse::<i64>().ok())
+                    .map(|m| m as f32 / 1000.0)
+            };
+            let Some(temp) = read_milli("temp") else { continue };
+            power.temp_c = Some(power.temp_c.map_or(temp, |t| t.max(temp)));
+
+            // Past the first passive trip point the kernel starts throttling
+            for i in 0.. {
+                let Ok(kind) = std::fs::read_to_string(zone.join(format!("trip_point_{}_type", i))) else {
+                    break;
+                };
+                if kind.trim() == "passive" {
+                    if read_milli(&format!("trip_point_{}_temp", i)).is_some_and(|trip| temp >= trip) {
+                        power.throttled = true;
+                    }
+                    break;
+                }
+            }
+        }
+    }
+    power
+}
+
+/// Enumerate GPUs: `nvidia-smi` for NVIDIA, DRM render nodes for everything else.
+pub fn scan_gpus() -> Vec<GpuDevice> {
+    let mut gpus = scan_nvidia();
+
+    if let Ok(entries) = std::fs::read_dir("/sys/class/drm") {
+        let mut cards: Vec<_> = entries
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|n| n.starts_with("card") && !n.contains('-'))
+            .collect();
+        cards.sort();
+
+        for card in cards {
+            let dev = Path::new("/sys/class/drm").join(&card).join("device");
+            let vendor = match std::fs::read_to_string(dev.join("vendor")).unwrap_or_default().trim() {
+                "0x1002" => "amd",
+                "0x8086" => "intel",
+                // NVIDIA is covered by nvidia-smi, which also knows its memory
+                _ => continue,
+            };
+            let memory_mb = std::fs::read_to_string(dev.join("mem_info_vram_total"))
+                .ok()
+                .and_then(|s| s.trim().parse::<u64>().ok())
+                .map(|b| b / (1024 * 1024))
+                .unwrap_or(0);
+            let render = std::fs::read_dir(dev.join("drm"))
+                .ok()
+                .and_then(|e| {
+                    e.flatten()
+                        .map(|e| e.file_name().to_string_lossy().to_string())
+                        .find(|n| n.starts_with("renderD"))
+                })
+                .map(|n| format!("/dev/dri/{}", n))
+                .unwrap_or_else(|| format!("/dev/dri/{}", card));
+            gpus.push(GpuDevice {
+                vendor: vendor.to_string(),
+                name: card,
+                memory_mb,
+                device: render,
+            });
+        }
+    }
+
+    if cfg!(all(target_os = "macos", target_arch = "aarch64")) && gpus.is_empty() {
+        gpus.push(GpuDevice {
+            vendor: "apple".to_string(),
+            name: "Apple Silicon".to_string(),
+            memory_mb: 0, // unified memory
+            device: String::new(),
+        });
+    }
+    gpus
+}
+
+fn scan_nvidia() -> Vec<GpuDevice> {
+    let Ok(out) = std::process::Command::new("nvidia-smi")
+        .args(["--query-gpu=index,name,memory.total", "--format=csv,noheader,nounits"])
+        .output()
+    else {
+        return Vec::new();
+    };
+    if !out.status.success() {
+        return Vec::new();
+    }
+    String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut cols = line.split(',').map(str::trim);
+            let index = cols.next()?;
+            let name = cols.next()?;
+            let memory_mb = cols.next()?.parse().ok()?;
+            Some(GpuDevice {
+                vendor: "nvidia".to_string(),
+                name: name.to_string(),
+                memory_mb,
+                device: format!("/dev/nvidia{}", index),
+            })
+        })
+        .collect()
+}
@@ -25,9 +25,21 @@ mod tests {
             total_memory_mb: 512000,
             has_avx512: true,
             has_gpu: true,
+            gpus: vec![cell_model::placement::GpuDevice {
+                vendor: "nvidia".into(),
+                name: "test".into(),
+                memory_mb: 24576,
+                device: "/dev/nvidia0".into(),
+            }],
             is_tee: true,
             load_avg: 0.5,
             thermal_zone_temp: Some(60.0),
+            power: cell_model::placement::PowerState {
+                on_battery: true,
+                battery_pct: Some(42),
+                temp_c: Some(60.0),
+                throttled: false,
+            },
         };
 
         let bytes = rkyv::to_bytes::<_, 256>(&original).expect("Serialize failed");
@@ -37,5 +49,7 @@ mod tests {
         assert_eq!(original.cpu_cores, deserialized.cpu_cores);
         assert_eq!(original.has_avx512, deserialized.has_avx512);
         assert_eq!(original.is_tee, deserialized.is_tee);
+        assert_eq!(original.gpus, deserialized.gpus);
+        assert_eq!(original.power, deserialized.power);
     }
 }
\ No newline at end of file
@@ -77,6 +77,10 @@ async fn main() -> Result<()> {
 
     // 3. Start Raft Node
     let raft = RaftNode::new(raft_config, state_machine.clone()).await?;
+    cell_sdk::status::set_consensus_role("Enabled");
+    if let Some(repair) = raft.wal_repair() {
+        cell_sdk::status::report_wal_repair(repair.to_string());
+    }
 
     // 4. Bind Cell Membrane
     let raft_handle = raft.clone();
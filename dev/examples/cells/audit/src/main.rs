@@ -49,18 +49,15 @@ impl AuditService {
     }
 }
 
-#[handler]
-impl AuditService {
-    async fn log(&self, event: AuditEvent) -> Result<u64> {
-        let mut state = self.state.write().await;
-
-        let prev_hash = state
+impl AuditState {
+    fn append(&mut self, event: AuditEvent) -> u64 {
+        let prev_hash = self
             .chain
             .last()
             .map(|e| e.hash.clone())
             .unwrap_or_else(|| vec![0u8; 32]);
 
-        let id = state.chain.len() as u64 + 1;
+        let id = self.chain.len() as u64 + 1;
 
         // Merkle Chain Hashing
         let mut hasher = blake3::Hasher::new();
@@ -78,7 +75,24 @@ impl AuditService {
             hash,
         };
 
-        state.chain.push(signed);
+        self.chain.push(signed);
+        id
+    }
+}
+
+#[handler]
+impl AuditService {
+    async fn log(&self, event: AuditEvent) -> Result<u64> {
+        Ok(self.state.write().await.append(event))
+    }
+
+    /// For producers using `telemetry::Batcher`. Returns the id of the last event.
+    async fn log_batch(&self, events: Vec<AuditEvent>) -> Result<u64> {
+        let mut state = self.state.write().await;
+        let mut id = state.chain.len() as u64;
+        for event in events {
+            id = state.append(event);
+        }
         Ok(id)
     }
 
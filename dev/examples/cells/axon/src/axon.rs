@@ -54,6 +54,11 @@ impl AxonServer {
         })
     }
 
+    /// A QUIC endpoint accepting connections on `addr`
+    pub fn bind(addr: SocketAddr) -> Result<quinn::Endpoint> {
+        Ok(quinn::Endpoint::server(make_server_config()?, addr)?)
+    }
+
     #[allow(dead_code)]
     pub async fn accept(&self) -> Option<quinn::Connecting> {
         use futures::stream::{FuturesUnordered, StreamExt};
@@ -0,0 +1,226 @@
+// cells/axon/src/files.rs
+// SPDX-License-Identifier: MIT
+// File transfer between nodes, for compiled cell binaries.
+//
+// With CELL_FILES_ADDR set, Axon serves the artifact store (~/.cell/artifacts)
+// over QUIC there. A peer opens a stream, sends [len u32][grant json] and
+// [len u32][relative path] and reads back [size u64][bytes], or u64::MAX when
+// there is no such file. The grant is an ImpersonationGrant for "axon" issued
+// with the mesh admin key; without one that verifies the stream is dropped, and
+// a node without the admin key serves nothing. Paths are plain relative
+// components, so nothing outside the store can be read.
+//
+// Files are only fetched from the file servers listed in CELL_ARTIFACT_PEERS.
+// Artifacts carry their own signature; the fetching side checks it.
+
+use crate::axon::{AxonClient, AxonServer};
+use anyhow::{bail, Context, Result};
+use cell_model::auth::ImpersonationGrant;
+use cell_sdk::auth::AdminKey;
+use std::net::SocketAddr;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tracing::{info, warn};
+
+/// Longest path a peer may ask for
+const MAX_PATH: usize = 1024;
+/// Largest grant a peer may present
+const MAX_GRANT: usize = 4096;
+/// Lifetime of the grant presented with each fetch
+const GRANT_TTL_SECS: u64 = 60;
+/// Largest file fetched from a peer
+const MAX_FILE: u64 = 1 << 30;
+/// Size sent for a file the store does not have
+const MISSING: u64 = u64::MAX;
+
+fn store_root() -> Result<PathBuf> {
+    Ok(dirs::home_dir().context("No HOME")?.join(".cell/artifacts"))
+}
+
+/// `path` below the store root, if it names a file inside it
+fn resolve(root: &Path, path: &str) -> Option<PathBuf> {
+    let relative = Path::new(path);
+    if path.is_empty()
+        || !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+    {
+        return None;
+    }
+    Some(root.join(relative))
+}
+
+/// File servers this node may fetch from (CELL_ARTIFACT_PEERS, comma separated)
+fn configured_peers() -> Vec<String> {
+    std::env::var("CELL_ARTIFACT_PEERS")
+        .map(|v| {
+            v.split(',')
+                .map(|p| p.trim().to_string())
+                .filter(|p| !p.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Serve the artifact store on CELL_FILES_ADDR, if set
+pub async fn ignite() -> Result<()> {
+    let Ok(addr) = std::env::var("CELL_FILES_ADDR") else {
+        return Ok(());
+    };
+    let addr: SocketAddr = addr
+        .parse()
+        .with_context(|| format!("CELL_FILES_ADDR {} is not an address", addr))?;
+    let admin = match AdminKey::load_default() {
+        Ok(key) => Arc::new(key),
+        Err(e) => {
+            warn!(
+                "[Axon] Not serving artifacts, peers cannot be authenticated: {}",
+                e
+            );
+            return Ok(());
+        }
+    };
+    let endpoint = AxonServer::bind(addr)?;
+    info!("[Axon] Serving artifacts on {}", endpoint.local_addr()?);
+
+    tokio::spawn(async move {
+        while let Some(connecting) = endpoint.accept().await {
+            let admin = admin.clone();
+            tokio::spawn(async move {
+                let conn = match connecting.await {
+                    Ok(conn) => conn,
+                    Err(e) => return warn!("[Axon] File peer failed to connect: {}", e),
+                };
+                while let Ok((send, recv)) = conn.accept_bi().await {
+                    let admin = admin.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = serve(&admin, send, recv).await {
+                            warn!("[Axon] File transfer failed: {}", e);
+                        }
+                    });
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+/// One `[len u32][bytes]` field of a request, at most `max` bytes long
+async fn read_field(recv: &mut quinn::RecvStream, max: usize, what: &str) -> Result<Vec<u8>> {
+    let mut len = [0u8; 4];
+    recv.read_exact(&mut len).await?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > max {
+        bail!("{} is {} bytes long", what, len);
+    }
+    let mut field = vec![0u8; len];
+    recv.read_exact(&mut field).await?;
+    Ok(field)
+}
+
+async fn serve(
+    admin: &AdminKey,
+    mut send: quinn::SendStream,
+    mut recv: quinn::RecvStream,
+) -> Result<()> {
+    let grant = read_field(&mut recv, MAX_GRANT, "Grant").await?;
+    let grant: ImpersonationGrant =
+        serde_json::from_slice(&grant).context("File request grant is corrupt")?;
+    admin
+        .verify(&grant, "axon")
+        .context("File request not authenticated")?;
+
+    let path = read_field(&mut recv, MAX_PATH, "Requested path").await?;
+    let path = String::from_utf8(path).context("Requested path is not UTF-8")?;
+
+    let file = match resolve(&store_root()?, &path) {
+        Some(file) => tokio::fs::File::open(file).await.ok(),
+        None => {
+            warn!(
+                "[Axon] Refused file request from {} for {:?}",
+                grant.principal, path
+            );
+            None
+        }
+    };
+    let Some(mut file) = file else {
+        send.write_all(&MISSING.to_le_bytes()).await?;
+        send.finish().await?;
+        return Ok(());
+    };
+    let size = file.metadata().await?.len();
+    send.write_all(&size.to_le_bytes()).await?;
+    tokio::io::copy(&mut file, &mut send).await?;
+    send.finish().await?;
+    Ok(())
+}
+
+/// Fetch `path` from the artifact store served at `peer` into a local file
+/// under ~/.cell/incoming. `None` when the peer does not have it.
+pub async fn fetch(peer: &str, path: &str) -> Result<Option<PathBuf>> {
+    if !configured_peers().iter().any(|p| p == peer) {
+        bail!("{} is not in CELL_ARTIFACT_PEERS", peer);
+    }
+    if path.len() > MAX_PATH || resolve(Path::new(""), path).is_none() {
+        bail!("Not a path in the artifact store: {:?}", path);
+    }
+    let node = format!(
+        "node:{}",
+        std::env::var("CELL_NODE_ID").unwrap_or_else(|_| "1".to_string())
+    );
+    let grant = AdminKey::load_default()
+        .context("Fetching artifacts needs the mesh admin key")?
+        .issue(
+            &node,
+            "axon",
+            &node,
+            "system",
+            "artifact fetch",
+            GRANT_TTL_SECS,
+        );
+    let grant = serde_json::to_vec(&grant)?;
+
+    let conn = AxonClient::connect_exact(peer)
+        .await?
+        .with_context(|| format!("No file server at {}", peer))?;
+    let (mut send, mut recv) = conn.open_bi().await?;
+    send.write_all(&(grant.len() as u32).to_le_bytes()).await?;
+    send.write_all(&grant).await?;
+    send.write_all(&(path.len() as u32).to_le_bytes()).await?;
+    send.write_all(path.as_bytes()).await?;
+    send.finish().await?;
+
+    let mut size = [0u8; 8];
+    recv.read_exact(&mut size).await?;
+    let size = u64::from_le_bytes(size);
+    if size == MISSING {
+        return Ok(None);
+    }
+    if size > MAX_FILE {
+        bail!(
+            "{} from {} is {} bytes, more than {}",
+            path,
+            peer,
+            size,
+            MAX_FILE
+        );
+    }
+
+    let dir = dirs::home_dir().context("No HOME")?.join(".cell/incoming");
+    tokio::fs::create_dir_all(&dir).await?;
+    let local = dir.join(format!("{:016x}", rand::random::<u64>()));
+    let mut file = tokio::fs::File::create(&local).await?;
+    let copied = tokio::io::copy(&mut tokio::io::AsyncReadExt::take(recv, size), &mut file).await;
+    file.flush().await?;
+    match copied {
+        Ok(n) if n == size => Ok(Some(local)),
+        result => {
+            let _ = tokio::fs::remove_file(&local).await;
+            match result {
+                Ok(n) => bail!("{} from {} ended after {} of {} bytes", path, peer, n, size),
+                Err(e) => Err(e.into()),
+            }
+        }
+    }
+}
@@ -1,9 +1,22 @@
 // cells/axon/src/main.rs
 // SPDX-License-Identifier: MIT
 // The Network Gateway Cell. Handles QUIC, Pheromones, and WAN routing.
+//
+// A mounted target can be split across several cells with `set_weights`:
+// each new connection to its proxy then tunnels to one of them, chosen by
+// weight (cell_model::routing). The swap-coordinator runs canaries this way.
+//
+// CELL_VIEWER_ADDR opens a read-only viewer proxy for dashboards outside the
+// mesh: status, metrics, health, SLOs and topology, nothing else (viewer.rs).
+//
+// CELL_FILES_ADDR serves this node's compiled cell binaries to nodes holding
+// the mesh admin key; `fetch_file` downloads them from a peer's, if it is one
+// of CELL_ARTIFACT_PEERS (files.rs).
 
 mod axon;
+mod files;
 mod pheromones;
+mod viewer;
 
 use cell_sdk::*;
 use cell_sdk::resolve_socket_dir;
@@ -11,12 +24,13 @@ use axon::{AxonServer, AxonClient};
 use pheromones::PheromoneSystem;
 use cell_model::bridge::{BridgeResponse}; // Removed BridgeRequest
 use cell_model::protocol::{SHM_UPGRADE_REQUEST, SHM_UPGRADE_ACK};
+use cell_model::routing::{self, Backend};
 use anyhow::{Result, Context};
 use tracing::{info, warn, error};
 use tokio::net::{UnixListener, UnixStream};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use std::collections::{HashMap};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use tokio::sync::Mutex;
 // Removed PathBuf
 use std::os::unix::io::AsRawFd;
@@ -25,16 +39,39 @@ use std::os::unix::io::AsRawFd;
 use cell_transport::shm::{RingBuffer};
 use cell_transport::membrane::{get_shm_auth_token, send_fds};
 
+#[protein]
+pub struct RouteWeight {
+    pub cell: String,
+    pub weight: u32,
+}
+
 /// The Proxy Manager creates on-demand tunnels
 struct ProxyManager {
     proxies: Arc<Mutex<HashMap<String, String>>>, // Map target -> socket_path
+    weights: Arc<RwLock<HashMap<String, Vec<Backend>>>>, // Map target -> split
 }
 
 impl ProxyManager {
     fn new() -> Self {
         Self {
             proxies: Arc::new(Mutex::new(HashMap::new())),
+            weights: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Split new connections to `target` across `backends`; empty sends them
+    /// all to `target` again.
+    fn set_weights(&self, target: &str, backends: Vec<Backend>) -> Result<()> {
+        let mut weights = self.weights.write().unwrap();
+        if backends.is_empty() {
+            weights.remove(target);
+            info!("[Axon] Routing all of '{}' to itself", target);
+            return Ok(());
         }
+        routing::validate(&backends).map_err(|e| anyhow::anyhow!("{}: {}", target, e))?;
+        info!("[Axon] Routing '{}' as {:?}", target, backends);
+        weights.insert(target.to_string(), backends);
+        Ok(())
     }
 
     async fn ensure_proxy(&self, target: &str) -> Result<String> {
@@ -63,13 +100,14 @@ impl ProxyManager {
         let listener = UnixListener::bind(&path).context("Failed to bind proxy socket")?;
         
         let target_clone = target.to_string();
+        let weights = self.weights.clone();
         
         tokio::spawn(async move {
             info!("[Axon] Spawning proxy for '{}' at {:?}", target_clone, path);
             loop {
                 match listener.accept().await {
                     Ok((stream, _)) => {
-                        let target = target_clone.clone();
+                        let target = route(&weights, &target_clone);
                         tokio::spawn(async move {
                             // Fixed: _e to suppress unused variable warning
                             if let Err(_e) = handle_smart_proxy_connection(&target, stream).await {
@@ -91,6 +129,16 @@ impl ProxyManager {
     }
 }
 
+/// Cell the next connection to `target` tunnels to
+fn route(weights: &RwLock<HashMap<String, Vec<Backend>>>, target: &str) -> String {
+    let weights = weights.read().unwrap();
+    weights
+        .get(target)
+        .and_then(|backends| routing::pick(backends, rand::random()))
+        .unwrap_or(target)
+        .to_string()
+}
+
 /// Handles a connection to the proxy socket.
 /// Supports "Smart Bridging": If client requests SHM, we bridge SHM <-> QUIC directly.
 /// Otherwise, we bridge Unix <-> QUIC.
@@ -258,6 +306,21 @@ impl AxonService {
             }
         }
     }
+
+    /// Send new connections for `target` to `weights`' cells in proportion to
+    /// their weights. An empty list routes everything to `target` again.
+    async fn set_weights(&self, target: String, weights: Vec<RouteWeight>) -> Result<()> {
+        let backends = weights.into_iter().map(|w| Backend::new(w.cell, w.weight)).collect();
+        self.proxy_manager.set_weights(&target, backends)
+    }
+
+    /// Download `path` from the artifact store of the node whose Axon serves
+    /// files on `peer`, which must be in CELL_ARTIFACT_PEERS. Returns the
+    /// local copy, or None if the peer lacks it.
+    async fn fetch_file(&self, peer: String, path: String) -> Result<Option<String>> {
+        let local = files::fetch(&peer, &path).await?;
+        Ok(local.map(|p| p.to_string_lossy().into_owned()))
+    }
 }
 
 #[tokio::main]
@@ -273,6 +336,8 @@ async fn main() -> Result<()> {
     // 1. Infrastructure (Discovery + QUIC Listener)
     let _pheromones = PheromoneSystem::ignite(node_id).await?;
     let _server = AxonServer::ignite("axon", node_id).await?; 
+    viewer::ignite().await?;
+    files::ignite().await?;
 
     // 2. Proxy Manager
     let proxy_manager = Arc::new(ProxyManager::new());
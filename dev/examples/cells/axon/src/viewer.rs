@@ -0,0 +1,138 @@
+// cells/axon/src/viewer.rs
+// SPDX-License-Identifier: MIT
+// Read-only introspection for dashboards outside the mesh.
+//
+// With CELL_VIEWER_ADDR set, Axon listens there for viewers speaking
+// newline-delimited JSON: one cell_model::view::ViewRequest per line, one
+// ViewResponse per line back. A request decodes into that enum or is refused,
+// and each variant maps to one fixed read-only query, so nothing a viewer
+// sends can mount a proxy, change weights or reach a cell's handlers.
+
+use anyhow::{Context, Result};
+use cell_discovery::Discovery;
+use cell_model::ops::{OpsRequest, OpsResponse};
+use cell_model::view::{TopologyEntry, ViewRequest, ViewResponse};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{info, warn};
+
+/// Longest request line a viewer may send
+const MAX_LINE: u64 = 4096;
+
+/// Serve viewers on CELL_VIEWER_ADDR, if set
+pub async fn ignite() -> Result<()> {
+    let Ok(addr) = std::env::var("CELL_VIEWER_ADDR") else {
+        return Ok(());
+    };
+    let listener = TcpListener::bind(&addr)
+        .await
+        .with_context(|| format!("Failed to bind viewer address {}", addr))?;
+    info!(
+        "[Axon] Read-only viewer proxy on {}",
+        listener.local_addr()?
+    );
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, peer)) => {
+                    tokio::spawn(async move {
+                        if let Err(e) = serve(stream).await {
+                            warn!("[Axon] Viewer {} dropped: {}", peer, e);
+                        }
+                    });
+                }
+                Err(e) => warn!("[Axon] Viewer accept error: {}", e),
+            }
+        }
+    });
+    Ok(())
+}
+
+async fn serve(stream: TcpStream) -> Result<()> {
+    let (read, mut write) = stream.into_split();
+    let mut reader = BufReader::new(read);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let n = (&mut reader)
+            .take(MAX_LINE)
+            .read_until(b'\n', &mut line)
+            .await?;
+        if n == 0 {
+            return Ok(());
+        }
+        let too_long = line.last() != Some(&b'\n') && n as u64 == MAX_LINE;
+
+        let resp = match serde_json::from_slice::<ViewRequest>(&line) {
+            _ if too_long => ViewResponse::Error {
+                message: format!("Request longer than {} bytes", MAX_LINE),
+            },
+            Ok(req) => answer(req).await,
+            Err(e) => ViewResponse::Error {
+                message: format!("Not a view request: {}", e),
+            },
+        };
+        let mut out = serde_json::to_vec(&resp)?;
+        out.push(b'\n');
+        write.write_all(&out).await?;
+        if too_long {
+            return Ok(());
+        }
+    }
+}
+
+async fn answer(req: ViewRequest) -> ViewResponse {
+    let Some((cell, query)) = req.ops() else {
+        return ViewResponse::Topology(topology().await);
+    };
+    // The name ends up in a socket path, so nothing but a plain cell name
+    if !is_cell_name(cell) {
+        return ViewResponse::Error {
+            message: format!("{:?} is not a cell name", cell),
+        };
+    }
+    let answer = match query {
+        OpsRequest::Status => cell_sdk::status::fetch(cell).await,
+        OpsRequest::Metrics => cell_sdk::metrics::fetch(cell)
+            .await
+            .map(OpsResponse::Metrics),
+        OpsRequest::Prometheus => cell_sdk::metrics::scrape(cell)
+            .await
+            .map(|text| OpsResponse::Prometheus { text }),
+        OpsRequest::HealthCheck => Ok(OpsResponse::HealthCheck(
+            cell_sdk::watchdog::check(cell).await,
+        )),
+        OpsRequest::Slo => cell_sdk::slo::fetch(cell).await.map(OpsResponse::Slo),
+        other => Err(anyhow::anyhow!("{:?} is not a view", other)),
+    };
+    match answer {
+        Ok(resp) => ViewResponse::Ops(resp),
+        Err(e) => ViewResponse::Error {
+            message: e.to_string(),
+        },
+    }
+}
+
+fn is_cell_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+}
+
+async fn topology() -> Vec<TopologyEntry> {
+    let mut entries = Vec::new();
+    for mut node in Discovery::scan().await {
+        node.probe().await;
+        entries.push(TopologyEntry {
+            // LAN signals are only kept while their cells keep announcing
+            alive: node.status.is_alive || node.lan_address.is_some(),
+            cell: node.name,
+            instance_id: node.instance_id,
+            lan_address: node.lan_address,
+            local: node.local_socket.is_some(),
+        });
+    }
+    entries
+}
@@ -21,4 +21,20 @@ async fn axon_gateway_mounts_remote_cell() {
     } else {
         panic!("Axon failed to mount local cell: {:?}", resp);
     }
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn axon_rejects_weights_that_route_nowhere() {
+    cell_sdk::System::ignite_local_cluster().await.unwrap();
+    let mut axon = Axon::Client::connect().await.expect("Axon not running");
+
+    let canary = vec![
+        Axon::RouteWeight { cell: "ledger-v2".into(), weight: 90 },
+        Axon::RouteWeight { cell: "ledger-v2-new".into(), weight: 10 },
+    ];
+    axon.set_weights("ledger-v2".into(), canary).await.unwrap();
+
+    let nowhere = vec![Axon::RouteWeight { cell: "ledger-v2".into(), weight: 0 }];
+    assert!(axon.set_weights("ledger-v2".into(), nowhere).await.is_err());
+
+    axon.set_weights("ledger-v2".into(), vec![]).await.unwrap();
+}
@@ -3,11 +3,14 @@
 // Encrypted Backup Orchestration
 
 use cell_sdk::*;
+use cell_sdk::wal_archive::ArchiveCatalog;
 use anyhow::Result;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+cell_remote!(Blobstore = "blobstore");
+
 #[protein]
 pub struct BackupJob {
     pub cell_name: String,
@@ -21,9 +24,18 @@ pub struct RestoreRequest {
     pub timestamp: u64,
 }
 
+/// A sealed WAL segment shipped by a consensus cell (see `cell_sdk::wal_archive`)
+#[protein]
+pub struct WalSegment {
+    pub first: u64,
+    pub last: u64,
+    pub blob: String,
+}
+
 struct BackupState {
     jobs: HashMap<String, BackupJob>,
-    snapshots: HashMap<String, Vec<u64>>, // Cell -> Timestamps
+    snapshots: HashMap<String, Vec<(u64, String)>>, // Cell -> (Timestamp, Blob hash)
+    wal: HashMap<String, ArchiveCatalog>,
 }
 
 #[service]
@@ -41,25 +53,60 @@ impl BackupService {
     }
 
     async fn trigger(&self, cell_name: String) -> Result<u64> {
-        // In a real implementation:
-        // 1. Connect to cell via Ops channel
-        // 2. Request Snapshot
-        // 3. Encrypt via Vault
-        // 4. Store in S3/Disk
+        // TODO: Encrypt via Vault before archiving
+        let snapshot = cell_sdk::state::fetch(&cell_name).await?;
+        let blob = Blobstore::Client::connect().await?.put(snapshot).await?;
         
         let ts = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)?.as_secs();
             
         let mut state = self.state.write().await;
-        state.snapshots.entry(cell_name.clone()).or_insert_with(Vec::new).push(ts);
+        state.snapshots.entry(cell_name.clone()).or_insert_with(Vec::new).push((ts, blob.hash));
         
-        tracing::info!("[Backup] Snapshot taken for {}", cell_name);
+        tracing::info!("[Backup] Snapshot taken for {} ({} bytes)", cell_name, blob.size);
         Ok(ts)
     }
 
     async fn list_backups(&self, cell_name: String) -> Result<Vec<u64>> {
         let state = self.state.read().await;
-        Ok(state.snapshots.get(&cell_name).cloned().unwrap_or_default())
+        Ok(state.snapshots.get(&cell_name)
+            .map(|s| s.iter().map(|(ts, _)| *ts).collect())
+            .unwrap_or_default())
+    }
+
+    async fn restore(&self, req: RestoreRequest) -> Result<u64> {
+        let hash = {
+            let state = self.state.read().await;
+            state.snapshots.get(&req.cell_name)
+                .and_then(|s| s.iter().find(|(ts, _)| *ts == req.timestamp))
+                .map(|(_, hash)| hash.clone())
+                .ok_or_else(|| anyhow::anyhow!("No backup of {} at {}", req.cell_name, req.timestamp))?
+        };
+        let snapshot = Blobstore::Client::connect().await?.get(hash).await?;
+        let len = snapshot.len() as u64;
+        cell_sdk::state::push(&req.cell_name, snapshot).await?;
+        tracing::info!("[Backup] Restored {} from {}", req.cell_name, req.timestamp);
+        Ok(len)
+    }
+
+    async fn archive_segment(&self, cell_name: String, segment: WalSegment) -> Result<bool> {
+        let mut state = self.state.write().await;
+        let added = state.wal.entry(cell_name.clone()).or_default().record(
+            cell_sdk::wal_archive::WalSegment { first: segment.first, last: segment.last, blob: segment.blob },
+        );
+        if added {
+            tracing::info!("[Backup] Archived WAL {}..={} of {}", segment.first, segment.last, cell_name);
+        }
+        Ok(added)
+    }
+
+    async fn wal_segments(&self, cell_name: String) -> Result<Vec<WalSegment>> {
+        let state = self.state.read().await;
+        Ok(state.wal.get(&cell_name)
+            .map(|c| c.segments().iter()
+                .map(|s| WalSegment { first: s.first, last: s.last, blob: s.blob.clone() })
+                .collect())
+            .unwrap_or_default())
     }
 }
 
@@ -67,7 +114,7 @@ impl BackupService {
 async fn main() -> Result<()> {
     tracing_subscriber::fmt().init();
     tracing::info!("[Backup] Orchestrator Active");
-    let state = BackupState { jobs: HashMap::new(), snapshots: HashMap::new() };
+    let state = BackupState { jobs: HashMap::new(), snapshots: HashMap::new(), wal: HashMap::new() };
     let service = BackupService { state: Arc::new(RwLock::new(state)) };
     service.serve("backup").await
 }
\ No newline at end of file
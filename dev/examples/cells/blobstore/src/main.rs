@@ -0,0 +1,351 @@
+// cells/blobstore/src/main.rs
+// SPDX-License-Identifier: MIT
+// Content-Addressed Blob Store (chunked, reference counted)
+//
+// Each blob carries the data domains (cell_sdk::domain) it was stored in;
+// identical content stored by two organisms carries both. A caller reads a
+// blob only if it may reach one of them. Blobs stored before domains, or
+// without one, belong to this cell's own domain.
+
+use cell_sdk::*;
+use cell_sdk::blob::{BlobIndex, BlobManifest, CHUNK_SIZE};
+use cell_sdk::domain::{self, Domain, DomainGrant};
+use cell_sdk::error::{CellError, ErrorContext};
+use anyhow::Result;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+cell_remote!(Iam = "iam", methods = [domain_grants]);
+
+// === PROTOCOL ===
+
+#[protein]
+pub struct BlobRef {
+    pub hash: String,
+    pub size: u64,
+    pub chunks: u32,
+    pub refs: u32,
+}
+
+#[protein]
+pub struct GcReport {
+    pub blobs_removed: u32,
+    pub chunks_removed: u32,
+    pub bytes_freed: u64,
+}
+
+/// Orphan chunks younger than this belong to uploads still in progress
+const UPLOAD_GRACE: std::time::Duration = std::time::Duration::from_secs(3600);
+
+// === SERVICE ===
+
+#[service]
+#[derive(Clone)]
+struct BlobStore {
+    root: PathBuf,
+    index: Arc<RwLock<BlobIndex>>,
+    /// Blob hash -> domain labels; absent blobs are in the local domain
+    domains: Arc<RwLock<BTreeMap<String, BTreeSet<String>>>>,
+}
+
+impl BlobStore {
+    async fn open(root: PathBuf) -> Result<Self> {
+        tokio::fs::create_dir_all(root.join("chunks")).await?;
+        let index = match tokio::fs::read(root.join("index.json")).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(_) => BlobIndex::new(),
+        };
+        let domains = match tokio::fs::read(root.join("domains.json")).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(_) => BTreeMap::new(),
+        };
+        Ok(Self {
+            root,
+            index: Arc::new(RwLock::new(index)),
+            domains: Arc::new(RwLock::new(domains)),
+        })
+    }
+
+    fn chunk_path(&self, hash: &str) -> PathBuf {
+        self.root.join("chunks").join(hash)
+    }
+
+    async fn write_chunk(&self, data: &[u8]) -> Result<String> {
+        let hash = blake3::hash(data).to_hex().to_string();
+        let path = self.chunk_path(&hash);
+        // Content addressed: an existing chunk already holds these bytes.
+        // Touch it so GC treats it as part of this upload.
+        if let Ok(file) = std::fs::File::options().write(true).open(&path) {
+            file.set_modified(std::time::SystemTime::now())?;
+        } else {
+            let tmp = path.with_extension("tmp");
+            tokio::fs::write(&tmp, data).await?;
+            tokio::fs::rename(&tmp, &path).await?;
+        }
+        Ok(hash)
+    }
+
+    async fn read_chunk_bytes(&self, hash: &str) -> Result<Vec<u8>> {
+        let bytes = tokio::fs::read(self.chunk_path(hash)).await.map_err(|_| {
+            ErrorContext::new(CellError::NotFound).with_message(format!("Chunk {} missing", hash))
+        })?;
+        if blake3::hash(&bytes).to_hex().as_str() != hash {
+            return Err(ErrorContext::new(CellError::Corruption)
+                .with_message(format!("Chunk {} does not match its hash", hash))
+                .into());
+        }
+        Ok(bytes)
+    }
+
+    /// Record a blob made of stored chunks and take a reference to it.
+    async fn commit_chunks(&self, chunks: Vec<String>, domain: Domain) -> Result<BlobRef> {
+        domain::authorize_write(&domain)?;
+        let mut hasher = blake3::Hasher::new();
+        let mut size = 0u64;
+        for chunk in &chunks {
+            let bytes = self.read_chunk_bytes(chunk).await?;
+            size += bytes.len() as u64;
+            hasher.update(&bytes);
+        }
+        let manifest = BlobManifest {
+            hash: hasher.finalize().to_hex().to_string(),
+            size,
+            chunks,
+        };
+
+        let mut index = self.index.write().await;
+        let refs = index.insert(manifest.clone());
+        self.persist(&index).await?;
+        self.tag(&manifest.hash, &domain).await?;
+        tracing::info!("[Blobstore] Stored {} ({} bytes, {} refs)", manifest.hash, size, refs);
+        Ok(Self::blob_ref(&manifest, refs))
+    }
+
+    async fn persist(&self, index: &BlobIndex) -> Result<()> {
+        let path = self.root.join("index.json");
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, serde_json::to_vec(index)?).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+
+    async fn tag(&self, hash: &str, domain: &Domain) -> Result<()> {
+        let mut domains = self.domains.write().await;
+        if domains.entry(hash.to_string()).or_default().insert(domain.label()) {
+            self.persist_domains(&domains).await?;
+        }
+        Ok(())
+    }
+
+    async fn persist_domains(&self, domains: &BTreeMap<String, BTreeSet<String>>) -> Result<()> {
+        let path = self.root.join("domains.json");
+        let tmp = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, serde_json::to_vec(domains)?).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+
+    /// Refuse a caller outside every domain `hash` was stored in. Unknown
+    /// blobs pass, so the caller gets the usual NotFound.
+    async fn authorize(&self, hash: &str) -> Result<()> {
+        let domains = self.domains.read().await;
+        let Some(labels) = domains.get(hash) else {
+            return domain::authorize(&domain::local());
+        };
+        let mut refused = None;
+        for label in labels {
+            match domain::authorize(&Domain::parse(label)) {
+                Ok(()) => return Ok(()),
+                Err(e) => refused = Some(e),
+            }
+        }
+        refused.map_or(Ok(()), Err)
+    }
+
+    /// Keep the domain grant table current from IAM
+    async fn refresh_grants() {
+        loop {
+            match Iam::Client::connect().await {
+                Ok(iam) => match iam.domain_grants().await {
+                    Ok(grants) => domain::set_grants(
+                        grants
+                            .into_iter()
+                            .map(|g| DomainGrant {
+                                principal: g.principal,
+                                organism: g.organism,
+                                residency: g.residency,
+                                expires_at: g.expires_at,
+                            })
+                            .collect(),
+                    ),
+                    Err(e) => tracing::warn!("[Blobstore] Fetching domain grants failed: {}", e),
+                },
+                Err(e) => tracing::debug!("[Blobstore] IAM unreachable: {}", e),
+            }
+            tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+        }
+    }
+
+    fn blob_ref(manifest: &BlobManifest, refs: u32) -> BlobRef {
+        BlobRef {
+            hash: manifest.hash.clone(),
+            size: manifest.size,
+            chunks: manifest.chunks.len() as u32,
+            refs,
+        }
+    }
+
+    fn not_found(hash: &str) -> anyhow::Error {
+        ErrorContext::new(CellError::NotFound)
+            .with_message(format!("Blob {} not found", hash))
+            .into()
+    }
+}
+
+#[handler]
+impl BlobStore {
+    /// Store a blob that fits in one message
+    async fn put(&self, data: Vec<u8>) -> Result<BlobRef> {
+        self.put_in(data, domain::local().label()).await
+    }
+
+    /// `put` into a domain (`organism` or `organism@residency`)
+    async fn put_in(&self, data: Vec<u8>, domain: String) -> Result<BlobRef> {
+        let domain = Domain::parse(&domain);
+        domain::authorize_write(&domain)?;
+        let mut chunks = Vec::new();
+        for piece in data.chunks(CHUNK_SIZE) {
+            chunks.push(self.write_chunk(piece).await?);
+        }
+        self.commit_chunks(chunks, domain).await
+    }
+
+    /// Large blobs: upload each chunk (at most CHUNK_SIZE bytes), then commit
+    async fn upload_chunk(&self, data: Vec<u8>) -> Result<String> {
+        if data.len() > CHUNK_SIZE {
+            return Err(ErrorContext::new(CellError::InvalidMessage)
+                .with_message(format!("Chunk larger than {} bytes", CHUNK_SIZE))
+                .into());
+        }
+        self.write_chunk(&data).await
+    }
+
+    async fn commit(&self, chunks: Vec<String>) -> Result<BlobRef> {
+        self.commit_chunks(chunks, domain::local()).await
+    }
+
+    async fn commit_in(&self, chunks: Vec<String>, domain: String) -> Result<BlobRef> {
+        self.commit_chunks(chunks, Domain::parse(&domain)).await
+    }
+
+    async fn get(&self, hash: String) -> Result<Vec<u8>> {
+        self.authorize(&hash).await?;
+        let manifest = self.index.read().await.get(&hash).cloned()
+            .ok_or_else(|| Self::not_found(&hash))?;
+        let mut data = Vec::with_capacity(manifest.size as usize);
+        for chunk in &manifest.chunks {
+            data.extend(self.read_chunk_bytes(chunk).await?);
+        }
+        Ok(data)
+    }
+
+    /// Read a large blob piece by piece
+    async fn read_chunk(&self, hash: String, index: u32) -> Result<Vec<u8>> {
+        self.authorize(&hash).await?;
+        let chunk = self.index.read().await.get(&hash)
+            .and_then(|m| m.chunks.get(index as usize).cloned())
+            .ok_or_else(|| Self::not_found(&hash))?;
+        self.read_chunk_bytes(&chunk).await
+    }
+
+    async fn stat(&self, hash: String) -> Result<Option<BlobRef>> {
+        self.authorize(&hash).await?;
+        let index = self.index.read().await;
+        Ok(index.get(&hash).map(|m| Self::blob_ref(m, index.refs(&hash))))
+    }
+
+    async fn retain(&self, hash: String) -> Result<u32> {
+        self.authorize(&hash).await?;
+        let mut index = self.index.write().await;
+        let refs = index.retain(&hash).ok_or_else(|| Self::not_found(&hash))?;
+        self.persist(&index).await?;
+        Ok(refs)
+    }
+
+    /// Drop a reference; the blob is deleted by the next gc once unreferenced
+    async fn release(&self, hash: String) -> Result<u32> {
+        self.authorize(&hash).await?;
+        let mut index = self.index.write().await;
+        let refs = index.release(&hash).ok_or_else(|| Self::not_found(&hash))?;
+        self.persist(&index).await?;
+        Ok(refs)
+    }
+
+    async fn gc(&self) -> Result<GcReport> {
+        // Hold the index so no commit races the sweep
+        let mut index = self.index.write().await;
+
+        // Chunks old enough that no upload can still be using them
+        let mut stored = Vec::new();
+        let mut dir = tokio::fs::read_dir(self.root.join("chunks")).await?;
+        while let Some(entry) = dir.next_entry().await? {
+            let age = entry.metadata().await?.modified()?.elapsed().unwrap_or_default();
+            if age >= UPLOAD_GRACE {
+                stored.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+
+        let plan = index.gc(stored.iter().map(String::as_str));
+        self.persist(&index).await?;
+        if !plan.blobs.is_empty() {
+            let mut domains = self.domains.write().await;
+            for blob in &plan.blobs {
+                domains.remove(blob);
+            }
+            self.persist_domains(&domains).await?;
+        }
+
+        let mut bytes_freed = 0;
+        for chunk in &plan.chunks {
+            let path = self.chunk_path(chunk);
+            bytes_freed += tokio::fs::metadata(&path).await.map(|m| m.len()).unwrap_or(0);
+            tokio::fs::remove_file(&path).await?;
+        }
+        tracing::info!("[Blobstore] GC removed {} blobs, {} chunks", plan.blobs.len(), plan.chunks.len());
+        Ok(GcReport {
+            blobs_removed: plan.blobs.len() as u32,
+            chunks_removed: plan.chunks.len() as u32,
+            bytes_freed,
+        })
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt().init();
+
+    let root = dirs::home_dir()
+        .ok_or_else(|| anyhow::anyhow!("No home dir"))?
+        .join(".cell/blobs");
+    let store = BlobStore::open(root).await?;
+
+    // Periodic GC
+    let gc_store = store.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600));
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(e) = gc_store.gc().await {
+                tracing::warn!("[Blobstore] GC failed: {}", e);
+            }
+        }
+    });
+
+    tokio::spawn(BlobStore::refresh_grants());
+
+    tracing::info!("[Blobstore] Content-addressed store active");
+    store.serve("blobstore").await
+}
@@ -3,7 +3,8 @@
 // Polyglot code generator for Python, Go, TypeScript, etc.
 
 use cell_sdk::*;
-use anyhow::Result;
+use cell_build::schema::{self, Schema};
+use anyhow::{Context, Result};
 
 // === PROTOCOL ===
 
@@ -132,9 +133,37 @@ impl CodegenService {
         })
     }
 
+    /// OpenAPI 3 document (JSON) for the HTTP gateway mapping of a cell.
+    pub async fn openapi(&self, cell_name: String) -> Result<String> {
+        let schema = load_schema(&cell_name).await?;
+        let doc = cell_build::openapi::document(&cell_name, &schema);
+        Ok(serde_json::to_string_pretty(&doc)?)
+    }
+
+    /// TypeScript module with React hooks (`useLedgerDeposit()`) over the HTTP gateway.
+    pub async fn typescript_react(&self, cell_name: String) -> Result<String> {
+        let schema = load_schema(&cell_name).await?;
+        Ok(cell_build::typescript::react_hooks(&cell_name, &schema))
+    }
+
     pub async fn list_languages(&self) -> Result<Vec<String>> {
-        Ok(vec!["python".to_string(), "go".to_string(), "typescript".to_string()])
+        Ok(vec![
+            "python".to_string(),
+            "go".to_string(),
+            "typescript".to_string(),
+            "typescript-react".to_string(),
+        ])
+    }
+}
+
+/// Prefer what the running cell serves; fall back to the registry copy.
+async fn load_schema(cell_name: &str) -> Result<Schema> {
+    if let Ok(source) = cell_sdk::source::fetch(cell_name).await {
+        return Schema::from_source(&source);
     }
+    let path = schema::registry_source(cell_name)
+        .with_context(|| format!("'{}' is neither running nor in the registry", cell_name))?;
+    Ok(Schema::from_file(&cell_build::load_and_flatten_source(&path)?))
 }
 
 #[tokio::main]
@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! WAL archival for point-in-time recovery.
+//!
+//! Every `CELL_WAL_SEGMENT` committed entries (default 1024) form a sealed
+//! segment: it is shipped to the blobstore named by `CELL_WAL_ARCHIVE`
+//! (`blobstore`, or `blobstore@host:port` to keep it off this node) and listed
+//! with the backup cell. `consensus restore --until <index>` rebuilds the local
+//! log from those segments before the node boots.
+
+use anyhow::{bail, Result};
+use cell_sdk::wal_archive::{ArchiveCatalog, WalSegment, DEFAULT_SEGMENT_ENTRIES};
+use cell_sdk::{cell_remote, ResilientSynapse};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::time::Duration;
+use tracing::{info, warn};
+
+use crate::raft::RaftNode;
+use crate::wal::{LogEntry, WriteAheadLog};
+
+cell_remote!(Blobstore = "blobstore");
+cell_remote!(Backup = "backup");
+
+const ARCHIVE_INTERVAL: Duration = Duration::from_secs(5);
+
+pub struct ArchiveConfig {
+    /// Blobstore cell receiving the segments
+    pub target: String,
+    pub segment_entries: u64,
+}
+
+impl ArchiveConfig {
+    /// `None` unless `CELL_WAL_ARCHIVE` is set
+    pub fn from_env() -> Option<Self> {
+        let target = std::env::var("CELL_WAL_ARCHIVE").ok()?;
+        let segment_entries = std::env::var("CELL_WAL_SEGMENT")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_SEGMENT_ENTRIES);
+        Some(Self {
+            target,
+            segment_entries,
+        })
+    }
+
+    async fn blobstore(&self) -> Result<Blobstore::Client> {
+        Ok(Blobstore::Client::new(
+            ResilientSynapse::grow(&self.target).await?,
+        ))
+    }
+}
+
+async fn catalog(cell_name: &str) -> Result<ArchiveCatalog> {
+    let segments = Backup::Client::connect()
+        .await?
+        .wal_segments(cell_name.to_string())
+        .await?;
+    Ok(ArchiveCatalog::new(
+        segments
+            .into_iter()
+            .map(|s| WalSegment {
+                first: s.first,
+                last: s.last,
+                blob: s.blob,
+            })
+            .collect(),
+    ))
+}
+
+/// Ship segments as the commit index passes them, for as long as the node runs.
+pub fn spawn(raft: Arc<RaftNode>, cell_name: String, config: ArchiveConfig) {
+    tokio::spawn(async move {
+        let mut known: Option<ArchiveCatalog> = None;
+        loop {
+            tokio::time::sleep(ARCHIVE_INTERVAL).await;
+            let catalog = match known.as_mut() {
+                Some(catalog) => catalog,
+                None => match catalog(&cell_name).await {
+                    Ok(catalog) => known.insert(catalog),
+                    Err(e) => {
+                        warn!("[Archive] Cannot load the catalog of {}: {}", cell_name, e);
+                        continue;
+                    }
+                },
+            };
+            if let Err(e) = archive_pending(&raft, &cell_name, &config, catalog).await {
+                warn!("[Archive] {}", e);
+                // Other replicas may have archived meanwhile: reload
+                known = None;
+            }
+        }
+    });
+}
+
+async fn archive_pending(
+    raft: &RaftNode,
+    cell_name: &str,
+    config: &ArchiveConfig,
+    catalog: &mut ArchiveCatalog,
+) -> Result<()> {
+    let committed = raft.commit_index().await;
+    for (first, last) in catalog.pending(committed, config.segment_entries) {
+        let entries = raft.wal.lock().await.get_entries_between(first, last);
+        if entries.len() as u64 != last - first + 1 {
+            bail!("Entries {}..={} are no longer in the log", first, last);
+        }
+
+        let blob = config
+            .blobstore()
+            .await?
+            .put(bincode::serialize(&entries)?)
+            .await?;
+        Backup::Client::connect()
+            .await?
+            .archive_segment(
+                cell_name.to_string(),
+                Backup::WalSegment {
+                    first,
+                    last,
+                    blob: blob.hash.clone(),
+                },
+            )
+            .await?;
+        catalog.record(WalSegment {
+            first,
+            last,
+            blob: blob.hash,
+        });
+        info!("[Archive] Shipped entries {}..={}", first, last);
+    }
+    Ok(())
+}
+
+/// Rebuild the log at `storage_path` as entries `1..=until`: archived
+/// segments first, then whatever the local log still holds past them.
+/// Returns the new last index.
+pub async fn restore(
+    storage_path: &Path,
+    cell_name: &str,
+    config: &ArchiveConfig,
+    until: u64,
+) -> Result<u64> {
+    let catalog = catalog(cell_name).await?;
+    let mut blobstore = config.blobstore().await?;
+    let mut entries: Vec<LogEntry> = Vec::new();
+    for segment in catalog.covering(until) {
+        let part: Vec<LogEntry> =
+            bincode::deserialize(&blobstore.get(segment.blob.clone()).await?)?;
+        if entries.len() as u64 + 1 != segment.first
+            || part.len() as u64 != segment.last - segment.first + 1
+        {
+            bail!(
+                "Archived segment {}..={} is damaged",
+                segment.first,
+                segment.last
+            );
+        }
+        entries.extend(part);
+    }
+    let archived = entries.len() as u64;
+
+    let mut wal = WriteAheadLog::open(storage_path)?;
+    entries.extend(wal.get_entries_between(archived + 1, until));
+    entries.truncate(until as usize);
+    let restored = entries.len() as u64;
+    if restored < until {
+        warn!("[Archive] Log ends at {}, short of {}", restored, until);
+    }
+    wal.replace(entries)?;
+
+    info!(
+        "[Archive] Restored {} entries ({} from the archive)",
+        restored,
+        archived.min(restored)
+    );
+    Ok(restored)
+}
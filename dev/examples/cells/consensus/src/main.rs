@@ -3,15 +3,52 @@
 
 mod wal;
 mod raft;
+mod archive;
+mod membership;
+mod recovery;
+mod signed;
+mod snapshot;
+mod shard;
 
 use anyhow::Result;
+use cell_sdk::clap::{self, Parser, Subcommand};
 use cell_sdk::{service, handler, protein, Synapse};
 use cell_sdk as cell;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tracing::info;
 use tokio::time::Duration;
 
+use crate::archive::ArchiveConfig;
+use crate::membership::MembershipChange;
 use crate::raft::{RaftNode, RaftConfig, StateMachine};
+use crate::shard::{MultiRaft, ShardMap};
+use crate::signed::SignaturePolicy;
+
+cell_sdk::cell_remote!(Nucleus = "nucleus");
+
+#[derive(Parser)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Cmd>,
+}
+
+#[derive(Subcommand)]
+enum Cmd {
+    /// Rebuild the log from the WAL archive up to an index, then boot
+    Restore {
+        #[arg(long)]
+        until: u64,
+    },
+    /// After permanent quorum loss, make node `from` the only voter, then
+    /// boot. Run on every surviving node; entries only lost nodes had are gone.
+    ForceRecover {
+        #[arg(long)]
+        from: u64,
+        /// Confirm the membership override
+        #[arg(long)]
+        yes: bool,
+    },
+}
 
 // --- API PROTOCOL ---
 
@@ -25,6 +62,41 @@ pub struct ProposeResult {
     pub index: u64,
 }
 
+/// A command signed with `cell_sdk::signing::ProducerKey::sign`
+#[protein]
+pub struct SignedCommand {
+    pub producer: String,
+    pub seq: u64,
+    pub data: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+/// A command for the shard owning `key`
+#[protein]
+pub struct KeyedCommand {
+    pub key: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+/// Indexes are per shard: each has its own log
+#[protein]
+pub struct ShardedResult {
+    pub shard: u32,
+    pub index: u64,
+}
+
+#[protein]
+pub struct ShardInfo {
+    pub shard: u32,
+    /// First key of the range
+    pub start: Vec<u8>,
+    /// First key past the range; `None` for the last shard
+    pub end: Option<Vec<u8>>,
+    /// Node to send the shard's writes to, if one is known
+    pub leader: Option<u64>,
+    pub commit_index: u64,
+}
+
 #[protein]
 pub struct LogQuery {
     pub index: u64,
@@ -36,9 +108,56 @@ pub struct LogResult {
     pub data: Option<Vec<u8>>,
 }
 
+/// How current a read must be
+#[protein]
+pub enum ReadConsistency {
+    /// From the leader, as of its last applied entry
+    Strong,
+    /// From any node with a read lease, at most this many ms behind the leader
+    BoundedStaleness(u64),
+}
+
+#[protein]
+pub struct StateRead {
+    pub applied_index: u64,
+    /// Upper bound on how far the state trails the leader's
+    pub staleness_ms: u64,
+    /// Commands applied, and the hash chained over them
+    pub commands: u64,
+    pub hash: Vec<u8>,
+}
+
+#[protein]
+pub struct Learner {
+    pub id: u64,
+    pub address: String,
+}
+
+#[protein]
+pub struct Members {
+    pub voters: Vec<u64>,
+    /// Voters being moved to while a change is under way
+    pub joint: Option<Vec<u64>>,
+    pub learners: Vec<Learner>,
+}
+
+#[protein]
+pub struct QuorumStatus {
+    /// Quorum lost for too long: writes are refused
+    pub safe_mode: bool,
+    pub lost_ms: u64,
+    /// Survivor named by a forced recovery, if any
+    pub recovered_from: Option<u64>,
+}
+
 // --- STATE MACHINE ---
 
-struct SimpleStateMachine;
+/// Counts applied commands and chains their hashes, so the snapshot pins
+/// down exactly what was applied, in order
+#[derive(Default)]
+struct SimpleStateMachine {
+    state: Mutex<(u64, [u8; 32])>,
+}
 
 impl StateMachine for SimpleStateMachine {
     fn apply(&self, command: &[u8]) {
@@ -47,15 +166,29 @@ impl StateMachine for SimpleStateMachine {
         } else {
             info!("[StateMachine] Applied binary command, len: {}", command.len());
         }
+        let mut state = self.state.lock().unwrap();
+        state.0 += 1;
+        state.1 = cell_sdk::replay::state_hash(&[&state.1[..], command].concat());
+    }
+    fn take_snapshot(&self) -> Vec<u8> {
+        let state = self.state.lock().unwrap();
+        [&state.0.to_le_bytes()[..], &state.1[..]].concat()
+    }
+    fn restore_snapshot(&self, data: &[u8]) {
+        if data.len() == 40 {
+            let mut state = self.state.lock().unwrap();
+            state.0 = u64::from_le_bytes(data[..8].try_into().unwrap());
+            state.1.copy_from_slice(&data[8..]);
+        }
     }
-    fn take_snapshot(&self) -> Vec<u8> { vec![] }
-    fn restore_snapshot(&self, _data: &[u8]) {}
 }
 
 // --- SERVICE ---
 
 struct ConsensusState {
+    /// Shard 0, which also carries membership, archive and replay audits
     raft: Arc<RaftNode>,
+    shards: MultiRaft,
 }
 
 #[service]
@@ -71,23 +204,197 @@ impl ConsensusService {
         Ok(ProposeResult { index })
     }
 
+    async fn propose_signed(&self, cmd: SignedCommand) -> Result<ProposeResult> {
+        let command = cell_sdk::signing::SignedCommand {
+            producer: cmd.producer,
+            seq: cmd.seq,
+            data: cmd.data,
+            signature: cmd.signature,
+        };
+        let index = self.state.raft.propose_signed(command).await?;
+        Ok(ProposeResult { index })
+    }
+
+    /// Committed entries skipped because of missing or bad signatures
+    async fn rejected_entries(&self) -> Result<u64> {
+        Ok(self.state.raft.rejected())
+    }
+
+    /// Read the state machine. Followers serve `BoundedStaleness` reads
+    /// while holding a lease from the leader.
+    async fn read(&self, consistency: ReadConsistency) -> Result<StateRead> {
+        let max_staleness = match consistency {
+            ReadConsistency::Strong => None,
+            ReadConsistency::BoundedStaleness(ms) => Some(Duration::from_millis(ms)),
+        };
+        let (applied_index, staleness, snapshot) =
+            self.state.raft.read(max_staleness, |sm| sm.take_snapshot()).await?;
+        let (commands, hash) = snapshot.split_at(8);
+        Ok(StateRead {
+            applied_index,
+            staleness_ms: staleness.as_millis() as u64,
+            commands: u64::from_le_bytes(commands.try_into()?),
+            hash: hash.to_vec(),
+        })
+    }
+
     async fn get_log_entry(&self, query: LogQuery) -> Result<LogResult> {
-        let wal = self.state.raft.wal.lock().await;
-        if let Some(entry) = wal.get_entry(query.index) {
-             match entry {
-                 crate::wal::LogEntry::Command { term, data } => {
-                     Ok(LogResult { term, data: Some(data) })
-                 }
-                 crate::wal::LogEntry::NoOp { term } => {
-                     Ok(LogResult { term, data: None })
-                 }
-             }
-        } else {
-             Err(anyhow::anyhow!("Log index out of bounds"))
+        log_entry(&self.state.raft, query.index).await
+    }
+
+    /// Propose to the shard owning `cmd.key`; fails unless this node leads it
+    async fn propose_keyed(&self, cmd: KeyedCommand) -> Result<ShardedResult> {
+        let (shard, raft) = self.state.shards.route(&cmd.key);
+        let index = raft.propose(cmd.data).await?;
+        Ok(ShardedResult { shard, index })
+    }
+
+    async fn get_shard_entry(&self, shard: u32, query: LogQuery) -> Result<LogResult> {
+        log_entry(self.state.shards.group(shard)?, query.index).await
+    }
+
+    /// Key ranges and leaders, for clients routing writes themselves
+    async fn shards(&self) -> Result<Vec<ShardInfo>> {
+        let map = self.state.shards.map();
+        let mut shards = Vec::new();
+        for shard in 0..map.len() {
+            let raft = self.state.shards.group(shard)?;
+            let (start, end) = map.range(shard);
+            shards.push(ShardInfo {
+                shard,
+                start,
+                end,
+                leader: raft.leader().await,
+                commit_index: raft.commit_index().await,
+            });
+        }
+        Ok(shards)
+    }
+
+    /// Start replicating to a non-voting learner (leader only)
+    async fn add_learner(&self, learner: Learner) -> Result<bool> {
+        let change = MembershipChange::AddLearner { id: learner.id, address: learner.address };
+        self.state.raft.change_membership(change).await?;
+        Ok(true)
+    }
+
+    async fn remove_learner(&self, id: u64) -> Result<bool> {
+        self.remove_peer(id).await
+    }
+
+    /// Make caught-up learners voters in one joint-consensus change; returns
+    /// once the new voters alone have committed (leader only)
+    async fn promote(&self, ids: Vec<u64>) -> Result<Members> {
+        self.state.raft.change_membership(MembershipChange::Promote { ids }).await?;
+        self.members().await
+    }
+
+    /// Remove a learner, or a voter through a joint-consensus change (leader only)
+    async fn remove_peer(&self, id: u64) -> Result<bool> {
+        self.state.raft.change_membership(MembershipChange::RemoveNode { id }).await?;
+        Ok(true)
+    }
+
+    async fn members(&self) -> Result<Members> {
+        let config = self.state.raft.configuration().await;
+        let learners = config.learners.into_iter().map(|(id, address)| Learner { id, address }).collect();
+        Ok(Members { voters: config.voters, joint: config.joint, learners })
+    }
+
+    /// Hand leadership to voter `target` before taking this node down (leader only)
+    async fn transfer_leadership(&self, target: u64) -> Result<bool> {
+        self.state.raft.transfer_leadership(target).await?;
+        Ok(true)
+    }
+
+    async fn quorum(&self) -> Result<QuorumStatus> {
+        let (safe_mode, lost, recovered_from) = self.state.raft.quorum().await;
+        Ok(QuorumStatus { safe_mode, lost_ms: lost.as_millis() as u64, recovered_from })
+    }
+}
+
+async fn log_entry(raft: &RaftNode, index: u64) -> Result<LogResult> {
+    let wal = raft.wal.lock().await;
+    match wal.get_entry(index) {
+        Some(crate::wal::LogEntry::Command { term, data }) => Ok(LogResult { term, data: Some(data) }),
+        Some(crate::wal::LogEntry::NoOp { term } | crate::wal::LogEntry::Config { term, .. }) => {
+            Ok(LogResult { term, data: None })
+        }
+        Some(crate::wal::LogEntry::Signed { term, command }) => {
+            Ok(LogResult { term, data: Some(command.data) })
         }
+        None => Err(anyhow::anyhow!("Log index out of bounds")),
     }
 }
 
+/// Grow the cluster from what nucleus discovers, while leading: learners
+/// registered as `<cell>#learner` are added, and promoted once caught up, as
+/// many at a time as keep the voter count odd, up to `CELL_RAFT_MAX_VOTERS`
+/// (default 5). So a lone node becomes 3 voters, then 5. Only shard 0 grows;
+/// the other shards keep the voters they were ignited with.
+async fn grow(raft: Arc<RaftNode>, cell_name: String) {
+    let max_voters: usize = std::env::var("CELL_RAFT_MAX_VOTERS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5);
+    loop {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        if raft.leader().await != Some(raft.config.id) {
+            continue;
+        }
+        if let Err(e) = grow_once(&raft, &cell_name, max_voters).await {
+            tracing::debug!("[Raft] Not growing the cluster yet: {}", e);
+        }
+    }
+}
+
+async fn grow_once(raft: &RaftNode, cell_name: &str, max_voters: usize) -> Result<()> {
+    let found = Nucleus::Client::connect().await?
+        .discover(Nucleus::DiscoveryQuery {
+            cell_name: format!("{}#learner", cell_name),
+            prefer_local: false,
+        })
+        .await?;
+
+    let config = raft.configuration().await;
+    if config.joint.is_some() {
+        return Ok(());
+    }
+    let known = |id: u64| config.voters.contains(&id) || config.learners.iter().any(|(l, _)| *l == id);
+    if let Some(new) = found.instances.into_iter().find(|i| !known(i.node_id)) {
+        info!("[Raft] Discovered learner {} at {}", new.node_id, new.address);
+        let change = MembershipChange::AddLearner { id: new.node_id, address: new.address };
+        return raft.change_membership(change).await;
+    }
+
+    let voters = config.voters.len();
+    let caught_up = raft.caught_up().await;
+    let mut target = (voters + caught_up.len()).min(max_voters.max(voters));
+    if target % 2 == 0 {
+        target -= 1;
+    }
+    if target <= voters {
+        return Ok(());
+    }
+    let ids: Vec<u64> = caught_up.into_iter().take(target - voters).collect();
+    info!("[Raft] Promoting learners {:?}: {} -> {} voters", ids, voters, target);
+    raft.change_membership(MembershipChange::Promote { ids }).await
+}
+
+/// Learners are discovered as `<cell>#learner`, apart from the voters
+async fn register_learner(cell_name: &str, node_id: u64) -> Result<bool> {
+    Nucleus::Client::connect().await?
+        .register(Nucleus::CellRegistration {
+            name: format!("{}#learner", cell_name),
+            node_id,
+            capabilities: vec!["consensus-learner".to_string()],
+            endpoints: vec![cell_name.to_string()],
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            env: vec![],
+        })
+        .await
+}
+
 // --- MAIN ---
 
 #[tokio::main]
@@ -105,33 +412,82 @@ async fn main() -> Result<()> {
     info!("Injected Peers: {:?}", peers);
 
     let storage_path = std::env::current_dir()?.join(format!("raft_{}.wal", identity.node_id));
+    let archive = ArchiveConfig::from_env();
+
+    match Cli::parse().command {
+        Some(Cmd::Restore { until }) => {
+            let config = archive.as_ref()
+                .ok_or_else(|| anyhow::anyhow!("Restoring needs CELL_WAL_ARCHIVE"))?;
+            archive::restore(&storage_path, &identity.cell_name, config, until).await?;
+        }
+        Some(Cmd::ForceRecover { from, yes }) => {
+            recovery::force_recover(&storage_path, &identity.cell_name, identity.node_id, from, yes).await?;
+        }
+        None => {}
+    }
 
     let (tx, mut rx) = tokio::sync::mpsc::channel(1000);
     
     let raft_config = RaftConfig {
         id: identity.node_id,
+        address: identity.cell_name.clone(),
         peers: peers.clone(),
         storage_path,
         election_timeout_min: 150,
         election_timeout_max: 300,
         heartbeat_interval: 50,
+        learner: std::env::var("CELL_RAFT_LEARNER").is_ok_and(|v| v == "1" || v == "true"),
+        signatures: SignaturePolicy::from_env(&identity.cell_name)?,
+        snapshot_threshold: std::env::var("CELL_RAFT_SNAPSHOT_AFTER")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1000),
     };
+    if raft_config.signatures.is_some() {
+        info!("[Raft] Applying producer-signed entries only");
+    }
+    let learner = raft_config.learner;
+
+    let map = ShardMap::from_env()?;
+    if map.len() > 1 {
+        info!("[Raft] Hosting {} shards", map.len());
+    }
+    let shards = MultiRaft::ignite(raft_config, map, || Arc::new(SimpleStateMachine::default()), tx).await?;
+    let raft = shards.group(0)?.clone();
+
+    if let Some(config) = archive {
+        info!("[Archive] Shipping WAL segments of {} entries to {}", config.segment_entries, config.target);
+        archive::spawn(raft.clone(), identity.cell_name.clone(), config);
+    }
 
-    let sm = Arc::new(SimpleStateMachine);
-    let raft = RaftNode::ignite(raft_config, sm, tx).await?;
+    // Audited by the observer over OPS VerifyReplay
+    let audited = raft.clone();
+    cell_sdk::replay::register(move || {
+        let raft = audited.clone();
+        async move { raft.verify_replay(Arc::new(SimpleStateMachine::default())).await }
+    });
 
     let service = ConsensusService {
-        state: Arc::new(ConsensusState { raft: raft.clone() }),
+        state: Arc::new(ConsensusState { raft: raft.clone(), shards }),
     };
 
-    let peers_clone = peers.clone();
+    tokio::spawn(grow(raft.clone(), identity.cell_name.clone()));
+
+    if learner {
+        info!("[Raft] Following as a learner: no vote, no quorum");
+        if let Err(e) = register_learner(&identity.cell_name, identity.node_id).await {
+            tracing::warn!("Could not register learner with nucleus: {}", e);
+        }
+    }
+
+    let router = service.state.clone();
     tokio::spawn(async move {
         // Fix: Use cell_sdk which re-exports rkyv and cell_core
         use cell_sdk::rkyv;
         
         while let Some((target_idx, msg)) = rx.recv().await {
-             if let Some(peer_name) = peers_clone.get(target_idx as usize) {
-                 let p_name = peer_name.to_string();
+             let Ok(raft) = router.shards.group(msg.group) else { continue };
+             if let Some(p_name) = raft.address_of(target_idx).await {
                  tokio::spawn(async move {
                      if let Ok(mut syn) = Synapse::grow(&p_name).await {
                          if let Ok(bytes) = rkyv::to_bytes::<_, 1024>(&msg) {
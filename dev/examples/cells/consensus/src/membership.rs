@@ -1,73 +1,207 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
 
+//! Cluster membership, changed while live through the log.
+//!
+//! Every change is a `LogEntry::Config` carrying the whole configuration, and
+//! each node uses the newest one in its log, committed or not. Learner changes
+//! leave quorum alone and take one entry. Voter changes use joint consensus:
+//! the leader first appends the old and new voters together (`joint`), during
+//! which elections and commits need a majority of both sets, and once that
+//! entry commits appends the new voters alone. No two majorities can disagree
+//! at any point, so the cluster grows from 1 to 3 to 5 voters without a pause.
+//!
+//! The last committed configuration is kept next to the WAL as `<wal>.members`,
+//! since the log entry carrying it may be compacted into a snapshot.
+
 use anyhow::Result;
-use std::collections::HashSet;
-use rkyv::{Archive, Serialize, Deserialize};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
 
-#[derive(Archive, Serialize, Deserialize, Debug, Clone)]
+#[derive(cell_sdk::rkyv::Archive, cell_sdk::rkyv::Serialize, cell_sdk::rkyv::Deserialize, Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[archive(check_bytes)]
+#[archive(crate = "cell_sdk::rkyv")]
+pub struct Configuration {
+    pub voters: Vec<u64>,
+    /// Voters being moved to; decisions need a majority of both meanwhile
+    pub joint: Option<Vec<u64>>,
+    pub learners: Vec<(u64, String)>,
+    /// Addresses of voters that joined after ignite, i.e. outside `peers`
+    pub addresses: Vec<(u64, String)>,
+}
+
+impl Configuration {
+    pub fn path(storage_path: &Path) -> PathBuf {
+        storage_path.with_extension("members")
+    }
+
+    pub fn load(storage_path: &Path) -> Result<Option<Self>> {
+        match std::fs::read(Self::path(storage_path)) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, storage_path: &Path) -> Result<()> {
+        std::fs::write(Self::path(storage_path), serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum MembershipChange {
-    AddNode { id: u64, address: String },
+    /// Non-voting replica: receives the log, never counts toward quorum
+    AddLearner { id: u64, address: String },
+    /// Make caught-up learners voters, in one joint change
+    Promote { ids: Vec<u64> },
+    /// Drop a learner, or a voter through a joint change
     RemoveNode { id: u64 },
 }
 
 pub struct MembershipManager {
-    current_members: HashSet<u64>,
-    pending_change: Option<MembershipChange>,
+    current_members: BTreeSet<u64>,
+    joint: Option<BTreeSet<u64>>,
+    learners: BTreeMap<u64, String>,
+    addresses: BTreeMap<u64, String>,
+    /// Log index of the entry carrying this configuration, 0 if none
+    index: u64,
 }
 
 impl MembershipManager {
     pub fn new(initial_members: Vec<u64>) -> Self {
         Self {
             current_members: initial_members.into_iter().collect(),
-            pending_change: None,
+            joint: None,
+            learners: BTreeMap::new(),
+            addresses: BTreeMap::new(),
+            index: 0,
+        }
+    }
+
+    /// Switch to `config`, found at log index `index`
+    pub fn adopt(&mut self, index: u64, config: &Configuration) {
+        self.current_members = config.voters.iter().copied().collect();
+        self.joint = config.joint.as_ref().map(|j| j.iter().copied().collect());
+        self.learners = config.learners.iter().cloned().collect();
+        self.addresses = config.addresses.iter().cloned().collect();
+        self.index = index;
+    }
+
+    pub fn configuration(&self) -> Configuration {
+        Configuration {
+            voters: self.current_members.iter().copied().collect(),
+            joint: self.joint.as_ref().map(|j| j.iter().copied().collect()),
+            learners: self.learners.iter().map(|(id, a)| (*id, a.clone())).collect(),
+            addresses: self.addresses.iter().map(|(id, a)| (*id, a.clone())).collect(),
         }
     }
 
-    pub fn propose_change(&mut self, change: MembershipChange) -> Result<()> {
-        if self.pending_change.is_some() {
+    /// The configuration `change` leads to: the final one for learner changes,
+    /// the joint one for voter changes. `committed` is the commit index; a
+    /// change waits until the last one has committed.
+    pub fn propose_change(&self, change: &MembershipChange, committed: u64) -> Result<Configuration> {
+        if self.is_joint() || !self.is_committed(committed) {
             anyhow::bail!("Cannot have multiple pending membership changes");
         }
 
-        match &change {
-            MembershipChange::AddNode { id, .. } => {
-                if self.current_members.contains(id) {
+        let mut next = self.configuration();
+        match change {
+            MembershipChange::AddLearner { id, address } => {
+                if self.current_members.contains(id) || self.learners.contains_key(id) {
                     anyhow::bail!("Node {} already exists", id);
                 }
+                next.learners.push((*id, address.clone()));
+            }
+            MembershipChange::Promote { ids } => {
+                if ids.is_empty() {
+                    anyhow::bail!("No learners to promote");
+                }
+                let mut voters = self.current_members.clone();
+                for id in ids {
+                    let Some(address) = self.learners.get(id) else {
+                        anyhow::bail!("Node {} is not a learner", id);
+                    };
+                    next.addresses.push((*id, address.clone()));
+                    voters.insert(*id);
+                }
+                next.learners.retain(|(id, _)| !ids.contains(id));
+                next.joint = Some(voters.into_iter().collect());
+            }
+            MembershipChange::RemoveNode { id } if self.learners.contains_key(id) => {
+                next.learners.retain(|(l, _)| l != id);
             }
             MembershipChange::RemoveNode { id } => {
                 if !self.current_members.contains(id) {
                     anyhow::bail!("Node {} does not exist", id);
                 }
+                if self.current_members.len() == 1 {
+                    anyhow::bail!("Node {} is the last voter", id);
+                }
+                let voters = self.current_members.iter().copied().filter(|v| v != id);
+                next.joint = Some(voters.collect());
             }
         }
+        Ok(next)
+    }
 
-        self.pending_change = Some(change);
-        Ok(())
+    /// The configuration ending a committed joint one: the new voters alone
+    pub fn leave_joint(&self, committed: u64) -> Option<Configuration> {
+        let joint = self.joint.as_ref().filter(|_| self.is_committed(committed))?;
+        let mut next = self.configuration();
+        next.addresses.retain(|(id, _)| joint.contains(id));
+        next.voters = joint.iter().copied().collect();
+        next.joint = None;
+        Some(next)
     }
 
-    pub fn commit_change(&mut self) -> Result<()> {
-        let change = self.pending_change.take()
-            .ok_or_else(|| anyhow::anyhow!("No pending change"))?;
+    /// Every voter, of both configurations while joint
+    pub fn members(&self) -> Vec<u64> {
+        let mut voters = self.current_members.clone();
+        voters.extend(self.joint.iter().flatten());
+        voters.into_iter().collect()
+    }
 
-        match change {
-            MembershipChange::AddNode { id, .. } => {
-                self.current_members.insert(id);
-            }
-            MembershipChange::RemoveNode { id } => {
-                self.current_members.remove(&id);
-            }
-        }
+    pub fn is_joint(&self) -> bool {
+        self.joint.is_some()
+    }
 
-        Ok(())
+    /// Whether the entry carrying this configuration has committed
+    pub fn is_committed(&self, commit: u64) -> bool {
+        self.index <= commit
     }
 
-    pub fn members(&self) -> Vec<u64> {
-        self.current_members.iter().copied().collect()
+    pub fn is_voter(&self, id: u64) -> bool {
+        self.current_members.contains(&id) || self.joint.as_ref().is_some_and(|j| j.contains(&id))
+    }
+
+    /// Learner ids and addresses
+    pub fn learners(&self) -> &BTreeMap<u64, String> {
+        &self.learners
     }
 
-    pub fn majority(&self) -> usize {
-        (self.current_members.len() / 2) + 1
+    /// Address of a voter that joined after ignite, or of a learner
+    pub fn address(&self, id: u64) -> Option<&String> {
+        self.addresses.get(&id).or_else(|| self.learners.get(&id))
     }
-}
\ No newline at end of file
+
+    /// Whether `ids` include a majority of the voters, of both configurations
+    /// while joint. Learners never count toward quorum.
+    pub fn is_quorum(&self, ids: impl Fn(u64) -> bool) -> bool {
+        let majority = |voters: &BTreeSet<u64>| voters.iter().filter(|v| ids(**v)).count() > voters.len() / 2;
+        majority(&self.current_members) && self.joint.as_ref().is_none_or(majority)
+    }
+
+    /// Highest index stored by a majority, of both configurations while joint,
+    /// given each voter's match index
+    pub fn committed(&self, matched: impl Fn(u64) -> u64) -> u64 {
+        let at = |voters: &BTreeSet<u64>| {
+            let mut indices: Vec<u64> = voters.iter().map(|v| matched(*v)).collect();
+            indices.sort_unstable();
+            indices.get(indices.len().saturating_sub(voters.len() / 2 + 1)).copied().unwrap_or(0)
+        };
+        let old = at(&self.current_members);
+        self.joint.as_ref().map_or(old, |joint| old.min(at(joint)))
+    }
+}
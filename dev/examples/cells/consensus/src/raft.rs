@@ -7,10 +7,16 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex, RwLock};
 use tokio::time::{Duration, Instant};
-use tracing::{info, debug};
+use tracing::{info, debug, error, warn};
 use rand::Rng;
 
+use crate::membership::{Configuration, MembershipChange, MembershipManager};
+use crate::recovery::ForcedRecovery;
+use crate::signed::{EntryGate, SignaturePolicy};
+use crate::snapshot::{Incoming, Snapshot};
 use crate::wal::{LogEntry, WriteAheadLog};
+use cell_sdk::replay::{state_hash, ReplayReport};
+use cell_sdk::signing::{ProducerWatermarks, SignedCommand};
 
 // --- RPC MESSAGES ---
 
@@ -25,6 +31,9 @@ pub enum RaftMessage {
         prev_log_term: u64,
         entries: Vec<LogEntry>,
         leader_commit: u64,
+        /// How long the receiver may serve reads as of `leader_commit`; 0
+        /// grants no lease
+        lease_ms: u64,
     },
     AppendEntriesResponse {
         term: u64,
@@ -42,6 +51,45 @@ pub enum RaftMessage {
         term: u64,
         vote_granted: bool,
     },
+    /// Would the receiver vote for `candidate_id` in `term`? Nobody's term
+    /// changes, so a node cut off from the leader cannot disrupt it on return.
+    PreVoteRequest {
+        term: u64,
+        candidate_id: u64,
+        last_log_index: u64,
+        last_log_term: u64,
+    },
+    PreVoteResponse {
+        term: u64,
+        /// The term the pre-vote was for
+        pre_term: u64,
+        vote_granted: bool,
+    },
+    /// Sent by a leader handing over to a caught-up voter: elect yourself now
+    TimeoutNow {
+        term: u64,
+        leader_id: u64,
+    },
+    /// A chunk of the leader's snapshot, for a node whose next entry the log
+    /// no longer has or that joined empty
+    InstallSnapshot {
+        term: u64,
+        leader_id: u64,
+        last_included_index: u64,
+        last_included_term: u64,
+        offset: u64,
+        data: Vec<u8>,
+        done: bool,
+        /// The snapshot's producer watermarks; only on the last chunk
+        watermarks: ProducerWatermarks,
+    },
+    InstallSnapshotResponse {
+        term: u64,
+        last_included_index: u64,
+        /// Bytes held so far: where the next chunk starts
+        received: u64,
+        installed: bool,
+    },
 }
 
 // --- CONFIG & TRAITS ---
@@ -49,15 +97,27 @@ pub enum RaftMessage {
 #[derive(Clone, Debug)]
 pub struct RaftConfig {
     pub id: u64,
+    /// Where other nodes reach this one; handed to nodes joining later
+    pub address: String,
+    /// Voters at ignite, addressed by index; later ones join through the log
     pub peers: Vec<String>,
     pub storage_path: std::path::PathBuf,
     pub election_timeout_min: u64,
     pub election_timeout_max: u64,
     pub heartbeat_interval: u64,
+    /// Start as a learner: follow the log without voting or standing for
+    /// election until the leader promotes this node
+    pub learner: bool,
+    /// Only apply entries signed by trusted producers
+    pub signatures: Option<SignaturePolicy>,
+    /// Applied entries past which a node joining with an empty log gets a
+    /// snapshot instead of the whole log; 0 always replays
+    pub snapshot_threshold: u64,
 }
 
 pub trait StateMachine: Send + Sync + 'static {
     fn apply(&self, command: &[u8]);
+    /// Must be deterministic: replay audits compare snapshot hashes
     fn take_snapshot(&self) -> Vec<u8>;
     fn restore_snapshot(&self, data: &[u8]);
 }
@@ -78,17 +138,60 @@ struct VolatileState {
     leader_id: Option<u64>,
     last_heartbeat: Instant,
     votes_received: HashSet<u64>,
+    /// Last time a majority of voters was known to be reachable
+    quorum_seen: Instant,
+    /// Quorum lost for longer than `quorum_loss_after`: writes are refused
+    safe_mode: bool,
+    /// Term a pre-vote is running for, and who would vote
+    pre_vote: Option<u64>,
+    pre_votes: HashSet<u64>,
+    /// Read lease from the current leader, on followers
+    lease: Option<ReadLease>,
+}
+
+/// Leave to serve reads: the state as of `commit` is current at `granted`
+struct ReadLease {
+    granted: Instant,
+    until: Instant,
+    commit: u64,
 }
 
 struct LeaderState {
     next_index: HashMap<usize, u64>, // Peer Index -> Next Log Index
     match_index: HashMap<usize, u64>, // Peer Index -> Match Index
+    learner_next: HashMap<u64, u64>, // Learner ID -> Next Log Index, never counted for commit
+    learner_match: HashMap<u64, u64>, // Learner ID -> Match Index, to tell when it may be promoted
+    acked: HashMap<usize, Instant>, // Peer Index -> Last response, for quorum loss
+    snapshot: Option<Arc<Snapshot>>, // Last snapshot taken for streaming, shared by joiners
+    streams: HashMap<u64, SnapshotStream>, // Node ID -> Snapshot transfer in progress
+    transfer: Option<Transfer>, // Leadership handover in progress; writes wait for the new leader
+}
+
+struct Transfer {
+    target: u64,
+    started: Instant,
+    /// TimeoutNow went out once the target had the whole log
+    sent: bool,
+}
+
+struct SnapshotStream {
+    snapshot: Arc<Snapshot>,
+    /// Bytes the receiver confirmed
+    acked: u64,
+    /// Last chunk sent; resent after an election timeout without an answer
+    sent: Instant,
 }
 
 pub struct RaftNode {
     pub config: RaftConfig,
     pub wal: Arc<Mutex<WriteAheadLog>>,
     state_machine: Arc<dyn StateMachine>,
+    membership: Mutex<MembershipManager>,
+    /// Set once an operator forced recovery: voters are reseeded from it
+    recovery: Option<ForcedRecovery>,
+    gate: std::sync::Mutex<EntryGate>,
+    /// Snapshot being received from the leader, kept across restarts
+    incoming: std::sync::Mutex<Option<Incoming>>,
     
     // Internal State
     v_state: RwLock<VolatileState>,
@@ -109,12 +212,25 @@ impl RaftNode {
         let wal = WriteAheadLog::open(&config.storage_path)?;
         let last_index = wal.last_index();
         let hs = wal.hard_state();
+        let snapshot = wal.snapshot()?;
+        let incoming = Incoming::resume(&config.storage_path)?;
+        if let Some(i) = &incoming {
+            info!("[Raft] Resuming snapshot {} from byte {}", i.index, i.received);
+        }
 
         info!("[Raft] Node {} recovered. Term: {}, LastIndex: {}", config.id, hs.current_term, last_index);
 
+        let recovery = ForcedRecovery::load(&config.storage_path)?;
+        if let Some(r) = &recovery {
+            error!("[Raft] Membership was forced by {} at {}: node {} is the only voter", r.operator, r.at, r.from);
+        }
+
         let node = Arc::new(Self {
-            config,
             wal: Arc::new(Mutex::new(wal)),
+            membership: Mutex::new(seed_membership(&config, recovery.as_ref())?),
+            recovery,
+            gate: std::sync::Mutex::new(EntryGate::new(config.signatures.clone())),
+            incoming: std::sync::Mutex::new(incoming),
             state_machine: sm,
             v_state: RwLock::new(VolatileState {
                 role: Role::Follower,
@@ -123,16 +239,29 @@ impl RaftNode {
                 leader_id: None,
                 last_heartbeat: Instant::now(),
                 votes_received: HashSet::new(),
+                quorum_seen: Instant::now(),
+                safe_mode: false,
+                pre_vote: None,
+                pre_votes: HashSet::new(),
+                lease: None,
             }),
             l_state: Mutex::new(None),
             outbox,
+            config,
         });
 
-        // Replay any committed but unapplied logs (Simulated)
+        // The state machine starts from the snapshot, if any: replay the rest of the log
         {
             let mut v = node.v_state.write().await;
-            v.last_applied = last_index; // Assuming clean shutdown for this simplified version
-            v.commit_index = last_index;
+            if let Some(snapshot) = snapshot {
+                node.state_machine.restore_snapshot(&snapshot.data);
+                node.gate.lock().unwrap().restore(snapshot.watermarks);
+                v.last_applied = snapshot.last_included_index;
+            }
+            v.commit_index = last_index; // Assuming clean shutdown for this simplified version
+            let wal = node.wal.lock().await;
+            node.reload_membership(&wal).await?;
+            node.apply_committed(&mut v, &wal);
         }
 
         // Start Ticks
@@ -156,18 +285,40 @@ impl RaftNode {
             tokio::time::sleep(Duration::from_millis(10)).await;
 
             let mut v = self.v_state.write().await;
+
+            if v.role == Role::Leader {
+                if let Some(ls) = self.l_state.lock().await.as_ref() {
+                    if self.leader_has_quorum(ls).await {
+                        v.quorum_seen = Instant::now();
+                    }
+                }
+            }
+            let lost = v.quorum_seen.elapsed() > self.quorum_loss_after();
+            if lost != v.safe_mode {
+                v.safe_mode = lost;
+                if lost {
+                    error!("[Raft] QUORUM LOST for {:?}: safe mode, writes refused", self.quorum_loss_after());
+                    error!("[Raft] If the missing voters are gone for good: consensus force-recover --from <node> --yes");
+                } else {
+                    info!("[Raft] Quorum regained, leaving safe mode");
+                }
+            }
             
+            let votes = self.votes().await;
             match v.role {
+                // Learners and demoted voters only follow
+                Role::Follower | Role::Candidate if !votes => {}
                 Role::Follower | Role::Candidate => {
                     if v.last_heartbeat.elapsed().as_millis() as u64 > timeout_ms {
-                        info!("[Raft] Election timeout. Starting election for term.");
-                        self.start_election(&mut v).await;
+                        info!("[Raft] Election timeout. Asking voters before starting an election.");
+                        self.start_pre_vote(&mut v).await;
                     }
                 }
                 Role::Leader => {
                     if v.last_heartbeat.elapsed().as_millis() as u64 > self.config.heartbeat_interval {
                         v.last_heartbeat = Instant::now();
                         drop(v); // Drop lock before sending IO
+                        self.advance_membership().await;
                         self.send_heartbeats().await;
                     }
                 }
@@ -177,6 +328,42 @@ impl RaftNode {
 
     // --- TRANSITIONS ---
 
+    /// Canvass voters for the next term without touching anyone's term; a
+    /// real election only follows if a majority would vote for this node
+    async fn start_pre_vote(&self, v: &mut VolatileState) {
+        let (term, last_idx, last_term) = {
+            let wal = self.wal.lock().await;
+            let (last_idx, last_term) = wal.last_log_info();
+            (wal.hard_state().current_term + 1, last_idx, last_term)
+        };
+
+        v.pre_vote = Some(term);
+        v.pre_votes.clear();
+        v.pre_votes.insert(self.config.id);
+        v.last_heartbeat = Instant::now(); // Next round after another timeout
+
+        let (voters, won) = {
+            let membership = self.membership.lock().await;
+            (membership.members(), membership.is_quorum(|id| v.pre_votes.contains(&id)))
+        };
+        if won {
+            v.pre_vote = None;
+            self.start_election(v).await;
+            return;
+        }
+
+        let req = RaftMessage::PreVoteRequest {
+            term,
+            candidate_id: self.config.id,
+            last_log_index: last_idx,
+            last_log_term: last_term,
+        };
+        for i in voters {
+            if i == self.config.id { continue; }
+            let _ = self.outbox.send((i, req.clone())).await;
+        }
+    }
+
     async fn start_election(&self, v: &mut VolatileState) {
         let mut wal = self.wal.lock().await;
         let mut hs = wal.hard_state();
@@ -187,6 +374,8 @@ impl RaftNode {
 
         v.role = Role::Candidate;
         v.leader_id = None;
+        v.pre_vote = None;
+        v.lease = None;
         v.votes_received.clear();
         v.votes_received.insert(self.config.id); // Vote for self
         v.last_heartbeat = Instant::now();
@@ -204,31 +393,46 @@ impl RaftNode {
 
         drop(wal);
 
-        for (i, _) in self.config.peers.iter().enumerate() {
-            if (i as u64) == self.config.id { continue; } // Don't send to self (assuming ID maps to index)
+        let (voters, won) = {
+            let membership = self.membership.lock().await;
+            (membership.members(), membership.is_quorum(|id| v.votes_received.contains(&id)))
+        };
+        for i in voters {
+            if i == self.config.id { continue; } // Don't send to self (assuming ID maps to index)
             // Note: In this impl we assume ID corresponds to index in `peers`.
-            let _ = self.outbox.send((i as u64, req.clone())).await;
+            let _ = self.outbox.send((i, req.clone())).await;
+        }
+
+        // A lone voter, e.g. after forced recovery, elects itself
+        if won {
+            self.become_leader(hs.current_term, last_idx, v).await;
         }
     }
 
-    async fn become_leader(&self, term: u64, v: &mut VolatileState) {
+    /// Callers hold the state lock and may hold the log
+    async fn become_leader(&self, term: u64, last_idx: u64, v: &mut VolatileState) {
         info!("[Raft] Node {} elected LEADER for Term {}", self.config.id, term);
         v.role = Role::Leader;
         v.leader_id = Some(self.config.id);
+        v.quorum_seen = Instant::now();
         
-        let last_idx = self.wal.lock().await.last_index();
-        let mut next_index = HashMap::new();
-        let mut match_index = HashMap::new();
-
-        for i in 0..self.config.peers.len() {
-            next_index.insert(i, last_idx + 1);
-            match_index.insert(i, 0);
-        }
+        let mut ls = LeaderState {
+            next_index: HashMap::new(),
+            match_index: HashMap::new(),
+            learner_next: HashMap::new(),
+            learner_match: HashMap::new(),
+            acked: HashMap::new(),
+            snapshot: None,
+            streams: HashMap::new(),
+            transfer: None,
+        };
+        track_members(&mut ls, &*self.membership.lock().await, last_idx);
+        *self.l_state.lock().await = Some(ls);
 
-        *self.l_state.lock().await = Some(LeaderState { next_index, match_index });
-        
-        drop(v);
-        self.send_heartbeats().await;
+        // Heartbeat on the next tick, once the caller's locks are released
+        v.last_heartbeat = Instant::now()
+            .checked_sub(Duration::from_millis(self.config.heartbeat_interval + 1))
+            .unwrap_or_else(Instant::now);
     }
 
     // --- MESSAGE HANDLER ---
@@ -243,6 +447,12 @@ impl RaftNode {
             RaftMessage::VoteRequest { term, .. } => *term,
             RaftMessage::AppendEntriesResponse { term, .. } => *term,
             RaftMessage::VoteResponse { term, .. } => *term,
+            RaftMessage::InstallSnapshot { term, .. } => *term,
+            RaftMessage::InstallSnapshotResponse { term, .. } => *term,
+            RaftMessage::PreVoteResponse { term, .. } => *term,
+            RaftMessage::TimeoutNow { term, .. } => *term,
+            // Only a proposal: the whole point is that it changes no term
+            RaftMessage::PreVoteRequest { .. } => 0,
         };
 
         // Step down if we see a higher term
@@ -253,8 +463,10 @@ impl RaftNode {
             wal.save_hard_state(hs.current_term, hs.voted_for).unwrap();
             v.role = Role::Follower;
             v.leader_id = None;
+            v.lease = None;
         }
 
+        let votes = self.votes().await;
         match msg {
             RaftMessage::VoteRequest { term, candidate_id, last_log_index, last_log_term } => {
                 let (my_last_idx, my_last_term) = wal.last_log_info();
@@ -262,7 +474,7 @@ impl RaftNode {
                 let log_ok = (last_log_term > my_last_term) || 
                              (last_log_term == my_last_term && last_log_index >= my_last_idx);
 
-                let grant = if term < hs.current_term {
+                let grant = if term < hs.current_term || !votes {
                     false
                 } else if (hs.voted_for.is_none() || hs.voted_for == Some(candidate_id)) && log_ok {
                     hs.voted_for = Some(candidate_id);
@@ -280,6 +492,42 @@ impl RaftNode {
                 })).await;
             }
 
+            RaftMessage::PreVoteRequest { term, candidate_id, last_log_index, last_log_term } => {
+                let (my_last_idx, my_last_term) = wal.last_log_info();
+                let log_ok = (last_log_term > my_last_term) ||
+                             (last_log_term == my_last_term && last_log_index >= my_last_idx);
+                // While the leader is heard from, the candidate is the one cut off
+                let leader_alive = v.role == Role::Leader || (v.leader_id.is_some()
+                    && v.last_heartbeat.elapsed() < Duration::from_millis(self.config.election_timeout_min));
+
+                let grant = votes && term > hs.current_term && log_ok && !leader_alive;
+                debug!("[Raft] Pre-vote request from {} for term {}: Granted={}", candidate_id, term, grant);
+                let _ = self.outbox.send((candidate_id, RaftMessage::PreVoteResponse {
+                    term: hs.current_term,
+                    pre_term: term,
+                    vote_granted: grant,
+                })).await;
+            }
+
+            RaftMessage::PreVoteResponse { pre_term, vote_granted, .. } => {
+                if vote_granted && v.pre_vote == Some(pre_term) && pre_term == hs.current_term + 1 {
+                    v.pre_votes.insert(_from);
+                    if self.membership.lock().await.is_quorum(|id| v.pre_votes.contains(&id)) {
+                        info!("[Raft] Pre-vote won for term {}", pre_term);
+                        drop(wal); // The election takes the log
+                        self.start_election(&mut v).await;
+                    }
+                }
+            }
+
+            RaftMessage::TimeoutNow { term, leader_id } => {
+                if term == hs.current_term && votes {
+                    info!("[Raft] Leader {} is handing over: starting election", leader_id);
+                    drop(wal);
+                    self.start_election(&mut v).await;
+                }
+            }
+
             RaftMessage::VoteResponse { term, vote_granted } => {
                 if v.role == Role::Candidate && term == hs.current_term && vote_granted {
                     // We don't have 'from' ID in params easily without changing sig, 
@@ -290,13 +538,13 @@ impl RaftNode {
                     // Updated signature to take `from`.
                     
                     v.votes_received.insert(_from);
-                    if v.votes_received.len() > self.config.peers.len() / 2 {
-                        self.become_leader(hs.current_term, &mut v).await;
+                    if self.membership.lock().await.is_quorum(|id| v.votes_received.contains(&id)) {
+                        self.become_leader(hs.current_term, wal.last_index(), &mut v).await;
                     }
                 }
             }
 
-            RaftMessage::AppendEntries { term, leader_id, prev_log_index, prev_log_term, entries, leader_commit } => {
+            RaftMessage::AppendEntries { term, leader_id, prev_log_index, prev_log_term, entries, leader_commit, lease_ms } => {
                 if term < hs.current_term {
                     let _ = self.outbox.send((leader_id, RaftMessage::AppendEntriesResponse {
                         term: hs.current_term, success: false, match_index: 0, conflict_index: 0
@@ -307,11 +555,13 @@ impl RaftNode {
                 v.role = Role::Follower;
                 v.leader_id = Some(leader_id);
                 v.last_heartbeat = Instant::now();
+                v.quorum_seen = Instant::now();
+                v.pre_vote = None;
 
                 // Consistency Check
                 if prev_log_index > 0 {
-                    match wal.get_entry(prev_log_index) {
-                        Some(entry) if entry.term() == prev_log_term => {} // OK
+                    match wal.term_at(prev_log_index) {
+                        Some(t) if t == prev_log_term => {} // OK
                         _ => {
                             // Inconsistent
                             let _ = self.outbox.send((leader_id, RaftMessage::AppendEntriesResponse {
@@ -323,65 +573,115 @@ impl RaftNode {
                 }
 
                 // Append
+                let mut reconfigured = false;
                 for (i, entry) in entries.iter().enumerate() {
                     let idx = prev_log_index + 1 + i as u64;
                     if let Some(existing) = wal.get_entry(idx) {
                         if existing.term() != entry.term() {
+                            // The dropped suffix may have held a configuration
                             wal.truncate_suffix(idx)?;
                             wal.append(entry.clone())?;
+                            reconfigured = true;
                         }
                     } else {
                         wal.append(entry.clone())?;
+                        reconfigured |= matches!(entry, LogEntry::Config { .. });
                     }
                 }
+                if reconfigured {
+                    self.reload_membership(&wal).await?;
+                }
 
                 let last_new_idx = prev_log_index + entries.len() as u64;
                 if leader_commit > v.commit_index {
                     v.commit_index = std::cmp::min(leader_commit, last_new_idx);
-                    // Apply
-                    while v.last_applied < v.commit_index {
-                        v.last_applied += 1;
-                        if let Some(LogEntry::Command { data, .. }) = wal.get_entry(v.last_applied) {
-                            self.state_machine.apply(&data);
-                        }
-                    }
+                    self.apply_committed(&mut v, &wal);
                 }
+                // Only a log that matches the leader's may be read from
+                let granted = Instant::now();
+                v.lease = (lease_ms > 0).then(|| ReadLease {
+                    granted,
+                    until: granted + Duration::from_millis(lease_ms),
+                    commit: leader_commit,
+                });
 
                 let _ = self.outbox.send((leader_id, RaftMessage::AppendEntriesResponse {
                     term: hs.current_term, success: true, match_index: last_new_idx, conflict_index: 0
                 })).await;
             }
 
-            RaftMessage::AppendEntriesResponse { term, success, match_index, conflict_index: _ } => {
+            RaftMessage::AppendEntriesResponse { term, success, match_index, conflict_index } => {
                 if v.role == Role::Leader && term == hs.current_term {
                     let mut ls_guard = self.l_state.lock().await;
                     if let Some(ls) = ls_guard.as_mut() {
                         let peer_idx = _from as usize;
-                        if success {
+                        if let Some(next) = ls.learner_next.get_mut(&_from) {
+                            // Learners catch up, but their progress never commits anything
+                            *next = if success {
+                                ls.learner_match.insert(_from, match_index);
+                                match_index + 1
+                            } else {
+                                conflict_index.min(next.saturating_sub(1)).max(1)
+                            };
+                        } else if success {
+                            ls.acked.insert(peer_idx, Instant::now());
                             ls.match_index.insert(peer_idx, match_index);
                             ls.next_index.insert(peer_idx, match_index + 1);
-                            
-                            // Advance commit index
-                            let mut indices: Vec<u64> = ls.match_index.values().cloned().collect();
-                            indices.push(wal.last_index()); // Include self
-                            indices.sort_unstable();
-                            
-                            // Majority index
-                            let majority_idx = indices[indices.len().saturating_sub((self.config.peers.len() / 2) + 1)];
-                            
-                            if majority_idx > v.commit_index {
-                                if let Some(e) = wal.get_entry(majority_idx) {
-                                    if e.term() == hs.current_term {
-                                        v.commit_index = majority_idx;
-                                        // Apply locally
-                                        // Note: Logic duplicated for brevity, ideally helper function
-                                    }
-                                }
-                            }
+                            self.advance_commit(&mut v, &wal, ls, hs.current_term).await;
                         } else {
-                            // Backtrack
+                            ls.acked.insert(peer_idx, Instant::now());
+                            // Backtrack, straight to the end of a short log
                             let next = ls.next_index.entry(peer_idx).or_insert(1);
-                            *next = (*next).saturating_sub(1).max(1);
+                            *next = conflict_index.min(next.saturating_sub(1)).max(1);
+                        }
+                    }
+                }
+            }
+
+            RaftMessage::InstallSnapshot { term, leader_id, last_included_index, last_included_term, offset, data, done, watermarks } => {
+                let reply = |received, installed| RaftMessage::InstallSnapshotResponse {
+                    term: hs.current_term, last_included_index, received, installed
+                };
+                if term < hs.current_term {
+                    let _ = self.outbox.send((leader_id, reply(0, false))).await;
+                    return Ok(());
+                }
+
+                v.role = Role::Follower;
+                v.leader_id = Some(leader_id);
+                v.last_heartbeat = Instant::now();
+                v.quorum_seen = Instant::now();
+
+                // Caught up some other way meanwhile
+                if v.last_applied >= last_included_index {
+                    let _ = self.outbox.send((leader_id, reply(offset + data.len() as u64, true))).await;
+                    return Ok(());
+                }
+
+                let (received, snapshot) = self.receive_chunk(last_included_index, last_included_term, offset, &data, done, watermarks)?;
+                let installed = snapshot.is_some();
+                if let Some(snapshot) = snapshot {
+                    wal.install(&snapshot)?;
+                    self.reload_membership(&wal).await?;
+                    self.state_machine.restore_snapshot(&snapshot.data);
+                    self.gate.lock().unwrap().restore(snapshot.watermarks);
+                    v.last_applied = snapshot.last_included_index;
+                    v.commit_index = v.commit_index.max(snapshot.last_included_index);
+                    info!("[Raft] Installed snapshot at index {} ({} bytes)", last_included_index, received);
+                }
+                let _ = self.outbox.send((leader_id, reply(received, installed))).await;
+            }
+
+            RaftMessage::InstallSnapshotResponse { term, last_included_index, received, installed } => {
+                if v.role == Role::Leader && term == hs.current_term {
+                    let mut ls_guard = self.l_state.lock().await;
+                    if let Some(ls) = ls_guard.as_mut() {
+                        if !ls.learner_next.contains_key(&_from) {
+                            ls.acked.insert(_from as usize, Instant::now());
+                        }
+                        let next = self.snapshot_progress(ls, _from, hs.current_term, last_included_index, received, installed);
+                        if let Some(msg) = next {
+                            let _ = self.outbox.send((_from, msg)).await;
                         }
                     }
                 }
@@ -390,19 +690,460 @@ impl RaftNode {
         Ok(())
     }
 
+    /// Store a chunk of the snapshot at `index`. Returns the bytes held, which
+    /// the leader continues from, and the snapshot once complete.
+    fn receive_chunk(&self, index: u64, term: u64, offset: u64, data: &[u8], done: bool, watermarks: ProducerWatermarks) -> Result<(u64, Option<Snapshot>)> {
+        let mut incoming = self.incoming.lock().unwrap();
+        if !incoming.as_ref().is_some_and(|i| i.index == index && i.term == term) {
+            // Another snapshot, or none: the leader starts over from byte 0
+            if offset != 0 {
+                return Ok((0, None));
+            }
+            *incoming = Some(Incoming::start(&self.config.storage_path, index, term)?);
+        }
+        let Some(transfer) = incoming.as_mut() else {
+            return Ok((0, None));
+        };
+        // Out of order, e.g. a resend: tell the leader where to resume
+        if offset != transfer.received {
+            return Ok((transfer.received, None));
+        }
+        transfer.write(offset, data)?;
+        let received = transfer.received;
+        if !done {
+            return Ok((received, None));
+        }
+        match incoming.take() {
+            Some(transfer) => Ok((received, Some(transfer.finish(watermarks)?))),
+            None => Ok((received, None)),
+        }
+    }
+
+    /// The leader's side of an answered chunk: the next chunk to send, if any
+    fn snapshot_progress(&self, ls: &mut LeaderState, target: u64, term: u64, index: u64, received: u64, installed: bool) -> Option<RaftMessage> {
+        if ls.streams.get(&target)?.snapshot.last_included_index != index {
+            return None;
+        }
+        if installed {
+            ls.streams.remove(&target);
+            info!("[Raft] Node {} installed the snapshot at index {}", target, index);
+            match ls.learner_next.get_mut(&target) {
+                Some(next) => {
+                    *next = index + 1;
+                    ls.learner_match.insert(target, index);
+                }
+                None => {
+                    ls.match_index.insert(target as usize, index);
+                    ls.next_index.insert(target as usize, index + 1);
+                }
+            }
+            return None;
+        }
+        let stream = ls.streams.get_mut(&target)?;
+        // A second answer for the same byte: that chunk is already on its way
+        if received == stream.acked {
+            return None;
+        }
+        stream.acked = received;
+        stream.sent = Instant::now();
+        Some(self.snapshot_chunk(term, &stream.snapshot, received))
+    }
+
+    fn snapshot_chunk(&self, term: u64, snapshot: &Snapshot, offset: u64) -> RaftMessage {
+        let (data, done) = snapshot.chunk(offset);
+        RaftMessage::InstallSnapshot {
+            term,
+            leader_id: self.config.id,
+            last_included_index: snapshot.last_included_index,
+            last_included_term: snapshot.last_included_term,
+            offset,
+            data: data.to_vec(),
+            done,
+            watermarks: if done { snapshot.watermarks.clone() } else { ProducerWatermarks::default() },
+        }
+    }
+
+    /// Whether `next` calls for a snapshot: the log no longer has it, or the
+    /// node is empty while the leader has applied past the threshold
+    fn needs_snapshot(&self, v: &VolatileState, wal: &WriteAheadLog, next: u64) -> bool {
+        let threshold = self.config.snapshot_threshold;
+        next <= wal.snapshot_index() || (next == 1 && threshold > 0 && v.last_applied >= threshold)
+    }
+
+    /// The snapshot to stream, reused for other joiners until it is a
+    /// threshold behind. Callers hold the state lock and the log.
+    fn leader_snapshot(&self, v: &VolatileState, wal: &WriteAheadLog, ls: &mut LeaderState) -> Arc<Snapshot> {
+        let stale_after = self.config.snapshot_threshold.max(1);
+        if let Some(s) = ls.snapshot.as_ref().filter(|s| v.last_applied - s.last_included_index < stale_after) {
+            return s.clone();
+        }
+        let snapshot = Arc::new(Snapshot {
+            last_included_index: v.last_applied,
+            last_included_term: wal.term_at(v.last_applied).unwrap_or(0),
+            data: self.state_machine.take_snapshot(),
+            watermarks: self.gate.lock().unwrap().watermarks().clone(),
+        });
+        ls.snapshot = Some(snapshot.clone());
+        snapshot
+    }
+
+    /// Hand leadership to voter `target`, e.g. to drain this node before
+    /// maintenance. Writes are refused meanwhile; once `target` has the whole
+    /// log it is told to start an election right away, which it wins.
+    pub async fn transfer_leadership(&self, target: u64) -> Result<()> {
+        {
+            let v = self.v_state.read().await;
+            if v.role != Role::Leader {
+                anyhow::bail!("Not leader");
+            }
+            if target == self.config.id {
+                anyhow::bail!("Node {} already leads", target);
+            }
+            if !self.membership.lock().await.members().contains(&target) {
+                anyhow::bail!("Node {} is not a voter", target);
+            }
+            let mut ls = self.l_state.lock().await;
+            let Some(ls) = ls.as_mut() else {
+                anyhow::bail!("Not leader");
+            };
+            ls.transfer = Some(Transfer { target, started: Instant::now(), sent: false });
+        }
+        info!("[Raft] Transferring leadership to node {}", target);
+        self.send_heartbeats().await; // Catch the target up
+
+        let deadline = Instant::now() + self.transfer_timeout();
+        while Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(self.config.heartbeat_interval)).await;
+            if self.v_state.read().await.leader_id == Some(target) {
+                return Ok(());
+            }
+        }
+        anyhow::bail!("Node {} did not take over within {:?}", target, self.transfer_timeout())
+    }
+
+    /// Follower read leases end well before anyone may stand for election
+    fn lease_ms(&self) -> u64 {
+        self.config.election_timeout_min.saturating_sub(self.config.heartbeat_interval)
+    }
+
+    /// Run `read` on the state machine, if this node may serve the read.
+    /// `max_staleness: None` reads on the leader only; otherwise a follower
+    /// holding a lease serves state at most that old. Returns the applied
+    /// index and how stale the state may be.
+    pub async fn read<T>(
+        &self,
+        max_staleness: Option<Duration>,
+        read: impl FnOnce(&dyn StateMachine) -> T,
+    ) -> Result<(u64, Duration, T)> {
+        // Under the state lock: nothing is applied while reading
+        let v = self.v_state.read().await;
+        if v.role == Role::Leader {
+            if v.safe_mode {
+                anyhow::bail!("Quorum lost: this leader may be deposed, read elsewhere");
+            }
+            return Ok((v.last_applied, Duration::ZERO, read(&*self.state_machine)));
+        }
+        let Some(max) = max_staleness else {
+            anyhow::bail!("Not leader: strong reads go to node {:?}", v.leader_id);
+        };
+        let Some(lease) = v.lease.as_ref().filter(|l| l.until > Instant::now()) else {
+            anyhow::bail!("No read lease from a leader");
+        };
+        if v.last_applied < lease.commit {
+            anyhow::bail!("Still applying up to index {} ({} so far)", lease.commit, v.last_applied);
+        }
+        let staleness = lease.granted.elapsed();
+        if staleness > max {
+            anyhow::bail!("State is {:?} old, more than the {:?} allowed", staleness, max);
+        }
+        Ok((v.last_applied, staleness, read(&*self.state_machine)))
+    }
+
+    fn transfer_timeout(&self) -> Duration {
+        Duration::from_millis(self.config.election_timeout_max * 2)
+    }
+
+    /// Entries skipped by signature checks since boot
+    pub fn rejected(&self) -> u64 {
+        self.gate.lock().unwrap().rejected
+    }
+
+    /// Commit what a majority of voters, this node included, has stored.
+    /// Callers hold the state lock and the log.
+    async fn advance_commit(&self, v: &mut VolatileState, wal: &WriteAheadLog, ls: &LeaderState, term: u64) {
+        let majority_idx = self.membership.lock().await.committed(|id| {
+            if id == self.config.id {
+                wal.last_index()
+            } else {
+                ls.match_index.get(&(id as usize)).copied().unwrap_or(0)
+            }
+        });
+
+        if majority_idx > v.commit_index {
+            if let Some(e) = wal.get_entry(majority_idx) {
+                if e.term() == term {
+                    v.commit_index = majority_idx;
+                    self.apply_committed(v, wal);
+                }
+            }
+        }
+    }
+
+    /// Whether this node may vote and stand for election: learners and
+    /// removed voters may not
+    async fn votes(&self) -> bool {
+        self.membership.lock().await.is_voter(self.config.id)
+    }
+
+    /// How long without a reachable majority before safe mode
+    fn quorum_loss_after(&self) -> Duration {
+        Duration::from_millis(self.config.election_timeout_max * 20)
+    }
+
+    /// A leader keeps quorum while a majority of voters, itself included,
+    /// answered within an election timeout
+    async fn leader_has_quorum(&self, ls: &LeaderState) -> bool {
+        let recent = Duration::from_millis(self.config.election_timeout_max);
+        self.membership.lock().await.is_quorum(|id| {
+            id == self.config.id || ls.acked.get(&(id as usize)).is_some_and(|t| t.elapsed() < recent)
+        })
+    }
+
+    /// Safe mode, how long quorum has been missing, and the forced
+    /// recovery's survivor if there was one
+    pub async fn quorum(&self) -> (bool, Duration, Option<u64>) {
+        let v = self.v_state.read().await;
+        let lost = if v.safe_mode { v.quorum_seen.elapsed() } else { Duration::ZERO };
+        (v.safe_mode, lost, self.recovery.as_ref().map(|r| r.from))
+    }
+
+    /// Apply entries up to the commit index. Callers hold both locks.
+    fn apply_committed(&self, v: &mut VolatileState, wal: &WriteAheadLog) {
+        let mut gate = self.gate.lock().unwrap();
+        while v.last_applied < v.commit_index {
+            v.last_applied += 1;
+            if let Some(entry) = wal.get_entry(v.last_applied) {
+                // Kept aside: the entry itself may be compacted away
+                if let LogEntry::Config { config, .. } = &entry {
+                    if let Err(e) = config.save(&self.config.storage_path) {
+                        warn!("[Raft] Could not save the committed membership: {}", e);
+                    }
+                }
+                if let Some(data) = gate.admit(v.last_applied, &entry) {
+                    self.state_machine.apply(data);
+                }
+            }
+        }
+    }
+
+    /// Change membership through the log (see `membership`). Learner changes
+    /// return once appended; voter changes once the new voters alone have
+    /// committed. Leader only.
+    pub async fn change_membership(&self, change: MembershipChange) -> Result<()> {
+        let commit = {
+            let v = self.v_state.read().await;
+            if v.role != Role::Leader {
+                anyhow::bail!("Not leader");
+            }
+            v.commit_index
+        };
+        if let MembershipChange::Promote { ids } = &change {
+            // A voter that is far behind would stall commits until it caught up
+            let ls = self.l_state.lock().await;
+            for id in ids {
+                let matched = ls.as_ref().and_then(|ls| ls.learner_match.get(id)).copied().unwrap_or(0);
+                if matched < commit {
+                    anyhow::bail!("Learner {} is still catching up (at {} of {})", id, matched, commit);
+                }
+            }
+        }
+        let next = self.membership.lock().await.propose_change(&change, commit)?;
+        let joint = next.joint.is_some();
+
+        info!("[Raft] Membership change: {:?}", change);
+        let index = self.append(|term| LogEntry::Config { term, config: next }).await?;
+        if !joint {
+            return Ok(());
+        }
+
+        // The leader leaves the joint configuration once it commits (see `advance_membership`)
+        let deadline = Instant::now() + self.quorum_loss_after();
+        while Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(self.config.heartbeat_interval)).await;
+            let commit = self.v_state.read().await.commit_index;
+            let membership = self.membership.lock().await;
+            if commit > index && !membership.is_joint() && membership.is_committed(commit) {
+                return Ok(());
+            }
+        }
+        anyhow::bail!("Membership change at index {} did not complete within {:?}", index, self.quorum_loss_after())
+    }
+
+    /// Leader only, on heartbeats: move from a committed joint configuration
+    /// to the new voters alone, and step down once a configuration without
+    /// this node has committed
+    async fn advance_membership(&self) {
+        let commit = self.v_state.read().await.commit_index;
+        let (next, removed) = {
+            let membership = self.membership.lock().await;
+            let removed = membership.is_committed(commit) && !membership.is_voter(self.config.id);
+            (membership.leave_joint(commit), removed)
+        };
+        if let Some(next) = next {
+            info!("[Raft] Joint configuration committed, moving to voters {:?}", next.voters);
+            if let Err(e) = self.append(|term| LogEntry::Config { term, config: next }).await {
+                warn!("[Raft] Could not leave the joint configuration yet: {}", e);
+            }
+        } else if removed {
+            info!("[Raft] No longer a voter, stepping down");
+            let mut v = self.v_state.write().await;
+            v.role = Role::Follower;
+            v.leader_id = None;
+            *self.l_state.lock().await = None;
+        }
+    }
+
+    /// Use the newest configuration in the log, or the last committed one if
+    /// the log holds none. Callers hold the log.
+    async fn reload_membership(&self, wal: &WriteAheadLog) -> Result<()> {
+        let since = wal.snapshot_index().max(self.recovery.as_ref().map_or(0, |r| r.index));
+        let latest = (since + 1..=wal.last_index()).rev().find_map(|index| match wal.get_entry(index) {
+            Some(LogEntry::Config { config, .. }) => Some((index, config)),
+            _ => None,
+        });
+        let mut ls = self.l_state.lock().await;
+        let mut membership = self.membership.lock().await;
+        match latest {
+            Some((index, config)) => membership.adopt(index, &config),
+            None => *membership = seed_membership(&self.config, self.recovery.as_ref())?,
+        }
+        if let Some(ls) = ls.as_mut() {
+            track_members(ls, &membership, wal.last_index());
+        }
+        Ok(())
+    }
+
+    pub async fn configuration(&self) -> Configuration {
+        self.membership.lock().await.configuration()
+    }
+
+    /// Learners that have the whole committed log and may be promoted (leader only)
+    pub async fn caught_up(&self) -> Vec<u64> {
+        let commit = self.v_state.read().await.commit_index;
+        let ls = self.l_state.lock().await;
+        let Some(ls) = ls.as_ref() else {
+            return Vec::new();
+        };
+        let mut ids: Vec<u64> = ls.learner_match.iter().filter(|(_, m)| **m >= commit).map(|(id, _)| *id).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Where to send messages for `id`: a voter by its index in `peers`, or
+    /// by the address it joined with
+    pub async fn address_of(&self, id: u64) -> Option<String> {
+        match self.config.peers.get(id as usize) {
+            Some(peer) => Some(peer.clone()),
+            None => self.membership.lock().await.address(id).cloned(),
+        }
+    }
+
+    pub async fn commit_index(&self) -> u64 {
+        self.v_state.read().await.commit_index
+    }
+
+    /// The leader this node last heard from, or itself when leading
+    pub async fn leader(&self) -> Option<u64> {
+        self.v_state.read().await.leader_id
+    }
+
+    /// Read the log back from disk, apply what this node has applied to
+    /// `fresh`, and hash both state machines (see `cell_sdk::replay`).
+    pub async fn verify_replay(&self, fresh: Arc<dyn StateMachine>) -> Result<ReplayReport> {
+        // Snapshot and index taken together: nothing is applied under the read lock
+        let (applied, live_hash) = {
+            let v = self.v_state.read().await;
+            (v.last_applied, state_hash(&self.state_machine.take_snapshot()))
+        };
+
+        let on_disk = WriteAheadLog::open(&self.config.storage_path)?;
+        let mut gate = EntryGate::new(self.config.signatures.clone());
+        let mut replayed = 0;
+        if let Some(snapshot) = on_disk.snapshot()? {
+            fresh.restore_snapshot(&snapshot.data);
+            gate.restore(snapshot.watermarks);
+            replayed = snapshot.last_included_index;
+        }
+        for index in replayed + 1..=applied {
+            let Some(entry) = on_disk.get_entry(index) else {
+                break;
+            };
+            if let Some(data) = gate.admit(index, &entry) {
+                fresh.apply(data);
+            }
+            replayed = index;
+        }
+
+        Ok(ReplayReport {
+            applied,
+            live_hash,
+            replayed,
+            replay_hash: state_hash(&fresh.take_snapshot()),
+        })
+    }
+
     async fn send_heartbeats(&self) {
+        let members = self.membership.lock().await.members();
         let wal = self.wal.lock().await;
         let hs = wal.hard_state();
         let v = self.v_state.read().await;
         let mut ls_guard = self.l_state.lock().await;
         
         if let Some(ls) = ls_guard.as_mut() {
-            for (i, _) in self.config.peers.iter().enumerate() {
-                if (i as u64) == self.config.id { continue; }
-                
-                let next = *ls.next_index.get(&i).unwrap_or(&(wal.last_index() + 1));
+            let voters = members.into_iter()
+                .filter(|i| *i != self.config.id)
+                .map(|i| (i, *ls.next_index.get(&(i as usize)).unwrap_or(&(wal.last_index() + 1))));
+            let learners = ls.learner_next.iter().map(|(id, next)| (*id, *next));
+            let targets: Vec<_> = voters.chain(learners).collect();
+
+            if let Some(transfer) = ls.transfer.as_mut() {
+                let target = transfer.target;
+                if transfer.started.elapsed() > self.transfer_timeout() {
+                    warn!("[Raft] Leadership transfer to node {} timed out, staying leader", target);
+                    ls.transfer = None;
+                } else if !transfer.sent && ls.match_index.get(&(target as usize)).copied().unwrap_or(0) >= wal.last_index() {
+                    transfer.sent = true;
+                    info!("[Raft] Node {} has the whole log, handing over leadership", target);
+                    let msg = RaftMessage::TimeoutNow { term: hs.current_term, leader_id: self.config.id };
+                    let _ = self.outbox.send((target, msg)).await;
+                }
+            }
+
+            // Nobody reads from followers while they may be out of touch
+            let lease_ms = if v.safe_mode || ls.transfer.is_some() { 0 } else { self.lease_ms() };
+
+            for (target, next) in targets {
+                if !ls.streams.contains_key(&target) && self.needs_snapshot(&v, &wal, next) {
+                    let snapshot = self.leader_snapshot(&v, &wal, ls);
+                    info!("[Raft] Streaming snapshot at index {} to node {} ({} bytes)",
+                        snapshot.last_included_index, target, snapshot.data.len());
+                    let msg = self.snapshot_chunk(hs.current_term, &snapshot, 0);
+                    ls.streams.insert(target, SnapshotStream { snapshot, acked: 0, sent: Instant::now() });
+                    let _ = self.outbox.send((target, msg)).await;
+                    continue;
+                }
+                if let Some(stream) = ls.streams.get_mut(&target) {
+                    // Chunks follow answers; resend only once the last one seems lost
+                    if stream.sent.elapsed() > Duration::from_millis(self.config.election_timeout_max) {
+                        stream.sent = Instant::now();
+                        let msg = self.snapshot_chunk(hs.current_term, &stream.snapshot, stream.acked);
+                        let _ = self.outbox.send((target, msg)).await;
+                    }
+                    continue;
+                }
+
                 let prev_log_index = next - 1;
-                let prev_log_term = wal.get_entry(prev_log_index).map(|e| e.term()).unwrap_or(0);
+                let prev_log_term = wal.term_at(prev_log_index).unwrap_or(0);
                 
                 let entries = wal.get_entries_from(next);
                 
@@ -413,28 +1154,126 @@ impl RaftNode {
                     prev_log_term,
                     entries,
                     leader_commit: v.commit_index,
+                    lease_ms,
                 };
                 
-                let _ = self.outbox.send((i as u64, msg)).await;
+                let _ = self.outbox.send((target, msg)).await;
             }
         }
     }
 
     pub async fn propose(&self, data: Vec<u8>) -> Result<u64> {
+        if self.config.signatures.is_some() {
+            anyhow::bail!("This log only accepts signed entries");
+        }
+        self.append(|term| LogEntry::Command { term, data }).await
+    }
+
+    /// Propose a producer-signed command. Replicas verify it again before
+    /// apply; checking here only spares the producer a silent rejection.
+    pub async fn propose_signed(&self, command: SignedCommand) -> Result<u64> {
+        if let Some(policy) = &self.config.signatures {
+            policy.verify(&command)?;
+            if !self.gate.lock().unwrap().is_new(&command) {
+                anyhow::bail!("Entry {} of '{}' was already applied", command.seq, command.producer);
+            }
+        }
+        self.append(|term| LogEntry::Signed { term, command }).await
+    }
+
+    async fn append(&self, entry: impl FnOnce(u64) -> LogEntry) -> Result<u64> {
         let v = self.v_state.read().await;
+        if v.safe_mode {
+            anyhow::bail!(
+                "Quorum lost for {:?}: writes are refused until a majority of voters is back",
+                v.quorum_seen.elapsed()
+            );
+        }
         if v.role != Role::Leader {
             anyhow::bail!("Not leader");
         }
         drop(v); // Drop read lock
+        if let Some(t) = self.l_state.lock().await.as_ref().and_then(|ls| ls.transfer.as_ref()) {
+            anyhow::bail!("Handing leadership to node {}: retry on the new leader", t.target);
+        }
 
         let mut wal = self.wal.lock().await;
         let hs = wal.hard_state();
         
-        let entry = LogEntry::Command { term: hs.current_term, data };
+        let entry = entry(hs.current_term);
+        let config = match &entry {
+            LogEntry::Config { config, .. } => Some(config.clone()),
+            _ => None,
+        };
         let index = wal.append(entry)?;
+        if let Some(config) = config {
+            // In use from now on, committed or not
+            let mut ls = self.l_state.lock().await;
+            let mut membership = self.membership.lock().await;
+            membership.adopt(index, &config);
+            if let Some(ls) = ls.as_mut() {
+                track_members(ls, &membership, index - 1);
+            }
+        }
         
         drop(wal);
+        {
+            // A lone voter commits on its own
+            let mut v = self.v_state.write().await;
+            let wal = self.wal.lock().await;
+            if let Some(ls) = self.l_state.lock().await.as_ref() {
+                self.advance_commit(&mut v, &wal, ls, hs.current_term).await;
+            }
+        }
         self.send_heartbeats().await; // Replicate immediately
         Ok(index)
     }
-}
\ No newline at end of file
+}
+
+/// The membership a node starts from before reading its log: the last
+/// committed configuration (forced recovery deletes older ones), or else
+/// the voters it was ignited with
+fn seed_membership(config: &RaftConfig, recovery: Option<&ForcedRecovery>) -> Result<MembershipManager> {
+    if let Some(committed) = Configuration::load(&config.storage_path)? {
+        let mut membership = MembershipManager::new(Vec::new());
+        membership.adopt(0, &committed);
+        return Ok(membership);
+    }
+    let voters = match (recovery, config.peers.len() as u64) {
+        (Some(r), _) => vec![r.from],
+        // A node started on its own leads alone until others join
+        (None, 0) if !config.learner => {
+            let mut membership = MembershipManager::new(Vec::new());
+            membership.adopt(0, &Configuration {
+                voters: vec![config.id],
+                joint: None,
+                learners: Vec::new(),
+                addresses: vec![(config.id, config.address.clone())],
+            });
+            return Ok(membership);
+        }
+        (None, peers) => (0..peers).filter(|i| !(config.learner && *i == config.id)).collect(),
+    };
+    Ok(MembershipManager::new(voters))
+}
+
+/// Replicate to every voter and learner in use, carrying a learner's progress
+/// over when it is promoted. `last_idx` is where new members start.
+fn track_members(ls: &mut LeaderState, membership: &MembershipManager, last_idx: u64) {
+    for id in membership.members() {
+        let peer = id as usize;
+        if !ls.next_index.contains_key(&peer) {
+            ls.next_index.insert(peer, ls.learner_next.get(&id).copied().unwrap_or(last_idx + 1));
+            ls.match_index.insert(peer, ls.learner_match.get(&id).copied().unwrap_or(0));
+        }
+    }
+    ls.next_index.retain(|peer, _| membership.is_voter(*peer as u64));
+    ls.match_index.retain(|peer, _| membership.is_voter(*peer as u64));
+
+    let learners = membership.learners();
+    ls.learner_next.retain(|id, _| learners.contains_key(id));
+    ls.learner_match.retain(|id, _| learners.contains_key(id));
+    for id in learners.keys() {
+        ls.learner_next.entry(*id).or_insert(last_idx + 1);
+    }
+}
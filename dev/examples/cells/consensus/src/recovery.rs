@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Manual recovery after permanent quorum loss.
+//!
+//! A cluster that cannot reach a majority of its voters stops committing and
+//! goes into safe mode: writes are refused until quorum returns. If the missing
+//! voters are gone for good, an operator runs `consensus force-recover --from
+//! <node> --yes` on every surviving node. Node `<node>` becomes the only voter,
+//! keeping its log; the others become non-voters that follow it once it adds
+//! them back as learners. Anything only the lost voters had is gone, which is
+//! why every forced recovery is recorded next to the WAL and with the audit cell.
+//! Membership changes logged before the recovery no longer apply.
+
+use anyhow::{bail, Result};
+use cell_sdk::cell_remote;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::{error, warn};
+
+use crate::membership::Configuration;
+use crate::wal::WriteAheadLog;
+
+cell_remote!(Audit = "audit");
+
+/// Why this node's voters differ from its configured peers
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ForcedRecovery {
+    /// The surviving node, now the only voter
+    pub from: u64,
+    pub operator: String,
+    /// Unix seconds
+    pub at: u64,
+    /// Last log index at the time; configurations up to it are ignored
+    #[serde(default)]
+    pub index: u64,
+}
+
+impl ForcedRecovery {
+    pub fn path(storage_path: &Path) -> PathBuf {
+        storage_path.with_extension("recovery")
+    }
+
+    pub fn load(storage_path: &Path) -> Result<Option<Self>> {
+        match std::fs::read(Self::path(storage_path)) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, storage_path: &Path) -> Result<()> {
+        std::fs::write(Self::path(storage_path), serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Reseed this node's membership from survivor `from`. Refuses to run
+/// without `confirmed`, after saying what will be lost.
+pub async fn force_recover(
+    storage_path: &Path,
+    cell_name: &str,
+    node_id: u64,
+    from: u64,
+    confirmed: bool,
+) -> Result<ForcedRecovery> {
+    error!("╔══════════════════════════════════════════════════════════╗");
+    error!("║ FORCED RECOVERY: overriding Raft membership              ║");
+    error!("║ Node {:<4} becomes the only voter of '{:<18}' ║", from, cell_name);
+    error!("║ Entries only the lost voters had are discarded.          ║");
+    error!("║ Never run this while the lost voters may come back.      ║");
+    error!("╚══════════════════════════════════════════════════════════╝");
+    if !confirmed {
+        bail!("Refusing to force recovery without --yes");
+    }
+
+    let mut wal = WriteAheadLog::open(storage_path)?;
+    let recovery = ForcedRecovery {
+        from,
+        operator: std::env::var("USER").unwrap_or_else(|_| "unknown".to_string()),
+        at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        index: wal.last_index(),
+    };
+    if node_id == from {
+        // A term no stale voter has used, so the survivor's leadership is unambiguous
+        let term = wal.hard_state().current_term + 1;
+        wal.save_hard_state(term, None)?;
+    } else {
+        // A non-voter never votes, so forgetting its term is safe; it adopts the
+        // survivor's on the first append and its conflicting suffix is truncated
+        wal.save_hard_state(0, None)?;
+        warn!(
+            "[Recovery] Node {} is no longer a voter; add it back as a learner on node {}",
+            node_id, from
+        );
+    }
+    if let Err(e) = std::fs::remove_file(Configuration::path(storage_path)) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            return Err(e.into());
+        }
+    }
+    recovery.save(storage_path)?;
+    record(cell_name, node_id, &recovery).await;
+    Ok(recovery)
+}
+
+/// Best effort: the record next to the WAL is what the node boots from.
+async fn record(cell_name: &str, node_id: u64, recovery: &ForcedRecovery) {
+    let Ok(mut audit) = Audit::Client::connect().await else {
+        warn!("[Recovery] Audit cell not reachable; forced recovery not recorded centrally");
+        return;
+    };
+    let event = Audit::AuditEvent {
+        actor: recovery.operator.clone(),
+        action: "consensus:force-recover".to_string(),
+        resource: cell_name.to_string(),
+        outcome: "Success".to_string(),
+        metadata: format!("node {} reseeded from node {}", node_id, recovery.from),
+        timestamp: recovery.at,
+    };
+    if let Err(e) = audit.log(event).await {
+        warn!("[Recovery] Failed to record forced recovery: {}", e);
+    }
+}
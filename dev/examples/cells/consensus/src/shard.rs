@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Many Raft groups in one cell, one per key range.
+//!
+//! `CELL_RAFT_SHARDS` splits the key space at the given keys: `m,t` makes
+//! three groups covering `[..m)`, `[m..t)` and `[t..)`. Each group is a full
+//! Raft log with its own leader, WAL and state machine; they share the peer
+//! set and the CONSENSUS channel, where every message names its group. As
+//! elections land on different nodes, so do the leaders, and writes to
+//! different ranges stop queueing behind one leader.
+//!
+//! Group 0 keeps the unsharded WAL path, so an existing log becomes the first
+//! shard. The splits are written next to it on first boot; booting with
+//! other splits is refused, since keys would move to a log that never saw them.
+
+use anyhow::{bail, Result};
+use rkyv::{Archive, Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use crate::raft::{RaftConfig, RaftMessage, RaftNode, StateMachine};
+
+/// A Raft message for one group
+#[derive(Archive, Serialize, Deserialize, Debug, Clone)]
+#[archive(check_bytes)]
+pub struct GroupMessage {
+    pub group: u32,
+    pub message: RaftMessage,
+}
+
+/// Which group owns a key
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ShardMap {
+    splits: Vec<Vec<u8>>,
+}
+
+impl ShardMap {
+    /// `splits` must be non-empty keys in ascending order
+    pub fn new(splits: Vec<Vec<u8>>) -> Result<Self> {
+        if splits.iter().any(|s| s.is_empty()) {
+            bail!("Shard splits must be non-empty keys");
+        }
+        if splits.windows(2).any(|w| w[0] >= w[1]) {
+            bail!("Shard splits must be in ascending order");
+        }
+        Ok(Self { splits })
+    }
+
+    /// `CELL_RAFT_SHARDS`, comma separated; one group without it
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("CELL_RAFT_SHARDS") {
+            Ok(v) if !v.trim().is_empty() => {
+                Self::new(v.split(',').map(|s| s.trim().as_bytes().to_vec()).collect())
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn len(&self) -> u32 {
+        self.splits.len() as u32 + 1
+    }
+
+    pub fn group_for(&self, key: &[u8]) -> u32 {
+        self.splits.partition_point(|s| s.as_slice() <= key) as u32
+    }
+
+    /// First key of `group`, and the first key past it (`None`: unbounded)
+    pub fn range(&self, group: u32) -> (Vec<u8>, Option<Vec<u8>>) {
+        let g = group as usize;
+        let start = if g == 0 { Vec::new() } else { self.splits[g - 1].clone() };
+        (start, self.splits.get(g).cloned())
+    }
+
+    /// Pin the splits to the log on first boot; later boots must match
+    fn pin(&self, storage_path: &Path) -> Result<()> {
+        let path = storage_path.with_extension("shards");
+        let pinned = self.splits.iter().map(|s| String::from_utf8_lossy(s)).collect::<Vec<_>>().join(",");
+        match std::fs::read_to_string(&path) {
+            Ok(existing) if existing == pinned => Ok(()),
+            Ok(existing) => bail!(
+                "This log was sharded at '{}', not '{}': resharding is not supported",
+                existing, pinned
+            ),
+            Err(_) if storage_path.exists() && !self.splits.is_empty() => {
+                bail!("This log predates sharding: it can only boot unsharded")
+            }
+            Err(_) => Ok(std::fs::write(&path, pinned)?),
+        }
+    }
+}
+
+/// WAL of `group` for the unsharded path `base`
+pub fn storage_path(base: &Path, group: u32) -> PathBuf {
+    if group == 0 {
+        return base.to_path_buf();
+    }
+    let stem = base.file_stem().unwrap_or_default().to_string_lossy();
+    base.with_file_name(format!("{}.g{}.wal", stem, group))
+}
+
+/// The Raft groups hosted by this node
+pub struct MultiRaft {
+    map: ShardMap,
+    groups: Vec<Arc<RaftNode>>,
+}
+
+impl MultiRaft {
+    /// Start every group of `map`, each with a state machine from `sm`.
+    /// Their messages leave through `outbox` tagged with the group.
+    pub async fn ignite(
+        config: RaftConfig,
+        map: ShardMap,
+        sm: impl Fn() -> Arc<dyn StateMachine>,
+        outbox: mpsc::Sender<(u64, GroupMessage)>,
+    ) -> Result<Self> {
+        map.pin(&config.storage_path)?;
+        let mut groups = Vec::new();
+        for group in 0..map.len() {
+            let (tx, mut rx) = mpsc::channel(1000);
+            let outbox = outbox.clone();
+            tokio::spawn(async move {
+                while let Some((target, message)) = rx.recv().await {
+                    if outbox.send((target, GroupMessage { group, message })).await.is_err() {
+                        break;
+                    }
+                }
+            });
+            let config = RaftConfig {
+                storage_path: storage_path(&config.storage_path, group),
+                ..config.clone()
+            };
+            groups.push(RaftNode::ignite(config, sm(), tx).await?);
+        }
+        Ok(Self { map, groups })
+    }
+
+    pub fn map(&self) -> &ShardMap {
+        &self.map
+    }
+
+    pub fn group(&self, group: u32) -> Result<&Arc<RaftNode>> {
+        match self.groups.get(group as usize) {
+            Some(raft) => Ok(raft),
+            None => bail!("No shard {} (this cell has {})", group, self.groups.len()),
+        }
+    }
+
+    /// The group owning `key`
+    pub fn route(&self, key: &[u8]) -> (u32, &Arc<RaftNode>) {
+        let group = self.map.group_for(key);
+        (group, &self.groups[group as usize])
+    }
+
+    /// Deliver a message from the CONSENSUS channel to its group
+    pub async fn handle_message(&self, from: u64, msg: GroupMessage) -> Result<()> {
+        self.group(msg.group)?.handle_message(from, msg.message).await
+    }
+}
@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Signature checks before apply.
+//!
+//! With `CELL_RAFT_PRODUCERS` set to a directory of trusted producer keys
+//! (see `cell_sdk::signing`), the log only applies `Signed` entries whose
+//! signature verifies and whose sequence number is new for their producer.
+//! Every replica runs the same check on the same log, so a compromised leader
+//! gains nothing by appending forged, unsigned or replayed entries: they are
+//! skipped everywhere, and logged loudly.
+
+use anyhow::Result;
+use cell_sdk::signing::{ProducerWatermarks, SignedCommand, TrustedProducers};
+use std::path::Path;
+use tracing::error;
+
+use crate::wal::LogEntry;
+
+#[derive(Debug, Clone)]
+pub struct SignaturePolicy {
+    /// Log name signatures are bound to, the cell's name
+    pub log: String,
+    pub producers: TrustedProducers,
+}
+
+impl SignaturePolicy {
+    /// `None` unless `CELL_RAFT_PRODUCERS` names a key directory
+    pub fn from_env(log: &str) -> Result<Option<Self>> {
+        let Ok(dir) = std::env::var("CELL_RAFT_PRODUCERS") else {
+            return Ok(None);
+        };
+        let producers = TrustedProducers::load_dir(Path::new(&dir))?;
+        if producers.is_empty() {
+            anyhow::bail!("No producer keys in {}: nothing could be applied", dir);
+        }
+        Ok(Some(Self { log: log.to_string(), producers }))
+    }
+
+    /// Check a command before it is proposed, to fail the producer fast
+    pub fn verify(&self, command: &SignedCommand) -> Result<()> {
+        self.producers.verify(&self.log, command)
+    }
+}
+
+/// Decides which committed entries reach the state machine
+#[derive(Default)]
+pub struct EntryGate {
+    policy: Option<SignaturePolicy>,
+    watermarks: ProducerWatermarks,
+    /// Entries skipped so far
+    pub rejected: u64,
+}
+
+impl EntryGate {
+    pub fn new(policy: Option<SignaturePolicy>) -> Self {
+        Self { policy, ..Self::default() }
+    }
+
+    /// The command to apply for the entry at `index`, if any
+    pub fn admit<'a>(&mut self, index: u64, entry: &'a LogEntry) -> Option<&'a [u8]> {
+        match (entry, &self.policy) {
+            (LogEntry::NoOp { .. } | LogEntry::Config { .. }, _) => None,
+            (LogEntry::Command { data, .. }, None) => Some(data),
+            (LogEntry::Signed { command, .. }, None) => Some(&command.data),
+            (LogEntry::Command { .. }, Some(_)) => {
+                self.reject(index, "unsigned entry".to_string());
+                None
+            }
+            (LogEntry::Signed { command, .. }, Some(policy)) => {
+                if let Err(e) = policy.verify(command) {
+                    self.reject(index, e.to_string());
+                    None
+                } else if !self.watermarks.admit(&command.producer, command.seq) {
+                    let reason = format!("replayed entry {} of '{}'", command.seq, command.producer);
+                    self.reject(index, reason);
+                    None
+                } else {
+                    Some(&command.data)
+                }
+            }
+        }
+    }
+
+    /// Sequence numbers applied so far, for a snapshot
+    pub fn watermarks(&self) -> &ProducerWatermarks {
+        &self.watermarks
+    }
+
+    /// Continue from a snapshot's watermarks
+    pub fn restore(&mut self, watermarks: ProducerWatermarks) {
+        self.watermarks = watermarks;
+    }
+
+    /// Whether `command` would be applied now, as far as its sequence goes
+    pub fn is_new(&self, command: &SignedCommand) -> bool {
+        self.watermarks.last(&command.producer).is_none_or(|last| command.seq > last)
+    }
+
+    fn reject(&mut self, index: u64, reason: String) {
+        self.rejected += 1;
+        error!("[Raft] REJECTED log entry {}: {} (not applied; the leader may be compromised)", index, reason);
+    }
+}
@@ -1,11 +1,24 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
 
-use anyhow::Result;
-use std::path::Path;
-use tokio::fs::File;
-use tokio::io::{AsyncWriteExt, AsyncReadExt};
-use rkyv::{Archive, Serialize, Deserialize};
+//! State-machine snapshots, and receiving them from the leader.
+//!
+//! A node that joins with an empty log gets the leader's snapshot instead of
+//! the whole history, in chunks of [`CHUNK`] bytes over the CONSENSUS channel.
+//! Chunks land in `<wal>.snapshot.part` as they arrive, so a dropped
+//! connection or a restart resumes from the last byte on disk rather than
+//! from zero. The installed snapshot lives next to the log as
+//! `<wal>.snapshot`; the log continues after its last included entry.
+
+use anyhow::{bail, Result};
+use cell_sdk::signing::ProducerWatermarks;
+use rkyv::{Archive, Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Bytes per InstallSnapshot message
+pub const CHUNK: usize = 64 * 1024;
 
 #[derive(Archive, Serialize, Deserialize)]
 #[archive(check_bytes)]
@@ -13,23 +26,111 @@ pub struct Snapshot {
     pub last_included_index: u64,
     pub last_included_term: u64,
     pub data: Vec<u8>,
+    /// Producer sequence numbers applied up to the snapshot, so replays of
+    /// entries it covers stay rejected
+    pub watermarks: ProducerWatermarks,
 }
 
 impl Snapshot {
-    pub async fn save(&self, path: &Path) -> Result<()> {
+    pub fn path(storage_path: &Path) -> PathBuf {
+        storage_path.with_extension("snapshot")
+    }
+
+    /// Written aside and renamed into place: a crash leaves the old one
+    pub fn save(&self, path: &Path) -> Result<()> {
         let bytes = rkyv::to_bytes::<_, 1024>(self)?.into_vec();
-        let mut file = File::create(path).await?;
-        file.write_all(&bytes).await?;
-        file.sync_all().await?;
+        let tmp = path.with_extension("snapshot.tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)?;
         Ok(())
     }
 
-    pub async fn load(path: &Path) -> Result<Self> {
-        let mut file = File::open(path).await?;
-        let mut bytes = Vec::new();
-        file.read_to_end(&mut bytes).await?;
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = std::fs::read(path)?;
+        let archived = rkyv::check_archived_root::<Snapshot>(&bytes)
+            .map_err(|e| anyhow::anyhow!("Snapshot corrupted: {}", e))?;
+        Ok(Some(archived.deserialize(&mut rkyv::Infallible)?))
+    }
+
+    /// The chunk starting at `offset`, and whether it is the last
+    pub fn chunk(&self, offset: u64) -> (&[u8], bool) {
+        let start = (offset as usize).min(self.data.len());
+        let end = (start + CHUNK).min(self.data.len());
+        (&self.data[start..end], end == self.data.len())
+    }
+}
+
+/// A snapshot being received. On disk: index and term, then the bytes so far.
+pub struct Incoming {
+    path: PathBuf,
+    pub index: u64,
+    pub term: u64,
+    pub received: u64,
+}
+
+const HEADER: u64 = 16;
 
-        let archived = rkyv::check_archived_root::<Snapshot>(&bytes).map_err(|e| anyhow::anyhow!("Snapshot corrupted: {}", e))?;
-        Ok(archived.deserialize(&mut rkyv::Infallible)?)
+impl Incoming {
+    fn part_path(storage_path: &Path) -> PathBuf {
+        storage_path.with_extension("snapshot.part")
     }
-}
\ No newline at end of file
+
+    /// Pick up a transfer interrupted by a restart
+    pub fn resume(storage_path: &Path) -> Result<Option<Self>> {
+        let path = Self::part_path(storage_path);
+        let Ok(mut file) = File::open(&path) else {
+            return Ok(None);
+        };
+        let mut header = [0u8; HEADER as usize];
+        if file.read_exact(&mut header).is_err() {
+            std::fs::remove_file(&path)?;
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            index: u64::from_le_bytes(header[..8].try_into().unwrap()),
+            term: u64::from_le_bytes(header[8..].try_into().unwrap()),
+            received: file.metadata()?.len() - HEADER,
+            path,
+        }))
+    }
+
+    /// Start over for the snapshot at `index`, dropping any other transfer
+    pub fn start(storage_path: &Path, index: u64, term: u64) -> Result<Self> {
+        let path = Self::part_path(storage_path);
+        let mut file = File::create(&path)?;
+        file.write_all(&index.to_le_bytes())?;
+        file.write_all(&term.to_le_bytes())?;
+        file.sync_all()?;
+        Ok(Self { path, index, term, received: 0 })
+    }
+
+    /// Append the chunk at `offset`, which must be the next byte
+    pub fn write(&mut self, offset: u64, chunk: &[u8]) -> Result<()> {
+        if offset != self.received {
+            bail!("Chunk at byte {} while {} were received", offset, self.received);
+        }
+        let mut file = OpenOptions::new().append(true).open(&self.path)?;
+        file.write_all(chunk)?;
+        file.sync_data()?;
+        self.received += chunk.len() as u64;
+        Ok(())
+    }
+
+    /// The complete snapshot, with the watermarks sent along with its last
+    /// chunk; the partial file is gone afterwards
+    pub fn finish(self, watermarks: ProducerWatermarks) -> Result<Snapshot> {
+        let bytes = std::fs::read(&self.path)?;
+        std::fs::remove_file(&self.path)?;
+        Ok(Snapshot {
+            last_included_index: self.index,
+            last_included_term: self.term,
+            data: bytes[HEADER as usize..].to_vec(),
+            watermarks,
+        })
+    }
+}
@@ -6,6 +6,10 @@ use serde::{Serialize, Deserialize};
 use std::fs::{File, OpenOptions};
 use std::io::{BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
+use cell_sdk::signing::SignedCommand;
+
+use crate::membership::Configuration;
+use crate::snapshot::Snapshot;
 
 #[derive(cell_sdk::rkyv::Archive, cell_sdk::rkyv::Serialize, cell_sdk::rkyv::Deserialize, Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[archive(check_bytes)]
@@ -13,6 +17,10 @@ use std::path::{Path, PathBuf};
 pub enum LogEntry {
     Command { term: u64, data: Vec<u8> },
     NoOp { term: u64 },
+    /// A producer's command, verified by every replica before apply
+    Signed { term: u64, command: SignedCommand },
+    /// Membership from this index on (see `membership`)
+    Config { term: u64, config: Configuration },
 }
 
 impl LogEntry {
@@ -20,6 +28,8 @@ impl LogEntry {
         match self {
             LogEntry::Command { term, .. } => *term,
             LogEntry::NoOp { term } => *term,
+            LogEntry::Signed { term, .. } => *term,
+            LogEntry::Config { term, .. } => *term,
         }
     }
 }
@@ -35,6 +45,9 @@ pub struct WriteAheadLog {
     path: PathBuf,
     state_path: PathBuf,
     entries: Vec<LogEntry>, // In-memory cache of log for fast reads
+    /// Index and term of the last entry covered by the snapshot; the log
+    /// continues after it
+    base: (u64, u64),
     hard_state: HardState,
 }
 
@@ -50,6 +63,7 @@ impl WriteAheadLog {
             path: storage_path.to_path_buf(),
             state_path,
             entries: Vec::new(),
+            base: (0, 0),
             hard_state: HardState::default(),
         };
 
@@ -66,7 +80,12 @@ impl WriteAheadLog {
                 .unwrap_or_else(|_| HardState::default());
         }
 
-        // 2. Recover Log Entries
+        // 2. Recover where the log starts
+        if let Some(snapshot) = Snapshot::load(&Snapshot::path(&self.path))? {
+            self.base = (snapshot.last_included_index, snapshot.last_included_term);
+        }
+
+        // 3. Recover Log Entries
         if self.path.exists() {
             let file = File::open(&self.path)?;
             let len = file.metadata()?.len();
@@ -110,35 +129,83 @@ impl WriteAheadLog {
         writer.flush()?;
 
         self.entries.push(entry);
-        Ok(self.entries.len() as u64) // Index starts at 1 conceptually, but 0-indexed vec + 1 = len
+        Ok(self.last_index())
     }
 
+    /// Entries up to the snapshot are not kept
     pub fn get_entry(&self, index: u64) -> Option<LogEntry> {
-        if index == 0 || index > self.entries.len() as u64 {
+        if index <= self.base.0 || index > self.last_index() {
             return None;
         }
-        Some(self.entries[(index - 1) as usize].clone())
+        Some(self.entries[(index - self.base.0 - 1) as usize].clone())
+    }
+
+    /// Term of the entry at `index`, also the snapshot's last one
+    pub fn term_at(&self, index: u64) -> Option<u64> {
+        if index == self.base.0 {
+            return Some(self.base.1);
+        }
+        self.get_entry(index).map(|e| e.term())
     }
 
     pub fn last_index(&self) -> u64 {
-        self.entries.len() as u64
+        self.base.0 + self.entries.len() as u64
+    }
+
+    /// Last index covered by the snapshot, 0 without one
+    pub fn snapshot_index(&self) -> u64 {
+        self.base.0
     }
 
     pub fn last_log_info(&self) -> (u64, u64) {
         let idx = self.last_index();
-        let term = self.get_entry(idx).map(|e| e.term()).unwrap_or(0);
+        let term = self.term_at(idx).unwrap_or(0);
         (idx, term)
     }
 
     pub fn truncate_suffix(&mut self, index: u64) -> Result<()> {
         // Delete everything from index onwards
         // Used when conflict is found
-        if index > self.entries.len() as u64 {
+        if index > self.last_index() {
             return Ok(());
         }
 
-        self.entries.truncate((index - 1) as usize);
-        
+        self.entries.truncate(index.saturating_sub(self.base.0 + 1) as usize);
+        self.rewrite()
+    }
+
+    /// Replace the whole log from index 1, e.g. with entries restored from
+    /// the archive. Any snapshot is dropped with the old log.
+    pub fn replace(&mut self, entries: Vec<LogEntry>) -> Result<()> {
+        let snapshot = Snapshot::path(&self.path);
+        if snapshot.exists() {
+            std::fs::remove_file(snapshot)?;
+        }
+        self.base = (0, 0);
+        self.entries = entries;
+        self.rewrite()
+    }
+
+    pub fn snapshot(&self) -> Result<Option<Snapshot>> {
+        Snapshot::load(&Snapshot::path(&self.path))
+    }
+
+    /// Make `snapshot` the start of the log. As in Raft, entries after it
+    /// survive only if the log agrees with its last included entry.
+    pub fn install(&mut self, snapshot: &Snapshot) -> Result<()> {
+        let (index, term) = (snapshot.last_included_index, snapshot.last_included_term);
+        let keep = if self.term_at(index) == Some(term) {
+            self.get_entries_from(index + 1)
+        } else {
+            Vec::new()
+        };
+        snapshot.save(&Snapshot::path(&self.path))?;
+        self.base = (index, term);
+        self.entries = keep;
+        self.rewrite()
+    }
+
+    fn rewrite(&self) -> Result<()> {
         // Rewrite disk file (Simplified approach: Rewrite whole log)
         // In production, you'd use `ftruncate` but serde framing makes that tricky without index.
         let file = OpenOptions::new()
@@ -157,9 +224,20 @@ impl WriteAheadLog {
     }
 
     pub fn get_entries_from(&self, start_idx: u64) -> Vec<LogEntry> {
-        if start_idx == 0 || start_idx > self.entries.len() as u64 {
+        if start_idx <= self.base.0 || start_idx > self.last_index() {
+            return Vec::new();
+        }
+        self.entries[(start_idx - self.base.0 - 1) as usize..].to_vec()
+    }
+
+    /// Entries `first..=last`, cut short at the end of the log; empty if
+    /// `first` is covered by the snapshot
+    pub fn get_entries_between(&self, first: u64, last: u64) -> Vec<LogEntry> {
+        let end = last.min(self.last_index());
+        if first <= self.base.0 || first > end {
             return Vec::new();
         }
-        self.entries[(start_idx - 1) as usize..].to_vec()
+        let base = self.base.0;
+        self.entries[(first - base - 1) as usize..(end - base) as usize].to_vec()
     }
 }
\ No newline at end of file
@@ -15,4 +15,127 @@ async fn consensus_raft_single_node() {
     let res = c.propose(cmd).await.unwrap();
     
     assert!(res.index > 0);
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn consensus_learners_stay_out_of_quorum() {
+    cell_sdk::System::ignite_local_cluster().await.unwrap();
+
+    System::spawn("consensus", None).await.expect("Failed to spawn consensus");
+    let synapse = Synapse::grow_await("consensus").await.expect("Failed to connect");
+    let mut c = Consensus::Client::new(synapse);
+
+    // Leader of a single-node cluster once it accepts writes
+    c.propose(Consensus::Command { data: b"hello".to_vec() }).await.unwrap();
+    let learner = Consensus::Learner { id: 7, address: "consensus-learner".to_string() };
+    assert!(c.add_learner(learner.clone()).await.unwrap());
+    assert!(c.add_learner(learner).await.is_err());
+
+    let members = c.members().await.unwrap();
+    assert_eq!(members.learners.len(), 1);
+    assert_eq!(members.learners[0].id, 7);
+
+    // Still commits alone: the learner never counts
+    let res = c.propose(Consensus::Command { data: b"after".to_vec() }).await.unwrap();
+    assert!(res.index > 1);
+    assert!(c.remove_learner(7).await.unwrap());
+}
+
+#[tokio::test]
+async fn consensus_voter_changes_need_caught_up_learners() {
+    cell_sdk::System::ignite_local_cluster().await.unwrap();
+
+    System::spawn("consensus", None).await.expect("Failed to spawn consensus");
+    let synapse = Synapse::grow_await("consensus").await.expect("Failed to connect");
+    let mut c = Consensus::Client::new(synapse);
+
+    c.propose(Consensus::Command { data: b"hello".to_vec() }).await.unwrap();
+    let members = c.members().await.unwrap();
+    assert_eq!(members.voters.len(), 1);
+    assert!(members.joint.is_none());
+    assert!(c.remove_peer(members.voters[0]).await.is_err());
+    assert!(c.promote(vec![7]).await.is_err());
+
+    // Never answered, so never caught up: promoting it would stall commits
+    let learner = Consensus::Learner { id: 7, address: "consensus-learner".to_string() };
+    assert!(c.add_learner(learner).await.unwrap());
+    assert!(c.promote(vec![7]).await.is_err());
+
+    // Nothing left pending
+    assert!(c.members().await.unwrap().joint.is_none());
+    let res = c.propose(Consensus::Command { data: b"after".to_vec() }).await.unwrap();
+    assert!(res.index > 1);
+    assert!(c.remove_peer(7).await.unwrap());
+}
+
+#[tokio::test]
+async fn consensus_lone_voter_never_enters_safe_mode() {
+    cell_sdk::System::ignite_local_cluster().await.unwrap();
+
+    System::spawn("consensus", None).await.expect("Failed to spawn consensus");
+    let synapse = Synapse::grow_await("consensus").await.expect("Failed to connect");
+    let mut c = Consensus::Client::new(synapse);
+
+    c.propose(Consensus::Command { data: b"hello".to_vec() }).await.unwrap();
+    let quorum = c.quorum().await.unwrap();
+    assert!(!quorum.safe_mode);
+    assert_eq!(quorum.lost_ms, 0);
+    assert_eq!(quorum.recovered_from, None);
+}
+
+#[tokio::test]
+async fn consensus_transfer_needs_a_voter() {
+    cell_sdk::System::ignite_local_cluster().await.unwrap();
+
+    System::spawn("consensus", None).await.expect("Failed to spawn consensus");
+    let synapse = Synapse::grow_await("consensus").await.expect("Failed to connect");
+    let mut c = Consensus::Client::new(synapse);
+
+    c.propose(Consensus::Command { data: b"hello".to_vec() }).await.unwrap();
+    assert!(c.transfer_leadership(42).await.is_err());
+
+    // Still leading and writable: the failed transfer left nothing behind
+    let res = c.propose(Consensus::Command { data: b"after".to_vec() }).await.unwrap();
+    assert!(res.index > 1);
+}
+
+#[tokio::test]
+async fn consensus_unsharded_cell_is_one_shard() {
+    cell_sdk::System::ignite_local_cluster().await.unwrap();
+
+    System::spawn("consensus", None).await.expect("Failed to spawn consensus");
+    let synapse = Synapse::grow_await("consensus").await.expect("Failed to connect");
+    let mut c = Consensus::Client::new(synapse);
+
+    c.propose(Consensus::Command { data: b"hello".to_vec() }).await.unwrap();
+    let keyed = Consensus::KeyedCommand { key: b"user/42".to_vec(), data: b"keyed".to_vec() };
+    let res = c.propose_keyed(keyed).await.unwrap();
+    assert_eq!(res.shard, 0);
+
+    let shards = c.shards().await.unwrap();
+    assert_eq!(shards.len(), 1);
+    assert!(shards[0].start.is_empty() && shards[0].end.is_none());
+    assert!(shards[0].commit_index >= res.index);
+
+    let entry = c.get_shard_entry(0, Consensus::LogQuery { index: res.index }).await.unwrap();
+    assert_eq!(entry.data, Some(b"keyed".to_vec()));
+    assert!(c.get_shard_entry(1, Consensus::LogQuery { index: 1 }).await.is_err());
+}
+
+#[tokio::test]
+async fn consensus_leader_serves_every_consistency() {
+    cell_sdk::System::ignite_local_cluster().await.unwrap();
+
+    System::spawn("consensus", None).await.expect("Failed to spawn consensus");
+    let synapse = Synapse::grow_await("consensus").await.expect("Failed to connect");
+    let mut c = Consensus::Client::new(synapse);
+
+    let res = c.propose(Consensus::Command { data: b"hello".to_vec() }).await.unwrap();
+    let strong = c.read(Consensus::ReadConsistency::Strong).await.unwrap();
+    assert!(strong.applied_index >= res.index);
+    assert_eq!(strong.staleness_ms, 0);
+    assert!(strong.commands >= 1);
+
+    let bounded = c.read(Consensus::ReadConsistency::BoundedStaleness(100)).await.unwrap();
+    assert_eq!(bounded.hash, strong.hash);
+}
@@ -31,6 +31,30 @@ pub struct CheckPermission {
     pub action: String,
 }
 
+/// Lets `principal` reach records of another organism in storage cells
+#[protein]
+pub struct DomainAccess {
+    pub principal: String,
+    pub organism: String,
+    pub residency: Option<String>,
+    /// Unix seconds
+    pub expires_at: Option<u64>,
+}
+
+impl DomainAccess {
+    fn same_scope(&self, other: &DomainAccess) -> bool {
+        self.principal == other.principal
+            && self.organism == other.organism
+            && self.residency == other.residency
+    }
+}
+
+#[protein]
+pub struct GrantDomain {
+    pub token: String,
+    pub access: DomainAccess,
+}
+
 // === JWT CLAIMS ===
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -45,6 +69,7 @@ struct Claims {
 struct IamState {
     users: HashMap<String, String>, // ClientID -> Secret (Hash)
     roles: HashMap<String, HashSet<String>>, // Role -> { "resource:action" }
+    domain_grants: Vec<DomainAccess>,
     enc_key: EncodingKey,
     dec_key: DecodingKey,
 }
@@ -80,11 +105,22 @@ impl IamService {
             state: Arc::new(RwLock::new(IamState {
                 users,
                 roles,
+                domain_grants: Vec::new(),
                 enc_key: EncodingKey::from_secret(secret),
                 dec_key: DecodingKey::from_secret(secret),
             })),
         }
     }
+
+    fn require_admin(state: &IamState, token: &str) -> Result<String> {
+        let claims = decode::<Claims>(token, &state.dec_key, &Validation::default())
+            .map_err(|_| anyhow::anyhow!("Invalid token"))?
+            .claims;
+        if !state.roles.get(&claims.role).is_some_and(|p| p.contains("*")) {
+            bail!("{} may not manage domain grants", claims.sub);
+        }
+        Ok(claims.sub)
+    }
 }
 
 #[handler]
@@ -145,6 +181,38 @@ impl IamService {
         tracing::info!("[IAM] Access Denied: {} -> {} (Role: {})", token_data.claims.sub, req.resource, role);
         Ok(false)
     }
+
+    /// Admin only: let a principal reach another organism's records
+    async fn grant_domain(&self, req: GrantDomain) -> Result<bool> {
+        let mut state = self.state.write().await;
+        let admin = Self::require_admin(&state, &req.token)?;
+        let access = req.access;
+        state.domain_grants.retain(|g| !g.same_scope(&access));
+        tracing::info!(
+            "[IAM] {} granted {} access to domain {}{}",
+            admin,
+            access.principal,
+            access.organism,
+            access.residency.as_deref().map(|r| format!("@{}", r)).unwrap_or_default()
+        );
+        state.domain_grants.push(access);
+        Ok(true)
+    }
+
+    /// Admin only: returns whether a grant was removed
+    async fn revoke_domain(&self, req: GrantDomain) -> Result<bool> {
+        let mut state = self.state.write().await;
+        let admin = Self::require_admin(&state, &req.token)?;
+        let before = state.domain_grants.len();
+        state.domain_grants.retain(|g| !g.same_scope(&req.access));
+        tracing::info!("[IAM] {} revoked {}'s access to {}", admin, req.access.principal, req.access.organism);
+        Ok(state.domain_grants.len() < before)
+    }
+
+    /// Grant table for storage cells, which enforce it locally
+    async fn domain_grants(&self) -> Result<Vec<DomainAccess>> {
+        Ok(self.state.read().await.domain_grants.clone())
+    }
 }
 
 #[tokio::main]
@@ -34,4 +34,42 @@ async fn iam_enforces_rbac() {
         action: "launch".into(),
     }).await.unwrap();
     assert!(!denied);
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn iam_publishes_domain_grants_from_admins() {
+    cell_sdk::System::ignite_local_cluster().await.unwrap();
+
+    System::spawn("iam", None).await.expect("Failed to spawn");
+    let synapse = Synapse::grow_await("iam").await.expect("Failed to connect");
+    let mut iam = Iam::Client::new(synapse);
+
+    let login = |client_id: &str, secret: &str| Iam::LoginRequest {
+        client_id: client_id.into(),
+        client_secret: secret.into(),
+    };
+    let admin = iam.login(login("admin", "admin123")).await.unwrap();
+    let finance = iam.login(login("finance", "moneyprinter")).await.unwrap();
+
+    let access = Iam::DomainAccess {
+        principal: "observer".into(),
+        organism: "acme".into(),
+        residency: Some("eu".into()),
+        expires_at: None,
+    };
+
+    // Only admins hand out cross-domain access
+    assert!(iam.grant_domain(Iam::GrantDomain {
+        token: finance.token,
+        access: access.clone(),
+    }).await.is_err());
+    assert!(iam.domain_grants().await.unwrap().is_empty());
+
+    iam.grant_domain(Iam::GrantDomain {
+        token: admin.token.clone(),
+        access: access.clone(),
+    }).await.unwrap();
+    assert_eq!(iam.domain_grants().await.unwrap(), vec![access.clone()]);
+
+    assert!(iam.revoke_domain(Iam::GrantDomain { token: admin.token, access }).await.unwrap());
+    assert!(iam.domain_grants().await.unwrap().is_empty());
+}
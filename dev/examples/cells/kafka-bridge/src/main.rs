@@ -0,0 +1,432 @@
+// cells/kafka-bridge/src/main.rs
+// SPDX-License-Identifier: MIT
+// Kafka <-> mesh topic bridge
+//
+// Inbound routes consume Kafka topics into mesh topics that cells `poll`;
+// outbound routes produce whatever cells `publish` to a mesh topic onto Kafka.
+// Consumer offsets live in state-manager rather than in Kafka's group
+// commits, and only advance once an event has been polled, so a restarted
+// bridge redelivers anything the mesh had not yet seen (at-least-once).
+
+use anyhow::Result;
+use cell_sdk::error::{CellError, ErrorContext};
+use cell_sdk::*;
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::{ClientConfig, Message, Offset, TopicPartitionList};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+cell_remote!(StateManager = "state-manager", methods = [store, fetch]);
+
+/// How often polled offsets are written to state-manager
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(5);
+const PRODUCE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[protein]
+pub struct MeshEvent {
+    /// Position in the mesh topic. Restarts at 1 with the bridge; consumers
+    /// seeing `seq <= after` should poll again from 0.
+    pub seq: u64,
+    pub key: Option<Vec<u8>>,
+    pub payload: Vec<u8>,
+    /// Kafka record timestamp, ms since the epoch
+    pub timestamp: u64,
+}
+
+#[protein]
+pub struct RouteStatus {
+    pub kafka_topic: String,
+    pub mesh_topic: String,
+    /// Kafka -> mesh when true, mesh -> Kafka otherwise
+    pub inbound: bool,
+    pub forwarded: u64,
+    /// Inbound only: checkpointed next offset per partition
+    pub offsets: Vec<(i32, i64)>,
+}
+
+/// Bridge configuration, from the environment:
+///
+/// - `CELL_KAFKA_BROKERS`: bootstrap servers (default `localhost:9092`)
+/// - `CELL_KAFKA_GROUP`: names this bridge's checkpoints (default `cell-kafka-bridge`)
+/// - `CELL_KAFKA_INBOUND`: `kafka_topic=mesh_topic,...`
+/// - `CELL_KAFKA_OUTBOUND`: `mesh_topic=kafka_topic,...`
+/// - `CELL_KAFKA_RETAIN`: events buffered per mesh topic (default 10000)
+struct Config {
+    brokers: String,
+    group: String,
+    inbound: Vec<(String, String)>,
+    outbound: Vec<(String, String)>,
+    retain: usize,
+}
+
+impl Config {
+    fn from_env() -> Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        Ok(Self {
+            brokers: var("CELL_KAFKA_BROKERS").unwrap_or_else(|| "localhost:9092".into()),
+            group: var("CELL_KAFKA_GROUP").unwrap_or_else(|| "cell-kafka-bridge".into()),
+            inbound: parse_routes(var("CELL_KAFKA_INBOUND").as_deref().unwrap_or(""))?,
+            outbound: parse_routes(var("CELL_KAFKA_OUTBOUND").as_deref().unwrap_or(""))?,
+            retain: var("CELL_KAFKA_RETAIN")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10_000),
+        })
+    }
+}
+
+fn parse_routes(spec: &str) -> Result<Vec<(String, String)>> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .map(|route| match route.split_once('=') {
+            Some((from, to)) if !from.is_empty() && !to.is_empty() => {
+                Ok((from.trim().to_string(), to.trim().to_string()))
+            }
+            _ => anyhow::bail!("Invalid route '{}', expected 'from=to'", route),
+        })
+        .collect()
+}
+
+/// An inbound event and the Kafka record it came from
+struct Buffered {
+    event: MeshEvent,
+    partition: i32,
+    offset: i64,
+}
+
+struct MeshTopic {
+    kafka_topic: String,
+    events: VecDeque<Buffered>,
+    next_seq: u64,
+    forwarded: u64,
+}
+
+#[derive(Default)]
+struct Checkpoints {
+    /// (kafka topic, partition) -> next offset to consume
+    offsets: HashMap<(String, i32), i64>,
+    dirty: bool,
+}
+
+#[service]
+#[derive(Clone)]
+struct KafkaBridge {
+    group: String,
+    retain: usize,
+    /// Inbound mesh topics by name
+    topics: Arc<RwLock<HashMap<String, MeshTopic>>>,
+    /// Outbound mesh topic -> (kafka topic, forwarded)
+    outbound: Arc<RwLock<HashMap<String, (String, u64)>>>,
+    checkpoints: Arc<RwLock<Checkpoints>>,
+    producer: FutureProducer,
+}
+
+impl KafkaBridge {
+    fn new(config: &Config) -> Result<Self> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", &config.brokers)
+            .set("message.timeout.ms", "5000")
+            .create()?;
+        let topics = config
+            .inbound
+            .iter()
+            .map(|(kafka, mesh)| {
+                let topic = MeshTopic {
+                    kafka_topic: kafka.clone(),
+                    events: VecDeque::new(),
+                    next_seq: 1,
+                    forwarded: 0,
+                };
+                (mesh.clone(), topic)
+            })
+            .collect();
+        let outbound = config
+            .outbound
+            .iter()
+            .map(|(mesh, kafka)| (mesh.clone(), (kafka.clone(), 0)))
+            .collect();
+        Ok(Self {
+            group: config.group.clone(),
+            retain: config.retain,
+            topics: Arc::new(RwLock::new(topics)),
+            outbound: Arc::new(RwLock::new(outbound)),
+            checkpoints: Arc::new(RwLock::new(Checkpoints::default())),
+            producer,
+        })
+    }
+
+    fn checkpoint_key(&self, topic: &str, partition: i32) -> String {
+        format!("kafka-bridge/{}/{}/{}", self.group, topic, partition)
+    }
+
+    /// Assign every partition of the inbound topics, resuming from the
+    /// checkpoints in state-manager.
+    async fn assign(&self, consumer: &StreamConsumer, kafka_topics: &[String]) -> Result<()> {
+        let state = StateManager::Client::connect().await?;
+        let mut assignment = TopicPartitionList::new();
+        let mut checkpoints = self.checkpoints.write().await;
+
+        for topic in kafka_topics {
+            let metadata = consumer.fetch_metadata(Some(topic), Duration::from_secs(10))?;
+            let partitions = metadata
+                .topics()
+                .first()
+                .map(|t| t.partitions().iter().map(|p| p.id()).collect::<Vec<_>>())
+                .unwrap_or_default();
+            if partitions.is_empty() {
+                anyhow::bail!("Kafka topic '{}' has no partitions", topic);
+            }
+
+            for partition in partitions {
+                let saved = state
+                    .fetch(StateManager::FetchRequest {
+                        key: self.checkpoint_key(topic, partition),
+                        max_staleness_ms: Some(0),
+                    })
+                    .await?
+                    .and_then(|entry| <[u8; 8]>::try_from(entry.value.as_slice()).ok())
+                    .map(i64::from_le_bytes);
+                let offset = match saved {
+                    Some(next) => {
+                        checkpoints.offsets.insert((topic.clone(), partition), next);
+                        Offset::Offset(next)
+                    }
+                    None => Offset::Beginning,
+                };
+                tracing::info!(
+                    "[KafkaBridge] {}[{}] resuming at {:?}",
+                    topic,
+                    partition,
+                    offset
+                );
+                assignment.add_partition_offset(topic, partition, offset)?;
+            }
+        }
+        consumer.assign(&assignment)?;
+        Ok(())
+    }
+
+    /// Buffer a Kafka record on every mesh topic routed from its topic.
+    async fn ingest(&self, msg: &impl Message) {
+        let mut topics = self.topics.write().await;
+        for (name, topic) in topics
+            .iter_mut()
+            .filter(|(_, t)| t.kafka_topic == msg.topic())
+        {
+            let event = MeshEvent {
+                seq: topic.next_seq,
+                key: msg.key().map(<[u8]>::to_vec),
+                payload: msg.payload().map(<[u8]>::to_vec).unwrap_or_default(),
+                timestamp: msg.timestamp().to_millis().unwrap_or(0).max(0) as u64,
+            };
+            topic.next_seq += 1;
+            topic.forwarded += 1;
+            topic.events.push_back(Buffered {
+                event,
+                partition: msg.partition(),
+                offset: msg.offset(),
+            });
+            if topic.events.len() > self.retain {
+                // Not checkpointed yet, so a restart replays it
+                topic.events.pop_front();
+                tracing::warn!(
+                    "[KafkaBridge] Buffer of {} full, dropped oldest event",
+                    name
+                );
+            }
+        }
+    }
+
+    async fn consume(self, consumer: StreamConsumer) {
+        loop {
+            match consumer.recv().await {
+                Ok(msg) => self.ingest(&msg).await,
+                Err(e) => {
+                    tracing::warn!("[KafkaBridge] Consume failed: {}", e);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    }
+
+    /// Write offsets that moved since the last checkpoint.
+    async fn checkpoint(&self) -> Result<()> {
+        let offsets: Vec<((String, i32), i64)> = {
+            let mut checkpoints = self.checkpoints.write().await;
+            if !std::mem::take(&mut checkpoints.dirty) {
+                return Ok(());
+            }
+            checkpoints
+                .offsets
+                .iter()
+                .map(|(k, v)| (k.clone(), *v))
+                .collect()
+        };
+
+        let result = async {
+            let state = StateManager::Client::connect().await?;
+            for ((topic, partition), next) in offsets {
+                state
+                    .store(StateManager::StoreRequest {
+                        key: self.checkpoint_key(&topic, partition),
+                        value: next.to_le_bytes().to_vec(),
+                        ttl_secs: None,
+                        domain: None,
+                    })
+                    .await?;
+            }
+            Ok(())
+        }
+        .await;
+        if result.is_err() {
+            self.checkpoints.write().await.dirty = true;
+        }
+        result
+    }
+
+    fn unknown_topic(topic: &str) -> anyhow::Error {
+        ErrorContext::new(CellError::NotFound)
+            .with_message(format!("No route for mesh topic '{}'", topic))
+            .into()
+    }
+}
+
+#[handler]
+impl KafkaBridge {
+    /// Events of an inbound mesh topic with `seq > after`, oldest first.
+    /// Polling past an event marks its Kafka record as consumed.
+    async fn poll(&self, topic: String, after: u64, max: u32) -> Result<Vec<MeshEvent>> {
+        let topics = self.topics.read().await;
+        let mesh = topics
+            .get(&topic)
+            .ok_or_else(|| Self::unknown_topic(&topic))?;
+        let polled: Vec<&Buffered> = mesh
+            .events
+            .iter()
+            .filter(|b| b.event.seq > after)
+            .take(max as usize)
+            .collect();
+
+        let mut checkpoints = self.checkpoints.write().await;
+        for b in &polled {
+            let next = checkpoints
+                .offsets
+                .entry((mesh.kafka_topic.clone(), b.partition))
+                .or_insert(0);
+            if b.offset + 1 > *next {
+                *next = b.offset + 1;
+                checkpoints.dirty = true;
+            }
+        }
+        Ok(polled.into_iter().map(|b| b.event.clone()).collect())
+    }
+
+    /// Produce onto the Kafka topic an outbound mesh topic is routed to.
+    /// Returns the Kafka offset of the record.
+    async fn publish(&self, topic: String, key: Option<Vec<u8>>, payload: Vec<u8>) -> Result<i64> {
+        let kafka_topic = match self.outbound.read().await.get(&topic) {
+            Some((kafka_topic, _)) => kafka_topic.clone(),
+            None => return Err(Self::unknown_topic(&topic)),
+        };
+        let mut record = FutureRecord::<[u8], [u8]>::to(&kafka_topic).payload(&payload);
+        if let Some(key) = &key {
+            record = record.key(key.as_slice());
+        }
+        let (_, offset) = self
+            .producer
+            .send(record, PRODUCE_TIMEOUT)
+            .await
+            .map_err(|(e, _)| {
+                anyhow::anyhow!("Kafka produce to '{}' failed: {}", kafka_topic, e)
+            })?;
+
+        if let Some((_, forwarded)) = self.outbound.write().await.get_mut(&topic) {
+            *forwarded += 1;
+        }
+        Ok(offset)
+    }
+
+    async fn routes(&self) -> Result<Vec<RouteStatus>> {
+        let checkpoints = self.checkpoints.read().await;
+        let mut routes: Vec<RouteStatus> = self
+            .topics
+            .read()
+            .await
+            .iter()
+            .map(|(mesh, t)| {
+                let mut offsets: Vec<(i32, i64)> = checkpoints
+                    .offsets
+                    .iter()
+                    .filter(|((topic, _), _)| *topic == t.kafka_topic)
+                    .map(|((_, partition), next)| (*partition, *next))
+                    .collect();
+                offsets.sort();
+                RouteStatus {
+                    kafka_topic: t.kafka_topic.clone(),
+                    mesh_topic: mesh.clone(),
+                    inbound: true,
+                    forwarded: t.forwarded,
+                    offsets,
+                }
+            })
+            .collect();
+        routes.extend(
+            self.outbound
+                .read()
+                .await
+                .iter()
+                .map(|(mesh, (kafka, forwarded))| RouteStatus {
+                    kafka_topic: kafka.clone(),
+                    mesh_topic: mesh.clone(),
+                    inbound: false,
+                    forwarded: *forwarded,
+                    offsets: Vec::new(),
+                }),
+        );
+        routes.sort_by(|a, b| a.mesh_topic.cmp(&b.mesh_topic));
+        Ok(routes)
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt().init();
+    let config = Config::from_env()?;
+    tracing::info!(
+        "[KafkaBridge] {} inbound, {} outbound routes via {}",
+        config.inbound.len(),
+        config.outbound.len(),
+        config.brokers
+    );
+    let service = KafkaBridge::new(&config)?;
+
+    if !config.inbound.is_empty() {
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("bootstrap.servers", &config.brokers)
+            .set("group.id", &config.group)
+            // Offsets are ours to keep, in state-manager
+            .set("enable.auto.commit", "false")
+            .create()?;
+        let mut kafka_topics: Vec<String> = config.inbound.iter().map(|(k, _)| k.clone()).collect();
+        kafka_topics.sort();
+        kafka_topics.dedup();
+        service.assign(&consumer, &kafka_topics).await?;
+        tokio::spawn(service.clone().consume(consumer));
+
+        let checkpointer = service.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(CHECKPOINT_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(e) = checkpointer.checkpoint().await {
+                    tracing::error!("[KafkaBridge] Checkpoint failed: {}", e);
+                }
+            }
+        });
+    }
+
+    service.serve("kafka-bridge").await
+}
@@ -0,0 +1,17 @@
+use cell_sdk::*;
+
+cell_remote!(KafkaBridge = "kafka-bridge");
+
+// Without inbound routes the bridge never talks to Kafka, so this runs without a broker
+#[tokio::test]
+async fn bridge_rejects_unrouted_topics() {
+    cell_sdk::System::ignite_local_cluster().await.unwrap();
+
+    System::spawn("kafka-bridge", None).await.expect("Failed to spawn");
+    let synapse = Synapse::grow_await("kafka-bridge").await.expect("Failed to connect");
+    let mut bridge = KafkaBridge::Client::new(synapse);
+
+    assert!(bridge.routes().await.unwrap().is_empty());
+    assert!(bridge.poll("orders".into(), 0, 10).await.is_err());
+    assert!(bridge.publish("orders".into(), None, b"{}".to_vec()).await.is_err());
+}
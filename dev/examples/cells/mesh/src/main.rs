@@ -19,6 +19,19 @@ pub struct HealthReport {
     pub healthy: bool,
 }
 
+/// What a node's hypervisor gave up under resource pressure
+#[protein]
+pub struct NodeMode {
+    pub node: String,
+    /// `Normal`, `Constrained` or `Critical`
+    pub level: String,
+    pub degraded: bool,
+    /// Cells stopped to relieve the pressure
+    pub shed: Vec<String>,
+    pub mem_available_pct: f32,
+    pub load_per_cpu: f32,
+}
+
 // Protocol expected by Nucleus and others
 #[protein]
 pub enum MeshRequest {
@@ -39,6 +52,8 @@ pub enum MeshResponse {
 struct MeshService {
     // consumer -> providers
     graph: Arc<Mutex<HashMap<String, Vec<String>>>>,
+    // node -> last reported mode
+    modes: Arc<Mutex<HashMap<String, NodeMode>>>,
 }
 
 #[handler]
@@ -65,6 +80,24 @@ impl MeshService {
     async fn get_graph(&self) -> Result<HashMap<String, Vec<String>>> {
         Ok(self.graph.lock().unwrap().clone())
     }
+
+    async fn report_node_mode(&self, mode: NodeMode) -> Result<()> {
+        let mut modes = self.modes.lock().unwrap();
+        let was_degraded = modes.get(&mode.node).is_some_and(|m| m.degraded);
+        if mode.degraded && !was_degraded {
+            tracing::warn!("Node {} degraded ({}), shed {:?}", mode.node, mode.level, mode.shed);
+        } else if !mode.degraded && was_degraded {
+            tracing::info!("Node {} recovered", mode.node);
+        }
+        modes.insert(mode.node.clone(), mode);
+        Ok(())
+    }
+
+    /// Nodes currently running in degraded mode
+    async fn degraded_nodes(&self) -> Result<Vec<NodeMode>> {
+        let modes = self.modes.lock().unwrap();
+        Ok(modes.values().filter(|m| m.degraded).cloned().collect())
+    }
 }
 
 // Manual dispatch glue to match cell-model expectations if strictly needed, 
@@ -78,6 +111,7 @@ async fn main() -> Result<()> {
 
     let service = MeshService {
         graph: Arc::new(Mutex::new(HashMap::new())),
+        modes: Arc::new(Mutex::new(HashMap::new())),
     };
 
     service.serve("mesh").await
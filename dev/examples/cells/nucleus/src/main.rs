@@ -8,10 +8,16 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use cell_discovery::Discovery;
-use cell_model::manifest::{MeshManifest, PlacementStrategy, ResourceLimits};
+use cell_model::manifest::{Drift, DriftKind, MeshManifest, ObservedCell, PlacementStrategy, ResourceLimits};
+use cell_model::upgrade::{blocked_by, upgrade_order, QuorumGroup};
+use cell_model::placement::{
+    drain_plan, migration_plan, place_with_policy, EdgePolicy, GpuDevice, Migration, NodeProfile, PowerState,
+    Requirement, RunningCell,
+};
 
 // Define explicit remote to Mesh so we can query the graph
 cell_remote!(Mesh = "mesh");
+cell_remote!(StateManager = "state-manager", methods = [record_deployment]);
 
 // === PROTOCOL DEFINITIONS ===
 
@@ -35,6 +41,8 @@ pub struct CellRegistration {
     pub node_id: u64,
     pub capabilities: Vec<String>,
     pub endpoints: Vec<String>,
+    pub version: Option<String>,
+    pub env: Vec<(String, String)>,
 }
 
 #[protein]
@@ -72,6 +80,119 @@ pub struct PruneResult {
     pub killed: Vec<String>,
 }
 
+#[protein]
+pub struct DriftEntry {
+    pub cell: String,
+    pub kind: String,
+    pub detail: String,
+}
+
+#[protein]
+pub struct DriftReport {
+    pub mesh: String,
+    pub entries: Vec<DriftEntry>,
+}
+
+#[protein]
+pub struct ReconcileResult {
+    pub actions: Vec<String>,
+    pub unresolved: Vec<DriftEntry>,
+}
+
+#[protein]
+pub struct GpuInfo {
+    pub vendor: String,
+    pub name: String,
+    pub memory_mb: u64,
+    pub device: String,
+}
+
+/// Hardware a node offers for placement
+#[protein]
+pub struct NodeReport {
+    pub node_id: u64,
+    pub address: String,
+    pub gpus: Vec<GpuInfo>,
+    pub has_avx512: bool,
+    pub is_tee: bool,
+    pub on_battery: bool,
+    pub battery_pct: Option<u8>,
+    pub temp_c: Option<f32>,
+    pub throttled: bool,
+    /// Stable identity of the node (its key fingerprint). A node coming back
+    /// from an upgrade must report the same one.
+    pub identity: Option<String>,
+}
+
+#[protein]
+pub struct PlacementDecision {
+    pub node_id: u64,
+    pub address: String,
+    /// Devices to expose to the cell on that node
+    pub devices: Vec<String>,
+}
+
+/// A heavy cell moved off a stressed edge node
+#[protein]
+pub struct MigrationRecord {
+    pub cell: String,
+    pub from: u64,
+    pub to: u64,
+    pub reason: String,
+}
+
+/// What `drain_node` did with the node's cells
+#[protein]
+pub struct DrainReport {
+    pub node_id: u64,
+    pub migrated: Vec<MigrationRecord>,
+    /// Cells no other node could take
+    pub stopped: Vec<String>,
+}
+
+#[protein]
+pub struct UpgradeRequest {
+    pub nodes: Vec<u64>,
+    /// Shell command that upgrades and reboots a node, run with
+    /// `CELL_NODE_ID` and `CELL_NODE_ADDRESS` set. Without one the operator
+    /// does it and calls `confirm_upgrade`.
+    pub hook: Option<String>,
+    /// Limit on each wait for quorum, migrations and the node rejoining
+    pub timeout_secs: u64,
+}
+
+#[protein]
+pub enum UpgradePhase {
+    Pending,
+    /// Taking the node down now would cost a quorum-bearing cell its majority
+    WaitingForQuorum { reason: String },
+    Draining,
+    /// Waiting for the node's cells to come up elsewhere
+    Migrating,
+    AwaitingOperator,
+    RunningHook,
+    /// Waiting for the node to report in again
+    Rejoining,
+    Completed,
+    Failed { reason: String },
+}
+
+#[protein]
+pub struct UpgradeStatus {
+    pub id: String,
+    /// Nodes in the order they are upgraded
+    pub order: Vec<u64>,
+    pub current: Option<u64>,
+    pub phase: UpgradePhase,
+    pub upgraded: Vec<u64>,
+}
+
+#[protein]
+pub struct RolloutPolicy {
+    pub replicas: u32,
+    pub min_available: Option<u32>,
+}
+
 // === NUCLEUS SERVICE ===
 
 pub struct Nucleus {
@@ -83,8 +204,23 @@ pub struct Nucleus {
 struct NucleusState {
     desired_state: Option<MeshManifest>,
     spores: HashMap<String, Vec<u8>>,
+    nodes: HashMap<u64, NodeProfile>,
+    migrations: Vec<Migration>,
+    /// State checkpoints of cells migrated away, until a node takes them
+    handoffs: HashMap<String, Vec<u8>>,
+    /// Nodes under maintenance: nothing new is placed there
+    cordoned: HashSet<u64>,
+    /// Identity each node last reported, and when
+    identities: HashMap<u64, String>,
+    reported: HashMap<u64, std::time::Instant>,
+    upgrades: HashMap<String, UpgradeStatus>,
+    /// When the operator said a node was upgraded
+    confirmed: HashMap<u64, std::time::Instant>,
 }
 
+/// This node's id in `NucleusState::nodes`
+const LOCAL_NODE: u64 = 0;
+
 struct CellRegistry {
     cells: HashMap<String, Vec<CellRegistration>>,
     last_heartbeat: HashMap<String, std::time::Instant>,
@@ -101,11 +237,37 @@ impl Nucleus {
             state: Arc::new(RwLock::new(NucleusState {
                 desired_state: None,
                 spores: HashMap::new(),
+                nodes: HashMap::new(),
+                migrations: Vec::new(),
+                handoffs: HashMap::new(),
+                cordoned: HashSet::new(),
+                identities: HashMap::new(),
+                reported: HashMap::new(),
+                upgrades: HashMap::new(),
+                confirmed: HashMap::new(),
             })),
         }
     }
 
     pub async fn start_background_tasks(&self) {
+        match Self::scan_local().await {
+            Ok(local) => {
+                tracing::info!("[Nucleus] Local node: {} GPU(s)", local.gpus.len());
+                self.state.write().await.nodes.insert(LOCAL_NODE, local);
+            }
+            Err(e) => tracing::warn!("[Nucleus] {:#}", e),
+        }
+
+        let state = self.state.clone();
+        let registry = self.registry.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
+            loop {
+                interval.tick().await;
+                Self::rebalance(&state, &registry).await;
+            }
+        });
+
         let registry = self.registry.clone();
         
         tokio::spawn(async move {
@@ -191,6 +353,713 @@ impl Nucleus {
         }
     }
 
+    // --- PLACEMENT ---
+
+    pub async fn report_node(&self, report: NodeReport) -> Result<bool> {
+        let profile = NodeProfile {
+            node_id: report.node_id,
+            address: report.address,
+            gpus: report.gpus.into_iter().map(|g| GpuDevice {
+                vendor: g.vendor,
+                name: g.name,
+                memory_mb: g.memory_mb,
+                device: g.device,
+            }).collect(),
+            has_avx512: report.has_avx512,
+            is_tee: report.is_tee,
+            power: PowerState {
+                on_battery: report.on_battery,
+                battery_pct: report.battery_pct,
+                temp_c: report.temp_c,
+                throttled: report.throttled,
+            },
+        };
+        tracing::info!("[Nucleus] Node {} reported {} GPU(s)", profile.node_id, profile.gpus.len());
+        let mut state = self.state.write().await;
+        state.reported.insert(profile.node_id, std::time::Instant::now());
+        if let Some(identity) = report.identity {
+            state.identities.insert(profile.node_id, identity);
+        }
+        state.nodes.insert(profile.node_id, profile);
+        Ok(true)
+    }
+
+    /// Pick a node satisfying `requires`, preferring this one. Heavy cells skip
+    /// nodes on battery or thermally throttled.
+    pub async fn place(&self, requires: &[String], heavy: bool) -> Result<PlacementDecision> {
+        let reqs = Requirement::parse_all(requires).map_err(|e| anyhow!(e))?;
+        let state = self.state.read().await;
+        let mut nodes: Vec<NodeProfile> = state.nodes.values()
+            .filter(|n| !state.cordoned.contains(&n.node_id))
+            .cloned()
+            .collect();
+        nodes.sort_by_key(|n| n.node_id);
+
+        let node = place_with_policy(&nodes, &reqs, heavy, &EdgePolicy::default())
+            .ok_or_else(|| anyhow!("No node satisfies {:?}", requires))?;
+        Ok(PlacementDecision {
+            node_id: node.node_id,
+            address: node.address.clone(),
+            devices: node.matching_gpus(&reqs).iter().map(|g| g.device.clone()).collect(),
+        })
+    }
+
+    /// Hardware requirements declared for `cell` in the applied manifest, and
+    /// whether it counts as heavy
+    async fn requirements_of(&self, cell: &str) -> (Vec<String>, bool) {
+        let state = self.state.read().await;
+        state.desired_state.as_ref()
+            .and_then(|m| m.cells.iter().find(|c| c.name == cell))
+            .map(|c| (c.requires.clone(), c.is_heavy()))
+            .unwrap_or_default()
+    }
+
+    /// Version and env the manifest deploys `cell` with, env sorted by key
+    async fn deployment_of(&self, cell: &str) -> (Option<String>, Vec<(String, String)>) {
+        let state = self.state.read().await;
+        let Some(spec) = state.desired_state.as_ref().and_then(|m| m.cells.iter().find(|c| c.name == cell)) else {
+            return (None, Vec::new());
+        };
+        let mut env: Vec<(String, String)> = spec.env.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        env.sort();
+        (spec.version.clone(), env)
+    }
+
+    /// Spawn locally, unless the cell needs hardware this node lacks, is
+    /// heavy and this node is stressed, or this node is cordoned
+    async fn spawn_placed(&self, cell: &str) -> Result<()> {
+        let (requires, heavy) = self.requirements_of(cell).await;
+        let cordoned = self.state.read().await.cordoned.contains(&LOCAL_NODE);
+        if !requires.is_empty() || heavy || cordoned {
+            let decision = self.place(&requires, heavy).await?;
+            if decision.node_id != LOCAL_NODE {
+                anyhow::bail!("{} ({:?}) is placed on node {}, not here", cell, requires, decision.node_id);
+            }
+        }
+        let handoff = self.claim_handoff(cell).await;
+        let socket_path = match System::spawn(cell, None).await {
+            Ok(path) => path,
+            Err(e) => {
+                // Kept for the next attempt
+                if let Some(bytes) = handoff {
+                    self.state.write().await.handoffs.insert(cell.to_string(), bytes);
+                }
+                return Err(e).with_context(|| format!("Failed to spawn {}", cell));
+            }
+        };
+
+        // Registered as deployed, so drift shows manifest changes it predates
+        let (version, env) = self.deployment_of(cell).await;
+        self.register(CellRegistration {
+            name: cell.to_string(),
+            node_id: LOCAL_NODE,
+            capabilities: Vec::new(),
+            endpoints: vec![socket_path],
+            version,
+            env,
+        }).await?;
+
+        if let Some(bytes) = handoff {
+            if let Err(e) = cell_sdk::state::push(cell, bytes).await {
+                tracing::warn!("[Nucleus] Could not restore state of {}: {}", cell, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checkpoint of a cell migrated away from this node, for the node taking it over
+    pub async fn take_handoff(&self, cell: &str) -> Option<Vec<u8>> {
+        self.state.write().await.handoffs.remove(cell)
+    }
+
+    /// Checkpoint of `cell` for starting it here: held by this nucleus if it
+    /// moved between cells on this node, otherwise taken from the nucleus of
+    /// the node it migrated away from
+    async fn claim_handoff(&self, cell: &str) -> Option<Vec<u8>> {
+        if let Some(bytes) = self.take_handoff(cell).await {
+            return Some(bytes);
+        }
+        let peers: Vec<(u64, String)> = self.state.read().await.nodes.values()
+            .filter(|n| n.node_id != LOCAL_NODE)
+            .map(|n| (n.node_id, n.address.clone()))
+            .collect();
+        for (node_id, address) in peers {
+            let req = NucleusServiceProtocol::TakeHandoff { cell_name: cell.to_string() };
+            match Self::call_nucleus(&address, &req).await {
+                Ok(NucleusServiceResponse::TakeHandoff(bytes)) if !bytes.is_empty() => {
+                    tracing::info!("[Nucleus] Took the state of {} from node {}", cell, node_id);
+                    return Some(bytes);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::debug!("[Nucleus] Node {} has no handoff of {}: {}", node_id, cell, e),
+            }
+        }
+        None
+    }
+
+    /// Rescan local power/thermal state and stop heavy cells once the edge
+    /// policy says this node should shed them. The target node picks them up
+    /// on its next reconcile; spawn_placed keeps them from returning here.
+    /// Hardware of this node. Scanning reads sysfs and may shell out, so it
+    /// runs on the blocking pool.
+    async fn scan_local() -> Result<NodeProfile> {
+        tokio::task::spawn_blocking(|| {
+            cell_discovery::hardware::HardwareCaps::scan().profile(LOCAL_NODE, "local")
+        })
+        .await
+        .context("Hardware scan failed")
+    }
+
+    async fn rebalance(state: &RwLock<NucleusState>, registry: &RwLock<CellRegistry>) {
+        let local = match Self::scan_local().await {
+            Ok(local) => local,
+            Err(e) => {
+                tracing::warn!("[Nucleus] Not rebalancing: {:#}", e);
+                return;
+            }
+        };
+        // Never wait on the registry while holding the state
+        let registered: HashSet<String> = registry.read().await.cells.keys().cloned().collect();
+        let mut st = state.write().await;
+        st.nodes.insert(LOCAL_NODE, local);
+
+        let Some(desired) = st.desired_state.as_ref() else { return };
+        let running: Vec<RunningCell> = desired.cells.iter()
+            .filter(|c| registered.contains(&c.name))
+            .map(|c| RunningCell {
+                name: c.name.clone(),
+                node_id: LOCAL_NODE,
+                heavy: c.is_heavy(),
+                requirements: Requirement::parse_all(&c.requires).unwrap_or_default(),
+            })
+            .collect();
+        // Cordoned nodes are being drained by hand; leave them out
+        let mut nodes: Vec<NodeProfile> = st.nodes.values()
+            .filter(|n| !st.cordoned.contains(&n.node_id))
+            .cloned()
+            .collect();
+        nodes.sort_by_key(|n| n.node_id);
+        let plan = migration_plan(&nodes, &running, &EdgePolicy::default());
+        drop(st);
+
+        for m in plan {
+            tracing::warn!("[Nucleus] Migrating {} to node {}: {}", m.cell, m.to, m.reason);
+            if let Ok(bytes) = cell_sdk::state::fetch(&m.cell).await {
+                state.write().await.handoffs.insert(m.cell.clone(), bytes);
+            }
+            if let Err(e) = Self::stop_here(&m.cell).await {
+                tracing::warn!("[Nucleus] Not migrating {}: {:#}", m.cell, e);
+                state.write().await.handoffs.remove(&m.cell);
+                continue;
+            }
+            {
+                let mut reg = registry.write().await;
+                reg.cells.remove(&m.cell);
+                reg.last_heartbeat.remove(&m.cell);
+            }
+            state.write().await.migrations.push(m);
+        }
+    }
+
+    pub async fn migrations(&self) -> Vec<MigrationRecord> {
+        self.state.read().await.migrations.iter().map(|m| MigrationRecord {
+            cell: m.cell.clone(),
+            from: m.from,
+            to: m.to,
+            reason: m.reason.clone(),
+        }).collect()
+    }
+
+    // --- MAINTENANCE ---
+
+    /// Stop (or resume) placing cells on `node_id`. The local hypervisor is
+    /// told as well, so it refuses spawns that bypass the nucleus.
+    pub async fn cordon(&self, node_id: u64, cordoned: bool) -> Result<()> {
+        {
+            let mut state = self.state.write().await;
+            if !state.nodes.contains_key(&node_id) {
+                anyhow::bail!("Unknown node {}", node_id);
+            }
+            if cordoned {
+                state.cordoned.insert(node_id);
+            } else {
+                state.cordoned.remove(&node_id);
+            }
+        }
+        if node_id == LOCAL_NODE {
+            System::cordon(cordoned).await.context("Hypervisor did not take the cordon")?;
+        }
+        tracing::info!("[Nucleus] Node {} {}", node_id, if cordoned { "cordoned" } else { "uncordoned" });
+        Ok(())
+    }
+
+    /// Cordon `node_id` and move its cells elsewhere: each one stops taking
+    /// requests, hands its state checkpoint to the nucleus and is stopped; the
+    /// target node starts it on its next reconcile, taking the checkpoint from
+    /// this nucleus. Cells no other node can
+    /// take are only stopped. A cell counts as moved only once its node
+    /// acknowledged the stop; the drain fails if any cell did not stop.
+    pub async fn drain(&self, node_id: u64) -> Result<DrainReport> {
+        self.cordon(node_id, true).await?;
+
+        let (cells, plan) = {
+            let state = self.state.read().await;
+            let reg = self.registry.read().await;
+            let running: Vec<RunningCell> = reg.cells.values()
+                .flatten()
+                .filter(|r| Self::node_of(r, &state.nodes) == node_id)
+                .filter(|r| !Self::is_system_cell(&r.name))
+                .map(|r| {
+                    let deployment = state.desired_state.as_ref()
+                        .and_then(|m| m.cells.iter().find(|c| c.name == r.name));
+                    RunningCell {
+                        name: r.name.clone(),
+                        node_id,
+                        heavy: deployment.is_some_and(|c| c.is_heavy()),
+                        requirements: deployment
+                            .and_then(|c| Requirement::parse_all(&c.requires).ok())
+                            .unwrap_or_default(),
+                    }
+                })
+                .collect();
+            let mut nodes: Vec<NodeProfile> = state.nodes.values().cloned().collect();
+            nodes.sort_by_key(|n| n.node_id);
+            let cordoned: Vec<u64> = state.cordoned.iter().copied().collect();
+            let plan = drain_plan(&nodes, &running, node_id, &cordoned, &EdgePolicy::default());
+            (running.len(), plan)
+        };
+        tracing::warn!("[Nucleus] Draining node {}: {} cell(s)", node_id, cells);
+
+        let mut report = DrainReport { node_id, migrated: Vec::new(), stopped: Vec::new() };
+        let mut failed = Vec::new();
+        for m in plan.migrations {
+            if let Ok(bytes) = cell_sdk::state::fetch(&m.cell).await {
+                self.state.write().await.handoffs.insert(m.cell.clone(), bytes);
+            }
+            match self.stop_on(&m.cell, node_id).await {
+                Ok(()) => {
+                    report.migrated.push(MigrationRecord {
+                        cell: m.cell.clone(),
+                        from: m.from,
+                        to: m.to,
+                        reason: m.reason.clone(),
+                    });
+                    self.state.write().await.migrations.push(m);
+                }
+                Err(e) => {
+                    // Still running here; the target must not restore it
+                    self.state.write().await.handoffs.remove(&m.cell);
+                    failed.push(format!("{}: {:#}", m.cell, e));
+                }
+            }
+        }
+        for cell in plan.stopped {
+            tracing::warn!("[Nucleus] No other node can take {}; stopping it", cell);
+            match self.stop_on(&cell, node_id).await {
+                Ok(()) => report.stopped.push(cell),
+                Err(e) => failed.push(format!("{}: {:#}", cell, e)),
+            }
+        }
+        if !failed.is_empty() {
+            anyhow::bail!("Could not stop {}; node {} stays cordoned", failed.join(", "), node_id);
+        }
+        Ok(report)
+    }
+
+    /// Gracefully stop `cell` on `node_id` and drop its registration there.
+    /// Cells on other nodes are stopped by that node's nucleus.
+    async fn stop_on(&self, cell: &str, node_id: u64) -> Result<()> {
+        if node_id == LOCAL_NODE {
+            Self::stop_here(cell).await?;
+        } else {
+            let address = self.state.read().await.nodes.get(&node_id)
+                .map(|n| n.address.clone())
+                .ok_or_else(|| anyhow!("Unknown node {}", node_id))?;
+            Self::stop_remote(cell, &address).await
+                .with_context(|| format!("Node {} did not stop {}", node_id, cell))?;
+        }
+
+        let nodes = self.state.read().await.nodes.clone();
+        let mut reg = self.registry.write().await;
+        if let Some(instances) = reg.cells.get_mut(cell) {
+            instances.retain(|r| Self::node_of(r, &nodes) != node_id);
+            if instances.is_empty() {
+                reg.cells.remove(cell);
+                reg.last_heartbeat.remove(cell);
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop every registered instance of `cell`, each on its own node
+    async fn stop_everywhere(&self, cell: &str) -> Result<()> {
+        let mut nodes: Vec<u64> = {
+            let state = self.state.read().await;
+            let reg = self.registry.read().await;
+            reg.cells.get(cell).into_iter()
+                .flatten()
+                .map(|r| Self::node_of(r, &state.nodes))
+                .collect()
+        };
+        nodes.sort_unstable();
+        nodes.dedup();
+        if nodes.is_empty() {
+            let mut reg = self.registry.write().await;
+            reg.cells.remove(cell);
+            reg.last_heartbeat.remove(cell);
+        }
+        for node_id in nodes {
+            self.stop_on(cell, node_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Replace every instance of `cell` with `replicas` deployed from the
+    /// current manifest. A local instance hands its state to its successor.
+    async fn replace(&self, cell: &str, replicas: u32) -> Result<()> {
+        if let Ok(bytes) = cell_sdk::state::fetch(cell).await {
+            self.state.write().await.handoffs.insert(cell.to_string(), bytes);
+        }
+        if let Err(e) = self.stop_everywhere(cell).await {
+            // Still running; nothing may restore from its checkpoint
+            self.state.write().await.handoffs.remove(cell);
+            return Err(e);
+        }
+        for _ in 0..replicas {
+            self.spawn_placed(cell).await?;
+        }
+        Ok(())
+    }
+
+    /// Stop `cell` on this node. OPS Shutdown on its admin socket drains it
+    /// and lets it finish in-flight requests before exiting; the hypervisor
+    /// then forgets it, so its watchdog does not bring it back.
+    async fn stop_here(cell: &str) -> Result<()> {
+        cell_sdk::watchdog::shutdown_cell(cell).await
+            .with_context(|| format!("{} did not shut down", cell))?;
+        if let Err(e) = System::stop(cell).await {
+            tracing::debug!("[Nucleus] {} is not supervised here: {}", cell, e);
+        }
+        Ok(())
+    }
+
+    /// Have the nucleus at `address` stop `cell` on its node
+    async fn stop_remote(cell: &str, address: &str) -> Result<()> {
+        let req = NucleusServiceProtocol::StopCell { cell_name: cell.to_string() };
+        match Self::call_nucleus(address, &req).await? {
+            NucleusServiceResponse::StopCell(true) => Ok(()),
+            _ => Err(anyhow!("Protocol Mismatch")),
+        }
+    }
+
+    /// Call the nucleus of the node at `address`
+    async fn call_nucleus(address: &str, req: &NucleusServiceProtocol) -> Result<NucleusServiceResponse> {
+        let synapse = Synapse::grow(&format!("nucleus@{}", address)).await?;
+        let resp_bytes = synapse.fire(req).await?.into_owned();
+        let archived = cell_model::rkyv::check_archived_root::<NucleusServiceResponse>(&resp_bytes)
+            .map_err(|e| anyhow!("Validation Error: {}", e))?;
+        cell_model::rkyv::Deserialize::deserialize(
+            archived,
+            &mut cell_model::rkyv::de::deserializers::SharedDeserializeMap::new(),
+        ).map_err(|e| anyhow!("Deserialization Error: {}", e))
+    }
+
+    // --- ROLLING UPGRADES ---
+
+    /// Upgrade `req.nodes` one at a time: wait until taking the node down
+    /// keeps every quorum-bearing cell at a majority, drain it, wait for its
+    /// cells to come up elsewhere, have the hook or the operator upgrade it,
+    /// wait for it to rejoin with the identity it had, and uncordon it. The
+    /// first failure stops the upgrade and leaves that node cordoned.
+    pub async fn upgrade(self: Arc<Self>, req: UpgradeRequest) -> Result<String> {
+        let order = upgrade_order(&req.nodes, &self.quorum_groups().await);
+        let id = format!("upgrade-{}", std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs());
+        {
+            let mut state = self.state.write().await;
+            if let Some(running) = state.upgrades.values()
+                .find(|u| !matches!(u.phase, UpgradePhase::Completed | UpgradePhase::Failed { .. }))
+            {
+                anyhow::bail!("{} is still in progress", running.id);
+            }
+            if let Some(unknown) = order.iter().find(|n| !state.nodes.contains_key(*n)) {
+                anyhow::bail!("Unknown node {}", unknown);
+            }
+            state.upgrades.insert(id.clone(), UpgradeStatus {
+                id: id.clone(),
+                order: order.clone(),
+                current: None,
+                phase: UpgradePhase::Pending,
+                upgraded: Vec::new(),
+            });
+        }
+
+        let upgrade_id = id.clone();
+        tokio::spawn(async move {
+            let timeout = std::time::Duration::from_secs(req.timeout_secs);
+            for node in order {
+                if let Err(e) = self.upgrade_node(&upgrade_id, node, req.hook.as_deref(), timeout).await {
+                    tracing::error!("[Nucleus] Upgrade of node {} failed: {}", node, e);
+                    self.set_phase(&upgrade_id, Some(node), UpgradePhase::Failed { reason: e.to_string() }).await;
+                    return;
+                }
+                if let Some(status) = self.state.write().await.upgrades.get_mut(&upgrade_id) {
+                    status.upgraded.push(node);
+                }
+            }
+            self.set_phase(&upgrade_id, None, UpgradePhase::Completed).await;
+        });
+        Ok(id)
+    }
+
+    async fn upgrade_node(&self, id: &str, node: u64, hook: Option<&str>, timeout: std::time::Duration) -> Result<()> {
+        let deadline = || std::time::Instant::now() + timeout;
+
+        let until = deadline();
+        while let Some(reason) = blocked_by(&self.quorum_groups().await, node) {
+            if std::time::Instant::now() > until {
+                anyhow::bail!("quorum did not allow taking node {} down: {}", node, reason);
+            }
+            self.set_phase(id, Some(node), UpgradePhase::WaitingForQuorum { reason }).await;
+            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        }
+
+        self.set_phase(id, Some(node), UpgradePhase::Draining).await;
+        let drained = self.drain(node).await?;
+
+        self.set_phase(id, Some(node), UpgradePhase::Migrating).await;
+        let until = deadline();
+        loop {
+            let pending: Vec<String> = {
+                let state = self.state.read().await;
+                let reg = self.registry.read().await;
+                drained.migrated.iter()
+                    .filter(|m| !reg.cells.get(&m.cell).is_some_and(|instances| {
+                        instances.iter().any(|r| Self::node_of(r, &state.nodes) != node)
+                    }))
+                    .map(|m| m.cell.clone())
+                    .collect()
+            };
+            let quorum = blocked_by(&self.quorum_groups().await, node);
+            if pending.is_empty() && quorum.is_none() {
+                break;
+            }
+            if std::time::Instant::now() > until {
+                let mut waiting = pending;
+                waiting.extend(quorum);
+                anyhow::bail!("cells did not come up elsewhere: {}", waiting.join(", "));
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        }
+
+        // The node has to report its identity again once it is back
+        let (expected, address) = {
+            let mut state = self.state.write().await;
+            let address = state.nodes.get(&node).map(|n| n.address.clone()).unwrap_or_default();
+            (state.identities.remove(&node), address)
+        };
+        let upgraded_at = match hook {
+            Some(hook) => {
+                self.set_phase(id, Some(node), UpgradePhase::RunningHook).await;
+                let status = tokio::process::Command::new("sh")
+                    .arg("-c")
+                    .arg(hook)
+                    .env("CELL_NODE_ID", node.to_string())
+                    .env("CELL_NODE_ADDRESS", &address)
+                    .status()
+                    .await
+                    .context("Failed to run upgrade hook")?;
+                if !status.success() {
+                    anyhow::bail!("upgrade hook exited with {}", status);
+                }
+                std::time::Instant::now()
+            }
+            None => {
+                self.set_phase(id, Some(node), UpgradePhase::AwaitingOperator).await;
+                tracing::warn!("[Nucleus] Node {} is drained: upgrade it, then confirm", node);
+                let asked = std::time::Instant::now();
+                loop {
+                    if let Some(at) = self.state.read().await.confirmed.get(&node).filter(|at| **at > asked) {
+                        break *at;
+                    }
+                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                }
+            }
+        };
+
+        // Reports from before the upgrade do not count
+        self.set_phase(id, Some(node), UpgradePhase::Rejoining).await;
+        let until = deadline();
+        loop {
+            {
+                let state = self.state.read().await;
+                if state.reported.get(&node).is_some_and(|at| *at > upgraded_at) {
+                    match (&expected, state.identities.get(&node)) {
+                        (Some(expected), Some(actual)) if expected != actual => anyhow::bail!(
+                            "node {} rejoined as {}, expected {}", node, actual, expected
+                        ),
+                        (Some(_), None) => anyhow::bail!("node {} rejoined without an identity", node),
+                        _ => break,
+                    }
+                }
+            }
+            if std::time::Instant::now() > until {
+                anyhow::bail!("node {} did not rejoin within {:?}", node, timeout);
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        }
+
+        self.cordon(node, false).await?;
+        tracing::info!("[Nucleus] Node {} upgraded and back in service", node);
+        Ok(())
+    }
+
+    async fn set_phase(&self, id: &str, node: Option<u64>, phase: UpgradePhase) {
+        if let Some(status) = self.state.write().await.upgrades.get_mut(id) {
+            status.current = node;
+            status.phase = phase;
+        }
+    }
+
+    pub async fn upgrade_status(&self, id: &str) -> Option<UpgradeStatus> {
+        self.state.read().await.upgrades.get(id).cloned()
+    }
+
+    /// The operator has upgraded `node_id`; wait for it to rejoin
+    pub async fn confirm_upgrade(&self, node_id: u64) -> Result<()> {
+        let mut state = self.state.write().await;
+        let awaiting = state.upgrades.values().any(|u| {
+            u.current == Some(node_id) && matches!(u.phase, UpgradePhase::AwaitingOperator)
+        });
+        if !awaiting {
+            anyhow::bail!("No upgrade is waiting for node {}", node_id);
+        }
+        state.confirmed.insert(node_id, std::time::Instant::now());
+        Ok(())
+    }
+
+    /// Live replicas of every cell the manifest marks `quorum`
+    async fn quorum_groups(&self) -> Vec<QuorumGroup> {
+        let state = self.state.read().await;
+        let Some(desired) = state.desired_state.as_ref() else { return Vec::new() };
+        let reg = self.registry.read().await;
+        desired.cells.iter()
+            .filter(|c| c.quorum)
+            .map(|c| QuorumGroup {
+                cell: c.name.clone(),
+                replicas: c.replicas,
+                members: reg.cells.iter()
+                    .filter(|(name, _)| cell_model::protocol::is_instance_of(name, &c.name))
+                    .flat_map(|(_, instances)| instances)
+                    .map(|r| Self::node_of(r, &state.nodes))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// The node a registered instance runs on. Cells register with the
+    /// node id from their config; ids no node reported are this node's.
+    fn node_of(reg: &CellRegistration, nodes: &HashMap<u64, NodeProfile>) -> u64 {
+        if nodes.contains_key(&reg.node_id) { reg.node_id } else { LOCAL_NODE }
+    }
+
+    // --- DRIFT DETECTION ---
+
+    /// Cells the nucleus treats as infrastructure, never part of a MeshManifest
+    fn is_system_cell(name: &str) -> bool {
+        matches!(
+            name,
+            "nucleus" | "mesh" | "axon" | "hypervisor" | "mycelium" | "builder" | "observer" | "ca" | "vault" | "iam"
+        )
+    }
+
+    /// Snapshot of what is actually registered, one entry per cell
+    pub async fn observed(&self) -> Vec<ObservedCell> {
+        let registry = self.registry.read().await;
+        let mut observed: Vec<ObservedCell> = registry.cells.iter()
+            .filter(|(name, _)| !Self::is_system_cell(name))
+            .map(|(name, instances)| {
+                // Instances may disagree; report the first one and let the
+                // replica count surface the rest.
+                let first = instances.first();
+                let mut env = first.map(|r| r.env.clone()).unwrap_or_default();
+                env.sort();
+                ObservedCell {
+                    name: name.clone(),
+                    version: first.and_then(|r| r.version.clone()),
+                    replicas: instances.len() as u32,
+                    env,
+                }
+            })
+            .collect();
+        observed.sort_by(|a, b| a.name.cmp(&b.name));
+        observed
+    }
+
+    async fn desired(&self) -> Result<MeshManifest> {
+        let state = self.state.read().await;
+        state.desired_state.clone().ok_or_else(|| anyhow!("No manifest applied"))
+    }
+
+    pub async fn drift(&self) -> Result<DriftReport> {
+        let desired = self.desired().await?;
+        let entries = desired.drift(&self.observed().await).iter().map(drift_entry).collect();
+        Ok(DriftReport { mesh: desired.mesh, entries })
+    }
+
+    /// Correct what can be corrected automatically and report the rest
+    pub async fn reconcile(&self) -> Result<ReconcileResult> {
+        let mut actions = Vec::new();
+        let mut unresolved = Vec::new();
+        let mut respawned = HashSet::new();
+
+        let desired = self.desired().await?;
+        for d in desired.drift(&self.observed().await) {
+            match &d.kind {
+                DriftKind::Missing => match self.spawn_placed(&d.cell).await {
+                    Ok(()) => actions.push(format!("spawned {}", d.cell)),
+                    Err(e) => unresolved.push(DriftEntry { detail: e.to_string(), ..drift_entry(&d) }),
+                },
+                DriftKind::Replicas { desired, actual } if actual < desired => {
+                    let mut spawned = 0;
+                    for _ in *actual..*desired {
+                        if let Err(e) = self.spawn_placed(&d.cell).await {
+                            unresolved.push(DriftEntry { detail: e.to_string(), ..drift_entry(&d) });
+                            break;
+                        }
+                        spawned += 1;
+                    }
+                    if spawned > 0 {
+                        actions.push(format!("scaled {} {} -> {}", d.cell, actual, actual + spawned));
+                    }
+                }
+                DriftKind::Version { .. } | DriftKind::Env { .. } => {
+                    // Several drifts on one cell lead to a single replacement
+                    if respawned.insert(d.cell.clone()) {
+                        let replicas = desired.cells.iter()
+                            .find(|c| c.name == d.cell)
+                            .map_or(1, |c| c.replicas);
+                        match self.replace(&d.cell, replicas).await {
+                            Ok(()) => actions.push(format!("replaced {}", d.cell)),
+                            Err(e) => unresolved.push(DriftEntry { detail: format!("{:#}", e), ..drift_entry(&d) }),
+                        }
+                    }
+                }
+                DriftKind::Unexpected => match self.stop_everywhere(&d.cell).await {
+                    Ok(()) => actions.push(format!("stopped {}", d.cell)),
+                    Err(e) => unresolved.push(DriftEntry { detail: format!("{:#}", e), ..drift_entry(&d) }),
+                },
+                // Scaling down needs instance-level addressing
+                DriftKind::Replicas { .. } => unresolved.push(drift_entry(&d)),
+            }
+        }
+
+        tracing::info!("[Nucleus] Reconcile: {} actions, {} unresolved", actions.len(), unresolved.len());
+        Ok(ReconcileResult { actions, unresolved })
+    }
+
     // --- GARBAGE COLLECTION ---
     
     pub async fn prune(&self) -> Result<PruneResult> {
@@ -275,20 +1144,10 @@ impl Nucleus {
             for target in &iteration_kills {
                 tracing::info!("[Nucleus] Pruning unused cell: {}", target);
                 
-                // Connect and send Shutdown Ops command
-                if let Ok(mut synapse) = Synapse::grow(target).await {
-                    let req = cell_model::ops::OpsRequest::Shutdown;
-                    let req_bytes = cell_model::rkyv::to_bytes::<_, 256>(&req)?.into_vec();
-                    
-                    // OPS channel = 2
-                    let _ = synapse.fire_on_channel(2, &req_bytes).await;
-                }
+                // Stops it on every node it runs on and drops its registration
+                self.stop_everywhere(target).await
+                    .with_context(|| format!("Failed to prune {}", target))?;
 
-                // Remove from local registry immediately
-                let mut reg = self.registry.write().await;
-                reg.cells.remove(target);
-                reg.last_heartbeat.remove(target);
-                
                 killed_total.push(target.clone());
             }
         }
@@ -297,6 +1156,17 @@ impl Nucleus {
     }
 }
 
+fn drift_entry(d: &Drift) -> DriftEntry {
+    let kind = match d.kind {
+        DriftKind::Missing => "missing",
+        DriftKind::Unexpected => "unexpected",
+        DriftKind::Version { .. } => "version",
+        DriftKind::Replicas { .. } => "replicas",
+        DriftKind::Env { .. } => "env",
+    };
+    DriftEntry { cell: d.cell.clone(), kind: kind.to_string(), detail: d.to_string() }
+}
+
 #[service]
 #[derive(Clone)]
 struct NucleusService {
@@ -326,18 +1196,119 @@ impl NucleusService {
             .map_err(|e| anyhow!("Invalid TOML: {}", e))?;
         let mut state = self.inner.state.write().await;
         state.desired_state = Some(manifest.clone());
+        drop(state);
         tracing::info!("[Nucleus] Applied manifest for mesh '{}'", manifest.mesh);
+
+        // Deployment history for `cell rollback`; a missing state-manager
+        // does not fail the apply
+        for cell in &manifest.cells {
+            let Some(version) = &cell.version else { continue };
+            let deployment = StateManager::Deployment {
+                cell: cell.name.clone(),
+                version: version.clone(),
+                kind: "apply".to_string(),
+                artifact: None,
+                schema: None,
+                deployed_at: 0,
+            };
+            let recorded = match StateManager::Client::connect().await {
+                Ok(sm) => sm.record_deployment(deployment).await.map(drop),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = recorded {
+                tracing::warn!("[Nucleus] Deployment of {} not recorded: {}", cell.name, e);
+            }
+        }
         Ok(true)
     }
 
     async fn schedule(&self, req: ScheduleSpore) -> Result<String> {
         tracing::info!("[Nucleus] Scheduling spore '{}'...", req.spore_id);
-        Ok("127.0.0.1:9000".to_string())
+        let decision = self.inner.place(&[req.required_caps], false).await?;
+        if decision.node_id == LOCAL_NODE {
+            return Ok("127.0.0.1:9000".to_string());
+        }
+        Ok(decision.address)
+    }
+
+    async fn report_node(&self, report: NodeReport) -> Result<bool> {
+        self.inner.report_node(report).await
+    }
+
+    /// Node (and devices) a cell with these requirements would be placed on
+    async fn place(&self, requires: Vec<String>, heavy: bool) -> Result<PlacementDecision> {
+        self.inner.place(&requires, heavy).await
+    }
+
+    /// Heavy cells moved off this node because of battery or thermal pressure
+    async fn migrations(&self) -> Result<Vec<MigrationRecord>> {
+        Ok(self.inner.migrations().await)
+    }
+
+    /// State checkpoint of a migrated cell; empty if none is held
+    async fn take_handoff(&self, cell_name: String) -> Result<Vec<u8>> {
+        Ok(self.inner.take_handoff(&cell_name).await.unwrap_or_default())
+    }
+
+    /// Stop a cell on this node; other nodes' nuclei call this to stop
+    /// cells they no longer want here
+    async fn stop_cell(&self, cell_name: String) -> Result<bool> {
+        self.inner.stop_on(&cell_name, LOCAL_NODE).await?;
+        Ok(true)
+    }
+
+    /// Stop placing new cells on a node (`cordoned = false` lifts it)
+    async fn cordon_node(&self, node_id: u64, cordoned: bool) -> Result<bool> {
+        self.inner.cordon(node_id, cordoned).await?;
+        Ok(true)
+    }
+
+    /// Cordon a node and move its cells elsewhere, for host maintenance
+    async fn drain_node(&self, node_id: u64) -> Result<DrainReport> {
+        self.inner.drain(node_id).await
+    }
+
+    /// Start a rolling OS upgrade of the given nodes; returns its id
+    async fn upgrade_nodes(&self, req: UpgradeRequest) -> Result<String> {
+        self.inner.clone().upgrade(req).await
+    }
+
+    async fn upgrade_status(&self, upgrade_id: String) -> Result<Option<UpgradeStatus>> {
+        Ok(self.inner.upgrade_status(&upgrade_id).await)
+    }
+
+    /// The operator finished upgrading a drained node
+    async fn confirm_upgrade(&self, node_id: u64) -> Result<bool> {
+        self.inner.confirm_upgrade(node_id).await?;
+        Ok(true)
     }
 
     async fn vacuum(&self) -> Result<PruneResult> {
         self.inner.prune().await
     }
+
+    /// Compare the applied MeshManifest against the running mesh
+    async fn drift(&self) -> Result<DriftReport> {
+        self.inner.drift().await
+    }
+
+    async fn reconcile(&self) -> Result<ReconcileResult> {
+        self.inner.reconcile().await
+    }
+
+    /// Replica constraints the manifest places on rolling operations
+    async fn rollout_policy(&self, cell_name: String) -> Result<RolloutPolicy> {
+        let desired = self.inner.desired().await?;
+        let deployment = desired
+            .cells
+            .iter()
+            .find(|c| c.name == cell_name)
+            .ok_or_else(|| anyhow!("'{}' is not in the manifest", cell_name))?;
+        Ok(RolloutPolicy {
+            replicas: deployment.replicas,
+            min_available: deployment.min_available,
+        })
+    }
 }
 
 #[tokio::main]
@@ -0,0 +1,70 @@
+use cell_sdk::membrane::BoxFuture;
+use cell_sdk::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+cell_remote!(Nucleus = "nucleus");
+
+static ASKED: AtomicBool = AtomicBool::new(false);
+
+/// Stands in for the nucleus of the node `handoff-demo` was drained from
+fn source_nucleus(
+    req: &Nucleus::ArchivedNucleusProtocol,
+) -> BoxFuture<'_, anyhow::Result<Nucleus::NucleusResponse>> {
+    Box::pin(async move {
+        match req {
+            Nucleus::ArchivedNucleusProtocol::TakeHandoff { cell_name } if cell_name == "handoff-demo" => {
+                ASKED.store(true, Ordering::SeqCst);
+                Ok(Nucleus::NucleusResponse::TakeHandoff(vec![1, 2, 3]))
+            }
+            _ => anyhow::bail!("unexpected request"),
+        }
+    })
+}
+
+#[tokio::test]
+async fn spawning_node_takes_the_checkpoint_from_the_source_node() {
+    cell_sdk::System::ignite_local_cluster().await.unwrap();
+    System::spawn("nucleus", None).await.expect("Failed to spawn nucleus");
+    let synapse = Synapse::grow_await("nucleus").await.expect("Failed to connect");
+    let n = Nucleus::Client::new(synapse);
+
+    let port = std::net::UdpSocket::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    std::env::set_var("CELL_TRANSPORT", format!("quic://127.0.0.1:{}", port));
+    let source = tokio::spawn(Membrane::bind::<_, Nucleus::NucleusProtocol, Nucleus::NucleusResponse>(
+        "source-nucleus",
+        source_nucleus,
+        None,
+        None,
+        None,
+    ));
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    n.report_node(Nucleus::NodeReport {
+        node_id: 7,
+        address: format!("127.0.0.1:{}", port),
+        gpus: vec![],
+        has_avx512: false,
+        is_tee: false,
+        on_battery: false,
+        battery_pct: None,
+        temp_c: None,
+        throttled: false,
+        identity: None,
+    })
+    .await
+    .unwrap();
+    let toml = "mesh = \"handoff\"\n\n[[cells]]\nname = \"handoff-demo\"\n";
+    n.apply(Nucleus::ApplyManifest { toml: toml.to_string() }).await.unwrap();
+
+    // There is no such cell to spawn; the checkpoint it took stays with the
+    // local nucleus for the next attempt
+    n.reconcile().await.unwrap();
+    assert!(ASKED.load(Ordering::SeqCst), "source node was not asked for the checkpoint");
+    assert_eq!(n.take_handoff("handoff-demo".to_string()).await.unwrap(), vec![1, 2, 3]);
+
+    source.abort();
+}
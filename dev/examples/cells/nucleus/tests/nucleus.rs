@@ -14,7 +14,9 @@ async fn nucleus_keeps_registry_across_restart() {
         name: "test-persist".into(),
         node_id: 99,
         capabilities: vec!["persist".into()],
-        endpoints: vec!["tcp://1.2.3.4:9000".into()]
+        endpoints: vec!["tcp://1.2.3.4:9000".into()],
+        version: Some("0.1.0".into()),
+        env: vec![],
     };
 
     let success = n.register(reg).await.expect("Registration failed");
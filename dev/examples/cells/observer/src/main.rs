@@ -26,6 +26,54 @@ pub struct LogEntry {
     pub span: TelemetrySpan,
 }
 
+/// A handler call that exceeded its cell's slow-request threshold
+#[protein]
+pub struct SlowCall {
+    pub cell: String,
+    pub method: String,
+    pub duration_ms: u64,
+    pub caller: Option<String>,
+    pub ok: bool,
+    pub args: Option<String>,
+    pub at_unix_ms: u64,
+}
+
+/// One span of a distributed trace, in the cell that recorded it
+#[protein]
+pub struct TraceSpan {
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_id: Option<String>,
+    pub cell: String,
+    pub name: String,
+    pub start_unix_us: u64,
+    pub duration_us: u64,
+    pub ok: bool,
+}
+
+/// One method's SLO compliance (percent) and share of error budget left
+#[protein]
+pub struct SloBudget {
+    pub cell: String,
+    pub method: String,
+    pub availability: f64,
+    pub latency: f64,
+    pub budget_remaining: f64,
+    pub exhausted: bool,
+}
+
+/// Result of replaying one consensus cell's log through a fresh state machine
+#[protein]
+pub struct ReplayAudit {
+    pub cell: String,
+    /// "consistent", "log-short", "diverged", or "unreachable: <error>"
+    pub outcome: String,
+    pub applied: u64,
+    pub replayed: u64,
+    pub live_hash: String,
+    pub replay_hash: String,
+}
+
 // === SERVICE ===
 
 struct ObserverState {
@@ -50,35 +98,48 @@ impl ObserverService {
     }
 }
 
-#[handler]
-impl ObserverService {
-    async fn emit(&self, span: TelemetrySpan) -> Result<String> {
-        let mut state = self.state.write().await;
-        
+impl ObserverState {
+    fn append(&mut self, span: TelemetrySpan) -> String {
         // 1. Canonicalize
         let json = serde_json::to_string(&span).unwrap_or_default();
         
         // 2. Chain Hash
         let mut hasher = blake3::Hasher::new();
-        hasher.update(state.last_hash.as_bytes());
+        hasher.update(self.last_hash.as_bytes());
         hasher.update(json.as_bytes());
         
         let hash = hasher.finalize().to_hex().to_string();
         
         let entry = LogEntry {
             hash: hash.clone(),
-            prev_hash: state.last_hash.clone(),
+            prev_hash: self.last_hash.clone(),
             span,
         };
         
         // 3. Store
-        state.logs.push(entry);
-        state.last_hash = hash.clone();
+        self.logs.push(entry);
+        self.last_hash = hash.clone();
         
         // In real world: Flush to Grafana/Loki/Elastic
         // tracing::info!("[Observer] Ingested trace: {}", hash);
         
-        Ok(hash)
+        hash
+    }
+}
+
+#[handler]
+impl ObserverService {
+    async fn emit(&self, span: TelemetrySpan) -> Result<String> {
+        Ok(self.state.write().await.append(span))
+    }
+
+    /// For producers using `telemetry::Batcher`. Returns the last chain hash.
+    async fn emit_batch(&self, spans: Vec<TelemetrySpan>) -> Result<String> {
+        let mut state = self.state.write().await;
+        for span in spans {
+            state.append(span);
+        }
+        Ok(state.last_hash.clone())
     }
 
     async fn tail(&self, limit: u32) -> Result<Vec<LogEntry>> {
@@ -87,6 +148,85 @@ impl ObserverService {
         Ok(state.logs[start..].to_vec())
     }
 
+    /// Slowest recent calls across `cells`, slowest first.
+    async fn slow_requests(&self, cells: Vec<String>, limit: u32) -> Result<Vec<SlowCall>> {
+        let mut calls = Vec::new();
+        for cell in cells {
+            match cell_sdk::slowlog::fetch(&cell, limit).await {
+                Ok(entries) => calls.extend(entries.into_iter().map(|e| SlowCall {
+                    cell: cell.clone(),
+                    method: e.method,
+                    duration_ms: e.duration_ms,
+                    caller: e.caller,
+                    ok: e.ok,
+                    args: e.args,
+                    at_unix_ms: e.at_unix_ms,
+                })),
+                Err(e) => tracing::warn!("[Observer] No slow-request log from {}: {}", cell, e),
+            }
+        }
+        calls.sort_by(|a, b| b.duration_ms.cmp(&a.duration_ms));
+        calls.truncate(limit as usize);
+        Ok(calls)
+    }
+
+    /// Spans of one trace from each of `cells`, in start order. Parent ids
+    /// join them into the call tree across hops.
+    async fn trace(&self, trace_id: String, cells: Vec<String>) -> Result<Vec<TraceSpan>> {
+        let mut spans = Vec::new();
+        for cell in cells {
+            match cell_sdk::trace::fetch(&cell, &trace_id).await {
+                Ok(records) => spans.extend(records.into_iter().map(|s| TraceSpan {
+                    trace_id: s.trace_id,
+                    span_id: s.span_id,
+                    parent_id: s.parent_id,
+                    cell: s.cell,
+                    name: s.name,
+                    start_unix_us: s.start_unix_us,
+                    duration_us: s.duration_us,
+                    ok: s.ok,
+                })),
+                Err(e) => tracing::warn!("[Observer] No spans from {}: {}", cell, e),
+            }
+        }
+        spans.sort_by_key(|s| s.start_unix_us);
+        Ok(spans)
+    }
+
+    /// Error budgets of every method with SLOs across `cells`, most spent
+    /// first. Exhausted budgets are logged as alerts.
+    async fn slo_budgets(&self, cells: Vec<String>) -> Result<Vec<SloBudget>> {
+        let mut budgets = Vec::new();
+        for cell in cells {
+            match cell_sdk::slo::status(&cell).await {
+                Ok(statuses) => budgets.extend(statuses.into_iter().map(|s| SloBudget {
+                    cell: cell.clone(),
+                    exhausted: s.exhausted(),
+                    method: s.method,
+                    availability: s.availability,
+                    latency: s.latency,
+                    budget_remaining: s.budget_remaining,
+                })),
+                Err(e) => tracing::warn!("[Observer] No SLO report from {}: {}", cell, e),
+            }
+        }
+        for b in budgets.iter().filter(|b| b.exhausted) {
+            tracing::error!(
+                target: "cell::alert",
+                "[Observer] Error budget exhausted: {}::{} (availability {:.3}%, latency {:.3}%)",
+                b.cell, b.method, b.availability, b.latency
+            );
+        }
+        budgets.sort_by(|a, b| a.budget_remaining.total_cmp(&b.budget_remaining));
+        Ok(budgets)
+    }
+
+    /// Replay audit of every cell in `cells`. Divergence and short logs are
+    /// logged as alerts.
+    async fn replay_audits(&self, cells: Vec<String>) -> Result<Vec<ReplayAudit>> {
+        Ok(audit_replays(cells).await)
+    }
+
     async fn verify_chain(&self) -> Result<bool> {
         let state = self.state.read().await;
         
@@ -116,10 +256,80 @@ impl ObserverService {
     }
 }
 
+async fn audit_replays(cells: Vec<String>) -> Vec<ReplayAudit> {
+    use cell_sdk::replay::ReplayOutcome;
+
+    let hex = |hash: &[u8; 32]| hash.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    let mut audits = Vec::new();
+    for cell in cells {
+        let report = match cell_sdk::replay::fetch(&cell).await {
+            Ok(report) => report,
+            Err(e) => {
+                tracing::warn!("[Observer] No replay audit from {}: {}", cell, e);
+                audits.push(ReplayAudit {
+                    cell,
+                    outcome: format!("unreachable: {}", e),
+                    applied: 0,
+                    replayed: 0,
+                    live_hash: String::new(),
+                    replay_hash: String::new(),
+                });
+                continue;
+            }
+        };
+        let outcome = report.outcome();
+        let audit = ReplayAudit {
+            cell,
+            outcome: match outcome {
+                ReplayOutcome::Consistent => "consistent",
+                ReplayOutcome::LogShort => "log-short",
+                ReplayOutcome::Diverged => "diverged",
+            }
+            .to_string(),
+            applied: report.applied,
+            replayed: report.replayed,
+            live_hash: hex(&report.live_hash),
+            replay_hash: hex(&report.replay_hash),
+        };
+        if outcome != ReplayOutcome::Consistent {
+            tracing::error!(
+                target: "cell::alert",
+                "[Observer] Replay audit of {}: {} (applied {}, replayed {}, live {} vs replay {})",
+                audit.cell, audit.outcome, audit.applied, audit.replayed, audit.live_hash, audit.replay_hash
+            );
+        }
+        audits.push(audit);
+    }
+    audits
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     tracing_subscriber::fmt().init();
     tracing::info!("[Observer] Telemetry Bus Active");
+
+    // CELL_REPLAY_AUDIT=consensus-0,consensus-1 audits those cells every
+    // CELL_REPLAY_AUDIT_SECS (default 300)
+    let audited: Vec<String> = std::env::var("CELL_REPLAY_AUDIT")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .map(String::from)
+        .collect();
+    if !audited.is_empty() {
+        let every = std::env::var("CELL_REPLAY_AUDIT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300);
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(std::time::Duration::from_secs(every));
+            loop {
+                ticks.tick().await;
+                audit_replays(audited.clone()).await;
+            }
+        });
+    }
     
     let service = ObserverService::new();
     service.serve("observer").await
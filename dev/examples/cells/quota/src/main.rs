@@ -0,0 +1,306 @@
+// cells/quota/src/main.rs
+// SPDX-License-Identifier: MIT
+// Per-principal request, storage and queue quotas, with usage metering
+
+use anyhow::Result;
+use cell_sdk::metering::{to_csv, Meter, UsageRecord};
+use cell_sdk::quota::{now_ms, QuotaKind, QuotaLedger, QuotaLimits, QuotaTable};
+use cell_sdk::*;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+// === PROTOCOL ===
+
+#[protein]
+pub struct Limits {
+    pub requests_per_min: Option<u32>,
+    pub storage_bytes: Option<u64>,
+    pub queue_depth: Option<u32>,
+}
+
+#[protein]
+pub struct Admission {
+    pub allowed: bool,
+    pub reason: Option<String>,
+}
+
+#[protein]
+pub struct Usage {
+    pub principal: String,
+    pub requests_last_min: u32,
+    pub storage_bytes: u64,
+    pub queue_depth: u32,
+    pub limits: Limits,
+}
+
+#[protein]
+pub struct Alert {
+    pub principal: String,
+    pub kind: String,
+    pub current: u64,
+    pub limit: u64,
+}
+
+#[protein]
+pub struct MeteringRecord {
+    pub organism: String,
+    pub principal: String,
+    pub period_start: u64,
+    pub period_end: u64,
+    pub requests: u64,
+    pub rejected: u64,
+    pub storage_byte_seconds: u64,
+    pub peak_storage_bytes: u64,
+}
+
+// === METERING EXPORT ===
+
+enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// Where closed metering periods are written: `CELL_METERING_DIR`
+/// (default `~/.cell/metering`) in `CELL_METERING_FORMAT` (`csv` or `json`).
+struct Exporter {
+    dir: PathBuf,
+    format: ExportFormat,
+}
+
+impl Exporter {
+    fn from_env() -> Self {
+        let dir = std::env::var("CELL_METERING_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| dirs::home_dir().unwrap_or_default().join(".cell/metering"));
+        let format = match std::env::var("CELL_METERING_FORMAT").as_deref() {
+            Ok("json") => ExportFormat::Json,
+            _ => ExportFormat::Csv,
+        };
+        Self { dir, format }
+    }
+
+    fn write(&self, period_start: u64, records: &[UsageRecord]) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+        let (ext, body) = match self.format {
+            ExportFormat::Csv => ("csv", to_csv(records)),
+            ExportFormat::Json => ("json", serde_json::to_string_pretty(records)?),
+        };
+        let path = self.dir.join(format!("usage-{}.{}", period_start, ext));
+        std::fs::write(&path, body)?;
+        Ok(path)
+    }
+}
+
+// === SERVICE ===
+
+struct QuotaState {
+    ledger: QuotaLedger,
+    /// What the ledger enforces, published for every Membrane on this node
+    table: QuotaTable,
+    meter: Meter,
+    /// Bytes each principal holds per organism; the ledger only sees the total
+    held: BTreeMap<(String, String), u64>,
+    /// Records of the most recently closed period, for pull-based consumers
+    last_period: Vec<UsageRecord>,
+}
+
+#[service]
+#[derive(Clone)]
+struct QuotaService {
+    state: Arc<RwLock<QuotaState>>,
+}
+
+impl QuotaService {
+    /// Starts from the limits published last, so they survive restarts
+    fn new() -> Self {
+        let table = match cell_sdk::quota::load_table() {
+            Ok(table) => table.unwrap_or_default(),
+            Err(e) => {
+                tracing::warn!("[Quota] Starting without the published limits: {:#}", e);
+                QuotaTable::default()
+            }
+        };
+        let mut ledger = QuotaLedger::new(table.default.clone());
+        for (principal, limits) in &table.principals {
+            ledger.set_limits(principal, limits.clone());
+        }
+        Self {
+            state: Arc::new(RwLock::new(QuotaState {
+                ledger,
+                table,
+                meter: Meter::new(now_ms() / 1000),
+                held: BTreeMap::new(),
+                last_period: Vec::new(),
+            })),
+        }
+    }
+
+    async fn close_period(&self, exporter: &Exporter) -> Result<()> {
+        // Requests the Membranes on this node served since the last period
+        let spooled = tokio::task::spawn_blocking(cell_sdk::quota::take_spooled).await??;
+        let mut state = self.state.write().await;
+        for r in spooled {
+            state.meter.add_requests(&r.organism, &r.principal, r.admitted, r.rejected);
+        }
+        let start = state.meter.period_start();
+        let records = state.meter.close_period(now_ms() / 1000);
+        state.last_period = records.clone();
+        drop(state);
+
+        if !records.is_empty() {
+            let path = exporter.write(start, &records)?;
+            tracing::info!("[Quota] Exported {} usage records to {:?}", records.len(), path);
+        }
+        Ok(())
+    }
+}
+
+fn admission(result: std::result::Result<(), cell_sdk::quota::QuotaBreach>) -> Admission {
+    match result {
+        Ok(()) => Admission { allowed: true, reason: None },
+        Err(breach) => {
+            tracing::warn!("[Quota] {}", breach);
+            Admission { allowed: false, reason: Some(breach.to_string()) }
+        }
+    }
+}
+
+fn kind_name(kind: QuotaKind) -> String {
+    match kind {
+        QuotaKind::Requests => "requests",
+        QuotaKind::Storage => "storage",
+        QuotaKind::Queue => "queue",
+    }
+    .to_string()
+}
+
+#[handler]
+impl QuotaService {
+    async fn set_limits(&self, principal: String, limits: Limits) -> Result<bool> {
+        let limits = QuotaLimits {
+            requests_per_min: limits.requests_per_min,
+            storage_bytes: limits.storage_bytes,
+            queue_depth: limits.queue_depth,
+        };
+        let mut state = self.state.write().await;
+        state.ledger.set_limits(&principal, limits.clone());
+        state.table.principals.insert(principal.clone(), limits);
+        // Membranes enforce request and queue limits from the published table
+        cell_sdk::quota::publish(&state.table)?;
+        drop(state);
+        tracing::info!("[Quota] Updated limits for '{}'", principal);
+        Ok(true)
+    }
+
+    /// Charge one request made by `principal` in `organism`, for callers
+    /// outside a Membrane; Membranes meter the requests they serve themselves
+    async fn admit(&self, organism: String, principal: String) -> Result<Admission> {
+        let mut state = self.state.write().await;
+        let result = state.ledger.admit_request(&principal, now_ms());
+        state.meter.record_request(&organism, &principal, result.is_ok());
+        Ok(admission(result))
+    }
+
+    /// Called by storage cells with the change in bytes held for `principal`
+    async fn charge_storage(&self, organism: String, principal: String, delta: i64) -> Result<Admission> {
+        let mut state = self.state.write().await;
+        let result = state.ledger.reserve_storage(&principal, delta);
+        if result.is_ok() {
+            let held = state.held.entry((organism.clone(), principal.clone())).or_default();
+            *held = held.saturating_add_signed(delta);
+            let held = *held;
+            state.meter.record_storage(&organism, &principal, held, now_ms() / 1000);
+        }
+        Ok(admission(result))
+    }
+
+    /// `entered = true` when a request is queued for `principal`, false when it leaves
+    async fn queue(&self, principal: String, entered: bool) -> Result<Admission> {
+        let mut state = self.state.write().await;
+        if entered {
+            Ok(admission(state.ledger.enter_queue(&principal)))
+        } else {
+            state.ledger.leave_queue(&principal);
+            Ok(admission(Ok(())))
+        }
+    }
+
+    async fn usage(&self, principal: String) -> Result<Usage> {
+        let mut state = self.state.write().await;
+        let usage = state.ledger.usage(&principal, now_ms());
+        let limits = state.ledger.limits(&principal).clone();
+        Ok(Usage {
+            principal,
+            requests_last_min: usage.requests_last_min,
+            storage_bytes: usage.storage_bytes,
+            queue_depth: usage.queue_depth,
+            limits: Limits {
+                requests_per_min: limits.requests_per_min,
+                storage_bytes: limits.storage_bytes,
+                queue_depth: limits.queue_depth,
+            },
+        })
+    }
+
+    /// Principals at or above `threshold_pct` percent of any limit
+    async fn alerts(&self, threshold_pct: u8) -> Result<Vec<Alert>> {
+        let alerts = self.state.write().await.ledger.alerts(now_ms(), threshold_pct);
+        Ok(alerts
+            .into_iter()
+            .map(|a| Alert {
+                principal: a.principal,
+                kind: kind_name(a.kind),
+                current: a.current,
+                limit: a.limit,
+            })
+            .collect())
+    }
+
+    /// Usage records of the last closed metering period
+    async fn metering(&self) -> Result<Vec<MeteringRecord>> {
+        let state = self.state.read().await;
+        Ok(state
+            .last_period
+            .iter()
+            .map(|r| MeteringRecord {
+                organism: r.organism.clone(),
+                principal: r.principal.clone(),
+                period_start: r.period_start,
+                period_end: r.period_end,
+                requests: r.requests,
+                rejected: r.rejected,
+                storage_byte_seconds: r.storage_byte_seconds,
+                peak_storage_bytes: r.peak_storage_bytes,
+            })
+            .collect())
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt().init();
+    tracing::info!("[Quota] Quota service active");
+    let service = QuotaService::new();
+
+    // Close a metering period every CELL_METERING_INTERVAL seconds (default hourly)
+    let interval_secs = std::env::var("CELL_METERING_INTERVAL")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(3600);
+    let metering = service.clone();
+    tokio::spawn(async move {
+        let exporter = Exporter::from_env();
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_secs));
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(e) = metering.close_period(&exporter).await {
+                tracing::error!("[Quota] Metering export failed: {}", e);
+            }
+        }
+    });
+
+    service.serve("quota").await
+}
@@ -0,0 +1,30 @@
+use cell_sdk::*;
+
+cell_remote!(Quota = "quota");
+
+#[tokio::test]
+async fn quota_enforces_and_alerts() {
+    cell_sdk::System::ignite_local_cluster().await.unwrap();
+
+    System::spawn("quota", None).await.expect("Failed to spawn");
+    let synapse = Synapse::grow_await("quota").await.expect("Failed to connect");
+    let mut quota = Quota::Client::new(synapse);
+
+    quota.set_limits("tenant-a".into(), Quota::Limits {
+        requests_per_min: Some(1),
+        storage_bytes: Some(100),
+        queue_depth: None,
+    }).await.unwrap();
+
+    assert!(quota.admit("acme".into(), "tenant-a".into()).await.unwrap().allowed);
+    assert!(!quota.admit("acme".into(), "tenant-a".into()).await.unwrap().allowed);
+
+    assert!(quota.charge_storage("acme".into(), "tenant-a".into(), 90).await.unwrap().allowed);
+    assert!(!quota.charge_storage("acme".into(), "tenant-a".into(), 20).await.unwrap().allowed);
+
+    let usage = quota.usage("tenant-a".into()).await.unwrap();
+    assert_eq!(usage.storage_bytes, 90);
+
+    let alerts = quota.alerts(80).await.unwrap();
+    assert!(alerts.iter().any(|a| a.principal == "tenant-a" && a.kind == "storage"));
+}
@@ -0,0 +1,268 @@
+// cells/reaper/src/main.rs
+// SPDX-License-Identifier: MIT
+// Janitor for orphaned runtime artifacts
+//
+// Every CELL_REAPER_INTERVAL_SECS (default 600) the reaper scans for
+// artifacts that crashed or killed cells leave behind:
+//
+//   shared memory    /dev/shm/cell*, not mapped or open by any process
+//   proxy sockets    axon_proxy_*.sock in CELL_SOCKET_DIR (default /tmp/cell)
+//                    that no axon accepts on any more
+//   WAL temp files   *.tmp and *.snapshot.part under CELL_REAPER_ROOTS
+//                    (colon separated, default ~/.cell), not open anywhere
+//   test sockets     anything under CELL_REAPER_TEST_SOCKETS (default
+//                    target/test-sockets) nobody listens on
+//
+// and removes those past their grace period (cell_sdk::reaper::ReapPolicy).
+// With CELL_REAPER_DRY_RUN=1 scheduled passes only report. `reap(true)` is
+// always a dry run; `last_report` returns the latest pass.
+
+use cell_sdk::*;
+use cell_sdk::reaper::{self, ArtifactKind, Finding, ReapPolicy};
+use anyhow::Result;
+use std::collections::HashSet;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+
+// === PROTOCOL ===
+
+#[protein]
+pub struct Reaped {
+    pub path: String,
+    pub kind: String,
+    pub bytes: u64,
+    pub age_secs: u64,
+}
+
+#[protein]
+pub struct ReapReport {
+    pub dry_run: bool,
+    pub started_at: u64,
+    pub scanned: u32,
+    /// In a dry run, what would have been removed
+    pub removed: Vec<Reaped>,
+    pub bytes_freed: u64,
+    pub errors: Vec<String>,
+}
+
+/// Directories deeper than this under a root are not scanned
+const MAX_DEPTH: usize = 8;
+
+#[derive(Clone)]
+struct ReaperConfig {
+    interval: Duration,
+    dry_run: bool,
+    shm_dir: PathBuf,
+    socket_dir: PathBuf,
+    test_socket_dir: PathBuf,
+    roots: Vec<PathBuf>,
+    policy: ReapPolicy,
+}
+
+impl ReaperConfig {
+    fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let roots = match var("CELL_REAPER_ROOTS") {
+            Some(roots) => roots.split(':').map(PathBuf::from).collect(),
+            None => dirs::home_dir().map(|h| vec![h.join(".cell")]).unwrap_or_default(),
+        };
+        Self {
+            interval: Duration::from_secs(
+                var("CELL_REAPER_INTERVAL_SECS").and_then(|v| v.parse().ok()).unwrap_or(600).max(1),
+            ),
+            dry_run: var("CELL_REAPER_DRY_RUN").is_some_and(|v| v != "0"),
+            shm_dir: PathBuf::from("/dev/shm"),
+            socket_dir: var("CELL_SOCKET_DIR").map(PathBuf::from).unwrap_or_else(|| "/tmp/cell".into()),
+            test_socket_dir: var("CELL_REAPER_TEST_SOCKETS")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| "target/test-sockets".into()),
+            roots,
+            policy: ReapPolicy::default(),
+        }
+    }
+}
+
+/// One scan-and-remove pass. Blocking: run it off the runtime.
+fn pass(config: &ReaperConfig, dry_run: bool) -> ReapReport {
+    let open = open_files();
+    let mut findings = Vec::new();
+
+    for entry in list(&config.shm_dir) {
+        if entry.file_name().to_string_lossy().starts_with("cell") {
+            findings.extend(finding(&entry.path(), ArtifactKind::ShmSegment, &open));
+        }
+    }
+    for entry in list(&config.socket_dir) {
+        if reaper::is_proxy_socket(&entry.file_name().to_string_lossy()) {
+            findings.extend(finding(&entry.path(), ArtifactKind::ProxySocket, &open));
+        }
+    }
+    for root in &config.roots {
+        walk(root, 0, &mut |path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if reaper::is_wal_temp(&name) {
+                findings.extend(finding(path, ArtifactKind::WalTemp, &open));
+            }
+        });
+    }
+    walk(&config.test_socket_dir, 0, &mut |path| {
+        findings.extend(finding(path, ArtifactKind::TestSocket, &open));
+    });
+
+    let mut report = ReapReport {
+        dry_run,
+        started_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        scanned: findings.len() as u32,
+        removed: Vec::new(),
+        bytes_freed: 0,
+        errors: Vec::new(),
+    };
+    for orphan in reaper::plan(&findings, &config.policy) {
+        if !dry_run {
+            if let Err(e) = std::fs::remove_file(&orphan.path) {
+                report.errors.push(format!("{}: {}", orphan.path, e));
+                continue;
+            }
+        }
+        report.bytes_freed += orphan.bytes;
+        report.removed.push(Reaped {
+            path: orphan.path,
+            kind: orphan.kind.name().to_string(),
+            bytes: orphan.bytes,
+            age_secs: orphan.age_secs,
+        });
+    }
+    report
+}
+
+fn finding(path: &Path, kind: ArtifactKind, open: &HashSet<PathBuf>) -> Option<Finding> {
+    let meta = std::fs::symlink_metadata(path).ok()?;
+    if meta.is_dir() || meta.file_type().is_symlink() {
+        return None;
+    }
+    let in_use = if meta.file_type().is_socket() {
+        std::os::unix::net::UnixStream::connect(path).is_ok()
+    } else {
+        open.contains(path)
+    };
+    Some(Finding {
+        path: path.to_string_lossy().to_string(),
+        kind,
+        bytes: meta.len(),
+        age_secs: meta.modified().ok()?.elapsed().map(|d| d.as_secs()).unwrap_or(0),
+        in_use,
+    })
+}
+
+fn list(dir: &Path) -> Vec<std::fs::DirEntry> {
+    std::fs::read_dir(dir).map(|d| d.flatten().collect()).unwrap_or_default()
+}
+
+/// Call `visit` for every non-directory under `dir`, without following symlinks
+fn walk(dir: &Path, depth: usize, visit: &mut dyn FnMut(&Path)) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    for entry in list(dir) {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => walk(&path, depth + 1, visit),
+            Ok(t) if t.is_symlink() => {}
+            Ok(_) => visit(&path),
+            Err(_) => {}
+        }
+    }
+}
+
+/// Files some process holds open or mapped, from /proc
+fn open_files() -> HashSet<PathBuf> {
+    let mut open = HashSet::new();
+    for process in list(Path::new("/proc")) {
+        if !process.file_name().to_string_lossy().bytes().all(|b| b.is_ascii_digit()) {
+            continue;
+        }
+        for fd in list(&process.path().join("fd")) {
+            if let Ok(target) = std::fs::read_link(fd.path()) {
+                open.insert(target);
+            }
+        }
+        if let Ok(maps) = std::fs::read_to_string(process.path().join("maps")) {
+            for line in maps.lines() {
+                if let Some(pos) = line.find(" /") {
+                    open.insert(PathBuf::from(line[pos + 1..].trim_end_matches(" (deleted)")));
+                }
+            }
+        }
+    }
+    open
+}
+
+// === SERVICE ===
+
+#[service]
+#[derive(Clone)]
+struct Reaper {
+    config: ReaperConfig,
+    last: Arc<RwLock<Option<ReapReport>>>,
+}
+
+impl Reaper {
+    async fn run(&self, dry_run: bool) -> Result<ReapReport> {
+        let config = self.config.clone();
+        let report = tokio::task::spawn_blocking(move || pass(&config, dry_run)).await?;
+        let verb = if dry_run { "Would remove" } else { "Removed" };
+        if !report.removed.is_empty() || !report.errors.is_empty() {
+            tracing::info!(
+                "[Reaper] {} {} of {} artifacts ({} bytes), {} errors",
+                verb, report.removed.len(), report.scanned, report.bytes_freed, report.errors.len()
+            );
+        }
+        *self.last.write().await = Some(report.clone());
+        Ok(report)
+    }
+}
+
+#[handler]
+impl Reaper {
+    /// Run a pass now; `dry_run` only reports what would be removed
+    async fn reap(&self, dry_run: bool) -> Result<ReapReport> {
+        self.run(dry_run).await
+    }
+
+    #[handler(read)]
+    async fn last_report(&self) -> Result<Option<ReapReport>> {
+        Ok(self.last.read().await.clone())
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt().init();
+
+    let config = ReaperConfig::from_env();
+    let reaper = Reaper {
+        config: config.clone(),
+        last: Arc::new(RwLock::new(None)),
+    };
+
+    let scheduled = reaper.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(config.interval);
+        loop {
+            interval.tick().await;
+            if let Err(e) = scheduled.run(config.dry_run).await {
+                tracing::warn!("[Reaper] Pass failed: {}", e);
+            }
+        }
+    });
+
+    tracing::info!(
+        "[Reaper] Sweeping every {:?}{}",
+        config.interval,
+        if config.dry_run { " (dry run)" } else { "" }
+    );
+    reaper.serve("reaper").await
+}
@@ -1,14 +1,32 @@
 // cells/registry/src/main.rs
 // SPDX-License-Identifier: MIT
 // Git-as-registry with signature verification (decentralized package manager)
+//
+// Published versions are immutable. Release channels (stable, beta, nightly)
+// point at them: publishers push to nightly or beta, versions are promoted a
+// channel at a time, and every move is kept as history and sent to the audit
+// cell. Consumers pin `name@channel`, which `resolve` turns into the version
+// and commit to build.
+//
+// Publishing with a Cargo.lock stores a scan with the version: its crates.io
+// dependencies, their licenses and the RustSec advisories against them
+// (checkout in CELL_ADVISORY_DB or ~/.cell/advisory-db, pulled on start).
+// `audit` rescans those inventories, so advisories published later show up
+// for versions already running.
 
 use cell_sdk::*;
 use anyhow::Result;
-use std::collections::HashMap;
+use cell_build::advisory::{self, AdvisoryDb, Dependency};
+use cell_build::schema::{self, Schema};
+use cell_sdk::release::{self, CellRef, Channel, ChannelEntry, Pin};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+cell_remote!(Blobstore = "blobstore");
+cell_remote!(Audit = "audit");
+
 // === REGISTRY PROTOCOL ===
 
 #[protein]
@@ -27,6 +45,75 @@ pub struct PublishRequest {
     pub package: Package,
     pub source_tarball: Vec<u8>,
     pub signing_key: Vec<u8>,
+    /// Channel to push the new version to, if any
+    pub channel: Option<String>,
+    /// Cargo.lock of the published source, scanned for advisories
+    pub lockfile: Option<String>,
+}
+
+#[protein]
+pub struct DependencyLicense {
+    pub name: String,
+    pub version: String,
+    pub license: Option<String>,
+}
+
+#[protein]
+pub struct Vulnerability {
+    pub package: String,
+    pub version: String,
+    pub advisory: String,
+    pub title: String,
+    pub patched: Vec<String>,
+}
+
+/// Dependencies of a published version and the advisories against them
+#[protein]
+pub struct ScanReport {
+    pub name: String,
+    pub version: String,
+    pub dependencies: Vec<DependencyLicense>,
+    pub vulnerabilities: Vec<Vulnerability>,
+    /// Advisories checked against; 0 when no database was available
+    pub advisories: u32,
+    pub scanned_at: u64,
+}
+
+/// Point a channel at an already published version
+#[protein]
+pub struct ChannelPush {
+    pub name: String,
+    pub version: String,
+    pub channel: String,
+    pub actor: String,
+}
+
+/// Move the version on `from` to the next channel up
+#[protein]
+pub struct Promotion {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+    pub actor: String,
+}
+
+/// What a `name`, `name@channel` or `name@version` stands for right now
+#[protein]
+pub struct Release {
+    pub name: String,
+    pub version: String,
+    pub channel: Option<String>,
+    pub git_url: String,
+    pub commit_hash: String,
+}
+
+#[protein]
+pub struct ChannelMove {
+    pub channel: String,
+    pub version: String,
+    pub action: String,
+    pub actor: String,
+    pub at: u64,
 }
 
 #[protein]
@@ -69,12 +156,30 @@ pub struct TrustKey {
     pub public_key: Vec<u8>,
 }
 
+/// A pub/sub topic in the event catalog
+#[protein]
+pub struct TopicDecl {
+    pub name: String,
+    /// Payload protein, as named in the producer's source
+    pub payload: String,
+    pub producers: Vec<String>,
+    pub consumers: Vec<String>,
+    pub description: Option<String>,
+}
+
 // === REGISTRY SERVICE ===
 
 pub struct RegistryService {
     repo_root: PathBuf,
     packages: Arc<RwLock<HashMap<String, Vec<Package>>>>,
+    /// (name, version) -> blobstore hash of the source tarball
+    tarballs: Arc<RwLock<HashMap<(String, String), String>>>,
     trusted_keys: Arc<RwLock<HashMap<String, Vec<u8>>>>,
+    /// (name, version) -> dependency scan from publish, or the latest audit
+    scans: Arc<RwLock<HashMap<(String, String), ScanReport>>>,
+    /// name -> every channel move, oldest first
+    channels: Arc<RwLock<HashMap<String, Vec<ChannelEntry>>>>,
+    topics: Arc<RwLock<BTreeMap<String, TopicDecl>>>,
     stats: Arc<RwLock<PackageStats>>,
 }
 
@@ -88,7 +193,11 @@ impl RegistryService {
         Self {
             repo_root,
             packages: Arc::new(RwLock::new(HashMap::new())),
+            tarballs: Arc::new(RwLock::new(HashMap::new())),
             trusted_keys: Arc::new(RwLock::new(HashMap::new())),
+            scans: Arc::new(RwLock::new(HashMap::new())),
+            channels: Arc::new(RwLock::new(HashMap::new())),
+            topics: Arc::new(RwLock::new(BTreeMap::new())),
             stats: Arc::new(RwLock::new(PackageStats {
                 downloads: HashMap::new(),
                 stars: HashMap::new(),
@@ -146,6 +255,45 @@ impl RegistryService {
         Ok(repo_path)
     }
 
+    /// Append a channel move and record it with the audit cell
+    async fn move_channel(
+        &self,
+        name: &str,
+        channel: Channel,
+        version: &str,
+        action: &str,
+        actor: &str,
+    ) {
+        let at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.channels.write().await.entry(name.to_string()).or_default().push(ChannelEntry {
+            channel,
+            version: version.to_string(),
+            action: action.to_string(),
+            actor: actor.to_string(),
+            at,
+        });
+        println!("[Registry] {}@{} -> {} ({} by {})", name, channel, version, action, actor);
+
+        let event = Audit::AuditEvent {
+            actor: actor.to_string(),
+            action: format!("registry:{}", action),
+            resource: format!("{}@{}", name, channel),
+            outcome: "Success".to_string(),
+            metadata: format!("version {}", version),
+            timestamp: at,
+        };
+        let logged = match Audit::Client::connect().await {
+            Ok(mut audit) => audit.log(event).await.map(drop),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = logged {
+            tracing::warn!("[Registry] Channel move of {} not audited: {}", name, e);
+        }
+    }
+
     async fn build_package(&self, source_path: &PathBuf) -> Result<PathBuf> {
         use std::process::Command;
         
@@ -169,7 +317,27 @@ impl RegistryService {
         if !self.verify_signature(&req.package).await? {
             anyhow::bail!("Invalid signature");
         }
+        let channel = req.channel.as_deref().map(pushable).transpose()?;
+        let dependencies = match &req.lockfile {
+            Some(lockfile) => {
+                let mut deps = advisory::dependencies(lockfile)?;
+                advisory::with_licenses(&mut deps);
+                Some(deps)
+            }
+            None => None,
+        };
+        if self.packages.read().await.get(&req.package.name)
+            .is_some_and(|versions| versions.iter().any(|p| p.version == req.package.version))
+        {
+            anyhow::bail!("{}@{} is already published", req.package.name, req.package.version);
+        }
         
+        let blob = Blobstore::Client::connect().await?.put(req.source_tarball).await?;
+        self.tarballs.write().await.insert(
+            (req.package.name.clone(), req.package.version.clone()),
+            blob.hash,
+        );
+
         // Add to registry
         self.packages.write().await
             .entry(req.package.name.clone())
@@ -178,10 +346,103 @@ impl RegistryService {
         
         // Commit to git repo (in real impl)
         println!("[Registry] Published {}@{}", req.package.name, req.package.version);
+
+        match dependencies {
+            Some(deps) => {
+                let report = scan(&advisory_db(), &req.package.name, &req.package.version, &deps);
+                for v in &report.vulnerabilities {
+                    tracing::warn!("[Registry] {}@{} depends on {} {}: {} ({})",
+                        report.name, report.version, v.package, v.version, v.advisory, v.title);
+                }
+                let key = (report.name.clone(), report.version.clone());
+                self.scans.write().await.insert(key, report);
+            }
+            None => tracing::warn!("[Registry] {}@{} published without a Cargo.lock; not scanned",
+                req.package.name, req.package.version),
+        }
+
+        if let Some(channel) = channel {
+            let p = &req.package;
+            self.move_channel(&p.name, channel, &p.version, "publish", &p.author).await;
+        }
         
         Ok(true)
     }
 
+    /// Point nightly or beta at a published version; stable only moves by promotion
+    pub async fn push(&self, req: ChannelPush) -> Result<bool> {
+        let channel = pushable(&req.channel)?;
+        let published = self.packages.read().await.get(&req.name)
+            .is_some_and(|versions| versions.iter().any(|p| p.version == req.version));
+        if !published {
+            anyhow::bail!("{}@{} is not published", req.name, req.version);
+        }
+        self.move_channel(&req.name, channel, &req.version, "push", &req.actor).await;
+        Ok(true)
+    }
+
+    /// Move the version on `from` up to `to`; returns that version
+    pub async fn promote(&self, req: Promotion) -> Result<String> {
+        let from = parse_channel(&req.from)?;
+        let to = parse_channel(&req.to)?;
+        let version = {
+            let channels = self.channels.read().await;
+            let history = channels.get(&req.name).map(Vec::as_slice).unwrap_or_default();
+            release::promotion(history, from, to)
+                .map_err(|e| anyhow::anyhow!("{}: {}", req.name, e))?
+                .to_string()
+        };
+        self.move_channel(&req.name, to, &version, "promote", &req.actor).await;
+        Ok(version)
+    }
+
+    /// The version and commit a cell reference stands for now
+    pub async fn resolve(&self, cell: String) -> Result<Release> {
+        let cell = CellRef::parse(&cell);
+        let (version, channel) = match &cell.pin {
+            Pin::Latest => (None, None),
+            Pin::Version(version) => (Some(version.clone()), None),
+            Pin::Channel(channel) => {
+                let channels = self.channels.read().await;
+                let history = channels.get(&cell.name).map(Vec::as_slice).unwrap_or_default();
+                let version = release::head(history, *channel)
+                    .ok_or_else(|| anyhow::anyhow!("Nothing on {}", cell))?;
+                (Some(version.to_string()), Some(channel.to_string()))
+            }
+        };
+
+        let packages = self.packages.read().await;
+        let versions = packages.get(&cell.name)
+            .ok_or_else(|| anyhow::anyhow!("Package not found"))?;
+        let package = match &version {
+            Some(version) => versions.iter().find(|p| &p.version == version),
+            None => versions.last(),
+        }
+        .ok_or_else(|| anyhow::anyhow!("{} is not published", cell))?;
+
+        Ok(Release {
+            name: package.name.clone(),
+            version: package.version.clone(),
+            channel,
+            git_url: package.git_url.clone(),
+            commit_hash: package.commit_hash.clone(),
+        })
+    }
+
+    /// Every channel move of a package, oldest first
+    pub async fn channel_history(&self, name: String) -> Result<Vec<ChannelMove>> {
+        let channels = self.channels.read().await;
+        Ok(channels.get(&name).into_iter().flatten()
+            .map(|e| ChannelMove {
+                channel: e.channel.to_string(),
+                version: e.version.clone(),
+                action: e.action.clone(),
+                actor: e.actor.clone(),
+                at: e.at,
+            })
+            .collect())
+    }
+
     pub async fn search(&self, query: SearchQuery) -> Result<SearchResult> {
         let packages_map = self.packages.read().await;
         let stats = self.stats.read().await;
@@ -243,11 +504,83 @@ impl RegistryService {
         })
     }
 
+    /// Source tarball of a published version
+    pub async fn tarball(&self, name: String, version: String) -> Result<Vec<u8>> {
+        let hash = self.tarballs.read().await.get(&(name, version)).cloned()
+            .ok_or_else(|| anyhow::anyhow!("Package not found"))?;
+        Blobstore::Client::connect().await?.get(hash).await
+    }
+
+    /// Scan stored when the version was published (or last audited)
+    pub async fn scan_report(&self, name: String, version: String) -> Result<Option<ScanReport>> {
+        Ok(self.scans.read().await.get(&(name, version)).cloned())
+    }
+
+    /// Rescan `versions` of a package (all scanned ones if empty) against
+    /// today's advisory database
+    pub async fn audit(&self, name: String, versions: Vec<String>) -> Result<Vec<ScanReport>> {
+        let mut scans = self.scans.write().await;
+        let mut keys: Vec<(String, String)> = scans.keys()
+            .filter(|(n, v)| *n == name && (versions.is_empty() || versions.contains(v)))
+            .cloned()
+            .collect();
+        keys.sort();
+
+        let db = advisory_db();
+        let mut reports = Vec::new();
+        for key in keys {
+            let deps: Vec<Dependency> = scans[&key].dependencies.iter()
+                .map(|d| Dependency {
+                    name: d.name.clone(),
+                    version: d.version.clone(),
+                    license: d.license.clone(),
+                })
+                .collect();
+            let report = scan(&db, &key.0, &key.1, &deps);
+            scans.insert(key, report.clone());
+            reports.push(report);
+        }
+        Ok(reports)
+    }
+
     pub async fn trust(&self, key: TrustKey) -> Result<bool> {
         self.trusted_keys.write().await.insert(key.author, key.public_key);
         Ok(true)
     }
 
+    /// Add or replace a topic in the event catalog
+    pub async fn declare_topic(&self, topic: TopicDecl) -> Result<bool> {
+        self.topics.write().await.insert(topic.name.clone(), topic);
+        Ok(true)
+    }
+
+    /// AsyncAPI document (JSON) describing every declared topic
+    pub async fn asyncapi(&self) -> Result<String> {
+        let topics: Vec<cell_build::asyncapi::Topic> = self.topics.read().await.values()
+            .map(|t| cell_build::asyncapi::Topic {
+                name: t.name.clone(),
+                payload: t.payload.clone(),
+                producers: t.producers.clone(),
+                consumers: t.consumers.clone(),
+                description: t.description.clone(),
+            })
+            .collect();
+
+        let mut schemas = BTreeMap::new();
+        for cell in topics.iter().flat_map(|t| &t.producers) {
+            if schemas.contains_key(cell) {
+                continue;
+            }
+            match producer_schema(cell).await {
+                Some(schema) => { schemas.insert(cell.clone(), schema); }
+                None => tracing::warn!("[Registry] No schema for producer '{}'", cell),
+            }
+        }
+
+        let doc = cell_build::asyncapi::document("Cell event catalog", &topics, &schemas);
+        Ok(serde_json::to_string_pretty(&doc)?)
+    }
+
     pub async fn star(&self, package_name: String) -> Result<u32> {
         let mut stats = self.stats.write().await;
         let count = stats.stars.entry(package_name).or_insert(0);
@@ -256,6 +589,65 @@ impl RegistryService {
     }
 }
 
+/// The local advisory database, empty (and scans finding nothing) without one
+fn advisory_db() -> AdvisoryDb {
+    AdvisoryDb::default_path()
+        .ok_or_else(|| anyhow::anyhow!("No HOME dir"))
+        .and_then(|path| AdvisoryDb::open(&path))
+        .unwrap_or_else(|e| {
+            tracing::warn!("[Registry] {}; dependencies not checked for advisories", e);
+            AdvisoryDb::default()
+        })
+}
+
+fn scan(db: &AdvisoryDb, name: &str, version: &str, deps: &[Dependency]) -> ScanReport {
+    ScanReport {
+        name: name.to_string(),
+        version: version.to_string(),
+        dependencies: deps.iter()
+            .map(|d| DependencyLicense {
+                name: d.name.clone(),
+                version: d.version.clone(),
+                license: d.license.clone(),
+            })
+            .collect(),
+        vulnerabilities: db.scan(deps).into_iter()
+            .map(|f| Vulnerability {
+                package: f.package,
+                version: f.version,
+                advisory: f.advisory,
+                title: f.title,
+                patched: f.patched,
+            })
+            .collect(),
+        advisories: db.len() as u32,
+        scanned_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    }
+}
+
+fn parse_channel(name: &str) -> Result<Channel> {
+    Channel::parse(name).ok_or_else(|| anyhow::anyhow!("Unknown channel '{}'", name))
+}
+
+fn pushable(name: &str) -> Result<Channel> {
+    match parse_channel(name)? {
+        Channel::Stable => anyhow::bail!("Stable only takes promoted versions"),
+        channel => Ok(channel),
+    }
+}
+
+/// Running cell first, then the installed copy
+async fn producer_schema(cell: &str) -> Option<Schema> {
+    if let Ok(source) = cell_sdk::source::fetch(cell).await {
+        return Schema::from_source(&source).ok();
+    }
+    let path = schema::registry_source(cell)?;
+    Some(Schema::from_file(&cell_build::load_and_flatten_source(&path).ok()?))
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let repo_root = dirs::home_dir()
@@ -263,6 +655,14 @@ async fn main() -> Result<()> {
         .join(".cell/registry");
     
     tokio::fs::create_dir_all(&repo_root).await?;
+
+    if let Some(db) = AdvisoryDb::default_path() {
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = AdvisoryDb::update(&db) {
+                tracing::warn!("[Registry] Advisory database not updated: {}", e);
+            }
+        });
+    }
     
     let registry = RegistryService::new(repo_root);
     
@@ -31,6 +31,8 @@ async fn registry_publish_flow() {
         package: pkg,
         source_tarball: vec![],
         signing_key: dummy_pub_key,
+        channel: None,
+        lockfile: None,
     }).await;
     
     assert!(res.is_ok());
@@ -41,4 +43,140 @@ async fn registry_publish_flow() {
     }).await.unwrap();
     
     assert_eq!(results.packages.len(), 1);
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn registry_channels_resolve_and_promote() {
+    cell_sdk::System::ignite_local_cluster().await.unwrap();
+
+    System::spawn("registry", None).await.expect("Failed to spawn");
+    let synapse = Synapse::grow_await("registry").await.expect("Failed to connect");
+    let mut r = Registry::Client::new(synapse);
+
+    r.trust(Registry::TrustKey {
+        author: "alice".into(),
+        public_key: vec![1, 2, 3, 4],
+    }).await.unwrap();
+
+    for (version, commit) in [("1.0.0", "aaaaaa"), ("1.1.0", "bbbbbb")] {
+        r.publish(Registry::PublishRequest {
+            package: Registry::Package {
+                name: "ledger".into(),
+                version: version.into(),
+                description: "test".into(),
+                author: "alice".into(),
+                git_url: "https://github.com/alice/ledger".into(),
+                commit_hash: commit.into(),
+                signature: vec![0u8; 64],
+            },
+            source_tarball: vec![],
+            signing_key: vec![1, 2, 3, 4],
+            channel: Some("nightly".into()),
+            lockfile: None,
+        }).await.unwrap();
+    }
+
+    // Versions are immutable and stable only takes promotions
+    let republish = r.publish(Registry::PublishRequest {
+        package: Registry::Package {
+            name: "ledger".into(),
+            version: "1.0.0".into(),
+            description: "test".into(),
+            author: "alice".into(),
+            git_url: "https://github.com/alice/ledger".into(),
+            commit_hash: "cccccc".into(),
+            signature: vec![0u8; 64],
+        },
+        source_tarball: vec![],
+        signing_key: vec![1, 2, 3, 4],
+        channel: None,
+        lockfile: None,
+    }).await;
+    assert!(republish.is_err());
+    let push = Registry::ChannelPush {
+        name: "ledger".into(),
+        version: "1.0.0".into(),
+        channel: "stable".into(),
+        actor: "alice".into(),
+    };
+    assert!(r.push(push).await.is_err());
+
+    r.push(Registry::ChannelPush {
+        name: "ledger".into(),
+        version: "1.0.0".into(),
+        channel: "beta".into(),
+        actor: "alice".into(),
+    }).await.unwrap();
+
+    let beta = r.resolve("ledger@beta".into()).await.unwrap();
+    assert_eq!(beta.version, "1.0.0");
+    assert_eq!(beta.commit_hash, "aaaaaa");
+    assert_eq!(r.resolve("ledger@nightly".into()).await.unwrap().version, "1.1.0");
+    assert!(r.resolve("ledger@stable".into()).await.is_err());
+
+    let promoted = r.promote(Registry::Promotion {
+        name: "ledger".into(),
+        from: "beta".into(),
+        to: "stable".into(),
+        actor: "bob".into(),
+    }).await.unwrap();
+    assert_eq!(promoted, "1.0.0");
+    assert_eq!(r.resolve("ledger@stable".into()).await.unwrap().version, "1.0.0");
+
+    let history = r.channel_history("ledger".into()).await.unwrap();
+    let last = history.last().unwrap();
+    assert_eq!(history.len(), 4);
+    assert_eq!((last.channel.as_str(), last.action.as_str()), ("stable", "promote"));
+}
+
+
+#[tokio::test]
+async fn registry_publish_stores_dependency_scan() {
+    cell_sdk::System::ignite_local_cluster().await.unwrap();
+
+    System::spawn("registry", None).await.expect("Failed to spawn");
+    let synapse = Synapse::grow_await("registry").await.expect("Failed to connect");
+    let mut r = Registry::Client::new(synapse);
+
+    r.trust(Registry::TrustKey {
+        author: "alice".into(),
+        public_key: vec![1, 2, 3, 4],
+    }).await.unwrap();
+
+    let lockfile = r#"
+version = 3
+
+[[package]]
+name = "scanned"
+version = "0.1.0"
+
+[[package]]
+name = "time"
+version = "0.1.43"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+    r.publish(Registry::PublishRequest {
+        package: Registry::Package {
+            name: "scanned".into(),
+            version: "0.1.0".into(),
+            description: "test".into(),
+            author: "alice".into(),
+            git_url: "https://github.com/alice/scanned".into(),
+            commit_hash: "abcdef".into(),
+            signature: vec![0u8; 64],
+        },
+        source_tarball: vec![],
+        signing_key: vec![1, 2, 3, 4],
+        channel: None,
+        lockfile: Some(lockfile.into()),
+    }).await.unwrap();
+
+    let report = r.scan_report("scanned".into(), "0.1.0".into()).await.unwrap().unwrap();
+    let deps: Vec<&str> = report.dependencies.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(deps, vec!["time"]);
+
+    let audited = r.audit("scanned".into(), vec![]).await.unwrap();
+    assert_eq!(audited.len(), 1);
+    assert_eq!(audited[0].version, "0.1.0");
+    assert!(r.scan_report("scanned".into(), "0.2.0".into()).await.unwrap().is_none());
+}
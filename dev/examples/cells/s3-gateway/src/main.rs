@@ -0,0 +1,653 @@
+// cells/s3-gateway/src/main.rs
+// SPDX-License-Identifier: MIT
+// S3-compatible facade over the blobstore
+//
+// Path-style requests only (`http://host/bucket/key`; SDKs need
+// `force_path_style`). Supported: ListBuckets, CreateBucket, HeadBucket,
+// DeleteBucket, ListObjects (v1 and v2), PutObject, GetObject, HeadObject,
+// DeleteObject, and SigV4 presigned URLs. Objects map to blobstore blobs: the
+// gateway holds one blob reference per object and releases it on overwrite
+// or delete, leaving the bytes to blobstore GC. No multipart, ranges, ACLs,
+// versioning or aws-chunked uploads.
+
+mod sigv4;
+
+use anyhow::Result;
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use cell_sdk::blob::CHUNK_SIZE;
+use cell_sdk::*;
+use futures::StreamExt;
+use md5::Md5;
+use percent_encoding::percent_decode_str;
+use sha2::{Digest, Sha256};
+use sigv4::{Credentials, Payload};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+cell_remote!(
+    Blobstore = "blobstore",
+    methods = [upload_chunk, commit, read_chunk, stat, release]
+);
+
+const DEFAULT_MAX_KEYS: usize = 1000;
+
+#[protein]
+pub struct ObjectInfo {
+    pub key: String,
+    /// Blobstore hash of the content
+    pub hash: String,
+    pub size: u64,
+    /// Hex MD5 of the content, as S3 reports for single-part uploads
+    pub etag: String,
+    pub content_type: String,
+    pub last_modified: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+struct Bucket {
+    created: i64,
+    objects: BTreeMap<String, ObjectInfo>,
+}
+
+/// An S3 error response
+struct S3Error {
+    code: &'static str,
+    message: String,
+}
+
+impl S3Error {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self.code {
+            "NoSuchBucket" | "NoSuchKey" => StatusCode::NOT_FOUND,
+            "AccessDenied"
+            | "SignatureDoesNotMatch"
+            | "InvalidAccessKeyId"
+            | "RequestTimeTooSkewed" => StatusCode::FORBIDDEN,
+            "BucketNotEmpty" | "BucketAlreadyOwnedByYou" => StatusCode::CONFLICT,
+            "MethodNotAllowed" => StatusCode::METHOD_NOT_ALLOWED,
+            "NotImplemented" => StatusCode::NOT_IMPLEMENTED,
+            "InternalError" => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl From<anyhow::Error> for S3Error {
+    fn from(e: anyhow::Error) -> Self {
+        S3Error::new("InternalError", e.to_string())
+    }
+}
+
+impl IntoResponse for S3Error {
+    fn into_response(self) -> Response {
+        let body = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<Error><Code>{}</Code><Message>{}</Message></Error>",
+            self.code,
+            xml_escape(&self.message)
+        );
+        (
+            self.status(),
+            [(header::CONTENT_TYPE, "application/xml")],
+            body,
+        )
+            .into_response()
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn iso8601(secs: i64) -> String {
+    chrono::DateTime::from_timestamp(secs, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%dT%H:%M:%S.000Z")
+        .to_string()
+}
+
+fn http_date(secs: i64) -> String {
+    chrono::DateTime::from_timestamp(secs, 0)
+        .unwrap_or_default()
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+/// S3 naming rules, loosely: 3-63 lowercase letters, digits, `-` and `.`
+fn valid_bucket_name(name: &str) -> bool {
+    (3..=63).contains(&name.len())
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'.')
+        && !name.starts_with(['-', '.'])
+        && !name.ends_with(['-', '.'])
+}
+
+#[service]
+#[derive(Clone)]
+struct S3Gateway {
+    buckets: Arc<RwLock<BTreeMap<String, Bucket>>>,
+    /// `None` accepts unsigned requests
+    credentials: Option<Credentials>,
+    /// Base URL presigned links point at
+    endpoint: String,
+    dir: PathBuf,
+}
+
+impl S3Gateway {
+    async fn open(
+        dir: PathBuf,
+        credentials: Option<Credentials>,
+        endpoint: String,
+    ) -> Result<Self> {
+        let buckets = match tokio::fs::read(dir.join("index.json")).await {
+            Ok(raw) => serde_json::from_slice(&raw)?,
+            Err(_) => BTreeMap::new(),
+        };
+        Ok(Self {
+            buckets: Arc::new(RwLock::new(buckets)),
+            credentials,
+            endpoint,
+            dir,
+        })
+    }
+
+    async fn persist(&self, buckets: &BTreeMap<String, Bucket>) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let path = self.dir.join("index.json");
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, serde_json::to_vec(buckets)?).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+
+    fn authorize(
+        &self,
+        method: &Method,
+        uri: &Uri,
+        headers: &HeaderMap,
+    ) -> Result<Payload, S3Error> {
+        match &self.credentials {
+            Some(credentials) => credentials
+                .verify(method, uri, headers)
+                .map_err(|code| S3Error::new(code, "Request signature rejected")),
+            None => Ok(Payload::Unsigned),
+        }
+    }
+
+    async fn list_buckets(&self) -> Result<Response, S3Error> {
+        let buckets = self.buckets.read().await;
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<ListAllMyBucketsResult><Owner><ID>cell</ID></Owner><Buckets>",
+        );
+        for (name, bucket) in buckets.iter() {
+            xml.push_str(&format!(
+                "<Bucket><Name>{}</Name><CreationDate>{}</CreationDate></Bucket>",
+                xml_escape(name),
+                iso8601(bucket.created)
+            ));
+        }
+        xml.push_str("</Buckets></ListAllMyBucketsResult>");
+        Ok(xml_response(xml))
+    }
+
+    async fn create_bucket(&self, name: &str) -> Result<Response, S3Error> {
+        if !valid_bucket_name(name) {
+            return Err(S3Error::new(
+                "InvalidBucketName",
+                format!("'{}' is not a valid bucket name", name),
+            ));
+        }
+        let mut buckets = self.buckets.write().await;
+        if buckets.contains_key(name) {
+            return Err(S3Error::new("BucketAlreadyOwnedByYou", name));
+        }
+        buckets.insert(
+            name.to_string(),
+            Bucket {
+                created: chrono::Utc::now().timestamp(),
+                objects: BTreeMap::new(),
+            },
+        );
+        self.persist(&buckets).await?;
+        Ok((StatusCode::OK, [(header::LOCATION, format!("/{}", name))]).into_response())
+    }
+
+    async fn delete_bucket(&self, name: &str) -> Result<Response, S3Error> {
+        let mut buckets = self.buckets.write().await;
+        match buckets.get(name) {
+            None => return Err(S3Error::new("NoSuchBucket", name)),
+            Some(bucket) if !bucket.objects.is_empty() => {
+                return Err(S3Error::new("BucketNotEmpty", name))
+            }
+            Some(_) => {}
+        }
+        buckets.remove(name);
+        self.persist(&buckets).await?;
+        Ok(StatusCode::NO_CONTENT.into_response())
+    }
+
+    async fn list_objects(
+        &self,
+        name: &str,
+        query: &HashMap<String, String>,
+    ) -> Result<Response, S3Error> {
+        let buckets = self.buckets.read().await;
+        let bucket = buckets
+            .get(name)
+            .ok_or_else(|| S3Error::new("NoSuchBucket", name))?;
+
+        let v2 = query.get("list-type").map(String::as_str) == Some("2");
+        let prefix = query.get("prefix").cloned().unwrap_or_default();
+        let delimiter = query.get("delimiter").filter(|d| !d.is_empty());
+        let max_keys = query
+            .get("max-keys")
+            .and_then(|m| m.parse().ok())
+            .unwrap_or(DEFAULT_MAX_KEYS)
+            .min(DEFAULT_MAX_KEYS);
+        // v2 pages with an opaque token (here: the last key), v1 with a marker
+        let after = match v2 {
+            true => query.get("continuation-token").or(query.get("start-after")),
+            false => query.get("marker"),
+        }
+        .cloned()
+        .unwrap_or_default();
+
+        let mut contents = Vec::new();
+        let mut prefixes: Vec<String> = Vec::new();
+        let mut last = None;
+        let mut truncated = false;
+        let candidates = bucket
+            .objects
+            .range::<String, _>((
+                std::ops::Bound::Excluded(&after),
+                std::ops::Bound::Unbounded,
+            ))
+            .filter(|(key, _)| key.starts_with(&prefix));
+        for (key, object) in candidates {
+            if contents.len() + prefixes.len() >= max_keys {
+                truncated = true;
+                break;
+            }
+            // Keys below a delimiter after the prefix roll up into one common prefix
+            let rolled_up = delimiter.and_then(|d| {
+                key[prefix.len()..]
+                    .find(d.as_str())
+                    .map(|i| key[..prefix.len() + i + d.len()].to_string())
+            });
+            match rolled_up {
+                Some(common) if prefixes.last() == Some(&common) => {}
+                Some(common) => prefixes.push(common),
+                None => contents.push(object),
+            }
+            last = Some(key.clone());
+        }
+
+        let mut xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<ListBucketResult><Name>{}</Name><Prefix>{}</Prefix><MaxKeys>{}</MaxKeys><IsTruncated>{}</IsTruncated>",
+            xml_escape(name),
+            xml_escape(&prefix),
+            max_keys,
+            truncated
+        );
+        if let Some(d) = delimiter {
+            xml.push_str(&format!("<Delimiter>{}</Delimiter>", xml_escape(d)));
+        }
+        if v2 {
+            xml.push_str(&format!(
+                "<KeyCount>{}</KeyCount>",
+                contents.len() + prefixes.len()
+            ));
+            if let (true, Some(last)) = (truncated, &last) {
+                xml.push_str(&format!(
+                    "<NextContinuationToken>{}</NextContinuationToken>",
+                    xml_escape(last)
+                ));
+            }
+        } else if let (true, Some(last)) = (truncated, &last) {
+            xml.push_str(&format!("<NextMarker>{}</NextMarker>", xml_escape(last)));
+        }
+        for object in contents {
+            xml.push_str(&format!(
+                "<Contents><Key>{}</Key><LastModified>{}</LastModified><ETag>&quot;{}&quot;</ETag><Size>{}</Size><StorageClass>STANDARD</StorageClass></Contents>",
+                xml_escape(&object.key),
+                iso8601(object.last_modified),
+                object.etag,
+                object.size
+            ));
+        }
+        for common in prefixes {
+            xml.push_str(&format!(
+                "<CommonPrefixes><Prefix>{}</Prefix></CommonPrefixes>",
+                xml_escape(&common)
+            ));
+        }
+        xml.push_str("</ListBucketResult>");
+        Ok(xml_response(xml))
+    }
+
+    async fn put_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        headers: &HeaderMap,
+        payload: Payload,
+        body: Body,
+    ) -> Result<Response, S3Error> {
+        if !self.buckets.read().await.contains_key(bucket) {
+            return Err(S3Error::new("NoSuchBucket", bucket));
+        }
+        let blobstore = Blobstore::Client::connect().await?;
+
+        // Stream the body into blobstore chunks, hashing as we go
+        let mut sha = Sha256::new();
+        let mut md5 = Md5::new();
+        let mut size = 0u64;
+        let mut pending = Vec::with_capacity(CHUNK_SIZE);
+        let mut chunks = Vec::new();
+        let mut stream = body.into_data_stream();
+        while let Some(bytes) = stream.next().await {
+            let bytes = bytes.map_err(|e| S3Error::new("IncompleteBody", e.to_string()))?;
+            sha.update(&bytes);
+            md5.update(&bytes);
+            size += bytes.len() as u64;
+            pending.extend_from_slice(&bytes);
+            while pending.len() >= CHUNK_SIZE {
+                let rest = pending.split_off(CHUNK_SIZE);
+                chunks.push(
+                    blobstore
+                        .upload_chunk(std::mem::replace(&mut pending, rest))
+                        .await?,
+                );
+            }
+        }
+        if !pending.is_empty() {
+            chunks.push(blobstore.upload_chunk(pending).await?);
+        }
+
+        // Uncommitted chunks are collected by blobstore GC
+        if let Payload::Sha256(expected) = payload {
+            if hex::encode(sha.finalize()) != expected {
+                return Err(S3Error::new(
+                    "XAmzContentSHA256Mismatch",
+                    "Body does not match x-amz-content-sha256",
+                ));
+            }
+        }
+        let blob = blobstore.commit(chunks).await?;
+
+        let object = ObjectInfo {
+            key: key.to_string(),
+            hash: blob.hash,
+            size,
+            etag: hex::encode(md5.finalize()),
+            content_type: headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("binary/octet-stream")
+                .to_string(),
+            last_modified: chrono::Utc::now().timestamp(),
+        };
+        let etag = object.etag.clone();
+
+        let replaced = {
+            let mut buckets = self.buckets.write().await;
+            let Some(objects) = buckets.get_mut(bucket).map(|b| &mut b.objects) else {
+                blobstore.release(object.hash).await?;
+                return Err(S3Error::new("NoSuchBucket", bucket));
+            };
+            let replaced = objects.insert(key.to_string(), object);
+            self.persist(&buckets).await?;
+            replaced
+        };
+        if let Some(old) = replaced {
+            blobstore.release(old.hash).await?;
+        }
+        Ok((StatusCode::OK, [(header::ETAG, format!("\"{}\"", etag))]).into_response())
+    }
+
+    async fn object(&self, bucket: &str, key: &str) -> Result<ObjectInfo, S3Error> {
+        let buckets = self.buckets.read().await;
+        let bucket_ref = buckets
+            .get(bucket)
+            .ok_or_else(|| S3Error::new("NoSuchBucket", bucket))?;
+        bucket_ref
+            .objects
+            .get(key)
+            .cloned()
+            .ok_or_else(|| S3Error::new("NoSuchKey", key))
+    }
+
+    async fn get_object(&self, bucket: &str, key: &str, head: bool) -> Result<Response, S3Error> {
+        let object = self.object(bucket, key).await?;
+        let mut response = Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, &object.content_type)
+            .header(header::CONTENT_LENGTH, object.size)
+            .header(header::ETAG, format!("\"{}\"", object.etag))
+            .header(header::LAST_MODIFIED, http_date(object.last_modified));
+        if head {
+            return Ok(response.body(Body::empty()).map_err(anyhow::Error::from)?);
+        }
+
+        let blobstore = Blobstore::Client::connect().await?;
+        let blob = blobstore.stat(object.hash.clone()).await?.ok_or_else(|| {
+            S3Error::new("InternalError", format!("Blob {} is missing", object.hash))
+        })?;
+        let hash = object.hash;
+        let body = futures::stream::iter(0..blob.chunks).then(move |i| {
+            let blobstore = blobstore.clone();
+            let hash = hash.clone();
+            async move {
+                blobstore
+                    .read_chunk(hash, i)
+                    .await
+                    .map_err(std::io::Error::other)
+            }
+        });
+        response = response.header(header::ACCEPT_RANGES, HeaderValue::from_static("none"));
+        Ok(response
+            .body(Body::from_stream(body))
+            .map_err(anyhow::Error::from)?)
+    }
+
+    async fn delete_object(&self, bucket: &str, key: &str) -> Result<Response, S3Error> {
+        let removed = {
+            let mut buckets = self.buckets.write().await;
+            let bucket_ref = buckets
+                .get_mut(bucket)
+                .ok_or_else(|| S3Error::new("NoSuchBucket", bucket))?;
+            let removed = bucket_ref.objects.remove(key);
+            self.persist(&buckets).await?;
+            removed
+        };
+        // Deleting a missing key succeeds in S3 too
+        if let Some(object) = removed {
+            Blobstore::Client::connect()
+                .await?
+                .release(object.hash)
+                .await?;
+        }
+        Ok(StatusCode::NO_CONTENT.into_response())
+    }
+}
+
+fn xml_response(xml: String) -> Response {
+    ([(header::CONTENT_TYPE, "application/xml")], xml).into_response()
+}
+
+async fn handle(
+    State(gateway): State<S3Gateway>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Body,
+) -> Response {
+    match route(&gateway, method, uri, headers, body).await {
+        Ok(response) => response,
+        Err(e) => {
+            if e.status().is_server_error() {
+                tracing::warn!("[S3] {}: {}", e.code, e.message);
+            }
+            e.into_response()
+        }
+    }
+}
+
+async fn route(
+    gateway: &S3Gateway,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Response, S3Error> {
+    let payload = gateway.authorize(&method, &uri, &headers)?;
+    let query: HashMap<String, String> = uri
+        .query()
+        .unwrap_or("")
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let (k, v) = p.split_once('=').unwrap_or((p, ""));
+            let decode = |s: &str| percent_decode_str(s).decode_utf8_lossy().into_owned();
+            (decode(k), decode(v))
+        })
+        .collect();
+
+    let path = uri.path().trim_start_matches('/');
+    let (bucket, key) = match path.split_once('/') {
+        Some((bucket, key)) if !key.is_empty() => (bucket, Some(key)),
+        Some((bucket, _)) => (bucket, None),
+        None => (path, None),
+    };
+    let key = key.map(|k| percent_decode_str(k).decode_utf8_lossy().into_owned());
+
+    match (method, bucket, key) {
+        (Method::GET, "", None) => gateway.list_buckets().await,
+        (Method::PUT, bucket, None) => gateway.create_bucket(bucket).await,
+        (Method::HEAD, bucket, None) => {
+            gateway
+                .buckets
+                .read()
+                .await
+                .get(bucket)
+                .ok_or_else(|| S3Error::new("NoSuchBucket", bucket))?;
+            Ok(StatusCode::OK.into_response())
+        }
+        (Method::DELETE, bucket, None) => gateway.delete_bucket(bucket).await,
+        (Method::GET, bucket, None) => gateway.list_objects(bucket, &query).await,
+        (Method::PUT, bucket, Some(key)) => {
+            if headers.contains_key("x-amz-copy-source") {
+                return Err(S3Error::new(
+                    "NotImplemented",
+                    "CopyObject is not supported",
+                ));
+            }
+            gateway
+                .put_object(bucket, &key, &headers, payload, body)
+                .await
+        }
+        (Method::GET, bucket, Some(key)) => gateway.get_object(bucket, &key, false).await,
+        (Method::HEAD, bucket, Some(key)) => gateway.get_object(bucket, &key, true).await,
+        (Method::DELETE, bucket, Some(key)) => gateway.delete_object(bucket, &key).await,
+        (method, _, _) => Err(S3Error::new(
+            "MethodNotAllowed",
+            format!("{} is not supported", method),
+        )),
+    }
+}
+
+#[handler]
+impl S3Gateway {
+    /// A time-limited URL for GET or PUT of one object
+    async fn presign(
+        &self,
+        bucket: String,
+        key: String,
+        method: String,
+        expires_secs: u64,
+    ) -> Result<String> {
+        let Some(credentials) = &self.credentials else {
+            anyhow::bail!("Presigned URLs need CELL_S3_ACCESS_KEY and CELL_S3_SECRET_KEY");
+        };
+        let method = method.to_uppercase();
+        if !matches!(method.as_str(), "GET" | "PUT" | "HEAD" | "DELETE") {
+            anyhow::bail!("Cannot presign {}", method);
+        }
+        let encoded: Vec<String> = key
+            .split('/')
+            .map(|segment| {
+                percent_encoding::utf8_percent_encode(segment, percent_encoding::NON_ALPHANUMERIC)
+                    .to_string()
+            })
+            .collect();
+        let path = format!("/{}/{}", bucket, encoded.join("/"));
+        Ok(credentials.presign(&method, &self.endpoint, &path, expires_secs))
+    }
+
+    async fn list(&self, bucket: String, prefix: String) -> Result<Vec<ObjectInfo>> {
+        let buckets = self.buckets.read().await;
+        let bucket_ref = buckets
+            .get(&bucket)
+            .ok_or_else(|| anyhow::anyhow!("No bucket '{}'", bucket))?;
+        Ok(bucket_ref
+            .objects
+            .values()
+            .filter(|o| o.key.starts_with(&prefix))
+            .cloned()
+            .collect())
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt().init();
+
+    let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+    let listen = var("CELL_S3_LISTEN").unwrap_or_else(|| "127.0.0.1:9000".into());
+    let credentials = match (var("CELL_S3_ACCESS_KEY"), var("CELL_S3_SECRET_KEY")) {
+        (Some(access_key), Some(secret_key)) => Some(Credentials {
+            access_key,
+            secret_key,
+            region: var("CELL_S3_REGION").unwrap_or_else(|| "us-east-1".into()),
+        }),
+        _ => {
+            tracing::warn!(
+                "[S3] No CELL_S3_ACCESS_KEY/CELL_S3_SECRET_KEY: accepting unsigned requests"
+            );
+            None
+        }
+    };
+    let endpoint = var("CELL_S3_ENDPOINT").unwrap_or_else(|| format!("http://{}", listen));
+    let dir = var("CELL_S3_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".cell/s3"));
+    let gateway = S3Gateway::open(dir, credentials, endpoint).await?;
+
+    let app = Router::new().fallback(handle).with_state(gateway.clone());
+    let listener = tokio::net::TcpListener::bind(&listen).await?;
+    tracing::info!("[S3] Serving the blobstore on http://{}", listen);
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            tracing::error!("[S3] HTTP server stopped: {}", e);
+        }
+    });
+
+    gateway.serve("s3-gateway").await
+}
@@ -0,0 +1,261 @@
+// cells/s3-gateway/src/sigv4.rs
+// SPDX-License-Identifier: MIT
+// AWS Signature Version 4, for both `Authorization` headers and presigned
+// URLs. The gateway has a single credential pair; there is no IAM.
+
+use axum::http::{HeaderMap, Method, Uri};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use hmac::{Hmac, Mac};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use sha2::{Digest, Sha256};
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+pub const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+/// Longest validity AWS accepts for a presigned URL
+pub const MAX_EXPIRES: u64 = 7 * 24 * 3600;
+/// Allowed clock skew for header-signed requests
+const MAX_SKEW_SECS: i64 = 15 * 60;
+
+/// Everything but the unreserved characters, as SigV4 encodes
+const AWS_ENCODE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+#[derive(Clone)]
+pub struct Credentials {
+    pub access_key: String,
+    pub secret_key: String,
+    pub region: String,
+}
+
+/// What a verified request still has to prove about its body
+#[derive(Debug, PartialEq)]
+pub enum Payload {
+    Unsigned,
+    /// Hex SHA-256 the body must match
+    Sha256(String),
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn encode(s: &str) -> String {
+    utf8_percent_encode(s, AWS_ENCODE).to_string()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn parse_time(amz_date: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(amz_date, "%Y%m%dT%H%M%SZ")
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+/// Query parameters, decoded, minus `skip`, sorted and re-encoded.
+fn canonical_query(query: &str, skip: Option<&str>) -> String {
+    let mut pairs: Vec<(String, String)> = query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let (k, v) = p.split_once('=').unwrap_or((p, ""));
+            let decode = |s: &str| percent_decode_str(s).decode_utf8_lossy().into_owned();
+            (decode(k), decode(v))
+        })
+        .filter(|(k, _)| Some(k.as_str()) != skip)
+        .map(|(k, v)| (encode(&k), encode(&v)))
+        .collect();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn canonical_headers(headers: &HeaderMap, signed: &[&str]) -> Option<String> {
+    let mut out = String::new();
+    for name in signed {
+        let value = headers.get(*name)?.to_str().ok()?;
+        let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+        out.push_str(&format!("{}:{}\n", name, value));
+    }
+    Some(out)
+}
+
+impl Credentials {
+    fn signing_key(&self, date: &str) -> Vec<u8> {
+        let k_date = hmac(format!("AWS4{}", self.secret_key).as_bytes(), date);
+        let k_region = hmac(&k_date, &self.region);
+        let k_service = hmac(&k_region, "s3");
+        hmac(&k_service, "aws4_request")
+    }
+
+    fn scope(&self, date: &str) -> String {
+        format!("{}/{}/s3/aws4_request", date, self.region)
+    }
+
+    fn signature(&self, amz_date: &str, canonical_request: &str) -> String {
+        let date = &amz_date[..8.min(amz_date.len())];
+        let string_to_sign = format!(
+            "{}\n{}\n{}\n{}",
+            ALGORITHM,
+            amz_date,
+            self.scope(date),
+            sha256_hex(canonical_request.as_bytes())
+        );
+        hex::encode(hmac(&self.signing_key(date), &string_to_sign))
+    }
+
+    /// Check a request signed either way. Errors name the S3 error code.
+    pub fn verify(
+        &self,
+        method: &Method,
+        uri: &Uri,
+        headers: &HeaderMap,
+    ) -> Result<Payload, &'static str> {
+        let query = uri.query().unwrap_or("");
+        let presigned = query.contains("X-Amz-Signature=");
+
+        let param = |name: &str| {
+            query.split('&').find_map(|p| {
+                let (k, v) = p.split_once('=')?;
+                (k == name).then(|| percent_decode_str(v).decode_utf8_lossy().into_owned())
+            })
+        };
+        let (credential, signed_headers, signature, amz_date, payload) = if presigned {
+            if param("X-Amz-Algorithm").as_deref() != Some(ALGORITHM) {
+                return Err("AuthorizationQueryParametersError");
+            }
+            let amz_date = param("X-Amz-Date").ok_or("AuthorizationQueryParametersError")?;
+            let expires: u64 = param("X-Amz-Expires")
+                .and_then(|e| e.parse().ok())
+                .filter(|e| *e <= MAX_EXPIRES)
+                .ok_or("AuthorizationQueryParametersError")?;
+            let signed_at = parse_time(&amz_date).ok_or("AuthorizationQueryParametersError")?;
+            if Utc::now() > signed_at + chrono::Duration::seconds(expires as i64) {
+                return Err("AccessDenied");
+            }
+            (
+                param("X-Amz-Credential").ok_or("AuthorizationQueryParametersError")?,
+                param("X-Amz-SignedHeaders").ok_or("AuthorizationQueryParametersError")?,
+                param("X-Amz-Signature").ok_or("AuthorizationQueryParametersError")?,
+                amz_date,
+                UNSIGNED_PAYLOAD.to_string(),
+            )
+        } else {
+            let auth = headers
+                .get("authorization")
+                .and_then(|v| v.to_str().ok())
+                .ok_or("AccessDenied")?;
+            let fields = auth
+                .strip_prefix(ALGORITHM)
+                .ok_or("AuthorizationHeaderMalformed")?;
+            let field = |name: &str| {
+                fields.split(',').find_map(|f| {
+                    f.trim()
+                        .strip_prefix(name)
+                        .and_then(|f| f.strip_prefix('='))
+                        .map(str::to_string)
+                })
+            };
+            let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+            let amz_date = header("x-amz-date").ok_or("AuthorizationHeaderMalformed")?;
+            let signed_at = parse_time(amz_date).ok_or("AuthorizationHeaderMalformed")?;
+            if (Utc::now() - signed_at).num_seconds().abs() > MAX_SKEW_SECS {
+                return Err("RequestTimeTooSkewed");
+            }
+            (
+                field("Credential").ok_or("AuthorizationHeaderMalformed")?,
+                field("SignedHeaders").ok_or("AuthorizationHeaderMalformed")?,
+                field("Signature").ok_or("AuthorizationHeaderMalformed")?,
+                amz_date.to_string(),
+                header("x-amz-content-sha256")
+                    .ok_or("AuthorizationHeaderMalformed")?
+                    .to_string(),
+            )
+        };
+
+        // AKID/date/region/s3/aws4_request
+        let (access_key, scope) = credential
+            .split_once('/')
+            .ok_or("AuthorizationHeaderMalformed")?;
+        if access_key != self.access_key {
+            return Err("InvalidAccessKeyId");
+        }
+        if scope != self.scope(&amz_date[..8.min(amz_date.len())]) {
+            return Err("AuthorizationHeaderMalformed");
+        }
+        if payload.starts_with("STREAMING-") {
+            // aws-chunked uploads sign every chunk separately
+            return Err("NotImplemented");
+        }
+
+        let signed: Vec<&str> = signed_headers.split(';').collect();
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method.as_str(),
+            uri.path(),
+            canonical_query(query, presigned.then_some("X-Amz-Signature")),
+            canonical_headers(headers, &signed).ok_or("AccessDenied")?,
+            signed_headers,
+            payload
+        );
+        let expected = self.signature(&amz_date, &canonical_request);
+
+        if !constant_time_eq(expected.as_bytes(), signature.as_bytes()) {
+            return Err("SignatureDoesNotMatch");
+        }
+
+        Ok(match payload.as_str() {
+            UNSIGNED_PAYLOAD => Payload::Unsigned,
+            hash => Payload::Sha256(hash.to_lowercase()),
+        })
+    }
+
+    /// A URL for `method` on `path` (`/bucket/key`, already encoded) that is
+    /// valid for `expires` seconds without further credentials.
+    pub fn presign(&self, method: &str, endpoint: &str, path: &str, expires: u64) -> String {
+        let host = endpoint
+            .split_once("://")
+            .map_or(endpoint, |(_, rest)| rest)
+            .trim_end_matches('/');
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+
+        let query = format!(
+            "X-Amz-Algorithm={}&X-Amz-Credential={}&X-Amz-Date={}&X-Amz-Expires={}&X-Amz-SignedHeaders=host",
+            ALGORITHM,
+            encode(&format!("{}/{}", self.access_key, self.scope(&date))),
+            amz_date,
+            expires.min(MAX_EXPIRES)
+        );
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\n\nhost\n{}",
+            method,
+            path,
+            canonical_query(&query, None),
+            host,
+            UNSIGNED_PAYLOAD
+        );
+        let signature = self.signature(&amz_date, &canonical_request);
+        format!(
+            "{}{}?{}&X-Amz-Signature={}",
+            endpoint.trim_end_matches('/'),
+            path,
+            query,
+            signature
+        )
+    }
+}
@@ -0,0 +1,48 @@
+use cell_sdk::*;
+
+cell_remote!(S3Gateway = "s3-gateway");
+
+// Without CELL_S3_ACCESS_KEY the gateway accepts unsigned requests on 127.0.0.1:9000
+#[tokio::test]
+async fn objects_round_trip_through_blobstore() {
+    cell_sdk::System::ignite_local_cluster().await.unwrap();
+
+    System::spawn("blobstore", None).await.expect("Failed to spawn blobstore");
+    System::spawn("s3-gateway", None).await.expect("Failed to spawn");
+    let synapse = Synapse::grow_await("s3-gateway").await.expect("Failed to connect");
+    let mut gateway = S3Gateway::Client::new(synapse);
+
+    let http = reqwest::Client::new();
+    let base = "http://127.0.0.1:9000";
+    assert!(http.put(format!("{}/artifacts", base)).send().await.unwrap().status().is_success());
+
+    let put = http
+        .put(format!("{}/artifacts/builds/app.tar", base))
+        .body("payload")
+        .send()
+        .await
+        .unwrap();
+    assert!(put.status().is_success());
+    // MD5 of "payload"
+    assert_eq!(put.headers()["etag"], "\"321c3cf486ed509164edec1e1981fec8\"");
+
+    let get = http.get(format!("{}/artifacts/builds/app.tar", base)).send().await.unwrap();
+    assert_eq!(get.text().await.unwrap(), "payload");
+
+    let listing = http
+        .get(format!("{}/artifacts?list-type=2&prefix=builds/&delimiter=/", base))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(listing.contains("<Key>builds/app.tar</Key>"));
+
+    let objects = gateway.list("artifacts".into(), "builds/".into()).await.unwrap();
+    assert_eq!(objects.len(), 1);
+    assert_eq!(objects[0].size, 7);
+
+    let missing = http.get(format!("{}/artifacts/nope", base)).send().await.unwrap();
+    assert_eq!(missing.status(), 404);
+}
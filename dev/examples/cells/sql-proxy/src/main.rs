@@ -0,0 +1,432 @@
+// cells/sql-proxy/src/main.rs
+// SPDX-License-Identifier: MIT
+// Postgres-compatible SQL access to mesh data
+//
+// Tables and the handlers behind them:
+//
+//   state     (key, value, version, timestamp)     state-manager fetch / store
+//   balances  (account, asset, balance)            ledger balance
+//   entries   (id, reference, description,         ledger audit / record
+//              account, asset, amount, timestamp)
+//
+// Backing cells answer point lookups only, so a SELECT must pin the lookup
+// columns (`state.key`, `balances.account` and `asset`, `entries.id`) with
+// `=`; any further conditions filter the rows. INSERT into `entries` records
+// one transaction per distinct `reference`.
+
+mod pgwire;
+mod sql;
+
+use anyhow::Result;
+use cell_sdk::*;
+use pgwire::{QueryResult, SqlError};
+use sql::{Literal, Statement};
+use std::collections::BTreeMap;
+
+cell_remote!(StateManager = "state-manager", methods = [store, fetch]);
+cell_remote!(Ledger = "ledger-v2");
+
+const STATE: &[&str] = &["key", "value", "version", "timestamp"];
+const BALANCES: &[&str] = &["account", "asset", "balance"];
+const ENTRIES: &[&str] = &[
+    "id",
+    "reference",
+    "description",
+    "account",
+    "asset",
+    "amount",
+    "timestamp",
+];
+
+#[protein]
+pub struct ResultSet {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Option<String>>>,
+    pub tag: String,
+}
+
+fn sql_error(code: &'static str, message: impl Into<String>) -> SqlError {
+    SqlError {
+        code,
+        message: message.into(),
+    }
+}
+
+fn table_columns(table: &str) -> Result<&'static [&'static str], SqlError> {
+    match table {
+        "state" => Ok(STATE),
+        "balances" => Ok(BALANCES),
+        "entries" => Ok(ENTRIES),
+        // 42P01: undefined_table
+        _ => Err(sql_error(
+            "42P01",
+            format!("relation \"{}\" does not exist", table),
+        )),
+    }
+}
+
+fn column_index(columns: &[&str], table: &str, column: &str) -> Result<usize, SqlError> {
+    columns.iter().position(|c| *c == column).ok_or_else(|| {
+        // 42703: undefined_column
+        sql_error(
+            "42703",
+            format!("column \"{}\" of \"{}\" does not exist", column, table),
+        )
+    })
+}
+
+/// Row values by column name, with NULL for columns not given.
+fn named_rows(
+    table: &str,
+    columns: Option<Vec<String>>,
+    rows: Vec<Vec<Literal>>,
+) -> Result<Vec<BTreeMap<String, Option<String>>>, SqlError> {
+    let all = table_columns(table)?;
+    let names = columns.unwrap_or_else(|| all.iter().map(|c| c.to_string()).collect());
+    for name in &names {
+        column_index(all, table, name)?;
+    }
+    rows.into_iter()
+        .map(|row| {
+            if row.len() != names.len() {
+                // 42601: syntax_error
+                return Err(sql_error(
+                    "42601",
+                    "VALUES lists must match the column list",
+                ));
+            }
+            Ok(names
+                .iter()
+                .cloned()
+                .zip(row.iter().map(Literal::text))
+                .collect())
+        })
+        .collect()
+}
+
+fn required(
+    row: &BTreeMap<String, Option<String>>,
+    table: &str,
+    column: &str,
+) -> Result<String, SqlError> {
+    row.get(column).cloned().flatten().ok_or_else(|| {
+        // 23502: not_null_violation
+        sql_error("23502", format!("\"{}\".\"{}\" is required", table, column))
+    })
+}
+
+fn parse_int<T: std::str::FromStr>(value: &str, column: &str) -> Result<T, SqlError> {
+    value.parse().map_err(|_| {
+        // 22P02: invalid_text_representation
+        sql_error(
+            "22P02",
+            format!("invalid integer for \"{}\": \"{}\"", column, value),
+        )
+    })
+}
+
+#[service]
+#[derive(Clone)]
+struct SqlProxy {
+    state_cell: String,
+    ledger_cell: String,
+}
+
+impl SqlProxy {
+    fn from_env() -> Self {
+        let var =
+            |name: &str, default: &str| std::env::var(name).unwrap_or_else(|_| default.into());
+        Self {
+            state_cell: var("CELL_SQL_STATE_CELL", "state-manager"),
+            ledger_cell: var("CELL_SQL_LEDGER_CELL", "ledger-v2"),
+        }
+    }
+
+    async fn state(&self) -> Result<StateManager::Client> {
+        Ok(StateManager::Client::new(
+            ResilientSynapse::grow(&self.state_cell).await?,
+        ))
+    }
+
+    async fn ledger(&self) -> Result<Ledger::Client> {
+        Ok(Ledger::Client::new(
+            ResilientSynapse::grow(&self.ledger_cell).await?,
+        ))
+    }
+
+    /// Run every statement of a simple query, stopping at the first error.
+    async fn run(&self, sql: &str) -> Vec<Result<QueryResult, SqlError>> {
+        let statements = match sql::parse(sql) {
+            Ok(statements) => statements,
+            Err(e) => return vec![Err(sql_error("42601", e.to_string()))],
+        };
+        let mut results = Vec::new();
+        for statement in statements {
+            let result = self.execute(statement).await;
+            let failed = result.is_err();
+            results.push(result);
+            if failed {
+                break;
+            }
+        }
+        results
+    }
+
+    async fn execute(&self, statement: Statement) -> Result<QueryResult, SqlError> {
+        match statement {
+            Statement::Select {
+                columns,
+                table,
+                filters,
+                limit,
+            } => self.select(columns, &table, filters, limit).await,
+            Statement::Insert {
+                table,
+                columns,
+                rows,
+            } => {
+                self.insert(&table, named_rows(&table, columns, rows)?)
+                    .await
+            }
+            Statement::Noop(tag) => Ok(QueryResult {
+                columns: Vec::new(),
+                rows: Vec::new(),
+                tag,
+            }),
+        }
+    }
+
+    async fn select(
+        &self,
+        columns: Option<Vec<String>>,
+        table: &str,
+        filters: Vec<(String, Literal)>,
+        limit: Option<u64>,
+    ) -> Result<QueryResult, SqlError> {
+        let all = table_columns(table)?;
+        let mut conditions = BTreeMap::new();
+        for (column, value) in &filters {
+            column_index(all, table, column)?;
+            conditions.insert(column.clone(), value.text());
+        }
+        let lookup = |column: &str| {
+            required(&conditions, table, column).map_err(|_| {
+                // 0A000: feature_not_supported
+                sql_error(
+                    "0A000",
+                    format!(
+                        "SELECT from \"{}\" needs a WHERE {} = ... condition",
+                        table, column
+                    ),
+                )
+            })
+        };
+
+        let mut rows: Vec<Vec<Option<String>>> = match table {
+            "state" => {
+                let entry = self
+                    .state()
+                    .await?
+                    .fetch(StateManager::FetchRequest {
+                        key: lookup("key")?,
+                        max_staleness_ms: None,
+                    })
+                    .await?;
+                entry
+                    .map(|e| {
+                        vec![
+                            Some(e.key),
+                            Some(String::from_utf8_lossy(&e.value).into_owned()),
+                            Some(e.version.to_string()),
+                            Some(e.timestamp.to_string()),
+                        ]
+                    })
+                    .into_iter()
+                    .collect()
+            }
+            "balances" => {
+                let (account, asset) = (lookup("account")?, lookup("asset")?);
+                let balance = self
+                    .ledger()
+                    .await?
+                    .balance(Ledger::BalanceQuery {
+                        account: account.clone(),
+                        asset: asset.clone(),
+                    })
+                    .await?;
+                vec![vec![Some(account), Some(asset), Some(balance.to_string())]]
+            }
+            _ => {
+                let id: u64 = parse_int(&lookup("id")?, "id")?;
+                let entry = self.ledger().await?.audit(id).await?;
+                entry
+                    .tx
+                    .postings
+                    .iter()
+                    .map(|p| {
+                        vec![
+                            Some(entry.id.to_string()),
+                            Some(entry.tx.reference.clone()),
+                            Some(entry.tx.description.clone()),
+                            Some(p.account.clone()),
+                            Some(p.asset.clone()),
+                            Some(p.amount.to_string()),
+                            Some(entry.timestamp.to_string()),
+                        ]
+                    })
+                    .collect()
+            }
+        };
+
+        // Lookup columns already match; the rest of WHERE narrows the rows
+        rows.retain(|row| {
+            conditions
+                .iter()
+                .all(|(column, value)| row[all.iter().position(|c| c == column).unwrap()] == *value)
+        });
+        if let Some(limit) = limit {
+            rows.truncate(limit as usize);
+        }
+
+        let selected: Vec<String> = match columns {
+            Some(columns) => columns,
+            None => all.iter().map(|c| c.to_string()).collect(),
+        };
+        let indexes = selected
+            .iter()
+            .map(|c| column_index(all, table, c))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(QueryResult {
+            tag: format!("SELECT {}", rows.len()),
+            rows: rows
+                .into_iter()
+                .map(|row| indexes.iter().map(|&i| row[i].clone()).collect())
+                .collect(),
+            columns: selected,
+        })
+    }
+
+    async fn insert(
+        &self,
+        table: &str,
+        rows: Vec<BTreeMap<String, Option<String>>>,
+    ) -> Result<QueryResult, SqlError> {
+        let count = rows.len();
+        match table {
+            "state" => {
+                let state = self.state().await?;
+                for row in &rows {
+                    state
+                        .store(StateManager::StoreRequest {
+                            key: required(row, table, "key")?,
+                            value: required(row, table, "value")?.into_bytes(),
+                            ttl_secs: None,
+                            domain: None,
+                        })
+                        .await?;
+                }
+            }
+            "entries" => {
+                // Rows sharing a reference are the postings of one transaction
+                let mut transactions: BTreeMap<String, Ledger::Transaction> = BTreeMap::new();
+                for row in &rows {
+                    let reference = required(row, table, "reference")?;
+                    let posting = Ledger::Posting {
+                        account: required(row, table, "account")?,
+                        asset: required(row, table, "asset")?,
+                        amount: parse_int(&required(row, table, "amount")?, "amount")?,
+                    };
+                    transactions
+                        .entry(reference.clone())
+                        .or_insert_with(|| Ledger::Transaction {
+                            reference,
+                            description: row
+                                .get("description")
+                                .cloned()
+                                .flatten()
+                                .unwrap_or_default(),
+                            postings: Vec::new(),
+                        })
+                        .postings
+                        .push(posting);
+                }
+                let ledger = self.ledger().await?;
+                for tx in transactions.into_values() {
+                    ledger.record(tx).await?;
+                }
+            }
+            _ => {
+                table_columns(table)?;
+                return Err(sql_error("0A000", format!("\"{}\" is read-only", table)));
+            }
+        }
+        Ok(QueryResult {
+            columns: Vec::new(),
+            rows: Vec::new(),
+            tag: format!("INSERT 0 {}", count),
+        })
+    }
+}
+
+#[handler]
+impl SqlProxy {
+    /// The same queries for mesh cells, without a Postgres client
+    async fn query(&self, sql: String) -> Result<Vec<ResultSet>> {
+        self.run(&sql)
+            .await
+            .into_iter()
+            .map(|result| match result {
+                Ok(r) => Ok(ResultSet {
+                    columns: r.columns,
+                    rows: r.rows,
+                    tag: r.tag,
+                }),
+                Err(e) => Err(anyhow::anyhow!("{} ({})", e.message, e.code)),
+            })
+            .collect()
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt().init();
+    let service = SqlProxy::from_env();
+
+    // Cleartext password auth when CELL_SQL_PASSWORD is set, so keep the
+    // default listener on loopback
+    let addr = std::env::var("CELL_SQL_LISTEN").unwrap_or_else(|_| "127.0.0.1:5433".into());
+    let password = std::env::var("CELL_SQL_PASSWORD").ok();
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    tracing::info!(
+        "[SqlProxy] Postgres protocol on {} (state: {}, ledger: {})",
+        addr,
+        service.state_cell,
+        service.ledger_cell
+    );
+
+    let proxy = service.clone();
+    tokio::spawn(async move {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    tracing::warn!("[SqlProxy] Accept failed: {}", e);
+                    continue;
+                }
+            };
+            let proxy = proxy.clone();
+            let password = password.clone();
+            tokio::spawn(async move {
+                let session = pgwire::serve(stream, password.as_deref(), |sql| {
+                    let proxy = proxy.clone();
+                    async move { proxy.run(&sql).await }
+                });
+                if let Err(e) = session.await {
+                    tracing::debug!("[SqlProxy] Session from {} ended: {}", peer, e);
+                }
+            });
+        }
+    });
+
+    service.serve("sql-proxy").await
+}
@@ -0,0 +1,249 @@
+// cells/sql-proxy/src/pgwire.rs
+// SPDX-License-Identifier: MIT
+// Postgres frontend/backend protocol v3, simple query flow only
+//
+// Enough for psql and for BI tools configured to use simple queries: startup
+// (TLS is declined), optional cleartext password, `Query` and `Terminate`.
+// Extended-protocol messages are answered with an error at the next `Sync`.
+
+use anyhow::{bail, Result};
+use std::future::Future;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+const PROTOCOL_V3: i32 = 196_608;
+const SSL_REQUEST: i32 = 80_877_103;
+const GSSENC_REQUEST: i32 = 80_877_104;
+const CANCEL_REQUEST: i32 = 80_877_102;
+/// Every column is reported as `text`
+const TEXT_OID: i32 = 25;
+
+/// Result of one statement, as text
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Option<String>>>,
+    /// Command tag, e.g. `SELECT 3` or `INSERT 0 1`
+    pub tag: String,
+}
+
+/// An error reported to the client with its SQLSTATE
+pub struct SqlError {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl<E: std::fmt::Display> From<E> for SqlError {
+    fn from(e: E) -> Self {
+        // XX000: internal_error
+        SqlError {
+            code: "XX000",
+            message: e.to_string(),
+        }
+    }
+}
+
+struct Out(Vec<u8>);
+
+impl Out {
+    fn new() -> Self {
+        Out(Vec::new())
+    }
+
+    fn message(&mut self, tag: u8, body: impl FnOnce(&mut Vec<u8>)) {
+        let mut buf = Vec::new();
+        body(&mut buf);
+        self.0.push(tag);
+        self.0.extend(((buf.len() + 4) as i32).to_be_bytes());
+        self.0.extend(buf);
+    }
+
+    fn ready(&mut self) {
+        self.message(b'Z', |b| b.push(b'I'));
+    }
+
+    fn error(&mut self, code: &str, message: &str) {
+        self.message(b'E', |b| {
+            for (field, value) in [
+                (b'S', "ERROR"),
+                (b'V', "ERROR"),
+                (b'C', code),
+                (b'M', message),
+            ] {
+                b.push(field);
+                cstring(b, value);
+            }
+            b.push(0);
+        });
+    }
+
+    fn result(&mut self, result: &QueryResult) {
+        if !result.columns.is_empty() {
+            self.message(b'T', |b| {
+                b.extend((result.columns.len() as i16).to_be_bytes());
+                for column in &result.columns {
+                    cstring(b, column);
+                    b.extend(0i32.to_be_bytes()); // table oid
+                    b.extend(0i16.to_be_bytes()); // attribute number
+                    b.extend(TEXT_OID.to_be_bytes());
+                    b.extend((-1i16).to_be_bytes()); // variable length
+                    b.extend((-1i32).to_be_bytes()); // no type modifier
+                    b.extend(0i16.to_be_bytes()); // text format
+                }
+            });
+            for row in &result.rows {
+                self.message(b'D', |b| {
+                    b.extend((row.len() as i16).to_be_bytes());
+                    for value in row {
+                        match value {
+                            Some(v) => {
+                                b.extend((v.len() as i32).to_be_bytes());
+                                b.extend(v.as_bytes());
+                            }
+                            None => b.extend((-1i32).to_be_bytes()),
+                        }
+                    }
+                });
+            }
+        }
+        self.message(b'C', |b| cstring(b, &result.tag));
+    }
+}
+
+fn cstring(buf: &mut Vec<u8>, s: &str) {
+    buf.extend(s.as_bytes());
+    buf.push(0);
+}
+
+/// Read a NUL-terminated string starting at `pos`, advancing past it.
+fn read_cstring(buf: &[u8], pos: &mut usize) -> Option<String> {
+    let len = buf.get(*pos..)?.iter().position(|&b| b == 0)?;
+    let s = String::from_utf8_lossy(&buf[*pos..*pos + len]).into_owned();
+    *pos += len + 1;
+    Some(s)
+}
+
+async fn read_body<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Vec<u8>> {
+    let len = stream.read_i32().await?;
+    if !(4..=64 * 1024 * 1024).contains(&len) {
+        bail!("Invalid message length {}", len);
+    }
+    let mut body = vec![0; len as usize - 4];
+    stream.read_exact(&mut body).await?;
+    Ok(body)
+}
+
+/// Serve one client connection. `query` runs the text of a `Query` message,
+/// one result per statement; the first error ends the batch, as in Postgres.
+pub async fn serve<S, F, Fut>(mut stream: S, password: Option<&str>, query: F) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Vec<Result<QueryResult, SqlError>>>,
+{
+    // Startup, after declining any encryption request
+    let params = loop {
+        let body = read_body(&mut stream).await?;
+        let code = i32::from_be_bytes(body.get(..4).unwrap_or(&[0; 4]).try_into()?);
+        match code {
+            SSL_REQUEST | GSSENC_REQUEST => stream.write_all(b"N").await?,
+            CANCEL_REQUEST => return Ok(()),
+            PROTOCOL_V3 => break body[4..].to_vec(),
+            other => bail!("Unsupported protocol version {}", other),
+        }
+    };
+    let mut pos = 0;
+    let mut user = String::new();
+    while let Some(key) = read_cstring(&params, &mut pos).filter(|k| !k.is_empty()) {
+        let value = read_cstring(&params, &mut pos).unwrap_or_default();
+        if key == "user" {
+            user = value;
+        }
+    }
+
+    let mut out = Out::new();
+    if let Some(expected) = password {
+        out.message(b'R', |b| b.extend(3i32.to_be_bytes())); // cleartext password
+        stream.write_all(&out.0).await?;
+        out = Out::new();
+        if stream.read_u8().await? != b'p' {
+            bail!("Expected a password message");
+        }
+        let body = read_body(&mut stream).await?;
+        if read_cstring(&body, &mut 0).as_deref() != Some(expected) {
+            // 28P01: invalid_password
+            out.error(
+                "28P01",
+                &format!("password authentication failed for user \"{}\"", user),
+            );
+            stream.write_all(&out.0).await?;
+            return Ok(());
+        }
+    }
+    out.message(b'R', |b| b.extend(0i32.to_be_bytes()));
+    for (key, value) in [
+        ("server_version", "14.0 (cell sql-proxy)"),
+        ("server_encoding", "UTF8"),
+        ("client_encoding", "UTF8"),
+        ("DateStyle", "ISO, MDY"),
+        ("integer_datetimes", "on"),
+        ("standard_conforming_strings", "on"),
+    ] {
+        out.message(b'S', |b| {
+            cstring(b, key);
+            cstring(b, value);
+        });
+    }
+    out.message(b'K', |b| {
+        b.extend((std::process::id() as i32).to_be_bytes());
+        b.extend(0i32.to_be_bytes());
+    });
+    out.ready();
+    stream.write_all(&out.0).await?;
+    tracing::debug!("[SqlProxy] Session for '{}' started", user);
+
+    let mut extended_failed = false;
+    loop {
+        let tag = match stream.read_u8().await {
+            Ok(tag) => tag,
+            Err(_) => return Ok(()),
+        };
+        let body = read_body(&mut stream).await?;
+        let mut out = Out::new();
+        match tag {
+            b'Q' => {
+                let sql = read_cstring(&body, &mut 0).unwrap_or_default();
+                if sql.trim().trim_matches(';').trim().is_empty() {
+                    out.message(b'I', |_| {});
+                }
+                for result in query(sql).await {
+                    match result {
+                        Ok(result) => out.result(&result),
+                        Err(e) => {
+                            out.error(e.code, &e.message);
+                            break;
+                        }
+                    }
+                }
+                out.ready();
+            }
+            b'X' => return Ok(()),
+            // Parse, Bind, Describe, Execute, Close, Flush
+            b'P' | b'B' | b'D' | b'E' | b'C' | b'H' => {
+                if !extended_failed {
+                    // 0A000: feature_not_supported
+                    out.error("0A000", "Only the simple query protocol is supported");
+                    extended_failed = true;
+                }
+            }
+            b'S' => {
+                extended_failed = false;
+                out.ready();
+            }
+            other => {
+                // 08P01: protocol_violation
+                out.error("08P01", &format!("Unexpected message '{}'", other as char));
+                out.ready();
+            }
+        }
+        stream.write_all(&out.0).await?;
+    }
+}
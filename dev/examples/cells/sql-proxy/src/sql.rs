@@ -0,0 +1,291 @@
+// cells/sql-proxy/src/sql.rs
+// SPDX-License-Identifier: MIT
+// The SQL subset the proxy understands
+//
+//   SELECT <* | col, ...> FROM <table> [WHERE col = lit [AND col = lit]...] [LIMIT n]
+//   INSERT INTO <table> [(col, ...)] VALUES (lit, ...)[, (lit, ...)...]
+//
+// plus SET/BEGIN/COMMIT/ROLLBACK/DISCARD, accepted as no-ops because clients
+// send them on connect. Literals are 'strings', integers and NULL.
+
+use anyhow::{bail, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Str(String),
+    Int(i64),
+    Null,
+}
+
+impl Literal {
+    pub fn text(&self) -> Option<String> {
+        match self {
+            Literal::Str(s) => Some(s.clone()),
+            Literal::Int(i) => Some(i.to_string()),
+            Literal::Null => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Select {
+        /// `None` for `*`
+        columns: Option<Vec<String>>,
+        table: String,
+        filters: Vec<(String, Literal)>,
+        limit: Option<u64>,
+    },
+    Insert {
+        table: String,
+        columns: Option<Vec<String>>,
+        rows: Vec<Vec<Literal>>,
+    },
+    /// Accepted and ignored; carries the command tag to report
+    Noop(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Int(i64),
+    Symbol(char),
+}
+
+/// Parse every `;`-separated statement of a simple query.
+pub fn parse(src: &str) -> Result<Vec<Statement>> {
+    let tokens = tokenize(src)?;
+    tokens
+        .split(|t| *t == Token::Symbol(';'))
+        .filter(|s| !s.is_empty())
+        .map(|s| Parser { tokens: s, pos: 0 }.statement())
+        .collect()
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = src.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '-' && chars.clone().nth(1) == Some('-') {
+            for c in chars.by_ref() {
+                if c == '\n' {
+                    break;
+                }
+            }
+        } else if c == '\'' {
+            chars.next();
+            let mut s = String::new();
+            loop {
+                match chars.next() {
+                    // '' is an escaped quote
+                    Some('\'') if chars.peek() == Some(&'\'') => {
+                        chars.next();
+                        s.push('\'');
+                    }
+                    Some('\'') => break,
+                    Some(c) => s.push(c),
+                    None => bail!("Unterminated string literal"),
+                }
+            }
+            tokens.push(Token::Str(s));
+        } else if c.is_ascii_digit() || c == '-' {
+            let mut s = String::new();
+            s.push(c);
+            chars.next();
+            while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                s.push(d);
+                chars.next();
+            }
+            tokens.push(Token::Int(s.parse()?));
+        } else if c.is_alphabetic() || c == '_' || c == '"' {
+            let quoted = c == '"';
+            if quoted {
+                chars.next();
+            }
+            let mut s = String::new();
+            while let Some(&d) = chars.peek() {
+                if quoted && d == '"' {
+                    chars.next();
+                    break;
+                }
+                if !quoted && !(d.is_alphanumeric() || d == '_' || d == '.') {
+                    break;
+                }
+                s.push(d);
+                chars.next();
+            }
+            // Unquoted identifiers are case-insensitive
+            tokens.push(Token::Word(if quoted { s } else { s.to_lowercase() }));
+        } else {
+            tokens.push(Token::Symbol(c));
+            chars.next();
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn keyword(&mut self, word: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Word(w)) if w == word) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_keyword(&mut self, word: &str) -> Result<()> {
+        if !self.keyword(word) {
+            bail!("Expected {}", word.to_uppercase());
+        }
+        Ok(())
+    }
+
+    fn symbol(&mut self, c: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(c)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_symbol(&mut self, c: char) -> Result<()> {
+        if !self.symbol(c) {
+            bail!("Expected '{}'", c);
+        }
+        Ok(())
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        match self.next() {
+            // `public.state` and `state` name the same table
+            Some(Token::Word(w)) => Ok(w.rsplit('.').next().unwrap_or(&w).to_string()),
+            other => bail!("Expected identifier, found {:?}", other),
+        }
+    }
+
+    fn literal(&mut self) -> Result<Literal> {
+        match self.next() {
+            Some(Token::Str(s)) => Ok(Literal::Str(s)),
+            Some(Token::Int(i)) => Ok(Literal::Int(i)),
+            Some(Token::Word(w)) if w == "null" => Ok(Literal::Null),
+            other => bail!("Expected a literal, found {:?}", other),
+        }
+    }
+
+    fn ident_list(&mut self) -> Result<Vec<String>> {
+        let mut idents = vec![self.ident()?];
+        while self.symbol(',') {
+            idents.push(self.ident()?);
+        }
+        Ok(idents)
+    }
+
+    fn statement(mut self) -> Result<Statement> {
+        let statement = match self.next() {
+            Some(Token::Word(w)) if w == "select" => self.select()?,
+            Some(Token::Word(w)) if w == "insert" => self.insert()?,
+            Some(Token::Word(w))
+                if matches!(
+                    w.as_str(),
+                    "set" | "begin" | "commit" | "rollback" | "discard"
+                ) =>
+            {
+                // Skip the rest: `SET client_encoding TO 'UTF8'`
+                self.pos = self.tokens.len();
+                Statement::Noop(w.to_uppercase())
+            }
+            other => bail!("Unsupported statement starting with {:?}", other),
+        };
+        if let Some(extra) = self.peek() {
+            bail!("Unexpected {:?}", extra);
+        }
+        Ok(statement)
+    }
+
+    fn select(&mut self) -> Result<Statement> {
+        let columns = if self.symbol('*') {
+            None
+        } else {
+            Some(self.ident_list()?)
+        };
+        self.expect_keyword("from")?;
+        let table = self.ident()?;
+
+        let mut filters = Vec::new();
+        if self.keyword("where") {
+            loop {
+                let column = self.ident()?;
+                self.expect_symbol('=')?;
+                filters.push((column, self.literal()?));
+                if !self.keyword("and") {
+                    break;
+                }
+            }
+        }
+        let limit = match self.keyword("limit") {
+            true => match self.next() {
+                Some(Token::Int(n)) if n >= 0 => Some(n as u64),
+                other => bail!("Expected a row count after LIMIT, found {:?}", other),
+            },
+            false => None,
+        };
+        Ok(Statement::Select {
+            columns,
+            table,
+            filters,
+            limit,
+        })
+    }
+
+    fn insert(&mut self) -> Result<Statement> {
+        self.expect_keyword("into")?;
+        let table = self.ident()?;
+        let columns = if self.symbol('(') {
+            let columns = self.ident_list()?;
+            self.expect_symbol(')')?;
+            Some(columns)
+        } else {
+            None
+        };
+        self.expect_keyword("values")?;
+
+        let mut rows = Vec::new();
+        loop {
+            self.expect_symbol('(')?;
+            let mut row = vec![self.literal()?];
+            while self.symbol(',') {
+                row.push(self.literal()?);
+            }
+            self.expect_symbol(')')?;
+            rows.push(row);
+            if !self.symbol(',') {
+                break;
+            }
+        }
+        Ok(Statement::Insert {
+            table,
+            columns,
+            rows,
+        })
+    }
+}
@@ -0,0 +1,29 @@
+use cell_sdk::*;
+
+cell_remote!(SqlProxy = "sql-proxy");
+
+#[tokio::test]
+async fn sql_round_trips_through_state_manager() {
+    cell_sdk::System::ignite_local_cluster().await.unwrap();
+
+    System::spawn("state-manager", None).await.expect("Failed to spawn state-manager");
+    System::spawn("sql-proxy", None).await.expect("Failed to spawn");
+    let synapse = Synapse::grow_await("sql-proxy").await.expect("Failed to connect");
+    let mut sql = SqlProxy::Client::new(synapse);
+
+    let inserted = sql
+        .query("INSERT INTO state (key, value) VALUES ('greeting', 'it''s alive')".into())
+        .await
+        .unwrap();
+    assert_eq!(inserted[0].tag, "INSERT 0 1");
+
+    let selected = sql
+        .query("SET search_path TO public; SELECT value FROM state WHERE key = 'greeting'".into())
+        .await
+        .unwrap();
+    assert_eq!(selected[1].columns, ["value"]);
+    assert_eq!(selected[1].rows, [vec![Some("it's alive".to_string())]]);
+
+    assert!(sql.query("SELECT * FROM state".into()).await.is_err());
+    assert!(sql.query("SELECT * FROM nowhere WHERE id = 1".into()).await.is_err());
+}
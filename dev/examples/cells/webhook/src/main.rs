@@ -0,0 +1,535 @@
+// cells/webhook/src/main.rs
+// SPDX-License-Identifier: MIT
+// Webhook ingress & egress
+//
+// Ingress: each configured HTTPS path verifies the sender's HMAC and turns the
+// JSON body into a call on a cell, using the gateway mapping (the body holds
+// one property per argument, the response is the return value as JSON).
+// Only cells linked in with `cell_remote!` and listed in `targets()` can be
+// called, since the wire format of a call is fixed at compile time.
+//
+// Egress: cells `emit` JSON events to named subscriptions; each delivery is
+// signed the same way, retried with backoff and dead-lettered when it keeps
+// failing.
+
+use anyhow::{Context, Result};
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Router;
+use cell_sdk::error::{CellError, ErrorContext};
+use cell_sdk::rkyv::de::deserializers::SharedDeserializeMap;
+use cell_sdk::rkyv::ser::serializers::AllocSerializer;
+use cell_sdk::rkyv::validation::validators::DefaultValidator;
+use cell_sdk::*;
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+cell_remote!(Audit = "audit");
+
+/// `sha256=<hex HMAC of the body>`, as GitHub and Stripe-style senders use
+const SIGNATURE_HEADER: &str = "x-cell-signature";
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+type Caller =
+    fn(ResilientSynapse, String, Value) -> Pin<Box<dyn Future<Output = Result<Value>> + Send>>;
+
+/// Cells reachable from inbound routes
+fn targets() -> HashMap<&'static str, Caller> {
+    HashMap::from([(
+        "audit",
+        call::<Audit::AuditProtocol, Audit::AuditResponse> as Caller,
+    )])
+}
+
+/// Call `method` on a cell whose protocol is `P`, with its arguments as JSON.
+fn call<P, R>(
+    conn: ResilientSynapse,
+    method: String,
+    args: Value,
+) -> Pin<Box<dyn Future<Output = Result<Value>> + Send>>
+where
+    P: serde::de::DeserializeOwned + rkyv::Serialize<AllocSerializer<1024>> + Send,
+    R: rkyv::Archive + serde::Serialize,
+    R::Archived:
+        rkyv::Deserialize<R, SharedDeserializeMap> + for<'a> rkyv::CheckBytes<DefaultValidator<'a>>,
+{
+    Box::pin(async move {
+        let variant = to_pascal(&method);
+        let invalid =
+            |e: &dyn std::fmt::Display| {
+                anyhow::Error::from(ErrorContext::new(CellError::InvalidMessage).with_message(
+                    format!("Body does not match the arguments of '{}': {}", method, e),
+                ))
+            };
+        let req: P = serde_json::from_value(serde_json::json!({ variant.as_str(): args }))
+            .map_err(|e| invalid(&e))?;
+
+        let resp = conn.fire(&req).await?.into_owned();
+        let archived = rkyv::check_archived_root::<R>(&resp)
+            .map_err(|e| anyhow::anyhow!("Invalid response: {}", e))?;
+        let resp: R = rkyv::Deserialize::deserialize(archived, &mut SharedDeserializeMap::new())
+            .map_err(|e| anyhow::anyhow!("Invalid response: {}", e))?;
+
+        // Responses are externally tagged by method: `{ "Log": 42 }`
+        match serde_json::to_value(resp)? {
+            Value::Object(map) if map.len() == 1 => Ok(map.into_iter().next().unwrap().1),
+            other => Ok(other),
+        }
+    })
+}
+
+fn to_pascal(snake: &str) -> String {
+    snake
+        .split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+fn verify(secret: &str, body: &[u8], header: Option<&str>) -> bool {
+    let Some(sig) = header.and_then(|h| h.strip_prefix("sha256=")) else {
+        return false;
+    };
+    let Ok(sig) = hex::decode(sig) else {
+        return false;
+    };
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes any key length");
+    mac.update(body);
+    // Constant time
+    mac.verify_slice(&sig).is_ok()
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+// === CONFIG ===
+
+#[derive(serde::Deserialize, Clone)]
+struct InboundRoute {
+    /// Served as `POST /hooks/{name}`
+    name: String,
+    cell: String,
+    method: String,
+    /// Shared with the sender; unsigned requests are refused
+    secret: String,
+}
+
+#[derive(serde::Deserialize, Default)]
+struct TlsConfig {
+    cert: PathBuf,
+    key: PathBuf,
+}
+
+/// `CELL_WEBHOOK_CONFIG` (default `~/.cell/webhook/config.json`)
+#[derive(serde::Deserialize)]
+struct Config {
+    #[serde(default = "default_listen")]
+    listen: String,
+    /// Plain HTTP without it, for use behind a TLS-terminating proxy
+    tls: Option<TlsConfig>,
+    #[serde(default)]
+    inbound: Vec<InboundRoute>,
+    #[serde(default)]
+    subscriptions: Vec<Subscription>,
+}
+
+fn default_listen() -> String {
+    "0.0.0.0:8443".into()
+}
+
+fn data_dir() -> PathBuf {
+    std::env::var("CELL_WEBHOOK_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| dirs::home_dir().unwrap_or_default().join(".cell/webhook"))
+}
+
+impl Config {
+    fn load() -> Result<Self> {
+        let path = std::env::var("CELL_WEBHOOK_CONFIG")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| data_dir().join("config.json"));
+        if !path.exists() {
+            return Ok(serde_json::from_str("{}")?);
+        }
+        let raw = std::fs::read(&path).with_context(|| format!("Failed to read {:?}", path))?;
+        serde_json::from_slice(&raw).with_context(|| format!("Invalid webhook config {:?}", path))
+    }
+}
+
+// === EGRESS ===
+
+#[protein]
+pub struct Subscription {
+    pub name: String,
+    pub url: String,
+    /// Signs deliveries when set
+    pub secret: Option<String>,
+    /// Attempts before the event is dead-lettered
+    pub max_attempts: u32,
+}
+
+#[protein]
+pub struct DeadLetter {
+    pub id: u64,
+    pub subscription: String,
+    /// JSON event
+    pub payload: String,
+    pub attempts: u32,
+    pub last_error: String,
+    pub failed_at: u64,
+}
+
+enum Outcome {
+    Delivered,
+    /// Worth another attempt: network errors, 5xx, 408, 429
+    Retry(String),
+    /// The receiver refused the event; retrying will not help
+    Rejected(String),
+}
+
+#[service]
+#[derive(Clone)]
+struct Webhook {
+    subscriptions: Arc<RwLock<HashMap<String, Subscription>>>,
+    dead_letters: Arc<RwLock<Vec<DeadLetter>>>,
+    next_id: Arc<AtomicU64>,
+    http: reqwest::Client,
+    dir: PathBuf,
+}
+
+impl Webhook {
+    async fn new(config: &Config) -> Result<Self> {
+        let dir = data_dir();
+        let dead_letters: Vec<DeadLetter> =
+            match tokio::fs::read(dir.join("dead-letters.json")).await {
+                Ok(raw) => serde_json::from_slice(&raw)?,
+                Err(_) => Vec::new(),
+            };
+        let next_id = dead_letters.iter().map(|d| d.id).max().unwrap_or(0) + 1;
+        let subscriptions = config
+            .subscriptions
+            .iter()
+            .map(|s| (s.name.clone(), s.clone()))
+            .collect();
+        Ok(Self {
+            subscriptions: Arc::new(RwLock::new(subscriptions)),
+            dead_letters: Arc::new(RwLock::new(dead_letters)),
+            next_id: Arc::new(AtomicU64::new(next_id)),
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()?,
+            dir,
+        })
+    }
+
+    async fn attempt(&self, sub: &Subscription, id: u64, attempt: u32, payload: &str) -> Outcome {
+        let mut req = self
+            .http
+            .post(&sub.url)
+            .header("content-type", "application/json")
+            .header("x-cell-delivery", id.to_string())
+            .header("x-cell-attempt", attempt.to_string())
+            .body(payload.to_string());
+        if let Some(secret) = &sub.secret {
+            req = req.header(SIGNATURE_HEADER, sign(secret, payload.as_bytes()));
+        }
+        match req.send().await {
+            Ok(resp) if resp.status().is_success() => Outcome::Delivered,
+            Ok(resp) => {
+                let status = resp.status();
+                let error = format!("{} responded {}", sub.url, status);
+                if status.is_server_error() || status.as_u16() == 408 || status.as_u16() == 429 {
+                    Outcome::Retry(error)
+                } else {
+                    Outcome::Rejected(error)
+                }
+            }
+            Err(e) => Outcome::Retry(e.to_string()),
+        }
+    }
+
+    /// Deliver in the background until it succeeds or is dead-lettered.
+    fn dispatch(&self, sub: Subscription, id: u64, payload: String) {
+        let this = self.clone();
+        tokio::spawn(async move {
+            let mut backoff = Duration::from_secs(1);
+            let mut attempts = 0;
+            let error = loop {
+                attempts += 1;
+                match this.attempt(&sub, id, attempts, &payload).await {
+                    Outcome::Delivered => return,
+                    Outcome::Rejected(e) => break e,
+                    Outcome::Retry(e) if attempts >= sub.max_attempts.max(1) => break e,
+                    Outcome::Retry(e) => {
+                        tracing::debug!("[Webhook] Delivery {} to {} failed: {}", id, sub.name, e);
+                        tokio::time::sleep(backoff).await;
+                        backoff = (backoff * 2).min(MAX_BACKOFF);
+                    }
+                }
+            };
+
+            tracing::warn!(
+                "[Webhook] Dead-lettered delivery {} to {}: {}",
+                id,
+                sub.name,
+                error
+            );
+            let mut dead = this.dead_letters.write().await;
+            dead.push(DeadLetter {
+                id,
+                subscription: sub.name.clone(),
+                payload,
+                attempts,
+                last_error: error,
+                failed_at: now(),
+            });
+            if let Err(e) = this.persist(&dead).await {
+                tracing::error!("[Webhook] Failed to persist dead letters: {}", e);
+            }
+        });
+    }
+
+    async fn persist(&self, dead: &[DeadLetter]) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let path = self.dir.join("dead-letters.json");
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, serde_json::to_vec(dead)?).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+
+    fn not_found(what: &str) -> anyhow::Error {
+        ErrorContext::new(CellError::NotFound)
+            .with_message(what.to_string())
+            .into()
+    }
+}
+
+#[handler]
+impl Webhook {
+    async fn subscribe(&self, sub: Subscription) -> Result<bool> {
+        reqwest::Url::parse(&sub.url).map_err(|e| {
+            ErrorContext::new(CellError::InvalidMessage)
+                .with_message(format!("Invalid URL '{}': {}", sub.url, e))
+        })?;
+        Ok(self
+            .subscriptions
+            .write()
+            .await
+            .insert(sub.name.clone(), sub)
+            .is_none())
+    }
+
+    async fn unsubscribe(&self, name: String) -> Result<bool> {
+        Ok(self.subscriptions.write().await.remove(&name).is_some())
+    }
+
+    /// Queue a JSON event for delivery. Returns the delivery id.
+    async fn emit(&self, subscription: String, payload: String) -> Result<u64> {
+        let sub = self
+            .subscriptions
+            .read()
+            .await
+            .get(&subscription)
+            .cloned()
+            .ok_or_else(|| Self::not_found(&format!("No subscription '{}'", subscription)))?;
+        serde_json::from_str::<serde::de::IgnoredAny>(&payload).map_err(|e| {
+            ErrorContext::new(CellError::InvalidMessage)
+                .with_message(format!("Payload is not JSON: {}", e))
+        })?;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.dispatch(sub, id, payload);
+        Ok(id)
+    }
+
+    async fn dead_letters(&self) -> Result<Vec<DeadLetter>> {
+        Ok(self.dead_letters.read().await.clone())
+    }
+
+    /// Retry a dead letter against its subscription's current URL.
+    async fn redeliver(&self, id: u64) -> Result<bool> {
+        let mut dead = self.dead_letters.write().await;
+        let Some(pos) = dead.iter().position(|d| d.id == id) else {
+            return Ok(false);
+        };
+        let sub = self
+            .subscriptions
+            .read()
+            .await
+            .get(&dead[pos].subscription)
+            .cloned()
+            .ok_or_else(|| {
+                Self::not_found(&format!("No subscription '{}'", dead[pos].subscription))
+            })?;
+        let letter = dead.remove(pos);
+        self.persist(&dead).await?;
+        self.dispatch(sub, letter.id, letter.payload);
+        Ok(true)
+    }
+}
+
+// === INGRESS ===
+
+struct Ingress {
+    routes: HashMap<String, InboundRoute>,
+    targets: HashMap<&'static str, Caller>,
+    conns: RwLock<HashMap<String, ResilientSynapse>>,
+}
+
+impl Ingress {
+    async fn conn(&self, cell: &str) -> Result<ResilientSynapse> {
+        if let Some(conn) = self.conns.read().await.get(cell) {
+            return Ok(conn.clone());
+        }
+        let conn = ResilientSynapse::grow(cell).await?;
+        self.conns
+            .write()
+            .await
+            .insert(cell.to_string(), conn.clone());
+        Ok(conn)
+    }
+}
+
+async fn hook(
+    State(ingress): State<Arc<Ingress>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> (StatusCode, String) {
+    let Some(route) = ingress.routes.get(&name) else {
+        return (StatusCode::NOT_FOUND, format!("No webhook '{}'", name));
+    };
+    let signature = headers.get(SIGNATURE_HEADER).and_then(|v| v.to_str().ok());
+    if !verify(&route.secret, &body, signature) {
+        tracing::warn!(
+            "[Webhook] Rejected unsigned or forged request for '{}'",
+            name
+        );
+        return (StatusCode::UNAUTHORIZED, "Invalid signature".into());
+    }
+    let args: Value = match serde_json::from_slice(&body) {
+        Ok(args) => args,
+        Err(e) => return (StatusCode::BAD_REQUEST, format!("Body is not JSON: {}", e)),
+    };
+
+    // Checked at startup, so the target is linked in
+    let caller = ingress.targets[route.cell.as_str()];
+    let result = match ingress.conn(&route.cell).await {
+        Ok(conn) => caller(conn, route.method.clone(), args).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(value) => (StatusCode::OK, value.to_string()),
+        Err(e) => {
+            let status = match ErrorContext::classify(&e, CellError::TransportUnavailable).code {
+                CellError::InvalidMessage => StatusCode::BAD_REQUEST,
+                _ => StatusCode::BAD_GATEWAY,
+            };
+            tracing::warn!(
+                "[Webhook] '{}' -> {}.{} failed: {}",
+                name,
+                route.cell,
+                route.method,
+                e
+            );
+            (status, e.to_string())
+        }
+    }
+}
+
+async fn serve_ingress(config: &Config) -> Result<()> {
+    let targets = targets();
+    for route in &config.inbound {
+        if !targets.contains_key(route.cell.as_str()) {
+            anyhow::bail!(
+                "Webhook '{}' targets '{}', which is not linked into this cell",
+                route.name,
+                route.cell
+            );
+        }
+    }
+    let ingress = Arc::new(Ingress {
+        routes: config
+            .inbound
+            .iter()
+            .map(|r| (r.name.clone(), r.clone()))
+            .collect(),
+        targets,
+        conns: RwLock::new(HashMap::new()),
+    });
+    let app = Router::new()
+        .route("/hooks/:name", axum::routing::post(hook))
+        .with_state(ingress);
+
+    let addr: std::net::SocketAddr = config.listen.parse()?;
+    match &config.tls {
+        Some(tls) => {
+            let rustls =
+                axum_server::tls_rustls::RustlsConfig::from_pem_file(&tls.cert, &tls.key).await?;
+            tracing::info!("[Webhook] Listening on https://{}", addr);
+            axum_server::bind_rustls(addr, rustls)
+                .serve(app.into_make_service())
+                .await?;
+        }
+        None => {
+            tracing::info!("[Webhook] Listening on http://{}", addr);
+            axum_server::bind(addr)
+                .serve(app.into_make_service())
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt().init();
+    let config = Config::load()?;
+    tracing::info!(
+        "[Webhook] {} inbound routes, {} subscriptions",
+        config.inbound.len(),
+        config.subscriptions.len()
+    );
+    let service = Webhook::new(&config).await?;
+
+    if !config.inbound.is_empty() {
+        let config = Config {
+            subscriptions: Vec::new(),
+            ..config
+        };
+        tokio::spawn(async move {
+            if let Err(e) = serve_ingress(&config).await {
+                tracing::error!("[Webhook] Ingress stopped: {}", e);
+            }
+        });
+    }
+
+    service.serve("webhook").await
+}
@@ -0,0 +1,30 @@
+use cell_sdk::*;
+
+cell_remote!(Webhook = "webhook");
+
+#[tokio::test]
+async fn failed_deliveries_are_dead_lettered() {
+    cell_sdk::System::ignite_local_cluster().await.unwrap();
+
+    System::spawn("webhook", None).await.expect("Failed to spawn");
+    let synapse = Synapse::grow_await("webhook").await.expect("Failed to connect");
+    let mut webhook = Webhook::Client::new(synapse);
+
+    webhook.subscribe(Webhook::Subscription {
+        name: "nowhere".into(),
+        url: "http://127.0.0.1:1/events".into(),
+        secret: Some("s3cret".into()),
+        max_attempts: 1,
+    }).await.unwrap();
+
+    assert!(webhook.emit("nowhere".into(), "not json".into()).await.is_err());
+    assert!(webhook.emit("missing".into(), "{}".into()).await.is_err());
+
+    let id = webhook.emit("nowhere".into(), r#"{"event":"deployed"}"#.into()).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+
+    let dead = webhook.dead_letters().await.unwrap();
+    let letter = dead.iter().find(|d| d.id == id).expect("delivery was not dead-lettered");
+    assert_eq!(letter.attempts, 1);
+    assert!(webhook.redeliver(id).await.unwrap());
+}
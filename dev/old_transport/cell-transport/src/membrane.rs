@@ -321,6 +321,7 @@ where
                             } else {
                                 "Disabled".into()
                             },
+                            wal_repair: None,
                         }
                     }
                     ArchivedOpsRequest::Metrics => {
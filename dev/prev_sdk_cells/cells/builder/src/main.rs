@@ -1,15 +1,26 @@
 // cells/builder/src/main.rs
 // SPDX-License-Identifier: MIT
 // The Ribosome: Compiles DNA (Source) into Proteins (Binaries)
+//
+// Every standard build is attested: an in-toto provenance statement (source
+// repo and commit, source hash, toolchain, flags) about the binary's blake3
+// digest, signed with this node's key and stored next to the binary (see
+// cell_sdk::provenance). Hypervisors check it before running the binary.
 
 mod ribosome;
 
 use anyhow::Result;
 use cell_sdk::*;
-use ribosome::Ribosome;
+use cell_sdk::provenance::{self, Provenance, Statement};
+use cell_sdk::release::CellRef;
+use ribosome::{Ribosome, BUILD_ARGS};
 use std::fs;
 use std::path::{Path, PathBuf};
-use tracing::{error, info};
+use std::process::Command;
+use tracing::{error, info, warn};
+
+cell_remote!(Blobstore = "blobstore");
+cell_remote!(Registry = "registry", methods = [resolve]);
 
 #[protein]
 pub enum BuildMode {
@@ -19,6 +30,7 @@ pub enum BuildMode {
 
 #[protein]
 pub struct BuildRequest {
+    /// `name`, or `name@channel` / `name@version` to build a registry release
     pub cell_name: String,
     pub mode: BuildMode,
 }
@@ -27,6 +39,8 @@ pub struct BuildRequest {
 pub struct BuildResponse {
     pub binary_path: String,
     pub source_hash: String, // New field for versioning
+    /// Blobstore hash of the binary, if the blobstore is running
+    pub artifact_blob: Option<String>,
 }
 
 struct BuilderService {
@@ -65,16 +79,50 @@ impl BuilderService {
         anyhow::bail!("Cell '{}' not found.", cell_name);
     }
 
-    fn build(&self, cell_name: &str, mode: BuildMode) -> Result<(PathBuf, String)> {
-        let source_path = self.resolve_source(cell_name)?;
+    /// Source of a pinned release. A published version never changes, so it is
+    /// checked out once into `.releases/<name>/<version>` and reused.
+    async fn checkout(&self, cell: &CellRef) -> Result<PathBuf> {
+        let release = Registry::Client::connect().await?.resolve(cell.to_string()).await?;
+        let dir = self.registry_path.join(".releases").join(&release.name).join(&release.version);
+        if dir.exists() {
+            return Ok(dir);
+        }
+        info!("[Builder] {} resolved to {} ({})", cell, release.version, release.commit_hash);
+
+        let staging = dir.with_extension("partial");
+        fs::remove_dir_all(&staging).ok();
+        fs::create_dir_all(dir.parent().unwrap_or(&self.registry_path))?;
+        let cloned = std::process::Command::new("git")
+            .args(["clone", "--quiet", &release.git_url])
+            .arg(&staging)
+            .status()?;
+        if !cloned.success() {
+            anyhow::bail!("Git clone of {} failed", release.git_url);
+        }
+        let checked_out = std::process::Command::new("git")
+            .args(["checkout", "--quiet", &release.commit_hash])
+            .current_dir(&staging)
+            .status()?;
+        if !checked_out.success() {
+            anyhow::bail!("Commit {} not found in {}", release.commit_hash, release.git_url);
+        }
+        fs::rename(&staging, &dir)?;
+        Ok(dir)
+    }
 
+    fn build(
+        &self,
+        source_path: &Path,
+        cell_name: &str,
+        mode: BuildMode,
+    ) -> Result<(PathBuf, String)> {
         match mode {
-            BuildMode::Standard => Ribosome::synthesize(&source_path, cell_name),
+            BuildMode::Standard => Ribosome::synthesize(source_path, cell_name),
             BuildMode::Test => {
                 // For tests, we use a dummy hash or compute one, 
                 // but usually tests are one-off. 
                 // We'll reuse synthesize logic for consistency or just return empty hash.
-                let path = Ribosome::synthesize_test(&source_path, cell_name)?;
+                let path = Ribosome::synthesize_test(source_path, cell_name)?;
                 Ok((path, "test-ephemeral".to_string()))
             },
         }
@@ -90,14 +138,102 @@ struct Builder {
 #[handler]
 impl Builder {
     async fn build(&self, req: BuildRequest) -> Result<BuildResponse> {
-        let (path, hash) = self.svc.build(&req.cell_name, req.mode)?;
+        let standard = matches!(req.mode, BuildMode::Standard);
+        let cell = CellRef::parse(&req.cell_name);
+        let source_path = if cell.is_pinned() {
+            self.svc.checkout(&cell).await?
+        } else {
+            self.svc.resolve_source(&cell.name)?
+        };
+        let (path, hash) = self.svc.build(&source_path, &cell.name, req.mode)?;
+        if standard {
+            if let Err(e) = attest(&cell.name, &source_path, &path, &hash) {
+                warn!("[Builder] Provenance for {} not recorded: {}", cell.name, e);
+            }
+        }
+        let artifact_blob = if standard {
+            match archive_artifact(&cell.name, &path).await {
+                Ok(blob) => Some(blob),
+                Err(e) => {
+                    warn!("[Builder] Artifact for {} not archived: {}", cell.name, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
         Ok(BuildResponse {
             binary_path: path.to_string_lossy().to_string(),
             source_hash: hash,
+            artifact_blob,
         })
     }
 }
 
+/// Sign and store the provenance of `binary`, unless it already has one
+fn attest(cell_name: &str, source: &Path, binary: &Path, source_hash: &str) -> Result<()> {
+    let digest = provenance::digest(binary)?;
+    if provenance::attestation_path(&digest)?.exists() {
+        return Ok(());
+    }
+    let mut build_flags: Vec<String> = BUILD_ARGS.iter().map(|a| a.to_string()).collect();
+    if let Ok(rustflags) = std::env::var("RUSTFLAGS") {
+        build_flags.push(format!("RUSTFLAGS={}", rustflags));
+    }
+    let predicate = Provenance {
+        builder: provenance::node_name(),
+        source_repo: git(source, &["remote", "get-url", "origin"]),
+        commit: git(source, &["rev-parse", "HEAD"]),
+        source_hash: source_hash.to_string(),
+        toolchain: output(Command::new("rustc").arg("-V")).unwrap_or_else(|| "unknown".into()),
+        build_flags,
+        built_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs(),
+    };
+    let path = provenance::attest(&digest, &Statement::new(cell_name, &digest, predicate))?;
+    info!("[Builder] Attested {} ({}) in {:?}", cell_name, digest, path);
+    Ok(())
+}
+
+/// `git <args>` in `dir`, if it is a repository
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    output(Command::new("git").args(args).current_dir(dir))
+}
+
+fn output(cmd: &mut Command) -> Option<String> {
+    let out = cmd.output().ok().filter(|o| o.status.success())?;
+    let text = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    Some(text).filter(|t| !t.is_empty())
+}
+
+/// Store a built binary in the blobstore and drop the reference to the
+/// previous build of the same cell, so GC can reclaim it.
+async fn archive_artifact(cell_name: &str, binary: &Path) -> Result<String> {
+    let blobs = Blobstore::Client::connect().await?;
+    let bytes = tokio::fs::read(binary).await?;
+    let mut chunks = Vec::new();
+    for piece in bytes.chunks(cell_sdk::blob::CHUNK_SIZE) {
+        chunks.push(blobs.upload_chunk(piece.to_vec()).await?);
+    }
+    let blob = blobs.commit(chunks).await?;
+
+    let record = dirs::home_dir()
+        .expect("No HOME dir")
+        .join(".cell/bin/.meta")
+        .join(cell_name)
+        .join("artifact.blob");
+    let previous = fs::read_to_string(&record).ok();
+    fs::write(&record, &blob.hash)?;
+    // Also when unchanged: commit took a second reference to the same blob
+    if let Some(previous) = previous {
+        blobs.release(previous.trim().to_string()).await?;
+    }
+
+    info!("[Builder] Archived {} as {}", cell_name, blob.hash);
+    Ok(blob.hash)
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     tracing_subscriber::fmt().with_writer(std::io::stderr).init();
@@ -9,6 +9,9 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 use serde_json::Value;
 
+/// Cargo arguments of a standard build, as recorded in its provenance
+pub const BUILD_ARGS: &[&str] = &["build", "--release"];
+
 pub struct Ribosome;
 
 impl Ribosome {
@@ -46,7 +49,7 @@ impl Ribosome {
 
         // Build
         let mut cmd = Command::new("cargo");
-        cmd.arg("build").arg("--release");
+        cmd.args(BUILD_ARGS);
         Self::sanitize_cargo_cmd(&mut cmd);
 
         let status = cmd
@@ -181,6 +181,17 @@ impl MyceliumRoot {
                 };
                 self.send_resp(&mut stream, resp).await?;
             }
+            ArchivedMitosisRequest::ListInstances { .. }
+            | ArchivedMitosisRequest::Restart { .. }
+            | ArchivedMitosisRequest::SpawnLazy { .. }
+            | ArchivedMitosisRequest::Stop { .. }
+            | ArchivedMitosisRequest::Cordon { .. }
+            | ArchivedMitosisRequest::SpawnArtifact { .. } => {
+                let resp = MitosisResponse::Denied {
+                    reason: "Instance management not supported in Builder Shim. Connect to Hypervisor.".to_string()
+                };
+                self.send_resp(&mut stream, resp).await?;
+            }
         }
         Ok(())
     }
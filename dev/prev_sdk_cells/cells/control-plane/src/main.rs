@@ -2,10 +2,12 @@
 // The zero-dependency supervisor that manages the entire mesh lifecycle
 
 use std::collections::{HashMap, HashSet};
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::process::{Child, Command};
 use std::time::{Duration, Instant};
 use serde::{Deserialize, Serialize};
+use tokio::task::JoinSet;
+use cell_build::boot::{BootGraph, BootTimeline};
 
 /// Persistent state stored in ~/.cell/control-plane.json
 #[derive(Serialize, Deserialize, Default)]
@@ -54,7 +56,8 @@ impl ControlPlane {
             state_file,
             running: HashMap::new(),
             boot_order: vec![
-                "builder",      // Must be first (compiles everything)
+                "mycelium",     // Router, so shut down last
+                "builder",      // Compiles everything
                 "hypervisor",   // Process manager
                 // nucleus removed
                 "mesh",         // Dependency graph
@@ -64,53 +67,108 @@ impl ControlPlane {
         }
     }
 
+    /// Readiness dependencies between kernel cells
+    fn kernel_graph() -> BootGraph {
+        let mut graph = BootGraph::new();
+        graph
+            .add("mycelium", &[])                          // Routes every other cell
+            .add("builder", &["mycelium"])
+            .add("mesh", &["mycelium"])
+            .add("axon", &["mycelium"])
+            .add("hypervisor", &["mycelium", "builder"])   // Spawns cells via builder
+            .add("observer", &["mycelium", "hypervisor"]); // Watches hypervisor's process table
+        graph
+    }
+
     /// PHASE 1: Bootstrap the kernel cells
+    ///
+    /// Cells start as soon as everything they depend on is ready, so independent
+    /// cells come up concurrently instead of one after another.
     async fn bootstrap_kernel(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         println!("PHASE 1: Bootstrapping kernel cells...\n");
 
-        for cell in &self.boot_order {
-            println!("  ├─ Starting {}...", cell);
-            
-            // Check if already running and healthy
-            if self.is_running_and_healthy(cell).await {
-                println!("  │  └─ ✓ Already running");
-                continue;
-            }
+        let graph = Self::kernel_graph();
+        graph.validate()?;
 
-            // Kill stale process if exists
-            if let Some(info) = self.state.processes.get(*cell) {
-                self.kill_process(info.pid);
-            }
+        let mut timeline = BootTimeline::new();
+        let mut started: HashSet<String> = HashSet::new();
+        let mut ready: HashSet<String> = HashSet::new();
+        let mut pending = JoinSet::new();
+
+        loop {
+            // Start everything that became startable, repeating while cells
+            // turn out to be already running
+            loop {
+                let startable = graph.startable(&ready, &started);
+                if startable.is_empty() {
+                    break;
+                }
 
-            // Spawn new process
-            match self.spawn_cell(cell).await {
-                Ok(child) => {
+                for cell in startable {
+                    started.insert(cell.clone());
+
+                    if self.is_running_and_healthy(&cell).await {
+                        println!("  ├─ {} already running", cell);
+                        timeline.ready(&cell);
+                        ready.insert(cell);
+                        continue;
+                    }
+
+                    // Kill stale process if exists
+                    if let Some(info) = self.state.processes.get(&cell) {
+                        self.kill_process(info.pid);
+                    }
+
+                    let child = match self.spawn_cell(&cell).await {
+                        Ok(child) => child,
+                        Err(e) => {
+                            timeline.failed(&cell, &e.to_string());
+                            eprintln!("\n{}", timeline.render());
+                            return Err(e);
+                        }
+                    };
                     let pid = child.id();
-                    self.running.insert(cell.to_string(), child);
-                    
-                    self.state.processes.insert(cell.to_string(), ProcessInfo {
+                    self.running.insert(cell.clone(), child);
+                    self.state.processes.insert(cell.clone(), ProcessInfo {
                         pid,
-                        socket_path: self.socket_path(cell),
+                        socket_path: self.socket_path(&cell),
                         version_hash: "kernel".to_string(),
                         start_time: Self::now(),
                         restart_count: 0,
                     });
-                    
-                    println!("  │  └─ ✓ Started (PID {})", pid);
-                    
-                    // Wait for readiness
-                    self.wait_for_ready(cell, Duration::from_secs(10)).await?;
-                    println!("  │  └─ ✓ Ready");
+
+                    println!("  ├─ Starting {} (PID {})", cell, pid);
+                    timeline.started(&cell);
+
+                    pending.spawn(async move {
+                        let ok = wait_until_ready(&cell, Duration::from_secs(10)).await;
+                        (cell, ok)
+                    });
                 }
-                Err(e) => {
-                    eprintln!("  │  └─ ✗ Failed: {}", e);
-                    return Err(e);
+            }
+
+            // Wait for the next readiness event
+            match pending.join_next().await {
+                None => break,
+                Some(Ok((cell, true))) => {
+                    println!("  │  └─ ✓ {} ready", cell);
+                    timeline.ready(&cell);
+                    ready.insert(cell);
+                }
+                Some(Ok((cell, false))) => {
+                    timeline.failed(&cell, "timeout waiting for readiness");
+                    eprintln!("  │  └─ ✗ {} failed", cell);
+                    eprintln!("\n{}", timeline.render());
+                    return Err(format!("{} did not become ready", cell).into());
                 }
+                Some(Err(e)) => return Err(e.into()),
             }
         }
 
         self.persist_state()?;
-        println!("\n✓ Kernel online\n");
+        self.persist_timeline(&timeline)?;
+        println!("\n{}", timeline.render());
+        println!("✓ Kernel online\n");
         Ok(())
     }
 
@@ -151,12 +209,23 @@ impl ControlPlane {
 
             for (cell, info) in &self.state.processes {
                 if !self.is_process_alive(info.pid) {
+                    println!("⚠ {} died, restarting...", cell);
                     unhealthy.push(cell.clone());
+                    continue;
                 }
+                // Running but not answering: hung, as good as dead
+                let status = cell_sdk::watchdog::check(cell).await;
+                if !status.live {
+                    println!("⚠ {} stopped answering, restarting...", cell);
+                    unhealthy.push(cell.clone());
+                } else if !status.ready {
+                    let failing: Vec<_> = status.checks.iter().filter(|c| !c.healthy).map(|c| c.name.as_str()).collect();
+                    println!("⚠ {} not ready: {}", cell, failing.join(", "));
+                }
+                self.state.last_health.insert(cell.clone(), Self::now());
             }
 
             for cell in unhealthy {
-                println!("⚠ {} died, restarting...", cell);
                 if let Err(e) = self.restart_cell(&cell).await {
                     eprintln!("  └─ Restart failed: {}", e);
                 }
@@ -200,8 +269,13 @@ impl ControlPlane {
     // --- HELPER METHODS ---
 
     async fn spawn_cell(&self, name: &str) -> Result<Child, Box<dyn std::error::Error>> {
-        let mut cmd = Command::new("cargo");
-        cmd.args(&["run", "--release", "-p", name]);
+        let mut cmd = if cell_build::kernel::KERNEL_CELLS.contains(&name) {
+            cell_build::kernel::resolve_kernel(name)?.command(name)
+        } else {
+            let mut cmd = Command::new("cargo");
+            cmd.args(["run", "--release", "-p", name]);
+            cmd
+        };
         cmd.env("CELL_SOCKET_DIR", self.socket_dir());
         cmd.stdin(std::process::Stdio::null());
         cmd.stdout(std::process::Stdio::null());
@@ -289,9 +363,7 @@ impl ControlPlane {
                 return false;
             }
             
-            // Try connecting to socket
-            let socket = PathBuf::from(&info.socket_path);
-            tokio::net::UnixStream::connect(socket).await.is_ok()
+            cell_sdk::watchdog::check(name).await.ready
         } else {
             false
         }
@@ -312,19 +384,11 @@ impl ControlPlane {
     }
 
     async fn wait_for_ready(&self, name: &str, timeout: Duration) -> Result<(), Box<dyn std::error::Error>> {
-        let socket = PathBuf::from(self.socket_path(name));
-        let deadline = Instant::now() + timeout;
-
-        while Instant::now() < deadline {
-            if socket.exists() {
-                if tokio::net::UnixStream::connect(&socket).await.is_ok() {
-                    return Ok(());
-                }
-            }
-            tokio::time::sleep(Duration::from_millis(100)).await;
+        if wait_until_ready(name, timeout).await {
+            Ok(())
+        } else {
+            Err("Timeout waiting for cell readiness".into())
         }
-
-        Err("Timeout waiting for cell readiness".into())
     }
 
     async fn send_shutdown_signal(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
@@ -381,6 +445,13 @@ impl ControlPlane {
         Ok(())
     }
 
+    /// Last boot timeline, kept next to the state file for `cell up` to report
+    fn persist_timeline(&self, timeline: &BootTimeline) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self.state_file.with_file_name("boot-timeline.json");
+        std::fs::write(path, serde_json::to_string_pretty(timeline)?)?;
+        Ok(())
+    }
+
     fn socket_dir(&self) -> String {
         dirs::home_dir()
             .unwrap()
@@ -401,6 +472,22 @@ impl ControlPlane {
     }
 }
 
+/// A cell counts as ready once its OPS `HealthCheck` says so: an accepting
+/// socket alone may front a cell still loading, or one whose checks fail
+async fn wait_until_ready(name: &str, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if let Ok(status) = tokio::time::timeout(remaining, cell_sdk::watchdog::check(name)).await {
+            if status.ready {
+                return true;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    false
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut cp = ControlPlane::new();
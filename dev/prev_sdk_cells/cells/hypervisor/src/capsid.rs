@@ -3,9 +3,11 @@
 // Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
 
 use anyhow::{Context, Result, bail};
+use std::os::unix::io::RawFd;
 use std::path::Path;
 use std::process::{Child, Command, Stdio};
 use cell_model::config::CellInitConfig;
+use cell_model::placement::{gpu_env, GpuDevice};
 use cell_model::protocol::{MitosisSignal, MitosisControl};
 use cell_transport::gap_junction::{spawn_with_gap_junction, GapJunction};
 use tracing::{info, warn};
@@ -20,6 +22,9 @@ impl Capsid {
         args: &[&str],
         config: &CellInitConfig,
         capture_output: bool, // New flag
+        gpus: &[GpuDevice],
+        // Socket activation: a bound listener the cell serves instead of binding its own
+        listen_fd: Option<RawFd>,
     ) -> Result<Child> {
         let binary_canonical = binary.canonicalize()
             .context("Binary path invalid or does not exist")?;
@@ -48,6 +53,16 @@ impl Capsid {
             if Path::new("/lib").exists() { c.arg("--ro-bind").arg("/lib").arg("/lib"); }
             if Path::new("/lib64").exists() { c.arg("--ro-bind").arg("/lib64").arg("/lib64"); }
 
+            // --dev only creates the basic nodes; pass granted GPUs through
+            for gpu in gpus.iter().filter(|g| Path::new(&g.device).exists()) {
+                c.arg("--dev-bind").arg(&gpu.device).arg(&gpu.device);
+            }
+            if gpus.iter().any(|g| g.vendor == "nvidia") {
+                for ctl in ["/dev/nvidiactl", "/dev/nvidia-uvm"] {
+                    if Path::new(ctl).exists() { c.arg("--dev-bind").arg(ctl).arg(ctl); }
+                }
+            }
+
             c.args(args);
             c.arg("/tmp/dna/payload");
             c.env("CELL_SOCKET_DIR", "/tmp/cell");
@@ -60,9 +75,32 @@ impl Capsid {
         };
 
         cmd.env("CELL_ORGANISM", &config.organism);
+        let granted: Vec<&GpuDevice> = gpus.iter().collect();
+        for (k, v) in gpu_env(&granted) {
+            cmd.env(k, v);
+        }
         cmd.env_remove("CELL_NODE_ID"); 
         cmd.env_remove("CELL_IDENTITY");
 
+        if let Some(fd) = listen_fd {
+            use std::os::unix::process::CommandExt;
+            // Inherited under the same number; it must not collide with the
+            // gap junction, which the child finds at fd 3
+            if fd <= 3 {
+                bail!("Listener fd {} would be clobbered by the gap junction", fd);
+            }
+            cmd.env("CELL_LISTEN_FD", fd.to_string());
+            unsafe {
+                cmd.pre_exec(move || {
+                    let flags = libc::fcntl(fd, libc::F_GETFD);
+                    if flags < 0 || libc::fcntl(fd, libc::F_SETFD, flags & !libc::FD_CLOEXEC) < 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+
         // IO Configuration
         cmd.stdin(Stdio::null());
         if capture_output {
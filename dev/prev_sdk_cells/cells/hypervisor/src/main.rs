@@ -1,13 +1,63 @@
 // cells/hypervisor/src/main.rs
 // SPDX-License-Identifier: MIT
 // The Daemon: System Hypervisor and Process Manager
+//
+// Cells spawned lazily (MitosisRequest::SpawnLazy) are socket activated: the
+// hypervisor binds their socket and only starts the binary once a connection
+// is waiting, passing the listener in CELL_LISTEN_FD. Until then the socket
+// exists and queues callers, so discovery sees the cell, but it costs no
+// memory. When such a cell exits (with CELL_IDLE_EXIT_SECS set it does so
+// once idle that long), the hypervisor holds the socket again until the next
+// connection.
+//
+// Under memory or CPU pressure (/proc/meminfo, /proc/loadavg, sampled every
+// CELL_PRESSURE_INTERVAL seconds) the node enters degraded mode: it stops
+// best-effort cells first, standard cells only when critical, refuses to
+// start cells of the classes it shed, and reports the mode to the mesh. Shed
+// cells come back once the pressure clears. Critical cells are never shed,
+// and every cell's oom_score_adj follows its class, so should the kernel
+// still run out it picks best-effort cells too.
+//
+// A cordoned node (MitosisRequest::Cordon, sent by the nucleus for `cell node
+// cordon`) refuses cells it does not already host. Draining then stops its
+// cells one by one with MitosisRequest::Stop: SIGTERM, CELL_STOP_GRACE_SECS
+// (default 10) to exit, then SIGKILL. Stopped cells are forgotten, so neither
+// the watchdog nor pressure relief brings them back.
+//
+// MitosisRequest::SpawnArtifact starts a cell from a binary archived in the
+// blobstore instead of building its source (rollbacks use this). The binary
+// is cached under ~/.cell/bin/.artifacts and restarts reuse it.
+//
+// A cell spawned as `name@channel` or `name@version` runs under `name` but is
+// built from that registry release; the builder resolves the channel. The pin
+// is kept, so restarts rebuild the same release rather than local source.
+//
+// Before running a binary the hypervisor checks its provenance attestation
+// (cell_sdk::provenance): a trusted node's signature over a statement about
+// exactly this binary. One that does not verify is refused. A binary without
+// an attestation runs with a warning, or is refused when
+// CELL_REQUIRE_PROVENANCE=1. Kernel cells and test binaries are not checked.
+//
+// Binaries are cached in the artifact store (cell_build::artifact) under the
+// cell, target triple and source hash. Before asking the builder, the
+// hypervisor looks there and then asks the file servers in
+// CELL_ARTIFACT_PEERS, through Axon, for the same key; a fetched binary is only
+// used if its artifact signature verifies. Pinned releases are always built.
 
 mod capsid;
 
 use capsid::Capsid;
+use cell_build::artifact::{ArtifactKey, ArtifactStore, SigningKey};
 use cell_sdk::cell_remote;
 use cell_model::protocol::{MitosisRequest, MitosisResponse, MitosisSignal, MitosisControl, TestEvent};
 use cell_model::config::CellInitConfig;
+use cell_model::manifest::CellManifest;
+use cell_model::placement::{GpuDevice, Requirement};
+use cell_model::release::CellRef;
+use cell_model::pressure::{self, Pressure, PressureLevel, PressureThresholds, PriorityClass};
+use cell_model::watchdog::{Escalation, EscalationPolicy, Health, WatchdogCounters};
+use cell_sdk::telemetry::{BatchConfig, Batcher};
+use cell_sdk::watchdog;
 use cell_transport::GapJunction;
 use anyhow::{Context, Result, anyhow};
 use std::path::PathBuf;
@@ -15,12 +65,17 @@ use tokio::net::{UnixListener, UnixStream};
 use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader, AsyncBufReadExt};
 use tracing::{info, error, warn};
 use cell_model::rkyv::Deserialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::os::unix::io::{AsRawFd, FromRawFd};
 use std::sync::{Arc, Mutex};
 use std::process::Child;
 
 // Remote interface to Builder
 cell_remote!(Builder = "builder");
+cell_remote!(Observer = "observer", methods = [emit_batch]);
+cell_remote!(Mesh = "mesh", methods = [report_node_mode]);
+cell_remote!(Blobstore = "blobstore", methods = [get]);
+cell_remote!(Axon = "axon", methods = [fetch_file]);
 
 #[cell_sdk::service]
 struct HypervisorService;
@@ -44,12 +99,31 @@ impl HypervisorService {
 struct ProcessTable {
     // cell_name -> (Child Process, Source Hash)
     running: HashMap<String, (Child, String)>, 
+    // cell_name -> config it was spawned with (reused on restart)
+    configs: HashMap<String, CellInitConfig>,
+    // Socket-activated cells, whose listener the hypervisor holds
+    lazy: HashSet<String>,
+    // cell_name -> priority class from its Cell.toml
+    classes: HashMap<String, PriorityClass>,
+    // Cells stopped to relieve pressure, restarted once it clears
+    shed: HashSet<String>,
+    level: PressureLevel,
+    // Under maintenance: no cells the node does not already host
+    cordoned: bool,
+    // cell_name -> blobstore hash of the binary it runs, if not built from source
+    artifacts: HashMap<String, String>,
+    // cell_name -> `name@channel` or `name@version` it is built from, if pinned
+    releases: HashMap<String, String>,
 }
 
 pub struct Hypervisor {
     system_socket_dir: PathBuf,
     daemon_socket_path: PathBuf,
     processes: Arc<Mutex<ProcessTable>>,
+    artifacts: ArtifactStore,
+    artifact_key: SigningKey,
+    // File servers of peer Axons (their CELL_FILES_ADDR)
+    artifact_peers: Vec<String>,
 }
 
 impl Hypervisor {
@@ -82,10 +156,27 @@ impl Hypervisor {
 
         info!("[Hypervisor] Kernel Active. Listening on {:?}", daemon_socket_path);
 
+        let artifact_peers = std::env::var("CELL_ARTIFACT_PEERS")
+            .map(|v| v.split(',').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect())
+            .unwrap_or_default();
+
         let hv = Self { 
             system_socket_dir: system_socket_dir.clone(), 
             daemon_socket_path: daemon_socket_path.clone(),
-            processes: Arc::new(Mutex::new(ProcessTable { running: HashMap::new() })),
+            processes: Arc::new(Mutex::new(ProcessTable {
+                running: HashMap::new(),
+                configs: HashMap::new(),
+                lazy: HashSet::new(),
+                classes: HashMap::new(),
+                shed: HashSet::new(),
+                level: PressureLevel::Normal,
+                cordoned: false,
+                artifacts: HashMap::new(),
+                releases: HashMap::new(),
+            })),
+            artifacts: ArtifactStore::open_default()?,
+            artifact_key: SigningKey::load_or_create(&SigningKey::default_path()?)?,
+            artifact_peers,
         };
 
         // Bootstrap basic services (Nucleus removed)
@@ -117,6 +208,14 @@ impl Hypervisor {
             }
         });
 
+        // Watchdog: probe every running cell and escalate the degraded ones
+        let watchdog_hv = hv_arc.clone();
+        tokio::spawn(async move { watchdog_hv.watchdog().await });
+
+        // Degraded mode: shed low priority cells before the kernel OOM-kills
+        let pressure_hv = hv_arc.clone();
+        tokio::spawn(async move { pressure_hv.relieve_pressure().await });
+
         loop {
             if let Ok((stream, _)) = listener.accept().await {
                 let r_inner = hv_arc.clone();
@@ -130,10 +229,8 @@ impl Hypervisor {
     }
 
     async fn bootstrap_kernel_cell(&self, name: &str) -> Result<()> {
-        // ... (Bootstrapping uses raw cargo run, skipping hash check for simplicity in MVP)
-        // ... (See existing code)
-        // For rigorous implementation, this should also use builder, but kernel cells are special.
-        use std::process::Command;
+        // Kernel cells come prebuilt from ~/.cell/bin (see `cell install`);
+        // cargo is only used in dev mode.
         use cell_transport::gap_junction::spawn_with_gap_junction;
         
         let socket = self.system_socket_dir.join(format!("{}.sock", name));
@@ -146,8 +243,9 @@ impl Hypervisor {
         }
 
         info!("[Hypervisor] Bootstrapping {}...", name);
-        let mut cmd = Command::new("cargo");
-        cmd.arg("run").arg("--release").arg("-p").arg(name);
+        let launch = cell_build::kernel::resolve_kernel(name)
+            .with_context(|| format!("Cannot launch kernel cell '{}'", name))?;
+        let mut cmd = launch.command(name);
         
         if let Ok(s) = std::env::var("CELL_SOCKET_DIR") { cmd.env("CELL_SOCKET_DIR", s); }
         if let Ok(r) = std::env::var("CELL_REGISTRY_DIR") { cmd.env("CELL_REGISTRY_DIR", r); }
@@ -190,18 +288,11 @@ impl Hypervisor {
 
         match req {
             cell_model::protocol::ArchivedMitosisRequest::Spawn { cell_name, config } => {
-                let name = cell_name.to_string();
+                let name = self.pin(cell_name.as_str());
                 let final_config = if let cell_model::rkyv::option::ArchivedOption::Some(c) = config {
                     c.deserialize(&mut cell_model::rkyv::Infallible).unwrap()
                 } else {
-                    let socket_path = self.system_socket_dir.join(format!("{}.sock", name));
-                    CellInitConfig {
-                        node_id: rand::random(),
-                        cell_name: name.clone(),
-                        peers: vec![],
-                        socket_path: socket_path.to_string_lossy().to_string(),
-                        organism: "system".to_string(),
-                    }
+                    self.default_config(&name)
                 };
 
                 match self.perform_spawn(&name, &final_config).await {
@@ -214,26 +305,232 @@ impl Hypervisor {
                     }
                 }
             }
+            cell_model::protocol::ArchivedMitosisRequest::SpawnLazy { cell_name, config } => {
+                let name = self.pin(cell_name.as_str());
+                let final_config = if let cell_model::rkyv::option::ArchivedOption::Some(c) = config {
+                    c.deserialize(&mut cell_model::rkyv::Infallible).unwrap()
+                } else {
+                    self.default_config(&name)
+                };
+                let resp = match self.perform_spawn_lazy(&name, &final_config).await {
+                    Ok(()) => MitosisResponse::Ok { socket_path: final_config.socket_path },
+                    Err(e) => MitosisResponse::Denied { reason: e.to_string() },
+                };
+                self.send_resp(&mut stream, resp).await?;
+            }
             cell_model::protocol::ArchivedMitosisRequest::Test { target_cell, filter } => {
                 let target = target_cell.to_string();
                 let _filter = filter.as_ref().map(|s| s.to_string());
                 self.perform_test(target, _filter, &mut stream).await?;
             }
+            cell_model::protocol::ArchivedMitosisRequest::ListInstances { cell_name } => {
+                let mut names: Vec<String> = {
+                    let table = self.processes.lock().unwrap();
+                    table.running.keys()
+                        .filter(|n| cell_model::protocol::is_instance_of(n, cell_name.as_str()))
+                        .cloned()
+                        .collect()
+                };
+                names.sort();
+                self.send_resp(&mut stream, MitosisResponse::Instances { names }).await?;
+            }
+            cell_model::protocol::ArchivedMitosisRequest::Restart { instance } => {
+                let resp = match self.perform_restart(instance.as_str()).await {
+                    Ok(socket_path) => MitosisResponse::Ok { socket_path },
+                    Err(e) => MitosisResponse::Denied { reason: e.to_string() },
+                };
+                self.send_resp(&mut stream, resp).await?;
+            }
+            cell_model::protocol::ArchivedMitosisRequest::SpawnArtifact { cell_name, artifact, config } => {
+                let name = cell_name.to_string();
+                let final_config = if let cell_model::rkyv::option::ArchivedOption::Some(c) = config {
+                    c.deserialize(&mut cell_model::rkyv::Infallible).unwrap()
+                } else {
+                    self.default_config(&name)
+                };
+                let resp = match self.perform_spawn_artifact(&name, artifact.as_str(), &final_config).await {
+                    Ok(()) => MitosisResponse::Ok { socket_path: final_config.socket_path },
+                    Err(e) => MitosisResponse::Denied { reason: e.to_string() },
+                };
+                self.send_resp(&mut stream, resp).await?;
+            }
+            cell_model::protocol::ArchivedMitosisRequest::Stop { instance } => {
+                let resp = match self.perform_stop(instance.as_str()).await {
+                    Ok(socket_path) => MitosisResponse::Ok { socket_path },
+                    Err(e) => MitosisResponse::Denied { reason: e.to_string() },
+                };
+                self.send_resp(&mut stream, resp).await?;
+            }
+            cell_model::protocol::ArchivedMitosisRequest::Cordon { cordoned } => {
+                let cordoned = *cordoned;
+                self.processes.lock().unwrap().cordoned = cordoned;
+                if cordoned {
+                    warn!("[Hypervisor] Node cordoned: refusing new cells");
+                } else {
+                    info!("[Hypervisor] Node uncordoned");
+                }
+                let resp = MitosisResponse::Ok { socket_path: String::new() };
+                self.send_resp(&mut stream, resp).await?;
+            }
         }
         Ok(())
     }
 
+    fn default_config(&self, name: &str) -> CellInitConfig {
+        let socket_path = self.system_socket_dir.join(format!("{}.sock", name));
+        CellInitConfig {
+            node_id: rand::random(),
+            cell_name: name.to_string(),
+            peers: vec![],
+            socket_path: socket_path.to_string_lossy().to_string(),
+            organism: "system".to_string(),
+        }
+    }
+
+    /// Name to run a spawn request's cell under, remembering the release it
+    /// pins so restarts build the same one
+    fn pin(&self, reference: &str) -> String {
+        let cell = CellRef::parse(reference);
+        let mut table = self.processes.lock().unwrap();
+        if cell.is_pinned() {
+            table.releases.insert(cell.name.clone(), cell.to_string());
+        } else {
+            table.releases.remove(&cell.name);
+        }
+        cell.name
+    }
+
+    /// What the builder builds for `cell_name`: its pinned release, if any
+    fn build_target(&self, cell_name: &str) -> String {
+        let table = self.processes.lock().unwrap();
+        table.releases.get(cell_name).cloned().unwrap_or_else(|| cell_name.to_string())
+    }
+
     async fn perform_spawn(&self, cell_name: &str, config: &CellInitConfig) -> Result<()> {
+        // Socket activated: it starts itself on demand
+        if self.processes.lock().unwrap().lazy.contains(cell_name) {
+            return Ok(());
+        }
+        self.check_cordon(cell_name)?;
+        let class = self.admit(cell_name)?;
+
         // 1. Build & Check Hash
+        let (binary_path, source_hash) = self.binary(cell_name).await?;
+        self.processes.lock().unwrap().artifacts.remove(cell_name);
+        self.launch(cell_name, config, &binary_path, source_hash, class).await
+    }
+
+    /// A binary of `cell_name` and the source hash it was built from: from the
+    /// artifact store, a peer's, or else the builder. See the header comment.
+    async fn binary(&self, cell_name: &str) -> Result<(PathBuf, String)> {
+        let target = self.build_target(cell_name);
+        let key = match self.source_dir(cell_name) {
+            Some(dir) if target == cell_name => {
+                let name = base_name(cell_name).to_string();
+                match tokio::task::spawn_blocking(move || ArtifactKey::for_host(&name, &dir)).await? {
+                    Ok(key) => Some(key),
+                    Err(e) => {
+                        warn!("[Hypervisor] Cannot hash the source of {}: {}", cell_name, e);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        if let Some(key) = &key {
+            if let Some(binary) = self.artifacts.lookup(key) {
+                return Ok((binary, key.source_hash.clone()));
+            }
+            for peer in &self.artifact_peers {
+                match self.fetch_from_peer(peer, key).await {
+                    Ok(Some(binary)) => {
+                        info!("[Hypervisor] Fetched {} from {} instead of building it", cell_name, peer);
+                        return Ok((binary, key.source_hash.clone()));
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!("[Hypervisor] Artifact fetch of {} from {} failed: {}", cell_name, peer, e),
+                }
+            }
+        }
+
         let mut builder = Builder::Client::connect().await
             .context("Hypervisor cannot reach Builder")?;
-            
-        let build_res = builder.build(cell_name.to_string(), Builder::BuildMode::Standard).await
+        let build_res = builder.build(target, Builder::BuildMode::Standard).await
             .context("Build failed")?;
-
         let binary_path = PathBuf::from(build_res.binary_path);
-        let new_hash = build_res.source_hash;
 
+        let Some(key) = key else {
+            return Ok((binary_path, build_res.source_hash));
+        };
+        // Offer it to peers; the key's hash keeps restarts from looking like an update
+        let binary_path = match self.artifacts.insert(&key, &binary_path, &self.artifact_key) {
+            Ok(stored) => stored,
+            Err(e) => {
+                warn!("[Hypervisor] {} not added to the artifact store: {}", cell_name, e);
+                binary_path
+            }
+        };
+        Ok((binary_path, key.source_hash))
+    }
+
+    /// Fetch a binary and its manifest from a peer through Axon's file
+    /// transfer and import it if the signature verifies
+    async fn fetch_from_peer(&self, peer: &str, key: &ArtifactKey) -> Result<Option<PathBuf>> {
+        let axon = Axon::Client::connect().await?;
+        let (binary, manifest) = key.transfer_files();
+        let Some(manifest) = axon.fetch_file(peer.to_string(), manifest).await? else {
+            return Ok(None);
+        };
+        let manifest = PathBuf::from(manifest);
+        let binary = axon.fetch_file(peer.to_string(), binary).await?.map(PathBuf::from);
+
+        let imported = match &binary {
+            Some(binary) => self.artifacts.import_files(key, binary, &manifest, &self.artifact_key).map(Some),
+            None => Ok(None),
+        };
+        for received in std::iter::once(&manifest).chain(binary.as_ref()) {
+            let _ = std::fs::remove_file(received);
+        }
+        imported
+    }
+
+    /// Like `perform_spawn`, but run an archived binary instead of building
+    async fn perform_spawn_artifact(&self, cell_name: &str, artifact: &str, config: &CellInitConfig) -> Result<()> {
+        self.check_cordon(cell_name)?;
+        let class = self.admit(cell_name)?;
+
+        let dir = dirs::home_dir().context("No HOME")?.join(".cell/bin/.artifacts");
+        let binary_path = dir.join(artifact);
+        if !binary_path.exists() {
+            let blobs = Blobstore::Client::connect().await
+                .context("Hypervisor cannot reach the blobstore")?;
+            let bytes = blobs.get(artifact.to_string()).await
+                .with_context(|| format!("Artifact {} of {} is not in the blobstore", artifact, cell_name))?;
+            tokio::fs::create_dir_all(&dir).await?;
+            // Never exec a partially written file
+            let tmp = dir.join(format!("{}.tmp", artifact));
+            tokio::fs::write(&tmp, &bytes).await?;
+            use std::os::unix::fs::PermissionsExt;
+            tokio::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o755)).await?;
+            tokio::fs::rename(&tmp, &binary_path).await?;
+            info!("[Hypervisor] Fetched artifact {} for {}", artifact, cell_name);
+        }
+
+        self.processes.lock().unwrap().artifacts.insert(cell_name.to_string(), artifact.to_string());
+        self.launch(cell_name, config, &binary_path, artifact.to_string(), class).await
+    }
+
+    /// Start `binary_path` as `cell_name`, replacing an instance running
+    /// another version
+    async fn launch(
+        &self,
+        cell_name: &str,
+        config: &CellInitConfig,
+        binary_path: &std::path::Path,
+        new_hash: String,
+        class: PriorityClass,
+    ) -> Result<()> {
         // 2. Reconciliation Logic
         {
             let mut table = self.processes.lock().unwrap();
@@ -268,17 +565,346 @@ impl Hypervisor {
         let runtime_dir = socket_path.parent().unwrap();
         tokio::fs::create_dir_all(runtime_dir).await?;
 
-        let child = Capsid::spawn(&binary_path, runtime_dir, &self.daemon_socket_path, &[], config, false)?;
+        let gpus = self.granted_gpus(cell_name)?;
+        check_provenance(cell_name, binary_path)?;
+        let child = Capsid::spawn(binary_path, runtime_dir, &self.daemon_socket_path, &[], config, false, &gpus, None)?;
+        set_oom_score(&child, class);
         
         // 4. Register
         {
             let mut table = self.processes.lock().unwrap();
             table.running.insert(cell_name.to_string(), (child, new_hash));
+            table.configs.insert(cell_name.to_string(), config.clone());
+            table.classes.insert(cell_name.to_string(), class);
         }
         
         Ok(())
     }
 
+    /// Build the cell and bind its socket, but leave starting it to the
+    /// first connection (see `activate`).
+    async fn perform_spawn_lazy(&self, cell_name: &str, config: &CellInitConfig) -> Result<()> {
+        {
+            let table = self.processes.lock().unwrap();
+            if table.lazy.contains(cell_name) || table.running.contains_key(cell_name) {
+                return Ok(());
+            }
+        }
+        self.check_cordon(cell_name)?;
+
+        // Build now, so the first caller does not wait for a compile
+        let (binary_path, source_hash) = self.binary(cell_name).await?;
+        check_provenance(cell_name, &binary_path)?;
+        let gpus = self.granted_gpus(cell_name)?;
+        let priority = self.priority(cell_name);
+
+        let socket_path = PathBuf::from(&config.socket_path);
+        let runtime_dir = socket_path.parent().unwrap().to_path_buf();
+        tokio::fs::create_dir_all(&runtime_dir).await?;
+        if socket_path.exists() {
+            if UnixStream::connect(&socket_path).await.is_ok() {
+                anyhow::bail!("{} is already being served at {:?}", cell_name, socket_path);
+            }
+            tokio::fs::remove_file(&socket_path).await?;
+        }
+        let listener = std::os::unix::net::UnixListener::bind(&socket_path)?;
+        // Out of the way of the child's gap junction at fd 3
+        let fd = unsafe { libc::fcntl(listener.as_raw_fd(), libc::F_DUPFD_CLOEXEC, 10) };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        drop(listener);
+        let listener = unsafe { std::os::unix::net::UnixListener::from_raw_fd(fd) };
+
+        {
+            let mut table = self.processes.lock().unwrap();
+            table.lazy.insert(cell_name.to_string());
+            table.configs.insert(cell_name.to_string(), config.clone());
+            table.classes.insert(cell_name.to_string(), priority);
+        }
+        info!("[Hypervisor] {} is socket activated at {:?}", cell_name, socket_path);
+
+        let activation = Activation {
+            name: cell_name.to_string(),
+            binary: binary_path,
+            hash: source_hash,
+            config: config.clone(),
+            runtime_dir,
+            daemon_socket_path: self.daemon_socket_path.clone(),
+            gpus,
+            priority,
+        };
+        let processes = self.processes.clone();
+        tokio::spawn(async move {
+            if let Err(e) = activation.run(listener, processes).await {
+                error!("[Hypervisor] Socket activation stopped: {}", e);
+            }
+        });
+        Ok(())
+    }
+
+    /// The cell's source directory in the registry, if registered
+    fn source_dir(&self, cell_name: &str) -> Option<PathBuf> {
+        let registry = std::env::var("CELL_REGISTRY_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| dirs::home_dir().unwrap_or_default().join(".cell/registry"));
+        let dir = registry.join(base_name(cell_name));
+        dir.exists().then_some(dir)
+    }
+
+    /// The cell's Cell.toml from the registry, if it has one
+    fn manifest(&self, cell_name: &str) -> Option<CellManifest> {
+        let toml = std::fs::read_to_string(self.source_dir(cell_name)?.join("Cell.toml")).ok()?;
+        toml::from_str(&toml).ok()
+    }
+
+    fn priority(&self, cell_name: &str) -> PriorityClass {
+        self.manifest(cell_name).map(|m| m.priority).unwrap_or_default()
+    }
+
+    /// The cell's priority class, if the node's pressure lets it start
+    fn admit(&self, cell_name: &str) -> Result<PriorityClass> {
+        let class = self.priority(cell_name);
+        let level = self.processes.lock().unwrap().level;
+        if !pressure::admits(class, level) {
+            anyhow::bail!(
+                "{} is {} and the node is in degraded mode ({:?})",
+                cell_name, class.name(), level
+            );
+        }
+        Ok(class)
+    }
+
+    /// Errors if the node is cordoned and `cell_name` is not one it already hosts
+    fn check_cordon(&self, cell_name: &str) -> Result<()> {
+        let table = self.processes.lock().unwrap();
+        if table.cordoned && !table.configs.contains_key(cell_name) {
+            anyhow::bail!("node is cordoned; {} must be placed elsewhere", cell_name);
+        }
+        Ok(())
+    }
+
+    /// GPUs on this node matching the `requires` of the cell's Cell.toml.
+    /// Errors if the cell needs hardware this node does not have.
+    fn granted_gpus(&self, cell_name: &str) -> Result<Vec<GpuDevice>> {
+        let Some(manifest) = self.manifest(cell_name) else {
+            return Ok(Vec::new());
+        };
+        let reqs = Requirement::parse_all(&manifest.requires).map_err(|e| anyhow!(e))?;
+
+        let hw = cell_discovery::hardware::HardwareCaps::scan();
+        let node = hw.profile(0, "local");
+        if !node.satisfies(&reqs) {
+            anyhow::bail!("{} requires {:?}, which this node cannot provide", cell_name, manifest.requires);
+        }
+        Ok(node.matching_gpus(&reqs).into_iter().cloned().collect())
+    }
+
+    async fn watchdog(&self) {
+        let interval = std::env::var("CELL_WATCHDOG_INTERVAL")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(10);
+        let policy = EscalationPolicy::default();
+        let mut counters: HashMap<String, WatchdogCounters> = HashMap::new();
+        let reports = Batcher::spawn(BatchConfig::default(), |spans| async move {
+            let observer = Observer::Client::connect().await?;
+            observer.emit_batch(spans).await.map(drop)
+        });
+
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+            let running: Vec<String> = self.processes.lock().unwrap().running.keys().cloned().collect();
+            counters.retain(|name, _| running.contains(name));
+
+            for name in running {
+                let health = match watchdog::probe(&name).await {
+                    Ok(report) => {
+                        for probe in report.failing() {
+                            warn!("[Watchdog] {}: probe '{}' failing: {}", name, probe.name, probe.detail);
+                        }
+                        report.health
+                    }
+                    Err(e) => {
+                        warn!("[Watchdog] {} did not answer: {}", name, e);
+                        Health::Degraded
+                    }
+                };
+
+                let entry = counters.entry(name.clone()).or_default();
+                let Some(step) = entry.observe(health, &policy) else { continue };
+                let snapshot = entry.clone();
+                warn!("[Watchdog] {} degraded, escalating: {:?}", name, step);
+
+                match step {
+                    Escalation::Degrade => {}
+                    Escalation::Drain => {
+                        if let Err(e) = watchdog::drain_cell(&name).await {
+                            warn!("[Watchdog] Drain of {} failed: {}", name, e);
+                        }
+                    }
+                    Escalation::Restart => {
+                        if let Err(e) = self.perform_restart(&name).await {
+                            error!("[Watchdog] Restart of {} failed: {}", name, e);
+                        }
+                    }
+                }
+                reports.push(escalation_span(&name, step, &snapshot)).await;
+            }
+        }
+    }
+
+    async fn relieve_pressure(&self) {
+        let interval = std::env::var("CELL_PRESSURE_INTERVAL")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(5);
+        let thresholds = PressureThresholds::default();
+        let cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        let node = std::env::var("CELL_NODE_ID").unwrap_or_else(|_| "0".to_string());
+        let mut reported: Option<(PressureLevel, Vec<String>)> = None;
+
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+            let (Ok(meminfo), Ok(loadavg)) = (
+                std::fs::read_to_string("/proc/meminfo"),
+                std::fs::read_to_string("/proc/loadavg"),
+            ) else { continue };
+            let Some(sample) = Pressure::from_proc(&meminfo, &loadavg, cpus) else { continue };
+
+            let (level, restore) = {
+                let mut table = self.processes.lock().unwrap();
+                let level = thresholds.classify(&sample, table.level);
+                if level != table.level {
+                    if level.degraded() {
+                        warn!("[Hypervisor] Node under pressure ({:?}): {:.1}% memory available, load {:.2}/cpu",
+                            level, sample.mem_available_pct, sample.load_per_cpu);
+                    } else {
+                        info!("[Hypervisor] Pressure cleared, leaving degraded mode");
+                    }
+                    table.level = level;
+                }
+
+                let running: Vec<(String, PriorityClass)> = table.running.keys()
+                    .map(|n| (n.clone(), table.classes.get(n).copied().unwrap_or_default()))
+                    .collect();
+                for name in pressure::shed(&running, level) {
+                    if let Some((mut child, _)) = table.running.remove(&name) {
+                        warn!("[Hypervisor] Shedding {} to relieve pressure", name);
+                        let _ = child.kill();
+                        let _ = child.wait(); // Reap
+                    }
+                    // Socket-activated cells wait for a connection on their own
+                    if !table.lazy.contains(&name) {
+                        table.shed.insert(name);
+                    }
+                }
+
+                let classes = &table.classes;
+                let restore: Vec<String> = table.shed.iter()
+                    .filter(|n| pressure::admits(classes.get(*n).copied().unwrap_or_default(), level))
+                    .cloned()
+                    .collect();
+                for name in &restore {
+                    table.shed.remove(name);
+                }
+                (level, restore)
+            };
+
+            for name in restore {
+                info!("[Hypervisor] Restoring {} after pressure cleared", name);
+                if let Err(e) = self.perform_restart(&name).await {
+                    error!("[Hypervisor] Restoring {} failed: {}", name, e);
+                }
+            }
+
+            let mut shed: Vec<String> = self.processes.lock().unwrap().shed.iter().cloned().collect();
+            shed.sort();
+            let state = (level, shed);
+            if reported.as_ref() == Some(&state) {
+                continue;
+            }
+            let mode = Mesh::NodeMode {
+                node: node.clone(),
+                level: format!("{:?}", state.0),
+                degraded: state.0.degraded(),
+                shed: state.1.clone(),
+                mem_available_pct: sample.mem_available_pct,
+                load_per_cpu: sample.load_per_cpu,
+            };
+            match Mesh::Client::connect().await {
+                Ok(mesh) => match mesh.report_node_mode(mode).await {
+                    Ok(()) => reported = Some(state),
+                    Err(e) => warn!("[Hypervisor] Reporting mode to mesh failed: {}", e),
+                },
+                Err(_) => {} // No mesh on this node; retried next sample
+            }
+        }
+    }
+
+    /// Stop one instance and start it again with the config it was spawned with.
+    async fn perform_restart(&self, instance: &str) -> Result<String> {
+        let (config, artifact) = {
+            let mut table = self.processes.lock().unwrap();
+            let config = table.configs.get(instance).cloned()
+                .ok_or_else(|| anyhow!("Unknown instance '{}'", instance))?;
+            if let Some((mut child, _)) = table.running.remove(instance) {
+                info!("[Hypervisor] Restarting {}", instance);
+                let _ = child.kill();
+                let _ = child.wait(); // Reap
+            }
+            // Its activation starts it again on the next connection
+            if table.lazy.contains(instance) {
+                return Ok(config.socket_path);
+            }
+            (config, table.artifacts.get(instance).cloned())
+        };
+
+        match artifact {
+            Some(artifact) => self.perform_spawn_artifact(instance, &artifact, &config).await?,
+            None => self.perform_spawn(instance, &config).await?,
+        }
+        Ok(config.socket_path)
+    }
+
+    /// Stop one instance and forget it. It gets SIGTERM and the grace period
+    /// to finish in-flight work before it is killed.
+    async fn perform_stop(&self, instance: &str) -> Result<String> {
+        let (child, config) = {
+            let mut table = self.processes.lock().unwrap();
+            let config = table.configs.remove(instance)
+                .ok_or_else(|| anyhow!("Unknown instance '{}'", instance))?;
+            // Its activation sees this and closes the socket
+            table.lazy.remove(instance);
+            table.shed.remove(instance);
+            table.classes.remove(instance);
+            table.artifacts.remove(instance);
+            table.releases.remove(instance);
+            (table.running.remove(instance), config)
+        };
+
+        if let Some((mut child, _)) = child {
+            let grace = std::env::var("CELL_STOP_GRACE_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(10);
+            info!("[Hypervisor] Stopping {} ({}s grace)", instance, grace);
+            unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGTERM) };
+            let deadline = std::time::Instant::now() + std::time::Duration::from_secs(grace);
+            while matches!(child.try_wait(), Ok(None)) {
+                if std::time::Instant::now() >= deadline {
+                    warn!("[Hypervisor] {} ignored SIGTERM; killing it", instance);
+                    let _ = child.kill();
+                    let _ = child.wait(); // Reap
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+        }
+        let _ = tokio::fs::remove_file(&config.socket_path).await;
+        Ok(config.socket_path)
+    }
+
     async fn perform_test(&self, target: String, filter: Option<String>, stream: &mut UnixStream) -> Result<()> {
         // ... (Test Logic mostly unchanged, omit spawn registration since tests are ephemeral) ...
         // Re-included for completeness
@@ -312,7 +938,8 @@ impl Hypervisor {
             args.push(&filter_val as &str);
         }
 
-        let mut child = Capsid::spawn(&binary_path, &socket_dir, &self.daemon_socket_path, &args, &config, true)?;
+        let gpus = self.granted_gpus(&target)?;
+        let mut child = Capsid::spawn(&binary_path, &socket_dir, &self.daemon_socket_path, &args, &config, true, &gpus, None)?;
         
         let stdout = child.stdout.take().unwrap();
         let mut reader = BufReader::new(stdout).lines();
@@ -353,6 +980,130 @@ impl Hypervisor {
     }
 }
 
+/// A socket-activated cell, started whenever a caller waits on its socket.
+struct Activation {
+    name: String,
+    binary: PathBuf,
+    hash: String,
+    config: CellInitConfig,
+    runtime_dir: PathBuf,
+    daemon_socket_path: PathBuf,
+    gpus: Vec<GpuDevice>,
+    priority: PriorityClass,
+}
+
+impl Activation {
+    async fn run(self, listener: std::os::unix::net::UnixListener, processes: Arc<Mutex<ProcessTable>>) -> Result<()> {
+        listener.set_nonblocking(true)?;
+        let listener = tokio::io::unix::AsyncFd::with_interest(listener, tokio::io::Interest::READABLE)?;
+        loop {
+            // Readable: a connection is waiting to be accepted. Leave it in the
+            // backlog for the cell.
+            listener.readable().await?.clear_ready();
+            // Stopped for good: dropping the listener closes the socket
+            if !processes.lock().unwrap().lazy.contains(&self.name) {
+                info!("[Hypervisor] {} was stopped; releasing its socket", self.name);
+                return Ok(());
+            }
+            // Degraded: callers wait in the backlog until the pressure clears
+            if !pressure::admits(self.priority, processes.lock().unwrap().level) {
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                continue;
+            }
+            info!("[Hypervisor] Connection for {}: starting it", self.name);
+
+            // The binary may have been replaced since it was checked at spawn
+            if let Err(e) = check_provenance(&self.name, &self.binary) {
+                error!("[Hypervisor] Not starting {}: {}", self.name, e);
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                continue;
+            }
+            let child = Capsid::spawn(
+                &self.binary, &self.runtime_dir, &self.daemon_socket_path, &[],
+                &self.config, false, &self.gpus, Some(listener.get_ref().as_raw_fd()),
+            );
+            match child {
+                Ok(child) => {
+                    set_oom_score(&child, self.priority);
+                    processes.lock().unwrap().running.insert(self.name.clone(), (child, self.hash.clone()));
+                }
+                Err(e) => {
+                    error!("[Hypervisor] Starting {} failed: {}", self.name, e);
+                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                    continue;
+                }
+            }
+
+            // The cell accepts while it runs; the reaper drops it once it exits
+            while processes.lock().unwrap().running.contains_key(&self.name) {
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            }
+            info!("[Hypervisor] {} exited; holding its socket until the next connection", self.name);
+        }
+    }
+}
+
+/// The cell an instance runs: instances ("worker-2") share the source and
+/// Cell.toml of their cell
+fn base_name(cell_name: &str) -> &str {
+    cell_name.rsplit_once('-')
+        .filter(|(_, n)| n.chars().all(|c| c.is_ascii_digit()))
+        .map(|(b, _)| b)
+        .unwrap_or(cell_name)
+}
+
+/// Refuse `binary` unless its provenance verifies; see the header comment
+fn check_provenance(cell_name: &str, binary: &std::path::Path) -> Result<()> {
+    match cell_sdk::provenance::check(binary) {
+        Ok(Some(statement)) => {
+            let built = &statement.predicate;
+            info!(
+                "[Hypervisor] {} built by {} from {} ({})",
+                cell_name,
+                built.builder,
+                built.source_repo.as_deref().unwrap_or("local source"),
+                built.commit.as_deref().unwrap_or(&built.source_hash),
+            );
+            Ok(())
+        }
+        Ok(None) if std::env::var("CELL_REQUIRE_PROVENANCE").is_ok_and(|v| v == "1") => {
+            Err(anyhow!("{} has no provenance attestation ({:?})", cell_name, binary))
+        }
+        Ok(None) => {
+            warn!("[Hypervisor] {} has no provenance attestation; running it anyway", cell_name);
+            Ok(())
+        }
+        Err(e) => Err(anyhow!("Provenance of {} does not verify: {}", cell_name, e)),
+    }
+}
+
+/// Point the kernel's OOM killer at low priority cells first. Best effort:
+/// lowering the score below zero needs CAP_SYS_RESOURCE.
+fn set_oom_score(child: &Child, class: PriorityClass) {
+    let path = format!("/proc/{}/oom_score_adj", child.id());
+    if let Err(e) = std::fs::write(&path, class.oom_score_adj().to_string()) {
+        tracing::debug!("[Hypervisor] Cannot set {}: {}", path, e);
+    }
+}
+
+/// Make watchdog counters visible in the observer.
+fn escalation_span(cell: &str, step: Escalation, counters: &WatchdogCounters) -> Observer::TelemetrySpan {
+    Observer::TelemetrySpan {
+        trace_id: String::new(),
+        span_id: String::new(),
+        service: "hypervisor".to_string(),
+        name: "watchdog".to_string(),
+        duration_us: 0,
+        tags: vec![
+            ("cell".to_string(), cell.to_string()),
+            ("escalation".to_string(), format!("{:?}", step)),
+            ("degraded_total".to_string(), counters.degraded_total.to_string()),
+            ("drains".to_string(), counters.drains.to_string()),
+            ("restarts".to_string(), counters.restarts.to_string()),
+        ],
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     tracing_subscriber::fmt().with_writer(std::io::stderr).init();
@@ -13,9 +13,20 @@ use cell_model::protocol::{MitosisSignal, MitosisControl};
 use cell_model::config::CellInitConfig;
 use cell_transport::gap_junction::spawn_with_gap_junction;
 use std::path::PathBuf;
+use cell_sdk::cell_remote;
+use cell_sdk::release::{CellRef, Pin};
+
+cell_remote!(Registry = "registry", methods = [resolve]);
 
 #[derive(Serialize, Deserialize, Debug)]
-pub enum ResolverRequest { EnsureRunning { cell_name: String } }
+pub enum ResolverRequest {
+    EnsureRunning {
+        cell_name: String,
+        /// Release channel the caller pins, resolved here at spawn time
+        #[serde(default)]
+        channel: Option<String>,
+    },
+}
 #[derive(Serialize, Deserialize, Debug)]
 pub enum ResolverResponse { Ok { socket_path: String }, Error { message: String } }
 
@@ -33,11 +44,17 @@ async fn main() -> Result<()> {
     tokio::fs::create_dir_all(&system_dir).await?;
     
     let mycelium_sock = system_dir.join("mycelium.sock");
-    if mycelium_sock.exists() { tokio::fs::remove_file(&mycelium_sock).await.ok(); }
+
+    // Under `cell install-service` systemd owns the socket and hands it to us
+    let activated = cell_build::daemon::activated_listener();
+    if activated.is_none() && mycelium_sock.exists() { tokio::fs::remove_file(&mycelium_sock).await.ok(); }
 
     ensure_hypervisor_running().await?;
 
-    let listener = UnixListener::bind(&mycelium_sock)?;
+    let listener = match activated {
+        Some(l) => { info!("[Mycelium] Using socket-activated listener"); l }
+        None => UnixListener::bind(&mycelium_sock)?,
+    };
     info!("[Mycelium] Listening...");
 
     let listener_handle = tokio::task::spawn_blocking(move || {
@@ -68,10 +85,14 @@ async fn handle_client(stream: &mut UnixStream) -> Result<()> {
     let req: ResolverRequest = serde_json::from_slice(&buf)?;
 
     let resp = match req {
-        ResolverRequest::EnsureRunning { cell_name } => {
+        ResolverRequest::EnsureRunning { cell_name, channel } => {
             // ALWAYS call ensure_cell, which now calls Hypervisor->Spawn
             // Hypervisor handles idempotency and hot-swapping.
-            match ensure_cell(&cell_name).await {
+            let cell = match channel {
+                Some(channel) => format!("{}@{}", cell_name, channel),
+                None => cell_name,
+            };
+            match ensure_cell(&cell).await {
                 Ok(path) => ResolverResponse::Ok { socket_path: path },
                 Err(e) => ResolverResponse::Error { message: e.to_string() },
             }
@@ -85,12 +106,46 @@ async fn handle_client(stream: &mut UnixStream) -> Result<()> {
     Ok(())
 }
 
-async fn ensure_cell(name: &str) -> Result<String> {
+/// Spawn `reference` (`name`, `name@channel` or `name@version`) and wait for
+/// it to answer. A channel is resolved to the version on it right now, so the
+/// hypervisor keeps building that version until asked for another.
+async fn ensure_cell(reference: &str) -> Result<String> {
+    let cell = CellRef::parse(reference);
+    let name = cell.name.as_str();
+    let target = match &cell.pin {
+        Pin::Channel(_) => {
+            let release = Registry::Client::connect().await
+                .context("Cannot reach the registry")?
+                .resolve(cell.to_string()).await
+                .with_context(|| format!("Cannot resolve {}", cell))?;
+            info!("[Mycelium] {} is {}", cell, release.version);
+            CellRef::at(name, &release.version).to_string()
+        }
+        _ => cell.to_string(),
+    };
+
     // We do NOT check for socket existence here anymore.
     // We actively request spawn to ensure version compliance.
-    let path = cell_sdk::System::spawn(name, None).await
+    let path = cell_sdk::System::spawn(&target, None).await
         .context("Failed to spawn/update cell via Hypervisor")?;
-    Ok(path)
+
+    // Hand out the socket once the cell answers its health check: the socket
+    // may exist before the runtime behind it serves anything
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    loop {
+        let status = cell_sdk::watchdog::check(name).await;
+        if status.live {
+            if !status.ready {
+                warn!("[Mycelium] {} is up but not ready", name);
+            }
+            return Ok(path);
+        }
+        if std::time::Instant::now() >= deadline {
+            let detail = status.checks.first().map(|c| c.detail.clone()).unwrap_or_default();
+            return Err(anyhow!("{} did not answer its health check: {}", name, detail));
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
 }
 
 // ... (ensure_hypervisor_running and find_binary remain same) ...
@@ -102,11 +157,11 @@ async fn ensure_hypervisor_running() -> Result<()> {
     if hv_sock.exists() { return Ok(()); }
 
     info!("[Mycelium] Booting Hypervisor...");
-    let current_exe = std::env::current_exe()?;
-    let bin_dir = current_exe.parent().context("No parent dir")?;
-    let hypervisor_bin = bin_dir.join("hypervisor"); // Assume built alongside mycelium
+    let launch = cell_build::kernel::resolve_kernel("hypervisor")
+        .context("Cannot launch hypervisor")?;
 
-    let mut cmd = std::process::Command::new(&hypervisor_bin);
+    let mut cmd = launch.command("hypervisor");
+    cell_build::daemon::without_activation(&mut cmd);
     cmd.env("CELL_SOCKET_DIR", system_dir.to_str().unwrap());
     cmd.env("CELL_NODE_ID", "0");
     cmd.env("CELL_ORGANISM", "system");
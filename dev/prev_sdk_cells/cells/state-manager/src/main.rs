@@ -1,21 +1,145 @@
 // cells/state-manager/src/main.rs
-// Persistent state storage for the mesh using SQLite + Write-Ahead Log
+// Persistent state storage for the mesh. The engine is SQLite in WAL mode
+// unless Cell.toml's [storage] or CELL_STORAGE picks redb or memory (see
+// storage.rs).
+//
+// Runs as the leader, or as a read replica when CELL_REPLICA_OF is set (see
+// cell_sdk::replica). Replicas follow the leader's change log, serve reads
+// within the caller's staleness bound and forward everything else.
+//
+// The leader expires rows past their ttl_secs in the background and logs
+// each one as a deletion on the change stream, so replicas, caches and
+// derived views drop it too. Expiry runs in batches of CELL_EXPIRY_BATCH rows
+// (default 500) for at most CELL_EXPIRY_BUDGET_MS (default 50) per pass,
+// every CELL_EXPIRY_INTERVAL_MS (default 1000), so a burst of expiring keys
+// never holds the database for long.
+//
+// Every row belongs to a data domain (cell_sdk::domain): the organism that
+// owns it and, optionally, the region it must stay in. Writes without one are
+// tagged with this cell's own domain. Rows of other organisms are only read or
+// written by callers IAM granted access to, and rows resident elsewhere are
+// refused outright.
+//
+// `scan` (by key prefix) and `range` (by key bounds) list rows in key order,
+// a page of at most MAX_PAGE at a time, so cells can keep secondary indexes
+// and listings in ordinary keys. A page ends with the key to continue after;
+// rows of domains the caller may not read are left out rather than refused.
+//
+// `watch` long-polls the change log for one key or a key prefix: it returns
+// as soon as a watched key changes or expires, or empty after wait_ms (at
+// most MAX_WATCH_MS), with the cursor to call again with. That lets a cell
+// hot-reload configuration it keeps here, like firewall rules, without
+// polling the rows. Replicas forward watches to the leader.
+//
+// The leader also keeps the mesh's deployment history: every applied
+// manifest, swap and rollback with the version that went live (see
+// cell_model::deploy). `cell rollback` reads it to pick the version to go
+// back to. Replicas forward history calls to the leader.
+//
+// With `[consensus]` in Cell.toml the state-manager runs replicated instead:
+// one member of a Raft group whose writes commit on a majority before they
+// return, three of them making a KV store that survives losing any one (see
+// replicated.rs). Read replicas ignore the section and follow as above.
+
+mod replicated;
+mod storage;
 
 use cell_sdk::*;
-use rusqlite::{Connection, params};
-use std::sync::{Arc, Mutex};
-use std::path::PathBuf;
+use cell_sdk::domain::{self, Domain, DomainGrant};
+use cell_sdk::error::{CellError, ErrorContext};
+use replicated::Replicated;
+use std::ops::Bound;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use storage::StorageBackend;
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+/// Most rows one `scan` or `range` page holds
+const MAX_PAGE: u32 = 1000;
+
+/// Longest a `watch` call waits for a change, below the callers' request
+/// timeout
+const MAX_WATCH_MS: u64 = 20_000;
+
+/// Change log entries a `watch` reads at a time
+const WATCH_SCAN: u32 = 1000;
+
+cell_remote!(Quota = "quota");
+cell_remote!(Iam = "iam", methods = [domain_grants]);
+// The leader, as seen from a replica; its proteins are this crate's own
+cell_remote!(Leader = "state-manager", proteins = crate);
 
 #[protein]
 pub struct StoreRequest {
     pub key: String,
     pub value: Vec<u8>,
     pub ttl_secs: Option<u64>,
+    /// Domain label (`organism` or `organism@residency`); `None` is this
+    /// cell's own domain
+    pub domain: Option<String>,
 }
 
 #[protein]
 pub struct FetchRequest {
     pub key: String,
+    /// How far behind the leader a replica may be to answer, in ms. `None`
+    /// accepts any synced replica; `Some(0)` always reads from the leader.
+    pub max_staleness_ms: Option<u64>,
+}
+
+/// Rows whose keys start with `prefix`
+#[protein]
+pub struct ScanRequest {
+    pub prefix: String,
+    /// Rows per page, capped at MAX_PAGE
+    pub limit: u32,
+    /// `next` of the previous page
+    pub cursor: Option<String>,
+    /// As in FetchRequest
+    pub max_staleness_ms: Option<u64>,
+}
+
+/// Rows with keys from `start` up to `end`
+#[protein]
+pub struct RangeRequest {
+    pub start: String,
+    /// Exclusive; `None` runs to the last key
+    pub end: Option<String>,
+    pub limit: u32,
+    pub cursor: Option<String>,
+    pub max_staleness_ms: Option<u64>,
+}
+
+/// Changes to watch for
+#[protein]
+pub struct WatchRequest {
+    /// The key, or with `prefix` every key starting with it
+    pub key: String,
+    pub prefix: bool,
+    /// `next` of the previous batch; `None` watches from now on
+    pub since: Option<u64>,
+    /// How long to wait for a change, capped at MAX_WATCH_MS
+    pub wait_ms: u64,
+}
+
+#[protein]
+pub struct WatchBatch {
+    /// Writes and expiries of watched keys, oldest first
+    pub changes: Vec<Change>,
+    /// `since` for the next call
+    pub next: u64,
+    /// The log was pruned past `since`: `changes` holds every watched row as
+    /// it is now, and anything missing from it was deleted
+    pub reset: bool,
+}
+
+#[protein]
+pub struct EntryPage {
+    pub entries: Vec<StateEntry>,
+    /// Cursor for the next page; `None` on the last one
+    pub next: Option<String>,
 }
 
 #[protein]
@@ -24,135 +148,387 @@ pub struct StateEntry {
     pub value: Vec<u8>,
     pub version: u64,
     pub timestamp: u64,
+    pub domain: String,
+}
+
+/// One write, as shipped to replicas
+#[protein]
+pub struct Change {
+    pub seq: u64,
+    pub key: String,
+    pub value: Vec<u8>,
+    pub version: u64,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub expires_at: Option<u64>,
+    /// The key is gone (its TTL ran out); `value` is empty
+    pub deleted: bool,
+    pub domain: Option<String>,
+}
+
+/// A version going live
+#[protein]
+pub struct Deployment {
+    pub cell: String,
+    /// Source hash, or the version from the applied manifest
+    pub version: String,
+    /// `apply`, `swap` or `rollback`
+    pub kind: String,
+    /// Blobstore hash of the binary
+    pub artifact: Option<String>,
+    /// Schema source the version serves
+    pub schema: Option<String>,
+    /// Set by the state-manager when recorded
+    pub deployed_at: u64,
+}
+
+#[protein]
+pub struct ChangeBatch {
+    pub changes: Vec<Change>,
+    /// Latest sequence number on the leader
+    pub head: u64,
+    /// The requested changes were pruned: `changes` is a full snapshot
+    pub reset: bool,
+}
+
+/// How much expiry work one background pass may do
+#[derive(Clone, Copy)]
+struct ExpiryConfig {
+    interval: Duration,
+    /// Rows deleted per transaction; the lock is released between batches
+    batch: u32,
+    /// Time after which a pass stops and leaves the rest for the next one
+    budget: Duration,
 }
 
-struct StateDb {
-    conn: Arc<Mutex<Connection>>,
-}
-
-impl StateDb {
-    fn new(path: &PathBuf) -> Result<Self> {
-        let conn = Connection::open(path)?;
-        
-        // Enable WAL mode for concurrent reads
-        conn.execute("PRAGMA journal_mode=WAL", [])?;
-        conn.execute("PRAGMA synchronous=NORMAL", [])?;
-        
-        // Create tables
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS state (
-                key TEXT PRIMARY KEY,
-                value BLOB NOT NULL,
-                version INTEGER NOT NULL DEFAULT 1,
-                created_at INTEGER NOT NULL,
-                updated_at INTEGER NOT NULL,
-                expires_at INTEGER
-            )",
-            [],
-        )?;
-
-        // Index for cleanup
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_expires 
-             ON state(expires_at) 
-             WHERE expires_at IS NOT NULL",
-            [],
-        )?;
-
-        Ok(Self {
-            conn: Arc::new(Mutex::new(conn)),
-        })
-    }
-
-    fn store(&self, key: &str, value: &[u8], ttl: Option<u64>) -> Result<u64> {
-        let conn = self.conn.lock().unwrap();
-        let now = Self::now();
-        let expires = ttl.map(|secs| now + secs);
-
-        conn.execute(
-            "INSERT INTO state (key, value, version, created_at, updated_at, expires_at)
-             VALUES (?1, ?2, 1, ?3, ?3, ?4)
-             ON CONFLICT(key) DO UPDATE SET
-                value = excluded.value,
-                version = version + 1,
-                updated_at = excluded.updated_at,
-                expires_at = excluded.expires_at",
-            params![key, value, now, expires],
-        )?;
-
-        let version: u64 = conn.query_row(
-            "SELECT version FROM state WHERE key = ?1",
-            params![key],
-            |row| row.get(0),
-        )?;
-
-        Ok(version)
-    }
-
-    fn fetch(&self, key: &str) -> Result<Option<StateEntry>> {
-        let conn = self.conn.lock().unwrap();
-        let now = Self::now();
-
-        let result = conn.query_row(
-            "SELECT key, value, version, created_at 
-             FROM state 
-             WHERE key = ?1 AND (expires_at IS NULL OR expires_at > ?2)",
-            params![key, now],
-            |row| {
-                Ok(StateEntry {
-                    key: row.get(0)?,
-                    value: row.get(1)?,
-                    version: row.get(2)?,
-                    timestamp: row.get(3)?,
-                })
-            },
-        );
-
-        match result {
-            Ok(entry) => Ok(Some(entry)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e.into()),
-        }
-    }
-
-    fn cleanup_expired(&self) -> Result<usize> {
-        let conn = self.conn.lock().unwrap();
-        let now = Self::now();
-        
-        let deleted = conn.execute(
-            "DELETE FROM state WHERE expires_at IS NOT NULL AND expires_at < ?1",
-            params![now],
-        )?;
-
-        Ok(deleted)
-    }
-
-    fn now() -> u64 {
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
+impl ExpiryConfig {
+    fn from_env() -> Self {
+        let var = |name: &str, default: u64| {
+            std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+        Self {
+            interval: Duration::from_millis(var("CELL_EXPIRY_INTERVAL_MS", 1000).max(1)),
+            batch: var("CELL_EXPIRY_BATCH", 500).max(1) as u32,
+            budget: Duration::from_millis(var("CELL_EXPIRY_BUDGET_MS", 50)),
+        }
     }
 }
 
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
 #[service]
 #[derive(Clone)]
 struct StateManager {
-    db: Arc<StateDb>,
+    db: Arc<dyn StorageBackend>,
+    /// Set on replicas
+    leader: Option<Leader::Client>,
+    /// Set on members of a Raft group, which write through it
+    replicated: Option<Arc<Replicated>>,
+    /// When this replica last caught up with the leader (Unix ms, 0 = never)
+    synced_at: Arc<AtomicU64>,
+    /// Bumped on every change logged, to wake waiting watches
+    logged: Arc<watch::Sender<u64>>,
 }
 
 #[handler]
 impl StateManager {
     async fn store(&self, req: StoreRequest) -> Result<u64> {
-        self.db.store(&req.key, &req.value, req.ttl_secs)
+        let target = domain::resolve(req.domain.as_deref());
+        domain::authorize_write(&target)?;
+        if let Some(leader) = &self.leader {
+            let req = Leader::StoreRequest {
+                key: req.key,
+                value: req.value,
+                ttl_secs: req.ttl_secs,
+                domain: Some(target.label()),
+            };
+            return leader.store(req).await;
+        }
+        let (size, owner) = self.db.existing(&req.key)?.unzip();
+        // Overwriting moves the key out of its current domain
+        if let Some(owner) = owner {
+            domain::authorize(&Domain::parse(&owner))?;
+        }
+        let delta = req.value.len() as i64 - size.unwrap_or(0) as i64;
+        self.charge_storage(delta).await?;
+        let stored = match &self.replicated {
+            Some(replicated) => replicated.store(req.key, req.value, req.ttl_secs, target.label()).await,
+            None => self.db
+                .store(&req.key, &req.value, req.ttl_secs, &target.label(), storage::now())
+                .inspect(|_| self.logged.send_modify(|n| *n += 1)),
+        };
+        if stored.is_err() {
+            // Nothing was stored: give back what the write was charged
+            if let Err(e) = self.charge_storage(-delta).await {
+                tracing::warn!("Storage charge of a failed write not refunded: {}", e);
+            }
+        }
+        stored
     }
 
+    #[handler(read)]
     async fn fetch(&self, req: FetchRequest) -> Result<Option<StateEntry>> {
-        self.db.fetch(&req.key)
+        if let Some(leader) = &self.leader {
+            if !self.fresh_enough(req.max_staleness_ms) {
+                let req = Leader::FetchRequest { key: req.key, max_staleness_ms: Some(0) };
+                return leader.fetch(req).await;
+            }
+        }
+        let entry = self.db.fetch(&req.key)?;
+        if let Some(entry) = &entry {
+            domain::authorize(&Domain::parse(&entry.domain))?;
+        }
+        Ok(entry)
+    }
+
+    /// A page of the rows under a key prefix, in key order
+    #[handler(read)]
+    async fn scan(&self, req: ScanRequest) -> Result<EntryPage> {
+        if let Some(leader) = &self.leader {
+            if !self.fresh_enough(req.max_staleness_ms) {
+                let req = Leader::ScanRequest {
+                    prefix: req.prefix,
+                    limit: req.limit,
+                    cursor: req.cursor,
+                    max_staleness_ms: Some(0),
+                };
+                return leader.scan(req).await;
+            }
+        }
+        let end = storage::prefix_end(&req.prefix);
+        self.page(&req.prefix, end.as_deref(), req.cursor.as_deref(), req.limit)
     }
 
+    /// A page of the rows with keys in `start..end`, in key order
+    #[handler(read)]
+    async fn range(&self, req: RangeRequest) -> Result<EntryPage> {
+        if let Some(leader) = &self.leader {
+            if !self.fresh_enough(req.max_staleness_ms) {
+                let req = Leader::RangeRequest {
+                    start: req.start,
+                    end: req.end,
+                    limit: req.limit,
+                    cursor: req.cursor,
+                    max_staleness_ms: Some(0),
+                };
+                return leader.range(req).await;
+            }
+        }
+        self.page(&req.start, req.end.as_deref(), req.cursor.as_deref(), req.limit)
+    }
+
+    /// Expire everything past its TTL now, without a time budget
     async fn vacuum(&self) -> Result<u64> {
-        Ok(self.db.cleanup_expired()? as u64)
+        if self.leader.is_some() {
+            return Err(ErrorContext::new(CellError::InvalidState)
+                .with_message("Replicas expire rows as the leader's change stream says")
+                .into());
+        }
+        let expiry = ExpiryConfig::from_env();
+        if let Some(replicated) = &self.replicated {
+            return Ok(replicated.expire(expiry.batch, Duration::MAX).await? as u64);
+        }
+        let expired = self.db.expire(expiry.batch, Duration::MAX)?;
+        if expired > 0 {
+            self.logged.send_modify(|n| *n += 1);
+        }
+        Ok(expired as u64)
+    }
+
+    /// Wait for changes to a key or key prefix. Returns as soon as there are
+    /// some, or empty once `wait_ms` passed; call again with `next`.
+    async fn watch(&self, req: WatchRequest) -> Result<WatchBatch> {
+        if let Some(leader) = &self.leader {
+            let req = Leader::WatchRequest {
+                key: req.key,
+                prefix: req.prefix,
+                since: req.since,
+                wait_ms: req.wait_ms,
+            };
+            return leader.watch(req).await;
+        }
+        let deadline = Instant::now() + Duration::from_millis(req.wait_ms.min(MAX_WATCH_MS));
+        let watched = |c: &Change| {
+            let key = if req.prefix { c.key.starts_with(&req.key) } else { c.key == req.key };
+            key && domain::allowed(&Domain::parse(&storage::label(c.domain.clone())))
+        };
+        let mut since = match req.since {
+            Some(since) => since,
+            None => self.db.head()?,
+        };
+        loop {
+            // Subscribed before reading, so a change right after still wakes us
+            let mut logged = self.logged.subscribe();
+            let batch = self.db.changes_since(since, WATCH_SCAN)?;
+            if batch.reset {
+                let changes = batch.changes.into_iter().filter(&watched).collect();
+                return Ok(WatchBatch { changes, next: batch.head, reset: true });
+            }
+            let scanned = batch.changes.len();
+            since = batch.changes.last().map_or(since, |c| c.seq);
+            let changes: Vec<Change> = batch.changes.into_iter().filter(&watched).collect();
+            if !changes.is_empty() || Instant::now() >= deadline {
+                return Ok(WatchBatch { changes, next: since, reset: false });
+            }
+            // More of the log to read before waiting
+            if scanned == WATCH_SCAN as usize {
+                continue;
+            }
+            if tokio::time::timeout_at(deadline, logged.changed()).await.is_err() {
+                return Ok(WatchBatch { changes: Vec::new(), next: since, reset: false });
+            }
+        }
+    }
+
+    /// Add to the deployment history; returns the record's id
+    async fn record_deployment(&self, deployment: Deployment) -> Result<u64> {
+        if let Some(leader) = &self.leader {
+            return leader.record_deployment(deployment).await;
+        }
+        if let Some(replicated) = &self.replicated {
+            return replicated.record_deployment(deployment).await;
+        }
+        self.db.record_deployment(&deployment)
+    }
+
+    /// The last `limit` deployments of a cell, newest first
+    async fn deployments(&self, cell_name: String, limit: u32) -> Result<Vec<Deployment>> {
+        if let Some(leader) = &self.leader {
+            return leader.deployments(cell_name, limit).await;
+        }
+        self.db.deployments(&cell_name, limit)
+    }
+
+    /// The change stream replicas follow
+    async fn changes(&self, since: u64, limit: u32) -> Result<ChangeBatch> {
+        if self.leader.is_some() {
+            return Err(ErrorContext::new(CellError::InvalidState)
+                .with_message("Only the leader serves the change stream")
+                .into());
+        }
+        self.db.changes_since(since, limit)
+    }
+}
+
+impl StateManager {
+    /// Rows from `start` (or after `cursor`) up to `end`, leaving out the
+    /// ones the caller may not read
+    fn page(
+        &self,
+        start: &str,
+        end: Option<&str>,
+        cursor: Option<&str>,
+        limit: u32,
+    ) -> Result<EntryPage> {
+        let from = match cursor {
+            Some(cursor) if cursor >= start => Bound::Excluded(cursor),
+            _ => Bound::Included(start),
+        };
+        let past_end = match (from, end) {
+            (Bound::Included(from) | Bound::Excluded(from), Some(end)) => from >= end,
+            _ => false,
+        };
+        if past_end {
+            return Ok(EntryPage { entries: Vec::new(), next: None });
+        }
+        let limit = limit.clamp(1, MAX_PAGE);
+        let rows = self.db.range(from, end, limit)?;
+        // A full page may have more after it, even if filtering emptied it
+        let next = (rows.len() == limit as usize).then(|| rows[rows.len() - 1].key.clone());
+        let entries = rows
+            .into_iter()
+            .filter(|e| domain::allowed(&Domain::parse(&e.domain)))
+            .collect();
+        Ok(EntryPage { entries, next })
+    }
+
+    fn fresh_enough(&self, max_staleness_ms: Option<u64>) -> bool {
+        let synced_at = self.synced_at.load(Ordering::Relaxed);
+        if synced_at == 0 {
+            return false;
+        }
+        match max_staleness_ms {
+            Some(bound) => now_ms().saturating_sub(synced_at) <= bound,
+            None => true,
+        }
+    }
+
+    /// Replica loop: pull the leader's changes and apply them.
+    async fn follow(self) {
+        let Some(leader) = self.leader.clone() else { return };
+        loop {
+            let since = self.db.applied_seq().unwrap_or(0);
+            let polled_at = now_ms();
+            match leader.changes(since, 1000).await {
+                Ok(batch) => {
+                    match self.db.apply(&batch) {
+                        Ok(position) if position >= batch.head => {
+                            self.synced_at.store(polled_at, Ordering::Relaxed);
+                        }
+                        // More to fetch: go again right away
+                        Ok(_) => continue,
+                        Err(e) => tracing::error!("Applying changes failed: {}", e),
+                    }
+                }
+                Err(e) => tracing::warn!("Following leader failed: {}", e),
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+        }
+    }
+
+    /// Keep the domain grant table current. Until IAM answers it stays
+    /// empty, and only this cell's own domain is reachable.
+    async fn refresh_grants() {
+        loop {
+            match Iam::Client::connect().await {
+                Ok(iam) => match iam.domain_grants().await {
+                    Ok(grants) => domain::set_grants(
+                        grants
+                            .into_iter()
+                            .map(|g| DomainGrant {
+                                principal: g.principal,
+                                organism: g.organism,
+                                residency: g.residency,
+                                expires_at: g.expires_at,
+                            })
+                            .collect(),
+                    ),
+                    Err(e) => tracing::warn!("Fetching domain grants failed: {}", e),
+                },
+                Err(e) => tracing::debug!("IAM unreachable: {}", e),
+            }
+            tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+        }
+    }
+
+    /// Charge a change in stored bytes to the caller's storage quota, under
+    /// the principal the Membrane charges its requests to. Fails open when
+    /// the quota cell is not running.
+    async fn charge_storage(&self, delta: i64) -> Result<()> {
+        if delta == 0 {
+            return Ok(());
+        }
+        let principal = cell_sdk::context::current().principal().to_string();
+
+        let Ok(quota) = Quota::Client::connect().await else {
+            return Ok(());
+        };
+        let organism = cell_sdk::identity::Identity::get().organism.clone();
+        match quota.charge_storage(organism, principal, delta).await {
+            Ok(admission) if !admission.allowed => {
+                anyhow::bail!(admission.reason.unwrap_or_else(|| "Storage quota exceeded".into()))
+            }
+            Ok(_) => Ok(()),
+            Err(e) => {
+                tracing::warn!("Quota check failed: {}", e);
+                Ok(())
+            }
+        }
     }
 }
 
@@ -160,26 +536,71 @@ impl StateManager {
 async fn main() -> Result<()> {
     tracing_subscriber::fmt().init();
 
-    let home = dirs::home_dir().unwrap();
-    let db_path = home.join(".cell/state.db");
-    
-    let db = StateDb::new(&db_path)?;
+    let dir = dirs::home_dir().unwrap().join(".cell");
+    let (name, replica_index, leader) = match replica::role() {
+        Some((leader, index)) => {
+            let conn = ResilientSynapse::grow(&leader).await?;
+            (replica::name(&leader, index), Some(index), Some(Leader::Client::new(conn)))
+        }
+        None => ("state-manager".to_string(), None, None),
+    };
 
-    // Background cleanup task
-    let db_clone = Arc::new(db);
-    let cleanup_db = db_clone.clone();
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
-        loop {
-            interval.tick().await;
-            if let Ok(count) = cleanup_db.cleanup_expired() {
-                if count > 0 {
-                    tracing::info!("Cleaned up {} expired entries", count);
+    let logged = Arc::new(watch::channel(0).0);
+
+    let consensus = match leader {
+        Some(_) => None,
+        None => replicated::config()?,
+    };
+    let (replicated, db) = match consensus {
+        Some(consensus) => {
+            let (replicated, db) = Replicated::start(&consensus, &dir, logged.clone()).await?;
+            (Some(replicated), db)
+        }
+        None => {
+            let config = storage::config()?;
+            let db_path = storage::path(&config, &dir, replica_index);
+            (None, storage::open(&config, db_path.as_deref())?)
+        }
+    };
+
+    // Background expiry, on the leader only: replicas follow its deletions,
+    // Raft members apply the passes their leader proposes
+    if leader.is_none() {
+        let expiry = ExpiryConfig::from_env();
+        let expiry_db = db.clone();
+        let replicated = replicated.clone();
+        let logged = logged.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(expiry.interval);
+            loop {
+                interval.tick().await;
+                let pass = match &replicated {
+                    Some(replicated) if !replicated.is_leader().await => continue,
+                    Some(replicated) => replicated.expire(expiry.batch, expiry.budget).await,
+                    None => expiry_db.expire(expiry.batch, expiry.budget),
+                };
+                match pass {
+                    Ok(count) if count > 0 => {
+                        logged.send_modify(|n| *n += 1);
+                        tracing::debug!("Expired {} entries", count);
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Expiry pass failed: {}", e),
                 }
             }
-        }
-    });
+        });
+    }
 
-    let service = StateManager { db: db_clone };
-    service.serve("state-manager").await
+    let service = StateManager {
+        db,
+        leader,
+        replicated,
+        synced_at: Arc::new(AtomicU64::new(0)),
+        logged,
+    };
+    if service.leader.is_some() {
+        tokio::spawn(service.clone().follow());
+    }
+    tokio::spawn(StateManager::refresh_grants());
+    service.serve(&name).await
 }
\ No newline at end of file
@@ -0,0 +1,267 @@
+// cells/state-manager/src/replicated.rs
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Replicated mode: with `[consensus]` in Cell.toml the state-manager is one
+//! member of a Raft group (cell-consensus), and three of them make a durable
+//! key-value store that survives losing any one:
+//!
+//! ```toml
+//! [consensus]
+//! id = 1
+//! peers = ["10.0.0.1:10001", "10.0.0.2:10002", "10.0.0.3:10003"]
+//! ```
+//!
+//! Writes (stores, deployments, expiry passes) are commands in the Raft log.
+//! Every member applies committed commands to its own store in log order, so
+//! all of them hold the same rows, versions and change log. A write returns
+//! once a majority has it on disk; any member takes writes, followers pass
+//! them on to the leader. Reads are answered from the local copy, which on a
+//! follower trails the leader by about a heartbeat.
+//!
+//! The Raft WAL is what persists: members keep rows in memory and rebuild
+//! them from the log on start, so `[storage]` does not apply. Commands carry
+//! the proposer's clock, so row timestamps and expiry come out the same on
+//! every member, and only the leader starts expiry passes.
+
+use crate::storage::{self, MemoryBackend, StorageBackend, CHANGE_LOG_LEN};
+use crate::Deployment;
+use anyhow::{anyhow, Context, Result};
+use cell_consensus::{RaftNode, StateMachine};
+use cell_model::manifest::ConsensusConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot, watch};
+
+/// Most commands proposed in one Raft round. Writes that arrive while a
+/// round is in flight are proposed together in the next.
+const MAX_BATCH: usize = 256;
+
+/// `[consensus]` from `./Cell.toml`
+pub fn config() -> Result<Option<ConsensusConfig>> {
+    Ok(storage::manifest()?.and_then(|m| m.consensus))
+}
+
+/// A write, as it goes through the Raft log
+#[derive(Serialize, Deserialize)]
+struct Command {
+    /// Member that proposed it, and its tag there to hand the result back
+    node: u64,
+    tag: u64,
+    op: Op,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Op {
+    Store {
+        key: String,
+        value: Vec<u8>,
+        ttl: Option<u64>,
+        domain: String,
+        now: u64,
+    },
+    RecordDeployment(Deployment),
+    Expire {
+        now: u64,
+        batch: u32,
+    },
+}
+
+/// Applies committed commands to the local store
+struct Machine {
+    id: u64,
+    db: Arc<dyn StorageBackend>,
+    logged: Arc<watch::Sender<u64>>,
+    /// This member's commands awaiting their result, by tag
+    results: Mutex<HashMap<u64, Option<Result<u64, String>>>>,
+}
+
+impl StateMachine for Machine {
+    fn apply(&self, command: &[u8]) {
+        let command: Command = match serde_json::from_slice(command) {
+            Ok(command) => command,
+            Err(e) => {
+                tracing::error!("Skipping undecodable Raft command: {}", e);
+                return;
+            }
+        };
+        let result = match command.op {
+            Op::Store {
+                key,
+                value,
+                ttl,
+                domain,
+                now,
+            } => self.db.store(&key, &value, ttl, &domain, now),
+            Op::RecordDeployment(deployment) => self.db.record_deployment(&deployment),
+            Op::Expire { now, batch } => self.db.expire_batch(now, batch).map(|n| n as u64),
+        };
+        match &result {
+            Ok(_) => self.logged.send_modify(|n| *n += 1),
+            Err(e) => tracing::error!("Applying Raft command failed: {}", e),
+        }
+        if command.node == self.id {
+            if let Some(slot) = self.results.lock().unwrap().get_mut(&command.tag) {
+                *slot = Some(result.map_err(|e| format!("{:#}", e)));
+            }
+        }
+    }
+}
+
+struct Proposal {
+    command: Vec<u8>,
+    done: oneshot::Sender<Result<(), String>>,
+}
+
+/// This member of the group
+pub struct Replicated {
+    machine: Arc<Machine>,
+    node: Arc<RaftNode>,
+    /// Random start, so commands proposed before a restart never match
+    next_tag: AtomicU64,
+    proposals: mpsc::Sender<Proposal>,
+}
+
+impl Replicated {
+    /// Join the group, replaying the WAL under `dir` into a fresh store.
+    /// Returns the store, which only the group writes to.
+    pub async fn start(
+        config: &ConsensusConfig,
+        dir: &Path,
+        logged: Arc<watch::Sender<u64>>,
+    ) -> Result<(Arc<Self>, Arc<dyn StorageBackend>)> {
+        let db: Arc<dyn StorageBackend> = Arc::new(MemoryBackend::default());
+        let machine = Arc::new(Machine {
+            id: config.id,
+            db: db.clone(),
+            logged,
+            results: Mutex::new(HashMap::new()),
+        });
+        let raft = cell_consensus::ConsensusConfig {
+            id: config.id,
+            peers: config.peers.clone(),
+            storage_path: dir.join(config.wal_file()),
+        };
+        let node = RaftNode::new(raft, machine.clone())
+            .await
+            .context("Failed to join the Raft group")?;
+        tracing::info!(
+            "Replicating state as node {} of {} ({} must have each write)",
+            config.id,
+            config.cluster_size(),
+            config.quorum()
+        );
+
+        let (proposals, queue) = mpsc::channel(MAX_BATCH * 4);
+        tokio::spawn(propose_batches(node.clone(), queue));
+        let replicated = Arc::new(Self {
+            machine,
+            node,
+            next_tag: AtomicU64::new(rand::random()),
+            proposals,
+        });
+        Ok((replicated, db))
+    }
+
+    /// Returns the new version
+    pub async fn store(
+        &self,
+        key: String,
+        value: Vec<u8>,
+        ttl: Option<u64>,
+        domain: String,
+    ) -> Result<u64> {
+        let now = storage::now();
+        self.propose(Op::Store {
+            key,
+            value,
+            ttl,
+            domain,
+            now,
+        })
+        .await
+    }
+
+    /// Returns the record's id
+    pub async fn record_deployment(&self, deployment: Deployment) -> Result<u64> {
+        self.propose(Op::RecordDeployment(deployment)).await
+    }
+
+    /// [`StorageBackend::expire`] through the log: propose `batch` rows at a
+    /// time while any are due and `budget` lasts, then prune the local
+    /// change log.
+    pub async fn expire(&self, batch: u32, budget: Duration) -> Result<usize> {
+        let started = Instant::now();
+        let mut expired = 0;
+        loop {
+            let now = storage::now();
+            // Checked first, so an idle pass adds nothing to the log
+            if !self.machine.db.next_expiry()?.is_some_and(|at| at <= now) {
+                break;
+            }
+            let deleted = self.propose(Op::Expire { now, batch }).await? as usize;
+            expired += deleted;
+            if deleted < batch as usize || started.elapsed() >= budget {
+                break;
+            }
+        }
+        self.machine.db.prune_changes(CHANGE_LOG_LEN)?;
+        Ok(expired)
+    }
+
+    pub async fn is_leader(&self) -> bool {
+        self.node.is_leader().await
+    }
+
+    /// Propose `op` and wait until it is applied here
+    async fn propose(&self, op: Op) -> Result<u64> {
+        let tag = self.next_tag.fetch_add(1, Ordering::Relaxed);
+        let command = serde_json::to_vec(&Command {
+            node: self.machine.id,
+            tag,
+            op,
+        })?;
+        self.machine.results.lock().unwrap().insert(tag, None);
+
+        let (done, answer) = oneshot::channel();
+        let proposed = match self.proposals.send(Proposal { command, done }).await {
+            Ok(()) => answer
+                .await
+                .unwrap_or_else(|_| Err("Proposer stopped".into())),
+            Err(_) => Err("Proposer stopped".into()),
+        };
+        let result = self.machine.results.lock().unwrap().remove(&tag).flatten();
+        proposed.map_err(|e| anyhow!(e))?;
+        match result {
+            Some(result) => result.map_err(|e| anyhow!(e)),
+            None => Err(anyhow!("Write {} committed but was not applied here", tag)),
+        }
+    }
+}
+
+/// Propose queued commands, as many at a time as are waiting
+async fn propose_batches(node: Arc<RaftNode>, mut queue: mpsc::Receiver<Proposal>) {
+    while let Some(first) = queue.recv().await {
+        let mut batch = vec![first];
+        while batch.len() < MAX_BATCH {
+            match queue.try_recv() {
+                Ok(proposal) => batch.push(proposal),
+                Err(_) => break,
+            }
+        }
+        let (commands, waiting): (Vec<_>, Vec<_>) =
+            batch.into_iter().map(|p| (p.command, p.done)).unzip();
+        let result = node
+            .propose_batch(commands)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("{:#}", e));
+        for done in waiting {
+            let _ = done.send(result.clone());
+        }
+    }
+}
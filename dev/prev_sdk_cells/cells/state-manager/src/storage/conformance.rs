@@ -0,0 +1,184 @@
+// cells/state-manager/src/storage/conformance.rs
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! One suite every engine has to pass, so switching `[storage]` never
+//! changes what the state-manager serves.
+
+use super::{now, prefix_end, MemoryBackend, StorageBackend};
+use anyhow::Result;
+use std::ops::Bound;
+use std::path::Path;
+
+type Open = fn(&Path) -> Result<Box<dyn StorageBackend>>;
+
+fn open_memory(_: &Path) -> Result<Box<dyn StorageBackend>> {
+    Ok(Box::new(MemoryBackend::default()))
+}
+
+#[cfg(feature = "sqlite")]
+fn open_sqlite(dir: &Path) -> Result<Box<dyn StorageBackend>> {
+    Ok(Box::new(super::SqliteBackend::open(&dir.join("state.db"))?))
+}
+
+#[cfg(feature = "redb")]
+fn open_redb(dir: &Path) -> Result<Box<dyn StorageBackend>> {
+    Ok(Box::new(super::RedbBackend::open(&dir.join("state.redb"))?))
+}
+
+fn keys(entries: &[crate::StateEntry]) -> Vec<&str> {
+    entries.iter().map(|e| e.key.as_str()).collect()
+}
+
+fn store_fetch_and_versions(open: Open) -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let db = open(dir.path())?;
+    let t = now();
+
+    assert!(db.fetch("k")?.is_none());
+    assert!(db.existing("k")?.is_none());
+
+    assert_eq!(db.store("k", b"one", None, "acme", t)?, 1);
+    assert_eq!(db.store("k", b"three", None, "acme", t + 5)?, 2);
+
+    let entry = db.fetch("k")?.expect("stored row");
+    assert_eq!(entry.value, b"three");
+    assert_eq!(entry.version, 2);
+    // Creation time survives overwrites
+    assert_eq!(entry.timestamp, t);
+    assert_eq!(entry.domain, "acme");
+    assert_eq!(db.existing("k")?, Some((5, "acme".to_string())));
+    Ok(())
+}
+
+fn ttl_expiry(open: Open) -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let db = open(dir.path())?;
+    let t = now();
+
+    db.store("stale", b"x", Some(10), "acme", t - 100)?;
+    db.store("fresh", b"y", Some(3600), "acme", t)?;
+    db.store("kept", b"z", None, "acme", t)?;
+
+    // Expired rows are hidden before they are deleted
+    assert!(db.fetch("stale")?.is_none());
+    assert_eq!(db.next_expiry()?, Some(t - 90));
+
+    assert_eq!(db.expire_batch(t, 10)?, 1);
+    assert!(db.existing("stale")?.is_none());
+    assert!(db.fetch("fresh")?.is_some());
+    assert!(db.fetch("kept")?.is_some());
+    assert_eq!(db.next_expiry()?, Some(t + 3600));
+
+    let last = db.changes_since(3, 10)?;
+    assert_eq!(last.changes.len(), 1);
+    assert_eq!(last.changes[0].key, "stale");
+    assert!(last.changes[0].deleted);
+    assert_eq!(db.expire_batch(t, 10)?, 0);
+    Ok(())
+}
+
+fn change_log(open: Open) -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let leader = open(dir.path())?;
+    let replica_dir = dir.path().join("replica");
+    std::fs::create_dir_all(&replica_dir)?;
+    let replica = open(&replica_dir)?;
+    let t = now();
+
+    assert_eq!(leader.head()?, 0);
+    leader.store("a", b"1", None, "acme", t)?;
+    leader.store("b", b"1", None, "acme", t)?;
+    leader.store("a", b"2", None, "acme", t)?;
+    assert_eq!(leader.head()?, 3);
+
+    let all = leader.changes_since(0, 10)?;
+    assert!(!all.reset);
+    assert_eq!(all.head, 3);
+    let log: Vec<(u64, &str, u64)> = all
+        .changes
+        .iter()
+        .map(|c| (c.seq, c.key.as_str(), c.version))
+        .collect();
+    assert_eq!(log, vec![(1, "a", 1), (2, "b", 1), (3, "a", 2)]);
+    assert_eq!(leader.changes_since(1, 1)?.changes[0].seq, 2);
+
+    assert_eq!(replica.apply(&all)?, 3);
+    assert_eq!(replica.applied_seq()?, 3);
+    let a = replica.fetch("a")?.expect("replicated row");
+    assert_eq!((a.value, a.version), (b"2".to_vec(), 2));
+
+    // A replica behind the retained log gets a snapshot instead
+    leader.prune_changes(1)?;
+    let snapshot = leader.changes_since(0, 10)?;
+    assert!(snapshot.reset);
+    assert_eq!(snapshot.head, 3);
+    let mut snapshot_keys: Vec<&str> = snapshot.changes.iter().map(|c| c.key.as_str()).collect();
+    snapshot_keys.sort();
+    assert_eq!(snapshot_keys, vec!["a", "b"]);
+    assert!(!leader.changes_since(2, 10)?.reset);
+    Ok(())
+}
+
+fn range_and_scan(open: Open) -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let db = open(dir.path())?;
+    let t = now();
+
+    for key in ["a/2", "b/1", "a/1"] {
+        db.store(key, key.as_bytes(), None, "acme", t)?;
+    }
+    db.store("a/3", b"gone", Some(1), "acme", t - 100)?;
+
+    let end = prefix_end("a/");
+    let scan = db.range(Bound::Included("a/"), end.as_deref(), 10)?;
+    assert_eq!(keys(&scan), vec!["a/1", "a/2"]);
+    assert_eq!(scan[0].value, b"a/1");
+
+    let first = db.range(Bound::Included("a/"), end.as_deref(), 1)?;
+    assert_eq!(keys(&first), vec!["a/1"]);
+    // Paging resumes after the last key seen
+    let rest = db.range(Bound::Excluded("a/1"), None, 10)?;
+    assert_eq!(keys(&rest), vec!["a/2", "b/1"]);
+    let all = db.range(Bound::Unbounded, None, 10)?;
+    assert_eq!(keys(&all), vec!["a/1", "a/2", "b/1"]);
+    assert!(db.range(Bound::Included("c"), None, 10)?.is_empty());
+    Ok(())
+}
+
+macro_rules! conformance {
+    ($($(#[$attr:meta])* $engine:ident => $open:expr;)*) => {$(
+        $(#[$attr])*
+        mod $engine {
+            use super::*;
+
+            #[test]
+            fn store_fetch_and_versions() -> Result<()> {
+                super::store_fetch_and_versions($open)
+            }
+
+            #[test]
+            fn ttl_expiry() -> Result<()> {
+                super::ttl_expiry($open)
+            }
+
+            #[test]
+            fn change_log() -> Result<()> {
+                super::change_log($open)
+            }
+
+            #[test]
+            fn range_and_scan() -> Result<()> {
+                super::range_and_scan($open)
+            }
+        }
+    )*};
+}
+
+conformance! {
+    memory => open_memory;
+    #[cfg(feature = "sqlite")]
+    sqlite => open_sqlite;
+    #[cfg(feature = "redb")]
+    redb => open_redb;
+}
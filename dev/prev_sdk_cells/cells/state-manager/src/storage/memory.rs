@@ -0,0 +1,213 @@
+// cells/state-manager/src/storage/memory.rs
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! State in process memory. Same semantics as the persistent engines, gone on
+//! restart: for tests and meshes that rebuild their state anyway.
+
+use super::{label, now, Row, StorageBackend};
+use crate::{Change, ChangeBatch, Deployment, StateEntry};
+use anyhow::Result;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+use std::sync::Mutex;
+
+#[derive(Default)]
+pub struct MemoryBackend {
+    tables: Mutex<Tables>,
+}
+
+#[derive(Default)]
+struct Tables {
+    state: BTreeMap<String, Row>,
+    /// (expires_at, key) of rows with a TTL, soonest first
+    expiry: BTreeSet<(u64, String)>,
+    changes: BTreeMap<u64, Change>,
+    last_seq: u64,
+    applied_seq: u64,
+    deployments: Vec<Deployment>,
+}
+
+impl Tables {
+    fn put(&mut self, key: &str, row: Row) {
+        self.remove(key);
+        if let Some(at) = row.expires_at {
+            self.expiry.insert((at, key.to_string()));
+        }
+        self.state.insert(key.to_string(), row);
+    }
+
+    fn remove(&mut self, key: &str) -> Option<Row> {
+        let row = self.state.remove(key)?;
+        if let Some(at) = row.expires_at {
+            self.expiry.remove(&(at, key.to_string()));
+        }
+        Some(row)
+    }
+
+    fn log(&mut self, change: impl FnOnce(u64) -> Change) {
+        self.last_seq += 1;
+        self.changes.insert(self.last_seq, change(self.last_seq));
+    }
+}
+
+impl StorageBackend for MemoryBackend {
+    fn store(
+        &self,
+        key: &str,
+        value: &[u8],
+        ttl: Option<u64>,
+        domain: &str,
+        now: u64,
+    ) -> Result<u64> {
+        let mut tables = self.tables.lock().unwrap();
+        let row = Row::written(tables.state.get(key), value, ttl, domain, now);
+        let version = row.version;
+        tables.log(|seq| row.change(key, seq));
+        tables.put(key, row);
+        Ok(version)
+    }
+
+    fn fetch(&self, key: &str) -> Result<Option<StateEntry>> {
+        let tables = self.tables.lock().unwrap();
+        let now = now();
+        Ok(tables
+            .state
+            .get(key)
+            .filter(|row| row.live(now))
+            .map(|row| row.entry(key)))
+    }
+
+    fn range(&self, from: Bound<&str>, to: Option<&str>, limit: u32) -> Result<Vec<StateEntry>> {
+        let tables = self.tables.lock().unwrap();
+        let now = now();
+        let to = to.map_or(Bound::Unbounded, Bound::Excluded);
+        Ok(tables
+            .state
+            .range::<str, _>((from, to))
+            .filter(|(_, row)| row.live(now))
+            .take(limit as usize)
+            .map(|(key, row)| row.entry(key))
+            .collect())
+    }
+
+    fn existing(&self, key: &str) -> Result<Option<(u64, String)>> {
+        let tables = self.tables.lock().unwrap();
+        Ok(tables
+            .state
+            .get(key)
+            .map(|row| (row.value.len() as u64, label(row.domain.clone()))))
+    }
+
+    fn head(&self) -> Result<u64> {
+        Ok(self.tables.lock().unwrap().last_seq)
+    }
+
+    fn changes_since(&self, since: u64, limit: u32) -> Result<ChangeBatch> {
+        let tables = self.tables.lock().unwrap();
+        let head = tables.last_seq;
+        let oldest = tables.changes.keys().next().copied();
+
+        // The replica is behind the retained log: send everything
+        if oldest.is_some_and(|oldest| since + 1 < oldest) {
+            let changes = tables
+                .state
+                .iter()
+                .map(|(key, row)| row.change(key, head))
+                .collect();
+            return Ok(ChangeBatch {
+                changes,
+                head,
+                reset: true,
+            });
+        }
+
+        let changes = tables
+            .changes
+            .range(since + 1..)
+            .take(limit as usize)
+            .map(|(_, c)| c.clone())
+            .collect();
+        Ok(ChangeBatch {
+            changes,
+            head,
+            reset: false,
+        })
+    }
+
+    fn apply(&self, batch: &ChangeBatch) -> Result<u64> {
+        let mut tables = self.tables.lock().unwrap();
+        if batch.reset {
+            tables.state.clear();
+            tables.expiry.clear();
+        }
+        for c in &batch.changes {
+            if c.deleted {
+                tables.remove(&c.key);
+            } else {
+                tables.put(&c.key, Row::from_change(c));
+            }
+        }
+        let position = match batch.changes.last() {
+            Some(c) => c.seq,
+            None if batch.reset => batch.head,
+            None => return Ok(tables.applied_seq),
+        };
+        tables.applied_seq = position;
+        Ok(position)
+    }
+
+    fn applied_seq(&self) -> Result<u64> {
+        Ok(self.tables.lock().unwrap().applied_seq)
+    }
+
+    fn record_deployment(&self, deployment: &Deployment) -> Result<u64> {
+        let mut tables = self.tables.lock().unwrap();
+        tables.deployments.push(Deployment {
+            deployed_at: now(),
+            ..deployment.clone()
+        });
+        Ok(tables.deployments.len() as u64)
+    }
+
+    fn deployments(&self, cell: &str, limit: u32) -> Result<Vec<Deployment>> {
+        let tables = self.tables.lock().unwrap();
+        Ok(tables
+            .deployments
+            .iter()
+            .rev()
+            .filter(|d| d.cell == cell)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    fn expire_batch(&self, now: u64, batch: u32) -> Result<usize> {
+        let mut tables = self.tables.lock().unwrap();
+        let due: Vec<String> = tables
+            .expiry
+            .iter()
+            .take_while(|(at, _)| *at <= now)
+            .take(batch as usize)
+            .map(|(_, key)| key.clone())
+            .collect();
+        for key in &due {
+            if let Some(row) = tables.remove(key) {
+                tables.log(|seq| row.expiry(key, seq, now));
+            }
+        }
+        Ok(due.len())
+    }
+
+    fn next_expiry(&self) -> Result<Option<u64>> {
+        let tables = self.tables.lock().unwrap();
+        Ok(tables.expiry.first().map(|(at, _)| *at))
+    }
+
+    fn prune_changes(&self, keep: u64) -> Result<()> {
+        let mut tables = self.tables.lock().unwrap();
+        let first_kept = tables.last_seq.saturating_sub(keep) + 1;
+        tables.changes = tables.changes.split_off(&first_kept);
+        Ok(())
+    }
+}
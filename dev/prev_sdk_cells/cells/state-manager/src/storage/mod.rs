@@ -0,0 +1,285 @@
+// cells/state-manager/src/storage/mod.rs
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Where the state-manager keeps its rows, change log and deployment history.
+//!
+//! The engine comes from `[storage]` in Cell.toml, overridden by
+//! `CELL_STORAGE`:
+//!
+//! ```toml
+//! [storage]
+//! engine = "redb"      # sqlite (default), redb or memory
+//! path = "state.redb"  # relative to ~/.cell unless absolute
+//! ```
+//!
+//! SQLite and redb are behind the `sqlite` (default) and `redb` features, so
+//! an embedded build can leave out the C library entirely. `memory` keeps
+//! nothing across restarts and is always available.
+
+#[cfg(test)]
+mod conformance;
+mod memory;
+#[cfg(feature = "redb")]
+mod redb;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+use crate::{ChangeBatch, Deployment, StateEntry};
+use anyhow::{Context, Result};
+use cell_model::manifest::{CellManifest, StorageConfig, StorageEngine};
+use cell_sdk::domain;
+use serde::{Deserialize, Serialize};
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "redb")]
+pub use self::redb::RedbBackend;
+pub use memory::MemoryBackend;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteBackend;
+
+#[cfg(any(feature = "sqlite", feature = "redb"))]
+const NO_PATH: &str = "No path for the state database";
+
+/// Changes kept for replicas to catch up from; older ones need a full resync
+pub const CHANGE_LOG_LEN: u64 = 100_000;
+
+/// Everything the state-manager asks of its store. Backends do their own
+/// locking; the service shares one between its handlers and background tasks.
+pub trait StorageBackend: Send + Sync {
+    /// Insert or overwrite `key` as of `now` (Unix seconds), logging the
+    /// write. Returns the new version.
+    fn store(
+        &self,
+        key: &str,
+        value: &[u8],
+        ttl: Option<u64>,
+        domain: &str,
+        now: u64,
+    ) -> Result<u64>;
+
+    /// The live (unexpired) row under `key`
+    fn fetch(&self, key: &str) -> Result<Option<StateEntry>>;
+
+    /// Live rows with keys from `from` up to `to` (exclusive, `None` for no
+    /// end), in key order, at most `limit`. `from` must not lie past `to`.
+    fn range(&self, from: Bound<&str>, to: Option<&str>, limit: u32) -> Result<Vec<StateEntry>>;
+
+    /// Bytes currently stored under `key` and their domain, if present
+    fn existing(&self, key: &str) -> Result<Option<(u64, String)>>;
+
+    /// Sequence number of the newest change, 0 before the first
+    fn head(&self) -> Result<u64>;
+
+    /// Changes after `since`, oldest first. A replica behind the retained
+    /// log gets a full snapshot with `reset` set.
+    fn changes_since(&self, since: u64, limit: u32) -> Result<ChangeBatch>;
+
+    /// Replica side: apply a batch from the leader. Returns the new position.
+    fn apply(&self, batch: &ChangeBatch) -> Result<u64>;
+
+    /// Last leader sequence number this replica applied
+    fn applied_seq(&self) -> Result<u64>;
+
+    /// Returns the record's id
+    fn record_deployment(&self, deployment: &Deployment) -> Result<u64>;
+
+    /// Deployments of `cell`, newest first
+    fn deployments(&self, cell: &str, limit: u32) -> Result<Vec<Deployment>>;
+
+    /// Delete up to `batch` rows expired at `now`, soonest expiry first,
+    /// logging each as a deletion. Returns how many went.
+    fn expire_batch(&self, now: u64, batch: u32) -> Result<usize>;
+
+    /// When the next row expires, if any has a TTL
+    fn next_expiry(&self) -> Result<Option<u64>>;
+
+    /// Drop change log entries more than `keep` behind the newest
+    fn prune_changes(&self, keep: u64) -> Result<()>;
+
+    /// Expire `batch` rows at a time until none are left or `budget` is
+    /// spent, then prune the change log.
+    fn expire(&self, batch: u32, budget: Duration) -> Result<usize> {
+        let started = Instant::now();
+        let mut expired = 0;
+        loop {
+            let deleted = self.expire_batch(now(), batch)?;
+            expired += deleted;
+            if deleted < batch as usize || started.elapsed() >= budget {
+                break;
+            }
+        }
+        self.prune_changes(CHANGE_LOG_LEN)?;
+        Ok(expired)
+    }
+}
+
+/// One row of state, as the key-value engines keep it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct Row {
+    pub value: Vec<u8>,
+    pub version: u64,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub expires_at: Option<u64>,
+    pub domain: Option<String>,
+}
+
+impl Row {
+    /// The row after writing `value` over `previous`
+    fn written(
+        previous: Option<&Row>,
+        value: &[u8],
+        ttl: Option<u64>,
+        domain: &str,
+        now: u64,
+    ) -> Self {
+        Self {
+            value: value.to_vec(),
+            version: previous.map_or(1, |p| p.version + 1),
+            created_at: previous.map_or(now, |p| p.created_at),
+            updated_at: now,
+            expires_at: ttl.map(|secs| now + secs),
+            domain: Some(domain.to_string()),
+        }
+    }
+
+    fn from_change(change: &crate::Change) -> Self {
+        Self {
+            value: change.value.clone(),
+            version: change.version,
+            created_at: change.created_at,
+            updated_at: change.updated_at,
+            expires_at: change.expires_at,
+            domain: change.domain.clone(),
+        }
+    }
+
+    fn live(&self, now: u64) -> bool {
+        self.expires_at.map_or(true, |at| at > now)
+    }
+
+    fn entry(&self, key: &str) -> StateEntry {
+        StateEntry {
+            key: key.to_string(),
+            value: self.value.clone(),
+            version: self.version,
+            timestamp: self.created_at,
+            domain: label(self.domain.clone()),
+        }
+    }
+
+    fn change(&self, key: &str, seq: u64) -> crate::Change {
+        crate::Change {
+            seq,
+            key: key.to_string(),
+            value: self.value.clone(),
+            version: self.version,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            expires_at: self.expires_at,
+            deleted: false,
+            domain: self.domain.clone(),
+        }
+    }
+
+    /// The deletion logged when the row expires at `now`
+    fn expiry(&self, key: &str, seq: u64, now: u64) -> crate::Change {
+        crate::Change {
+            value: Vec::new(),
+            updated_at: now,
+            deleted: true,
+            ..self.change(key, seq)
+        }
+    }
+}
+
+/// The first key after all keys starting with `prefix`, the end of a prefix
+/// scan. `None` when every key from `prefix` on starts with it.
+pub fn prefix_end(prefix: &str) -> Option<String> {
+    let mut chars: Vec<char> = prefix.chars().collect();
+    while let Some(last) = chars.pop() {
+        if let Some(next) = (last as u32 + 1..=char::MAX as u32).find_map(char::from_u32) {
+            chars.push(next);
+            return Some(chars.into_iter().collect());
+        }
+    }
+    None
+}
+
+/// Rows written before domains existed belong to the cell's own domain
+pub(crate) fn label(domain: Option<String>) -> String {
+    domain.unwrap_or_else(|| domain::local().label())
+}
+
+pub(crate) fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// `./Cell.toml`, if there is one
+pub fn manifest() -> Result<Option<CellManifest>> {
+    match std::fs::read_to_string("Cell.toml") {
+        Ok(content) => Ok(Some(
+            toml::from_str(&content).context("Failed to parse Cell.toml")?,
+        )),
+        Err(_) => Ok(None),
+    }
+}
+
+/// `[storage]` from `./Cell.toml`, with `CELL_STORAGE` overriding the engine
+pub fn config() -> Result<StorageConfig> {
+    let mut config = manifest()?.map(|m| m.storage).unwrap_or_default();
+    if let Ok(name) = std::env::var("CELL_STORAGE") {
+        config.engine = StorageEngine::parse(&name).with_context(|| {
+            format!(
+                "Unknown CELL_STORAGE '{}' (expected sqlite, redb or memory)",
+                name
+            )
+        })?;
+    }
+    Ok(config)
+}
+
+/// Database file for `config` under `dir`. Replica `n` gets its own file,
+/// `state.db` becoming `state-replica-<n>.db`.
+pub fn path(config: &StorageConfig, dir: &Path, replica: Option<usize>) -> Option<PathBuf> {
+    let file = config
+        .path
+        .as_deref()
+        .or_else(|| config.engine.default_file())?;
+    let path = dir.join(file);
+    let Some(n) = replica else {
+        return Some(path);
+    };
+    let stem = path.file_stem()?.to_string_lossy().into_owned();
+    let name = match path.extension() {
+        Some(ext) => format!("{}-replica-{}.{}", stem, n, ext.to_string_lossy()),
+        None => format!("{}-replica-{}", stem, n),
+    };
+    Some(path.with_file_name(name))
+}
+
+/// Open the engine `config` selects
+pub fn open(config: &StorageConfig, path: Option<&Path>) -> Result<Arc<dyn StorageBackend>> {
+    let backend: Arc<dyn StorageBackend> = match config.engine {
+        #[cfg(feature = "sqlite")]
+        StorageEngine::Sqlite => Arc::new(SqliteBackend::open(path.context(NO_PATH)?)?),
+        #[cfg(feature = "redb")]
+        StorageEngine::Redb => Arc::new(RedbBackend::open(path.context(NO_PATH)?)?),
+        StorageEngine::Memory => Arc::new(MemoryBackend::default()),
+        #[allow(unreachable_patterns)]
+        engine => anyhow::bail!(
+            "Storage engine '{}' is not compiled in (enable the state-manager's '{}' feature)",
+            engine.name(),
+            engine.name()
+        ),
+    };
+    tracing::info!("State stored in {}", config.engine.name());
+    Ok(backend)
+}
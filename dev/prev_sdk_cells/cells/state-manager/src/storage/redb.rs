@@ -0,0 +1,284 @@
+// cells/state-manager/src/storage/redb.rs
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! Pure Rust engine for builds without SQLite. Rows, changes and deployments
+//! are JSON values in redb tables, with an `(expires_at, key)` index for the
+//! expiry pass. Every call is one redb transaction.
+
+use super::{label, now, Row, StorageBackend};
+use crate::{Change, ChangeBatch, Deployment, StateEntry};
+use anyhow::Result;
+use redb::{Database, ReadableTable, TableDefinition, WriteTransaction};
+use std::ops::Bound;
+use std::path::Path;
+
+const STATE: TableDefinition<&str, &[u8]> = TableDefinition::new("state");
+const EXPIRY: TableDefinition<(u64, &str), ()> = TableDefinition::new("expiry");
+const CHANGES: TableDefinition<u64, &[u8]> = TableDefinition::new("changes");
+const DEPLOYMENTS: TableDefinition<u64, &[u8]> = TableDefinition::new("deployments");
+/// `last_seq`, `applied_seq`, `last_deployment`
+const COUNTERS: TableDefinition<&str, u64> = TableDefinition::new("counters");
+
+pub struct RedbBackend {
+    db: Database,
+}
+
+impl RedbBackend {
+    pub fn open(path: &Path) -> Result<Self> {
+        let db = Database::create(path)?;
+        // Read transactions fail on tables that were never created
+        let tx = db.begin_write()?;
+        tx.open_table(STATE)?;
+        tx.open_table(EXPIRY)?;
+        tx.open_table(CHANGES)?;
+        tx.open_table(DEPLOYMENTS)?;
+        tx.open_table(COUNTERS)?;
+        tx.commit()?;
+        Ok(Self { db })
+    }
+
+    fn counter(tx: &WriteTransaction, name: &str) -> Result<u64> {
+        let counters = tx.open_table(COUNTERS)?;
+        let value = counters.get(name)?.map(|v| v.value()).unwrap_or(0);
+        Ok(value)
+    }
+
+    fn set_counter(tx: &WriteTransaction, name: &str, value: u64) -> Result<()> {
+        tx.open_table(COUNTERS)?.insert(name, value)?;
+        Ok(())
+    }
+
+    fn put(tx: &WriteTransaction, key: &str, row: &Row) -> Result<()> {
+        Self::remove(tx, key)?;
+        if let Some(at) = row.expires_at {
+            tx.open_table(EXPIRY)?.insert((at, key), ())?;
+        }
+        tx.open_table(STATE)?
+            .insert(key, serde_json::to_vec(row)?.as_slice())?;
+        Ok(())
+    }
+
+    fn remove(tx: &WriteTransaction, key: &str) -> Result<Option<Row>> {
+        let Some(bytes) = tx
+            .open_table(STATE)?
+            .remove(key)?
+            .map(|v| v.value().to_vec())
+        else {
+            return Ok(None);
+        };
+        let row: Row = serde_json::from_slice(&bytes)?;
+        if let Some(at) = row.expires_at {
+            tx.open_table(EXPIRY)?.remove((at, key))?;
+        }
+        Ok(Some(row))
+    }
+
+    fn log(tx: &WriteTransaction, change: impl FnOnce(u64) -> Change) -> Result<()> {
+        let seq = Self::counter(tx, "last_seq")? + 1;
+        tx.open_table(CHANGES)?
+            .insert(seq, serde_json::to_vec(&change(seq))?.as_slice())?;
+        Self::set_counter(tx, "last_seq", seq)
+    }
+}
+
+impl StorageBackend for RedbBackend {
+    fn store(
+        &self,
+        key: &str,
+        value: &[u8],
+        ttl: Option<u64>,
+        domain: &str,
+        now: u64,
+    ) -> Result<u64> {
+        let tx = self.db.begin_write()?;
+        let previous = match tx.open_table(STATE)?.get(key)? {
+            Some(bytes) => Some(serde_json::from_slice::<Row>(bytes.value())?),
+            None => None,
+        };
+        let row = Row::written(previous.as_ref(), value, ttl, domain, now);
+        Self::log(&tx, |seq| row.change(key, seq))?;
+        Self::put(&tx, key, &row)?;
+        tx.commit()?;
+        Ok(row.version)
+    }
+
+    fn fetch(&self, key: &str) -> Result<Option<StateEntry>> {
+        let tx = self.db.begin_read()?;
+        let Some(bytes) = tx.open_table(STATE)?.get(key)? else {
+            return Ok(None);
+        };
+        let row: Row = serde_json::from_slice(bytes.value())?;
+        Ok(row.live(now()).then(|| row.entry(key)))
+    }
+
+    fn range(&self, from: Bound<&str>, to: Option<&str>, limit: u32) -> Result<Vec<StateEntry>> {
+        let tx = self.db.begin_read()?;
+        let table = tx.open_table(STATE)?;
+        let to = to.map_or(Bound::Unbounded, Bound::Excluded);
+        let now = now();
+        let mut entries = Vec::new();
+        for entry in table.range::<&str>((from, to))? {
+            if entries.len() >= limit as usize {
+                break;
+            }
+            let (key, bytes) = entry?;
+            let row: Row = serde_json::from_slice(bytes.value())?;
+            if row.live(now) {
+                entries.push(row.entry(key.value()));
+            }
+        }
+        Ok(entries)
+    }
+
+    fn existing(&self, key: &str) -> Result<Option<(u64, String)>> {
+        let tx = self.db.begin_read()?;
+        let Some(bytes) = tx.open_table(STATE)?.get(key)? else {
+            return Ok(None);
+        };
+        let row: Row = serde_json::from_slice(bytes.value())?;
+        Ok(Some((row.value.len() as u64, label(row.domain))))
+    }
+
+    fn head(&self) -> Result<u64> {
+        let tx = self.db.begin_read()?;
+        let seq = tx.open_table(COUNTERS)?.get("last_seq")?.map(|v| v.value());
+        Ok(seq.unwrap_or(0))
+    }
+
+    fn changes_since(&self, since: u64, limit: u32) -> Result<ChangeBatch> {
+        let tx = self.db.begin_read()?;
+        let changes = tx.open_table(CHANGES)?;
+        let head = tx
+            .open_table(COUNTERS)?
+            .get("last_seq")?
+            .map(|v| v.value())
+            .unwrap_or(0);
+        let oldest = changes.first()?.map(|(seq, _)| seq.value());
+
+        // The replica is behind the retained log: send everything
+        if oldest.is_some_and(|oldest| since + 1 < oldest) {
+            let mut snapshot = Vec::new();
+            for entry in tx.open_table(STATE)?.iter()? {
+                let (key, bytes) = entry?;
+                let row: Row = serde_json::from_slice(bytes.value())?;
+                snapshot.push(row.change(key.value(), head));
+            }
+            return Ok(ChangeBatch {
+                changes: snapshot,
+                head,
+                reset: true,
+            });
+        }
+
+        let mut batch = Vec::new();
+        for entry in changes.range(since + 1..)?.take(limit as usize) {
+            let (_, bytes) = entry?;
+            batch.push(serde_json::from_slice(bytes.value())?);
+        }
+        Ok(ChangeBatch {
+            changes: batch,
+            head,
+            reset: false,
+        })
+    }
+
+    fn apply(&self, batch: &ChangeBatch) -> Result<u64> {
+        let tx = self.db.begin_write()?;
+        if batch.reset {
+            tx.delete_table(STATE)?;
+            tx.delete_table(EXPIRY)?;
+        }
+        for c in &batch.changes {
+            if c.deleted {
+                Self::remove(&tx, &c.key)?;
+            } else {
+                Self::put(&tx, &c.key, &Row::from_change(c))?;
+            }
+        }
+        let position = match batch.changes.last() {
+            Some(c) => c.seq,
+            None if batch.reset => batch.head,
+            None => return Self::counter(&tx, "applied_seq"),
+        };
+        Self::set_counter(&tx, "applied_seq", position)?;
+        tx.commit()?;
+        Ok(position)
+    }
+
+    fn applied_seq(&self) -> Result<u64> {
+        let tx = self.db.begin_read()?;
+        let seq = tx
+            .open_table(COUNTERS)?
+            .get("applied_seq")?
+            .map(|v| v.value());
+        Ok(seq.unwrap_or(0))
+    }
+
+    fn record_deployment(&self, deployment: &Deployment) -> Result<u64> {
+        let tx = self.db.begin_write()?;
+        let id = Self::counter(&tx, "last_deployment")? + 1;
+        let deployment = Deployment {
+            deployed_at: now(),
+            ..deployment.clone()
+        };
+        tx.open_table(DEPLOYMENTS)?
+            .insert(id, serde_json::to_vec(&deployment)?.as_slice())?;
+        Self::set_counter(&tx, "last_deployment", id)?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    fn deployments(&self, cell: &str, limit: u32) -> Result<Vec<Deployment>> {
+        let tx = self.db.begin_read()?;
+        let mut history = Vec::new();
+        for entry in tx.open_table(DEPLOYMENTS)?.iter()?.rev() {
+            if history.len() >= limit as usize {
+                break;
+            }
+            let (_, bytes) = entry?;
+            let deployment: Deployment = serde_json::from_slice(bytes.value())?;
+            if deployment.cell == cell {
+                history.push(deployment);
+            }
+        }
+        Ok(history)
+    }
+
+    fn expire_batch(&self, now: u64, batch: u32) -> Result<usize> {
+        let tx = self.db.begin_write()?;
+        let mut due = Vec::new();
+        for entry in tx.open_table(EXPIRY)?.range(..(now + 1, ""))? {
+            if due.len() >= batch as usize {
+                break;
+            }
+            let (index, _) = entry?;
+            due.push(index.value().1.to_string());
+        }
+        for key in &due {
+            if let Some(row) = Self::remove(&tx, key)? {
+                Self::log(&tx, |seq| row.expiry(key, seq, now))?;
+            }
+        }
+        tx.commit()?;
+        Ok(due.len())
+    }
+
+    fn next_expiry(&self) -> Result<Option<u64>> {
+        let tx = self.db.begin_read()?;
+        let expiry = tx.open_table(EXPIRY)?;
+        let next = expiry.first()?.map(|(index, _)| index.value().0);
+        Ok(next)
+    }
+
+    fn prune_changes(&self, keep: u64) -> Result<()> {
+        let tx = self.db.begin_write()?;
+        let first_kept = Self::counter(&tx, "last_seq")?.saturating_sub(keep) + 1;
+        {
+            let mut changes = tx.open_table(CHANGES)?;
+            changes.retain_in(..first_kept, |_, _| false)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,488 @@
+// cells/state-manager/src/storage/sqlite.rs
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2025 Leif Rydenfalk – https://github.com/Leif-Rydenfalk/cell
+
+//! The default engine: one SQLite file in WAL mode behind a mutex. Its schema
+//! is versioned with `cell_sdk::migrations`, recorded in a `_migrations`
+//! table.
+
+use super::{label, now, StorageBackend};
+use crate::{Change, ChangeBatch, Deployment, StateEntry};
+use anyhow::Result;
+use cell_sdk::migrations::{self, Migratable, Migration};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::ops::{Bound, Deref};
+use std::path::Path;
+use std::sync::Mutex;
+
+const MIGRATIONS: &[Migration<Schema>] = &[
+    Migration {
+        version: 1,
+        name: "create-state",
+        up: create_state,
+    },
+    Migration {
+        version: 2,
+        name: "index-expiry",
+        up: index_expiry,
+    },
+    Migration {
+        version: 3,
+        name: "change-log",
+        up: change_log,
+    },
+    Migration {
+        version: 4,
+        name: "change-log-deletions",
+        up: change_log_deletions,
+    },
+    Migration {
+        version: 5,
+        name: "domains",
+        up: domains,
+    },
+    Migration {
+        version: 6,
+        name: "deployments",
+        up: deployments,
+    },
+];
+
+/// The connection while it is being migrated
+struct Schema(Connection);
+
+impl Deref for Schema {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        &self.0
+    }
+}
+
+impl Migratable for Schema {
+    fn applied(&mut self) -> Result<Vec<u32>> {
+        self.0.execute(
+            "CREATE TABLE IF NOT EXISTS _migrations (
+                version INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                applied_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+        let mut stmt = self.0.prepare("SELECT version FROM _migrations")?;
+        let versions = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(versions)
+    }
+
+    fn apply(&mut self, migration: &Migration<Self>) -> Result<()> {
+        self.0.execute_batch("BEGIN IMMEDIATE")?;
+        let applied = (migration.up)(self).and_then(|()| {
+            self.0.execute(
+                "INSERT INTO _migrations (version, name, applied_at) VALUES (?1, ?2, ?3)",
+                params![migration.version, migration.name, now()],
+            )?;
+            Ok(())
+        });
+        match applied {
+            Ok(()) => self.0.execute_batch("COMMIT")?,
+            Err(e) => {
+                let _ = self.0.execute_batch("ROLLBACK");
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn create_state(conn: &mut Schema) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS state (
+            key TEXT PRIMARY KEY,
+            value BLOB NOT NULL,
+            version INTEGER NOT NULL DEFAULT 1,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            expires_at INTEGER
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// Index for cleanup
+fn index_expiry(conn: &mut Schema) -> Result<()> {
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_expires 
+         ON state(expires_at) 
+         WHERE expires_at IS NOT NULL",
+        [],
+    )?;
+    Ok(())
+}
+
+fn change_log(conn: &mut Schema) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS changes (
+            seq INTEGER PRIMARY KEY AUTOINCREMENT,
+            key TEXT NOT NULL,
+            value BLOB NOT NULL,
+            version INTEGER NOT NULL,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            expires_at INTEGER
+        );
+        CREATE TABLE IF NOT EXISTS replication (
+            id INTEGER PRIMARY KEY CHECK (id = 0),
+            applied_seq INTEGER NOT NULL
+        );
+        INSERT OR IGNORE INTO replication (id, applied_seq) VALUES (0, 0);",
+    )?;
+    Ok(())
+}
+
+fn change_log_deletions(conn: &mut Schema) -> Result<()> {
+    conn.execute(
+        "ALTER TABLE changes ADD COLUMN deleted INTEGER NOT NULL DEFAULT 0",
+        [],
+    )?;
+    Ok(())
+}
+
+// Rows written before domains existed (NULL) belong to the cell's own domain
+fn domains(conn: &mut Schema) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE state ADD COLUMN domain TEXT;
+         ALTER TABLE changes ADD COLUMN domain TEXT;",
+    )?;
+    Ok(())
+}
+
+fn deployments(conn: &mut Schema) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS deployments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            cell TEXT NOT NULL,
+            version TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            artifact TEXT,
+            schema TEXT,
+            deployed_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_deployments_cell ON deployments(cell, id);",
+    )?;
+    Ok(())
+}
+
+pub struct SqliteBackend {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteBackend {
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)?;
+
+        // Enable WAL mode for concurrent reads
+        conn.execute("PRAGMA journal_mode=WAL", [])?;
+        conn.execute("PRAGMA synchronous=NORMAL", [])?;
+
+        let mut schema = Schema(conn);
+        migrations::run_with(&mut schema, MIGRATIONS)?;
+
+        Ok(Self {
+            conn: Mutex::new(schema.0),
+        })
+    }
+
+    fn applied_seq_in(conn: &Connection) -> Result<u64> {
+        let seq = conn
+            .query_row(
+                "SELECT applied_seq FROM replication WHERE id = 0",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(seq.unwrap_or(0))
+    }
+}
+
+impl StorageBackend for SqliteBackend {
+    fn store(
+        &self,
+        key: &str,
+        value: &[u8],
+        ttl: Option<u64>,
+        domain: &str,
+        now: u64,
+    ) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+        let expires = ttl.map(|secs| now + secs);
+
+        conn.execute(
+            "INSERT INTO state (key, value, version, created_at, updated_at, expires_at, domain)
+             VALUES (?1, ?2, 1, ?3, ?3, ?4, ?5)
+             ON CONFLICT(key) DO UPDATE SET
+                value = excluded.value,
+                version = version + 1,
+                updated_at = excluded.updated_at,
+                expires_at = excluded.expires_at,
+                domain = excluded.domain",
+            params![key, value, now, expires, domain],
+        )?;
+
+        let version: u64 = conn.query_row(
+            "SELECT version FROM state WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        )?;
+
+        // Feed the replicas
+        conn.execute(
+            "INSERT INTO changes (key, value, version, created_at, updated_at, expires_at, domain)
+             SELECT key, value, version, created_at, updated_at, expires_at, domain
+             FROM state WHERE key = ?1",
+            params![key],
+        )?;
+
+        Ok(version)
+    }
+
+    fn head(&self) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+        let head: Option<u64> =
+            conn.query_row("SELECT MAX(seq) FROM changes", [], |row| row.get(0))?;
+        Ok(head.unwrap_or(0))
+    }
+
+    fn changes_since(&self, since: u64, limit: u32) -> Result<ChangeBatch> {
+        let conn = self.conn.lock().unwrap();
+        let (oldest, head): (Option<u64>, Option<u64>) =
+            conn.query_row("SELECT MIN(seq), MAX(seq) FROM changes", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?;
+        let head = head.unwrap_or(0);
+
+        // The replica is behind the retained log: send everything
+        if oldest.is_some_and(|oldest| since + 1 < oldest) {
+            let mut stmt = conn.prepare(
+                "SELECT key, value, version, created_at, updated_at, expires_at, domain FROM state",
+            )?;
+            let changes = stmt
+                .query_map([], |row| {
+                    Ok(Change {
+                        seq: head,
+                        key: row.get(0)?,
+                        value: row.get(1)?,
+                        version: row.get(2)?,
+                        created_at: row.get(3)?,
+                        updated_at: row.get(4)?,
+                        expires_at: row.get(5)?,
+                        deleted: false,
+                        domain: row.get(6)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            return Ok(ChangeBatch {
+                changes,
+                head,
+                reset: true,
+            });
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT seq, key, value, version, created_at, updated_at, expires_at, deleted, domain
+             FROM changes WHERE seq > ?1 ORDER BY seq LIMIT ?2",
+        )?;
+        let changes = stmt
+            .query_map(params![since, limit], |row| {
+                Ok(Change {
+                    seq: row.get(0)?,
+                    key: row.get(1)?,
+                    value: row.get(2)?,
+                    version: row.get(3)?,
+                    created_at: row.get(4)?,
+                    updated_at: row.get(5)?,
+                    expires_at: row.get(6)?,
+                    deleted: row.get(7)?,
+                    domain: row.get(8)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(ChangeBatch {
+            changes,
+            head,
+            reset: false,
+        })
+    }
+
+    fn apply(&self, batch: &ChangeBatch) -> Result<u64> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        if batch.reset {
+            tx.execute("DELETE FROM state", [])?;
+        }
+        for c in &batch.changes {
+            if c.deleted {
+                tx.execute("DELETE FROM state WHERE key = ?1", params![c.key])?;
+                continue;
+            }
+            tx.execute(
+                "INSERT OR REPLACE INTO state (key, value, version, created_at, updated_at, expires_at, domain)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![c.key, c.value, c.version, c.created_at, c.updated_at, c.expires_at, c.domain],
+            )?;
+        }
+        let position = match batch.changes.last() {
+            Some(c) => c.seq,
+            None if batch.reset => batch.head,
+            None => return Self::applied_seq_in(&tx),
+        };
+        tx.execute(
+            "UPDATE replication SET applied_seq = ?1 WHERE id = 0",
+            params![position],
+        )?;
+        tx.commit()?;
+        Ok(position)
+    }
+
+    fn applied_seq(&self) -> Result<u64> {
+        Self::applied_seq_in(&self.conn.lock().unwrap())
+    }
+
+    fn fetch(&self, key: &str) -> Result<Option<StateEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let now = now();
+
+        let result = conn.query_row(
+            "SELECT key, value, version, created_at, domain
+             FROM state 
+             WHERE key = ?1 AND (expires_at IS NULL OR expires_at > ?2)",
+            params![key, now],
+            |row| {
+                Ok(StateEntry {
+                    key: row.get(0)?,
+                    value: row.get(1)?,
+                    version: row.get(2)?,
+                    timestamp: row.get(3)?,
+                    domain: label(row.get(4)?),
+                })
+            },
+        );
+
+        match result {
+            Ok(entry) => Ok(Some(entry)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn range(&self, from: Bound<&str>, to: Option<&str>, limit: u32) -> Result<Vec<StateEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let (op, from) = match from {
+            Bound::Included(key) => (">=", key),
+            Bound::Excluded(key) => (">", key),
+            Bound::Unbounded => (">=", ""),
+        };
+        // Keys compare as bytes, the same order as the other engines
+        let mut stmt = conn.prepare(&format!(
+            "SELECT key, value, version, created_at, domain
+             FROM state
+             WHERE key {} ?1 AND (?2 IS NULL OR key < ?2)
+               AND (expires_at IS NULL OR expires_at > ?3)
+             ORDER BY key LIMIT ?4",
+            op
+        ))?;
+        let entries = stmt
+            .query_map(params![from, to, now(), limit], |row| {
+                Ok(StateEntry {
+                    key: row.get(0)?,
+                    value: row.get(1)?,
+                    version: row.get(2)?,
+                    timestamp: row.get(3)?,
+                    domain: label(row.get(4)?),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(entries)
+    }
+
+    fn existing(&self, key: &str) -> Result<Option<(u64, String)>> {
+        let conn = self.conn.lock().unwrap();
+        let row = conn
+            .query_row(
+                "SELECT length(value), domain FROM state WHERE key = ?1",
+                params![key],
+                |row| Ok((row.get(0)?, label(row.get(1)?))),
+            )
+            .optional()?;
+        Ok(row)
+    }
+
+    fn record_deployment(&self, d: &Deployment) -> Result<u64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO deployments (cell, version, kind, artifact, schema, deployed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![d.cell, d.version, d.kind, d.artifact, d.schema, now()],
+        )?;
+        Ok(conn.last_insert_rowid() as u64)
+    }
+
+    fn deployments(&self, cell: &str, limit: u32) -> Result<Vec<Deployment>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT cell, version, kind, artifact, schema, deployed_at
+             FROM deployments WHERE cell = ?1 ORDER BY id DESC LIMIT ?2",
+        )?;
+        let history = stmt
+            .query_map(params![cell, limit], |row| {
+                Ok(Deployment {
+                    cell: row.get(0)?,
+                    version: row.get(1)?,
+                    kind: row.get(2)?,
+                    artifact: row.get(3)?,
+                    schema: row.get(4)?,
+                    deployed_at: row.get(5)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(history)
+    }
+
+    fn expire_batch(&self, now: u64, batch: u32) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO changes (key, value, version, created_at, updated_at, expires_at, deleted, domain)
+             SELECT key, x'', version, created_at, ?1, expires_at, 1, domain
+             FROM state WHERE expires_at IS NOT NULL AND expires_at <= ?1
+             ORDER BY expires_at, key LIMIT ?2",
+            params![now, batch],
+        )?;
+        let deleted = tx.execute(
+            "DELETE FROM state WHERE key IN (
+                SELECT key FROM state WHERE expires_at IS NOT NULL AND expires_at <= ?1
+                ORDER BY expires_at, key LIMIT ?2
+             )",
+            params![now, batch],
+        )?;
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    fn next_expiry(&self) -> Result<Option<u64>> {
+        let conn = self.conn.lock().unwrap();
+        let next = conn.query_row("SELECT MIN(expires_at) FROM state", [], |row| row.get(0))?;
+        Ok(next)
+    }
+
+    fn prune_changes(&self, keep: u64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM changes WHERE seq <= (SELECT MAX(seq) FROM changes) - ?1",
+            params![keep],
+        )?;
+        Ok(())
+    }
+}
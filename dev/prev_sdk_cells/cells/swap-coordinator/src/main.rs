@@ -1,7 +1,21 @@
 // cells/swap-coordinator/src/main.rs
 // Manages zero-downtime hot-swapping of cells
+//
+// Blue/green swaps move the old instance's listening socket to the new one
+// over its gap junction (cell_sdk::handover) instead of renaming sockets, so
+// live connections are not dropped.
+//
+// Canaries shift the share of new connections Axon tunnels to `<cell>-new`
+// in steps of 10% (Axon's `set_weights`), roll it back to the old instance
+// if the new one turns unhealthy, and finish with a blue/green swap.
+//
+// Every completed swap and rollback is recorded in the state-manager's
+// deployment history with the binary's blobstore hash, which is retained so
+// the version can be brought back. Rollbacks run that archived binary as
+// `<cell>-new` and hand over to it like a blue/green swap.
 
 use cell_sdk::*;
+use cell_sdk::system::System;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -23,6 +37,16 @@ pub enum SwapStrategy {
     Rolling,
 }
 
+#[protein]
+pub struct RollbackRequest {
+    pub cell_name: String,
+    /// Source hash to go back to
+    pub version: String,
+    /// Blobstore hash of that version's binary; without one the registry
+    /// source is rebuilt, and must be at `version`
+    pub artifact: Option<String>,
+}
+
 #[protein]
 pub struct SwapStatus {
     pub phase: SwapPhase,
@@ -43,6 +67,10 @@ pub enum SwapPhase {
 
 cell_remote!(Builder = "builder");
 cell_remote!(Hypervisor = "hypervisor");
+cell_remote!(Nucleus = "nucleus");
+cell_remote!(Axon = "axon", methods = [set_weights]);
+cell_remote!(StateManager = "state-manager", methods = [record_deployment]);
+cell_remote!(Blobstore = "blobstore", methods = [retain]);
 
 struct SwapState {
     active_swaps: HashMap<String, SwapStatus>,
@@ -80,6 +108,55 @@ impl SwapCoordinator {
         Ok(swap_id)
     }
 
+    /// Restart every instance of `cell_name` one at a time, never dropping below
+    /// `min_available` healthy instances (defaults to the manifest's value).
+    async fn rollout_restart(&self, cell_name: String, min_available: Option<u32>) -> Result<String> {
+        let rollout_id = format!("{}-restart-{}", cell_name, Self::now());
+
+        let mut state = self.state.write().await;
+        state.active_swaps.insert(rollout_id.clone(), SwapStatus {
+            phase: SwapPhase::Pending,
+            old_version: "running".to_string(),
+            new_version: "running".to_string(),
+            progress: 0,
+        });
+        drop(state);
+
+        let coordinator = self.clone();
+        let rollout_id_clone = rollout_id.clone();
+        tokio::spawn(async move {
+            if let Err(e) = coordinator.execute_rollout(&rollout_id_clone, &cell_name, min_available).await {
+                tracing::error!("Rollout failed: {}", e);
+            }
+        });
+
+        Ok(rollout_id)
+    }
+
+    /// Swap `cell_name` back to an earlier version (see `cell rollback`)
+    async fn rollback(&self, req: RollbackRequest) -> Result<String> {
+        let swap_id = format!("{}-rollback-{}", req.cell_name, Self::now());
+
+        let mut state = self.state.write().await;
+        state.active_swaps.insert(swap_id.clone(), SwapStatus {
+            phase: SwapPhase::Pending,
+            old_version: "running".to_string(),
+            new_version: req.version.clone(),
+            progress: 0,
+        });
+        drop(state);
+
+        let coordinator = self.clone();
+        let swap_id_clone = swap_id.clone();
+        tokio::spawn(async move {
+            if let Err(e) = coordinator.execute_rollback(&swap_id_clone, req).await {
+                tracing::error!("Rollback failed: {}", e);
+            }
+        });
+
+        Ok(swap_id)
+    }
+
     async fn get_status(&self, swap_id: String) -> Result<Option<SwapStatus>> {
         let state = self.state.read().await;
         Ok(state.active_swaps.get(&swap_id).cloned())
@@ -126,12 +203,18 @@ impl SwapCoordinator {
         // Wait for new instance to be healthy
         self.wait_for_health(&format!("{}-new", req.cell_name)).await?;
 
+        // Carry in-memory state over; cells without managed state start fresh
+        match cell_sdk::state::transfer(&req.cell_name, &format!("{}-new", req.cell_name)).await {
+            Ok(len) => tracing::info!("Transferred {} bytes of state to {}-new", len, req.cell_name),
+            Err(e) => tracing::warn!("No state transferred for {}: {}", req.cell_name, e),
+        }
+
         match req.strategy {
             SwapStrategy::BlueGreen => {
-                self.blue_green_swap(&swap_id, &req.cell_name, &new_socket).await?;
+                self.blue_green_swap(&swap_id, &req.cell_name).await?;
             }
             SwapStrategy::Canary { percentage } => {
-                self.canary_swap(&swap_id, &req.cell_name, &new_socket, percentage).await?;
+                self.canary_swap(&swap_id, &req.cell_name, percentage).await?;
             }
             SwapStrategy::Rolling => {
                 self.rolling_swap(&swap_id, &req.cell_name, &new_socket).await?;
@@ -140,36 +223,111 @@ impl SwapCoordinator {
 
         self.update_phase(&swap_id, SwapPhase::Completed, 100).await;
         tracing::info!("Swap {} completed", swap_id);
+        let version = build_result.source_hash;
+        self.record(&req.cell_name, &version, "swap", build_result.artifact_blob).await;
         Ok(())
     }
 
-    async fn blue_green_swap(
-        &self,
-        swap_id: &str,
-        cell_name: &str,
-        new_socket: &str,
-    ) -> Result<()> {
-        // PHASE 3: Drain old connections
-        self.update_phase(swap_id, SwapPhase::Draining, 60).await;
-        
-        // Signal old instance to stop accepting new connections
-        if let Ok(mut old_synapse) = Synapse::grow(cell_name).await {
-            let req = cell_model::ops::OpsRequest::Shutdown;
-            let req_bytes = rkyv::to_bytes::<_, 256>(&req)?.into_vec();
-            let _ = old_synapse.fire_on_channel(channel::OPS, &req_bytes).await;
+    async fn execute_rollback(&self, swap_id: &str, req: RollbackRequest) -> Result<()> {
+        tracing::info!("Rolling {} back to {}", req.cell_name, req.version);
+        let new_cell = format!("{}-new", req.cell_name);
+
+        // PHASE 1: Fetch the old binary, or rebuild it
+        self.update_phase(swap_id, SwapPhase::Building, 10).await;
+        let artifact = match req.artifact.clone() {
+            Some(artifact) => artifact,
+            None => {
+                let mut builder = Builder::Client::connect().await?;
+                let build_result = builder.build(
+                    req.cell_name.clone(),
+                    Builder::BuildMode::Standard
+                ).await?;
+                if build_result.source_hash != req.version {
+                    let reason = format!(
+                        "No archived binary of {}; the registry source is at {} \
+                         (check out {} to rebuild it)",
+                        req.version, build_result.source_hash, req.version
+                    );
+                    return self.fail_swap(swap_id, &reason).await;
+                }
+                match build_result.artifact_blob {
+                    Some(blob) => blob,
+                    None => {
+                        let reason = "Rebuilt, but the blobstore did not archive it";
+                        return self.fail_swap(swap_id, reason).await;
+                    }
+                }
+            }
+        };
+
+        // PHASE 2: Start the old version next to the live one
+        self.update_phase(swap_id, SwapPhase::Starting, 30).await;
+        let config = cell_model::config::CellInitConfig {
+            node_id: rand::random(),
+            cell_name: new_cell.clone(),
+            peers: vec![],
+            socket_path: format!("/tmp/cell/{}.sock", new_cell),
+            organism: "system".to_string(),
+        };
+        if let Err(e) = System::spawn_artifact(&new_cell, &artifact, Some(config)).await {
+            return self.fail_swap(swap_id, &e.to_string()).await;
         }
+        self.wait_for_health(&new_cell).await?;
 
-        // Wait for graceful drain (max 30 seconds)
-        tokio::time::sleep(tokio::time::Duration::from_secs(30)).await;
+        match cell_sdk::state::transfer(&req.cell_name, &new_cell).await {
+            Ok(len) => tracing::info!("Transferred {} bytes of state to {}", len, new_cell),
+            Err(e) => tracing::warn!("No state transferred for {}: {}", req.cell_name, e),
+        }
 
-        // PHASE 4: Atomic swap socket names
-        let old_socket = format!("/tmp/cell/{}.sock", cell_name);
-        let backup_socket = format!("/tmp/cell/{}-old.sock", cell_name);
+        // PHASE 3: Hand over, like a blue/green swap
+        self.blue_green_swap(swap_id, &req.cell_name).await?;
 
-        std::fs::rename(&old_socket, &backup_socket).ok();
-        std::fs::rename(new_socket, &old_socket)?;
+        self.update_phase(swap_id, SwapPhase::Completed, 100).await;
+        tracing::info!("Rollback {} completed", swap_id);
+        self.record(&req.cell_name, &req.version, "rollback", Some(artifact)).await;
+        Ok(())
+    }
+
+    /// Add the version now live to the deployment history and keep its
+    /// binary in the blobstore for later rollbacks. Best effort: the swap
+    /// already happened.
+    async fn record(&self, cell_name: &str, version: &str, kind: &str, artifact: Option<String>) {
+        if let Some(hash) = &artifact {
+            let retained = match Blobstore::Client::connect().await {
+                Ok(blobs) => blobs.retain(hash.clone()).await.map(drop),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = retained {
+                tracing::warn!("Artifact {} of {} not retained: {}", hash, cell_name, e);
+            }
+        }
+
+        let deployment = StateManager::Deployment {
+            cell: cell_name.to_string(),
+            version: version.to_string(),
+            kind: kind.to_string(),
+            artifact,
+            schema: cell_sdk::source::fetch(cell_name).await.ok(),
+            deployed_at: 0,
+        };
+        let recorded = match StateManager::Client::connect().await {
+            Ok(state) => state.record_deployment(deployment).await.map(drop),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = recorded {
+            tracing::warn!("Deployment of {} {} not recorded: {}", cell_name, version, e);
+        }
+    }
+
+    async fn blue_green_swap(&self, swap_id: &str, cell_name: &str) -> Result<()> {
+        // PHASE 3: Hand the listening socket to the new instance. It accepts
+        // on the same socket before the old one stops, so the socket path
+        // never changes and no queued connection is dropped. The old
+        // instance finishes its in-flight requests and exits on its own.
+        self.update_phase(swap_id, SwapPhase::Draining, 60).await;
+        cell_sdk::handover::hand_over(cell_name, &format!("{}-new", cell_name)).await?;
 
-        // PHASE 5: Update routing tables (notify Mesh, Axon, etc.)
+        // PHASE 4: Update routing tables (notify Mesh, Axon, etc.)
         // ... (implementation omitted for brevity)
 
         Ok(())
@@ -179,26 +337,45 @@ impl SwapCoordinator {
         &self,
         swap_id: &str,
         cell_name: &str,
-        new_socket: &str,
         target_percentage: u8,
     ) -> Result<()> {
+        let new_cell = format!("{}-new", cell_name);
+        let axon = Axon::Client::connect().await?;
+
         // Gradually increase traffic to new version
-        for step in (10..=target_percentage).step_by(10) {
+        for step in (10..=target_percentage.min(100)).step_by(10) {
             self.update_phase(swap_id, SwapPhase::Draining, 60 + step / 3).await;
-            
-            // Update load balancer weights
-            // ... (notify Axon or custom LB)
+            self.route(&axon, cell_name, cell_model::routing::canary(cell_name, &new_cell, step)).await?;
+            tracing::info!("Canary {}: {}% of new connections to {}", swap_id, step, new_cell);
             
             tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
             
             // Check error rates
-            if self.is_unhealthy(&format!("{}-new", cell_name)).await {
+            if self.is_unhealthy(&new_cell).await {
+                if let Err(e) = self.route(&axon, cell_name, Vec::new()).await {
+                    tracing::error!("Rolling {} back to {} failed: {}", swap_id, cell_name, e);
+                }
                 return self.fail_swap(swap_id, "New version unhealthy during canary").await;
             }
         }
 
-        // If canary successful, complete swap like blue-green
-        self.blue_green_swap(swap_id, cell_name, new_socket).await
+        // If canary successful, complete swap like blue-green. The new
+        // instance then serves the old socket, so Axon routes plainly again.
+        self.blue_green_swap(swap_id, cell_name).await?;
+        self.route(&axon, cell_name, Vec::new()).await
+    }
+
+    async fn route(
+        &self,
+        axon: &Axon::Client,
+        cell_name: &str,
+        backends: Vec<cell_model::routing::Backend>,
+    ) -> Result<()> {
+        let weights = backends
+            .into_iter()
+            .map(|b| Axon::RouteWeight { cell: b.cell, weight: b.weight })
+            .collect();
+        axon.set_weights(cell_name.to_string(), weights).await
     }
 
     async fn rolling_swap(
@@ -208,7 +385,8 @@ impl SwapCoordinator {
         new_socket: &str,
     ) -> Result<()> {
         // Immediate kill and replace
-        if let Ok(mut old_synapse) = Synapse::grow(cell_name).await {
+        // Shutdown is privileged: only the admin socket takes it
+        if let Ok(mut old_synapse) = Synapse::grow_admin(cell_name).await {
             let req = cell_model::ops::OpsRequest::Shutdown;
             let req_bytes = rkyv::to_bytes::<_, 256>(&req)?.into_vec();
             let _ = old_synapse.fire_on_channel(channel::OPS, &req_bytes).await;
@@ -222,6 +400,84 @@ impl SwapCoordinator {
         Ok(())
     }
 
+    async fn execute_rollout(
+        &self,
+        rollout_id: &str,
+        cell_name: &str,
+        min_available: Option<u32>,
+    ) -> Result<()> {
+        let instances = match System::list_instances(cell_name).await {
+            Ok(i) if !i.is_empty() => i,
+            Ok(_) => return self.fail_swap(rollout_id, "No running instances").await,
+            Err(e) => return self.fail_swap(rollout_id, &e.to_string()).await,
+        };
+
+        let min_available = match min_available {
+            Some(m) => m as usize,
+            None => match self.manifest_min_available(cell_name).await {
+                Some(m) => m as usize,
+                // Without a constraint, keep all but the instance being restarted up
+                None => instances.len().saturating_sub(1),
+            },
+        };
+        tracing::info!(
+            "Rolling restart {} of {} ({} instances, min available {})",
+            rollout_id, cell_name, instances.len(), min_available
+        );
+
+        for (i, instance) in instances.iter().enumerate() {
+            let mut healthy = 0;
+            for other in &instances {
+                if Synapse::grow(other).await.is_ok() {
+                    healthy += 1;
+                }
+            }
+            // Only count the restarting instance against the budget if it is up
+            let taken_down = usize::from(Synapse::grow(instance).await.is_ok());
+            // The probes race the instances: one may come up between them
+            let Some(left) = healthy.checked_sub(taken_down) else {
+                let reason = format!(
+                    "{} answered but was not counted healthy; instance health is changing",
+                    instance
+                );
+                return self.fail_swap(rollout_id, &reason).await;
+            };
+            if left < min_available {
+                let reason = format!(
+                    "Restarting {} would leave {} of {} required instances available",
+                    instance, left, min_available
+                );
+                return self.fail_swap(rollout_id, &reason).await;
+            }
+
+            let progress = (i * 100 / instances.len()) as u8;
+            self.update_phase(rollout_id, SwapPhase::Draining, progress).await;
+
+            let checkpoint = cell_sdk::state::fetch(instance).await.ok();
+            if let Err(e) = System::restart(instance).await {
+                return self.fail_swap(rollout_id, &format!("{}: {}", instance, e)).await;
+            }
+            self.update_phase(rollout_id, SwapPhase::Starting, progress).await;
+            if let Err(e) = self.wait_for_health(instance).await {
+                return self.fail_swap(rollout_id, &e.to_string()).await;
+            }
+            if let Some(bytes) = checkpoint {
+                if let Err(e) = cell_sdk::state::push(instance, bytes).await {
+                    tracing::warn!("Could not restore state of {}: {}", instance, e);
+                }
+            }
+        }
+
+        self.update_phase(rollout_id, SwapPhase::Completed, 100).await;
+        tracing::info!("Rollout {} completed", rollout_id);
+        Ok(())
+    }
+
+    async fn manifest_min_available(&self, cell_name: &str) -> Option<u32> {
+        let mut nucleus = Nucleus::Client::connect().await.ok()?;
+        nucleus.rollout_policy(cell_name.to_string()).await.ok()?.min_available
+    }
+
     async fn wait_for_health(&self, cell_name: &str) -> Result<()> {
         let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_secs(30);
         
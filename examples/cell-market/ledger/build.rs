@@ -3,5 +3,8 @@ fn main() {
     // Even without explicit #[cell_macro]s, it prepares the structure.
     cell_build::CellBuilder::configure()
         .extract_macros()
+        .unwrap()
+        // Lets clients be built without this source tree
+        .emit_schema()
         .unwrap();
 }
\ No newline at end of file